@@ -38,7 +38,7 @@ impl From<ConsensusConstants> for grpc::ConsensusConstants {
             difficulty_max_block_interval: cc.get_difficulty_max_block_interval(PowAlgorithm::Sha3),
             max_block_transaction_weight: cc.get_max_block_transaction_weight(),
             pow_algo_count: cc.get_pow_algo_count(),
-            median_timestamp_count: u64::try_from(cc.get_median_timestamp_count()).unwrap_or(0),
+            median_timestamp_count: u64::try_from(cc.median_timestamp_window()).unwrap_or(0),
             emission_initial: emission_initial.into(),
             emission_decay: emission_decay.to_vec(),
             emission_tail: emission_tail.into(),
@@ -37,7 +37,7 @@ pub const LOG_TARGET: &str = "tari_application";
 /// `identity_file` - Reference to file path
 /// `public_address` - Network address of the base node
 /// `create_id` - Whether an identity needs to be created or not
-/// `peer_features` - Enables features of the base node
+/// `peer_features` - Enables features of the base node. The features of a loaded identity are updated to match.
 ///
 /// # Return
 /// A NodeIdentity wrapped in an atomic reference counter on success, the exit code indicating the reason on failure
@@ -49,6 +49,20 @@ pub fn setup_node_identity<P: AsRef<Path>>(
 ) -> Result<Arc<NodeIdentity>, ExitCodes>
 {
     match load_identity(&identity_file) {
+        Ok(id) if id.features() != peer_features => {
+            info!(
+                target: LOG_TARGET,
+                "Updating node identity features from {} to {}.",
+                id.features(),
+                peer_features
+            );
+            recover_node_identity(
+                id.secret_key().clone(),
+                &identity_file,
+                &id.public_address(),
+                peer_features,
+            )
+        },
         Ok(id) => Ok(Arc::new(id)),
         Err(e) => {
             if !create_id {
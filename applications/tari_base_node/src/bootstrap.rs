@@ -46,6 +46,7 @@ use tari_core::{
         BaseNodeStateMachineConfig,
         BlockSyncConfig,
         StateMachineHandle,
+        ValidationLevel,
     },
     chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, BlockchainDatabase},
     consensus::ConsensusManager,
@@ -59,6 +60,7 @@ use tari_core::{
     },
     transactions::types::CryptoFactories,
 };
+use tari_crypto::tari_utilities::hex::{from_hex, Hex};
 use tari_p2p::{
     comms_connector::pubsub_connector,
     initialization,
@@ -99,7 +101,10 @@ where B: BlockchainBackend + 'static
         let peer_message_subscriptions = Arc::new(peer_message_subscriptions);
 
         let node_config = BaseNodeServiceConfig::default(); // TODO - make this configurable
-        let mempool_config = MempoolServiceConfig::default(); // TODO - make this configurable
+        let mempool_config = MempoolServiceConfig {
+            relay_transactions: config.mempool_relay_transactions,
+            ..Default::default()
+        };
 
         let comms_config = self.create_comms_config();
         let transport_type = comms_config.transport_type.clone();
@@ -113,6 +118,20 @@ where B: BlockchainBackend + 'static
 
         debug!(target: LOG_TARGET, "{} sync peer(s) configured", sync_peers.len());
 
+        let validation_level = match config.assume_valid_block_hash {
+            Some(ref hash) => {
+                let hash =
+                    from_hex(hash).map_err(|err| anyhow!("Invalid assume_valid_block_hash `{}`: {}", hash, err))?;
+                info!(
+                    target: LOG_TARGET,
+                    "Block bodies at or below block `{}` will not be fully validated during block sync",
+                    hash.to_hex()
+                );
+                ValidationLevel::AssumeValidBelow(hash)
+            },
+            None => ValidationLevel::Full,
+        };
+
         let rules = self.rules.clone();
 
         let mempool_sync = MempoolSyncInitializer::new(mempool_config, self.mempool.clone());
@@ -148,6 +167,7 @@ where B: BlockchainBackend + 'static
                 BaseNodeStateMachineConfig {
                     block_sync_config: BlockSyncConfig {
                         sync_peers,
+                        max_block_download_parallelism: config.max_block_download_parallelism,
                         ..Default::default()
                     },
                     horizon_sync_config: HorizonSyncConfig {
@@ -158,6 +178,7 @@ where B: BlockchainBackend + 'static
                     orphan_db_clean_out_threshold: config.orphan_db_clean_out_threshold,
                     max_randomx_vms: config.max_randomx_vms,
                     blocks_behind_before_considered_lagging: self.config.blocks_behind_before_considered_lagging,
+                    validation_level,
                     ..Default::default()
                 },
                 self.rules,
@@ -233,6 +254,7 @@ where B: BlockchainBackend + 'static
             peer_database_name: "peers".to_string(),
             max_concurrent_inbound_tasks: 100,
             outbound_buffer_size: 100,
+            max_queued_messages_per_peer: self.config.max_queued_messages_per_peer,
             dht: DhtConfig {
                 database_url: DbConnectionUrl::File(self.config.data_dir.join("dht.db")),
                 auto_join: true,
@@ -255,8 +277,9 @@ where B: BlockchainBackend + 'static
                 .chain(self.config.force_sync_peers.clone())
                 .collect(),
             dns_seeds: self.config.dns_seeds.clone(),
-            dns_seeds_name_server: self.config.dns_seeds_name_server,
+            dns_seeds_name_servers: self.config.dns_seeds_name_servers.clone(),
             dns_seeds_use_dnssec: self.config.dns_seeds_use_dnssec,
+            dns_seeds_doh_name: self.config.dns_seeds_doh_name.clone(),
         }
     }
 
@@ -28,7 +28,14 @@ use tari_comms::{peer_manager::NodeIdentity, protocol::rpc::RpcServerHandle, Com
 use tari_comms_dht::Dht;
 use tari_core::{
     base_node::{state_machine_service::states::StatusInfo, LocalNodeCommsInterface, StateMachineHandle},
-    chain_storage::{create_lmdb_database, BlockchainDatabase, BlockchainDatabaseConfig, LMDBDatabase, Validators},
+    chain_storage::{
+        create_lmdb_database,
+        run_compaction_scheduler,
+        BlockchainDatabase,
+        BlockchainDatabaseConfig,
+        LMDBDatabase,
+        Validators,
+    },
     consensus::ConsensusManagerBuilder,
     mempool::{service::LocalMempoolService, Mempool, MempoolConfig},
     proof_of_work::randomx_factory::{RandomXConfig, RandomXFactory},
@@ -47,7 +54,7 @@ use tari_core::{
 };
 use tari_service_framework::ServiceHandles;
 use tari_shutdown::ShutdownSignal;
-use tokio::sync::watch;
+use tokio::{sync::watch, task};
 
 const LOG_TARGET: &str = "c::bn::initialization";
 
@@ -216,6 +223,14 @@ async fn build_node_context(
         DifficultyCalculator::new(rules.clone(), randomx_factory),
         cleanup_orphans_at_startup,
     )?;
+    if let Some(interval) = config.db_config.compaction_interval() {
+        debug!(target: LOG_TARGET, "Database compaction enabled every {:.0?}", interval);
+        task::spawn(run_compaction_scheduler(
+            blockchain_db.clone(),
+            interval,
+            interrupt_signal.clone(),
+        ));
+    }
     let mempool_validator = MempoolValidator::new(vec![
         Box::new(TxInternalConsistencyValidator::new(factories.clone())),
         Box::new(TxInputAndMaturityValidator::new(blockchain_db.clone())),
@@ -42,7 +42,6 @@ use tari_comms::{
 use tari_comms_dht::{envelope::NodeDestination, DhtDiscoveryRequester, MetricsCollectorHandle};
 use tari_core::{
     base_node::{
-        comms_interface::BlockEvent,
         state_machine_service::states::{PeerMetadata, StatusInfo},
         LocalNodeCommsInterface,
     },
@@ -400,9 +399,7 @@ impl CommandHandler {
             if let Some(f) = filter {
                 let filter = f.to_lowercase();
                 query = query.select_where(move |p| match filter.as_str() {
-                    "basenode" | "basenodes" | "base_node" | "base-node" | "bn" => {
-                        p.features == PeerFeatures::COMMUNICATION_NODE
-                    },
+                    "basenode" | "basenodes" | "base_node" | "base-node" | "bn" => p.features.is_node(),
                     "wallet" | "wallets" | "w" => p.features == PeerFeatures::COMMUNICATION_CLIENT,
                     _ => false,
                 })
@@ -1012,7 +1009,7 @@ impl CommandHandler {
         let local_node_comms_interface = self.node_service.clone();
         self.executor.spawn(async move {
             let blocks = try_or_print!(db.rewind_to_height(new_height).await);
-            local_node_comms_interface.publish_block_event(BlockEvent::BlockSyncRewind(blocks));
+            local_node_comms_interface.publish_rewind(blocks);
         });
     }
 
@@ -530,6 +530,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                 }
             },
             TxStorageResponse::NotStored |
+            TxStorageResponse::NotStoredPolicyViolation |
             TxStorageResponse::NotStoredOrphan |
             TxStorageResponse::NotStoredTimeLocked => tari_rpc::SubmitTransactionResponse {
                 result: tari_rpc::SubmitTransactionResult::Rejected.into(),
@@ -597,6 +598,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                 }
             },
             TxStorageResponse::NotStored |
+            TxStorageResponse::NotStoredPolicyViolation |
             TxStorageResponse::NotStoredOrphan |
             TxStorageResponse::NotStoredTimeLocked => tari_rpc::TransactionStateResponse {
                 result: tari_rpc::TransactionLocation::NotStored.into(),
@@ -152,12 +152,18 @@ fn main_inner() -> Result<(), ExitCodes> {
 
 /// Sets up the base node and runs the cli_loop
 async fn run_node(node_config: Arc<GlobalConfig>, bootstrap: ConfigBootstrap) -> Result<(), ExitCodes> {
+    // Only archival nodes have the full history of the chain to offer
+    let mut peer_features = PeerFeatures::COMMUNICATION_NODE;
+    if node_config.advertise_archival && node_config.pruning_horizon == 0 {
+        peer_features |= PeerFeatures::ARCHIVAL;
+    }
+
     // Load or create the Node identity
     let node_identity = setup_node_identity(
         &node_config.base_node_identity_file,
         &node_config.public_address,
         bootstrap.create_id,
-        PeerFeatures::COMMUNICATION_NODE,
+        peer_features,
     )?;
 
     // Exit if create_id or init arguments were run
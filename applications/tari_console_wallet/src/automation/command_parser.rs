@@ -20,7 +20,10 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::automation::{commands::WalletCommand, error::ParseError};
+use crate::{
+    automation::{commands::WalletCommand, error::ParseError},
+    utils::destination::parse_and_validate_destination,
+};
 
 use chrono::{DateTime, Utc};
 use chrono_english::{parse_date_string, Dialect};
@@ -29,9 +32,8 @@ use std::{
     fmt::{Display, Formatter},
     str::FromStr,
 };
-use tari_app_utilities::utilities::parse_emoji_id_or_public_key;
-
 use tari_core::transactions::{tari_amount::MicroTari, types::PublicKey};
+use tari_wallet::output_manager_service::one_sided::OneSidedPaymentAddress;
 
 #[derive(Debug)]
 pub struct ParsedCommand {
@@ -43,13 +45,18 @@ impl Display for ParsedCommand {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let command = match self.command {
             WalletCommand::GetBalance => "get-balance",
+            WalletCommand::BalanceBreakdown => "balance-breakdown",
             WalletCommand::SendTari => "send-tari",
+            WalletCommand::SendOneSided => "send-one-sided",
+            WalletCommand::OneSidedAddress => "one-sided-address",
             WalletCommand::MakeItRain => "make-it-rain",
             WalletCommand::CoinSplit => "coin-split",
+            WalletCommand::Sweep => "sweep",
             WalletCommand::DiscoverPeer => "discover-peer",
             WalletCommand::Whois => "whois",
             WalletCommand::ExportUtxos => "export-utxos",
             WalletCommand::CountUtxos => "count-utxos",
+            WalletCommand::ExportTx => "export-tx",
         };
 
         let args = self
@@ -67,12 +74,16 @@ impl Display for ParsedCommand {
 pub enum ParsedArgument {
     Amount(MicroTari),
     PublicKey(PublicKey),
+    OneSidedPaymentAddress(OneSidedPaymentAddress),
     Text(String),
     Float(f64),
     Int(u64),
     Date(DateTime<Utc>),
     OutputToCSVFile(String),
     CSVFileName(String),
+    OutputAsJson,
+    FeePerGram(MicroTari),
+    AutoFeePerGram,
 }
 
 impl Display for ParsedArgument {
@@ -80,12 +91,16 @@ impl Display for ParsedArgument {
         match self {
             ParsedArgument::Amount(v) => write!(f, "{}", v.to_string()),
             ParsedArgument::PublicKey(v) => write!(f, "{}", v.to_string()),
+            ParsedArgument::OneSidedPaymentAddress(v) => write!(f, "{}", v.to_string()),
             ParsedArgument::Text(v) => write!(f, "{}", v.to_string()),
             ParsedArgument::Float(v) => write!(f, "{}", v.to_string()),
             ParsedArgument::Int(v) => write!(f, "{}", v.to_string()),
             ParsedArgument::Date(v) => write!(f, "{}", v.to_string()),
             ParsedArgument::OutputToCSVFile(v) => write!(f, "{}", v.to_string()),
             ParsedArgument::CSVFileName(v) => write!(f, "{}", v.to_string()),
+            ParsedArgument::OutputAsJson => write!(f, "--json"),
+            ParsedArgument::FeePerGram(v) => write!(f, "--fee {}", v),
+            ParsedArgument::AutoFeePerGram => write!(f, "--fee auto"),
         }
     }
 }
@@ -100,13 +115,18 @@ pub fn parse_command(command: &str) -> Result<ParsedCommand, ParseError> {
     use WalletCommand::*;
     let args = match command {
         GetBalance => Vec::new(),
+        BalanceBreakdown => parse_balance_breakdown(args)?,
         SendTari => parse_send_tari(args)?,
+        SendOneSided => parse_send_one_sided(args)?,
+        OneSidedAddress => Vec::new(),
         MakeItRain => parse_make_it_rain(args)?,
         CoinSplit => parse_coin_split(args)?,
+        Sweep => parse_sweep(args)?,
         DiscoverPeer => parse_discover_peer(args)?,
         Whois => parse_whois(args)?,
         ExportUtxos => parse_export_utxos(args)?, // todo: only show X number of utxos
         CountUtxos => Vec::new(),
+        ExportTx => parse_export_tx(args)?,
     };
 
     Ok(ParsedCommand { command, args })
@@ -119,7 +139,7 @@ fn parse_whois(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseEr
     let pubkey = args
         .next()
         .ok_or_else(|| ParseError::Empty("public key or emoji id".to_string()))?;
-    let pubkey = parse_emoji_id_or_public_key(pubkey).ok_or(ParseError::PublicKey)?;
+    let pubkey = parse_and_validate_destination(pubkey)?;
     parsed_args.push(ParsedArgument::PublicKey(pubkey));
 
     Ok(parsed_args)
@@ -132,7 +152,7 @@ fn parse_discover_peer(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>,
     let pubkey = args
         .next()
         .ok_or_else(|| ParseError::Empty("public key or emoji id".to_string()))?;
-    let pubkey = parse_emoji_id_or_public_key(pubkey).ok_or(ParseError::PublicKey)?;
+    let pubkey = parse_and_validate_destination(pubkey)?;
     parsed_args.push(ParsedArgument::PublicKey(pubkey));
 
     Ok(parsed_args)
@@ -188,7 +208,7 @@ fn parse_make_it_rain(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>,
     let pubkey = args
         .next()
         .ok_or_else(|| ParseError::Empty("public key or emoji id".to_string()))?;
-    let pubkey = parse_emoji_id_or_public_key(pubkey).ok_or(ParseError::PublicKey)?;
+    let pubkey = parse_and_validate_destination(pubkey)?;
     parsed_args.push(ParsedArgument::PublicKey(pubkey));
 
     // message
@@ -210,14 +230,55 @@ fn parse_send_tari(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, Par
     let pubkey = args
         .next()
         .ok_or_else(|| ParseError::Empty("public key or emoji id".to_string()))?;
-    let pubkey = parse_emoji_id_or_public_key(pubkey).ok_or(ParseError::PublicKey)?;
+    let pubkey = parse_and_validate_destination(pubkey)?;
     parsed_args.push(ParsedArgument::PublicKey(pubkey));
 
+    parse_fee_and_message(args, &mut parsed_args)?;
+
+    Ok(parsed_args)
+}
+
+fn parse_send_one_sided(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    // amount
+    let amount = args.next().ok_or_else(|| ParseError::Empty("amount".to_string()))?;
+    let amount = MicroTari::from_str(amount)?;
+    parsed_args.push(ParsedArgument::Amount(amount));
+
+    // one-sided payment address
+    let address = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("one-sided payment address".to_string()))?;
+    let address = OneSidedPaymentAddress::from_str(address)?;
+    parsed_args.push(ParsedArgument::OneSidedPaymentAddress(address));
+
+    parse_fee_and_message(args, &mut parsed_args)?;
+
+    Ok(parsed_args)
+}
+
+fn parse_fee_and_message(args: SplitWhitespace, parsed_args: &mut Vec<ParsedArgument>) -> Result<(), ParseError> {
+    // optional fee per gram
+    let mut args = args.peekable();
+    if args.peek() == Some(&"--fee") {
+        args.next();
+        let fee = args
+            .next()
+            .ok_or_else(|| ParseError::Empty("fee per gram or 'auto'".to_string()))?;
+        if fee == "auto" {
+            parsed_args.push(ParsedArgument::AutoFeePerGram);
+        } else {
+            let fee_per_gram = MicroTari::from_str(fee)?;
+            parsed_args.push(ParsedArgument::FeePerGram(fee_per_gram));
+        }
+    }
+
     // message
     let message = args.collect::<Vec<&str>>().join(" ");
     parsed_args.push(ParsedArgument::Text(message));
 
-    Ok(parsed_args)
+    Ok(())
 }
 
 fn parse_export_utxos(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
@@ -243,6 +304,32 @@ fn parse_export_utxos(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>,
     Ok(parsed_args)
 }
 
+fn parse_export_tx(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    let tx_id = args.next().ok_or_else(|| ParseError::Empty("tx_id".to_string()))?;
+    let tx_id = tx_id.parse::<u64>()?;
+    parsed_args.push(ParsedArgument::Int(tx_id));
+
+    Ok(parsed_args)
+}
+
+fn parse_balance_breakdown(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    if let Some(v) = args.next() {
+        if v == "--json" {
+            parsed_args.push(ParsedArgument::OutputAsJson);
+        } else {
+            return Err(ParseError::Empty(
+                "'--json' qualifier\n  Usage:\n    balance-breakdown\n    balance-breakdown --json".to_string(),
+            ));
+        }
+    };
+
+    Ok(parsed_args)
+}
+
 fn parse_coin_split(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
     let mut parsed_args = vec![];
 
@@ -260,17 +347,44 @@ fn parse_coin_split(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, Pa
     Ok(parsed_args)
 }
 
+fn parse_sweep(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = vec![];
+
+    let fee_per_gram = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("fee_per_gram".to_string()))?;
+    let fee_per_gram = MicroTari::from_str(fee_per_gram)?;
+    parsed_args.push(ParsedArgument::Amount(fee_per_gram));
+
+    let max_inputs = args.next().ok_or_else(|| ParseError::Empty("max_inputs".to_string()))?;
+    let max_inputs = max_inputs.parse::<u64>()?;
+    if max_inputs < 2 {
+        println!("At least two inputs are required to sweep");
+        return Err(ParseError::Invalid);
+    }
+    parsed_args.push(ParsedArgument::Int(max_inputs));
+
+    Ok(parsed_args)
+}
+
 #[cfg(test)]
 mod test {
-    use crate::automation::command_parser::{parse_command, ParsedArgument};
+    use crate::automation::{
+        command_parser::{parse_command, ParsedArgument},
+        commands::WalletCommand,
+    };
     use rand::rngs::OsRng;
     use std::str::FromStr;
-    use tari_core::transactions::{tari_amount::MicroTari, types::PublicKey};
+    use tari_core::transactions::{
+        tari_amount::MicroTari,
+        types::{CommitmentFactory, PublicKey},
+    };
     use tari_crypto::keys::PublicKey as PublicKeyTrait;
+    use tari_wallet::output_manager_service::one_sided::OneSidedPaymentAddress;
 
     #[test]
     fn test_parse_command() {
-        let (_secret_key, public_key) = PublicKey::random_keypair(&mut OsRng);
+        let (secret_key, public_key) = PublicKey::random_keypair(&mut OsRng);
 
         let command_str = "";
         let parsed = parse_command(command_str);
@@ -307,6 +421,44 @@ mod test {
             panic!("Parsed message is not the same as provided.");
         }
 
+        let command_str = format!("send-tari 999T {} --fee auto msg text", public_key);
+        let parsed = parse_command(&command_str).unwrap();
+
+        assert!(matches!(parsed.args[2], ParsedArgument::AutoFeePerGram));
+        if let ParsedArgument::Text(msg) = parsed.args[3].clone() {
+            assert_eq!(msg, "msg text");
+        } else {
+            panic!("Parsed message is not the same as provided.");
+        }
+
+        let command_str = format!("send-tari 999T {} --fee 40 msg text", public_key);
+        let parsed = parse_command(&command_str).unwrap();
+
+        if let ParsedArgument::FeePerGram(fee_per_gram) = parsed.args[2].clone() {
+            assert_eq!(fee_per_gram, MicroTari::from(40));
+        } else {
+            panic!("Parsed fee per gram is not the same as provided.");
+        }
+
+        let address = OneSidedPaymentAddress::from_secret_key(&secret_key, &CommitmentFactory::default());
+        let command_str = format!("send-one-sided 999T {} msg text", address);
+        let parsed = parse_command(&command_str).unwrap();
+
+        assert_eq!(parsed.command, WalletCommand::SendOneSided);
+        if let ParsedArgument::OneSidedPaymentAddress(parsed_address) = parsed.args[1].clone() {
+            assert_eq!(parsed_address, address);
+        } else {
+            panic!("Parsed one-sided payment address is not the same as provided.");
+        }
+
+        let command_str = format!("send-one-sided 999T {} msg text", public_key);
+        let parsed = parse_command(&command_str);
+        assert!(parsed.is_err());
+
+        let command_str = format!("send-tari 999T {} --fee", public_key);
+        let parsed = parse_command(&command_str);
+        assert!(parsed.is_err());
+
         let command_str = format!("send-tari 999ut {}", public_key);
         let parsed = parse_command(&command_str).unwrap();
 
@@ -342,5 +494,36 @@ mod test {
         } else {
             panic!("Parsed csv file name is not the same as provided.");
         }
+
+        let parsed = parse_command("balance-breakdown").unwrap();
+        assert!(parsed.args.is_empty());
+
+        let parsed = parse_command("balance-breakdown --json").unwrap();
+        assert!(matches!(parsed.args[0], ParsedArgument::OutputAsJson));
+
+        let parsed = parse_command("balance-breakdown --csv");
+        assert!(parsed.is_err());
+
+        let parsed = parse_command("sweep 5 100").unwrap();
+        if let ParsedArgument::Amount(fee_per_gram) = parsed.args[0].clone() {
+            assert_eq!(fee_per_gram, MicroTari::from(5));
+        } else {
+            panic!("Parsed fee per gram is not the same as provided.");
+        }
+        if let ParsedArgument::Int(max_inputs) = parsed.args[1].clone() {
+            assert_eq!(max_inputs, 100);
+        } else {
+            panic!("Parsed max inputs is not the same as provided.");
+        }
+
+        assert!(parse_command("sweep 5").is_err());
+        assert!(parse_command("sweep 5 1").is_err());
+
+        let parsed = parse_command("export-tx 12345").unwrap();
+        assert_eq!(parsed.command, WalletCommand::ExportTx);
+        assert!(matches!(parsed.args[0], ParsedArgument::Int(12345)));
+
+        assert!(parse_command("export-tx").is_err());
+        assert!(parse_command("export-tx abc").is_err());
     }
 }
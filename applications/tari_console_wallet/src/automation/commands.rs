@@ -25,6 +25,7 @@ use crate::automation::command_parser::{ParsedArgument, ParsedCommand};
 use chrono::{DateTime, Utc};
 use futures::{FutureExt, StreamExt};
 use log::*;
+use serde_json::json;
 use std::{
     fs::File,
     io::{LineWriter, Write},
@@ -40,12 +41,16 @@ use tari_core::{
     transactions::{
         tari_amount::{uT, MicroTari, Tari},
         transaction::OutputFeatures,
+        types::PublicKey,
     },
 };
 use tari_crypto::ristretto::pedersen::PedersenCommitmentFactory;
 use tari_wallet::{
-    output_manager_service::{handle::OutputManagerHandle, TxId},
-    transaction_service::handle::{TransactionEvent, TransactionServiceHandle},
+    output_manager_service::{handle::OutputManagerHandle, service::Balance, TxId},
+    transaction_service::{
+        handle::{TransactionEvent, TransactionServiceHandle},
+        transaction_detail::TransactionDetail,
+    },
     util::emoji::EmojiId,
     WalletSqlite,
 };
@@ -61,13 +66,18 @@ pub const LOG_TARGET: &str = "wallet::automation::commands";
 #[strum(serialize_all = "kebab_case")]
 pub enum WalletCommand {
     GetBalance,
+    BalanceBreakdown,
     SendTari,
+    SendOneSided,
+    OneSidedAddress,
     MakeItRain,
     CoinSplit,
+    Sweep,
     DiscoverPeer,
     Whois,
     ExportUtxos,
     CountUtxos,
+    ExportTx,
 }
 
 #[derive(Debug, EnumString, PartialEq, Clone)]
@@ -91,10 +101,47 @@ pub async fn send_tari(
     mut wallet_transaction_service: TransactionServiceHandle,
     args: Vec<ParsedArgument>,
 ) -> Result<TxId, CommandError>
+{
+    let (amount, dest_pubkey, fee_per_gram, message) = get_send_args(args)?;
+    wallet_transaction_service
+        .send_transaction(dest_pubkey, amount, fee_per_gram, message)
+        .await
+        .map_err(CommandError::Transaction)
+}
+
+/// Send a one-sided payment, which the recipient discovers by scanning the blockchain instead of negotiating it. The
+/// sender chooses the output's spending key, so the output is not exclusively the recipient's until the recipient
+/// spends it.
+pub async fn send_one_sided(
+    mut wallet_transaction_service: TransactionServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<TxId, CommandError>
 {
     // todo: consolidate "fee per gram" in codebase
     let fee_per_gram = 25 * uT;
 
+    use ParsedArgument::*;
+    let (amount, dest_address) = match &args[..2] {
+        [Amount(amount), OneSidedPaymentAddress(address)] => Ok((*amount, address.clone())),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let (fee_per_gram, message) = match &args[2..] {
+        [FeePerGram(fee), Text(msg)] => Ok((*fee, msg.clone())),
+        [Text(msg)] => Ok((fee_per_gram, msg.clone())),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    wallet_transaction_service
+        .send_one_sided_transaction(dest_address, amount, fee_per_gram, message)
+        .await
+        .map_err(CommandError::Transaction)
+}
+
+fn get_send_args(args: Vec<ParsedArgument>) -> Result<(MicroTari, PublicKey, MicroTari, String), CommandError> {
+    // todo: consolidate "fee per gram" in codebase
+    let fee_per_gram = 25 * uT;
+
     use ParsedArgument::*;
     let amount = match args[0].clone() {
         Amount(mtari) => Ok(mtari),
@@ -106,15 +153,33 @@ pub async fn send_tari(
         _ => Err(CommandError::Argument),
     }?;
 
-    let message = match args[2].clone() {
-        Text(msg) => Ok(msg),
+    let (fee_per_gram, message) = match &args[2..] {
+        [FeePerGram(fee), Text(msg)] => Ok((*fee, msg.clone())),
+        [Text(msg)] => Ok((fee_per_gram, msg.clone())),
         _ => Err(CommandError::Argument),
     }?;
 
-    wallet_transaction_service
-        .send_transaction(dest_pubkey, amount, fee_per_gram, message)
-        .await
-        .map_err(CommandError::Transaction)
+    Ok((amount, dest_pubkey, fee_per_gram, message))
+}
+
+/// Replace an automatic fee per gram argument with the fee per gram recommended by the wallet's fee oracle
+async fn resolve_fee_per_gram(
+    args: Vec<ParsedArgument>,
+    output_service: &mut OutputManagerHandle,
+) -> Result<Vec<ParsedArgument>, CommandError>
+{
+    let mut resolved = Vec::with_capacity(args.len());
+    for arg in args {
+        match arg {
+            ParsedArgument::AutoFeePerGram => {
+                let fee_per_gram = output_service.get_recommended_fee_per_gram().await?;
+                println!("Using recommended fee per gram of {}", fee_per_gram);
+                resolved.push(ParsedArgument::FeePerGram(fee_per_gram));
+            },
+            arg => resolved.push(arg),
+        }
+    }
+    Ok(resolved)
 }
 
 pub async fn coin_split(
@@ -144,6 +209,61 @@ pub async fn coin_split(
     Ok(tx_id)
 }
 
+pub async fn sweep(
+    args: &[ParsedArgument],
+    output_service: &mut OutputManagerHandle,
+    transaction_service: &mut TransactionServiceHandle,
+) -> Result<TxId, CommandError>
+{
+    use ParsedArgument::*;
+    let fee_per_gram = match args[0] {
+        Amount(s) => Ok(s),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let max_inputs = match args[1] {
+        Int(s) => Ok(s),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let (tx_id, tx, fee, amount) = output_service
+        .create_sweep_transaction(fee_per_gram, max_inputs as usize, None)
+        .await?;
+    println!(
+        "Sweeping {} UTXOs into a single output of {} (fee {})",
+        tx.body.inputs().len(),
+        amount - fee,
+        fee
+    );
+    transaction_service
+        .submit_transaction(tx_id, tx, fee, amount, "Sweep".into())
+        .await?;
+
+    Ok(tx_id)
+}
+
+fn print_balance_breakdown(balance: &Balance, as_json: bool) {
+    let spendable = balance.spendable_balance();
+    let time_locked = balance.time_locked_balance.unwrap_or_default();
+    let total = balance.available_balance + balance.pending_incoming_balance;
+    if as_json {
+        let breakdown = json!({
+            "available": spendable.as_u64(),
+            "pending_incoming": balance.pending_incoming_balance.as_u64(),
+            "pending_outgoing": balance.pending_outgoing_balance.as_u64(),
+            "time_locked": time_locked.as_u64(),
+            "total": total.as_u64(),
+        });
+        println!("{}", breakdown);
+    } else {
+        println!("Available (spendable now): {}", spendable);
+        println!("Pending incoming         : {}", balance.pending_incoming_balance);
+        println!("Pending outgoing         : {}", balance.pending_outgoing_balance);
+        println!("Time-locked (immature)   : {}", time_locked);
+        println!("Total                    : {}", total);
+    }
+}
+
 async fn wait_for_comms(connectivity_requester: &ConnectivityRequester) -> Result<bool, CommandError> {
     let mut connectivity = connectivity_requester.get_event_subscription().fuse();
     print!("Waiting for connectivity... ");
@@ -441,6 +561,10 @@ pub async fn command_runner(
                 },
                 Err(e) => eprintln!("GetBalance error! {}", e),
             },
+            BalanceBreakdown => match output_service.clone().get_balance().await {
+                Ok(balance) => print_balance_breakdown(&balance, !parsed.args.is_empty()),
+                Err(e) => eprintln!("BalanceBreakdown error! {}", e),
+            },
             DiscoverPeer => {
                 if !online {
                     online = wait_for_comms(&connectivity_requester).await?;
@@ -448,10 +572,22 @@ pub async fn command_runner(
                 discover_peer(dht_service.clone(), parsed.args).await?
             },
             SendTari => {
-                let tx_id = send_tari(transaction_service.clone(), parsed.args).await?;
+                let args = resolve_fee_per_gram(parsed.args, &mut output_service).await?;
+                let tx_id = send_tari(transaction_service.clone(), args).await?;
                 debug!(target: LOG_TARGET, "send-tari tx_id {}", tx_id);
                 tx_ids.push(tx_id);
             },
+            SendOneSided => {
+                let args = resolve_fee_per_gram(parsed.args, &mut output_service).await?;
+                let tx_id = send_one_sided(transaction_service.clone(), args).await?;
+                debug!(target: LOG_TARGET, "send-one-sided tx_id {}", tx_id);
+                println!("Note: this wallet can also spend the one-sided output until the recipient spends it.");
+                tx_ids.push(tx_id);
+            },
+            OneSidedAddress => {
+                let address = output_service.get_one_sided_payment_address().await?;
+                println!("One-sided payment address: {}", address);
+            },
             MakeItRain => {
                 let rain_ids = make_it_rain(handle.clone(), transaction_service.clone(), parsed.args).await?;
                 tx_ids.extend(rain_ids);
@@ -461,6 +597,11 @@ pub async fn command_runner(
                 tx_ids.push(tx_id);
                 println!("Coin split succeeded");
             },
+            Sweep => {
+                let tx_id = sweep(&parsed.args, &mut output_service, &mut transaction_service.clone()).await?;
+                tx_ids.push(tx_id);
+                println!("Sweep succeeded");
+            },
             Whois => {
                 let public_key = match parsed.args[0].clone() {
                     ParsedArgument::PublicKey(key) => Ok(Box::new(key)),
@@ -526,6 +667,15 @@ pub async fn command_runner(
                     println!("Maximum value UTXO   : {}", max);
                 }
             },
+            ExportTx => {
+                let tx_id = match parsed.args[0] {
+                    ParsedArgument::Int(tx_id) => Ok(tx_id),
+                    _ => Err(CommandError::Argument),
+                }?;
+                let tx = transaction_service.clone().get_completed_transaction(tx_id).await?;
+                let detail = TransactionDetail::from(&tx);
+                println!("{}", detail.to_json()?);
+            },
         }
     }
 
@@ -566,3 +716,58 @@ pub async fn command_runner(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tari_service_framework::reply_channel;
+    use tari_wallet::output_manager_service::{
+        error::OutputManagerError,
+        handle::{OutputManagerRequest, OutputManagerResponse},
+    };
+    use tokio::{runtime::Runtime, sync::broadcast};
+
+    #[test]
+    fn it_resolves_auto_fee_per_gram() {
+        let mut runtime = Runtime::new().unwrap();
+        let (request_sender, mut request_receiver) = reply_channel::unbounded();
+        let (event_sender, _) = broadcast::channel(1);
+        let mut output_service = OutputManagerHandle::new(request_sender, event_sender);
+
+        runtime.spawn(async move {
+            while let Some(request_context) = request_receiver.next().await {
+                let (request, reply_tx) = request_context.split();
+                let response = match request {
+                    OutputManagerRequest::GetRecommendedFeePerGram => {
+                        Ok(OutputManagerResponse::RecommendedFeePerGram(MicroTari::from(42)))
+                    },
+                    _ => Err(OutputManagerError::UnexpectedApiResponse),
+                };
+                let _ = reply_tx.send(response);
+            }
+        });
+
+        let args = vec![
+            ParsedArgument::Amount(MicroTari::from(1000)),
+            ParsedArgument::AutoFeePerGram,
+            ParsedArgument::Text("Hello".to_string()),
+        ];
+        let resolved = runtime
+            .block_on(resolve_fee_per_gram(args, &mut output_service))
+            .unwrap();
+
+        assert_eq!(resolved.len(), 3);
+        match resolved[0] {
+            ParsedArgument::Amount(amount) => assert_eq!(amount, MicroTari::from(1000)),
+            _ => panic!("Amount argument should be unchanged"),
+        }
+        match resolved[1] {
+            ParsedArgument::FeePerGram(fee_per_gram) => assert_eq!(fee_per_gram, MicroTari::from(42)),
+            _ => panic!("Auto fee per gram should be resolved"),
+        }
+        match &resolved[2] {
+            ParsedArgument::Text(msg) => assert_eq!(msg, "Hello"),
+            _ => panic!("Text argument should be unchanged"),
+        }
+    }
+}
@@ -22,10 +22,11 @@
 
 use std::num::{ParseFloatError, ParseIntError};
 
+use crate::utils::destination::DestinationError;
 use chrono_english::DateError;
 use log::*;
 use tari_app_utilities::utilities::ExitCodes;
-use tari_core::transactions::tari_amount::MicroTariError;
+use tari_core::{tari_utilities::hex::HexError, transactions::tari_amount::MicroTariError};
 use tari_wallet::{
     output_manager_service::error::OutputManagerError,
     transaction_service::error::TransactionServiceError,
@@ -51,6 +52,8 @@ pub enum CommandError {
     Comms(String),
     #[error("CSV file error `{0}`")]
     CSVFile(String),
+    #[error("JSON serialization error `{0}`")]
+    Json(#[from] serde_json::Error),
 }
 
 impl From<CommandError> for ExitCodes {
@@ -66,8 +69,10 @@ pub enum ParseError {
     WalletCommand(String),
     #[error("Failed to parse Tari amount.")]
     MicroTariAmount(#[from] MicroTariError),
-    #[error("Failed to parse public key or emoji id.")]
-    PublicKey,
+    #[error("Failed to parse public key or emoji id. {0}")]
+    PublicKey(#[from] DestinationError),
+    #[error("Failed to parse one-sided payment address. {0}")]
+    OneSidedPaymentAddress(#[from] HexError),
     #[error("Failed to parse a missing {0}")]
     Empty(String),
     #[error("Failed to parse float.")]
@@ -1,3 +1,4 @@
+use crate::utils::destination::parse_and_validate_destination;
 use futures::future;
 use log::*;
 use tari_app_grpc::{
@@ -27,10 +28,7 @@ use tari_app_grpc::{
     },
 };
 use tari_comms::types::CommsPublicKey;
-use tari_core::{
-    tari_utilities::{hex::Hex, ByteArray},
-    transactions::tari_amount::MicroTari,
-};
+use tari_core::{tari_utilities::ByteArray, transactions::tari_amount::MicroTari};
 use tari_wallet::{
     output_manager_service::handle::OutputManagerHandle,
     transaction_service::{handle::TransactionServiceHandle, storage::models},
@@ -121,8 +119,8 @@ impl wallet_server::Wallet for WalletGrpcServer {
             .into_iter()
             .enumerate()
             .map(|(idx, dest)| -> Result<_, String> {
-                let pk = CommsPublicKey::from_hex(&dest.address)
-                    .map_err(|_| format!("Destination address at index {} is malformed", idx))?;
+                let pk = parse_and_validate_destination(&dest.address)
+                    .map_err(|err| format!("Destination address at index {} is malformed: {}", idx, err))?;
                 Ok((dest.address, pk, dest.amount, dest.fee_per_gram, dest.message))
             })
             .collect::<Result<Vec<_>, _>>()
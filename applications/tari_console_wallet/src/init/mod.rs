@@ -66,7 +66,7 @@ use tari_wallet::{
     },
     storage::{
         database::{DbKey, DbKeyValuePair, DbValue, WalletBackend, WriteOperation},
-        sqlite_utilities::initialize_sqlite_database_backends,
+        sqlite_utilities::{check_pending_migrations, initialize_sqlite_database_backends},
     },
     transaction_service::{
         config::{TransactionRoutingMechanism, TransactionServiceConfig},
@@ -158,6 +158,42 @@ pub async fn change_password(
     Ok(())
 }
 
+/// Reports the wallet database migrations that would run on the next start without applying them. Fails if any of them
+/// cannot be reverted, so that the user can back up the database first.
+pub fn check_migrations(config: &GlobalConfig) -> Result<(), ExitCodes> {
+    let db_path = &config.console_wallet_db_file;
+    if !db_path.exists() {
+        return Err(ExitCodes::WalletError(format!(
+            "No wallet database found at {}",
+            db_path.display()
+        )));
+    }
+
+    let pending = check_pending_migrations(db_path)?;
+    if pending.is_empty() {
+        println!("The wallet database is up to date.");
+        return Ok(());
+    }
+
+    println!("{} pending wallet database migration(s):", pending.len());
+    for migration in &pending {
+        if migration.reversible {
+            println!("  {}", migration.version);
+        } else {
+            println!("  {} (irreversible)", migration.version);
+        }
+    }
+
+    if pending.iter().any(|m| !m.reversible) {
+        return Err(ExitCodes::WalletError(
+            "Some pending migrations cannot be reverted. Back up the wallet database before starting the wallet."
+                .to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
 /// Populates the PeerConfig struct from:
 /// 1. The custom peer in the wallet if it exists
 /// 2. The service peers defined in config they exist
@@ -351,6 +387,7 @@ pub async fn init_wallet(
         peer_database_name: "peers".to_string(),
         max_concurrent_inbound_tasks: 100,
         outbound_buffer_size: 100,
+        max_queued_messages_per_peer: config.max_queued_messages_per_peer,
         // TODO - make this configurable
         dht: DhtConfig {
             database_url: DbConnectionUrl::File(config.data_dir.join("dht-console-wallet.db")),
@@ -365,10 +402,11 @@ pub async fn init_wallet(
         allow_test_addresses: config.allow_test_addresses,
         listener_liveness_allowlist_cidrs: Vec::new(),
         listener_liveness_max_sessions: 0,
-        dns_seeds_name_server: DEFAULT_DNS_SEED_RESOLVER.parse().unwrap(),
+        dns_seeds_name_servers: vec![DEFAULT_DNS_SEED_RESOLVER.parse().unwrap()],
         peer_seeds: Default::default(),
         dns_seeds: Default::default(),
         dns_seeds_use_dnssec: true,
+        dns_seeds_doh_name: None,
     };
 
     let network = match &config.network {
@@ -390,6 +428,10 @@ pub async fn init_wallet(
         factories,
         Some(TransactionServiceConfig {
             broadcast_monitoring_timeout: config.transaction_broadcast_monitoring_timeout,
+            broadcast_monitoring_interval: config.transaction_broadcast_monitoring_interval,
+            adaptive_broadcast_monitoring: config.transaction_adaptive_broadcast_monitoring,
+            relay_delay_min: config.transaction_relay_delay_min,
+            relay_delay_max: config.transaction_relay_delay_max,
             chain_monitoring_timeout: config.transaction_chain_monitoring_timeout,
             direct_send_timeout: config.transaction_direct_send_timeout,
             broadcast_send_timeout: config.transaction_broadcast_send_timeout,
@@ -397,6 +439,7 @@ pub async fn init_wallet(
                 config.transaction_routing_mechanism.clone(),
             ),
             num_confirmations_required: config.transaction_num_confirmations_required,
+            transaction_retention_depth: config.transaction_retention_depth,
             ..Default::default()
         }),
         Some(OutputManagerServiceConfig {
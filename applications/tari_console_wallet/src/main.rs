@@ -10,6 +10,7 @@ use crate::recovery::get_private_key_from_seed_words;
 use init::{
     boot,
     change_password,
+    check_migrations,
     get_base_node_peer_config,
     get_notify_script,
     init_wallet,
@@ -90,6 +91,11 @@ fn main_inner() -> Result<(), ExitCodes> {
     let mut shutdown = Shutdown::new();
     let shutdown_signal = shutdown.to_signal();
 
+    if bootstrap.check_migrations {
+        info!(target: LOG_TARGET, "Wallet database migration check requested.");
+        return check_migrations(&config);
+    }
+
     if bootstrap.change_password {
         info!(target: LOG_TARGET, "Change password requested.");
         return runtime.block_on(change_password(&config, arg_password, shutdown_signal));
@@ -7,6 +7,7 @@ use crate::{
         CUSTOM_BASE_NODE_ADDRESS_KEY,
         CUSTOM_BASE_NODE_PUBLIC_KEY_KEY,
     },
+    utils::destination::parse_and_validate_destination,
     wallet_modes::PeerConfig,
 };
 use futures::{stream::Fuse, StreamExt};
@@ -116,12 +117,7 @@ impl AppState {
     pub async fn upsert_contact(&mut self, alias: String, public_key_or_emoji_id: String) -> Result<(), UiError> {
         let mut inner = self.inner.write().await;
 
-        let public_key = match CommsPublicKey::from_hex(public_key_or_emoji_id.as_str()) {
-            Ok(pk) => pk,
-            Err(_) => {
-                EmojiId::str_to_pubkey(public_key_or_emoji_id.as_str()).map_err(|_| UiError::PublicKeyParseError)?
-            },
-        };
+        let public_key = parse_and_validate_destination(&public_key_or_emoji_id)?;
 
         let contact = Contact { alias, public_key };
         inner.wallet.contacts_service.upsert_contact(contact).await?;
@@ -135,10 +131,7 @@ impl AppState {
 
     pub async fn delete_contact(&mut self, public_key: String) -> Result<(), UiError> {
         let mut inner = self.inner.write().await;
-        let public_key = match CommsPublicKey::from_hex(public_key.as_str()) {
-            Ok(pk) => pk,
-            Err(_) => EmojiId::str_to_pubkey(public_key.as_str()).map_err(|_| UiError::PublicKeyParseError)?,
-        };
+        let public_key = parse_and_validate_destination(&public_key)?;
 
         inner.wallet.contacts_service.remove_contact(public_key).await?;
 
@@ -159,10 +152,7 @@ impl AppState {
     ) -> Result<(), UiError>
     {
         let inner = self.inner.write().await;
-        let public_key = match CommsPublicKey::from_hex(public_key.as_str()) {
-            Ok(pk) => pk,
-            Err(_) => EmojiId::str_to_pubkey(public_key.as_str()).map_err(|_| UiError::PublicKeyParseError)?,
-        };
+        let public_key = parse_and_validate_destination(&public_key)?;
 
         let fee_per_gram = fee_per_gram * uT;
         let tx_service_handle = inner.wallet.transaction_service.clone();
@@ -136,6 +136,8 @@ impl WalletEventMonitor {
                                     BaseNodeEvent::BaseNodePeerSet(peer) => {
                                         self.trigger_base_node_peer_refresh(*peer).await;
                                     }
+                                    // The state refresh already picks up the new chain tip
+                                    BaseNodeEvent::TipChanged { .. } => (),
                                 }
                             },
                             Err(_) => debug!(target: LOG_TARGET, "Lagging read on base node event broadcast channel"),
@@ -1,3 +1,4 @@
+use crate::utils::destination::DestinationError;
 use tari_comms::{connectivity::ConnectivityError, peer_manager::node_id::NodeIdError};
 use tari_crypto::tari_utilities::hex::HexError;
 use tari_wallet::{
@@ -26,8 +27,8 @@ pub enum UiError {
     WalletError(#[from] WalletError),
     #[error(transparent)]
     WalletStorageError(#[from] WalletStorageError),
-    #[error("Could not convert string into Public Key")]
-    PublicKeyParseError,
+    #[error("Could not convert string into Public Key: {0}")]
+    PublicKeyParseError(#[from] DestinationError),
     #[error("Could not convert string into Net Address")]
     AddressParseError,
     #[error("Peer did not include an address")]
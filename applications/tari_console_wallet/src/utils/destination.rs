@@ -0,0 +1,155 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use tari_comms::types::CommsPublicKey;
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_wallet::util::emoji::{emoji_set, EmojiId};
+use thiserror::Error;
+
+/// Number of characters in a hex encoded public key
+const PUBLIC_KEY_HEX_LENGTH: usize = 64;
+/// Number of emoji in an emoji id, including the checksum emoji
+const EMOJI_ID_LENGTH: usize = 33;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum DestinationError {
+    #[error("No public key or emoji id was provided")]
+    Empty,
+    #[error("Hex public key must be {} characters long, got {0}", PUBLIC_KEY_HEX_LENGTH)]
+    HexLength(usize),
+    #[error("Hex string is not a valid public key")]
+    InvalidPublicKey,
+    #[error("Emoji id must be {} emoji long, got {0}", EMOJI_ID_LENGTH)]
+    EmojiIdLength(usize),
+    #[error("Emoji id checksum is invalid")]
+    EmojiIdChecksum,
+    #[error("`{0}` is neither a hex public key nor an emoji id")]
+    Unrecognised(String),
+}
+
+/// Parses a destination given as either a hex public key or an emoji id and returns the public key it represents.
+///
+/// Surrounding whitespace is ignored, hex may optionally be prefixed with `0x` and is case insensitive, and emoji ids
+/// may contain the `|` separators and spaces used when they are displayed.
+pub fn parse_and_validate_destination(input: &str) -> Result<CommsPublicKey, DestinationError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(DestinationError::Empty);
+    }
+
+    let hex = input.strip_prefix("0x").unwrap_or(input);
+    if hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return parse_hex_public_key(hex);
+    }
+
+    let emoji = input
+        .chars()
+        .filter(|c| *c != '|' && !c.is_whitespace())
+        .collect::<String>();
+    let emoji_set = emoji_set();
+    if emoji.chars().all(|c| emoji_set.contains(&c)) {
+        return parse_emoji_id(&emoji);
+    }
+
+    Err(DestinationError::Unrecognised(input.to_string()))
+}
+
+fn parse_hex_public_key(hex: &str) -> Result<CommsPublicKey, DestinationError> {
+    if hex.len() != PUBLIC_KEY_HEX_LENGTH {
+        return Err(DestinationError::HexLength(hex.len()));
+    }
+    CommsPublicKey::from_hex(&hex.to_lowercase()).map_err(|_| DestinationError::InvalidPublicKey)
+}
+
+fn parse_emoji_id(emoji: &str) -> Result<CommsPublicKey, DestinationError> {
+    let len = emoji.chars().count();
+    if len != EMOJI_ID_LENGTH {
+        return Err(DestinationError::EmojiIdLength(len));
+    }
+    EmojiId::str_to_pubkey(emoji).map_err(|_| DestinationError::EmojiIdChecksum)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::OsRng;
+    use tari_crypto::keys::PublicKey;
+
+    #[test]
+    fn it_parses_hex_public_keys() {
+        let (_, public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        let hex = public_key.to_hex();
+
+        assert_eq!(parse_and_validate_destination(&hex).unwrap(), public_key);
+        assert_eq!(parse_and_validate_destination(&hex.to_uppercase()).unwrap(), public_key);
+        assert_eq!(
+            parse_and_validate_destination(&format!("  0x{}\n", hex)).unwrap(),
+            public_key
+        );
+    }
+
+    #[test]
+    fn it_parses_emoji_ids() {
+        let (_, public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        let emoji_id = EmojiId::from_pubkey(&public_key).as_str().to_string();
+
+        assert_eq!(parse_and_validate_destination(&emoji_id).unwrap(), public_key);
+
+        let separated = emoji_id.chars().map(|c| c.to_string()).collect::<Vec<_>>().join(" | ");
+        assert_eq!(parse_and_validate_destination(&separated).unwrap(), public_key);
+    }
+
+    #[test]
+    fn it_rejects_malformed_destinations() {
+        assert_eq!(parse_and_validate_destination(""), Err(DestinationError::Empty));
+        assert_eq!(parse_and_validate_destination("   "), Err(DestinationError::Empty));
+        assert_eq!(
+            parse_and_validate_destination("abcdef"),
+            Err(DestinationError::HexLength(6))
+        );
+        // 0xff.. is not a canonical ristretto point
+        assert_eq!(
+            parse_and_validate_destination(&"f".repeat(PUBLIC_KEY_HEX_LENGTH)),
+            Err(DestinationError::InvalidPublicKey)
+        );
+        assert_eq!(
+            parse_and_validate_destination("not a key"),
+            Err(DestinationError::Unrecognised("not a key".to_string()))
+        );
+
+        let (_, public_key) = CommsPublicKey::random_keypair(&mut OsRng);
+        let emoji_id = EmojiId::from_pubkey(&public_key).as_str().to_string();
+        let truncated = emoji_id.chars().take(EMOJI_ID_LENGTH - 1).collect::<String>();
+        assert_eq!(
+            parse_and_validate_destination(&truncated),
+            Err(DestinationError::EmojiIdLength(EMOJI_ID_LENGTH - 1))
+        );
+
+        let mut chars = emoji_id.chars().collect::<Vec<_>>();
+        let checksum = chars.pop().unwrap();
+        chars.push(*emoji_set().iter().find(|c| **c != checksum).unwrap());
+        assert_eq!(
+            parse_and_validate_destination(&chars.into_iter().collect::<String>()),
+            Err(DestinationError::EmojiIdChecksum)
+        );
+    }
+}
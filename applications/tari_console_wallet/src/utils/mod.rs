@@ -22,6 +22,7 @@
 
 pub mod crossterm_events;
 pub mod db;
+pub mod destination;
 pub mod events;
 pub mod formatting;
 
@@ -54,6 +54,8 @@ pub enum CommsInterfaceError {
     DifficultyAdjustmentManagerError(#[from] ConsensusManagerError),
     #[error("Invalid peer response: {0}")]
     InvalidPeerResponse(String),
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
     #[error("Invalid Block Header: {0}")]
     InvalidBlockHeader(#[from] BlockHeaderValidationError),
     #[error("Internal error:{0}")]
@@ -29,7 +29,7 @@ use crate::{
         },
         OutboundNodeCommsInterface,
     },
-    blocks::{block_header::BlockHeader, Block, NewBlock, NewBlockTemplate},
+    blocks::{block_header::BlockHeader, Block, CompactBlock, NewBlock, NewBlockTemplate, MAX_SHORT_ID_REQUEST_SIZE},
     chain_storage::{async_db::AsyncBlockchainDb, BlockAddResult, BlockchainBackend, ChainBlock},
     consensus::{ConsensusConstants, ConsensusManager},
     mempool::{async_mempool, Mempool},
@@ -58,6 +58,52 @@ pub enum BlockEvent {
     AddBlockFailed(Arc<Block>, Broadcast),
     BlockSyncComplete(Arc<ChainBlock>),
     BlockSyncRewind(Vec<Arc<ChainBlock>>),
+    /// The main chain was reorganised. `depth` is the number of blocks that were removed from the previous main chain
+    /// and `fork_height` is the height of the last block that both chains have in common. This is also published when
+    /// the chain is rewound, in which case `new_tip` is the block at `fork_height`.
+    ReorgDetected {
+        fork_height: u64,
+        old_tip: BlockHash,
+        new_tip: BlockHash,
+        depth: u64,
+    },
+}
+
+impl BlockEvent {
+    /// Returns a `ReorgDetected` event if the given result is a chain reorg, otherwise None
+    pub fn reorg_detected(block_add_result: &BlockAddResult) -> Option<Self> {
+        match block_add_result {
+            BlockAddResult::ChainReorg { added, removed } => {
+                let first_added = added.first()?;
+                let new_tip = added.last()?;
+                // If no blocks were removed, the fork block was the previous tip
+                let old_tip = removed
+                    .iter()
+                    .max_by_key(|b| b.height())
+                    .map(|b| b.hash().clone())
+                    .unwrap_or_else(|| first_added.block().header.prev_hash.clone());
+                Some(BlockEvent::ReorgDetected {
+                    fork_height: first_added.height().saturating_sub(1),
+                    old_tip,
+                    new_tip: new_tip.hash().clone(),
+                    depth: removed.len() as u64,
+                })
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns a `ReorgDetected` event for a rewind that removed the given blocks, or None if no blocks were removed
+    pub fn rewind_detected(removed: &[Arc<ChainBlock>]) -> Option<Self> {
+        let first_removed = removed.iter().min_by_key(|b| b.height())?;
+        let old_tip = removed.iter().max_by_key(|b| b.height())?;
+        Some(BlockEvent::ReorgDetected {
+            fork_height: first_removed.height().saturating_sub(1),
+            old_tip: old_tip.hash().clone(),
+            new_tip: first_removed.block().header.prev_hash.clone(),
+            depth: removed.len() as u64,
+        })
+    }
 }
 
 /// Used to notify if the block event is for a propagated block.
@@ -405,6 +451,27 @@ where T: BlockchainBackend + 'static
 
                 Ok(NodeCommsResponse::TransactionKernels(kernels))
             },
+            NodeCommsRequest::FetchTransactionsByShortIds(block_hash, short_ids) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "A peer has requested {} transaction(s) by short id for block `{}`",
+                    short_ids.len(),
+                    block_hash.to_hex()
+                );
+                if short_ids.len() > MAX_SHORT_ID_REQUEST_SIZE {
+                    return Err(CommsInterfaceError::InvalidRequest(format!(
+                        "Requested {} short ids, which exceeds the maximum of {}",
+                        short_ids.len(),
+                        MAX_SHORT_ID_REQUEST_SIZE
+                    )));
+                }
+                let transactions = async_mempool::retrieve_by_short_ids(self.mempool.clone(), block_hash, short_ids)
+                    .await?
+                    .into_iter()
+                    .map(|tx| Arc::try_unwrap(tx).unwrap_or_else(|tx| (*tx).clone()))
+                    .collect();
+                Ok(NodeCommsResponse::Transactions(transactions))
+            },
         }
     }
 
@@ -418,7 +485,10 @@ where T: BlockchainBackend + 'static
         source_peer: NodeId,
     ) -> Result<(), CommsInterfaceError>
     {
-        let NewBlock { block_hash } = new_block;
+        let NewBlock {
+            block_hash,
+            compact_block,
+        } = new_block;
 
         // Only a single block request can complete at a time.
         // As multiple NewBlock requests arrive from propagation, this semaphore prevents multiple requests to nodes for
@@ -435,6 +505,29 @@ where T: BlockchainBackend + 'static
             return Ok(());
         }
 
+        if let Some(compact_block) = compact_block {
+            // A short id collision or a missing transaction results in a reconstructed block that does not match its
+            // header, so any failure here falls back to requesting the full block
+            let result = match self.reconstruct_compact_block(&compact_block, &source_peer).await {
+                Ok(block) => self
+                    .handle_block(Arc::new(block), true.into(), Some(source_peer.clone()))
+                    .await
+                    .map(|_| ()),
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(()) => return Ok(()),
+                Err(err) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Unable to reconstruct compact block `{}`: {}. Falling back to requesting the full block.",
+                        block_hash.to_hex(),
+                        err
+                    );
+                },
+            }
+        }
+
         debug!(
             target: LOG_TARGET,
             "Block with hash `{}` is unknown. Requesting it from peer `{}`.",
@@ -467,6 +560,49 @@ where T: BlockchainBackend + 'static
         }
     }
 
+    /// Reconstructs a compact block from the transactions in the local mempool. Only the transactions that are not in
+    /// the local mempool are requested from the peer that announced the block.
+    async fn reconstruct_compact_block(
+        &mut self,
+        compact_block: &CompactBlock,
+        source_peer: &NodeId,
+    ) -> Result<Block, CommsInterfaceError>
+    {
+        let block_hash = compact_block.hash();
+        let mut transactions = async_mempool::retrieve_by_short_ids(
+            self.mempool.clone(),
+            block_hash.clone(),
+            compact_block.short_ids.clone(),
+        )
+        .await?;
+        let missing = compact_block.missing_short_ids(&transactions);
+        if missing.len() > MAX_SHORT_ID_REQUEST_SIZE {
+            return Err(CommsInterfaceError::InvalidPeerResponse(format!(
+                "{} transaction(s) are missing, which exceeds the maximum of {} that can be requested",
+                missing.len(),
+                MAX_SHORT_ID_REQUEST_SIZE
+            )));
+        }
+        if !missing.is_empty() {
+            debug!(
+                target: LOG_TARGET,
+                "Requesting {} missing transaction(s) for compact block `{}` from peer `{}`",
+                missing.len(),
+                block_hash.to_hex(),
+                source_peer.short_str()
+            );
+            let fetched = self
+                .outbound_nci
+                .request_transactions_by_short_ids_from_peer(block_hash, missing, Some(source_peer.clone()))
+                .await?;
+            transactions.extend(fetched.into_iter().map(Arc::new));
+        }
+
+        compact_block
+            .reconstruct(&transactions)
+            .map_err(|err| CommsInterfaceError::InvalidPeerResponse(err.to_string()))
+    }
+
     /// Handle inbound blocks from remote nodes and local services.
     pub async fn handle_block(
         &self,
@@ -503,7 +639,11 @@ where T: BlockchainBackend + 'static
 
                 self.blockchain_db.cleanup_orphans().await?;
 
+                let reorg_event = BlockEvent::reorg_detected(&block_add_result);
                 self.publish_block_event(BlockEvent::ValidBlockAdded(block, block_add_result, broadcast));
+                if let Some(event) = reorg_event {
+                    self.publish_block_event(event);
+                }
 
                 if should_propagate && broadcast.is_true() {
                     info!(
@@ -512,7 +652,7 @@ where T: BlockchainBackend + 'static
                         block_hash.to_hex()
                     );
                     let exclude_peers = source_peer.into_iter().collect();
-                    let new_block = NewBlock::new(block_hash.clone());
+                    let new_block = NewBlock::from(&*block);
                     self.outbound_nci.propagate_block(new_block, exclude_peers).await?;
                 }
                 Ok(block_hash)
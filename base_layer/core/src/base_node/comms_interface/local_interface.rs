@@ -29,7 +29,7 @@ use crate::{
         NodeCommsResponse,
     },
     blocks::{Block, BlockHeader, NewBlockTemplate},
-    chain_storage::HistoricalBlock,
+    chain_storage::{ChainBlock, HistoricalBlock},
     proof_of_work::PowAlgorithm,
     transactions::{
         transaction::{TransactionKernel, TransactionOutput},
@@ -160,6 +160,16 @@ impl LocalNodeCommsInterface {
         self.block_event_sender.send(Arc::new(event)).unwrap_or(0)
     }
 
+    /// Publishes a `BlockSyncRewind` event for blocks that were rewound off the main chain, followed by a
+    /// `ReorgDetected` event if any blocks were removed
+    pub fn publish_rewind(&self, blocks: Vec<Arc<ChainBlock>>) {
+        let reorg_event = BlockEvent::rewind_detected(&blocks);
+        self.publish_block_event(BlockEvent::BlockSyncRewind(blocks));
+        if let Some(event) = reorg_event {
+            self.publish_block_event(event);
+        }
+    }
+
     pub async fn fetch_matching_utxos(
         &mut self,
         hashes: Vec<HashOutput>,
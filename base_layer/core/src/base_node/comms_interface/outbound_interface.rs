@@ -22,9 +22,12 @@
 
 use crate::{
     base_node::comms_interface::{error::CommsInterfaceError, NodeCommsRequest, NodeCommsResponse},
-    blocks::{block_header::BlockHeader, NewBlock},
+    blocks::{block_header::BlockHeader, NewBlock, ShortTxId},
     chain_storage::HistoricalBlock,
-    transactions::{transaction::TransactionOutput, types::HashOutput},
+    transactions::{
+        transaction::{Transaction, TransactionOutput},
+        types::HashOutput,
+    },
 };
 use futures::channel::mpsc::UnboundedSender;
 use log::*;
@@ -239,6 +242,30 @@ impl OutboundNodeCommsInterface {
         }
     }
 
+    /// Fetch the transactions matching the provided short ids of the compact block with the given hash from the mempool
+    /// of a specific base node. This is used to complete a compact block that could not be reconstructed from the
+    /// local mempool.
+    pub async fn request_transactions_by_short_ids_from_peer(
+        &mut self,
+        block_hash: BlockHash,
+        short_ids: Vec<ShortTxId>,
+        node_id: Option<NodeId>,
+    ) -> Result<Vec<Transaction>, CommsInterfaceError>
+    {
+        if let NodeCommsResponse::Transactions(transactions) = self
+            .request_sender
+            .call((
+                NodeCommsRequest::FetchTransactionsByShortIds(block_hash, short_ids),
+                node_id,
+            ))
+            .await??
+        {
+            Ok(transactions)
+        } else {
+            Err(CommsInterfaceError::UnexpectedApiResponse)
+        }
+    }
+
     /// Transmit a block to remote base nodes, excluding the provided peers.
     pub async fn propagate_block(
         &self,
@@ -56,6 +56,7 @@ pub use sync::{
     rpc::{create_base_node_sync_rpc_service, BaseNodeSyncService},
     BlockSyncConfig,
     SyncValidators,
+    ValidationLevel,
 };
 
 #[cfg(any(feature = "base_node", feature = "base_node_proto"))]
@@ -30,6 +30,7 @@ use crate::{
             BlockHeights,
             FetchHeadersAfter as ProtoFetchHeadersAfter,
             HashOutputs,
+            ShortTxIds,
         },
     },
     transactions::types::{Commitment, HashOutput, Signature},
@@ -82,6 +83,9 @@ impl TryInto<ci::NodeCommsRequest> for ProtoNodeCommsRequest {
             FetchKernelByExcessSig(sig) => ci::NodeCommsRequest::FetchKernelByExcessSig(
                 Signature::try_from(sig).map_err(|err: ByteArrayError| err.to_string())?,
             ),
+            FetchTransactionsByShortIds(short_ids) => {
+                ci::NodeCommsRequest::FetchTransactionsByShortIds(short_ids.block_hash, short_ids.short_ids)
+            },
         };
         Ok(request)
     }
@@ -119,6 +123,9 @@ impl From<ci::NodeCommsRequest> for ProtoNodeCommsRequest {
             },
             GetNewBlock(block_template) => ProtoNodeCommsRequest::GetNewBlock(block_template.into()),
             FetchKernelByExcessSig(signature) => ProtoNodeCommsRequest::FetchKernelByExcessSig(signature.into()),
+            FetchTransactionsByShortIds(block_hash, short_ids) => {
+                ProtoNodeCommsRequest::FetchTransactionsByShortIds(ShortTxIds { short_ids, block_hash })
+            },
         }
     }
 }
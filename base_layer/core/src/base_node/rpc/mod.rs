@@ -26,6 +26,8 @@ mod service;
 use crate::base_node::StateMachineHandle;
 use crate::proto::{
     base_node::{
+        BlockRewardResponse,
+        FeeHistogramResponse,
         FetchMatchingUtxos,
         FetchUtxosResponse,
         Signatures,
@@ -72,6 +74,15 @@ pub trait BaseNodeWalletService: Send + Sync + 'static {
 
     #[rpc(method = 5)]
     async fn get_tip_info(&self, request: Request<()>) -> Result<Response<TipInfoResponse>, RpcStatus>;
+
+    #[rpc(method = 6)]
+    async fn get_mempool_fee_histogram(
+        &self,
+        request: Request<()>,
+    ) -> Result<Response<FeeHistogramResponse>, RpcStatus>;
+
+    #[rpc(method = 7)]
+    async fn get_block_reward(&self, request: Request<u64>) -> Result<Response<BlockRewardResponse>, RpcStatus>;
 }
 
 #[cfg(feature = "base_node")]
@@ -23,9 +23,13 @@
 use crate::{
     base_node::{rpc::BaseNodeWalletService, state_machine_service::states::StateInfo, StateMachineHandle},
     chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend},
+    consensus::emission::Emission,
     mempool::{service::MempoolHandle, TxStorageResponse},
     proto::{
         base_node::{
+            BlockRewardResponse,
+            FeeHistogramBucket,
+            FeeHistogramResponse,
             FetchMatchingUtxos,
             FetchUtxosResponse,
             Signatures as SignaturesProto,
@@ -43,6 +47,7 @@ use crate::{
 };
 use std::convert::TryFrom;
 use tari_comms::protocol::rpc::{Request, Response, RpcStatus};
+use tokio::task;
 
 const LOG_TARGET: &str = "c::base_node::rpc";
 
@@ -128,6 +133,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletRpcService<B> {
             TxStorageResponse::NotStoredOrphan |
             TxStorageResponse::NotStoredTimeLocked |
             TxStorageResponse::NotStoredAlreadySpent |
+            TxStorageResponse::NotStoredPolicyViolation |
             TxStorageResponse::NotStored => TxQueryResponse {
                 location: TxLocation::NotStored as i32,
                 block_hash: None,
@@ -182,7 +188,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
                 is_synced,
             },
 
-            TxStorageResponse::NotStored => TxSubmissionResponse {
+            TxStorageResponse::NotStored | TxStorageResponse::NotStoredPolicyViolation => TxSubmissionResponse {
                 accepted: false,
                 rejection_reason: TxSubmissionRejectionReason::ValidationFailed.into(),
                 is_synced,
@@ -329,4 +335,56 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
             is_synced,
         }))
     }
+
+    async fn get_mempool_fee_histogram(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<FeeHistogramResponse>, RpcStatus>
+    {
+        let mut mempool = self.mempool();
+        let state_machine = self.state_machine();
+        let status_watch = state_machine.get_status_info_watch();
+        let is_synced = match (*status_watch.borrow()).state_info {
+            StateInfo::Listening(li) => li.is_synced(),
+            _ => false,
+        };
+
+        let histogram = mempool
+            .get_fee_histogram()
+            .await
+            .map_err(RpcStatus::log_internal_error(LOG_TARGET))?;
+
+        Ok(Response::new(FeeHistogramResponse {
+            buckets: histogram
+                .buckets
+                .into_iter()
+                .map(|bucket| FeeHistogramBucket {
+                    fee_per_gram: bucket.fee_per_gram,
+                    transaction_count: bucket.transaction_count,
+                    weight: bucket.weight,
+                })
+                .collect(),
+            is_synced,
+        }))
+    }
+
+    async fn get_block_reward(&self, request: Request<u64>) -> Result<Response<BlockRewardResponse>, RpcStatus> {
+        let height = request.into_message();
+        let rules = self.db.inner().rules().clone();
+        // Calculating the reward iterates over the emission schedule until the reward stops changing, so run it on a
+        // blocking thread
+        let (block_reward, tail_emission) = task::spawn_blocking(move || {
+            let (_, _, tail_emission) = rules.consensus_constants(height).emission_amounts();
+            (rules.emission_schedule().block_reward(height), tail_emission)
+        })
+        .await
+        .map_err(RpcStatus::log_internal_error(LOG_TARGET))?;
+
+        Ok(Response::new(BlockRewardResponse {
+            height,
+            block_reward: block_reward.as_u64(),
+            tail_emission: tail_emission.as_u64(),
+            is_tail_emission: block_reward == tail_emission,
+        }))
+    }
 }
@@ -23,7 +23,10 @@
 use crate::{
     base_node::{
         comms_interface::{InboundNodeCommsHandlers, LocalNodeCommsInterface, OutboundNodeCommsInterface},
-        service::service::{BaseNodeService, BaseNodeServiceConfig, BaseNodeStreams},
+        service::{
+            propagation::{BlockPropagationPolicy, MinerFastPathPolicy},
+            service::{BaseNodeService, BaseNodeServiceConfig, BaseNodeStreams},
+        },
         StateMachineHandle,
     },
     blocks::NewBlock,
@@ -61,6 +64,7 @@ pub struct BaseNodeServiceInitializer<T> {
     mempool: Mempool,
     consensus_manager: ConsensusManager,
     config: BaseNodeServiceConfig,
+    propagation_policy: Arc<dyn BlockPropagationPolicy>,
 }
 
 impl<T> BaseNodeServiceInitializer<T>
@@ -81,9 +85,16 @@ where T: BlockchainBackend
             mempool,
             consensus_manager,
             config,
+            propagation_policy: Arc::new(MinerFastPathPolicy),
         }
     }
 
+    /// Replace the policy that decides how urgently blocks are announced to peers. Defaults to `MinerFastPathPolicy`.
+    pub fn with_block_propagation_policy(mut self, policy: Arc<dyn BlockPropagationPolicy>) -> Self {
+        self.propagation_policy = policy;
+        self
+    }
+
     /// Get a stream for inbound Base Node request messages
     fn inbound_request_stream(&self) -> impl Stream<Item = DomainMessage<proto::BaseNodeServiceRequest>> {
         self.inbound_message_subscription_factory
@@ -171,6 +182,7 @@ where T: BlockchainBackend + 'static
             outbound_nci.clone(),
         );
         let config = self.config;
+        let propagation_policy = self.propagation_policy.clone();
 
         // Register handle to OutboundNodeCommsInterface before waiting for handles to be ready
         context.register_handle(outbound_nci);
@@ -191,8 +203,14 @@ where T: BlockchainBackend + 'static
                 local_request_stream,
                 local_block_stream,
             };
-            let service =
-                BaseNodeService::new(outbound_message_service, inbound_nch, config, state_machine).start(streams);
+            let service = BaseNodeService::new(
+                outbound_message_service,
+                inbound_nch,
+                config,
+                state_machine,
+                propagation_policy,
+            )
+            .start(streams);
             futures::pin_mut!(service);
             future::select(service, handles.get_shutdown_signal()).await;
             info!(target: LOG_TARGET, "Base Node Service shutdown");
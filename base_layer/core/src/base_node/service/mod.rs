@@ -31,6 +31,9 @@ pub use service::BaseNodeServiceConfig;
 pub use service_request::BaseNodeServiceRequest;
 pub use service_response::BaseNodeServiceResponse;
 
+mod propagation;
+pub use propagation::{BlockPropagationPolicy, MinerFastPathPolicy};
+
 mod service_request;
 mod service_response;
 
@@ -0,0 +1,144 @@
+// Copyright 2019, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{base_node::comms_interface::CommsInterfaceError, blocks::NewBlock, proto as shared_protos};
+use log::*;
+use tari_comms::peer_manager::NodeId;
+use tari_comms_dht::{
+    domain_message::OutboundDomainMessage,
+    envelope::NodeDestination,
+    outbound::{MessagePriority, OutboundEncryption, OutboundMessageRequester, SendMessageParams},
+};
+use tari_crypto::tari_utilities::hex::Hex;
+use tari_p2p::tari_message::TariMessageType;
+
+const LOG_TARGET: &str = "c::bn::base_node_service::propagation";
+
+/// Decides how urgently a block that was added to the chain is announced to peers.
+pub trait BlockPropagationPolicy: Send + Sync {
+    /// The outbound priority lane used to announce the block. `is_local` is true if the block was submitted by a local
+    /// service, e.g. a miner or merge mining proxy, rather than received from a peer.
+    fn announce_priority(&self, new_block: &NewBlock, is_local: bool) -> MessagePriority;
+}
+
+/// Announces freshly mined blocks ahead of all other queued outbound traffic, so that they reach peers as quickly as
+/// possible and are less likely to be orphaned. Blocks received from peers are relayed in the consensus lane.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinerFastPathPolicy;
+
+impl BlockPropagationPolicy for MinerFastPathPolicy {
+    fn announce_priority(&self, _: &NewBlock, is_local: bool) -> MessagePriority {
+        if is_local {
+            MessagePriority::Control
+        } else {
+            MessagePriority::Consensus
+        }
+    }
+}
+
+/// Announce the block hash to all connected peers, excluding the provided peers. Peers that do not have the block
+/// request the full block in response.
+pub(super) async fn announce_block(
+    mut outbound_message_service: OutboundMessageRequester,
+    new_block: NewBlock,
+    exclude_peers: Vec<NodeId>,
+    priority: MessagePriority,
+) -> Result<(), CommsInterfaceError>
+{
+    debug!(
+        target: LOG_TARGET,
+        "Announcing block {} with priority {:?}",
+        new_block.block_hash.to_hex(),
+        priority
+    );
+    outbound_message_service
+        .send_message(
+            SendMessageParams::new()
+                .flood(exclude_peers)
+                .with_destination(NodeDestination::Unknown)
+                .with_encryption(OutboundEncryption::ClearText)
+                .with_priority(priority)
+                .finish(),
+            OutboundDomainMessage::new(
+                TariMessageType::NewBlock,
+                shared_protos::core::NewBlock::from(new_block),
+            ),
+        )
+        .await?
+        .resolve()
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::{channel::mpsc, future, pin_mut, poll, StreamExt};
+    use tari_comms_dht::outbound::OutboundPriorityDispatcher;
+    use tari_shutdown::Shutdown;
+
+    #[test]
+    fn local_blocks_use_fast_path() {
+        let new_block = NewBlock::new(vec![1; 32]);
+        let policy = MinerFastPathPolicy;
+        assert_eq!(policy.announce_priority(&new_block, true), MessagePriority::Control);
+        assert_eq!(policy.announce_priority(&new_block, false), MessagePriority::Consensus);
+    }
+
+    #[tokio_macros::test_basic]
+    async fn mined_block_announced_before_queued_bulk_traffic() {
+        const NUM_BULK_MSGS: usize = 10;
+        let shutdown = Shutdown::new();
+        let (request_tx, request_rx) = mpsc::channel(NUM_BULK_MSGS + 1);
+        let outbound_message_service = OutboundMessageRequester::new(request_tx);
+
+        let bulk_sends = future::join_all((0..NUM_BULK_MSGS).map(|_| {
+            let mut requester = outbound_message_service.clone();
+            async move {
+                let params = SendMessageParams::new()
+                    .flood(vec![])
+                    .with_priority(MessagePriority::Bulk)
+                    .finish();
+                requester.send_raw(params, b"bulk".to_vec()).await
+            }
+        }));
+        pin_mut!(bulk_sends);
+        assert!(poll!(bulk_sends.as_mut()).is_pending());
+
+        let new_block = NewBlock::new(vec![1; 32]);
+        let priority = MinerFastPathPolicy.announce_priority(&new_block, true);
+        let announce = announce_block(outbound_message_service, new_block, vec![], priority);
+        pin_mut!(announce);
+        assert!(poll!(announce.as_mut()).is_pending());
+
+        // The pipeline only has capacity for one message at a time
+        let (outbound_tx, outbound_rx) = mpsc::channel(0);
+        OutboundPriorityDispatcher::new(request_rx, outbound_tx, shutdown.to_signal()).spawn();
+        let priorities = outbound_rx
+            .take(NUM_BULK_MSGS + 1)
+            .map(|request| request.priority())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(priorities[0], MessagePriority::Control);
+        assert!(priorities[1..].iter().all(|p| *p == MessagePriority::Bulk));
+    }
+}
@@ -29,13 +29,15 @@ use crate::{
             NodeCommsRequest,
             NodeCommsResponse,
         },
-        service::error::BaseNodeServiceError,
+        service::{
+            error::BaseNodeServiceError,
+            propagation::{announce_block, BlockPropagationPolicy},
+        },
         state_machine_service::states::StateInfo,
         StateMachineHandle,
     },
     blocks::{Block, NewBlock},
     chain_storage::BlockchainBackend,
-    proto as shared_protos,
     proto::{base_node as proto, base_node::base_node_service_request::Request},
 };
 use futures::{
@@ -58,8 +60,7 @@ use tari_common_types::{
 use tari_comms::peer_manager::NodeId;
 use tari_comms_dht::{
     domain_message::OutboundDomainMessage,
-    envelope::NodeDestination,
-    outbound::{OutboundEncryption, OutboundMessageRequester, SendMessageParams},
+    outbound::{OutboundMessageRequester, SendMessageParams},
 };
 use tari_crypto::tari_utilities::hex::Hex;
 use tari_p2p::{domain_message::DomainMessage, tari_message::TariMessageType};
@@ -98,7 +99,8 @@ pub(super) struct BaseNodeStreams<SOutReq, SInReq, SInRes, SBlockIn, SLocalReq,
     /// chosen.
     pub outbound_request_stream: SOutReq,
     /// Blocks to be propagated out to the network. The second element of the tuple is a list of peers to exclude from
-    /// this round of propagation
+    /// this round of propagation. Blocks received from a peer always exclude that peer, so a block with no excluded
+    /// peers was submitted by a local service.
     pub outbound_block_stream: UnboundedReceiver<(NewBlock, Vec<NodeId>)>,
     /// `BaseNodeRequest` messages received from external peers
     pub inbound_request_stream: SInReq,
@@ -123,6 +125,7 @@ pub(super) struct BaseNodeService<B> {
     timeout_receiver_stream: Option<Receiver<RequestKey>>,
     config: BaseNodeServiceConfig,
     state_machine_handle: StateMachineHandle,
+    propagation_policy: Arc<dyn BlockPropagationPolicy>,
 }
 
 impl<B> BaseNodeService<B>
@@ -133,6 +136,7 @@ where B: BlockchainBackend + 'static
         inbound_nch: InboundNodeCommsHandlers<B>,
         config: BaseNodeServiceConfig,
         state_machine_handle: StateMachineHandle,
+        propagation_policy: Arc<dyn BlockPropagationPolicy>,
     ) -> Self
     {
         let (timeout_sender, timeout_receiver) = channel(100);
@@ -144,6 +148,7 @@ where B: BlockchainBackend + 'static
             timeout_receiver_stream: Some(timeout_receiver),
             config,
             state_machine_handle,
+            propagation_policy,
         }
     }
 
@@ -266,8 +271,11 @@ where B: BlockchainBackend + 'static
 
     fn spawn_handle_outbound_block(&self, new_block: NewBlock, excluded_peers: Vec<NodeId>) {
         let outbound_message_service = self.outbound_message_service.clone();
+        let priority = self
+            .propagation_policy
+            .announce_priority(&new_block, excluded_peers.is_empty());
         task::spawn(async move {
-            let result = handle_outbound_block(outbound_message_service, new_block, excluded_peers).await;
+            let result = announce_block(outbound_message_service, new_block, excluded_peers, priority).await;
 
             if let Err(e) = result {
                 error!(target: LOG_TARGET, "Failed to handle outbound block message {:?}", e);
@@ -611,26 +619,6 @@ async fn handle_outbound_request(
     Ok(())
 }
 
-async fn handle_outbound_block(
-    mut outbound_message_service: OutboundMessageRequester,
-    new_block: NewBlock,
-    exclude_peers: Vec<NodeId>,
-) -> Result<(), CommsInterfaceError>
-{
-    outbound_message_service
-        .flood(
-            NodeDestination::Unknown,
-            OutboundEncryption::ClearText,
-            exclude_peers,
-            OutboundDomainMessage::new(
-                TariMessageType::NewBlock,
-                shared_protos::core::NewBlock::from(new_block),
-            ),
-        )
-        .await?;
-    Ok(())
-}
-
 async fn handle_request_timeout(
     waiting_requests: WaitingRequests<Result<NodeCommsResponse, CommsInterfaceError>>,
     request_key: RequestKey,
@@ -27,7 +27,7 @@ use crate::{
             states,
             states::{BaseNodeState, HorizonSyncConfig, StateEvent, StateInfo, StatusInfo, SyncPeerConfig, SyncStatus},
         },
-        sync::{BlockSyncConfig, SyncValidators},
+        sync::{BlockSyncConfig, SyncValidators, ValidationLevel},
     },
     chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend},
     consensus::ConsensusManager,
@@ -52,6 +52,7 @@ pub struct BaseNodeStateMachineConfig {
     pub pruning_horizon: u64,
     pub max_randomx_vms: usize,
     pub blocks_behind_before_considered_lagging: u64,
+    pub validation_level: ValidationLevel,
 }
 
 /// A Tari full node, aka Base Node.
@@ -124,11 +125,12 @@ impl<B: BlockchainBackend + 'static> BaseNodeStateMachine<B> {
         match (state, event) {
             (Starting(s), Initialized) => Listening(s.into()),
             (Listening(s), InitialSync) => HeaderSync(s.into()),
-            (HeaderSync(_), HeadersSynchronized(conn)) => {
+            (HeaderSync(s), HeadersSynchronized(conn)) => {
+                let candidate_peers = s.sync_peers().to_vec();
                 if self.config.pruning_horizon > 0 {
-                    HorizonStateSync(states::HorizonStateSync::with_peer(conn))
+                    HorizonStateSync(states::HorizonStateSync::with_peer(conn).with_candidate_peers(candidate_peers))
                 } else {
-                    BlockSync(states::BlockSync::with_peer(conn))
+                    BlockSync(states::BlockSync::with_peer(conn).with_candidate_peers(candidate_peers))
                 }
             },
             (HeaderSync(s), HeaderSyncFailed) => Waiting(s.into()),
@@ -137,6 +139,14 @@ impl<B: BlockchainBackend + 'static> BaseNodeStateMachine<B> {
             (HorizonStateSync(s), HorizonStateSyncFailure) => Waiting(s.into()),
             (BlockSync(s), BlocksSynchronized) => Listening(s.into()),
             (BlockSync(s), BlockSyncFailed) => Waiting(s.into()),
+            (BlockSync(s), BlocksPrunedAway(conn)) => {
+                if self.config.pruning_horizon > 0 {
+                    let candidate_peers = s.candidate_peers().to_vec();
+                    HorizonStateSync(states::HorizonStateSync::with_peer(conn).with_candidate_peers(candidate_peers))
+                } else {
+                    Waiting(s.into())
+                }
+            },
             (Listening(_), FallenBehind(Lagging(_, sync_peers))) => HeaderSync(sync_peers.into()),
             (Listening(_), FallenBehind(LaggingBehindHorizon(_, sync_peers))) => HeaderSync(sync_peers.into()),
             (Waiting(s), Continue) => Listening(s.into()),
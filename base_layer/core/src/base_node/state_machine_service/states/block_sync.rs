@@ -24,20 +24,22 @@ use crate::{
     base_node::{
         comms_interface::BlockEvent,
         state_machine_service::states::{BlockSyncInfo, HorizonStateSync, StateEvent, StateInfo, StatusInfo},
-        sync::BlockSynchronizer,
+        sync::{BlockSyncError, BlockSynchronizer, ValidationLevel},
         BaseNodeStateMachine,
     },
     chain_storage::{BlockAddResult, BlockchainBackend},
+    validation::{block_validators::AssumeValidBlockValidator, CandidateBlockBodyValidation},
 };
 use log::*;
-use std::time::Instant;
-use tari_comms::PeerConnection;
+use std::{sync::Arc, time::Instant};
+use tari_comms::{peer_manager::NodeId, PeerConnection};
 
 const LOG_TARGET: &str = "c::bn::block_sync";
 
 #[derive(Debug, Default)]
 pub struct BlockSync {
     sync_peer: Option<PeerConnection>,
+    candidate_peers: Vec<NodeId>,
     is_synced: bool,
 }
 
@@ -49,20 +51,42 @@ impl BlockSync {
     pub fn with_peer(sync_peer: PeerConnection) -> Self {
         Self {
             sync_peer: Some(sync_peer),
+            candidate_peers: Vec::new(),
             is_synced: false,
         }
     }
 
+    /// Sets the peers that header sync selected the sync peer from. Additional peers to download blocks from are
+    /// selected from these peers.
+    pub fn with_candidate_peers(mut self, candidate_peers: Vec<NodeId>) -> Self {
+        self.candidate_peers = candidate_peers;
+        self
+    }
+
+    pub fn candidate_peers(&self) -> &[NodeId] {
+        &self.candidate_peers
+    }
+
     pub async fn next_event<B: BlockchainBackend + 'static>(
         &mut self,
         shared: &mut BaseNodeStateMachine<B>,
     ) -> StateEvent
     {
+        let block_validator: Arc<dyn CandidateBlockBodyValidation<B>> = match shared.config.validation_level {
+            ValidationLevel::Full => shared.sync_validators.block_body.clone(),
+            ValidationLevel::AssumeValidBelow(ref hash) => Arc::new(AssumeValidBlockValidator::new(
+                shared.sync_validators.block_body.clone(),
+                hash.clone(),
+            )),
+        };
+        let sync_peer = self.sync_peer.take();
         let mut synchronizer = BlockSynchronizer::new(
+            shared.config.block_sync_config.clone(),
             shared.db.clone(),
             shared.connectivity.clone(),
-            self.sync_peer.take(),
-            shared.sync_validators.block_body.clone(),
+            sync_peer.clone(),
+            self.candidate_peers.clone(),
+            block_validator,
         );
 
         let status_event_sender = shared.status_event_sender.clone();
@@ -98,6 +122,22 @@ impl BlockSync {
                 self.is_synced = true;
                 StateEvent::BlocksSynchronized
             },
+            Err(BlockSyncError::AllSyncPeersPruned) => match sync_peer {
+                Some(conn) if Self::is_behind_pruning_horizon(shared).await => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Block sync failed because all sync peers have pruned the required block bodies"
+                    );
+                    StateEvent::BlocksPrunedAway(conn)
+                },
+                _ => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Block sync failed: all sync peers have pruned the block bodies"
+                    );
+                    StateEvent::BlockSyncFailed
+                },
+            },
             Err(err) => {
                 debug!(target: LOG_TARGET, "Block sync failed: {}", err);
                 StateEvent::BlockSyncFailed
@@ -105,13 +145,32 @@ impl BlockSync {
         }
     }
 
+    /// Returns true if this node is pruned and its tip is below the horizon sync height, in which case horizon sync can
+    /// skip the block bodies that sync peers no longer have.
+    async fn is_behind_pruning_horizon<B: BlockchainBackend + 'static>(shared: &BaseNodeStateMachine<B>) -> bool {
+        if shared.config.pruning_horizon == 0 {
+            return false;
+        }
+        let local_metadata = match shared.db.get_chain_metadata().await {
+            Ok(metadata) => metadata,
+            Err(_) => return false,
+        };
+        match shared.db.fetch_last_header().await {
+            Ok(header) => {
+                local_metadata.height_of_longest_chain() <
+                    header.height.saturating_sub(local_metadata.pruning_horizon())
+            },
+            Err(_) => false,
+        }
+    }
+
     pub fn is_synced(&self) -> bool {
         self.is_synced
     }
 }
 
 impl From<HorizonStateSync> for BlockSync {
-    fn from(_: HorizonStateSync) -> Self {
-        BlockSync::new()
+    fn from(sync: HorizonStateSync) -> Self {
+        BlockSync::new().with_candidate_peers(sync.candidate_peers().to_vec())
     }
 }
@@ -60,6 +60,7 @@ pub enum StateEvent {
     HorizonStateSyncFailure,
     BlocksSynchronized,
     BlockSyncFailed,
+    BlocksPrunedAway(PeerConnection),
     FallenBehind(SyncStatus),
     NetworkSilence,
     FatalError(String),
@@ -131,6 +132,11 @@ impl Display for StateEvent {
             HorizonStateSynchronized => f.write_str("Horizon State Synchronized"),
             HorizonStateSyncFailure => f.write_str("Horizon State Synchronization Failed"),
             BlockSyncFailed => f.write_str("Block Synchronization Failed"),
+            BlocksPrunedAway(conn) => write!(
+                f,
+                "Block bodies pruned away by sync peers, falling back to horizon sync with peer `{}`",
+                conn.peer_node_id()
+            ),
             FallenBehind(s) => write!(f, "Fallen behind main chain - {}", s),
             NetworkSilence => f.write_str("Network Silence"),
             Continue => f.write_str("Continuing"),
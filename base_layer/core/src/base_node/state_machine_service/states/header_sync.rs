@@ -22,7 +22,6 @@
 
 use crate::{
     base_node::{
-        comms_interface::BlockEvent,
         state_machine_service::states::{BlockSyncInfo, Listening, StateEvent, StateInfo, StatusInfo},
         sync::{BlockHeaderSyncError, HeaderSynchronizer, SyncPeers},
         BaseNodeStateMachine,
@@ -53,6 +52,12 @@ impl HeaderSync {
         self.is_synced
     }
 
+    /// The peers that the sync peer is selected from. If empty, the configured sync peers or all connected peers are
+    /// used.
+    pub fn sync_peers(&self) -> &[NodeId] {
+        &self.sync_peers
+    }
+
     pub async fn next_event<B: BlockchainBackend + 'static>(
         &mut self,
         shared: &mut BaseNodeStateMachine<B>,
@@ -88,7 +93,7 @@ impl HeaderSync {
 
         let local_nci = shared.local_node_interface.clone();
         synchronizer.on_rewind(move |blocks| {
-            local_nci.publish_block_event(BlockEvent::BlockSyncRewind(blocks));
+            local_nci.publish_rewind(blocks);
         });
 
         let timer = Instant::now();
@@ -44,18 +44,32 @@ use super::{
 };
 use crate::{base_node::BaseNodeStateMachine, chain_storage::BlockchainBackend, transactions::types::CryptoFactories};
 use log::*;
-use tari_comms::PeerConnection;
+use tari_comms::{peer_manager::NodeId, PeerConnection};
 
 const LOG_TARGET: &str = "c::bn::state_machine_service::states::horizon_state_sync";
 
 #[derive(Clone, Debug)]
 pub struct HorizonStateSync {
     sync_peer: PeerConnection,
+    candidate_peers: Vec<NodeId>,
 }
 
 impl HorizonStateSync {
     pub fn with_peer(sync_peer: PeerConnection) -> Self {
-        Self { sync_peer }
+        Self {
+            sync_peer,
+            candidate_peers: Vec::new(),
+        }
+    }
+
+    /// Sets the peers that header sync selected the sync peer from, which are passed on to block sync
+    pub fn with_candidate_peers(mut self, candidate_peers: Vec<NodeId>) -> Self {
+        self.candidate_peers = candidate_peers;
+        self
+    }
+
+    pub fn candidate_peers(&self) -> &[NodeId] {
+        &self.candidate_peers
     }
 
     pub async fn next_event<B: BlockchainBackend + 'static>(
@@ -68,18 +82,16 @@ impl HorizonStateSync {
             Err(err) => return StateEvent::FatalError(err.to_string()),
         };
 
-        if local_metadata.height_of_longest_chain() > 0 &&
-            local_metadata.height_of_longest_chain() >= local_metadata.pruned_height()
-        {
-            return StateEvent::HorizonStateSynchronized;
-        }
-
         let horizon_sync_height = match shared.db.fetch_last_header().await {
             Ok(header) => header.height.saturating_sub(local_metadata.pruning_horizon()),
             Err(err) => return StateEvent::FatalError(err.to_string()),
         };
 
-        if local_metadata.height_of_longest_chain() > horizon_sync_height {
+        // Block sync can continue from the local tip as long as it is not below the horizon sync height. A tip below
+        // the horizon sync height may require block bodies that sync peers have already pruned.
+        if local_metadata.height_of_longest_chain() > 0 &&
+            local_metadata.height_of_longest_chain() >= horizon_sync_height
+        {
             return StateEvent::HorizonStateSynchronized;
         }
 
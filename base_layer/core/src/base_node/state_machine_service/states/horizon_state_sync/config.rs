@@ -20,6 +20,8 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::time::Duration;
+
 /// Configuration for the Horizon State Synchronization.
 #[derive(Clone, Copy)]
 pub struct HorizonSyncConfig {
@@ -37,6 +39,11 @@ pub struct HorizonSyncConfig {
     pub header_request_size: usize,
     /// Maximum number of header retry attempts
     pub max_header_request_retry_attempts: usize,
+    /// The length of time to ban a sync peer that sent data that does not match the MMR roots of the synced headers
+    pub short_term_peer_ban_duration: Duration,
+    /// The number of kernels or outputs to commit at a time within a single block, so that an interrupted sync can
+    /// resume from the last committed MMR position
+    pub progress_commit_interval: u64,
 }
 
 impl Default for HorizonSyncConfig {
@@ -48,6 +55,8 @@ impl Default for HorizonSyncConfig {
             max_utxo_mmr_node_request_size: 1000,
             header_request_size: 100,
             max_header_request_retry_attempts: 5,
+            short_term_peer_ban_duration: Duration::from_secs(30 * 60),
+            progress_commit_interval: 1000,
         }
     }
 }
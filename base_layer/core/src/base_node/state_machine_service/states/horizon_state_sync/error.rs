@@ -27,7 +27,10 @@ use crate::{
     validation::ValidationError,
 };
 use std::num::TryFromIntError;
-use tari_comms::protocol::rpc::{RpcError, RpcStatus};
+use tari_comms::{
+    connectivity::ConnectivityError,
+    protocol::rpc::{RpcError, RpcStatus},
+};
 use tari_mmr::error::MerkleMountainRangeError;
 use thiserror::Error;
 use tokio::task;
@@ -67,6 +70,8 @@ pub enum HorizonSyncError {
     ConversionError(String),
     #[error("MerkleMountainRangeError: {0}")]
     MerkleMountainRangeError(#[from] MerkleMountainRangeError),
+    #[error("Failed to ban peer: {0}")]
+    FailedToBan(ConnectivityError),
 }
 
 impl From<TryFromIntError> for HorizonSyncError {
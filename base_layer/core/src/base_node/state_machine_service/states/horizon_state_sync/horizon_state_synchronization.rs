@@ -30,7 +30,15 @@ use crate::{
         sync::rpc,
     },
     blocks::BlockHeader,
-    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, ChainStorageError, MmrTree, PrunedOutput},
+    chain_storage::{
+        async_db::AsyncBlockchainDb,
+        BlockchainBackend,
+        ChainHeader,
+        ChainStorageError,
+        HorizonSyncProgress,
+        MmrTree,
+        PrunedOutput,
+    },
     proto::base_node::{
         sync_utxo as proto_sync_utxo,
         sync_utxos_response::UtxoOrDeleted,
@@ -183,8 +191,19 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
             start,
             current_header.height()
         );
-        let mut kernels = vec![];
         let db = self.db().clone();
+        // Kernels committed for this header before the sync was interrupted are needed to validate the MMR root
+        let mut kernels = db.fetch_kernels_in_block(current_header.hash().clone()).await?;
+        if !kernels.is_empty() {
+            self.check_sync_progress(MmrTree::Kernel, &current_header, start)
+                .await?;
+            debug!(
+                target: LOG_TARGET,
+                "Resuming kernel sync for header #{} after {} committed kernel(s)",
+                current_header.height(),
+                kernels.len()
+            );
+        }
         let mut txn = db.write_transaction();
         let mut mmr_position = start;
         while let Some(kernel) = kernel_stream.next().await {
@@ -217,12 +236,13 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
 
                 let mmr_root = kernel_mmr.get_merkle_root()?;
                 if mmr_root != current_header.header().kernel_mr {
-                    return Err(HorizonSyncError::InvalidMmrRoot {
+                    let err = HorizonSyncError::InvalidMmrRoot {
                         mmr_tree: MmrTree::Kernel,
                         at_height: current_header.height(),
                         expected_hex: current_header.header().kernel_mr.to_hex(),
                         actual_hex: mmr_root.to_hex(),
-                    });
+                    };
+                    return Err(self.ban_sync_peer_for_error(err).await);
                 }
 
                 txn.update_pruned_hash_set(
@@ -235,6 +255,13 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
                 if mmr_position < end - 1 {
                     current_header = db.fetch_chain_header(current_header.height() + 1).await?;
                 }
+            } else if (mmr_position + 1) % self.shared.config.horizon_sync_config.progress_commit_interval == 0 {
+                txn.set_horizon_sync_progress(Some(HorizonSyncProgress::new(
+                    MmrTree::Kernel,
+                    current_header.hash().clone(),
+                    mmr_position + 1,
+                )));
+                txn.commit().await?;
             }
             mmr_position += 1;
 
@@ -297,14 +324,6 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
             latency.unwrap_or_default().as_millis()
         );
 
-        let req = SyncUtxosRequest {
-            start,
-            end_header_hash: end_hash,
-            include_deleted_bitmaps: true,
-            include_pruned_utxos: true,
-        };
-        let mut output_stream = client.sync_utxos(req).await?;
-
         let mut current_header = self.db().fetch_header_containing_utxo_mmr(start + 1).await?;
         debug!(
             target: LOG_TARGET,
@@ -320,10 +339,41 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
         let mut rp_hashes = vec![];
         let mut txn = db.write_transaction();
         let mut unpruned_outputs = vec![];
-        let mut mmr_position = start;
         let mut height_utxo_counter = 0u64;
         let mut height_txo_counter = 0u64;
 
+        // Outputs committed for this header before the sync was interrupted are needed to validate the MMR roots
+        let committed_outputs = db.fetch_outputs_in_block(current_header.hash().clone()).await?;
+        if !committed_outputs.is_empty() {
+            self.check_sync_progress(MmrTree::Utxo, &current_header, start).await?;
+            debug!(
+                target: LOG_TARGET,
+                "Resuming output sync for header #{} after {} committed output(s)",
+                current_header.height(),
+                committed_outputs.len()
+            );
+        }
+        // The sync is resumed from the start of the block, because a peer may not support starting part way through a
+        // block. The outputs that are already committed are checked against the peer's outputs and skipped.
+        let block_start = start - committed_outputs.len() as u64;
+        let mut mmr_position = block_start;
+        for output in committed_outputs {
+            match output {
+                PrunedOutput::Pruned {
+                    output_hash,
+                    range_proof_hash,
+                } => {
+                    output_hashes.push(output_hash);
+                    rp_hashes.push(range_proof_hash);
+                },
+                PrunedOutput::NotPruned { output } => {
+                    output_hashes.push(output.hash());
+                    rp_hashes.push(output.proof().hash());
+                    unpruned_outputs.push(output);
+                },
+            }
+        }
+
         let block_data = db
             .fetch_block_accumulated_data(current_header.header().prev_hash.clone())
             .await?;
@@ -332,6 +382,14 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
         let mut output_mmr = MerkleMountainRange::<HashDigest, _>::new(output_pruned_set);
         let mut proof_mmr = MerkleMountainRange::<HashDigest, _>::new(rp_pruned_set);
 
+        let req = SyncUtxosRequest {
+            start: block_start,
+            end_header_hash: end_hash,
+            include_deleted_bitmaps: true,
+            include_pruned_utxos: true,
+        };
+        let mut output_stream = client.sync_utxos(req).await?;
+
         while let Some(response) = output_stream.next().await {
             let res: SyncUtxosResponse = response?;
 
@@ -346,6 +404,35 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
                 .utxo_or_deleted
                 .ok_or_else(|| HorizonSyncError::IncorrectResponse("Peer sent no transaction output data".into()))?;
 
+            if mmr_position < start {
+                let expected_hash = &output_hashes[(mmr_position - block_start) as usize];
+                let hash = match txo {
+                    UtxoOrDeleted::Utxo(SyncUtxo {
+                        utxo: Some(proto_sync_utxo::Utxo::Output(output)),
+                    }) => TransactionOutput::try_from(output)
+                        .map_err(HorizonSyncError::ConversionError)?
+                        .hash(),
+                    UtxoOrDeleted::Utxo(SyncUtxo {
+                        utxo: Some(proto_sync_utxo::Utxo::PrunedOutput(utxo)),
+                    }) => utxo.hash,
+                    _ => {
+                        return Err(HorizonSyncError::IncorrectResponse(format!(
+                            "Expected an output at MMR position {} that was already synchronized",
+                            mmr_position
+                        )))
+                    },
+                };
+                if hash != *expected_hash {
+                    return Err(HorizonSyncError::IncorrectResponse(format!(
+                        "Output at MMR position {} does not match the output already synchronized",
+                        mmr_position
+                    )));
+                }
+                mmr_position += 1;
+                continue;
+            }
+
+            let is_deleted_diff = matches!(txo, UtxoOrDeleted::DeletedDiff(_));
             match txo {
                 UtxoOrDeleted::Utxo(SyncUtxo {
                     utxo: Some(proto_sync_utxo::Utxo::Output(output)),
@@ -428,22 +515,24 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
 
                     let mmr_root = output_mmr.get_merkle_root()?;
                     if mmr_root != current_header.header().output_mr {
-                        return Err(HorizonSyncError::InvalidMmrRoot {
+                        let err = HorizonSyncError::InvalidMmrRoot {
                             mmr_tree: MmrTree::Utxo,
                             at_height: current_header.height(),
                             expected_hex: current_header.header().output_mr.to_hex(),
                             actual_hex: mmr_root.to_hex(),
-                        });
+                        };
+                        return Err(self.ban_sync_peer_for_error(err).await);
                     }
 
                     let mmr_root = proof_mmr.get_merkle_root()?;
                     if mmr_root != current_header.header().range_proof_mr {
-                        return Err(HorizonSyncError::InvalidMmrRoot {
+                        let err = HorizonSyncError::InvalidMmrRoot {
                             mmr_tree: MmrTree::RangeProof,
                             at_height: current_header.height(),
                             expected_hex: current_header.header().range_proof_mr.to_hex(),
                             actual_hex: mmr_root.to_hex(),
-                        });
+                        };
+                        return Err(self.ban_sync_peer_for_error(err).await);
                     }
 
                     // Validate rangeproofs if the MMR matches
@@ -477,6 +566,18 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
                 },
             }
 
+            if !is_deleted_diff &&
+                mmr_position < current_header.header().output_mmr_size &&
+                mmr_position % self.shared.config.horizon_sync_config.progress_commit_interval == 0
+            {
+                txn.set_horizon_sync_progress(Some(HorizonSyncProgress::new(
+                    MmrTree::Utxo,
+                    current_header.hash().clone(),
+                    mmr_position,
+                )));
+                txn.commit().await?;
+            }
+
             if mmr_position % 100 == 0 || mmr_position == self.num_outputs {
                 let info = HorizonSyncInfo::new(
                     vec![self.sync_peer.peer_node_id().clone()],
@@ -601,14 +702,349 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
                 header.accumulated_data().total_accumulated_difficulty,
             )
             .set_pruned_height(header.height(), pruned_kernel_sum, pruned_utxo_sum)
+            .set_horizon_sync_progress(None)
             .commit()
             .await?;
 
         Ok(())
     }
 
+    /// Checks that the persisted horizon sync progress agrees with the partially synced header that sync is resuming
+    /// from.
+    async fn check_sync_progress(
+        &self,
+        mmr_tree: MmrTree,
+        header: &ChainHeader,
+        mmr_position: u64,
+    ) -> Result<(), HorizonSyncError>
+    {
+        let progress = self.db().fetch_horizon_sync_progress().await?;
+        match progress {
+            Some(progress)
+                if progress.mmr_tree() == mmr_tree &&
+                    progress.header_hash() == header.hash() &&
+                    progress.mmr_position() == mmr_position =>
+            {
+                Ok(())
+            },
+            progress => Err(ChainStorageError::DataInconsistencyDetected {
+                function: "check_sync_progress",
+                details: format!(
+                    "{} MMR position {} in header #{} does not match the stored horizon sync progress ({})",
+                    mmr_tree,
+                    mmr_position,
+                    header.height(),
+                    progress.map(|p| p.to_string()).unwrap_or_else(|| "none".to_string())
+                ),
+            }
+            .into()),
+        }
+    }
+
+    /// Bans the sync peer for sending data that caused `err` and returns `err`. A failure to ban the peer is logged so
+    /// that it does not mask the original error.
+    async fn ban_sync_peer_for_error(&mut self, err: HorizonSyncError) -> HorizonSyncError {
+        if let Err(ban_err) = self.ban_sync_peer(err.to_string()).await {
+            error!(
+                target: LOG_TARGET,
+                "Failed to ban sync peer {}: {}",
+                self.sync_peer.peer_node_id(),
+                ban_err
+            );
+        }
+        err
+    }
+
+    async fn ban_sync_peer(&mut self, reason: String) -> Result<(), HorizonSyncError> {
+        let node_id = self.sync_peer.peer_node_id().clone();
+        if self.shared.config.block_sync_config.sync_peers.contains(&node_id) {
+            debug!(
+                target: LOG_TARGET,
+                "Not banning peer that is allowlisted for sync. Ban reason = {}", reason
+            );
+            return Ok(());
+        }
+        let duration = self.shared.config.horizon_sync_config.short_term_peer_ban_duration;
+        warn!(
+            target: LOG_TARGET,
+            "Banned sync peer {} for {:.2?} because {}", node_id, duration, reason
+        );
+        self.shared
+            .connectivity
+            .ban_peer_until(node_id, duration, reason)
+            .await
+            .map_err(HorizonSyncError::FailedToBan)?;
+        Ok(())
+    }
+
     #[inline]
     fn db(&self) -> &AsyncBlockchainDb<B> {
         &self.shared.db
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        base_node::{
+            state_machine_service::{
+                states::{HorizonSyncConfig, StatusInfo},
+                BaseNodeStateMachineConfig,
+            },
+            LocalNodeCommsInterface,
+            OutboundNodeCommsInterface,
+            SyncValidators,
+        },
+        consensus::{ConsensusManagerBuilder, Network},
+        proof_of_work::randomx_factory::RandomXFactory,
+        proto,
+        proto::base_node::{
+            FindChainSplitRequest,
+            FindChainSplitResponse,
+            GetBlocksByHashRequest,
+            GetBlocksByHashResponse,
+            SyncBlocksRequest,
+            SyncHeadersRequest,
+        },
+        test_helpers::{
+            blockchain::{create_test_blockchain_db, TempDatabase},
+            create_chain_header,
+            create_peer_manager,
+        },
+        transactions::{helpers::create_test_kernel, types::CryptoFactories},
+        validation::mocks::MockValidator,
+    };
+    use futures::channel::mpsc;
+    use std::{path::Path, sync::Arc};
+    use tari_comms::{
+        peer_manager::PeerFeatures,
+        protocol::rpc::{
+            mock::{MockRpcServer, RpcMock, RpcMockMethodState},
+            NamedProtocolService,
+            Request,
+            Response,
+            RpcStatus,
+            Streaming,
+        },
+        test_utils::{
+            mocks::{create_connectivity_mock, ConnectivityManagerMockState},
+            node_identity::build_node_identity,
+        },
+        NodeIdentity,
+    };
+    use tari_service_framework::reply_channel;
+    use tari_shutdown::Shutdown;
+    use tempfile::tempdir;
+    use tokio::{
+        sync::{broadcast, watch},
+        task,
+    };
+
+    fn create_state_machine(
+        config: BaseNodeStateMachineConfig,
+        data_path: &Path,
+        shutdown: &Shutdown,
+    ) -> (BaseNodeStateMachine<TempDatabase>, ConnectivityManagerMockState)
+    {
+        let (request_sender, _) = reply_channel::unbounded();
+        let (block_sender, _) = reply_channel::unbounded();
+        let (block_event_sender, _) = broadcast::channel(1);
+        let local_nci = LocalNodeCommsInterface::new(request_sender, block_sender, block_event_sender);
+        let (outbound_request_sender, _) = reply_channel::unbounded();
+        let (outbound_block_sender, _) = mpsc::unbounded();
+        let outbound_nci = OutboundNodeCommsInterface::new(outbound_request_sender, outbound_block_sender);
+
+        let (connectivity, connectivity_mock) = create_connectivity_mock();
+        let connectivity_mock_state = connectivity_mock.get_shared_state();
+        task::spawn(connectivity_mock.run());
+
+        let (_, metadata_event_stream) = broadcast::channel(1);
+        let (status_event_sender, _) = watch::channel(StatusInfo::new());
+        let (event_publisher, _) = broadcast::channel(1);
+        let state_machine = BaseNodeStateMachine::new(
+            create_test_blockchain_db().into(),
+            local_nci,
+            outbound_nci,
+            connectivity,
+            create_peer_manager(data_path),
+            metadata_event_stream,
+            config,
+            SyncValidators::new(MockValidator::new(true), MockValidator::new(true)),
+            status_event_sender,
+            event_publisher,
+            RandomXFactory::default(),
+            ConsensusManagerBuilder::new(Network::LocalNet).build(),
+            shutdown.to_signal(),
+        );
+        (state_machine, connectivity_mock_state)
+    }
+
+    async fn take_ban_calls<B: BlockchainBackend>(
+        state_machine: &mut BaseNodeStateMachine<B>,
+        connectivity_mock_state: &ConnectivityManagerMockState,
+    ) -> Vec<String>
+    {
+        // The mock handles requests in order, so once this request is answered all bans have been recorded
+        state_machine.connectivity.get_connectivity_status().await.unwrap();
+        connectivity_mock_state
+            .take_calls()
+            .await
+            .into_iter()
+            .filter(|call| call.starts_with("BanPeer"))
+            .collect()
+    }
+
+    /// A sync peer that only serves kernels, with the kernels it sends set by the test
+    #[derive(Default)]
+    struct KernelSyncPeerMock {
+        sync_kernels: RpcMockMethodState<SyncKernelsRequest, Vec<proto::types::TransactionKernel>>,
+    }
+
+    impl RpcMock for KernelSyncPeerMock {}
+
+    #[tari_comms::async_trait]
+    impl rpc::BaseNodeSyncService for KernelSyncPeerMock {
+        async fn sync_blocks(
+            &self,
+            _: Request<SyncBlocksRequest>,
+        ) -> Result<Streaming<proto::base_node::BlockBodyResponse>, RpcStatus>
+        {
+            Err(RpcStatus::not_implemented("sync_blocks"))
+        }
+
+        async fn sync_headers(
+            &self,
+            _: Request<SyncHeadersRequest>,
+        ) -> Result<Streaming<proto::core::BlockHeader>, RpcStatus>
+        {
+            Err(RpcStatus::not_implemented("sync_headers"))
+        }
+
+        async fn get_header_by_height(
+            &self,
+            _: Request<u64>,
+        ) -> Result<Response<proto::core::BlockHeader>, RpcStatus>
+        {
+            Err(RpcStatus::not_implemented("get_header_by_height"))
+        }
+
+        async fn find_chain_split(
+            &self,
+            _: Request<FindChainSplitRequest>,
+        ) -> Result<Response<FindChainSplitResponse>, RpcStatus>
+        {
+            Err(RpcStatus::not_implemented("find_chain_split"))
+        }
+
+        async fn get_chain_metadata(
+            &self,
+            _: Request<()>,
+        ) -> Result<Response<proto::base_node::ChainMetadata>, RpcStatus>
+        {
+            Err(RpcStatus::not_implemented("get_chain_metadata"))
+        }
+
+        async fn sync_kernels(
+            &self,
+            request: Request<SyncKernelsRequest>,
+        ) -> Result<Streaming<proto::types::TransactionKernel>, RpcStatus>
+        {
+            self.server_streaming(request, &self.sync_kernels).await
+        }
+
+        async fn sync_utxos(
+            &self,
+            _: Request<SyncUtxosRequest>,
+        ) -> Result<Streaming<SyncUtxosResponse>, RpcStatus>
+        {
+            Err(RpcStatus::not_implemented("sync_utxos"))
+        }
+
+        async fn get_blocks_by_hash(
+            &self,
+            _: Request<GetBlocksByHashRequest>,
+        ) -> Result<Response<GetBlocksByHashResponse>, RpcStatus>
+        {
+            Err(RpcStatus::not_implemented("get_blocks_by_hash"))
+        }
+    }
+
+    /// Horizon syncs to a header whose kernel MMR root does not match the kernel sent by the sync peer. Returns the
+    /// sync error, the node id of the sync peer and the ban calls that were made.
+    async fn sync_with_invalid_kernel_root(
+        config: BaseNodeStateMachineConfig,
+        sync_peer_identity: Arc<NodeIdentity>,
+    ) -> (HorizonSyncError, Vec<String>)
+    {
+        let shutdown = Shutdown::new();
+        let tmp = tempdir().unwrap();
+        let (mut state_machine, connectivity_mock_state) = create_state_machine(config, tmp.path(), &shutdown);
+
+        // Only the header is known locally, so its kernel has to be synced from the peer
+        let db = state_machine.db.inner().clone();
+        let genesis = db.fetch_chain_header(0).unwrap();
+        let mut header = BlockHeader::from_previous(genesis.header());
+        header.kernel_mmr_size = genesis.header().kernel_mmr_size + 1;
+        header.output_mmr_size = genesis.header().output_mmr_size + 1;
+        db.insert_valid_headers(vec![create_chain_header(header, &genesis.accumulated_data())])
+            .unwrap();
+
+        let service = KernelSyncPeerMock::default();
+        service
+            .sync_kernels
+            .set_response(Ok(vec![create_test_kernel(100.into(), 0).into()]))
+            .await;
+        let server = rpc::BaseNodeSyncRpcServer::new(service);
+        let protocol_name = server.as_protocol_name();
+        let mut mock_server = MockRpcServer::new(server, sync_peer_identity.clone());
+        mock_server.serve();
+        let sync_peer = mock_server
+            .create_connection(sync_peer_identity.to_peer(), protocol_name.into())
+            .await;
+
+        let prover = CryptoFactories::default().range_proof;
+        let err = HorizonStateSynchronization::new(&mut state_machine, sync_peer, 1, &prover)
+            .synchronize()
+            .await
+            .unwrap_err();
+
+        let calls = take_ban_calls(&mut state_machine, &connectivity_mock_state).await;
+        (err, calls)
+    }
+
+    #[tokio_macros::test]
+    async fn it_bans_a_sync_peer_that_sends_kernels_with_an_invalid_mmr_root() {
+        let sync_peer_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+        let (err, calls) =
+            sync_with_invalid_kernel_root(BaseNodeStateMachineConfig::default(), sync_peer_identity.clone()).await;
+
+        assert!(matches!(err, HorizonSyncError::InvalidMmrRoot {
+            mmr_tree: MmrTree::Kernel,
+            at_height: 1,
+            ..
+        }));
+        assert_eq!(calls.len(), 1);
+        let ban_duration = HorizonSyncConfig::default().short_term_peer_ban_duration;
+        assert_eq!(
+            calls[0],
+            format!(
+                "BanPeer({:?}, {:?}, {:?})",
+                sync_peer_identity.node_id(),
+                ban_duration,
+                err.to_string()
+            )
+        );
+    }
+
+    #[tokio_macros::test]
+    async fn it_does_not_ban_an_allowlisted_sync_peer() {
+        let sync_peer_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+        let mut config = BaseNodeStateMachineConfig::default();
+        config.block_sync_config.sync_peers = vec![sync_peer_identity.node_id().clone()];
+        let (err, calls) = sync_with_invalid_kernel_root(config, sync_peer_identity).await;
+
+        assert!(matches!(err, HorizonSyncError::InvalidMmrRoot { .. }));
+        assert!(calls.is_empty());
+    }
+}
@@ -40,6 +40,7 @@ use std::{
     ops::Deref,
 };
 use tari_common_types::chain_metadata::ChainMetadata;
+use tari_comms::peer_manager::{NodeId, PeerFlags};
 use tari_crypto::tari_utilities::epoch_time::EpochTime;
 use tokio::sync::broadcast;
 
@@ -182,7 +183,33 @@ impl Listening {
                     let local_tip_height = local.height_of_longest_chain();
                     // If we have configured sync peers, they are already filtered at this point
                     let sync_peers = if configured_sync_peers.is_empty() {
-                        select_sync_peers(local_tip_height, &best_metadata, &peer_metadata_list)
+                        let mut sync_peers = select_sync_peers(local_tip_height, &best_metadata, &peer_metadata_list);
+                        if is_deep_history_sync(local_tip_height, &sync_peers) {
+                            // Pruned peers may discard the blocks we need before we get to them
+                            let mut archival_peers = Vec::new();
+                            for peer in &sync_peers {
+                                if let Ok(p) = shared.peer_manager.find_by_node_id(&peer.node_id).await {
+                                    if p.features.is_archival() {
+                                        archival_peers.push(p.node_id);
+                                    }
+                                }
+                            }
+                            sync_peers = prefer_archival_peers(sync_peers, &archival_peers);
+                        }
+                        if self.is_synced {
+                            sync_peers
+                        } else {
+                            // Lean on trusted seed peers for initial sync
+                            let mut trusted_seeds = Vec::new();
+                            for peer in &sync_peers {
+                                if let Ok(p) = shared.peer_manager.find_by_node_id(&peer.node_id).await {
+                                    if p.flags.contains(PeerFlags::TRUSTED_SEED) {
+                                        trusted_seeds.push(p.node_id);
+                                    }
+                                }
+                            }
+                            prefer_trusted_seeds(sync_peers, &trusted_seeds)
+                        }
                     } else {
                         peer_metadata_list
                     };
@@ -262,6 +289,28 @@ fn select_sync_peers(
         .collect()
 }
 
+/// Orders the sync peers so that trusted seed peers are tried first. The order is otherwise preserved.
+fn prefer_trusted_seeds(mut sync_peers: Vec<PeerChainMetadata>, trusted_seeds: &[NodeId]) -> Vec<PeerChainMetadata> {
+    sync_peers.sort_by_key(|peer| !trusted_seeds.contains(&peer.node_id));
+    sync_peers
+}
+
+/// Returns true if the blocks required to catch up with the network reach back past the pruning horizon of a pruned
+/// sync peer, i.e. the peer may prune the requested blocks away before they are fetched.
+fn is_deep_history_sync(local_tip_height: u64, sync_peers: &[PeerChainMetadata]) -> bool {
+    sync_peers.iter().any(|peer| {
+        let metadata = &peer.chain_metadata;
+        metadata.is_pruned_node() && local_tip_height < metadata.horizon_block(metadata.height_of_longest_chain())
+    })
+}
+
+/// Orders the sync peers so that peers advertising archival features are tried first. The order is otherwise
+/// preserved.
+fn prefer_archival_peers(mut sync_peers: Vec<PeerChainMetadata>, archival_peers: &[NodeId]) -> Vec<PeerChainMetadata> {
+    sync_peers.sort_by_key(|peer| !archival_peers.contains(&peer.node_id));
+    sync_peers
+}
+
 /// Determine the best metadata from a set of metadata received from the network.
 fn best_metadata(metadata_list: &[PeerChainMetadata]) -> Option<&ChainMetadata> {
     // TODO: Use heuristics to weed out outliers / dishonest nodes.
@@ -344,7 +393,7 @@ fn determine_sync_mode(
 mod test {
     use super::*;
     use rand::rngs::OsRng;
-    use tari_comms::{peer_manager::NodeId, types::CommsPublicKey};
+    use tari_comms::types::CommsPublicKey;
     use tari_crypto::keys::PublicKey;
 
     fn random_node_id() -> NodeId {
@@ -435,6 +484,63 @@ mod test {
         sync_peers.iter().find(|p| p.node_id == node_id5).unwrap();
     }
 
+    #[test]
+    fn sync_peer_selection_prefers_trusted_seeds() {
+        let metadata = ChainMetadata::new(5000, vec![0, 1, 2, 3], 0, 0, 200000);
+        let node_ids = (0..4).map(|_| random_node_id()).collect::<Vec<_>>();
+        let sync_peers = node_ids
+            .iter()
+            .map(|node_id| PeerChainMetadata::new(node_id.clone(), metadata.clone()))
+            .collect::<Vec<_>>();
+
+        let trusted_seeds = vec![node_ids[2].clone(), node_ids[3].clone()];
+        let sync_peers = prefer_trusted_seeds(sync_peers, &trusted_seeds);
+        let ordered = sync_peers.into_iter().map(|p| p.node_id).collect::<Vec<_>>();
+        assert_eq!(ordered, vec![
+            node_ids[2].clone(),
+            node_ids[3].clone(),
+            node_ids[0].clone(),
+            node_ids[1].clone()
+        ]);
+
+        let sync_peers = node_ids
+            .iter()
+            .map(|node_id| PeerChainMetadata::new(node_id.clone(), metadata.clone()))
+            .collect::<Vec<_>>();
+        let sync_peers = prefer_trusted_seeds(sync_peers, &[]);
+        let ordered = sync_peers.into_iter().map(|p| p.node_id).collect::<Vec<_>>();
+        assert_eq!(ordered, node_ids);
+    }
+
+    #[test]
+    fn deep_history_sync_prefers_archival_peers() {
+        let network_tip_height = 5000;
+        let block_hash = vec![0, 1, 2, 3];
+        let archival_node_id = random_node_id();
+        let pruned_node_id = random_node_id();
+        let pruned_peer = PeerChainMetadata::new(
+            pruned_node_id.clone(),
+            ChainMetadata::new(network_tip_height, block_hash.clone(), 1000, 3000, 200000),
+        );
+        let archival_peer = PeerChainMetadata::new(
+            archival_node_id.clone(),
+            ChainMetadata::new(network_tip_height, block_hash, 0, 0, 200000),
+        );
+        let sync_peers = vec![pruned_peer, archival_peer];
+
+        // Within the pruned peer's horizon, so any peer will do
+        assert!(!is_deep_history_sync(4500, &sync_peers));
+        // Behind the pruned peer's horizon
+        let local_tip_height = 3500;
+        assert!(is_deep_history_sync(local_tip_height, &sync_peers));
+
+        let best_network_metadata = best_metadata(&sync_peers).unwrap().clone();
+        let sync_peers = select_sync_peers(local_tip_height, &best_network_metadata, &sync_peers);
+        let sync_peers = prefer_archival_peers(sync_peers, &[archival_node_id.clone()]);
+        let ordered = sync_peers.into_iter().map(|p| p.node_id).collect::<Vec<_>>();
+        assert_eq!(ordered, vec![archival_node_id, pruned_node_id]);
+    }
+
     #[test]
     fn sync_mode_selection() {
         let local = ChainMetadata::new(0, Vec::new(), 0, 0, 500_000);
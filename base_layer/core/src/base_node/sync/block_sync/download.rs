@@ -0,0 +1,307 @@
+// Copyright 2019, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use super::error::BlockSyncError;
+use crate::transactions::types::HashOutput;
+use futures::{stream::FuturesUnordered, Future, StreamExt};
+use log::*;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    mem,
+    time::Duration,
+};
+use tari_comms::peer_manager::NodeId;
+use tokio::{task, task::JoinHandle, time};
+
+const LOG_TARGET: &str = "c::bn::block_sync::download";
+
+/// A contiguous range of blocks to be downloaded from a single sync peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockChunk {
+    /// The position of this chunk in the download order
+    pub index: usize,
+    /// The hash of the block preceding the first block in this chunk
+    pub start_hash: HashOutput,
+    /// The hash of the last block in this chunk
+    pub end_hash: HashOutput,
+    /// The height of the first block in this chunk
+    pub start_height: u64,
+    /// The height of the last block in this chunk
+    pub end_height: u64,
+    /// The hashes of the blocks in this chunk in the synced header chain, in height order
+    pub block_hashes: Vec<HashOutput>,
+}
+
+impl BlockChunk {
+    /// The number of blocks in this chunk
+    pub fn num_blocks(&self) -> usize {
+        (self.end_height + 1).saturating_sub(self.start_height) as usize
+    }
+}
+
+type ChunkResult<T> = (BlockChunk, NodeId, Result<Vec<T>, BlockSyncError>);
+
+/// Downloads chunks of blocks from multiple sync peers in parallel and yields them in chunk order, so that blocks can
+/// be validated sequentially. A peer that fails to provide a chunk, or does not provide it within the stall timeout,
+/// is no longer used and its chunk is reassigned to another peer. Once no peers remain, the error from every failed peer
+/// is returned.
+pub struct ParallelBlockDownloader<F, T> {
+    fetch: F,
+    idle_peers: VecDeque<NodeId>,
+    queue: VecDeque<BlockChunk>,
+    in_flight: FuturesUnordered<JoinHandle<ChunkResult<T>>>,
+    completed: BTreeMap<usize, Vec<T>>,
+    next_index: usize,
+    parallelism: usize,
+    stall_timeout: Duration,
+    peer_errors: Vec<(NodeId, BlockSyncError)>,
+}
+
+impl<F, Fut, T> ParallelBlockDownloader<F, T>
+where
+    F: FnMut(NodeId, BlockChunk) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, BlockSyncError>> + Send + 'static,
+    T: Send + 'static,
+{
+    pub fn new(
+        peers: Vec<NodeId>,
+        chunks: Vec<BlockChunk>,
+        parallelism: usize,
+        stall_timeout: Duration,
+        fetch: F,
+    ) -> Self
+    {
+        Self {
+            fetch,
+            idle_peers: peers.into(),
+            queue: chunks.into(),
+            in_flight: FuturesUnordered::new(),
+            completed: BTreeMap::new(),
+            next_index: 0,
+            parallelism: parallelism.max(1),
+            stall_timeout,
+            peer_errors: Vec::new(),
+        }
+    }
+
+    /// Returns the next chunk of blocks in chunk order, waiting for it to be downloaded if necessary. None is returned
+    /// once all chunks have been yielded.
+    pub async fn next_chunk(&mut self) -> Option<Result<Vec<T>, BlockSyncError>> {
+        loop {
+            if let Some(blocks) = self.completed.remove(&self.next_index) {
+                self.next_index += 1;
+                self.dispatch();
+                return Some(Ok(blocks));
+            }
+
+            self.dispatch();
+            if self.in_flight.is_empty() {
+                if self.queue.is_empty() {
+                    return None;
+                }
+                let peer_errors = mem::take(&mut self.peer_errors);
+                if peer_errors.is_empty() {
+                    return Some(Err(BlockSyncError::NoSyncPeers));
+                }
+                return Some(Err(BlockSyncError::AllSyncPeersFailed(peer_errors)));
+            }
+
+            let (chunk, peer, result) = self
+                .in_flight
+                .next()
+                .await
+                .expect("in_flight is not empty")
+                .expect("block download task panicked");
+
+            match result {
+                Ok(blocks) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Downloaded blocks #{} - #{} from peer `{}`", chunk.start_height, chunk.end_height, peer
+                    );
+                    self.completed.insert(chunk.index, blocks);
+                    self.idle_peers.push_back(peer);
+                },
+                Err(err) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Failed to download blocks #{} - #{} from peer `{}`: {}. Reassigning to another peer.",
+                        chunk.start_height,
+                        chunk.end_height,
+                        peer,
+                        err
+                    );
+                    self.peer_errors.push((peer, err));
+                    // Keep the queue ordered so that the next chunk to be yielded is always dispatched first
+                    let pos = self
+                        .queue
+                        .iter()
+                        .position(|c| c.index > chunk.index)
+                        .unwrap_or_else(|| self.queue.len());
+                    self.queue.insert(pos, chunk);
+                },
+            }
+        }
+    }
+
+    fn dispatch(&mut self) {
+        // Limit the number of chunks held in memory while waiting for an earlier chunk. The next chunk to be yielded is
+        // always dispatched.
+        let max_buffered = self.parallelism * 2;
+        while self.in_flight.len() < self.parallelism {
+            let is_next = self.queue.front().map(|c| c.index == self.next_index).unwrap_or(false);
+            if !is_next && self.completed.len() + self.in_flight.len() >= max_buffered {
+                break;
+            }
+            let peer = match self.idle_peers.pop_front() {
+                Some(peer) => peer,
+                None => break,
+            };
+            let chunk = match self.queue.pop_front() {
+                Some(chunk) => chunk,
+                None => {
+                    self.idle_peers.push_front(peer);
+                    break;
+                },
+            };
+
+            let fut = (self.fetch)(peer.clone(), chunk.clone());
+            let stall_timeout = self.stall_timeout;
+            self.in_flight.push(task::spawn(async move {
+                let result = time::timeout(stall_timeout, fut)
+                    .await
+                    .unwrap_or_else(|_| Err(BlockSyncError::PeerStalled(peer.clone())));
+                (chunk, peer, result)
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::time::delay_for;
+
+    fn create_chunks(num_chunks: usize, chunk_size: u64) -> Vec<BlockChunk> {
+        (0..num_chunks)
+            .map(|index| {
+                let start_height = index as u64 * chunk_size + 1;
+                BlockChunk {
+                    index,
+                    start_hash: vec![index as u8],
+                    end_hash: vec![index as u8 + 1],
+                    start_height,
+                    end_height: start_height + chunk_size - 1,
+                    block_hashes: (start_height..start_height + chunk_size).map(|h| vec![h as u8]).collect(),
+                }
+            })
+            .collect()
+    }
+
+    #[tokio_macros::test_basic]
+    async fn it_yields_chunks_in_order() {
+        let peers = (0..3).map(|_| NodeId::new()).collect::<Vec<_>>();
+        let chunks = create_chunks(10, 5);
+        let slow_peer = peers[0].clone();
+        let mut downloader = ParallelBlockDownloader::new(
+            peers,
+            chunks,
+            3,
+            Duration::from_secs(10),
+            move |peer: NodeId, chunk: BlockChunk| {
+                let delay = if peer == slow_peer { 50 } else { 5 };
+                async move {
+                    delay_for(Duration::from_millis(delay)).await;
+                    let blocks = (chunk.start_height..=chunk.end_height)
+                        .map(|h| (h, peer.clone()))
+                        .collect();
+                    Result::<Vec<_>, BlockSyncError>::Ok(blocks)
+                }
+            },
+        );
+
+        let mut heights = Vec::new();
+        let mut used_peers = Vec::new();
+        while let Some(blocks) = downloader.next_chunk().await {
+            for (height, peer) in blocks.unwrap() {
+                heights.push(height);
+                if !used_peers.contains(&peer) {
+                    used_peers.push(peer);
+                }
+            }
+        }
+
+        assert_eq!(heights, (1..=50).collect::<Vec<_>>());
+        assert_eq!(used_peers.len(), 3);
+    }
+
+    #[tokio_macros::test_basic]
+    async fn it_reassigns_chunks_from_stalled_peers() {
+        let peers = (0..2).map(|_| NodeId::new()).collect::<Vec<_>>();
+        let stalled_peer = peers[0].clone();
+        let mut downloader = ParallelBlockDownloader::new(
+            peers,
+            create_chunks(4, 2),
+            2,
+            Duration::from_millis(100),
+            move |peer: NodeId, chunk: BlockChunk| {
+                let is_stalled = peer == stalled_peer;
+                async move {
+                    if is_stalled {
+                        delay_for(Duration::from_secs(60)).await;
+                    }
+                    Result::<_, BlockSyncError>::Ok((chunk.start_height..=chunk.end_height).collect::<Vec<_>>())
+                }
+            },
+        );
+
+        let mut heights = Vec::new();
+        while let Some(blocks) = downloader.next_chunk().await {
+            heights.extend(blocks.unwrap());
+        }
+        assert_eq!(heights, (1..=8).collect::<Vec<_>>());
+    }
+
+    #[tokio_macros::test_basic]
+    async fn it_fails_with_the_errors_from_all_peers_if_all_peers_fail() {
+        let peers = (0..3).map(|_| NodeId::new()).collect::<Vec<_>>();
+        let mut downloader = ParallelBlockDownloader::new(
+            peers.clone(),
+            create_chunks(2, 2),
+            2,
+            Duration::from_secs(10),
+            |_, _| async { Result::<Vec<u64>, _>::Err(BlockSyncError::NoSyncPeers) },
+        );
+
+        let err = downloader.next_chunk().await.unwrap().unwrap_err();
+        match err {
+            BlockSyncError::AllSyncPeersFailed(peer_errors) => {
+                let mut failed_peers = peer_errors.into_iter().map(|(peer, _)| peer).collect::<Vec<_>>();
+                failed_peers.sort();
+                let mut expected = peers;
+                expected.sort();
+                assert_eq!(failed_peers, expected);
+            },
+            err => panic!("Unexpected error {}", err),
+        }
+    }
+}
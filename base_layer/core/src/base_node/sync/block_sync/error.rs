@@ -23,6 +23,7 @@
 use crate::{chain_storage::ChainStorageError, proof_of_work::PowError, validation::ValidationError};
 use tari_comms::{
     connectivity::ConnectivityError,
+    peer_manager::NodeId,
     protocol::rpc::{RpcError, RpcStatus},
 };
 
@@ -38,14 +39,46 @@ pub enum BlockSyncError {
     ReceivedInvalidBlockBody(String),
     #[error("Peer sent a block that did not form a chain. Expected hash = {expected}, got = {got}")]
     PeerSentBlockThatDidNotFormAChain { expected: String, got: String },
+    #[error("Peer sent block `{got}` at height {height} that is not in the header chain. Expected `{expected}`")]
+    PeerSentUnexpectedBlock { height: u64, expected: String, got: String },
     #[error("Connectivity Error: {0}")]
     ConnectivityError(#[from] ConnectivityError),
     #[error("No sync peers available")]
     NoSyncPeers,
+    #[error("Sync peer `{0}` stalled while sending blocks")]
+    PeerStalled(NodeId),
     #[error("Error fetching PoW: {0}")]
     PowError(#[from] PowError),
     //#[error("Expected to find header at height {0} however the header did not exist")]
     // ExpectedHeaderNotFound(u64),
     #[error("Block validation failed: {0}")]
     ValidationError(#[from] ValidationError),
+    #[error("All sync peers have pruned the requested block bodies")]
+    AllSyncPeersPruned,
+    #[error("Failed to download blocks from all sync peers: {}", format_peer_errors(.0))]
+    AllSyncPeersFailed(Vec<(NodeId, BlockSyncError)>),
+}
+
+impl BlockSyncError {
+    /// Returns true if the peer responded that the requested block bodies have been pruned
+    pub fn is_pruned_away(&self) -> bool {
+        match self {
+            BlockSyncError::RpcRequestError(status) | BlockSyncError::RpcError(RpcError::RequestFailed(status)) => {
+                status.status_code().is_pruned_away()
+            },
+            BlockSyncError::AllSyncPeersPruned => true,
+            BlockSyncError::AllSyncPeersFailed(peer_errors) => {
+                !peer_errors.is_empty() && peer_errors.iter().all(|(_, err)| err.is_pruned_away())
+            },
+            _ => false,
+        }
+    }
+}
+
+fn format_peer_errors(peer_errors: &[(NodeId, BlockSyncError)]) -> String {
+    peer_errors
+        .iter()
+        .map(|(peer, err)| format!("`{}`: {}", peer, err))
+        .collect::<Vec<_>>()
+        .join(", ")
 }
@@ -20,19 +20,24 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use super::error::BlockSyncError;
+use super::{
+    download::{BlockChunk, ParallelBlockDownloader},
+    error::BlockSyncError,
+};
 use crate::{
-    base_node::sync::{hooks::Hooks, rpc},
+    base_node::sync::{hooks::Hooks, rpc, BlockSyncConfig},
     chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, ChainBlock},
-    proto::base_node::SyncBlocksRequest,
+    proto::base_node::{BlockBodyResponse, SyncBlocksRequest},
     tari_utilities::{hex::Hex, Hashable},
-    transactions::aggregated_body::AggregateBody,
+    transactions::{aggregated_body::AggregateBody, types::HashOutput},
     validation::CandidateBlockBodyValidation,
 };
 use futures::StreamExt;
 use log::*;
 use num_format::{Locale, ToFormattedString};
 use std::{
+    cmp,
+    collections::HashMap,
     convert::TryFrom,
     sync::Arc,
     time::{Duration, Instant},
@@ -46,26 +51,35 @@ use tokio::task;
 
 const LOG_TARGET: &str = "c::bn::block_sync";
 
+/// The number of blocks requested from a sync peer at a time
+const BLOCK_DOWNLOAD_CHUNK_SIZE: u64 = 50;
+
 pub struct BlockSynchronizer<B> {
+    config: BlockSyncConfig,
     db: AsyncBlockchainDb<B>,
     connectivity: ConnectivityRequester,
     sync_peer: Option<PeerConnection>,
+    candidate_peers: Vec<NodeId>,
     block_validator: Arc<dyn CandidateBlockBodyValidation<B>>,
     hooks: Hooks,
 }
 
 impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
     pub fn new(
+        config: BlockSyncConfig,
         db: AsyncBlockchainDb<B>,
         connectivity: ConnectivityRequester,
         sync_peer: Option<PeerConnection>,
+        candidate_peers: Vec<NodeId>,
         block_validator: Arc<dyn CandidateBlockBodyValidation<B>>,
     ) -> Self
     {
         Self {
+            config,
             db,
             connectivity,
             sync_peer,
+            candidate_peers,
             block_validator,
             hooks: Default::default(),
         }
@@ -82,46 +96,121 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
     }
 
     pub async fn synchronize(&mut self) -> Result<(), BlockSyncError> {
-        let peer_conn = self.get_next_sync_peer().await?;
-        let node_id = peer_conn.peer_node_id().clone();
+        let peer_conns = self.get_sync_peers().await?;
         info!(
             target: LOG_TARGET,
-            "Attempting to synchronize blocks with `{}`", node_id
+            "Attempting to synchronize blocks with {} peer(s): {}",
+            peer_conns.len(),
+            peer_conns
+                .iter()
+                .map(|conn| conn.peer_node_id().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
         );
-        self.attempt_block_sync(peer_conn).await?;
+        self.attempt_block_sync(peer_conns).await?;
 
         self.db.cleanup_orphans().await?;
         Ok(())
     }
 
-    async fn get_next_sync_peer(&mut self) -> Result<PeerConnection, BlockSyncError> {
-        match self.sync_peer {
-            Some(ref peer) => Ok(peer.clone()),
-            None => {
-                let mut peers = self
-                    .connectivity
-                    .select_connections(ConnectivitySelection::random_nodes(1, vec![]))
-                    .await?;
-                if peers.is_empty() {
-                    return Err(BlockSyncError::NoSyncPeers);
-                }
-                Ok(peers.remove(0))
-            },
+    /// Returns the sync peer provided by header sync, if any, along with additional connected peers up to the
+    /// configured maximum block download parallelism.
+    async fn get_sync_peers(&mut self) -> Result<Vec<PeerConnection>, BlockSyncError> {
+        let mut peers = self.sync_peer.iter().cloned().collect::<Vec<_>>();
+        let num_additional = self.config.max_block_download_parallelism.saturating_sub(peers.len());
+        if num_additional > 0 {
+            let exclude = peers.iter().map(|conn| conn.peer_node_id().clone()).collect();
+            let mut additional = self.select_additional_peers(num_additional, exclude).await?;
+            peers.append(&mut additional);
         }
+        if peers.is_empty() {
+            return Err(BlockSyncError::NoSyncPeers);
+        }
+        Ok(peers)
     }
 
-    async fn attempt_block_sync(&mut self, mut conn: PeerConnection) -> Result<(), BlockSyncError> {
-        let mut client = conn
-            .connect_rpc_using_builder(rpc::BaseNodeSyncRpcClient::builder().with_deadline(Duration::from_secs(60)))
-            .await?;
-        self.synchronize_blocks(conn.peer_node_id(), &mut client).await?;
+    /// Selects up to `num_peers` connected peers from the peers that header sync selected its sync peer from. Only
+    /// allowlisted peers are selected if `sync_peers` is configured.
+    async fn select_additional_peers(
+        &mut self,
+        num_peers: usize,
+        exclude: Vec<NodeId>,
+    ) -> Result<Vec<PeerConnection>, BlockSyncError>
+    {
+        let candidates = if self.candidate_peers.is_empty() {
+            &self.config.sync_peers
+        } else {
+            &self.candidate_peers
+        };
+        // Without candidates or allowlisted peers, header sync selects from all connected peers
+        if candidates.is_empty() {
+            let conns = self
+                .connectivity
+                .select_connections(ConnectivitySelection::random_nodes(num_peers, exclude))
+                .await?;
+            return Ok(conns);
+        }
+
+        let allowlist = &self.config.sync_peers;
+        let candidates = candidates
+            .iter()
+            .filter(|node_id| !exclude.contains(node_id))
+            .filter(|node_id| allowlist.is_empty() || allowlist.contains(node_id))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut conns = Vec::with_capacity(num_peers);
+        for node_id in candidates {
+            if conns.len() >= num_peers {
+                break;
+            }
+            if let Some(conn) = self.connectivity.get_connection(node_id).await? {
+                conns.push(conn);
+            }
+        }
+        Ok(conns)
+    }
+
+    async fn attempt_block_sync(&mut self, peer_conns: Vec<PeerConnection>) -> Result<(), BlockSyncError> {
+        let mut peers = Vec::with_capacity(peer_conns.len());
+        let mut clients = HashMap::with_capacity(peer_conns.len());
+        let mut peer_errors = Vec::new();
+        for mut conn in peer_conns {
+            match conn
+                .connect_rpc_using_builder(rpc::BaseNodeSyncRpcClient::builder().with_deadline(Duration::from_secs(60)))
+                .await
+            {
+                Ok(client) => {
+                    peers.push(conn.peer_node_id().clone());
+                    clients.insert(conn.peer_node_id().clone(), client);
+                },
+                Err(err) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Failed to connect to sync peer `{}`: {}",
+                        conn.peer_node_id(),
+                        err
+                    );
+                    peer_errors.push((conn.peer_node_id().clone(), err.into()));
+                },
+            }
+        }
+        if clients.is_empty() {
+            if peer_errors.is_empty() {
+                return Err(BlockSyncError::NoSyncPeers);
+            }
+            return Err(BlockSyncError::AllSyncPeersFailed(peer_errors));
+        }
+
+        self.synchronize_blocks(peers, clients).await?;
         Ok(())
     }
 
+    /// Downloads the block bodies from the given peers. Chunks are first assigned to peers in the given order, so the
+    /// sync peer provided by header sync is used first.
     async fn synchronize_blocks(
         &mut self,
-        peer: &NodeId,
-        client: &mut rpc::BaseNodeSyncRpcClient,
+        peers: Vec<NodeId>,
+        clients: HashMap<NodeId, rpc::BaseNodeSyncRpcClient>,
     ) -> Result<(), BlockSyncError>
     {
         let tip_header = self.db.fetch_last_header().await?;
@@ -142,99 +231,130 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
         let best_full_block_hash = chain_header.accumulated_data().hash.clone();
         debug!(
             target: LOG_TARGET,
-            "Starting block sync from peer `{}`. Current best block is #{} `{}`. Syncing to #{} ({}).",
-            peer,
+            "Starting block sync from {} peer(s). Current best block is #{} `{}`. Syncing to #{} ({}).",
+            peers.len(),
             best_height,
             best_full_block_hash.to_hex(),
             tip_height,
             tip_hash.to_hex()
         );
-        let request = SyncBlocksRequest {
-            start_hash: best_full_block_hash.clone(),
-            // To the tip!
-            end_hash: tip_hash.clone(),
-        };
 
-        let mut block_stream = client.sync_blocks(request).await?;
+        let chunks = self
+            .create_block_chunks(best_height, best_full_block_hash.clone(), tip_height)
+            .await?;
+        let clients = Arc::new(clients);
+        let connectivity = self.connectivity.clone();
+        let config = self.config.clone();
+        let mut downloader = ParallelBlockDownloader::new(
+            peers.clone(),
+            chunks,
+            self.config.max_block_download_parallelism,
+            self.config.block_download_stall_timeout,
+            move |peer, chunk| {
+                let client = clients.get(&peer).cloned();
+                let connectivity = connectivity.clone();
+                let config = config.clone();
+                async move {
+                    let result = download_chunk(client, &chunk).await;
+                    // The chunk is reassigned to another peer by the downloader
+                    if let Err(err @ BlockSyncError::PeerSentUnexpectedBlock { .. }) = &result {
+                        ban_peer(connectivity, &config, peer, err.to_string()).await?;
+                    }
+                    result
+                }
+            },
+        );
+
         let mut prev_hash = best_full_block_hash;
         let mut current_block = None;
-        while let Some(block) = block_stream.next().await {
-            let block = block?;
-
-            let header = self
-                .db
-                .fetch_chain_header_by_block_hash(block.hash.clone())
-                .await?
-                .ok_or_else(|| {
-                    BlockSyncError::ReceivedInvalidBlockBody("Peer sent hash for block header we do not have".into())
-                })?;
+        while let Some(blocks) = downloader.next_chunk().await {
+            // The downloader has already tried the chunk with every available peer, so if every peer had pruned the
+            // blocks there is no peer left that can provide them.
+            let blocks = blocks.map_err(|err| {
+                if err.is_pruned_away() {
+                    BlockSyncError::AllSyncPeersPruned
+                } else {
+                    err
+                }
+            })?;
+            for block in blocks {
+                let header = self
+                    .db
+                    .fetch_chain_header_by_block_hash(block.hash.clone())
+                    .await?
+                    .ok_or_else(|| {
+                        BlockSyncError::ReceivedInvalidBlockBody(
+                            "Peer sent hash for block header we do not have".into(),
+                        )
+                    })?;
 
-            let header_hash = header.hash().clone();
+                let header_hash = header.hash().clone();
 
-            if header.header().prev_hash != prev_hash {
-                return Err(BlockSyncError::PeerSentBlockThatDidNotFormAChain {
-                    expected: prev_hash.to_hex(),
-                    got: header.header().prev_hash.to_hex(),
-                });
-            }
+                if header.header().prev_hash != prev_hash {
+                    return Err(BlockSyncError::PeerSentBlockThatDidNotFormAChain {
+                        expected: prev_hash.to_hex(),
+                        got: header.header().prev_hash.to_hex(),
+                    });
+                }
 
-            let body = block
-                .body
-                .map(AggregateBody::try_from)
-                .ok_or_else(|| BlockSyncError::ReceivedInvalidBlockBody("Block body was empty".to_string()))?
-                .map_err(BlockSyncError::ReceivedInvalidBlockBody)?;
+                let body = block
+                    .body
+                    .map(AggregateBody::try_from)
+                    .ok_or_else(|| BlockSyncError::ReceivedInvalidBlockBody("Block body was empty".to_string()))?
+                    .map_err(BlockSyncError::ReceivedInvalidBlockBody)?;
 
-            prev_hash = header.hash().clone();
+                prev_hash = header.hash().clone();
 
-            debug!(
-                target: LOG_TARGET,
-                "Validating block body #{} (PoW = {}, {})",
-                header.height(),
-                header.header().pow_algo(),
-                body.to_counts_string(),
-            );
+                debug!(
+                    target: LOG_TARGET,
+                    "Validating block body #{} (PoW = {}, {})",
+                    header.height(),
+                    header.header().pow_algo(),
+                    body.to_counts_string(),
+                );
 
-            let timer = Instant::now();
-            let block = Arc::new(header.upgrade_to_chain_block(body));
-            self.validate_block(block.clone()).await?;
+                let timer = Instant::now();
+                let block = Arc::new(header.upgrade_to_chain_block(body));
+                self.validate_block(block.clone()).await?;
 
-            debug!(
-                target: LOG_TARGET,
-                "Validated in {:.0?}. Storing block body #{} (PoW = {}, {})",
-                timer.elapsed(),
-                block.header().height,
-                block.header().pow_algo(),
-                block.block().body.to_counts_string(),
-            );
+                debug!(
+                    target: LOG_TARGET,
+                    "Validated in {:.0?}. Storing block body #{} (PoW = {}, {})",
+                    timer.elapsed(),
+                    block.header().height,
+                    block.header().pow_algo(),
+                    block.block().body.to_counts_string(),
+                );
 
-            let timer = Instant::now();
-            self.db
-                .write_transaction()
-                .insert_block_body(block.clone())
-                .set_best_block(
-                    block.height(),
-                    header_hash,
-                    block.accumulated_data().total_accumulated_difficulty,
-                )
-                .commit()
-                .await?;
+                let timer = Instant::now();
+                self.db
+                    .write_transaction()
+                    .insert_block_body(block.clone())
+                    .set_best_block(
+                        block.height(),
+                        header_hash,
+                        block.accumulated_data().total_accumulated_difficulty,
+                    )
+                    .commit()
+                    .await?;
 
-            self.hooks
-                .call_on_progress_block_hooks(block.clone(), tip_height, &[peer.clone()]);
+                self.hooks
+                    .call_on_progress_block_hooks(block.clone(), tip_height, &peers);
 
-            debug!(
-                target: LOG_TARGET,
-                "Block body #{} added in {:.0?}, Tot_acc_diff {}, Monero {}, SHA3 {}",
-                block.height(),
-                timer.elapsed(),
-                block
-                    .accumulated_data()
-                    .total_accumulated_difficulty
-                    .to_formatted_string(&Locale::en),
-                block.accumulated_data().accumulated_monero_difficulty,
-                block.accumulated_data().accumulated_blake_difficulty,
-            );
-            current_block = Some(block);
+                debug!(
+                    target: LOG_TARGET,
+                    "Block body #{} added in {:.0?}, Tot_acc_diff {}, Monero {}, SHA3 {}",
+                    block.height(),
+                    timer.elapsed(),
+                    block
+                        .accumulated_data()
+                        .total_accumulated_difficulty
+                        .to_formatted_string(&Locale::en),
+                    block.accumulated_data().accumulated_monero_difficulty,
+                    block.accumulated_data().accumulated_blake_difficulty,
+                );
+                current_block = Some(block);
+            }
         }
 
         if let Some(block) = current_block {
@@ -252,11 +372,46 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
             self.hooks.call_on_complete_hooks(block);
         }
 
-        debug!(target: LOG_TARGET, "Completed block sync with peer `{}`", peer);
+        debug!(target: LOG_TARGET, "Completed block sync with {} peer(s)", peers.len());
 
         Ok(())
     }
 
+    /// Splits the blocks above the best block up to the tip into chunks to be downloaded from the sync peers.
+    async fn create_block_chunks(
+        &self,
+        best_height: u64,
+        best_hash: HashOutput,
+        tip_height: u64,
+    ) -> Result<Vec<BlockChunk>, BlockSyncError>
+    {
+        let mut chunks = Vec::new();
+        let mut start_hash = best_hash;
+        let mut start_height = best_height + 1;
+        while start_height <= tip_height {
+            let end_height = cmp::min(start_height + BLOCK_DOWNLOAD_CHUNK_SIZE - 1, tip_height);
+            let block_hashes = self
+                .db
+                .fetch_chain_headers(start_height..=end_height)
+                .await?
+                .into_iter()
+                .map(|header| header.hash().clone())
+                .collect::<Vec<_>>();
+            let end_hash = block_hashes.last().cloned().expect("chunk contains at least one block");
+            chunks.push(BlockChunk {
+                index: chunks.len(),
+                start_hash,
+                end_hash: end_hash.clone(),
+                start_height,
+                end_height,
+                block_hashes,
+            });
+            start_hash = end_hash;
+            start_height = end_height + 1;
+        }
+        Ok(chunks)
+    }
+
     async fn validate_block(&self, block: Arc<ChainBlock>) -> Result<(), BlockSyncError> {
         let validator = self.block_validator.clone();
 
@@ -270,3 +425,69 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
         .expect("block validator panicked")
     }
 }
+
+/// Downloads the blocks in the chunk from the peer. Every block must be the block at its height in the synced header
+/// chain.
+async fn download_chunk(
+    client: Option<rpc::BaseNodeSyncRpcClient>,
+    chunk: &BlockChunk,
+) -> Result<Vec<BlockBodyResponse>, BlockSyncError>
+{
+    let mut client = client.ok_or(BlockSyncError::NoSyncPeers)?;
+    let request = SyncBlocksRequest {
+        start_hash: chunk.start_hash.clone(),
+        end_hash: chunk.end_hash.clone(),
+    };
+    let mut block_stream = client.sync_blocks(request).await?;
+    let mut blocks = Vec::with_capacity(chunk.num_blocks());
+    while let Some(block) = block_stream.next().await {
+        let block = block?;
+        let height = chunk.start_height + blocks.len() as u64;
+        let expected = chunk.block_hashes.get(blocks.len()).ok_or_else(|| {
+            BlockSyncError::ReceivedInvalidBlockBody(format!(
+                "Peer sent more than {} block(s) for blocks #{} - #{}",
+                chunk.num_blocks(),
+                chunk.start_height,
+                chunk.end_height
+            ))
+        })?;
+        if block.hash != *expected {
+            return Err(BlockSyncError::PeerSentUnexpectedBlock {
+                height,
+                expected: expected.to_hex(),
+                got: block.hash.to_hex(),
+            });
+        }
+        blocks.push(block);
+    }
+
+    if blocks.len() != chunk.num_blocks() {
+        return Err(BlockSyncError::ReceivedInvalidBlockBody(format!(
+            "Peer sent {} block(s) for blocks #{} - #{}",
+            blocks.len(),
+            chunk.start_height,
+            chunk.end_height
+        )));
+    }
+    Ok(blocks)
+}
+
+/// Bans a sync peer for the configured ban period, unless it is allowlisted for sync
+async fn ban_peer(
+    mut connectivity: ConnectivityRequester,
+    config: &BlockSyncConfig,
+    node_id: NodeId,
+    reason: String,
+) -> Result<(), BlockSyncError>
+{
+    if config.sync_peers.contains(&node_id) {
+        debug!(
+            target: LOG_TARGET,
+            "Not banning peer that is allowlisted for sync. Ban reason = {}", reason
+        );
+        return Ok(());
+    }
+    warn!(target: LOG_TARGET, "Banned sync peer `{}` because {}", node_id, reason);
+    connectivity.ban_peer_until(node_id, config.ban_period, reason).await?;
+    Ok(())
+}
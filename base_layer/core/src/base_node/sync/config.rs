@@ -21,6 +21,7 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::time::Duration;
+use tari_common_types::types::BlockHash;
 use tari_comms::peer_manager::NodeId;
 
 #[derive(Debug, Clone)]
@@ -31,6 +32,10 @@ pub struct BlockSyncConfig {
     pub ban_period: Duration,
     pub short_ban_period: Duration,
     pub sync_peers: Vec<NodeId>,
+    /// The maximum number of sync peers that blocks are downloaded from in parallel during block sync
+    pub max_block_download_parallelism: usize,
+    /// The time a sync peer is given to provide a chunk of blocks before it is considered stalled
+    pub block_download_stall_timeout: Duration,
 }
 
 impl Default for BlockSyncConfig {
@@ -42,6 +47,25 @@ impl Default for BlockSyncConfig {
             ban_period: Duration::from_secs(30 * 60),
             short_ban_period: Duration::from_secs(60),
             sync_peers: Default::default(),
+            max_block_download_parallelism: 4,
+            block_download_stall_timeout: Duration::from_secs(60),
         }
     }
 }
+
+/// How thoroughly block bodies are validated during block sync.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationLevel {
+    /// Every synced block body is fully validated
+    Full,
+    /// Block bodies at or below the trusted block with this hash only have their MMR roots checked against their
+    /// headers. The expensive transaction validation (range proofs, signatures and the accounting balance) is skipped.
+    /// Blocks above the trusted block, or that are not its ancestors, are fully validated.
+    AssumeValidBelow(BlockHash),
+}
+
+impl Default for ValidationLevel {
+    fn default() -> Self {
+        ValidationLevel::Full
+    }
+}
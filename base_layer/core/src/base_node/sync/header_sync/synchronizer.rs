@@ -92,6 +92,10 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
         self.hooks.add_on_rewind_hook(hook);
     }
 
+    /// Synchronize headers with all candidate sync peers before any block bodies are downloaded. Each peer's headers
+    /// are validated (including PoW) and only committed if they form a chain with a higher accumulated difficulty
+    /// than the current header chain. The connection to the peer that provided the strongest chain is returned so
+    /// that block bodies are only downloaded for that chain.
     pub async fn synchronize(&mut self) -> Result<PeerConnection, BlockHeaderSyncError> {
         debug!(target: LOG_TARGET, "Starting header sync.",);
         let sync_peers = self.select_sync_peers().await?;
@@ -101,42 +105,73 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
             sync_peers.len()
         );
 
+        let mut best_sync_peer = None;
         for peer_conn in sync_peers {
             let node_id = peer_conn.peer_node_id().clone();
             debug!(
                 target: LOG_TARGET,
                 "Attempting to synchronize headers with `{}`", node_id
             );
+            let tip_hash_before = self.db.fetch_tip_header().await?.hash().clone();
             match self.attempt_sync(peer_conn.clone()).await {
-                Ok(()) => return Ok(peer_conn),
-                // Try another peer
-                Err(err @ BlockHeaderSyncError::NotInSync) => {
-                    debug!(target: LOG_TARGET, "{}", err);
-                },
-
-                Err(err @ BlockHeaderSyncError::RpcError(RpcError::HandshakeError(RpcHandshakeError::TimedOut))) => {
-                    debug!(target: LOG_TARGET, "{}", err);
-                    self.ban_peer_short(node_id, BanReason::RpcNegotiationTimedOut).await?;
-                },
-                Err(BlockHeaderSyncError::ValidationFailed(err)) => {
-                    debug!(target: LOG_TARGET, "Block header validation failed: {}", err);
-                    self.ban_peer_long(node_id, err.into()).await?;
-                },
-                Err(err @ BlockHeaderSyncError::InvalidBlockHeight { .. }) => {
-                    debug!(target: LOG_TARGET, "{}", err);
-                    self.ban_peer_long(node_id, BanReason::GeneralHeaderSyncFailure(err))
-                        .await?;
-                },
-                Err(err) => {
+                Ok(true) => {
                     debug!(
                         target: LOG_TARGET,
-                        "Failed to synchronize headers from peer `{}`: {}", node_id, err
+                        "Peer `{}` provided the strongest header chain so far", node_id
                     );
+                    best_sync_peer = Some(peer_conn);
+                },
+                Ok(false) => {
+                    if best_sync_peer.is_none() {
+                        best_sync_peer = Some(peer_conn);
+                    }
+                },
+                Err(err) => {
+                    // If some headers were committed before the peer failed, the previous best peer may not have the
+                    // blocks for the current header chain
+                    if *self.db.fetch_tip_header().await?.hash() != tip_hash_before {
+                        best_sync_peer = None;
+                    }
+                    self.handle_sync_error(node_id, err).await?;
                 },
             }
         }
 
-        Err(BlockHeaderSyncError::SyncFailedAllPeers)
+        best_sync_peer.ok_or(BlockHeaderSyncError::SyncFailedAllPeers)
+    }
+
+    async fn handle_sync_error(
+        &mut self,
+        node_id: NodeId,
+        err: BlockHeaderSyncError,
+    ) -> Result<(), BlockHeaderSyncError>
+    {
+        match err {
+            // Try another peer
+            err @ BlockHeaderSyncError::NotInSync | err @ BlockHeaderSyncError::WeakerChain => {
+                debug!(target: LOG_TARGET, "{}", err);
+            },
+            err @ BlockHeaderSyncError::RpcError(RpcError::HandshakeError(RpcHandshakeError::TimedOut)) => {
+                debug!(target: LOG_TARGET, "{}", err);
+                self.ban_peer_short(node_id, BanReason::RpcNegotiationTimedOut).await?;
+            },
+            BlockHeaderSyncError::ValidationFailed(err) => {
+                debug!(target: LOG_TARGET, "Block header validation failed: {}", err);
+                self.ban_peer_long(node_id, err.into()).await?;
+            },
+            err @ BlockHeaderSyncError::InvalidBlockHeight { .. } => {
+                debug!(target: LOG_TARGET, "{}", err);
+                self.ban_peer_long(node_id, BanReason::GeneralHeaderSyncFailure(err))
+                    .await?;
+            },
+            err => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Failed to synchronize headers from peer `{}`: {}", node_id, err
+                );
+            },
+        }
+        Ok(())
     }
 
     async fn wait_until_online(&mut self) -> Result<(), BlockHeaderSyncError> {
@@ -251,7 +286,9 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
         Ok(())
     }
 
-    async fn attempt_sync(&mut self, mut conn: PeerConnection) -> Result<(), BlockHeaderSyncError> {
+    /// Synchronize headers from the given peer. Returns true if the local header chain was switched to the peer's
+    /// chain, or false if the local node is already in sync with the peer.
+    async fn attempt_sync(&mut self, mut conn: PeerConnection) -> Result<bool, BlockHeaderSyncError> {
         let peer = conn.peer_node_id().clone();
         let mut client = conn.connect_rpc::<rpc::BaseNodeSyncRpcClient>().await?;
         let latency = client.get_last_request_latency().await?;
@@ -261,15 +298,23 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
             conn.peer_node_id(),
             latency.unwrap_or_default().as_millis()
         );
+        if let Some(latency) = latency {
+            if let Err(err) = self.connectivity.record_peer_latency(peer.clone(), latency).await {
+                warn!(
+                    target: LOG_TARGET,
+                    "Failed to record latency for peer `{}`: {}", peer, err
+                );
+            }
+        }
 
         let sync_status = self.determine_sync_status(&peer, &mut client).await?;
         match sync_status {
-            SyncStatus::InSync => Ok(()),
+            SyncStatus::InSync => Ok(false),
             // We're ahead of this peer, try another peer if possible
             SyncStatus::Ahead => Err(BlockHeaderSyncError::NotInSync),
             SyncStatus::Lagging(split_info) => {
                 self.synchronize_headers(&peer, &mut client, *split_info).await?;
-                Ok(())
+                Ok(true)
             },
         }
     }
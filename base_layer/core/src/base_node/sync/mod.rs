@@ -23,7 +23,7 @@
 #[cfg(feature = "base_node")]
 mod config;
 #[cfg(feature = "base_node")]
-pub use self::config::BlockSyncConfig;
+pub use self::config::{BlockSyncConfig, ValidationLevel};
 
 #[cfg(feature = "base_node")]
 mod block_sync;
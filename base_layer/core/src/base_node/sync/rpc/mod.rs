@@ -39,6 +39,8 @@ use crate::{
     proto::base_node::{
         FindChainSplitRequest,
         FindChainSplitResponse,
+        GetBlocksByHashRequest,
+        GetBlocksByHashResponse,
         SyncBlocksRequest,
         SyncHeadersRequest,
         SyncKernelsRequest,
@@ -89,6 +91,12 @@ pub trait BaseNodeSyncService: Send + Sync + 'static {
 
     #[rpc(method = 8)]
     async fn sync_utxos(&self, request: Request<SyncUtxosRequest>) -> Result<Streaming<SyncUtxosResponse>, RpcStatus>;
+
+    #[rpc(method = 9)]
+    async fn get_blocks_by_hash(
+        &self,
+        request: Request<GetBlocksByHashRequest>,
+    ) -> Result<Response<GetBlocksByHashResponse>, RpcStatus>;
 }
 
 #[cfg(feature = "base_node")]
@@ -22,13 +22,16 @@
 
 use crate::{
     base_node::sync::rpc::BaseNodeSyncService,
-    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, OrNotFound},
+    blocks::Block,
+    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, Optional, OrNotFound},
     crypto::tari_utilities::Hashable,
     iterators::NonOverlappingIntegerPairIter,
     proto,
     proto::base_node::{
         FindChainSplitRequest,
         FindChainSplitResponse,
+        GetBlocksByHashRequest,
+        GetBlocksByHashResponse,
         SyncBlocksRequest,
         SyncHeadersRequest,
         SyncKernelsRequest,
@@ -40,6 +43,7 @@ use crate::{
 use futures::{channel::mpsc, stream, SinkExt};
 use log::*;
 use std::{cmp, time::Instant};
+use tari_common_types::types::BlockHash;
 use tari_comms::protocol::rpc::{Request, Response, RpcStatus, Streaming};
 use tari_crypto::tari_utilities::hex::Hex;
 use tokio::task;
@@ -59,6 +63,30 @@ impl<B: BlockchainBackend + 'static> BaseNodeSyncRpcService<B> {
     fn db(&self) -> AsyncBlockchainDb<B> {
         self.db.clone()
     }
+
+    /// Fetch a block by hash from the main chain, falling back to the orphan pool. Main chain blocks that contain
+    /// pruned outputs cannot be returned in full, in which case a `PrunedAway` status is returned.
+    async fn fetch_block_or_orphan(&self, hash: BlockHash) -> Result<Option<Block>, RpcStatus> {
+        let db = self.db();
+        let maybe_block = db
+            .fetch_block_by_hash(hash.clone())
+            .await
+            .map_err(RpcStatus::log_internal_error(LOG_TARGET))?;
+
+        match maybe_block {
+            Some(block) if block.contains_pruned_txos() => Err(RpcStatus::pruned_away(format!(
+                "Block #{} `{}` has been pruned by this node",
+                block.header().height,
+                hash.to_hex()
+            ))),
+            Some(block) => Ok(Some(block.into())),
+            None => db
+                .fetch_orphan(hash)
+                .await
+                .optional()
+                .map_err(RpcStatus::log_internal_error(LOG_TARGET)),
+        }
+    }
 }
 
 #[tari_comms::async_trait]
@@ -85,7 +113,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeSyncService for BaseNodeSyncRpcServ
 
         let start = start_header.height + 1;
         if start < metadata.pruned_height() {
-            return Err(RpcStatus::bad_request(format!(
+            return Err(RpcStatus::pruned_away(format!(
                 "Requested full block body at height {}, however this node has an effective pruned height of {}",
                 start,
                 metadata.pruned_height()
@@ -469,6 +497,21 @@ impl<B: BlockchainBackend + 'static> BaseNodeSyncService for BaseNodeSyncRpcServ
                     .await
                     .map_err(RpcStatus::log_internal_error(LOG_TARGET))?;
                 let (mut prev_header, _) = prev_header.into_parts();
+                // A start index part way through a block (i.e. resuming an interrupted sync) streams the rest of that
+                // block
+                if prev_header.height > 0 && prev_header.output_mmr_size > self.request.start {
+                    prev_header = self
+                        .db
+                        .fetch_header(prev_header.height - 1)
+                        .await
+                        .map_err(RpcStatus::log_internal_error(LOG_TARGET))?
+                        .ok_or_else(|| {
+                            RpcStatus::general(format!(
+                                "Potential data consistency issue: header {} not found",
+                                prev_header.height - 1
+                            ))
+                        })?;
+                }
 
                 if prev_header.height > end_header.height {
                     return Err(RpcStatus::bad_request("start index is greater than end index"));
@@ -590,4 +633,49 @@ impl<B: BlockchainBackend + 'static> BaseNodeSyncService for BaseNodeSyncRpcServ
 
         Ok(Streaming::new(rx))
     }
+
+    async fn get_blocks_by_hash(
+        &self,
+        request: Request<GetBlocksByHashRequest>,
+    ) -> Result<Response<GetBlocksByHashResponse>, RpcStatus>
+    {
+        const MAX_ALLOWED_BLOCK_HASHES: usize = 100;
+
+        let peer = request.context().peer_node_id().clone();
+        let message = request.into_message();
+        if message.hashes.len() > MAX_ALLOWED_BLOCK_HASHES {
+            return Err(RpcStatus::bad_request(format!(
+                "Cannot query more than {} block hashes",
+                MAX_ALLOWED_BLOCK_HASHES,
+            )));
+        }
+
+        let mut blocks = Vec::with_capacity(message.hashes.len());
+        let mut missing_hashes = Vec::new();
+        let mut pruned_hashes = Vec::new();
+        for hash in message.hashes {
+            match self.fetch_block_or_orphan(hash.clone()).await {
+                Ok(Some(block)) => blocks.push(block.into()),
+                Ok(None) => missing_hashes.push(hash),
+                // A pruned block does not fail the rest of the batch
+                Err(status) if status.status_code().is_pruned_away() => pruned_hashes.push(hash),
+                Err(status) => return Err(status),
+            }
+        }
+
+        debug!(
+            target: LOG_TARGET,
+            "Sending {} block(s) to peer `{}` ({} hash(es) not found, {} hash(es) pruned)",
+            blocks.len(),
+            peer,
+            missing_hashes.len(),
+            pruned_hashes.len()
+        );
+
+        Ok(Response::new(GetBlocksByHashResponse {
+            blocks,
+            missing_hashes,
+            pruned_hashes,
+        }))
+    }
 }
@@ -24,7 +24,7 @@
 // Version 2.0, available at http://www.apache.org/licenses/LICENSE-2.0.
 
 use crate::{
-    blocks::BlockHeader,
+    blocks::{BlockHeader, CompactBlock},
     chain_storage::MmrTree,
     consensus::ConsensusConstants,
     proof_of_work::ProofOfWork,
@@ -250,11 +250,17 @@ impl Hashable for Block {
 //---------------------------------- NewBlock --------------------------------------------//
 pub struct NewBlock {
     pub block_hash: BlockHash,
+    /// The compact representation of the block, allowing peers to reconstruct the block from their mempool instead
+    /// of requesting the full block. Peers that do not support compact blocks only announce the block hash.
+    pub compact_block: Option<CompactBlock>,
 }
 
 impl NewBlock {
     pub fn new(block_hash: BlockHash) -> Self {
-        Self { block_hash }
+        Self {
+            block_hash,
+            compact_block: None,
+        }
     }
 }
 
@@ -262,6 +268,7 @@ impl From<&Block> for NewBlock {
     fn from(block: &Block) -> Self {
         Self {
             block_hash: block.hash(),
+            compact_block: Some(CompactBlock::from(block)),
         }
     }
 }
@@ -0,0 +1,228 @@
+// Copyright 2019, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    blocks::{Block, BlockHeader},
+    transactions::{
+        aggregated_body::AggregateBody,
+        transaction::{KernelFeatures, Transaction, TransactionKernel},
+        types::HashDigest,
+    },
+};
+use digest::Digest;
+use std::{collections::HashSet, convert::TryInto, sync::Arc};
+use tari_crypto::tari_utilities::Hashable;
+use thiserror::Error;
+
+/// The maximum number of short ids that may be requested from a peer in a single request. A compact block that is
+/// missing more transactions than this is requested in full.
+pub const MAX_SHORT_ID_REQUEST_SIZE: usize = 1000;
+
+/// A short identifier for a transaction in a compact block, derived from the hash of one of its kernels.
+pub type ShortTxId = u64;
+
+/// Returns the short transaction id for the given kernel in the block with the given hash. This is the first 8 bytes
+/// of the kernel hash salted with the block hash, so that colliding short ids cannot be prepared ahead of the block.
+pub fn short_tx_id(block_hash: &[u8], kernel: &TransactionKernel) -> ShortTxId {
+    let hash = HashDigest::new().chain(block_hash).chain(kernel.hash()).result();
+    u64::from_le_bytes(hash[..8].try_into().expect("hash is at least 8 bytes"))
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum CompactBlockError {
+    #[error("{} transaction(s) required to reconstruct the block are missing", .0.len())]
+    MissingTransactions(Vec<ShortTxId>),
+}
+
+/// A compact representation of a block used for block propagation. Peers are expected to already have most of the
+/// block's transactions in their mempool, so only the header, a short id for each non-coinbase kernel and the
+/// coinbase outputs and kernels are sent. The full block is reconstructed from the mempool and any missing
+/// transactions are requested from the announcing peer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompactBlock {
+    pub header: BlockHeader,
+    pub short_ids: Vec<ShortTxId>,
+    pub coinbase: AggregateBody,
+}
+
+impl CompactBlock {
+    /// Returns the short ids in this compact block that are not covered by any of the provided transactions.
+    pub fn missing_short_ids(&self, transactions: &[Arc<Transaction>]) -> Vec<ShortTxId> {
+        self.missing_from(&self.matching_transactions(transactions))
+    }
+
+    /// Reconstructs the full block from the provided transactions. Transactions that contain a kernel that is not
+    /// part of this block are ignored. If any short id is not covered by the provided transactions,
+    /// `CompactBlockError::MissingTransactions` is returned with the missing short ids.
+    pub fn reconstruct(&self, transactions: &[Arc<Transaction>]) -> Result<Block, CompactBlockError> {
+        let matching = self.matching_transactions(transactions);
+        let missing = self.missing_from(&matching);
+        if !missing.is_empty() {
+            return Err(CompactBlockError::MissingTransactions(missing));
+        }
+
+        let mut body = self.coinbase.clone();
+        let mut added = HashSet::new();
+        for (tx, ids) in matching {
+            if ids.iter().any(|id| added.contains(id)) {
+                continue;
+            }
+            added.extend(ids);
+            let (mut inputs, mut outputs, mut kernels) = tx.body.clone().dissolve();
+            body.add_inputs(&mut inputs);
+            body.add_outputs(&mut outputs);
+            body.add_kernels(&mut kernels);
+        }
+        body.do_cut_through();
+        body.sort();
+
+        Ok(Block::new(self.header.clone(), body))
+    }
+
+    fn missing_from(&self, matching: &[(&Arc<Transaction>, Vec<ShortTxId>)]) -> Vec<ShortTxId> {
+        let found = matching.iter().flat_map(|(_, ids)| ids).collect::<HashSet<_>>();
+        self.short_ids
+            .iter()
+            .filter(|id| !found.contains(id))
+            .copied()
+            .collect()
+    }
+
+    /// Returns the transactions whose kernels are all part of this block, along with their short ids.
+    fn matching_transactions<'a>(
+        &self,
+        transactions: &'a [Arc<Transaction>],
+    ) -> Vec<(&'a Arc<Transaction>, Vec<ShortTxId>)>
+    {
+        let block_hash = self.hash();
+        let short_ids = self.short_ids.iter().collect::<HashSet<_>>();
+        transactions
+            .iter()
+            .filter_map(|tx| {
+                let ids = tx
+                    .body
+                    .kernels()
+                    .iter()
+                    .map(|kernel| short_tx_id(&block_hash, kernel))
+                    .collect::<Vec<_>>();
+                if !ids.is_empty() && ids.iter().all(|id| short_ids.contains(id)) {
+                    Some((tx, ids))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+impl From<&Block> for CompactBlock {
+    fn from(block: &Block) -> Self {
+        let block_hash = block.hash();
+        let (short_ids, coinbase_kernels) = block
+            .body
+            .kernels()
+            .iter()
+            .partition::<Vec<_>, _>(|kernel| !kernel.features.contains(KernelFeatures::COINBASE_KERNEL));
+        let coinbase_outputs = block
+            .body
+            .outputs()
+            .iter()
+            .filter(|o| o.is_coinbase())
+            .cloned()
+            .collect();
+
+        Self {
+            header: block.header.clone(),
+            short_ids: short_ids
+                .into_iter()
+                .map(|kernel| short_tx_id(&block_hash, kernel))
+                .collect(),
+            coinbase: AggregateBody::new(
+                Vec::new(),
+                coinbase_outputs,
+                coinbase_kernels.into_iter().cloned().collect(),
+            ),
+        }
+    }
+}
+
+impl Hashable for CompactBlock {
+    fn hash(&self) -> Vec<u8> {
+        self.header.hash()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{blocks::BlockBuilder, transactions::helpers::create_tx};
+
+    fn create_block(transactions: &[Arc<Transaction>]) -> Block {
+        BlockBuilder::new(1)
+            .with_transactions(transactions.iter().map(|tx| (**tx).clone()).collect())
+            .build()
+    }
+
+    #[test]
+    fn it_reconstructs_the_block_from_transactions() {
+        let txs = (0..3)
+            .map(|_| Arc::new(create_tx(5000.into(), 15.into(), 1, 2, 1, 2).0))
+            .collect::<Vec<_>>();
+        let block = create_block(&txs);
+        let compact_block = CompactBlock::from(&block);
+        assert_eq!(compact_block.short_ids.len(), 3);
+
+        let (unrelated, _, _) = create_tx(5000.into(), 20.into(), 1, 1, 1, 1);
+        let mut mempool_txs = txs.iter().rev().cloned().collect::<Vec<_>>();
+        mempool_txs.push(Arc::new(unrelated));
+
+        assert!(compact_block.missing_short_ids(&mempool_txs).is_empty());
+        assert_eq!(compact_block.reconstruct(&mempool_txs).unwrap(), block);
+    }
+
+    #[test]
+    fn it_reports_missing_transactions() {
+        let txs = (0..3)
+            .map(|_| Arc::new(create_tx(5000.into(), 15.into(), 1, 2, 1, 2).0))
+            .collect::<Vec<_>>();
+        let compact_block = CompactBlock::from(&create_block(&txs));
+
+        let err = compact_block.reconstruct(&txs[1..]).unwrap_err();
+        assert_eq!(
+            err,
+            CompactBlockError::MissingTransactions(vec![short_tx_id(&compact_block.hash(), &txs[0].body.kernels()[0])])
+        );
+    }
+
+    #[test]
+    fn it_salts_short_ids_with_the_block_hash() {
+        let txs = vec![Arc::new(create_tx(5000.into(), 15.into(), 1, 2, 1, 2).0)];
+        let block = create_block(&txs);
+        let mut other_block = block.clone();
+        other_block.header.nonce += 1;
+
+        let compact_block = CompactBlock::from(&block);
+        let other_compact_block = CompactBlock::from(&other_block);
+        assert_ne!(compact_block.short_ids, other_compact_block.short_ids);
+        assert_eq!(other_compact_block.reconstruct(&txs).unwrap(), other_block);
+    }
+}
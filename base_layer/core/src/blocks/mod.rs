@@ -24,6 +24,8 @@
 mod block;
 #[cfg(any(feature = "base_node", feature = "base_node_proto"))]
 pub mod block_header;
+#[cfg(feature = "base_node")]
+mod compact_block;
 
 #[cfg(feature = "base_node")]
 pub mod genesis_block;
@@ -36,6 +38,8 @@ pub use block::{Block, BlockBuilder, BlockValidationError, NewBlock};
 #[cfg(any(feature = "base_node", feature = "base_node_proto"))]
 pub use block_header::{BlockHeader, BlockHeaderValidationError};
 #[cfg(feature = "base_node")]
+pub use compact_block::{short_tx_id, CompactBlock, CompactBlockError, ShortTxId, MAX_SHORT_ID_REQUEST_SIZE};
+#[cfg(feature = "base_node")]
 pub use new_block_template::NewBlockTemplate;
 #[cfg(feature = "base_node")]
 pub use new_blockheader_template::NewBlockHeaderTemplate;
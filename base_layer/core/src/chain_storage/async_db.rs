@@ -34,6 +34,7 @@ use crate::{
         DbTransaction,
         HistoricalBlock,
         HorizonData,
+        HorizonSyncProgress,
         MmrTree,
         PrunedOutput,
         TargetDifficulties,
@@ -137,16 +138,22 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
 
     make_async_fn!(fetch_horizon_data() -> Option<HorizonData>, "fetch_horizon_data");
 
+    make_async_fn!(fetch_horizon_sync_progress() -> Option<HorizonSyncProgress>, "fetch_horizon_sync_progress");
+
     //---------------------------------- TXO --------------------------------------------//
     make_async_fn!(fetch_utxo(hash: HashOutput) -> Option<TransactionOutput>, "fetch_utxo");
 
     make_async_fn!(fetch_utxos(hashes: Vec<HashOutput>, is_spent_as_of: Option<HashOutput>) -> Vec<Option<(TransactionOutput, bool)>>, "fetch_utxos");
 
+    make_async_fn!(fetch_outputs_in_block(header_hash: HashOutput) -> Vec<PrunedOutput>, "fetch_outputs_in_block");
+
     make_async_fn!(fetch_utxos_by_mmr_position(start: u64, end: u64, end_header_hash: HashOutput) -> (Vec<PrunedOutput>, Bitmap), "fetch_utxos_by_mmr_position");
 
     //---------------------------------- Kernel --------------------------------------------//
     make_async_fn!(fetch_kernel_by_excess_sig(excess_sig: Signature) -> Option<(TransactionKernel, HashOutput)>, "fetch_kernel_by_excess_sig");
 
+    make_async_fn!(fetch_kernels_in_block(header_hash: HashOutput) -> Vec<TransactionKernel>, "fetch_kernels_in_block");
+
     make_async_fn!(fetch_kernels_by_mmr_position(start: u64, end: u64) -> Vec<TransactionKernel>, "fetch_kernels_by_mmr_position");
 
     //---------------------------------- MMR --------------------------------------------//
@@ -259,6 +266,11 @@ impl<'a, B: BlockchainBackend + 'static> AsyncDbTransaction<'a, B> {
         self
     }
 
+    pub fn set_horizon_sync_progress(&mut self, progress: Option<HorizonSyncProgress>) -> &mut Self {
+        self.transaction.set_horizon_sync_progress(progress);
+        self
+    }
+
     pub fn insert_kernel_via_horizon_sync(
         &mut self,
         kernel: TransactionKernel,
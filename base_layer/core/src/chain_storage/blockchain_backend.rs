@@ -11,6 +11,7 @@ use crate::{
         DbTransaction,
         DbValue,
         HorizonData,
+        HorizonSyncProgress,
         MmrTree,
     },
     transactions::{
@@ -148,4 +149,7 @@ pub trait BlockchainBackend: Send + Sync {
     fn fetch_monero_seed_first_seen_height(&self, seed: &str) -> Result<u64, ChainStorageError>;
 
     fn fetch_horizon_data(&self) -> Result<Option<HorizonData>, ChainStorageError>;
+
+    /// Fetches the position reached by an interrupted horizon sync, if any
+    fn fetch_horizon_sync_progress(&self) -> Result<Option<HorizonSyncProgress>, ChainStorageError>;
 }
@@ -31,12 +31,15 @@ use crate::{
         db_transaction::{DbKey, DbTransaction, DbValue},
         error::ChainStorageError,
         pruned_output::PrunedOutput,
+        snapshot,
         BlockAddResult,
         BlockchainBackend,
         ChainBlock,
         ChainHeader,
         HistoricalBlock,
         HorizonData,
+        HorizonSyncProgress,
+        LMDBDatabase,
         MmrTree,
         Optional,
         OrNotFound,
@@ -48,9 +51,19 @@ use crate::{
     tari_utilities::epoch_time::EpochTime,
     transactions::{
         transaction::{TransactionKernel, TransactionOutput},
-        types::{Commitment, HashDigest, HashOutput, Signature},
+        types::{Commitment, HashDigest, HashOutput, RangeProofService, Signature},
+    },
+    validation::{
+        helpers::validate_genesis_block,
+        DifficultyCalculator,
+        FinalHorizonStateValidation,
+        HeaderValidation,
+        OrphanValidation,
+        PostOrphanBodyValidation,
+        ValidationError,
+        ValidationMetrics,
+        ValidationMetricsSnapshot,
     },
-    validation::{DifficultyCalculator, HeaderValidation, OrphanValidation, PostOrphanBodyValidation, ValidationError},
 };
 use croaring::Bitmap;
 use log::*;
@@ -60,6 +73,7 @@ use std::{
     collections::VecDeque,
     mem,
     ops::Bound,
+    path::Path,
     sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
     time::Instant,
 };
@@ -166,6 +180,7 @@ pub struct BlockchainDatabase<B> {
     config: BlockchainDatabaseConfig,
     consensus_manager: ConsensusManager,
     difficulty_calculator: Arc<DifficultyCalculator>,
+    validation_metrics: ValidationMetrics,
 }
 
 #[allow(clippy::ptr_arg)]
@@ -190,12 +205,15 @@ where B: BlockchainBackend
             config,
             consensus_manager,
             difficulty_calculator: Arc::new(difficulty_calculator),
+            validation_metrics: ValidationMetrics::new(),
         };
         if is_empty {
             info!(target: LOG_TARGET, "Blockchain db is empty. Adding genesis block.");
             let genesis_block = blockchain_db.consensus_manager.get_genesis_block();
             blockchain_db.insert_block(Arc::new(genesis_block))?;
             blockchain_db.store_pruning_horizon(config.pruning_horizon)?;
+        } else {
+            blockchain_db.check_genesis_block()?;
         }
         if cleanup_orphans_at_startup {
             match blockchain_db.cleanup_all_orphans() {
@@ -218,6 +236,39 @@ where B: BlockchainBackend
         Ok(blockchain_db)
     }
 
+    /// Checks that the block at height 0 of a non-empty database is the genesis block given by the consensus rules,
+    /// which fails if the database was created for another network.
+    fn check_genesis_block(&self) -> Result<(), ChainStorageError> {
+        let db = self.db_read_access()?;
+        let block = fetch_block(&*db, 0)?;
+        if block.contains_pruned_txos() {
+            // The pruned outputs of a pruned genesis block cannot be compared, but they are committed to by the header
+            let genesis_block = self.consensus_manager.get_genesis_block();
+            if block.hash() != genesis_block.hash() {
+                warn!(
+                    target: LOG_TARGET,
+                    "Stored block {} does not match the genesis block {}",
+                    block.hash().to_hex(),
+                    genesis_block.hash().to_hex()
+                );
+                return Err(ValidationError::InvalidGenesisBlock.into());
+            }
+            return Ok(());
+        }
+        validate_genesis_block(block.block(), &self.consensus_manager)?;
+        Ok(())
+    }
+
+    /// Returns the consensus rules that this database was created with.
+    pub fn rules(&self) -> &ConsensusManager {
+        &self.consensus_manager
+    }
+
+    /// Returns the number of blocks rejected by validation, by `ValidationError` variant
+    pub fn validation_metrics(&self) -> ValidationMetricsSnapshot {
+        self.validation_metrics.snapshot()
+    }
+
     /// Returns a reference to the consensus cosntants at the current height
     pub fn consensus_constants(&self) -> Result<&ConsensusConstants, ChainStorageError> {
         let height = self.get_height()?;
@@ -338,6 +389,12 @@ where B: BlockchainBackend
         db.fetch_kernel_by_excess_sig(&excess_sig)
     }
 
+    /// Returns the kernels that have been stored for the block with the given header hash, in MMR order
+    pub fn fetch_kernels_in_block(&self, header_hash: HashOutput) -> Result<Vec<TransactionKernel>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_kernels_in_block(&header_hash)
+    }
+
     pub fn fetch_kernels_by_mmr_position(
         &self,
         start: u64,
@@ -348,6 +405,12 @@ where B: BlockchainBackend
         db.fetch_kernels_by_mmr_position(start, end)
     }
 
+    /// Returns the outputs that have been stored for the block with the given header hash, in MMR order
+    pub fn fetch_outputs_in_block(&self, header_hash: HashOutput) -> Result<Vec<PrunedOutput>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_outputs_in_block(&header_hash)
+    }
+
     pub fn fetch_utxos_by_mmr_position(
         &self,
         start: u64,
@@ -445,8 +508,10 @@ where B: BlockchainBackend
                     field: "start_hash".to_string(),
                     value: start_hash.to_hex(),
                 })?;
-        let constants = self.consensus_manager.consensus_constants(start_header.height);
-        let timestamp_window = constants.get_median_timestamp_count();
+        // The same window as `fetch_median_timestamp_window` for the next block: the start header and the
+        // `median_timestamp_window` headers preceding it
+        let constants = self.consensus_manager.consensus_constants(start_header.height + 1);
+        let timestamp_window = constants.median_timestamp_window();
         let start_window = start_header.height.saturating_sub(timestamp_window as u64);
 
         let timestamps = self
@@ -455,7 +520,7 @@ where B: BlockchainBackend
             .map(|h| h.timestamp)
             .collect::<Vec<_>>();
 
-        let mut rolling = RollingVec::new(timestamp_window);
+        let mut rolling = RollingVec::new(timestamp_window + 1);
         rolling.extend(timestamps);
         Ok(rolling)
     }
@@ -730,6 +795,7 @@ where B: BlockchainBackend
                 block.hash().to_hex(),
                 e.to_string()
             );
+            self.validation_metrics.record_rejection(&e);
             return Err(e.into());
         }
 
@@ -746,7 +812,13 @@ where B: BlockchainBackend
             self.consensus_manager.chain_strength_comparer(),
             &self.difficulty_calculator,
             block,
-        )?;
+        )
+        .map_err(|err| {
+            if let ChainStorageError::ValidationError { source } = &err {
+                self.validation_metrics.record_rejection(source);
+            }
+            err
+        })?;
 
         if block_add_result.was_chain_modified() {
             // If blocks were added and the node is in pruned mode, perform pruning
@@ -899,6 +971,56 @@ where B: BlockchainBackend
         let db = self.db_read_access()?;
         db.fetch_horizon_data()
     }
+
+    pub fn fetch_horizon_sync_progress(&self) -> Result<Option<HorizonSyncProgress>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_horizon_sync_progress()
+    }
+
+    /// Writes a verifiable snapshot of the headers, kernels and UTXO set up to and including `height` to `path`.
+    pub fn export_snapshot<P: AsRef<Path>>(&self, height: u64, path: P) -> Result<(), ChainStorageError> {
+        let db = self.db_read_access()?;
+        snapshot::export_snapshot(&*db, height, path)
+    }
+
+    /// Bootstraps an empty database from a snapshot created by [BlockchainDatabase::export_snapshot]. The MMR roots
+    /// of every block are verified against the header chain before being committed. Returns the new chain tip.
+    pub fn import_snapshot<P: AsRef<Path>>(
+        &self,
+        path: P,
+        final_state_validator: &dyn FinalHorizonStateValidation<B>,
+        rangeproof_service: &RangeProofService,
+    ) -> Result<ChainHeader, ChainStorageError>
+    {
+        let mut db = self.db_write_access()?;
+        snapshot::import_snapshot(
+            &mut *db,
+            &*self.validators.header,
+            &self.difficulty_calculator,
+            final_state_validator,
+            rangeproof_service,
+            path,
+        )
+    }
+}
+
+impl BlockchainDatabase<LMDBDatabase> {
+    /// Returns the id of the last committed write transaction, which changes whenever the database is written to
+    pub fn last_txn_id(&self) -> Result<usize, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.last_txn_id()
+    }
+
+    /// Compacts the LMDB environment, reclaiming free pages on disk. The compacted copy is made without holding the
+    /// database lock, so blocks can still be added in the meantime. The write lock is only held to swap in the copy,
+    /// and if a write was committed while the copy was being made, the copy is discarded. Returns true if the database
+    /// was compacted.
+    pub fn compact(&self) -> Result<bool, ChainStorageError> {
+        let handle = self.db_read_access()?.compaction_handle()?;
+        let copy = handle.create_compacted_copy()?;
+        let mut db = self.db_write_access()?;
+        db.replace_with_compacted_copy(copy)
+    }
 }
 
 fn unexpected_result<T>(req: DbKey, res: DbValue) -> Result<T, ChainStorageError> {
@@ -1910,6 +2032,7 @@ impl<T> Clone for BlockchainDatabase<T> {
             config: self.config,
             consensus_manager: self.consensus_manager.clone(),
             difficulty_calculator: self.difficulty_calculator.clone(),
+            validation_metrics: self.validation_metrics.clone(),
         }
     }
 }
@@ -1946,7 +2069,12 @@ mod test {
         },
         proof_of_work::AchievedTargetDifficulty,
         test_helpers::{
-            blockchain::{create_new_blockchain, create_test_blockchain_db, TempDatabase},
+            blockchain::{
+                create_new_blockchain,
+                create_store_with_consensus_and_validators,
+                create_test_blockchain_db,
+                TempDatabase,
+            },
             create_block,
             mine_to_difficulty,
         },
@@ -1985,6 +2113,26 @@ mod test {
         }
     }
 
+    #[test]
+    fn it_records_validation_rejections_by_variant() {
+        let rules = ConsensusManagerBuilder::new(Network::LocalNet).build();
+        let validators = Validators::new(
+            MockValidator::new(true),
+            MockValidator::new(true),
+            MockValidator::new(false),
+        );
+        let db = create_store_with_consensus_and_validators(rules, validators);
+        let genesis = db.fetch_block(0).unwrap().try_into_chain_block().map(Arc::new).unwrap();
+        let (_, chain) = create_chained_blocks(&[("A->GB", 1, 120)], genesis);
+        let err = db.add_block(chain.get("A").unwrap().to_arc_block()).unwrap_err();
+        assert!(matches!(err, ChainStorageError::ValidationError {
+            source: ValidationError::CustomError(_)
+        }));
+        let metrics = db.validation_metrics();
+        assert_eq!(metrics.rejection_count("CustomError"), 1);
+        assert_eq!(metrics.total_rejections(), 1);
+    }
+
     mod get_orphan_link_main_chain {
         use super::*;
 
@@ -21,7 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 use crate::{
     blocks::{Block, BlockHeader},
-    chain_storage::{error::ChainStorageError, ChainBlock, ChainHeader, MmrTree},
+    chain_storage::{error::ChainStorageError, ChainBlock, ChainHeader, HorizonSyncProgress, MmrTree},
     transactions::{
         transaction::{TransactionInput, TransactionKernel, TransactionOutput},
         types::{Commitment, HashOutput},
@@ -244,6 +244,12 @@ impl DbTransaction {
         self
     }
 
+    /// Records the position reached by an in-progress horizon sync. Passing `None` clears it.
+    pub fn set_horizon_sync_progress(&mut self, progress: Option<HorizonSyncProgress>) -> &mut Self {
+        self.operations.push(WriteOperation::SetHorizonSyncProgress(progress));
+        self
+    }
+
     pub(crate) fn operations(&self) -> &[WriteOperation] {
         &self.operations
     }
@@ -327,6 +333,7 @@ pub enum WriteOperation {
         kernel_sum: Commitment,
         utxo_sum: Commitment,
     },
+    SetHorizonSyncProgress(Option<HorizonSyncProgress>),
 }
 
 impl fmt::Display for WriteOperation {
@@ -432,6 +439,8 @@ impl fmt::Display for WriteOperation {
             ),
             SetPruningHorizonConfig(pruning_horizon) => write!(f, "Set config: pruning horizon to {}", pruning_horizon),
             SetPrunedHeight { height, .. } => write!(f, "Set pruned height to {}", height),
+            SetHorizonSyncProgress(Some(progress)) => write!(f, "Set horizon sync progress to {}", progress),
+            SetHorizonSyncProgress(None) => write!(f, "Clear horizon sync progress"),
             DeleteHeader(height) => write!(f, "Delete header at height: {}", height),
             DeleteOrphan(hash) => write!(f, "Delete orphan with hash: {}", hash.to_hex()),
         }
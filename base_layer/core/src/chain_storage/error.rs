@@ -103,6 +103,8 @@ pub enum ChainStorageError {
     },
     #[error("Cannot acquire exclusive file lock, another instance of the application is already running")]
     CannotAcquireFileLock,
+    #[error("The snapshot is invalid: {0}")]
+    InvalidSnapshot(String),
     #[error("IO Error: `{0}`")]
     IoError(#[from] std::io::Error),
 }
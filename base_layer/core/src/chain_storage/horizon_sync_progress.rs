@@ -0,0 +1,72 @@
+//  Copyright 2021, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{chain_storage::MmrTree, transactions::types::HashOutput};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tari_crypto::tari_utilities::hex::Hex;
+
+/// The position reached by an in-progress horizon sync. Kernels and outputs received within a header are committed
+/// in batches, so an interrupted sync can continue from `mmr_position` in the header instead of from its start.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HorizonSyncProgress {
+    mmr_tree: MmrTree,
+    header_hash: HashOutput,
+    mmr_position: u64,
+}
+
+impl HorizonSyncProgress {
+    pub fn new(mmr_tree: MmrTree, header_hash: HashOutput, mmr_position: u64) -> Self {
+        Self {
+            mmr_tree,
+            header_hash,
+            mmr_position,
+        }
+    }
+
+    /// The MMR that was being synchronized
+    pub fn mmr_tree(&self) -> MmrTree {
+        self.mmr_tree
+    }
+
+    /// The hash of the header that the committed kernels or outputs belong to
+    pub fn header_hash(&self) -> &HashOutput {
+        &self.header_hash
+    }
+
+    /// The MMR position of the next kernel or output to be synchronized
+    pub fn mmr_position(&self) -> u64 {
+        self.mmr_position
+    }
+}
+
+impl fmt::Display for HorizonSyncProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} MMR position {} in header {}",
+            self.mmr_tree,
+            self.mmr_position,
+            self.header_hash.to_hex()
+        )
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright 2019. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::chain_storage::{BlockchainDatabase, ChainStorageError, LMDBDatabase};
+use futures::StreamExt;
+use log::*;
+use std::time::{Duration, Instant};
+use tari_shutdown::ShutdownSignal;
+use tokio::{task, time};
+
+const LOG_TARGET: &str = "c::cs::lmdb_db::compaction";
+
+/// Periodically compacts the blockchain database in the background. To avoid competing with block processing,
+/// compaction only runs if the database was not written to during the previous interval, and is skipped if nothing
+/// has been written since the last compaction.
+pub async fn run_compaction_scheduler(
+    db: BlockchainDatabase<LMDBDatabase>,
+    interval: Duration,
+    mut shutdown_signal: ShutdownSignal,
+)
+{
+    let mut ticker = time::interval_at(
+        Instant::now()
+            .checked_add(interval)
+            .expect("compaction interval caused overflow")
+            .into(),
+        interval,
+    )
+    .fuse();
+    let mut state = CompactionState::default();
+
+    loop {
+        futures::select! {
+            _ = ticker.next() => {
+                let db = db.clone();
+                match task::spawn_blocking(move || state.compact_if_idle(&db).map(|_| state)).await {
+                    Ok(Ok(new_state)) => {
+                        state = new_state;
+                    },
+                    Ok(Err(err)) => {
+                        error!(target: LOG_TARGET, "Database compaction failed: {}", err);
+                        state = CompactionState::default();
+                    },
+                    Err(err) => {
+                        error!(target: LOG_TARGET, "Database compaction task failed: {}", err);
+                        state = CompactionState::default();
+                    },
+                }
+            },
+            _ = shutdown_signal => {
+                info!(target: LOG_TARGET, "Database compaction scheduler is shutting down");
+                break;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct CompactionState {
+    /// The last write transaction id seen on the previous interval
+    last_txn_id: Option<usize>,
+    /// The last write transaction id immediately after the previous compaction
+    compacted_txn_id: Option<usize>,
+}
+
+impl CompactionState {
+    fn compact_if_idle(&mut self, db: &BlockchainDatabase<LMDBDatabase>) -> Result<(), ChainStorageError> {
+        let txn_id = db.last_txn_id()?;
+        let is_idle = self.last_txn_id == Some(txn_id);
+        self.last_txn_id = Some(txn_id);
+        if !is_idle {
+            debug!(
+                target: LOG_TARGET,
+                "Database was written to during the last interval. Skipping compaction."
+            );
+            return Ok(());
+        }
+        if self.compacted_txn_id == Some(txn_id) {
+            trace!(
+                target: LOG_TARGET,
+                "Database has not changed since it was last compacted"
+            );
+            return Ok(());
+        }
+
+        let timer = Instant::now();
+        if db.compact()? {
+            info!(target: LOG_TARGET, "Database compacted in {:.2?}", timer.elapsed());
+            let txn_id = db.last_txn_id()?;
+            self.last_txn_id = Some(txn_id);
+            self.compacted_txn_id = Some(txn_id);
+        } else {
+            debug!(
+                target: LOG_TARGET,
+                "Database was written to during compaction. Compaction will be retried."
+            );
+        }
+        Ok(())
+    }
+}
@@ -68,6 +68,7 @@ use crate::{
         ChainBlock,
         ChainHeader,
         HorizonData,
+        HorizonSyncProgress,
         MmrTree,
         PrunedOutput,
     },
@@ -80,7 +81,7 @@ use crate::{
 };
 use croaring::Bitmap;
 use fs2::FileExt;
-use lmdb_zero::{ConstTransaction, Database, Environment, ReadTransaction, WriteTransaction};
+use lmdb_zero::{copy, ConstTransaction, Database, Environment, ReadTransaction, WriteTransaction};
 use log::*;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -88,6 +89,7 @@ use std::{
     fmt,
     fs,
     fs::File,
+    mem,
     path::{Path, PathBuf},
     sync::Arc,
     time::Instant,
@@ -104,6 +106,15 @@ type DatabaseRef = Arc<Database<'static>>;
 
 pub const LOG_TARGET: &str = "c::cs::lmdb_db::lmdb_db";
 
+/// Subdirectory into which the compacted copy of the database is written
+const LMDB_COMPACTION_DIR: &str = "compaction";
+/// Subdirectory into which the database files are moved while the compacted copy is swapped in
+const LMDB_RETIRED_DIR: &str = "retired";
+/// Subdirectory containing the placeholder environment that is open while the compacted copy is swapped in
+const LMDB_SWAP_DIR: &str = "swap";
+/// Written to the compaction directory once the compacted copy is complete and may be swapped in
+const LMDB_COMPACTION_COMPLETE_MARKER: &str = "complete";
+
 /// This is a lmdb-based blockchain database for persistent storage of the chain state.
 pub struct LMDBDatabase {
     env: Arc<Environment>,
@@ -126,14 +137,21 @@ pub struct LMDBDatabase {
     orphan_header_accumulated_data_db: DatabaseRef,
     orphan_chain_tips_db: DatabaseRef,
     orphan_parent_map_index: DatabaseRef,
+    /// The number of references to `env` held by this database. Any more means that the environment is shared and
+    /// will not close when this database is dropped.
+    env_ref_count: usize,
     _file_lock: Arc<File>,
 }
 
 impl LMDBDatabase {
     pub fn new(store: LMDBStore, file_lock: File) -> Result<Self, ChainStorageError> {
+        Self::from_store(store, Arc::new(file_lock))
+    }
+
+    fn from_store(store: LMDBStore, file_lock: Arc<File>) -> Result<Self, ChainStorageError> {
         let env = store.env();
 
-        let res = Self {
+        let mut res = Self {
             metadata_db: get_database(&store, LMDB_DB_METADATA)?,
             headers_db: get_database(&store, LMDB_DB_HEADERS)?,
             header_accumulated_data_db: get_database(&store, LMDB_DB_HEADER_ACCUMULATED_DATA)?,
@@ -154,12 +172,128 @@ impl LMDBDatabase {
             orphan_parent_map_index: get_database(&store, LMDB_DB_ORPHAN_PARENT_MAP_INDEX)?,
             env,
             env_config: store.env_config(),
-            _file_lock: Arc::new(file_lock),
+            env_ref_count: 0,
+            _file_lock: file_lock,
         };
+        drop(store);
+        res.env_ref_count = Arc::strong_count(&res.env);
 
         Ok(res)
     }
 
+    /// Returns the directory containing the LMDB environment
+    pub fn path(&self) -> Result<PathBuf, ChainStorageError> {
+        let path = self
+            .env
+            .path()?
+            .to_str()
+            .map_err(|e| ChainStorageError::CriticalError(format!("Invalid LMDB path: {}", e)))?;
+        Ok(PathBuf::from(path))
+    }
+
+    /// Returns the id of the last committed write transaction, which changes whenever the database is written to
+    pub fn last_txn_id(&self) -> Result<usize, ChainStorageError> {
+        Ok(self.env.info()?.last_txnid)
+    }
+
+    /// Returns a handle that writes a compacted copy of this database. The handle does not borrow the database, so the
+    /// copy can be made without holding a lock on it.
+    pub fn compaction_handle(&self) -> Result<LMDBCompactionHandle, ChainStorageError> {
+        Ok(LMDBCompactionHandle {
+            env: self.env.clone(),
+            copy_path: self.path()?.join(LMDB_COMPACTION_DIR),
+        })
+    }
+
+    /// Replaces the database files with the compacted copy written by [LMDBCompactionHandle::create_compacted_copy]
+    /// and reopens the environment. If any write was committed after the copy was made, the copy is stale, in which
+    /// case it is discarded and false is returned.
+    ///
+    /// The files are only swapped while the environment is closed. A marker is written once the copy is complete, so
+    /// that a swap interrupted by a crash is completed (or rolled back if the copy is incomplete) the next time the
+    /// database is opened. If the swap fails, the original database is reopened and the error is returned. Only if the
+    /// original database cannot be reopened either is this database left unusable.
+    pub fn replace_with_compacted_copy(&mut self, copy: CompactedCopy) -> Result<bool, ChainStorageError> {
+        let path = self.path()?;
+        let copy_path = path.join(LMDB_COMPACTION_DIR);
+        if self.last_txn_id()? != copy.txn_id {
+            debug!(
+                target: LOG_TARGET,
+                "Database was written to while the compacted copy was being made. Discarding the copy."
+            );
+            fs::remove_dir_all(&copy_path)?;
+            return Ok(false);
+        }
+
+        // The environment only closes once every reference to it has been dropped. The compaction handle was consumed
+        // when the copy was made, so anything else holding a reference means the files cannot be swapped.
+        if Arc::strong_count(&self.env) != self.env_ref_count {
+            fs::remove_dir_all(&copy_path)?;
+            return Err(ChainStorageError::CriticalError(
+                "LMDB environment is still referenced elsewhere and cannot be closed for compaction".to_string(),
+            ));
+        }
+
+        File::open(copy_path.join("data.mdb"))?.sync_all()?;
+        File::create(copy_path.join(LMDB_COMPACTION_COMPLETE_MARKER))?.sync_all()?;
+
+        let retired_path = path.join(LMDB_RETIRED_DIR);
+        fs::create_dir_all(&retired_path)?;
+        let swap_path = path.join(LMDB_SWAP_DIR);
+        fs::create_dir_all(&swap_path)?;
+        // Close the environment by replacing it with an empty placeholder environment
+        let placeholder = Self::from_store(
+            open_lmdb_store(&swap_path, LMDBConfig::new_from_mb(1, 1, 0))?,
+            self._file_lock.clone(),
+        )?;
+        let env_config = self.env_config.clone();
+        let file_lock = self._file_lock.clone();
+        drop(mem::replace(self, placeholder));
+
+        let renames = [
+            (path.join("data.mdb"), retired_path.join("data.mdb")),
+            (path.join("lock.mdb"), retired_path.join("lock.mdb")),
+            (copy_path.join("data.mdb"), path.join("data.mdb")),
+        ];
+        let mut result = Ok(true);
+        for (i, (from, to)) in renames.iter().enumerate() {
+            if let Err(err) = fs::rename(from, to) {
+                for (from, to) in renames[..i].iter().rev() {
+                    let _ = fs::rename(to, from);
+                }
+                result = Err(err.into());
+                break;
+            }
+        }
+
+        let store = match open_lmdb_store(&path, env_config.clone()) {
+            Ok(store) => Ok(store),
+            Err(err) if result.is_ok() => {
+                error!(
+                    target: LOG_TARGET,
+                    "Failed to open the compacted database: {}. Restoring the original database.", err
+                );
+                result = Err(err);
+                fs::rename(retired_path.join("data.mdb"), path.join("data.mdb"))
+                    .map_err(ChainStorageError::from)
+                    .and_then(|_| open_lmdb_store(&path, env_config))
+            },
+            Err(err) => Err(err),
+        };
+        let store = store.map_err(|err| {
+            ChainStorageError::CriticalError(format!(
+                "Failed to reopen the database after compaction: {}. The node must be restarted.",
+                err
+            ))
+        })?;
+        *self = Self::from_store(store, file_lock)?;
+
+        let _ = fs::remove_dir_all(&copy_path);
+        let _ = fs::remove_dir_all(&retired_path);
+        let _ = fs::remove_dir_all(&swap_path);
+        result
+    }
+
     /// Try to establish a read lock on the LMDB database. If an exclusive write lock has been previously acquired, this
     /// method will block until that lock is released.
     fn read_transaction(&self) -> Result<ReadTransaction<'_>, ChainStorageError> {
@@ -311,6 +445,19 @@ impl LMDBDatabase {
                         MetadataValue::HorizonData(HorizonData::new(kernel_sum, utxo_sum)),
                     )?;
                 },
+                SetHorizonSyncProgress(Some(progress)) => {
+                    self.set_metadata(
+                        &write_txn,
+                        MetadataKey::HorizonSyncProgress,
+                        MetadataValue::HorizonSyncProgress(progress),
+                    )?;
+                },
+                SetHorizonSyncProgress(None) => {
+                    let k = MetadataKey::HorizonSyncProgress as u32;
+                    if lmdb_exists(&write_txn, &self.metadata_db, &k)? {
+                        lmdb_delete(&write_txn, &self.metadata_db, &k)?;
+                    }
+                },
             }
         }
         write_txn
@@ -1045,12 +1192,18 @@ impl LMDBDatabase {
 }
 
 pub fn create_lmdb_database<P: AsRef<Path>>(path: P, config: LMDBConfig) -> Result<LMDBDatabase, ChainStorageError> {
-    let flags = db::CREATE;
     let _ = std::fs::create_dir_all(&path);
 
     let file_lock = acquire_exclusive_file_lock(&path.as_ref().to_path_buf())?;
+    recover_interrupted_compaction(path.as_ref())?;
 
-    let lmdb_store = LMDBBuilder::new()
+    let lmdb_store = open_lmdb_store(path.as_ref(), config)?;
+    LMDBDatabase::new(lmdb_store, file_lock)
+}
+
+fn open_lmdb_store(path: &Path, config: LMDBConfig) -> Result<LMDBStore, ChainStorageError> {
+    let flags = db::CREATE;
+    LMDBBuilder::new()
         .set_path(path)
         .set_env_config(config)
         .set_max_number_of_databases(20)
@@ -1073,8 +1226,71 @@ pub fn create_lmdb_database<P: AsRef<Path>>(path: P, config: LMDBConfig) -> Resu
         .add_database(LMDB_DB_ORPHAN_CHAIN_TIPS, flags)
         .add_database(LMDB_DB_ORPHAN_PARENT_MAP_INDEX, flags | db::DUPSORT)
         .build()
-        .map_err(|err| ChainStorageError::CriticalError(format!("Could not create LMDB store:{}", err)))?;
-    LMDBDatabase::new(lmdb_store, file_lock)
+        .map_err(|err| ChainStorageError::CriticalError(format!("Could not create LMDB store:{}", err)))
+}
+
+/// Completes or rolls back a compaction that was interrupted while the compacted copy was being swapped in. This must
+/// be called while holding the file lock and before the environment is opened.
+fn recover_interrupted_compaction(path: &Path) -> Result<(), ChainStorageError> {
+    let copy_path = path.join(LMDB_COMPACTION_DIR);
+    let retired_path = path.join(LMDB_RETIRED_DIR);
+    let data_file = path.join("data.mdb");
+    if !data_file.exists() {
+        let copy_file = copy_path.join("data.mdb");
+        let retired_file = retired_path.join("data.mdb");
+        if copy_path.join(LMDB_COMPACTION_COMPLETE_MARKER).exists() && copy_file.exists() {
+            warn!(target: LOG_TARGET, "Completing an interrupted database compaction");
+            fs::rename(copy_file, &data_file)?;
+        } else if retired_file.exists() {
+            warn!(target: LOG_TARGET, "Rolling back an interrupted database compaction");
+            fs::rename(retired_file, &data_file)?;
+        }
+    }
+
+    for dir in &[copy_path, retired_path, path.join(LMDB_SWAP_DIR)] {
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes a compacted copy of an [LMDBDatabase], omitting free pages.
+pub struct LMDBCompactionHandle {
+    env: Arc<Environment>,
+    copy_path: PathBuf,
+}
+
+impl LMDBCompactionHandle {
+    /// Writes the compacted copy. The copy is made within a read transaction, so writers are not blocked while it is in
+    /// progress. The handle is consumed so that it does not keep the environment open once the copy is swapped in.
+    pub fn create_compacted_copy(self) -> Result<CompactedCopy, ChainStorageError> {
+        if self.copy_path.exists() {
+            fs::remove_dir_all(&self.copy_path)?;
+        }
+        fs::create_dir_all(&self.copy_path)?;
+        // Read before copying: a write committed in between makes the copy look stale, never the other way around
+        let txn_id = self.env.info()?.last_txnid;
+        let copy_path = self
+            .copy_path
+            .to_str()
+            .ok_or_else(|| ChainStorageError::CriticalError("Invalid LMDB compaction path".to_string()))?;
+        let timer = Instant::now();
+        self.env.copy(copy_path, copy::COMPACT)?;
+        debug!(
+            target: LOG_TARGET,
+            "Compacted copy of the database written in {:.2?}",
+            timer.elapsed()
+        );
+        Ok(CompactedCopy { txn_id })
+    }
+}
+
+/// A compacted copy written by [LMDBCompactionHandle::create_compacted_copy]
+#[derive(Debug, Clone, Copy)]
+pub struct CompactedCopy {
+    /// The id of the last write transaction that the copy is guaranteed to include
+    txn_id: usize,
 }
 
 pub fn create_recovery_lmdb_database<P: AsRef<Path>>(path: P) -> Result<(), ChainStorageError> {
@@ -1858,6 +2074,11 @@ impl BlockchainBackend for LMDBDatabase {
         let txn = self.read_transaction()?;
         fetch_horizon_data(&txn, &self.metadata_db)
     }
+
+    fn fetch_horizon_sync_progress(&self) -> Result<Option<HorizonSyncProgress>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        fetch_horizon_sync_progress(&txn, &self.metadata_db)
+    }
 }
 
 // Fetch the chain metadata
@@ -1908,6 +2129,24 @@ fn fetch_horizon_data(txn: &ConstTransaction<'_>, db: &Database) -> Result<Optio
         }),
     }
 }
+// Fetches the horizon sync progress from the provided metadata db.
+fn fetch_horizon_sync_progress(
+    txn: &ConstTransaction<'_>,
+    db: &Database,
+) -> Result<Option<HorizonSyncProgress>, ChainStorageError>
+{
+    let k = MetadataKey::HorizonSyncProgress;
+    let val: Option<MetadataValue> = lmdb_get(&txn, &db, &(k as u32))?;
+    match val {
+        Some(MetadataValue::HorizonSyncProgress(progress)) => Ok(Some(progress)),
+        None => Ok(None),
+        _ => Err(ChainStorageError::ValueNotFound {
+            entity: "ChainMetadata".to_string(),
+            field: "HorizonSyncProgress".to_string(),
+            value: "".to_string(),
+        }),
+    }
+}
 // Fetches the best block hash from the provided metadata db.
 fn fetch_best_block(txn: &ConstTransaction<'_>, db: &Database) -> Result<BlockHash, ChainStorageError> {
     let k = MetadataKey::BestBlock;
@@ -1961,6 +2200,7 @@ enum MetadataKey {
     PruningHorizon,
     PrunedHeight,
     HorizonData,
+    HorizonSyncProgress,
 }
 
 impl fmt::Display for MetadataKey {
@@ -1972,6 +2212,7 @@ impl fmt::Display for MetadataKey {
             MetadataKey::PrunedHeight => f.write_str("Effective pruned height"),
             MetadataKey::BestBlock => f.write_str("Chain tip block hash"),
             MetadataKey::HorizonData => f.write_str("Database info"),
+            MetadataKey::HorizonSyncProgress => f.write_str("Horizon sync progress"),
         }
     }
 }
@@ -1985,6 +2226,7 @@ enum MetadataValue {
     PruningHorizon(u64),
     PrunedHeight(u64),
     HorizonData(HorizonData),
+    HorizonSyncProgress(HorizonSyncProgress),
 }
 
 impl fmt::Display for MetadataValue {
@@ -1996,6 +2238,7 @@ impl fmt::Display for MetadataValue {
             MetadataValue::PrunedHeight(height) => write!(f, "Effective pruned height is {}", height),
             MetadataValue::BestBlock(hash) => write!(f, "Chain tip block hash is {}", hash.to_hex()),
             MetadataValue::HorizonData(_) => write!(f, "Horizon data"),
+            MetadataValue::HorizonSyncProgress(progress) => write!(f, "Horizon sync progress is {}", progress),
         }
     }
 }
@@ -20,6 +20,9 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+mod compaction;
+pub use compaction::run_compaction_scheduler;
+
 mod lmdb;
 #[allow(clippy::module_inception)]
 mod lmdb_db;
@@ -28,7 +31,13 @@ use crate::transactions::{
     transaction::{TransactionInput, TransactionKernel, TransactionOutput},
     types::HashOutput,
 };
-pub use lmdb_db::{create_lmdb_database, create_recovery_lmdb_database, LMDBDatabase};
+pub use lmdb_db::{
+    create_lmdb_database,
+    create_recovery_lmdb_database,
+    CompactedCopy,
+    LMDBCompactionHandle,
+    LMDBDatabase,
+};
 use serde::{Deserialize, Serialize};
 
 pub const LMDB_DB_METADATA: &str = "metadata";
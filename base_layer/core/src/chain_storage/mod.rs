@@ -73,6 +73,9 @@ pub use historical_block::HistoricalBlock;
 mod horizon_data;
 pub use horizon_data::HorizonData;
 
+mod horizon_sync_progress;
+pub use horizon_sync_progress::HorizonSyncProgress;
+
 mod pruned_output;
 pub use pruned_output::PrunedOutput;
 
@@ -80,6 +83,9 @@ mod lmdb_db;
 pub use lmdb_db::{
     create_lmdb_database,
     create_recovery_lmdb_database,
+    run_compaction_scheduler,
+    CompactedCopy,
+    LMDBCompactionHandle,
     LMDBDatabase,
     LMDB_DB_BLOCK_HASHES,
     LMDB_DB_HEADERS,
@@ -90,5 +96,7 @@ pub use lmdb_db::{
     LMDB_DB_UTXOS,
 };
 
+mod snapshot;
+
 mod target_difficulties;
 pub use target_difficulties::TargetDifficulties;
@@ -0,0 +1,397 @@
+// Copyright 2019, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Chain snapshots allow a fresh base node to bootstrap from a file instead of syncing from genesis.
+//!
+//! A snapshot contains every header up to the snapshot height along with the kernels and outputs of each block.
+//! Outputs that were spent at the snapshot height are stored as pruned hashes, so the snapshot is essentially the
+//! header chain plus the UTXO set. On import, every header is validated and every block's kernel, output and range
+//! proof MMR roots are recomputed and checked against the header chain, exactly as horizon sync does.
+
+use crate::{
+    blocks::BlockHeader,
+    chain_storage::{
+        accumulated_data::BlockHeaderAccumulatedData,
+        BlockchainBackend,
+        ChainHeader,
+        ChainStorageError,
+        DbTransaction,
+        MmrTree,
+        OrNotFound,
+        PrunedOutput,
+    },
+    transactions::{
+        transaction::TransactionKernel,
+        types::{Commitment, HashDigest, HashOutput, RangeProofService},
+    },
+    validation::{DifficultyCalculator, FinalHorizonStateValidation, HeaderValidation},
+};
+use croaring::Bitmap;
+use log::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    convert::TryFrom,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+use tari_crypto::tari_utilities::{hex::Hex, Hashable};
+use tari_mmr::{MerkleMountainRange, MutableMmr};
+
+const LOG_TARGET: &str = "c::cs::snapshot";
+
+/// The snapshot file format version. Bump this whenever the layout of `SnapshotMetadata` or `SnapshotBlock` changes.
+const SNAPSHOT_VERSION: u16 = 1;
+
+/// The first record in a snapshot file, identifying the chain and the block the snapshot was taken at.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SnapshotMetadata {
+    version: u16,
+    genesis_hash: HashOutput,
+    height: u64,
+    tip_hash: HashOutput,
+}
+
+/// The contents of a single block in a snapshot. `deleted_diff` is the serialized bitmap of the output MMR positions
+/// spent in this block.
+#[derive(Serialize, Deserialize)]
+struct SnapshotBlock {
+    header: BlockHeader,
+    kernels: Vec<TransactionKernel>,
+    outputs: Vec<PrunedOutput>,
+    deleted_diff: Vec<u8>,
+}
+
+/// Writes a snapshot of the chain at `height` to `path`. The height may not be below the pruned height of the
+/// database, since the bodies of outputs that were unspent at that height may already have been pruned.
+pub fn export_snapshot<T: BlockchainBackend, P: AsRef<Path>>(
+    db: &T,
+    height: u64,
+    path: P,
+) -> Result<(), ChainStorageError>
+{
+    let metadata = db.fetch_chain_metadata()?;
+    if height == 0 || height > metadata.height_of_longest_chain() {
+        return Err(ChainStorageError::InvalidArguments {
+            func: "export_snapshot",
+            arg: "height",
+            message: format!(
+                "Snapshot height must be between 1 and the chain tip height {}",
+                metadata.height_of_longest_chain()
+            ),
+        });
+    }
+    if height < metadata.pruned_height() {
+        return Err(ChainStorageError::InvalidArguments {
+            func: "export_snapshot",
+            arg: "height",
+            message: format!(
+                "Snapshot height must not be below the pruned height {}",
+                metadata.pruned_height()
+            ),
+        });
+    }
+
+    let genesis = db.fetch_chain_header_by_height(0)?;
+    let tip = db.fetch_chain_header_by_height(height)?;
+    let tip_deleted = db
+        .fetch_block_accumulated_data(tip.hash())?
+        .or_not_found("BlockAccumulatedData", "hash", tip.hash().to_hex())?
+        .dissolve()
+        .3;
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    serialize_record(&mut writer, &SnapshotMetadata {
+        version: SNAPSHOT_VERSION,
+        genesis_hash: genesis.hash().clone(),
+        height,
+        tip_hash: tip.hash().clone(),
+    })?;
+
+    let mut prev_header = genesis.header().clone();
+    let mut prev_deleted = db
+        .fetch_block_accumulated_data(genesis.hash())?
+        .or_not_found("BlockAccumulatedData", "hash", genesis.hash().to_hex())?
+        .dissolve()
+        .3;
+    for h in 1..=height {
+        let header = db.fetch_chain_header_by_height(h)?.header().clone();
+
+        let kernels = if header.kernel_mmr_size > prev_header.kernel_mmr_size {
+            db.fetch_kernels_by_mmr_position(prev_header.kernel_mmr_size, header.kernel_mmr_size - 1)?
+        } else {
+            Vec::new()
+        };
+        let outputs = if header.output_mmr_size > prev_header.output_mmr_size {
+            db.fetch_utxos_by_mmr_position(prev_header.output_mmr_size, header.output_mmr_size - 1, &tip_deleted)?
+                .0
+        } else {
+            Vec::new()
+        };
+
+        let mut deleted_diff = db
+            .fetch_block_accumulated_data(&header.hash())?
+            .or_not_found("BlockAccumulatedData", "hash", header.hash().to_hex())?
+            .dissolve()
+            .3;
+        let deleted = deleted_diff.clone();
+        deleted_diff.xor_inplace(&prev_deleted);
+        deleted_diff.run_optimize();
+
+        serialize_record(&mut writer, &SnapshotBlock {
+            header: header.clone(),
+            kernels,
+            outputs,
+            deleted_diff: deleted_diff.serialize(),
+        })?;
+
+        prev_header = header;
+        prev_deleted = deleted;
+    }
+    writer.flush()?;
+
+    info!(
+        target: LOG_TARGET,
+        "Exported chain snapshot at height {} ({})",
+        height,
+        tip.hash().to_hex()
+    );
+    Ok(())
+}
+
+/// Imports the snapshot at `path` into an empty database (i.e. one that only contains the genesis block). Every
+/// header is validated and every block's MMR roots are checked against its header before it is committed, and the
+/// resulting UTXO and kernel sums are checked by the `final_state_validator` before the chain tip is set. Returns the
+/// new chain tip.
+pub fn import_snapshot<T: BlockchainBackend, P: AsRef<Path>>(
+    db: &mut T,
+    header_validator: &dyn HeaderValidation<T>,
+    difficulty_calculator: &DifficultyCalculator,
+    final_state_validator: &dyn FinalHorizonStateValidation<T>,
+    rangeproof_service: &RangeProofService,
+    path: P,
+) -> Result<ChainHeader, ChainStorageError>
+{
+    let mut reader = BufReader::new(File::open(path)?);
+    let snapshot: SnapshotMetadata = deserialize_record(&mut reader)?;
+    if snapshot.version != SNAPSHOT_VERSION {
+        return Err(ChainStorageError::InvalidSnapshot(format!(
+            "Unsupported snapshot version {} (expected {})",
+            snapshot.version, SNAPSHOT_VERSION
+        )));
+    }
+
+    let metadata = db.fetch_chain_metadata()?;
+    if metadata.height_of_longest_chain() > 0 {
+        return Err(ChainStorageError::InvalidOperation(format!(
+            "A snapshot can only be imported into an empty database, but the chain tip is at height {}",
+            metadata.height_of_longest_chain()
+        )));
+    }
+    let genesis = db.fetch_chain_header_by_height(0)?;
+    if *genesis.hash() != snapshot.genesis_hash {
+        return Err(ChainStorageError::InvalidSnapshot(format!(
+            "Snapshot genesis block {} does not match the local genesis block {}",
+            snapshot.genesis_hash.to_hex(),
+            genesis.hash().to_hex()
+        )));
+    }
+
+    let (kernel_pruned_set, output_pruned_set, rp_pruned_set, mut deleted) = db
+        .fetch_block_accumulated_data(genesis.hash())?
+        .or_not_found("BlockAccumulatedData", "hash", genesis.hash().to_hex())?
+        .dissolve();
+    let mut kernel_mmr = MerkleMountainRange::<HashDigest, _>::new(kernel_pruned_set);
+    let mut output_mmr = MerkleMountainRange::<HashDigest, _>::new(output_pruned_set);
+    let mut proof_mmr = MerkleMountainRange::<HashDigest, _>::new(rp_pruned_set);
+
+    let mut kernel_sum = Commitment::default();
+    let mut utxo_sum = Commitment::default();
+    let mut prev = genesis.clone();
+    for height in 1..=snapshot.height {
+        let SnapshotBlock {
+            header,
+            kernels,
+            outputs,
+            deleted_diff,
+        } = deserialize_record(&mut reader)?;
+        if header.height != height || header.prev_hash != *prev.hash() {
+            return Err(ChainStorageError::InvalidSnapshot(format!(
+                "Header #{} ({}) does not build on the previous header #{} ({})",
+                header.height,
+                header.hash().to_hex(),
+                prev.height(),
+                prev.hash().to_hex()
+            )));
+        }
+        if kernels.len() as u64 != header.kernel_mmr_size.saturating_sub(prev.header().kernel_mmr_size) ||
+            outputs.len() as u64 != header.output_mmr_size.saturating_sub(prev.header().output_mmr_size)
+        {
+            return Err(ChainStorageError::InvalidSnapshot(format!(
+                "Snapshot block #{} has {} kernel(s) and {} output(s), which does not match its header",
+                height,
+                kernels.len(),
+                outputs.len()
+            )));
+        }
+
+        let achieved_target = header_validator.validate(db, &header, difficulty_calculator)?;
+        let hash = header.hash();
+        let accumulated_data = BlockHeaderAccumulatedData::builder(prev.accumulated_data())
+            .with_hash(hash.clone())
+            .with_achieved_target_difficulty(achieved_target)
+            .with_total_kernel_offset(header.total_kernel_offset.clone())
+            .build()?;
+        let chain_header = ChainHeader::try_construct(header, accumulated_data).ok_or_else(|| {
+            ChainStorageError::InvalidOperation(format!(
+                "Attempt to create mismatched ChainHeader with hash {}",
+                hash.to_hex()
+            ))
+        })?;
+
+        let mut txn = DbTransaction::new();
+        txn.insert_chain_header(chain_header.clone());
+
+        let mut mmr_position = prev.header().kernel_mmr_size;
+        for kernel in kernels {
+            kernel
+                .verify_signature()
+                .map_err(|err| ChainStorageError::InvalidSnapshot(err.to_string()))?;
+            kernel_sum = &kernel.excess + &kernel_sum;
+            kernel_mmr.push(kernel.hash())?;
+            txn.insert_kernel(kernel, hash.clone(), mmr_position_to_u32(mmr_position)?);
+            mmr_position += 1;
+        }
+        let mmr_root = kernel_mmr.get_merkle_root()?;
+        if mmr_root != chain_header.header().kernel_mr {
+            return Err(ChainStorageError::MismatchedMmrRoot(MmrTree::Kernel));
+        }
+
+        let mut mmr_position = prev.header().output_mmr_size;
+        for output in outputs {
+            match output {
+                PrunedOutput::NotPruned { output } => {
+                    match output.verify_range_proof(rangeproof_service) {
+                        Ok(true) => {},
+                        Ok(false) => {
+                            return Err(ChainStorageError::InvalidSnapshot(format!(
+                                "Invalid range proof for output {}",
+                                output.hash().to_hex()
+                            )));
+                        },
+                        Err(err) => return Err(ChainStorageError::InvalidSnapshot(err.to_string())),
+                    }
+                    utxo_sum = &output.commitment + &utxo_sum;
+                    output_mmr.push(output.hash())?;
+                    proof_mmr.push(output.proof().hash())?;
+                    txn.insert_utxo(output, hash.clone(), mmr_position_to_u32(mmr_position)?);
+                },
+                PrunedOutput::Pruned {
+                    output_hash,
+                    range_proof_hash,
+                } => {
+                    output_mmr.push(output_hash.clone())?;
+                    proof_mmr.push(range_proof_hash.clone())?;
+                    txn.insert_pruned_utxo(
+                        output_hash,
+                        range_proof_hash,
+                        hash.clone(),
+                        mmr_position_to_u32(mmr_position)?,
+                    );
+                },
+            }
+            mmr_position += 1;
+        }
+
+        deleted.or_inplace(&Bitmap::deserialize(&deleted_diff));
+        deleted.run_optimize();
+        let pruned_output_set = output_mmr.get_pruned_hash_set()?;
+        let mmr_root =
+            MutableMmr::<HashDigest, _>::new(pruned_output_set.clone(), deleted.clone())?.get_merkle_root()?;
+        if mmr_root != chain_header.header().output_mr {
+            return Err(ChainStorageError::MismatchedMmrRoot(MmrTree::Utxo));
+        }
+        let mmr_root = proof_mmr.get_merkle_root()?;
+        if mmr_root != chain_header.header().range_proof_mr {
+            return Err(ChainStorageError::MismatchedMmrRoot(MmrTree::RangeProof));
+        }
+
+        txn.update_pruned_hash_set(MmrTree::Kernel, hash.clone(), kernel_mmr.get_pruned_hash_set()?);
+        txn.update_pruned_hash_set(MmrTree::Utxo, hash.clone(), pruned_output_set);
+        txn.update_pruned_hash_set(MmrTree::RangeProof, hash.clone(), proof_mmr.get_pruned_hash_set()?);
+        txn.update_deleted_with_diff(hash, deleted.clone());
+        db.write(txn)?;
+
+        trace!(target: LOG_TARGET, "Imported snapshot block #{}", height);
+        prev = chain_header;
+    }
+
+    if *prev.hash() != snapshot.tip_hash {
+        return Err(ChainStorageError::InvalidSnapshot(format!(
+            "Snapshot ended at {} but the metadata specifies a tip of {}",
+            prev.hash().to_hex(),
+            snapshot.tip_hash.to_hex()
+        )));
+    }
+
+    // The genesis block is not part of the snapshot, so add its kernels and unspent outputs to the sums
+    for kernel in db.fetch_kernels_by_mmr_position(0, genesis.header().kernel_mmr_size - 1)? {
+        kernel_sum = &kernel.excess + &kernel_sum;
+    }
+    let (genesis_outputs, _) = db.fetch_utxos_by_mmr_position(0, genesis.header().output_mmr_size - 1, &deleted)?;
+    for output in genesis_outputs {
+        if let PrunedOutput::NotPruned { output } = output {
+            utxo_sum = &output.commitment + &utxo_sum;
+        }
+    }
+
+    final_state_validator.validate(prev.height(), &utxo_sum, &kernel_sum, db)?;
+
+    let mut txn = DbTransaction::new();
+    txn.set_best_block(
+        prev.height(),
+        prev.hash().clone(),
+        prev.accumulated_data().total_accumulated_difficulty,
+    )
+    .set_pruned_height(prev.height(), kernel_sum, utxo_sum);
+    db.write(txn)?;
+
+    info!(
+        target: LOG_TARGET,
+        "Imported chain snapshot at height {} ({})",
+        prev.height(),
+        prev.hash().to_hex()
+    );
+    Ok(prev)
+}
+
+fn mmr_position_to_u32(mmr_position: u64) -> Result<u32, ChainStorageError> {
+    u32::try_from(mmr_position).map_err(|_| ChainStorageError::OutOfRange)
+}
+
+fn serialize_record<W: Write, T: Serialize>(writer: &mut W, record: &T) -> Result<(), ChainStorageError> {
+    bincode::serialize_into(writer, record).map_err(|err| ChainStorageError::InvalidSnapshot(err.to_string()))
+}
+
+fn deserialize_record<R: Read, T: DeserializeOwned>(reader: &mut R) -> Result<T, ChainStorageError> {
+    bincode::deserialize_from(reader).map_err(|err| ChainStorageError::InvalidSnapshot(err.to_string()))
+}
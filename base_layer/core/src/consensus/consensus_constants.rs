@@ -153,7 +153,7 @@ impl ConsensusConstants {
     }
 
     /// This is how many blocks we use to count towards the median timestamp to ensure the block chain moves forward.
-    pub fn get_median_timestamp_count(&self) -> usize {
+    pub fn median_timestamp_window(&self) -> usize {
         self.median_timestamp_count
     }
 
@@ -382,6 +382,11 @@ impl ConsensusConstantsBuilder {
         self
     }
 
+    pub fn with_effective_from_height(mut self, height: u64) -> Self {
+        self.consensus.effective_from_height = height;
+        self
+    }
+
     pub fn with_coinbase_lockheight(mut self, height: u64) -> Self {
         self.consensus.coinbase_lock_height = height;
         self
@@ -101,6 +101,17 @@ impl ConsensusManager {
         constants
     }
 
+    /// Returns every set of consensus constants along with the height from which it is effective, ordered by
+    /// activation height. The first entry contains the constants in effect from the genesis block and each subsequent
+    /// entry is a hard fork.
+    pub fn hard_fork_schedule(&self) -> Vec<(u64, ConsensusConstants)> {
+        self.inner
+            .consensus_constants
+            .iter()
+            .map(|c| (c.effective_from_height(), c.clone()))
+            .collect()
+    }
+
     /// Create a new TargetDifficulty for the given proof of work using constants that are effective from the given
     /// height
     pub(crate) fn new_target_difficulty(&self, pow_algo: PowAlgorithm, height: u64) -> TargetDifficultyWindow {
@@ -213,3 +224,50 @@ impl ConsensusManagerBuilder {
         ConsensusManager { inner: Arc::new(inner) }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::consensus::{ConsensusConstantsBuilder, ConsensusManagerBuilder, Network};
+
+    #[test]
+    fn hard_fork_schedule() {
+        let network = Network::LocalNet;
+        let rules = ConsensusManagerBuilder::new(network)
+            .with_consensus_constants(
+                ConsensusConstantsBuilder::new(network)
+                    .with_coinbase_lockheight(1)
+                    .build(),
+            )
+            .with_consensus_constants(
+                ConsensusConstantsBuilder::new(network)
+                    .with_effective_from_height(100)
+                    .with_coinbase_lockheight(2)
+                    .build(),
+            )
+            .with_consensus_constants(
+                ConsensusConstantsBuilder::new(network)
+                    .with_effective_from_height(250)
+                    .with_coinbase_lockheight(3)
+                    .build(),
+            )
+            .build();
+
+        let schedule = rules.hard_fork_schedule();
+        let heights = schedule.iter().map(|(height, _)| *height).collect::<Vec<_>>();
+        assert_eq!(heights, vec![0, 100, 250]);
+        for (i, (height, constants)) in schedule.iter().enumerate() {
+            assert_eq!(constants.effective_from_height(), *height);
+            assert_eq!(constants.coinbase_lock_height(), i as u64 + 1);
+            assert_eq!(
+                rules.consensus_constants(*height).coinbase_lock_height(),
+                constants.coinbase_lock_height()
+            );
+        }
+
+        let schedule = ConsensusManagerBuilder::new(Network::Stibbons)
+            .build()
+            .hard_fork_schedule();
+        let heights = schedule.iter().map(|(height, _)| *height).collect::<Vec<_>>();
+        assert_eq!(heights, vec![0, 1400]);
+    }
+}
@@ -154,10 +154,15 @@ impl<'a> Iterator for EmissionRate<'a> {
 }
 
 impl Emission for EmissionSchedule {
-    /// Calculate the block reward for the given block height, in µTari
+    /// Calculate the block reward for the given block height, in µTari. The reward never increases, so once it stops
+    /// changing (at the tail emission, or where the decay rounds down to nothing) it is the reward for every later
+    /// block and the remaining heights are not iterated.
     fn block_reward(&self, height: u64) -> MicroTari {
         let mut iterator = self.iter();
         while iterator.block_height() < height {
+            if iterator.next_reward() == iterator.block_reward() {
+                break;
+            }
             iterator.next();
         }
         iterator.block_reward()
@@ -198,10 +203,21 @@ mod test {
         // let mut n = (std::i32::MAX - 1) as u64;
         let height = 262_800_000; // 1000 years' problem
         let schedule = EmissionSchedule::new(MicroTari::from(1e7 as u64), &[22, 23, 24, 26, 27], MicroTari::from(100));
-        // Slow but does not overflow
+        // Does not overflow
         assert_eq!(schedule.block_reward(height), MicroTari::from(4_194_303));
     }
 
+    #[test]
+    fn block_reward_at_max_height() {
+        let schedule = EmissionSchedule::new(MicroTari::from(10_000), &[1], MicroTari::from(100));
+        assert_eq!(schedule.block_reward(6), MicroTari::from(157));
+        assert_eq!(schedule.block_reward(7), MicroTari::from(100));
+        assert_eq!(schedule.block_reward(u64::MAX), MicroTari::from(100));
+
+        let schedule = EmissionSchedule::new(MicroTari::from(1e7 as u64), &[22, 23, 24, 26, 27], MicroTari::from(100));
+        assert_eq!(schedule.block_reward(u64::MAX), MicroTari::from(4_194_303));
+    }
+
     #[test]
     fn generate_emission_schedule_as_iterator() {
         const INITIAL: u64 = 10_000_100;
@@ -21,11 +21,24 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    blocks::Block,
-    mempool::{error::MempoolError, Mempool, StateResponse, StatsResponse, TxStorageResponse},
-    transactions::{transaction::Transaction, types::Signature},
+    blocks::{Block, ShortTxId},
+    mempool::{
+        error::MempoolError,
+        FeeHistogramResponse,
+        Mempool,
+        StateResponse,
+        StatsResponse,
+        TransactionPackage,
+        TxStorageResponse,
+        ValidationTimingResponse,
+    },
+    transactions::{
+        transaction::Transaction,
+        types::{Commitment, Signature},
+    },
 };
 use std::sync::Arc;
+use tari_common_types::types::BlockHash;
 
 macro_rules! make_async {
     ($fn:ident($($param1:ident:$ptype1:ty,$param2:ident:$ptype2:ty),+) -> $rtype:ty) => {
@@ -63,6 +76,95 @@ make_async!(process_published_block(published_block: Arc<Block>) -> ());
 make_async!(process_reorg(removed_blocks: Vec<Arc<Block>>, new_blocks: Vec<Arc<Block>>) -> ());
 make_async!(snapshot() -> Vec<Arc<Transaction>>);
 make_async!(retrieve(total_weight: u64) -> Vec<Arc<Transaction>>);
+make_async!(retrieve_highest_priority_txs(weight_limit: u64) -> Vec<Arc<Transaction>>);
+make_async!(retrieve_by_short_ids(block_hash: BlockHash, short_ids: Vec<ShortTxId>) -> Vec<Arc<Transaction>>);
 make_async!(has_tx_with_excess_sig(excess_sig: Signature) -> TxStorageResponse);
+make_async!(has_tx_with_output(commitment: Commitment) -> TxStorageResponse);
+make_async!(find_conflicting_transactions(tx: Arc<Transaction>) -> Vec<Arc<Transaction>>);
+make_async!(transaction_package(excess_sig: Signature) -> Option<TransactionPackage>);
 make_async!(stats() -> StatsResponse);
 make_async!(state() -> StateResponse);
+make_async!(validation_timing() -> ValidationTimingResponse);
+make_async!(fee_histogram() -> FeeHistogramResponse);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        consensus::{ConsensusManagerBuilder, Network},
+        test_helpers::create_orphan_block,
+        transactions::tari_amount::MicroTari,
+        tx,
+        validation::mocks::MockValidator,
+    };
+
+    #[tokio_macros::test_basic]
+    async fn it_finds_conflicting_transactions() {
+        let mempool = Mempool::new(Default::default(), Arc::new(MockValidator::new(true)));
+        let (tx1, _, _) = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1);
+        let (tx2, _, _) = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1);
+        let (tx3, _, _) = tx!(MicroTari(5_000), fee: MicroTari(80), inputs: 1, outputs: 1);
+        let tx1 = Arc::new(tx1);
+        insert(mempool.clone(), tx1.clone()).await.unwrap();
+        insert(mempool.clone(), Arc::new(tx2.clone())).await.unwrap();
+
+        // Spend one of the inputs of tx1 in a transaction with a different kernel
+        let conflicting_tx = Arc::new(Transaction::new(
+            vec![tx1.body.inputs()[0].clone()],
+            tx3.body.outputs().clone(),
+            tx3.body.kernels().clone(),
+            tx3.offset.clone(),
+        ));
+        let conflicts = find_conflicting_transactions(mempool.clone(), conflicting_tx)
+            .await
+            .unwrap();
+        assert_eq!(conflicts, vec![tx1.clone()]);
+
+        // A transaction does not conflict with itself
+        let conflicts = find_conflicting_transactions(mempool.clone(), tx1).await.unwrap();
+        assert!(conflicts.is_empty());
+
+        let conflicts = find_conflicting_transactions(mempool, Arc::new(tx3)).await.unwrap();
+        assert!(conflicts.is_empty());
+    }
+
+    #[tokio_macros::test_basic]
+    async fn it_finds_transactions_by_output_commitment() {
+        let mempool = Mempool::new(Default::default(), Arc::new(MockValidator::new(true)));
+        let consensus = ConsensusManagerBuilder::new(Network::LocalNet).build();
+        let (tx1, _, _) = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1);
+        let (tx2, _, _) = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1);
+        let (tx3, _, _) = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1);
+        insert(mempool.clone(), Arc::new(tx1.clone())).await.unwrap();
+        insert(mempool.clone(), Arc::new(tx2.clone())).await.unwrap();
+
+        let tx1_output = tx1.body.outputs()[0].commitment.clone();
+        let tx2_output = tx2.body.outputs()[0].commitment.clone();
+        let tx3_output = tx3.body.outputs()[0].commitment.clone();
+        assert!(mempool.has_unconfirmed_output(&tx1_output).unwrap());
+        assert!(!mempool.has_unconfirmed_output(&tx3_output).unwrap());
+        assert_eq!(
+            has_tx_with_output(mempool.clone(), tx1_output.clone()).await.unwrap(),
+            TxStorageResponse::UnconfirmedPool
+        );
+        assert_eq!(
+            has_tx_with_output(mempool.clone(), tx3_output).await.unwrap(),
+            TxStorageResponse::NotStored
+        );
+
+        // Publishing tx1 moves it to the reorg pool
+        let published_block = create_orphan_block(0, vec![tx1], &consensus);
+        process_published_block(mempool.clone(), Arc::new(published_block))
+            .await
+            .unwrap();
+        assert!(!mempool.has_unconfirmed_output(&tx1_output).unwrap());
+        assert_eq!(
+            has_tx_with_output(mempool.clone(), tx1_output).await.unwrap(),
+            TxStorageResponse::ReorgPool
+        );
+        assert_eq!(
+            has_tx_with_output(mempool, tx2_output).await.unwrap(),
+            TxStorageResponse::UnconfirmedPool
+        );
+    }
+}
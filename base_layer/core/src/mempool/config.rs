@@ -30,6 +30,12 @@ use tari_common::{configuration::seconds, NetworkConfigPath};
 pub struct MempoolConfig {
     pub unconfirmed_pool: UnconfirmedPoolConfig,
     pub reorg_pool: ReorgPoolConfig,
+    /// The maximum number of kernels that a transaction can have to be admitted, this bounds the cost of validating
+    /// large aggregated transactions independently of their weight.
+    pub max_kernels_per_transaction: usize,
+    /// If true, a DoubleSpendAttempt event is published when a transaction is admitted that spends an input already
+    /// spent by a pooled transaction. Attempts are always logged. Default: false
+    pub double_spend_alerts: bool,
 }
 
 impl Default for MempoolConfig {
@@ -37,6 +43,8 @@ impl Default for MempoolConfig {
         Self {
             unconfirmed_pool: UnconfirmedPoolConfig::default(),
             reorg_pool: ReorgPoolConfig::default(),
+            max_kernels_per_transaction: consts::MEMPOOL_MAX_KERNELS_PER_TRANSACTION,
+            double_spend_alerts: false,
         }
     }
 }
@@ -58,6 +66,15 @@ pub struct MempoolServiceConfig {
     pub initial_sync_num_peers: usize,
     /// The maximum number of transactions to sync in a single sync session Default: 10_000
     pub initial_sync_max_transactions: usize,
+    /// If false, this node asks peers not to push their transactions to it during a mempool sync. Instead, the peer
+    /// sends an inventory and this node pulls the transactions it is missing. The peer also stops propagating new
+    /// transactions to this node in full while it is connected. Default: true
+    pub relay_transactions: bool,
+    /// If true, new transactions are announced to connected base nodes by their excess signature and peers request
+    /// only the transactions they do not already have, instead of the full transactions being propagated. Peers that
+    /// do not support announcements still receive the full transactions.
+    /// Default: false
+    pub compact_gossip: bool,
 }
 
 impl Default for MempoolServiceConfig {
@@ -66,6 +83,8 @@ impl Default for MempoolServiceConfig {
             request_timeout: consts::MEMPOOL_SERVICE_REQUEST_TIMEOUT,
             initial_sync_num_peers: 2,
             initial_sync_max_transactions: 10_000,
+            relay_transactions: true,
+            compact_gossip: false,
         }
     }
 }
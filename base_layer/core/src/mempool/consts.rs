@@ -27,6 +27,19 @@ pub const MEMPOOL_UNCONFIRMED_POOL_STORAGE_CAPACITY: usize = 40_000;
 /// The maximum number of transactions that can be skipped when compiling a set of highest priority transactions,
 /// skipping over large transactions are performed in an attempt to fit more transactions into the remaining space.
 pub const MEMPOOL_UNCONFIRMED_POOL_WEIGHT_TRANSACTION_SKIP_COUNT: usize = 20;
+/// The maximum total serialized size, in bytes, of the transactions stored in the Unconfirmed Transaction pool
+pub const MEMPOOL_UNCONFIRMED_POOL_MAX_BYTES: u64 = 100 * 1024 * 1024;
+
+/// The maximum number of kernels that a transaction can have to be admitted into the mempool
+pub const MEMPOOL_MAX_KERNELS_PER_TRANSACTION: usize = 100;
+
+/// The number of most recent transaction validations included in the validation timing histogram
+pub const MEMPOOL_VALIDATION_TIMING_WINDOW_SIZE: usize = 1_000;
+
+/// The number of double spend attempts buffered for each subscriber before the oldest are dropped
+pub const MEMPOOL_DOUBLE_SPEND_EVENT_BUFFER_SIZE: usize = 100;
+/// The number of Mempool state events buffered for each subscriber before the oldest are dropped
+pub const MEMPOOL_STATE_EVENT_BUFFER_SIZE: usize = 1_000;
 
 /// The maximum number of transactions that can be stored in the Reorg pool
 pub const MEMPOOL_REORG_POOL_STORAGE_CAPACITY: usize = 5_000;
@@ -21,19 +21,30 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    blocks::Block,
+    blocks::{Block, ShortTxId},
     mempool::{
         error::MempoolError,
         mempool_storage::MempoolStorage,
+        DoubleSpendAttempt,
+        FeeHistogramResponse,
         MempoolConfig,
+        MempoolStateEvent,
         StateResponse,
         StatsResponse,
+        TransactionPackage,
+        TransactionPolicy,
         TxStorageResponse,
+        ValidationTimingResponse,
     },
-    transactions::{transaction::Transaction, types::Signature},
-    validation::MempoolTransactionValidation,
+    transactions::{
+        transaction::Transaction,
+        types::{Commitment, Signature},
+    },
+    validation::{MempoolTransactionValidation, ValidationMetricsSnapshot},
 };
 use std::sync::{Arc, RwLock};
+use tari_common_types::types::BlockHash;
+use tokio::sync::broadcast;
 
 /// The Mempool consists of an Unconfirmed Transaction Pool, Pending Pool, Orphan Pool and Reorg Pool and is responsible
 /// for managing and maintaining all unconfirmed transactions have not yet been included in a block, and transactions
@@ -51,6 +62,15 @@ impl Mempool {
         }
     }
 
+    /// Register a node-local policy that is consulted before the consensus validators when admitting transactions.
+    pub fn register_policy(&self, policy: Arc<dyn TransactionPolicy>) -> Result<(), MempoolError> {
+        self.pool_storage
+            .write()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .register_policy(policy);
+        Ok(())
+    }
+
     /// Insert an unconfirmed transaction into the Mempool. The transaction *MUST* have passed through the validation
     /// pipeline already and will thus always be internally consistent by this stage
     pub fn insert(&self, tx: Arc<Transaction>) -> Result<TxStorageResponse, MempoolError> {
@@ -100,6 +120,29 @@ impl Mempool {
             .retrieve(total_weight)
     }
 
+    /// Returns the unconfirmed transactions with the highest fee per gram, oldest first for equal fees, up to a total
+    /// weight of `weight_limit`.
+    pub fn retrieve_highest_priority_txs(&self, weight_limit: u64) -> Result<Vec<Arc<Transaction>>, MempoolError> {
+        self.pool_storage
+            .read()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .retrieve_highest_priority_txs(weight_limit)
+    }
+
+    /// Returns the transactions in the unconfirmed and reorg pools that have a kernel matching any of the provided
+    /// short ids of the block with the given hash. This is used to reconstruct compact blocks.
+    pub fn retrieve_by_short_ids(
+        &self,
+        block_hash: BlockHash,
+        short_ids: Vec<ShortTxId>,
+    ) -> Result<Vec<Arc<Transaction>>, MempoolError>
+    {
+        self.pool_storage
+            .read()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .retrieve_by_short_ids(block_hash, short_ids)
+    }
+
     /// Check if the specified transaction is stored in the Mempool.
     pub fn has_tx_with_excess_sig(&self, excess_sig: Signature) -> Result<TxStorageResponse, MempoolError> {
         self.pool_storage
@@ -108,6 +151,70 @@ impl Mempool {
             .has_tx_with_excess_sig(excess_sig)
     }
 
+    /// Check if a transaction that creates the output with the provided commitment is stored in the unconfirmed pool.
+    pub fn has_unconfirmed_output(&self, commitment: &Commitment) -> Result<bool, MempoolError> {
+        Ok(self
+            .pool_storage
+            .read()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .has_unconfirmed_output(commitment))
+    }
+
+    /// Check which pool, if any, stores a transaction that creates the output with the provided commitment.
+    pub fn has_tx_with_output(&self, commitment: Commitment) -> Result<TxStorageResponse, MempoolError> {
+        self.pool_storage
+            .read()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .has_tx_with_output(commitment)
+    }
+
+    /// Returns the unconfirmed transactions that the transaction with the provided excess signature depends on and
+    /// that depend on it, along with their aggregate fees and weights. Returns None if the transaction is not stored
+    /// in the unconfirmed pool.
+    pub fn transaction_package(&self, excess_sig: Signature) -> Result<Option<TransactionPackage>, MempoolError> {
+        self.pool_storage
+            .read()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .transaction_package(excess_sig)
+    }
+
+    /// Returns the unconfirmed transactions in the Mempool that spend any of the inputs of the provided transaction.
+    pub fn find_conflicting_transactions(&self, tx: Arc<Transaction>) -> Result<Vec<Arc<Transaction>>, MempoolError> {
+        self.pool_storage
+            .read()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .find_conflicting_transactions(tx)
+    }
+
+    /// Subscribe to the double spend attempts detected when admitting transactions. Attempts are only published if
+    /// double spend alerts are enabled in the MempoolConfig.
+    pub fn subscribe_double_spend_attempts(&self) -> Result<broadcast::Receiver<DoubleSpendAttempt>, MempoolError> {
+        Ok(self
+            .pool_storage
+            .read()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .subscribe_double_spend_attempts())
+    }
+
+    /// Subscribe to the events published when transactions are added to or removed from the unconfirmed pool, and to
+    /// the aggregate `Updated` event published after each change.
+    pub fn subscribe_events(&self) -> Result<broadcast::Receiver<MempoolStateEvent>, MempoolError> {
+        Ok(self
+            .pool_storage
+            .read()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .subscribe_events())
+    }
+
+    /// Returns the publisher of the events that can be subscribed to with `subscribe_events`.
+    pub fn event_publisher(&self) -> Result<broadcast::Sender<MempoolStateEvent>, MempoolError> {
+        Ok(self
+            .pool_storage
+            .read()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .event_publisher())
+    }
+
     /// Gathers and returns the stats of the Mempool.
     pub fn stats(&self) -> Result<StatsResponse, MempoolError> {
         self.pool_storage
@@ -116,6 +223,31 @@ impl Mempool {
             .stats()
     }
 
+    /// Returns a histogram of the time spent validating the most recently submitted transactions.
+    pub fn validation_timing(&self) -> Result<ValidationTimingResponse, MempoolError> {
+        self.pool_storage
+            .read()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .validation_timing()
+    }
+
+    /// Returns a histogram of the fee per gram paid by the unconfirmed transactions.
+    pub fn fee_histogram(&self) -> Result<FeeHistogramResponse, MempoolError> {
+        self.pool_storage
+            .read()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .fee_histogram()
+    }
+
+    /// Returns the number of transactions rejected by validation, by `ValidationError` variant
+    pub fn validation_metrics(&self) -> Result<ValidationMetricsSnapshot, MempoolError> {
+        Ok(self
+            .pool_storage
+            .read()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .validation_metrics())
+    }
+
     /// Gathers and returns a breakdown of all the transaction in the Mempool.
     pub fn state(&self) -> Result<StateResponse, MempoolError> {
         self.pool_storage
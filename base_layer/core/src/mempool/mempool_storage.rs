@@ -21,22 +21,41 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    blocks::Block,
+    blocks::{short_tx_id, Block, ShortTxId},
     mempool::{
+        consts::{
+            MEMPOOL_DOUBLE_SPEND_EVENT_BUFFER_SIZE,
+            MEMPOOL_STATE_EVENT_BUFFER_SIZE,
+            MEMPOOL_VALIDATION_TIMING_WINDOW_SIZE,
+        },
         error::MempoolError,
         reorg_pool::ReorgPool,
         unconfirmed_pool::UnconfirmedPool,
+        validation_timing::ValidationTimer,
+        DoubleSpendAttempt,
+        FeeHistogramResponse,
         MempoolConfig,
+        MempoolStateEvent,
+        PolicyViolation,
         StateResponse,
         StatsResponse,
+        TransactionPackage,
+        TransactionPolicy,
+        TxRemovalReason,
         TxStorageResponse,
+        ValidationTimingResponse,
     },
-    transactions::{transaction::Transaction, types::Signature},
-    validation::{MempoolTransactionValidation, ValidationError},
+    transactions::{
+        transaction::Transaction,
+        types::{Commitment, Signature},
+    },
+    validation::{MempoolTransactionValidation, ValidationError, ValidationMetrics, ValidationMetricsSnapshot},
 };
 use log::*;
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc, time::Instant};
+use tari_common_types::types::BlockHash;
 use tari_crypto::tari_utilities::{hex::Hex, Hashable};
+use tokio::sync::broadcast;
 
 pub const LOG_TARGET: &str = "c::mp::mempool";
 
@@ -47,16 +66,113 @@ pub struct MempoolStorage {
     unconfirmed_pool: UnconfirmedPool,
     reorg_pool: ReorgPool,
     validator: Arc<dyn MempoolTransactionValidation>,
+    policies: Vec<Arc<dyn TransactionPolicy>>,
+    max_kernels_per_transaction: usize,
+    validation_timer: ValidationTimer,
+    validation_metrics: ValidationMetrics,
+    double_spend_alerts: bool,
+    double_spend_publisher: broadcast::Sender<DoubleSpendAttempt>,
+    event_publisher: broadcast::Sender<MempoolStateEvent>,
 }
 
 impl MempoolStorage {
     /// Create a new Mempool with an UnconfirmedPool, OrphanPool, PendingPool and ReOrgPool.
     pub fn new(config: MempoolConfig, validators: Arc<dyn MempoolTransactionValidation>) -> Self {
+        let (double_spend_publisher, _) = broadcast::channel(MEMPOOL_DOUBLE_SPEND_EVENT_BUFFER_SIZE);
+        let (event_publisher, _) = broadcast::channel(MEMPOOL_STATE_EVENT_BUFFER_SIZE);
         Self {
             unconfirmed_pool: UnconfirmedPool::new(config.unconfirmed_pool),
             reorg_pool: ReorgPool::new(config.reorg_pool),
             validator: validators,
+            policies: Vec::new(),
+            max_kernels_per_transaction: config.max_kernels_per_transaction,
+            validation_timer: ValidationTimer::new(MEMPOOL_VALIDATION_TIMING_WINDOW_SIZE),
+            validation_metrics: ValidationMetrics::new(),
+            double_spend_alerts: config.double_spend_alerts,
+            double_spend_publisher,
+            event_publisher,
+        }
+    }
+
+    /// Subscribe to the events published when transactions are added to or removed from the unconfirmed pool.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<MempoolStateEvent> {
+        self.event_publisher.subscribe()
+    }
+
+    /// Returns the publisher of the mempool state events so that other services can hand out subscriptions to them.
+    pub fn event_publisher(&self) -> broadcast::Sender<MempoolStateEvent> {
+        self.event_publisher.clone()
+    }
+
+    fn publish_event(&self, event: MempoolStateEvent) {
+        // Sending only fails if there are no subscribers
+        let _ = self.event_publisher.send(event);
+    }
+
+    // Publish a removal event for each of the transactions and return their excess signatures
+    fn publish_removed(&self, txs: &[Arc<Transaction>], reason: TxRemovalReason) -> Vec<Signature> {
+        let excess_sigs = excess_sigs(txs);
+        for excess_sig in &excess_sigs {
+            self.publish_event(MempoolStateEvent::TxRemoved(excess_sig.clone(), reason));
         }
+        excess_sigs
+    }
+
+    /// Subscribe to the double spend attempts detected when admitting transactions. Attempts are only published if
+    /// double spend alerts are enabled in the MempoolConfig.
+    pub fn subscribe_double_spend_attempts(&self) -> broadcast::Receiver<DoubleSpendAttempt> {
+        self.double_spend_publisher.subscribe()
+    }
+
+    // Log, and publish if enabled, every input of the transaction that is already spent by a pooled transaction.
+    fn report_double_spend_attempts(&self, tx: &Transaction) {
+        let conflicting_excess_sig = match tx.first_kernel_excess_sig() {
+            Some(sig) => sig,
+            None => return,
+        };
+        for pooled_tx in self.unconfirmed_pool.find_conflicting_txs(tx) {
+            let pooled_excess_sig = match pooled_tx.first_kernel_excess_sig() {
+                Some(sig) => sig,
+                None => continue,
+            };
+            for input in pooled_tx
+                .body
+                .inputs()
+                .iter()
+                .filter(|input| tx.body.inputs().contains(*input))
+            {
+                let attempt = DoubleSpendAttempt {
+                    input: input.commitment.clone(),
+                    pooled_excess_sig: pooled_excess_sig.clone(),
+                    conflicting_excess_sig: conflicting_excess_sig.clone(),
+                };
+                warn!(target: LOG_TARGET, "{}", attempt);
+                if self.double_spend_alerts {
+                    // Sending only fails if there are no subscribers
+                    let _ = self.double_spend_publisher.send(attempt);
+                }
+            }
+        }
+    }
+
+    /// Register a node-local policy that transactions have to satisfy before they are validated and admitted.
+    pub fn register_policy(&mut self, policy: Arc<dyn TransactionPolicy>) {
+        self.policies.push(policy);
+    }
+
+    fn check_policies(&self, tx: &Transaction) -> Result<(), PolicyViolation> {
+        self.policies.iter().try_for_each(|policy| policy.check(tx))
+    }
+
+    // Inputs that spend outputs of transactions in the UnconfirmedPool are resolved by the pool and do not exist in the
+    // blockchain yet, so they are exempt from the UTXO set checks of the chain validator.
+    fn unconfirmed_inputs(&self, tx: &Transaction) -> Vec<Commitment> {
+        tx.body
+            .inputs()
+            .iter()
+            .filter(|input| self.unconfirmed_pool.has_tx_with_output(&input.commitment))
+            .map(|input| input.commitment.clone())
+            .collect()
     }
 
     /// Insert an unconfirmed transaction into the Mempool. The transaction *MUST* have passed through the validation
@@ -72,10 +188,45 @@ impl MempoolStorage {
                 .unwrap_or_else(|| "None".into())
         );
 
-        match self.validator.validate(&tx) {
+        let num_kernels = tx.body.kernels().len();
+        if num_kernels > self.max_kernels_per_transaction {
+            warn!(
+                target: LOG_TARGET,
+                "Transaction has {} kernels which exceeds the maximum of {}",
+                num_kernels,
+                self.max_kernels_per_transaction
+            );
+            return Ok(TxStorageResponse::NotStored);
+        }
+
+        if let Err(e) = self.check_policies(&tx) {
+            warn!(target: LOG_TARGET, "Transaction refused by mempool policy: {}", e);
+            return Ok(TxStorageResponse::NotStoredPolicyViolation);
+        }
+
+        let timer = Instant::now();
+        let validation_result = self
+            .validator
+            .validate_with_unconfirmed_inputs(&tx, &self.unconfirmed_inputs(&tx));
+        self.validation_timer.record(timer.elapsed());
+        if let Err(err) = &validation_result {
+            self.validation_metrics.record_rejection(err);
+        }
+        match validation_result {
             Ok(()) => {
-                self.unconfirmed_pool.insert(tx)?;
-                Ok(TxStorageResponse::UnconfirmedPool)
+                self.report_double_spend_attempts(&tx);
+                let is_new = tx
+                    .first_kernel_excess_sig()
+                    .map_or(false, |sig| !self.unconfirmed_pool.has_tx_with_excess_sig(sig));
+                let (response, evicted_txs) = self.unconfirmed_pool.insert_and_evict(tx.clone())?;
+                self.publish_removed(&evicted_txs, TxRemovalReason::Evicted);
+                if is_new && response == TxStorageResponse::UnconfirmedPool {
+                    if let Some(sig) = tx.first_kernel_excess_sig() {
+                        self.publish_event(MempoolStateEvent::TxAdded(sig.clone()));
+                    }
+                    self.publish_event(MempoolStateEvent::Updated);
+                }
+                Ok(response)
             },
             Err(ValidationError::UnknownInputs) => {
                 warn!(target: LOG_TARGET, "Validation failed due to unknown inputs");
@@ -96,32 +247,38 @@ impl MempoolStorage {
         }
     }
 
-    // Insert a set of new transactions into the UTxPool.
-    fn insert_txs(&mut self, txs: Vec<Arc<Transaction>>) -> Result<(), MempoolError> {
+    // Insert a set of new transactions into the UTxPool and return the excess signatures of the transactions that were
+    // stored in the unconfirmed pool.
+    fn insert_txs(&mut self, txs: Vec<Arc<Transaction>>) -> Result<Vec<Signature>, MempoolError> {
+        let mut stored_txs = Vec::new();
         for tx in txs {
-            self.insert(tx)?;
+            if self.insert(tx.clone())? == TxStorageResponse::UnconfirmedPool {
+                stored_txs.push(tx);
+            }
         }
-        Ok(())
+        Ok(excess_sigs(&stored_txs))
     }
 
     /// Update the Mempool based on the received published block.
     pub fn process_published_block(&mut self, published_block: Arc<Block>) -> Result<(), MempoolError> {
-        trace!(target: LOG_TARGET, "Mempool processing new block: {}", published_block);
-        // Move published txs to ReOrgPool and discard double spends
-        self.reorg_pool.insert_txs(
-            self.unconfirmed_pool
-                .remove_published_and_discard_double_spends(&published_block),
-        )?;
-
+        if !self.remove_published(&published_block)?.is_empty() {
+            self.publish_event(MempoolStateEvent::Updated);
+        }
         Ok(())
     }
 
-    // Update the Mempool based on the received set of published blocks.
-    fn process_published_blocks(&mut self, published_blocks: Vec<Arc<Block>>) -> Result<(), MempoolError> {
-        for published_block in published_blocks {
-            self.process_published_block(published_block)?;
-        }
-        Ok(())
+    // Move the published transactions of the block to the ReOrgPool and discard double spends. Returns the excess
+    // signatures of all the transactions that were removed from the unconfirmed pool.
+    fn remove_published(&mut self, published_block: &Block) -> Result<Vec<Signature>, MempoolError> {
+        trace!(target: LOG_TARGET, "Mempool processing new block: {}", published_block);
+        let (published_txs, discarded_txs) = self
+            .unconfirmed_pool
+            .remove_published_and_discard_double_spends(published_block);
+        let mut removed = self.publish_removed(&published_txs, TxRemovalReason::Mined);
+        removed.extend(self.publish_removed(&discarded_txs, TxRemovalReason::DoubleSpend));
+        self.reorg_pool.insert_txs(published_txs)?;
+
+        Ok(removed)
     }
 
     /// In the event of a ReOrg, resubmit all ReOrged transactions into the Mempool and process each newly introduced
@@ -153,11 +310,14 @@ impl MempoolStorage {
         let previous_tip = removed_blocks.last().map(|block| block.header.height);
         let new_tip = new_blocks.last().map(|block| block.header.height);
 
-        self.insert_txs(
+        let added = self.insert_txs(
             self.reorg_pool
                 .remove_reorged_txs_and_discard_double_spends(removed_blocks, &new_blocks)?,
         )?;
-        self.process_published_blocks(new_blocks)?;
+        let mut removed = Vec::new();
+        for block in &new_blocks {
+            removed.extend(self.remove_published(block)?);
+        }
 
         if let (Some(previous_tip_height), Some(new_tip_height)) = (previous_tip, new_tip) {
             if new_tip_height < previous_tip_height {
@@ -168,7 +328,8 @@ impl MempoolStorage {
                     previous_tip_height,
                     new_tip_height,
                 );
-                self.unconfirmed_pool.remove_timelocked(new_tip_height);
+                let timelocked_txs = self.unconfirmed_pool.remove_timelocked(new_tip_height);
+                removed.extend(self.publish_removed(&timelocked_txs, TxRemovalReason::TimeLocked));
             } else {
                 debug!(
                     target: LOG_TARGET,
@@ -180,6 +341,9 @@ impl MempoolStorage {
             }
         }
 
+        self.publish_event(MempoolStateEvent::Reorg { added, removed });
+        self.publish_event(MempoolStateEvent::Updated);
+
         Ok(())
     }
 
@@ -190,12 +354,42 @@ impl MempoolStorage {
         Ok(txs)
     }
 
+    /// Returns the transactions in the unconfirmed and reorg pools that have a kernel matching any of the provided
+    /// short ids of the block with the given hash.
+    pub fn retrieve_by_short_ids(
+        &self,
+        block_hash: BlockHash,
+        short_ids: Vec<ShortTxId>,
+    ) -> Result<Vec<Arc<Transaction>>, MempoolError>
+    {
+        let short_ids = short_ids.into_iter().collect::<HashSet<_>>();
+        let txs = self
+            .unconfirmed_pool
+            .snapshot()
+            .into_iter()
+            .chain(self.reorg_pool.snapshot()?)
+            .filter(|tx| {
+                tx.body
+                    .kernels()
+                    .iter()
+                    .any(|k| short_ids.contains(&short_tx_id(&block_hash, k)))
+            })
+            .collect();
+        Ok(txs)
+    }
+
     /// Returns a list of transaction ranked by transaction priority up to a given weight.
     /// Will only return transactions that will fit into a block
     pub fn retrieve(&self, total_weight: u64) -> Result<Vec<Arc<Transaction>>, MempoolError> {
         Ok(self.unconfirmed_pool.highest_priority_txs(total_weight)?)
     }
 
+    /// Returns the unconfirmed transactions with the highest fee per gram, oldest first for equal fees, up to a total
+    /// weight of `weight_limit`.
+    pub fn retrieve_highest_priority_txs(&self, weight_limit: u64) -> Result<Vec<Arc<Transaction>>, MempoolError> {
+        Ok(self.unconfirmed_pool.highest_fee_per_gram_txs(weight_limit))
+    }
+
     /// Check if the specified transaction is stored in the Mempool.
     pub fn has_tx_with_excess_sig(&self, excess_sig: Signature) -> Result<TxStorageResponse, MempoolError> {
         if self.unconfirmed_pool.has_tx_with_excess_sig(&excess_sig) {
@@ -207,6 +401,33 @@ impl MempoolStorage {
         }
     }
 
+    /// Check if a transaction that creates the output with the provided commitment is stored in the unconfirmed pool.
+    pub fn has_unconfirmed_output(&self, commitment: &Commitment) -> bool {
+        self.unconfirmed_pool.has_tx_with_output(commitment)
+    }
+
+    /// Check which pool, if any, stores a transaction that creates the output with the provided commitment.
+    pub fn has_tx_with_output(&self, commitment: Commitment) -> Result<TxStorageResponse, MempoolError> {
+        if self.has_unconfirmed_output(&commitment) {
+            Ok(TxStorageResponse::UnconfirmedPool)
+        } else if self.reorg_pool.has_tx_with_output(&commitment)? {
+            Ok(TxStorageResponse::ReorgPool)
+        } else {
+            Ok(TxStorageResponse::NotStored)
+        }
+    }
+
+    /// Returns the ancestor and descendant package of the unconfirmed transaction with the provided excess signature,
+    /// or None if it is not stored in the unconfirmed pool.
+    pub fn transaction_package(&self, excess_sig: Signature) -> Result<Option<TransactionPackage>, MempoolError> {
+        Ok(self.unconfirmed_pool.transaction_package(&excess_sig))
+    }
+
+    /// Returns the unconfirmed transactions that spend any of the inputs of the provided transaction.
+    pub fn find_conflicting_transactions(&self, tx: Arc<Transaction>) -> Result<Vec<Arc<Transaction>>, MempoolError> {
+        Ok(self.unconfirmed_pool.find_conflicting_txs(&tx))
+    }
+
     // Returns the total number of transactions in the Mempool.
     fn len(&self) -> Result<usize, MempoolError> {
         Ok(self.unconfirmed_pool.len())
@@ -217,6 +438,21 @@ impl MempoolStorage {
         Ok(self.unconfirmed_pool.calculate_weight() + self.reorg_pool.calculate_weight()?)
     }
 
+    /// Returns a histogram of the time spent validating the most recently submitted transactions.
+    pub fn validation_timing(&self) -> Result<ValidationTimingResponse, MempoolError> {
+        Ok(self.validation_timer.histogram())
+    }
+
+    /// Returns a histogram of the fee per gram paid by the unconfirmed transactions.
+    pub fn fee_histogram(&self) -> Result<FeeHistogramResponse, MempoolError> {
+        Ok(self.unconfirmed_pool.fee_histogram())
+    }
+
+    /// Returns the number of transactions rejected by validation, by `ValidationError` variant
+    pub fn validation_metrics(&self) -> ValidationMetricsSnapshot {
+        self.validation_metrics.snapshot()
+    }
+
     /// Gathers and returns the stats of the Mempool.
     pub fn stats(&self) -> Result<StatsResponse, MempoolError> {
         Ok(StatsResponse {
@@ -224,6 +460,8 @@ impl MempoolStorage {
             unconfirmed_txs: self.unconfirmed_pool.len(),
             reorg_txs: self.reorg_pool.len()?,
             total_weight: self.calculate_weight()?,
+            avg_fee_per_gram: self.unconfirmed_pool.calculate_avg_fee_per_gram(),
+            min_fee_per_gram: self.unconfirmed_pool.calculate_min_fee_per_gram(),
         })
     }
 
@@ -247,3 +485,10 @@ impl MempoolStorage {
         })
     }
 }
+
+fn excess_sigs(txs: &[Arc<Transaction>]) -> Vec<Signature> {
+    txs.iter()
+        .filter_map(|tx| tx.first_kernel_excess_sig())
+        .cloned()
+        .collect()
+}
@@ -35,6 +35,10 @@ mod mempool;
 #[cfg(feature = "base_node")]
 mod mempool_storage;
 #[cfg(feature = "base_node")]
+mod policy;
+#[cfg(feature = "base_node")]
+pub use policy::{PolicyViolation, TransactionPolicy};
+#[cfg(feature = "base_node")]
 mod priority;
 #[cfg(feature = "base_node")]
 mod reorg_pool;
@@ -46,6 +50,8 @@ pub use rpc::create_mempool_rpc_service;
 pub use rpc::{MempoolRpcClient, MempoolRpcServer, MempoolRpcService, MempoolService};
 #[cfg(feature = "base_node")]
 mod unconfirmed_pool;
+#[cfg(feature = "base_node")]
+mod validation_timing;
 
 // public modules
 #[cfg(feature = "base_node")]
@@ -70,9 +76,13 @@ pub use service::{MempoolServiceError, MempoolServiceInitializer, OutboundMempoo
 #[cfg(feature = "base_node")]
 mod sync_protocol;
 #[cfg(feature = "base_node")]
-pub use sync_protocol::MempoolSyncInitializer;
+pub use sync_protocol::{MempoolSyncInitializer, TransactionAnnouncer};
 
-use crate::transactions::{transaction::Transaction, types::Signature};
+use crate::transactions::{
+    tari_amount::MicroTari,
+    transaction::Transaction,
+    types::{Commitment, Signature},
+};
 use core::fmt::{Display, Error, Formatter};
 use serde::{Deserialize, Serialize};
 use tari_crypto::tari_utilities::hex::Hex;
@@ -83,18 +93,77 @@ pub struct StatsResponse {
     pub unconfirmed_txs: usize,
     pub reorg_txs: usize,
     pub total_weight: u64,
+    /// The total fees of the unconfirmed transactions divided by their total weight, in µT per gram
+    pub avg_fee_per_gram: u64,
+    /// The lowest fee per gram of an unconfirmed transaction, in µT per gram
+    pub min_fee_per_gram: u64,
 }
 
 impl Display for StatsResponse {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
         write!(
             fmt,
-            "Mempool stats: Total transactions: {}, Unconfirmed: {}, Published: {}, Total Weight: {}",
-            self.total_txs, self.unconfirmed_txs, self.reorg_txs, self.total_weight
+            "Mempool stats: Total transactions: {}, Unconfirmed: {}, Published: {}, Total Weight: {}, Average Fee per \
+             Gram: {}, Minimum Fee per Gram: {}",
+            self.total_txs,
+            self.unconfirmed_txs,
+            self.reorg_txs,
+            self.total_weight,
+            self.avg_fee_per_gram,
+            self.min_fee_per_gram
         )
     }
 }
 
+/// A histogram of the time spent validating the most recently submitted transactions.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ValidationTimingResponse {
+    pub num_validations: u64,
+    pub mean_duration_us: u64,
+    pub max_duration_us: u64,
+    pub buckets: Vec<ValidationTimingBucket>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ValidationTimingBucket {
+    /// The inclusive upper bound of the bucket in microseconds, the last bucket has an upper bound of `u64::MAX`
+    pub upper_bound_us: u64,
+    pub count: u64,
+}
+
+impl Display for ValidationTimingResponse {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        writeln!(
+            fmt,
+            "Mempool validation timing: Validations: {}, Mean: {}µs, Max: {}µs",
+            self.num_validations, self.mean_duration_us, self.max_duration_us
+        )?;
+        for bucket in &self.buckets {
+            if bucket.upper_bound_us == u64::MAX {
+                writeln!(fmt, "    >  previous: {}", bucket.count)?;
+            } else {
+                writeln!(fmt, "    <= {}µs: {}", bucket.upper_bound_us, bucket.count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A histogram of the fee per gram paid by the transactions in the unconfirmed pool, ordered from the highest to the
+/// lowest fee per gram.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FeeHistogramResponse {
+    pub buckets: Vec<FeeHistogramBucket>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FeeHistogramBucket {
+    /// The fee per gram, in µT per gram, paid by the transactions in the bucket
+    pub fee_per_gram: u64,
+    pub transaction_count: u64,
+    pub weight: u64,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct StateResponse {
     pub unconfirmed_pool: Vec<Transaction>,
@@ -125,6 +194,36 @@ impl Display for StateResponse {
     }
 }
 
+/// The unconfirmed transactions that a transaction depends on (ancestors) and that depend on it (descendants), along
+/// with the aggregate fees and weights of the packages the transaction forms with each set. A miner has to include all
+/// the ancestors of a transaction to include it, so the ancestor fee per gram is the effective fee rate of the
+/// transaction.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransactionPackage {
+    pub ancestors: Vec<Transaction>,
+    pub descendants: Vec<Transaction>,
+    /// The total fee of the transaction and its ancestors
+    pub ancestor_fee: MicroTari,
+    /// The total weight of the transaction and its ancestors
+    pub ancestor_weight: u64,
+    /// The total fee of the transaction and its descendants
+    pub descendant_fee: MicroTari,
+    /// The total weight of the transaction and its descendants
+    pub descendant_weight: u64,
+}
+
+impl TransactionPackage {
+    /// The effective fee per gram of the transaction when mined together with its ancestors
+    pub fn ancestor_fee_per_gram(&self) -> f64 {
+        self.ancestor_fee.0 as f64 / self.ancestor_weight as f64
+    }
+
+    /// The fee per gram of the transaction when mined together with its descendants
+    pub fn descendant_fee_per_gram(&self) -> f64 {
+        self.descendant_fee.0 as f64 / self.descendant_weight as f64
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum TxStorageResponse {
     UnconfirmedPool,
@@ -132,6 +231,7 @@ pub enum TxStorageResponse {
     NotStoredOrphan,
     NotStoredTimeLocked,
     NotStoredAlreadySpent,
+    NotStoredPolicyViolation,
     NotStored,
 }
 
@@ -149,6 +249,7 @@ impl Display for TxStorageResponse {
             TxStorageResponse::NotStoredOrphan => "Not stored orphan transaction",
             TxStorageResponse::NotStoredTimeLocked => "Not stored time locked transaction",
             TxStorageResponse::NotStoredAlreadySpent => "Not stored output already spent",
+            TxStorageResponse::NotStoredPolicyViolation => "Not stored due to a mempool policy violation",
             TxStorageResponse::NotStored => "Not stored",
         };
         fmt.write_str(&storage)
@@ -156,7 +257,66 @@ impl Display for TxStorageResponse {
 }
 
 /// Events that can be published on state changes of the Mempool
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum MempoolStateEvent {
+    /// The contents of the Mempool changed. This is published once after the detailed events of each change.
     Updated,
+    /// A transaction with the provided excess signature was added to the unconfirmed pool
+    TxAdded(Signature),
+    /// A transaction with the provided excess signature was removed from the unconfirmed pool
+    TxRemoved(Signature, TxRemovalReason),
+    /// A reorg returned the `added` transactions to the unconfirmed pool and removed the `removed` ones from it
+    Reorg {
+        added: Vec<Signature>,
+        removed: Vec<Signature>,
+    },
+}
+
+/// The reason that a transaction was removed from the unconfirmed pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxRemovalReason {
+    /// The transaction was included in a block and moved to the reorg pool
+    Mined,
+    /// The transaction spends an input that was spent by a block
+    DoubleSpend,
+    /// The transaction was evicted to make space for a higher priority transaction
+    Evicted,
+    /// The transaction became time-locked after the chain height was reduced by a reorg
+    TimeLocked,
+}
+
+impl Display for TxRemovalReason {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        let reason = match self {
+            TxRemovalReason::Mined => "Mined",
+            TxRemovalReason::DoubleSpend => "Double spend",
+            TxRemovalReason::Evicted => "Evicted",
+            TxRemovalReason::TimeLocked => "Time locked",
+        };
+        fmt.write_str(reason)
+    }
+}
+
+/// A record of a transaction being admitted to the Mempool while spending an input that is already spent by a pooled
+/// transaction. Zero-conf recipients can use this to detect an attempt to double spend a payment to them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DoubleSpendAttempt {
+    /// The commitment of the input spent by both transactions
+    pub input: Commitment,
+    /// The excess signature of the transaction that was already stored in the Mempool
+    pub pooled_excess_sig: Signature,
+    /// The excess signature of the newly submitted transaction
+    pub conflicting_excess_sig: Signature,
+}
+
+impl Display for DoubleSpendAttempt {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            fmt,
+            "Double spend attempt on input {}: pooled transaction {}, conflicting transaction {}",
+            self.input.to_hex(),
+            self.pooled_excess_sig.get_signature().to_hex(),
+            self.conflicting_excess_sig.get_signature().to_hex()
+        )
+    }
 }
@@ -27,4 +27,6 @@ use thiserror::Error;
 pub enum PriorityError {
     #[error("Message format error: `{0}`")]
     MessageFormatError(#[from] MessageFormatError),
+    #[error("Serialization error: `{0}`")]
+    SerializationError(#[from] bincode::Error),
 }
@@ -26,5 +26,5 @@ mod timelocked_transaction;
 
 // Public re-exports
 pub use error::PriorityError;
-pub use prioritized_transaction::{FeePriority, PrioritizedTransaction};
+pub use prioritized_transaction::{compare_fee_per_gram_then_age, FeePriority, PrioritizedTransaction};
 pub use timelocked_transaction::{TimelockPriority, TimelockedTransaction};
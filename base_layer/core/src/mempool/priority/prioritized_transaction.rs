@@ -21,7 +21,7 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{mempool::priority::PriorityError, transactions::transaction::Transaction};
-use std::{convert::TryFrom, sync::Arc};
+use std::{cmp::Ordering, sync::Arc};
 use tari_crypto::tari_utilities::message_format::MessageFormat;
 
 /// Create a unique unspent transaction priority based on the transaction fee, maturity of the oldest input UTXO and the
@@ -58,16 +58,29 @@ pub struct PrioritizedTransaction {
     pub transaction: Arc<Transaction>,
     pub priority: FeePriority,
     pub weight: u64,
+    /// The serialized size of the transaction in bytes
+    pub size: u64,
+    /// Increases with every transaction inserted into the pool, so a lower value indicates an older transaction
+    pub insert_epoch: u64,
 }
 
-impl TryFrom<Transaction> for PrioritizedTransaction {
-    type Error = PriorityError;
-
-    fn try_from(transaction: Transaction) -> Result<Self, Self::Error> {
+impl PrioritizedTransaction {
+    pub fn try_construct(transaction: Transaction, insert_epoch: u64) -> Result<Self, PriorityError> {
         Ok(Self {
             priority: FeePriority::try_from(&transaction)?,
             weight: transaction.calculate_weight(),
+            size: bincode::serialized_size(&transaction)?,
             transaction: Arc::new(transaction),
+            insert_epoch,
         })
     }
 }
+
+/// Orders transactions from the highest to the lowest priority. Transactions paying a higher fee per gram are ordered
+/// first and transactions paying the same fee per gram are ordered from the oldest to the newest.
+pub fn compare_fee_per_gram_then_age(a: &PrioritizedTransaction, b: &PrioritizedTransaction) -> Ordering {
+    // Cross multiply to compare fee/weight exactly
+    let a_fee = u128::from(a.transaction.body.get_total_fee().0) * u128::from(b.weight);
+    let b_fee = u128::from(b.transaction.body.get_total_fee().0) * u128::from(a.weight);
+    b_fee.cmp(&a_fee).then_with(|| a.insert_epoch.cmp(&b.insert_epoch))
+}
@@ -41,6 +41,11 @@ impl TryInto<MempoolRequest> for ProtoMempoolRequest {
                 excess_sig.try_into().map_err(|err: ByteArrayError| err.to_string())?,
             ),
             SubmitTransaction(tx) => MempoolRequest::SubmitTransaction(tx.try_into()?),
+            GetValidationTiming(_) => MempoolRequest::GetValidationTiming,
+            GetTxStateByOutputCommitment(commitment) => MempoolRequest::GetTxStateByOutputCommitment(
+                commitment.try_into().map_err(|err: ByteArrayError| err.to_string())?,
+            ),
+            GetFeeHistogram(_) => MempoolRequest::GetFeeHistogram,
         };
         Ok(request)
     }
@@ -54,6 +59,11 @@ impl From<MempoolRequest> for ProtoMempoolRequest {
             GetState => ProtoMempoolRequest::GetState(true),
             GetTxStateByExcessSig(excess_sig) => ProtoMempoolRequest::GetTxStateByExcessSig(excess_sig.into()),
             SubmitTransaction(tx) => ProtoMempoolRequest::SubmitTransaction(tx.into()),
+            GetValidationTiming => ProtoMempoolRequest::GetValidationTiming(true),
+            GetTxStateByOutputCommitment(commitment) => {
+                ProtoMempoolRequest::GetTxStateByOutputCommitment(commitment.into())
+            },
+            GetFeeHistogram => ProtoMempoolRequest::GetFeeHistogram(true),
         }
     }
 }
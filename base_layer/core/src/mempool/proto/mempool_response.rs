@@ -43,6 +43,8 @@ impl TryInto<MempoolResponse> for ProtoMempoolResponse {
                     .ok_or_else(|| "Invalid or unrecognised `TxStorageResponse` enum".to_string())?;
                 MempoolResponse::TxStorage(tx_storage_response.try_into()?)
             },
+            ValidationTiming(timing) => MempoolResponse::ValidationTiming(timing.into()),
+            FeeHistogram(histogram) => MempoolResponse::FeeHistogram(histogram.into()),
         };
         Ok(response)
     }
@@ -72,6 +74,8 @@ impl From<MempoolResponse> for ProtoMempoolResponse {
                 let tx_storage_response: ProtoTxStorageResponse = tx_storage_response.into();
                 ProtoMempoolResponse::TxStorage(tx_storage_response.into())
             },
+            ValidationTiming(timing) => ProtoMempoolResponse::ValidationTiming(timing.into()),
+            FeeHistogram(histogram) => ProtoMempoolResponse::FeeHistogram(histogram.into()),
         }
     }
 }
@@ -33,8 +33,10 @@ pub use mempool::{
 
 mod sync_protocol;
 // TODO: Clean up
+pub mod fee_histogram_response;
 pub mod mempool_request;
 pub mod mempool_response;
 pub mod state_response;
 pub mod stats_response;
 pub mod tx_storage_response;
+pub mod validation_timing_response;
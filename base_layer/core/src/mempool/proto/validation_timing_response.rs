@@ -0,0 +1,66 @@
+// Copyright 2019. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::mempool::{
+    proto::mempool::{
+        ValidationTimingBucket as ProtoValidationTimingBucket,
+        ValidationTimingResponse as ProtoValidationTimingResponse,
+    },
+    ValidationTimingBucket,
+    ValidationTimingResponse,
+};
+
+impl From<ProtoValidationTimingResponse> for ValidationTimingResponse {
+    fn from(timing: ProtoValidationTimingResponse) -> Self {
+        Self {
+            num_validations: timing.num_validations,
+            mean_duration_us: timing.mean_duration_us,
+            max_duration_us: timing.max_duration_us,
+            buckets: timing
+                .buckets
+                .into_iter()
+                .map(|b| ValidationTimingBucket {
+                    upper_bound_us: b.upper_bound_us,
+                    count: b.count,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<ValidationTimingResponse> for ProtoValidationTimingResponse {
+    fn from(timing: ValidationTimingResponse) -> Self {
+        Self {
+            num_validations: timing.num_validations,
+            mean_duration_us: timing.mean_duration_us,
+            max_duration_us: timing.max_duration_us,
+            buckets: timing
+                .buckets
+                .into_iter()
+                .map(|b| ProtoValidationTimingBucket {
+                    upper_bound_us: b.upper_bound_us,
+                    count: b.count,
+                })
+                .collect(),
+        }
+    }
+}
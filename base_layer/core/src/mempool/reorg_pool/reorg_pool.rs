@@ -26,7 +26,10 @@ use crate::{
         consts::{MEMPOOL_REORG_POOL_CACHE_TTL, MEMPOOL_REORG_POOL_STORAGE_CAPACITY},
         reorg_pool::{ReorgPoolError, ReorgPoolStorage},
     },
-    transactions::{transaction::Transaction, types::Signature},
+    transactions::{
+        transaction::Transaction,
+        types::{Commitment, Signature},
+    },
 };
 use serde::{Deserialize, Serialize};
 use std::{
@@ -100,6 +103,15 @@ impl ReorgPool {
             .has_tx_with_excess_sig(excess_sig))
     }
 
+    /// Check if a transaction that creates the output with the provided commitment is stored in the ReorgPool
+    pub fn has_tx_with_output(&self, commitment: &Commitment) -> Result<bool, ReorgPoolError> {
+        Ok(self
+            .pool_storage
+            .read()
+            .map_err(|e| ReorgPoolError::BackendError(e.to_string()))?
+            .has_tx_with_output(commitment))
+    }
+
     /// Remove the transactions from the ReorgPool that were used in provided removed blocks. The transactions can be
     /// resubmitted to the Unconfirmed Pool.
     pub fn remove_reorged_txs_and_discard_double_spends(
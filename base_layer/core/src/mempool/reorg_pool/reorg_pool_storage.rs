@@ -23,7 +23,10 @@
 use crate::{
     blocks::Block,
     mempool::reorg_pool::reorg_pool::ReorgPoolConfig,
-    transactions::{transaction::Transaction, types::Signature},
+    transactions::{
+        transaction::Transaction,
+        types::{Commitment, Signature},
+    },
 };
 use log::*;
 use std::sync::Arc;
@@ -77,6 +80,13 @@ impl ReorgPoolStorage {
         self.txs_by_signature.contains_key(excess_sig)
     }
 
+    /// Check if a transaction that creates the output with the provided commitment is stored in the ReorgPoolStorage
+    pub fn has_tx_with_output(&self, commitment: &Commitment) -> bool {
+        self.txs_by_signature
+            .iter()
+            .any(|(_, tx)| tx.body.outputs().iter().any(|output| &output.commitment == commitment))
+    }
+
     /// Remove double-spends from the ReorgPool. These transactions were orphaned by the provided published
     /// block. Check if any of the transactions in the ReorgPool has inputs that was spent by the provided
     /// published block.
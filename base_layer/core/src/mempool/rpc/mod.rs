@@ -29,8 +29,8 @@ mod test;
 use crate::{
     mempool::service::MempoolHandle,
     proto::{
-        mempool::{StateResponse, StatsResponse, TxStorage},
-        types::{Signature, Transaction},
+        mempool::{StateResponse, StatsResponse, TxStorage, ValidationTimingResponse},
+        types::{Commitment, Signature, Transaction},
     },
 };
 use tari_comms::protocol::rpc::{Request, Response, RpcStatus};
@@ -52,6 +52,18 @@ pub trait MempoolService: Send + Sync + 'static {
 
     #[rpc(method = 4)]
     async fn submit_transaction(&self, request: Request<Transaction>) -> Result<Response<TxStorage>, RpcStatus>;
+
+    #[rpc(method = 5)]
+    async fn get_validation_timing(
+        &self,
+        request: Request<()>,
+    ) -> Result<Response<ValidationTimingResponse>, RpcStatus>;
+
+    #[rpc(method = 6)]
+    async fn get_transaction_state_by_output_commitment(
+        &self,
+        request: Request<Commitment>,
+    ) -> Result<Response<TxStorage>, RpcStatus>;
 }
 
 pub fn create_mempool_rpc_service(mempool: MempoolHandle) -> MempoolRpcServer<MempoolRpcService> {
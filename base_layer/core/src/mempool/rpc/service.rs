@@ -104,4 +104,34 @@ impl MempoolService for MempoolRpcService {
         let tx_storage = self.mempool().submit_transaction(tx).await.map_err(to_internal_error)?;
         Ok(Response::new(tx_storage.into()))
     }
+
+    async fn get_validation_timing(
+        &self,
+        _: Request<()>,
+    ) -> Result<Response<proto::mempool::ValidationTimingResponse>, RpcStatus>
+    {
+        let timing = self
+            .mempool()
+            .get_validation_timing()
+            .await
+            .map_err(to_internal_error)?;
+        Ok(Response::new(timing.into()))
+    }
+
+    async fn get_transaction_state_by_output_commitment(
+        &self,
+        request: Request<proto::types::Commitment>,
+    ) -> Result<Response<proto::mempool::TxStorage>, RpcStatus>
+    {
+        let commitment = request
+            .into_message()
+            .try_into()
+            .map_err(|_| RpcStatus::bad_request("Invalid commitment received"))?;
+        let resp = self
+            .mempool()
+            .get_tx_state_by_output_commitment(commitment)
+            .await
+            .map_err(to_internal_error)?;
+        Ok(Response::new(resp.into()))
+    }
 }
@@ -52,6 +52,8 @@ mod get_stats {
 
             reorg_txs: 5,
             total_weight: 6,
+            avg_fee_per_gram: 7,
+            min_fee_per_gram: 8,
         };
         mempool.set_get_stats_response(expected_stats.clone()).await;
 
@@ -83,6 +85,36 @@ mod get_state {
     }
 }
 
+mod get_validation_timing {
+    use super::*;
+    use crate::mempool::{MempoolService, ValidationTimingBucket, ValidationTimingResponse};
+
+    #[tokio_macros::test_basic]
+    async fn it_returns_the_validation_timing() {
+        let (service, mempool, req_mock, _tmpdir) = setup();
+        let expected_timing = ValidationTimingResponse {
+            num_validations: 3,
+            mean_duration_us: 200,
+            max_duration_us: 400,
+            buckets: vec![ValidationTimingBucket {
+                upper_bound_us: 500,
+                count: 3,
+            }],
+        };
+        mempool
+            .set_get_validation_timing_response(expected_timing.clone())
+            .await;
+
+        let resp = service
+            .get_validation_timing(req_mock.request_no_context(()))
+            .await
+            .unwrap();
+        let timing = resp.into_message();
+        assert_eq!(timing, expected_timing.into());
+        assert_eq!(mempool.get_call_count(), 1);
+    }
+}
+
 mod get_tx_state_by_excess_sig {
     use super::*;
     use crate::{
@@ -128,6 +160,52 @@ mod get_tx_state_by_excess_sig {
     }
 }
 
+mod get_tx_state_by_output_commitment {
+    use super::*;
+    use crate::{
+        mempool::{MempoolService, TxStorageResponse},
+        proto::types::Commitment,
+        tari_utilities::ByteArray,
+    };
+    use tari_comms::protocol::rpc::RpcStatusCode;
+    use tari_crypto::ristretto::RistrettoPublicKey;
+    use tari_test_utils::unpack_enum;
+
+    #[tokio_macros::test_basic]
+    async fn it_returns_the_storage_status() {
+        let (service, mempool, req_mock, _tmpdir) = setup();
+        let commitment = Commitment {
+            data: RistrettoPublicKey::default().to_vec(),
+        };
+
+        let resp = service
+            .get_transaction_state_by_output_commitment(req_mock.request_no_context(commitment.clone()))
+            .await
+            .unwrap();
+        assert_eq!(resp.into_message(), TxStorageResponse::NotStored.into());
+
+        let expected = TxStorageResponse::ReorgPool;
+        mempool.set_get_tx_by_output_commitment_response(expected.clone()).await;
+        let resp = service
+            .get_transaction_state_by_output_commitment(req_mock.request_no_context(commitment))
+            .await
+            .unwrap();
+        assert_eq!(resp.into_message(), expected.into());
+        assert_eq!(mempool.get_call_count(), 2);
+    }
+
+    #[tokio_macros::test_basic]
+    async fn it_errors_on_invalid_commitment() {
+        let (service, _, req_mock, _tmpdir) = setup();
+        let status = service
+            .get_transaction_state_by_output_commitment(req_mock.request_no_context(Default::default()))
+            .await
+            .unwrap_err();
+
+        unpack_enum!(RpcStatusCode::BadRequest = status.status_code());
+    }
+}
+
 mod submit_transaction {
     use super::*;
     use crate::{
@@ -23,12 +23,17 @@
 use crate::{
     mempool::{
         service::{MempoolRequest, MempoolResponse},
+        FeeHistogramResponse,
         MempoolServiceError,
         StateResponse,
         StatsResponse,
         TxStorageResponse,
+        ValidationTimingResponse,
+    },
+    transactions::{
+        transaction::Transaction,
+        types::{Commitment, Signature},
     },
-    transactions::{transaction::Transaction, types::Signature},
 };
 use tari_service_framework::{reply_channel::TrySenderService, Service};
 
@@ -67,6 +72,21 @@ impl MempoolHandle {
         }
     }
 
+    pub async fn get_tx_state_by_output_commitment(
+        &mut self,
+        commitment: Commitment,
+    ) -> Result<TxStorageResponse, MempoolServiceError>
+    {
+        match self
+            .inner
+            .call(MempoolRequest::GetTxStateByOutputCommitment(commitment))
+            .await??
+        {
+            MempoolResponse::TxStorage(resp) => Ok(resp),
+            _ => panic!("Incorrect response"),
+        }
+    }
+
     pub async fn submit_transaction(
         &mut self,
         transaction: Transaction,
@@ -81,4 +101,18 @@ impl MempoolHandle {
             _ => panic!("Incorrect response"),
         }
     }
+
+    pub async fn get_validation_timing(&mut self) -> Result<ValidationTimingResponse, MempoolServiceError> {
+        match self.inner.call(MempoolRequest::GetValidationTiming).await?? {
+            MempoolResponse::ValidationTiming(resp) => Ok(resp),
+            _ => panic!("Incorrect response"),
+        }
+    }
+
+    pub async fn get_fee_histogram(&mut self) -> Result<FeeHistogramResponse, MempoolServiceError> {
+        match self.inner.call(MempoolRequest::GetFeeHistogram).await?? {
+            MempoolResponse::FeeHistogram(resp) => Ok(resp),
+            _ => panic!("Incorrect response"),
+        }
+    }
 }
@@ -27,7 +27,6 @@ use crate::{
         async_mempool,
         service::{MempoolRequest, MempoolResponse, MempoolServiceError, OutboundMempoolServiceInterface},
         Mempool,
-        MempoolStateEvent,
         TxStorageResponse,
     },
     transactions::transaction::Transaction,
@@ -36,7 +35,6 @@ use log::*;
 use std::sync::Arc;
 use tari_comms::peer_manager::NodeId;
 use tari_crypto::tari_utilities::hex::Hex;
-use tokio::sync::broadcast;
 
 pub const LOG_TARGET: &str = "c::mp::service::inbound_handlers";
 
@@ -44,24 +42,14 @@ pub const LOG_TARGET: &str = "c::mp::service::inbound_handlers";
 /// nodes.
 #[derive(Clone)]
 pub struct MempoolInboundHandlers {
-    event_publisher: broadcast::Sender<MempoolStateEvent>,
     mempool: Mempool,
     outbound_nmi: OutboundMempoolServiceInterface,
 }
 
 impl MempoolInboundHandlers {
     /// Construct the MempoolInboundHandlers.
-    pub fn new(
-        event_publisher: broadcast::Sender<MempoolStateEvent>,
-        mempool: Mempool,
-        outbound_nmi: OutboundMempoolServiceInterface,
-    ) -> Self
-    {
-        Self {
-            event_publisher,
-            mempool,
-            outbound_nmi,
-        }
+    pub fn new(mempool: Mempool, outbound_nmi: OutboundMempoolServiceInterface) -> Self {
+        Self { mempool, outbound_nmi }
     }
 
     /// Handle inbound Mempool service requests from remote nodes and local services.
@@ -86,6 +74,15 @@ impl MempoolInboundHandlers {
                 );
                 Ok(MempoolResponse::TxStorage(self.submit_transaction(tx, vec![]).await?))
             },
+            GetValidationTiming => Ok(MempoolResponse::ValidationTiming(
+                async_mempool::validation_timing(self.mempool.clone()).await?,
+            )),
+            GetTxStateByOutputCommitment(commitment) => Ok(MempoolResponse::TxStorage(
+                async_mempool::has_tx_with_output(self.mempool.clone(), commitment).await?,
+            )),
+            GetFeeHistogram => Ok(MempoolResponse::FeeHistogram(
+                async_mempool::fee_histogram(self.mempool.clone()).await?,
+            )),
         }
     }
 
@@ -150,26 +147,21 @@ impl MempoolInboundHandlers {
         }
     }
 
-    /// Handle inbound block events from the local base node service.
+    /// Handle inbound block events from the local base node service. The mempool publishes its own state events for
+    /// the changes these cause.
     pub async fn handle_block_event(&mut self, block_event: &BlockEvent) -> Result<(), MempoolServiceError> {
         use BlockEvent::*;
         match block_event {
-            ValidBlockAdded(block, BlockAddResult::Ok(_), broadcast) => {
+            ValidBlockAdded(block, BlockAddResult::Ok(_), _) => {
                 async_mempool::process_published_block(self.mempool.clone(), block.clone()).await?;
-                if broadcast.is_true() {
-                    let _ = self.event_publisher.send(MempoolStateEvent::Updated);
-                }
             },
-            ValidBlockAdded(_, BlockAddResult::ChainReorg { added, removed }, broadcast) => {
+            ValidBlockAdded(_, BlockAddResult::ChainReorg { added, removed }, _) => {
                 async_mempool::process_reorg(
                     self.mempool.clone(),
                     removed.iter().map(|b| b.to_arc_block()).collect(),
                     added.iter().map(|b| b.to_arc_block()).collect(),
                 )
                 .await?;
-                if broadcast.is_true() {
-                    let _ = self.event_publisher.send(MempoolStateEvent::Updated);
-                }
             },
             BlockSyncRewind(removed_blocks) if !removed_blocks.is_empty() => {
                 async_mempool::process_reorg(
@@ -178,11 +170,9 @@ impl MempoolInboundHandlers {
                     vec![],
                 )
                 .await?;
-                let _ = self.event_publisher.send(MempoolStateEvent::Updated);
             },
             BlockSyncComplete(tip_block) => {
                 async_mempool::process_published_block(self.mempool.clone(), tip_block.to_arc_block()).await?;
-                let _ = self.event_publisher.send(MempoolStateEvent::Updated);
             },
             _ => {},
         }
@@ -33,6 +33,7 @@ use crate::{
             MempoolHandle,
         },
         MempoolServiceConfig,
+        TransactionAnnouncer,
     },
     proto,
     transactions::transaction::Transaction,
@@ -53,7 +54,6 @@ use tari_service_framework::{
     ServiceInitializer,
     ServiceInitializerContext,
 };
-use tokio::sync::broadcast;
 
 const LOG_TARGET: &str = "c::bn::mempool_service::initializer";
 const SUBSCRIPTION_LABEL: &str = "Mempool";
@@ -152,17 +152,16 @@ impl ServiceInitializer for MempoolServiceInitializer {
         let (outbound_tx_sender, outbound_tx_stream) = mpsc::unbounded();
         let (outbound_request_sender_service, outbound_request_stream) = reply_channel::unbounded();
         let (local_request_sender_service, local_request_stream) = reply_channel::unbounded();
-        let (mempool_state_event_publisher, _) = broadcast::channel(100);
+        // The local interface hands out subscriptions to the events published by the mempool itself
+        let mempool_state_event_publisher = match self.mempool.event_publisher() {
+            Ok(publisher) => publisher,
+            Err(err) => return future::ready(Err(err.into())),
+        };
         let outbound_mp_interface =
             OutboundMempoolServiceInterface::new(outbound_request_sender_service, outbound_tx_sender);
-        let local_mp_interface =
-            LocalMempoolService::new(local_request_sender_service, mempool_state_event_publisher.clone());
+        let local_mp_interface = LocalMempoolService::new(local_request_sender_service, mempool_state_event_publisher);
         let config = self.config;
-        let inbound_handlers = MempoolInboundHandlers::new(
-            mempool_state_event_publisher,
-            self.mempool.clone(),
-            outbound_mp_interface.clone(),
-        );
+        let inbound_handlers = MempoolInboundHandlers::new(self.mempool.clone(), outbound_mp_interface.clone());
 
         // Register handle to OutboundMempoolServiceInterface before waiting for handles to be ready
         context.register_handle(outbound_mp_interface);
@@ -172,6 +171,7 @@ impl ServiceInitializer for MempoolServiceInitializer {
             let outbound_message_service = handles.expect_handle::<Dht>().outbound_requester();
             let state_machine = handles.expect_handle::<StateMachineHandle>();
             let base_node = handles.expect_handle::<LocalNodeCommsInterface>();
+            let transaction_announcer = handles.get_handle::<TransactionAnnouncer>();
 
             let streams = MempoolStreams {
                 outbound_request_stream,
@@ -183,8 +183,14 @@ impl ServiceInitializer for MempoolServiceInitializer {
                 block_event_stream: base_node.get_block_event_stream(),
                 request_receiver,
             };
-            let service =
-                MempoolService::new(outbound_message_service, inbound_handlers, config, state_machine).start(streams);
+            let service = MempoolService::new(
+                outbound_message_service,
+                inbound_handlers,
+                config,
+                state_machine,
+                transaction_announcer,
+            )
+            .start(streams);
             futures::pin_mut!(service);
             future::select(service, handles.get_shutdown_signal()).await;
             info!(target: LOG_TARGET, "Mempool Service shutdown");
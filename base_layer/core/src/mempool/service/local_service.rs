@@ -135,6 +135,8 @@ mod test {
             unconfirmed_txs: 3,
             reorg_txs: 4,
             total_weight: 1000,
+            avg_fee_per_gram: 25,
+            min_fee_per_gram: 20,
         }
     }
 
@@ -20,7 +20,10 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::transactions::{transaction::Transaction, types::Signature};
+use crate::transactions::{
+    transaction::Transaction,
+    types::{Commitment, Signature},
+};
 use core::fmt::{Display, Error, Formatter};
 use serde::{Deserialize, Serialize};
 use tari_common_types::waiting_requests::RequestKey;
@@ -34,6 +37,9 @@ pub enum MempoolRequest {
     GetState,
     GetTxStateByExcessSig(Signature),
     SubmitTransaction(Transaction),
+    GetValidationTiming,
+    GetTxStateByOutputCommitment(Commitment),
+    GetFeeHistogram,
 }
 
 impl Display for MempoolRequest {
@@ -48,6 +54,11 @@ impl Display for MempoolRequest {
                 "SubmitTransaction ({})",
                 tx.body.kernels()[0].excess_sig.get_signature().to_hex()
             )),
+            MempoolRequest::GetValidationTiming => f.write_str("GetValidationTiming"),
+            MempoolRequest::GetTxStateByOutputCommitment(commitment) => {
+                f.write_str(&format!("GetTxStateByOutputCommitment ({})", commitment.to_hex()))
+            },
+            MempoolRequest::GetFeeHistogram => f.write_str("GetFeeHistogram"),
         }
     }
 }
@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::mempool::{StateResponse, StatsResponse, TxStorageResponse};
+use crate::mempool::{FeeHistogramResponse, StateResponse, StatsResponse, TxStorageResponse, ValidationTimingResponse};
 use serde::{Deserialize, Serialize};
 use std::{fmt, fmt::Formatter};
 use tari_common_types::waiting_requests::RequestKey;
@@ -31,6 +31,8 @@ pub enum MempoolResponse {
     Stats(StatsResponse),
     State(StateResponse),
     TxStorage(TxStorageResponse),
+    ValidationTiming(ValidationTimingResponse),
+    FeeHistogram(FeeHistogramResponse),
 }
 
 impl fmt::Display for MempoolResponse {
@@ -40,6 +42,8 @@ impl fmt::Display for MempoolResponse {
             Stats(_) => write!(f, "Stats"),
             State(_) => write!(f, "State"),
             TxStorage(_) => write!(f, "TxStorage"),
+            ValidationTiming(_) => write!(f, "ValidationTiming"),
+            FeeHistogram(_) => write!(f, "FeeHistogram"),
         }
     }
 }
@@ -34,6 +34,7 @@ use crate::{
             MempoolResponse,
         },
         MempoolServiceConfig,
+        TransactionAnnouncer,
     },
     proto,
     transactions::transaction::Transaction,
@@ -84,6 +85,7 @@ pub struct MempoolService {
     timeout_receiver_stream: Option<mpsc::Receiver<RequestKey>>,
     config: MempoolServiceConfig,
     state_machine: StateMachineHandle,
+    transaction_announcer: Option<TransactionAnnouncer>,
 }
 
 impl MempoolService {
@@ -92,6 +94,7 @@ impl MempoolService {
         inbound_handlers: MempoolInboundHandlers,
         config: MempoolServiceConfig,
         state_machine: StateMachineHandle,
+        transaction_announcer: Option<TransactionAnnouncer>,
     ) -> Self
     {
         let (timeout_sender, timeout_receiver) = mpsc::channel(100);
@@ -103,6 +106,7 @@ impl MempoolService {
             timeout_receiver_stream: Some(timeout_receiver),
             config,
             state_machine,
+            transaction_announcer,
         }
     }
 
@@ -227,7 +231,17 @@ impl MempoolService {
         });
     }
 
-    fn spawn_handle_outbound_tx(&self, tx: Transaction, excluded_peers: Vec<NodeId>) {
+    fn spawn_handle_outbound_tx(&self, tx: Transaction, mut excluded_peers: Vec<NodeId>) {
+        if let Some(announcer) = self.transaction_announcer.as_ref() {
+            // Announce only the excess signature to peers that support compact gossip, and propagate the full
+            // transaction to the other peers
+            if self.config.compact_gossip {
+                announcer.announce(Arc::new(tx.clone()), excluded_peers.clone());
+                excluded_peers.extend(announcer.announcement_peers());
+            }
+            // Peers that opted out of transaction relay pull the transactions they are missing instead
+            excluded_peers.extend(announcer.no_relay_peers());
+        }
         let outbound_message_service = self.outbound_message_service.clone();
         task::spawn(async move {
             let result = handle_outbound_tx(outbound_message_service, tx, excluded_peers).await;
@@ -0,0 +1,121 @@
+//  Copyright 2021, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::transactions::transaction::Transaction;
+use futures::channel::mpsc;
+use log::*;
+use std::{
+    collections::HashSet,
+    sync::{Arc, RwLock},
+};
+use tari_comms::peer_manager::NodeId;
+
+const LOG_TARGET: &str = "c::mempool::sync_protocol::announcer";
+
+/// The stream of transactions to be announced, along with the peers to exclude from the announcement
+pub type TransactionAnnouncements = mpsc::UnboundedReceiver<(Arc<Transaction>, Vec<NodeId>)>;
+
+/// Handle used to announce new transactions to connected base nodes by their excess signature. Peers request the
+/// transactions they do not already have over the mempool sync protocol. Only peers that support the mempool
+/// announcement protocol are announced to.
+#[derive(Clone)]
+pub struct TransactionAnnouncer {
+    sender: mpsc::UnboundedSender<(Arc<Transaction>, Vec<NodeId>)>,
+    announcement_peers: Arc<RwLock<HashSet<NodeId>>>,
+    no_relay_peers: Arc<RwLock<HashSet<NodeId>>>,
+}
+
+impl TransactionAnnouncer {
+    pub fn new() -> (Self, TransactionAnnouncements) {
+        let (sender, receiver) = mpsc::unbounded();
+        (
+            Self {
+                sender,
+                announcement_peers: Default::default(),
+                no_relay_peers: Default::default(),
+            },
+            receiver,
+        )
+    }
+
+    /// Returns the connected base nodes that support transaction announcements. Transactions have to be propagated in
+    /// full to all other peers.
+    pub fn announcement_peers(&self) -> Vec<NodeId> {
+        self.announcement_peers
+            .read()
+            .expect("TransactionAnnouncer lock poisoned")
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    pub(super) fn supports_announcements(&self, node_id: &NodeId) -> bool {
+        self.announcement_peers
+            .read()
+            .expect("TransactionAnnouncer lock poisoned")
+            .contains(node_id)
+    }
+
+    pub(super) fn add_announcement_peer(&self, node_id: NodeId) {
+        self.announcement_peers
+            .write()
+            .expect("TransactionAnnouncer lock poisoned")
+            .insert(node_id);
+    }
+
+    pub(super) fn remove_announcement_peer(&self, node_id: &NodeId) {
+        self.announcement_peers
+            .write()
+            .expect("TransactionAnnouncer lock poisoned")
+            .remove(node_id);
+    }
+
+    /// Returns the connected base nodes that opted out of transaction relay in their last mempool sync. Full
+    /// transactions are not propagated to these peers, they pull the transactions they are missing instead.
+    pub fn no_relay_peers(&self) -> Vec<NodeId> {
+        self.no_relay_peers
+            .read()
+            .expect("TransactionAnnouncer lock poisoned")
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    pub(super) fn set_no_relay_peer(&self, node_id: NodeId, no_relay: bool) {
+        let mut no_relay_peers = self.no_relay_peers.write().expect("TransactionAnnouncer lock poisoned");
+        if no_relay {
+            no_relay_peers.insert(node_id);
+        } else {
+            no_relay_peers.remove(&node_id);
+        }
+    }
+
+    /// Announce a transaction to all connected base nodes that support announcements except the given peers
+    pub fn announce(&self, transaction: Arc<Transaction>, exclude_peers: Vec<NodeId>) {
+        if self.sender.unbounded_send((transaction, exclude_peers)).is_err() {
+            warn!(
+                target: LOG_TARGET,
+                "Unable to announce transaction because the mempool sync protocol has shut down"
+            );
+        }
+    }
+}
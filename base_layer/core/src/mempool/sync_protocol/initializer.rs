@@ -23,14 +23,22 @@
 use crate::{
     base_node::StateMachineHandle,
     mempool::{
-        sync_protocol::{MempoolSyncProtocol, MEMPOOL_SYNC_PROTOCOL},
+        sync_protocol::{
+            MempoolSyncProtocol,
+            TransactionAnnouncer,
+            MEMPOOL_ANNOUNCEMENT_PROTOCOL,
+            MEMPOOL_SYNC_PROTOCOL,
+        },
         Mempool,
         MempoolServiceConfig,
+        OutboundMempoolServiceInterface,
     },
 };
 use futures::{channel::mpsc, future};
+use std::sync::Arc;
 use tari_comms::{
     connectivity::ConnectivityRequester,
+    peer_manager::PeerManager,
     protocol::{ProtocolExtension, ProtocolExtensionContext, ProtocolExtensionError, ProtocolNotification},
     Substream,
 };
@@ -57,7 +65,10 @@ impl MempoolSyncInitializer {
     pub fn get_protocol_extension(&self) -> impl ProtocolExtension {
         let notif_tx = self.notif_tx.clone();
         move |context: &mut ProtocolExtensionContext| -> Result<(), ProtocolExtensionError> {
-            context.add_protocol(&[MEMPOOL_SYNC_PROTOCOL.clone()], notif_tx);
+            context.add_protocol(
+                &[MEMPOOL_SYNC_PROTOCOL.clone(), MEMPOOL_ANNOUNCEMENT_PROTOCOL.clone()],
+                notif_tx,
+            );
             Ok(())
         }
     }
@@ -70,16 +81,24 @@ impl ServiceInitializer for MempoolSyncInitializer {
         let config = self.config;
         let mempool = self.mempool.clone();
         let notif_rx = self.notif_rx.take().unwrap();
+        let (announcer, announcements) = TransactionAnnouncer::new();
+        context.register_handle(announcer.clone());
 
         context.spawn_when_ready(move |handles| {
             let state_machine = handles.expect_handle::<StateMachineHandle>();
             let connectivity = handles.expect_handle::<ConnectivityRequester>();
+            let outbound_mempool = handles.expect_handle::<OutboundMempoolServiceInterface>();
+            let peer_manager = handles.expect_handle::<Arc<PeerManager>>();
             MempoolSyncProtocol::new(
                 config,
                 notif_rx,
                 connectivity.get_event_subscription(),
                 mempool,
+                outbound_mempool,
+                peer_manager,
                 Some(state_machine),
+                announcer,
+                announcements,
             )
             .run()
         });
@@ -37,6 +37,29 @@
 //! Alice SHOULD return the Transactions relating to those indexes
 //! Alice SHOULD close the stream immediately after sending
 //!
+//! ### No-relay
+//!
+//! A bandwidth-constrained node may set the `no_relay` flag in its inventory to opt out of being pushed transactions.
+//! In that case, instead of streaming its transactions, Bob MUST send an inventory of the transactions known to him,
+//! excluding the transactions in Alice's inventory. Alice MUST respond with the indexes of the transactions she wants
+//! to pull, and if she requested at least one index, Bob SHOULD return the transactions relating to those indexes
+//! followed by a complete message. The protocol then continues from Bob sending the indexes of inventory items that
+//! are not known to him.
+//!
+//! Bob remembers the preference for as long as Alice is connected and does not propagate new transactions to her in
+//! full. Alice learns of new transactions from announcements (see below), which she pulls from, or from her next
+//! mempool sync.
+//!
+//! ### Compact gossip
+//!
+//! When compact gossip is enabled (`MempoolServiceConfig::compact_gossip`), new transactions are announced to
+//! connected base nodes over this protocol instead of being propagated in full. Only peers that advertise the
+//! `t/mempool-sync/2` protocol understand announcements, so announcements are only sent over that protocol and
+//! transactions are still propagated in full over the DHT to all other peers. The announcer sends an inventory with
+//! the `announcement` flag set. The peer MUST respond with the indexes of the announced transactions that are not
+//! known to it, and if it requested at least one index, the announcer SHOULD return the transactions relating to
+//! those indexes followed by a complete message. The peer then announces the transactions it added to its mempool to
+//! its own peers.
 //!
 //! ```text
 //!  +-------+                    +-----+
@@ -72,9 +95,19 @@ use error::MempoolProtocolError;
 mod initializer;
 pub use initializer::MempoolSyncInitializer;
 
+mod announcer;
+pub use announcer::{TransactionAnnouncements, TransactionAnnouncer};
+
 use crate::{
     base_node::StateMachineHandle,
-    mempool::{async_mempool, proto, Mempool, MempoolServiceConfig},
+    mempool::{
+        async_mempool,
+        proto,
+        Mempool,
+        MempoolServiceConfig,
+        OutboundMempoolServiceInterface,
+        TxStorageResponse,
+    },
     proto as shared_proto,
     transactions::transaction::Transaction,
 };
@@ -82,6 +115,7 @@ use futures::{stream, stream::Fuse, AsyncRead, AsyncWrite, SinkExt, Stream, Stre
 use log::*;
 use prost::Message;
 use std::{
+    collections::{HashMap, HashSet},
     convert::TryFrom,
     iter,
     sync::{
@@ -95,7 +129,7 @@ use tari_comms::{
     framing,
     framing::CanonicalFraming,
     message::MessageExt,
-    peer_manager::{NodeId, PeerFeatures},
+    peer_manager::{NodeId, PeerFeatures, PeerManager},
     protocol::{ProtocolEvent, ProtocolNotification, ProtocolNotificationRx},
     Bytes,
     PeerConnection,
@@ -107,15 +141,22 @@ const MAX_FRAME_SIZE: usize = 3 * 1024 * 1024; // 3 MiB
 const LOG_TARGET: &str = "c::mempool::sync_protocol";
 
 pub static MEMPOOL_SYNC_PROTOCOL: Bytes = Bytes::from_static(b"t/mempool-sync/1");
+/// The mempool sync protocol including transaction announcements
+pub static MEMPOOL_ANNOUNCEMENT_PROTOCOL: Bytes = Bytes::from_static(b"t/mempool-sync/2");
 
 pub struct MempoolSyncProtocol<TSubstream> {
     config: MempoolServiceConfig,
     protocol_notifier: ProtocolNotificationRx<TSubstream>,
     connectivity_events: Fuse<ConnectivityEventRx>,
     mempool: Mempool,
+    outbound_mempool: OutboundMempoolServiceInterface,
+    peer_manager: Arc<PeerManager>,
     num_synched: Arc<AtomicUsize>,
     permits: Arc<Semaphore>,
     state_machine: Option<StateMachineHandle>,
+    announcer: TransactionAnnouncer,
+    announcements: Fuse<TransactionAnnouncements>,
+    connected_peers: HashMap<NodeId, PeerConnection>,
 }
 
 impl<TSubstream> MempoolSyncProtocol<TSubstream>
@@ -126,7 +167,11 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + Sync + 'static
         protocol_notifier: ProtocolNotificationRx<TSubstream>,
         connectivity_events: ConnectivityEventRx,
         mempool: Mempool,
+        outbound_mempool: OutboundMempoolServiceInterface,
+        peer_manager: Arc<PeerManager>,
         state_machine: Option<StateMachineHandle>,
+        announcer: TransactionAnnouncer,
+        announcements: TransactionAnnouncements,
     ) -> Self
     {
         Self {
@@ -134,9 +179,14 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + Sync + 'static
             protocol_notifier,
             connectivity_events: connectivity_events.fuse(),
             mempool,
+            outbound_mempool,
+            peer_manager,
             num_synched: Arc::new(AtomicUsize::new(0)),
             permits: Arc::new(Semaphore::new(1)),
             state_machine,
+            announcer,
+            announcements: announcements.fuse(),
+            connected_peers: HashMap::new(),
         }
     }
 
@@ -165,6 +215,10 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + Sync + 'static
                     self.handle_protocol_notification(notif);
                 }
 
+                (transaction, exclude_peers) = self.announcements.select_next_some() => {
+                    self.spawn_announcements(transaction, exclude_peers);
+                }
+
                 // protocol_notifier and connectivity_events are closed
                 complete => {
                     info!(target: LOG_TARGET, "Mempool protocol handler is shutting down");
@@ -176,17 +230,36 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + Sync + 'static
 
     async fn handle_connectivity_event(&mut self, event: &ConnectivityEvent) {
         match event {
-            // If this node is connecting to a peer
-            ConnectivityEvent::PeerConnected(conn) if conn.direction().is_outbound() => {
+            ConnectivityEvent::PeerConnected(conn) => {
                 // This protocol is only spoken between base nodes
                 if !conn.peer_features().contains(PeerFeatures::COMMUNICATION_NODE) {
                     return;
                 }
 
-                if !self.is_synched() {
+                self.connected_peers.insert(conn.peer_node_id().clone(), conn.clone());
+                match self.peer_manager.find_by_node_id(conn.peer_node_id()).await {
+                    Ok(peer) if peer.supported_protocols().contains(&MEMPOOL_ANNOUNCEMENT_PROTOCOL) => {
+                        self.announcer.add_announcement_peer(conn.peer_node_id().clone());
+                    },
+                    Ok(_) => {},
+                    Err(err) => debug!(
+                        target: LOG_TARGET,
+                        "Unable to determine whether peer `{}` supports transaction announcements: {}",
+                        conn.peer_node_id().short_str(),
+                        err
+                    ),
+                }
+
+                // If this node is connecting to a peer
+                if conn.direction().is_outbound() && !self.is_synched() {
                     self.spawn_initiator_protocol(conn.clone()).await;
                 }
             },
+            ConnectivityEvent::PeerDisconnected(node_id) => {
+                self.connected_peers.remove(node_id);
+                self.announcer.remove_announcement_peer(node_id);
+                self.announcer.set_no_relay_peer(node_id.clone(), false);
+            },
             _ => {},
         }
     }
@@ -246,19 +319,86 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + Sync + 'static
         });
     }
 
+    fn spawn_announcements(&self, transaction: Arc<Transaction>, exclude_peers: Vec<NodeId>) {
+        let peers = self
+            .connected_peers
+            .values()
+            .filter(|conn| {
+                !exclude_peers.contains(conn.peer_node_id()) &&
+                    self.announcer.supports_announcements(conn.peer_node_id())
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        debug!(
+            target: LOG_TARGET,
+            "Announcing transaction `{}` to {} peer(s)",
+            transaction
+                .first_kernel_excess_sig()
+                .map(|sig| sig.get_signature().to_hex())
+                .unwrap_or_else(|| "<none>".to_string()),
+            peers.len()
+        );
+
+        for mut conn in peers {
+            let mempool = self.mempool.clone();
+            let config = self.config;
+            let transaction = transaction.clone();
+            task::spawn(async move {
+                match conn
+                    .open_framed_substream(&MEMPOOL_ANNOUNCEMENT_PROTOCOL, MAX_FRAME_SIZE)
+                    .await
+                {
+                    Ok(framed) => {
+                        let protocol = MempoolPeerProtocol::new(config, framed, conn.peer_node_id().clone(), mempool);
+                        if let Err(err) = protocol.start_announcer(transaction).await {
+                            debug!(
+                                target: LOG_TARGET,
+                                "Mempool announcer protocol failed for peer `{}`: {}",
+                                conn.peer_node_id().short_str(),
+                                err
+                            );
+                        }
+                    },
+                    Err(err) => debug!(
+                        target: LOG_TARGET,
+                        "Unable to establish mempool protocol substream to peer `{}`: {}",
+                        conn.peer_node_id().short_str(),
+                        err
+                    ),
+                }
+            });
+        }
+    }
+
     fn spawn_inbound_handler(&self, node_id: NodeId, substream: TSubstream) {
         let mempool = self.mempool.clone();
         let config = self.config;
+        let mut outbound_mempool = self.outbound_mempool.clone();
+        let announcer = self.announcer.clone();
         task::spawn(async move {
             let framed = framing::canonical(substream, MAX_FRAME_SIZE);
             let mut protocol = MempoolPeerProtocol::new(config, framed, node_id.clone(), mempool);
-            match protocol.start_responder().await {
-                Ok(_) => {
+            let result = protocol.start_responder().await;
+            if let Some(no_relay) = protocol.peer_no_relay() {
+                announcer.set_no_relay_peer(node_id.clone(), no_relay);
+            }
+            match result {
+                Ok(announced_transactions) => {
                     debug!(
                         target: LOG_TARGET,
                         "Mempool responder protocol succeeded for peer `{}`",
                         node_id.short_str()
                     );
+                    // Pass on the announced transactions that were added to the mempool, both as announcements and
+                    // over the DHT for peers that do not support announcements
+                    for transaction in announced_transactions {
+                        if let Err(err) = outbound_mempool
+                            .propagate_tx((*transaction).clone(), vec![node_id.clone()])
+                            .await
+                        {
+                            debug!(target: LOG_TARGET, "Unable to propagate announced transaction: {}", err);
+                        }
+                    }
                 },
                 Err(err) => {
                     debug!(
@@ -278,6 +418,7 @@ struct MempoolPeerProtocol<TSubstream> {
     framed: CanonicalFraming<TSubstream>,
     mempool: Mempool,
     peer_node_id: NodeId,
+    peer_no_relay: Option<bool>,
 }
 
 impl<TSubstream> MempoolPeerProtocol<TSubstream>
@@ -295,9 +436,15 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
             framed,
             peer_node_id,
             mempool,
+            peer_no_relay: None,
         }
     }
 
+    /// Returns the relay preference of the peer if it initiated a mempool sync
+    pub fn peer_no_relay(&self) -> Option<bool> {
+        self.peer_no_relay
+    }
+
     pub async fn start_initiator(mut self) -> Result<(), MempoolProtocolError> {
         match self.start_initiator_inner().await {
             Ok(_) => {
@@ -330,19 +477,29 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
             .map(|txn| txn.body.kernels()[0].excess_sig.get_signature().to_vec())
             .map(|sig| sig.to_vec())
             .collect();
-        let inventory = proto::TransactionInventory { items };
+        let inventory = proto::TransactionInventory {
+            items,
+            no_relay: !self.config.relay_transactions,
+            announcement: false,
+        };
 
         // Send an inventory of items currently in this node's mempool
         debug!(
             target: LOG_TARGET,
-            "Sending transaction inventory containing {} item(s) to peer `{}`",
+            "Sending transaction inventory containing {} item(s) to peer `{}` (relay = {})",
             inventory.items.len(),
-            self.peer_node_id.short_str()
+            self.peer_node_id.short_str(),
+            self.config.relay_transactions,
         );
 
+        let no_relay = inventory.no_relay;
         self.write_message(inventory).await?;
 
-        self.read_and_insert_transactions_until_complete().await?;
+        if no_relay {
+            self.pull_missing_transactions().await?;
+        } else {
+            self.read_and_insert_transactions_until_complete().await?;
+        }
 
         let missing_items: proto::InventoryIndexes = self.read_message().await?;
         debug!(
@@ -375,10 +532,11 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
         Ok(())
     }
 
-    pub async fn start_responder(&mut self) -> Result<(), MempoolProtocolError> {
-        match self.start_responder_inner().await {
+    /// Announces a transaction to the peer by its excess signature and sends it if the peer requests it.
+    pub async fn start_announcer(mut self, transaction: Arc<Transaction>) -> Result<(), MempoolProtocolError> {
+        match self.start_announcer_inner(transaction).await {
             Ok(_) => {
-                debug!(target: LOG_TARGET, "Responder protocol complete");
+                debug!(target: LOG_TARGET, "Announcer protocol complete");
                 Ok(())
             },
             Err(err) => {
@@ -393,7 +551,56 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
         }
     }
 
-    async fn start_responder_inner(&mut self) -> Result<(), MempoolProtocolError> {
+    async fn start_announcer_inner(&mut self, transaction: Arc<Transaction>) -> Result<(), MempoolProtocolError> {
+        let excess_sig = transaction
+            .first_kernel_excess_sig()
+            .ok_or_else(|| MempoolProtocolError::ExcessSignatureMissing(self.peer_node_id.clone()))?;
+        let inventory = proto::TransactionInventory {
+            items: vec![excess_sig.get_signature().to_vec()],
+            no_relay: false,
+            announcement: true,
+        };
+        self.write_message(inventory).await?;
+
+        let requested_items: proto::InventoryIndexes = self.read_message().await?;
+        if requested_items.indexes.is_empty() {
+            debug!(
+                target: LOG_TARGET,
+                "Peer `{}` already has announced transaction `{}`",
+                self.peer_node_id.short_str(),
+                excess_sig.get_signature().to_hex()
+            );
+        } else {
+            self.write_transactions(vec![transaction]).await?;
+        }
+
+        // Close the stream after writing
+        self.framed.close().await?;
+
+        Ok(())
+    }
+
+    /// Responds to a sync or announcement initiated by the peer. Returns the announced transactions that were added to
+    /// the unconfirmed pool, if any, so that they can be announced onward.
+    pub async fn start_responder(&mut self) -> Result<Vec<Arc<Transaction>>, MempoolProtocolError> {
+        match self.start_responder_inner().await {
+            Ok(announced_transactions) => {
+                debug!(target: LOG_TARGET, "Responder protocol complete");
+                Ok(announced_transactions)
+            },
+            Err(err) => {
+                if let Err(err) = self.framed.flush().await {
+                    debug!(target: LOG_TARGET, "IO error when flushing stream: {}", err);
+                }
+                if let Err(err) = self.framed.close().await {
+                    debug!(target: LOG_TARGET, "IO error when closing stream: {}", err);
+                }
+                Err(err)
+            },
+        }
+    }
+
+    async fn start_responder_inner(&mut self) -> Result<Vec<Arc<Transaction>>, MempoolProtocolError> {
         debug!(
             target: LOG_TARGET,
             "Starting responder mempool sync for peer `{}`",
@@ -409,6 +616,11 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
             inventory.items.len()
         );
 
+        if inventory.announcement {
+            return self.handle_announcement(inventory).await;
+        }
+        self.peer_no_relay = Some(inventory.no_relay);
+
         let transactions = async_mempool::snapshot(self.mempool.clone()).await?;
 
         let mut duplicate_inventory_items = Vec::new();
@@ -431,14 +643,18 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
             }
         });
 
-        debug!(
-            target: LOG_TARGET,
-            "Streaming {} transaction(s) to peer `{}`",
-            transactions.len(),
-            self.peer_node_id.short_str()
-        );
+        if inventory.no_relay {
+            self.offer_transactions(transactions).await?;
+        } else {
+            debug!(
+                target: LOG_TARGET,
+                "Streaming {} transaction(s) to peer `{}`",
+                transactions.len(),
+                self.peer_node_id.short_str()
+            );
 
-        self.write_transactions(transactions).await?;
+            self.write_transactions(transactions).await?;
+        }
 
         // Generate an index list of inventory indexes that this node does not have
         let missing_items = inventory
@@ -469,11 +685,130 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
             self.read_and_insert_transactions_until_complete().await?;
         }
 
+        Ok(Vec::new())
+    }
+
+    /// Requests the announced transactions that are not in this node's mempool and returns those that were added to
+    /// the unconfirmed pool.
+    async fn handle_announcement(
+        &mut self,
+        inventory: proto::TransactionInventory,
+    ) -> Result<Vec<Arc<Transaction>>, MempoolProtocolError>
+    {
+        let wanted_items = self.unknown_inventory_indexes(&inventory).await?;
+        debug!(
+            target: LOG_TARGET,
+            "Requesting {} of {} transaction(s) announced by peer `{}`",
+            wanted_items.len(),
+            inventory.items.len(),
+            self.peer_node_id.short_str(),
+        );
+
+        let num_wanted_items = wanted_items.len();
+        self.write_message(proto::InventoryIndexes { indexes: wanted_items })
+            .await?;
+        if num_wanted_items == 0 {
+            return Ok(Vec::new());
+        }
+
+        self.read_and_insert_transactions_until_complete().await
+    }
+
+    /// Returns the indexes of the inventory items that are not in this node's mempool.
+    async fn unknown_inventory_indexes(
+        &self,
+        inventory: &proto::TransactionInventory,
+    ) -> Result<Vec<u32>, MempoolProtocolError>
+    {
+        let transactions = async_mempool::snapshot(self.mempool.clone()).await?;
+        let known_excess_sigs = transactions
+            .iter()
+            .filter_map(|txn| txn.first_kernel_excess_sig())
+            .map(|sig| sig.get_signature().as_bytes())
+            .collect::<HashSet<_>>();
+        let indexes = inventory
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, bytes)| !known_excess_sigs.contains(bytes.as_slice()))
+            .map(|(i, _)| i as u32)
+            .collect();
+        Ok(indexes)
+    }
+
+    /// Sends an inventory of the given transactions to a peer that has opted out of transaction relay, followed by
+    /// the transactions that the peer requests.
+    async fn offer_transactions(&mut self, transactions: Vec<Arc<Transaction>>) -> Result<(), MempoolProtocolError> {
+        let transactions = transactions
+            .into_iter()
+            .take(self.config.initial_sync_max_transactions)
+            .collect::<Vec<_>>();
+        let items = transactions
+            .iter()
+            .map(|txn| txn.body.kernels()[0].excess_sig.get_signature().to_vec())
+            .collect();
+        let inventory = proto::TransactionInventory {
+            items,
+            no_relay: false,
+            announcement: false,
+        };
+        debug!(
+            target: LOG_TARGET,
+            "Peer `{}` does not accept transaction relay, sending inventory containing {} item(s)",
+            self.peer_node_id.short_str(),
+            inventory.items.len(),
+        );
+        self.write_message(inventory).await?;
+
+        let requested_items: proto::InventoryIndexes = self.read_message().await?;
+        debug!(
+            target: LOG_TARGET,
+            "Peer `{}` requested {} transaction(s)",
+            self.peer_node_id.short_str(),
+            requested_items.indexes.len(),
+        );
+        if !requested_items.indexes.is_empty() {
+            let requested_txns = requested_items
+                .indexes
+                .iter()
+                .filter_map(|idx| transactions.get(*idx as usize).cloned())
+                .collect();
+            self.write_transactions(requested_txns).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the inventory sent by a peer in response to a no-relay inventory and requests the transactions that are
+    /// not in this node's mempool.
+    async fn pull_missing_transactions(&mut self) -> Result<(), MempoolProtocolError> {
+        let inventory: proto::TransactionInventory = self.read_message().await?;
+        let wanted_items = self.unknown_inventory_indexes(&inventory).await?;
+        debug!(
+            target: LOG_TARGET,
+            "Pulling {} of {} transaction(s) offered by peer `{}`",
+            wanted_items.len(),
+            inventory.items.len(),
+            self.peer_node_id.short_str(),
+        );
+
+        let num_wanted_items = wanted_items.len();
+        self.write_message(proto::InventoryIndexes { indexes: wanted_items })
+            .await?;
+        if num_wanted_items > 0 {
+            self.read_and_insert_transactions_until_complete().await?;
+        }
+
         Ok(())
     }
 
-    async fn read_and_insert_transactions_until_complete(&mut self) -> Result<(), MempoolProtocolError> {
+    /// Reads and inserts transactions until a complete message is received. Returns the transactions that were added to
+    /// the unconfirmed pool.
+    async fn read_and_insert_transactions_until_complete(
+        &mut self,
+    ) -> Result<Vec<Arc<Transaction>>, MempoolProtocolError> {
         let mut num_recv = 0;
+        let mut inserted = Vec::new();
         while let Some(result) = self.framed.next().await {
             let bytes = result?;
             let item = proto::TransactionItem::decode(&mut bytes.freeze()).map_err(|err| {
@@ -485,7 +820,9 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
 
             match item.transaction {
                 Some(txn) => {
-                    self.validate_and_insert_transaction(txn).await?;
+                    if let Some(txn) = self.validate_and_insert_transaction(txn).await? {
+                        inserted.push(txn);
+                    }
                     num_recv += 1;
                 },
                 None => {
@@ -500,13 +837,13 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
             }
         }
 
-        Ok(())
+        Ok(inserted)
     }
 
     async fn validate_and_insert_transaction(
         &mut self,
         txn: shared_proto::types::Transaction,
-    ) -> Result<(), MempoolProtocolError>
+    ) -> Result<Option<Arc<Transaction>>, MempoolProtocolError>
     {
         let txn = Transaction::try_from(txn).map_err(|err| MempoolProtocolError::MessageConversionFailed {
             peer: self.peer_node_id.clone(),
@@ -526,10 +863,11 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
 
         let store_state = async_mempool::has_tx_with_excess_sig(self.mempool.clone(), excess_sig.clone()).await?;
         if store_state.is_stored() {
-            return Ok(());
+            return Ok(None);
         }
 
-        let stored_result = async_mempool::insert(self.mempool.clone(), Arc::new(txn)).await?;
+        let txn = Arc::new(txn);
+        let stored_result = async_mempool::insert(self.mempool.clone(), txn.clone()).await?;
         if stored_result.is_stored() {
             debug!(
                 target: LOG_TARGET,
@@ -544,7 +882,11 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
             )
         }
 
-        Ok(())
+        if matches!(stored_result, TxStorageResponse::UnconfirmedPool) {
+            Ok(Some(txn))
+        } else {
+            Ok(None)
+        }
     }
 
     async fn write_transactions(&mut self, transactions: Vec<Arc<Transaction>>) -> Result<(), MempoolProtocolError> {
@@ -24,8 +24,17 @@ use crate::{
     mempool::{
         async_mempool,
         proto,
-        sync_protocol::{MempoolPeerProtocol, MempoolSyncProtocol, MAX_FRAME_SIZE, MEMPOOL_SYNC_PROTOCOL},
+        sync_protocol::{
+            MempoolPeerProtocol,
+            MempoolSyncProtocol,
+            TransactionAnnouncer,
+            MAX_FRAME_SIZE,
+            MEMPOOL_ANNOUNCEMENT_PROTOCOL,
+            MEMPOOL_SYNC_PROTOCOL,
+        },
         Mempool,
+        MempoolServiceConfig,
+        OutboundMempoolServiceInterface,
     },
     transactions::{helpers::create_tx, tari_amount::uT, transaction::Transaction},
     validation::mocks::MockValidator,
@@ -37,13 +46,16 @@ use tari_comms::{
     framing,
     memsocket::MemorySocket,
     message::MessageExt,
-    peer_manager::PeerFeatures,
+    peer_manager::{NodeId, PeerFeatures, PeerManager},
     protocol::{ProtocolEvent, ProtocolNotification, ProtocolNotificationTx},
     test_utils::{mocks::create_peer_connection_mock_pair, node_identity::build_node_identity},
     Bytes,
     BytesMut,
 };
 use tari_crypto::tari_utilities::ByteArray;
+use tari_service_framework::reply_channel;
+use tari_storage::HashmapDatabase;
+use tari_test_utils::async_assert_eventually;
 use tokio::{sync::broadcast, task};
 
 pub fn create_transactions(n: usize) -> Vec<Transaction> {
@@ -66,6 +78,12 @@ fn new_mempool_with_transactions(n: usize) -> (Mempool, Vec<Transaction>) {
     (mempool, transactions)
 }
 
+struct TestHandles {
+    announcer: TransactionAnnouncer,
+    peer_manager: Arc<PeerManager>,
+    outbound_tx_rx: mpsc::UnboundedReceiver<(Transaction, Vec<NodeId>)>,
+}
+
 fn setup(
     num_txns: usize,
 ) -> (
@@ -73,21 +91,52 @@ fn setup(
     ConnectivityEventTx,
     Mempool,
     Vec<Transaction>,
+) {
+    let (protocol_notif_tx, connectivity_events_tx, mempool, transactions, _) = setup_with_handles(num_txns);
+    (protocol_notif_tx, connectivity_events_tx, mempool, transactions)
+}
+
+fn setup_with_handles(
+    num_txns: usize,
+) -> (
+    ProtocolNotificationTx<MemorySocket>,
+    ConnectivityEventTx,
+    Mempool,
+    Vec<Transaction>,
+    TestHandles,
 ) {
     let (protocol_notif_tx, protocol_notif_rx) = mpsc::channel(1);
     let (connectivity_events_tx, connectivity_events_rx) = broadcast::channel(10);
     let (mempool, transactions) = new_mempool_with_transactions(num_txns);
+    let (request_sender, _) = reply_channel::unbounded();
+    let (outbound_tx_tx, outbound_tx_rx) = mpsc::unbounded();
+    let peer_manager = Arc::new(PeerManager::new(HashmapDatabase::new(), None).unwrap());
+    let (announcer, announcements) = TransactionAnnouncer::new();
     let protocol = MempoolSyncProtocol::new(
         Default::default(),
         protocol_notif_rx,
         connectivity_events_rx,
         mempool.clone(),
+        OutboundMempoolServiceInterface::new(request_sender, outbound_tx_tx),
+        peer_manager.clone(),
         None,
+        announcer.clone(),
+        announcements,
     );
 
     task::spawn(protocol.run());
 
-    (protocol_notif_tx, connectivity_events_tx, mempool, transactions)
+    (
+        protocol_notif_tx,
+        connectivity_events_tx,
+        mempool,
+        transactions,
+        TestHandles {
+            announcer,
+            peer_manager,
+            outbound_tx_rx,
+        },
+    )
 }
 
 #[tokio_macros::test_basic]
@@ -249,6 +298,8 @@ async fn initiator_messages() {
             .iter()
             .map(|tx| tx.first_kernel_excess_sig().unwrap().get_signature().to_vec())
             .collect(),
+        no_relay: false,
+        announcement: false,
     };
     write_message(&mut framed, inventory).await;
     // Expect 1 transaction, a "stop message" and indexes for missing transactions
@@ -260,6 +311,183 @@ async fn initiator_messages() {
     assert_eq!(indexes.indexes, [0, 1]);
 }
 
+#[tokio_macros::test_basic]
+async fn no_relay_initiator_messages() {
+    let (mut protocol_notif, _, _, transactions1, handles) = setup_with_handles(2);
+
+    let node1 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+
+    let (sock_in, sock_out) = MemorySocket::new_pair();
+    protocol_notif
+        .send(ProtocolNotification::new(
+            MEMPOOL_SYNC_PROTOCOL.clone(),
+            ProtocolEvent::NewInboundSubstream(node1.node_id().clone(), sock_in),
+        ))
+        .await
+        .unwrap();
+
+    let mut transactions = create_transactions(2);
+    transactions.push(transactions1[0].clone());
+    let mut framed = framing::canonical(sock_out, MAX_FRAME_SIZE);
+    // As the initiator, send an inventory and opt out of transaction relay
+    let inventory = proto::TransactionInventory {
+        items: transactions
+            .iter()
+            .map(|tx| tx.first_kernel_excess_sig().unwrap().get_signature().to_vec())
+            .collect(),
+        no_relay: true,
+        announcement: false,
+    };
+    write_message(&mut framed, inventory).await;
+    // Expect an inventory of the single transaction we do not have instead of the transaction itself
+    let offered: proto::TransactionInventory = read_message(&mut framed).await;
+    let expected = transactions1[1]
+        .first_kernel_excess_sig()
+        .unwrap()
+        .get_signature()
+        .to_vec();
+    assert_eq!(offered.items, vec![expected]);
+    // Pull the offered transaction
+    write_message(&mut framed, proto::InventoryIndexes { indexes: vec![0] }).await;
+    let transaction: proto::TransactionItem = read_message(&mut framed).await;
+    assert!(transaction.transaction.is_some());
+    let stop: proto::TransactionItem = read_message(&mut framed).await;
+    assert!(stop.transaction.is_none());
+    let indexes: proto::InventoryIndexes = read_message(&mut framed).await;
+    assert_eq!(indexes.indexes, [0, 1]);
+    drop(framed);
+
+    // The preference is remembered so that new transactions are not propagated to the peer in full
+    async_assert_eventually!(
+        handles.announcer.no_relay_peers(),
+        expect = vec![node1.node_id().clone()],
+        max_attempts = 10
+    );
+}
+
+#[tokio_macros::test_basic]
+async fn no_relay_pull() {
+    let (mut protocol_notif, _, _, transactions1) = setup(2);
+
+    let node1 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let node2 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+
+    let (sock_in, sock_out) = MemorySocket::new_pair();
+    protocol_notif
+        .send(ProtocolNotification::new(
+            MEMPOOL_SYNC_PROTOCOL.clone(),
+            ProtocolEvent::NewInboundSubstream(node1.node_id().clone(), sock_in),
+        ))
+        .await
+        .unwrap();
+
+    let (mempool2, transactions2) = new_mempool_with_transactions(1);
+    let config = MempoolServiceConfig {
+        relay_transactions: false,
+        ..Default::default()
+    };
+    let framed = framing::canonical(sock_out, MAX_FRAME_SIZE);
+    MempoolPeerProtocol::new(config, framed, node2.node_id().clone(), mempool2.clone())
+        .start_initiator()
+        .await
+        .unwrap();
+
+    let transactions = get_snapshot(&mempool2);
+    assert_eq!(transactions.len(), 3);
+    assert!(transactions1.iter().all(|txn| transactions.contains(&txn)));
+    assert!(transactions2.iter().all(|txn| transactions.contains(&txn)));
+}
+
+#[tokio_macros::test_basic]
+async fn announcement_messages() {
+    let (mut protocol_notif, _, mempool1, transactions1, mut handles) = setup_with_handles(1);
+
+    let node1 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+
+    let (sock_in, sock_out) = MemorySocket::new_pair();
+    protocol_notif
+        .send(ProtocolNotification::new(
+            MEMPOOL_ANNOUNCEMENT_PROTOCOL.clone(),
+            ProtocolEvent::NewInboundSubstream(node1.node_id().clone(), sock_in),
+        ))
+        .await
+        .unwrap();
+
+    let new_transaction = create_transactions(1).remove(0);
+    let mut framed = framing::canonical(sock_out, MAX_FRAME_SIZE);
+    // Announce a transaction the peer already has and one it does not have
+    let inventory = proto::TransactionInventory {
+        items: [&transactions1[0], &new_transaction]
+            .iter()
+            .map(|tx| tx.first_kernel_excess_sig().unwrap().get_signature().to_vec())
+            .collect(),
+        no_relay: false,
+        announcement: true,
+    };
+    write_message(&mut framed, inventory).await;
+    // Expect only the transaction it does not have to be requested and no transactions to be pushed
+    let indexes: proto::InventoryIndexes = read_message(&mut framed).await;
+    assert_eq!(indexes.indexes, [1]);
+    write_message(&mut framed, proto::TransactionItem {
+        transaction: Some(new_transaction.clone().into()),
+    })
+    .await;
+    write_message(&mut framed, proto::TransactionItem::empty()).await;
+    // Expect stream to end once the transaction is inserted
+    assert!(framed.next().await.is_none());
+
+    let transactions = get_snapshot(&mempool1);
+    assert_eq!(transactions.len(), 2);
+    assert!(transactions.contains(&transactions1[0]));
+    assert!(transactions.contains(&new_transaction));
+
+    // The added transaction is passed on to the mempool service, which propagates it to all other peers
+    let (propagated, exclude_peers) = handles.outbound_tx_rx.next().await.unwrap();
+    assert_eq!(propagated, new_transaction);
+    assert_eq!(exclude_peers, vec![node1.node_id().clone()]);
+}
+
+#[tokio_macros::test_basic]
+async fn announcement_peers() {
+    let (_, connectivity_events_tx, _, _, handles) = setup_with_handles(0);
+
+    let node1 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let node2 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let node3 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    // Only node2 supports transaction announcements
+    let mut peer2 = node2.to_peer();
+    peer2.supported_protocols = vec![MEMPOOL_SYNC_PROTOCOL.clone(), MEMPOOL_ANNOUNCEMENT_PROTOCOL.clone()];
+    let mut peer3 = node3.to_peer();
+    peer3.supported_protocols = vec![MEMPOOL_SYNC_PROTOCOL.clone()];
+    handles.peer_manager.add_peer(peer2.clone()).await.unwrap();
+    handles.peer_manager.add_peer(peer3.clone()).await.unwrap();
+
+    let (_, _, node2_conn, _) = create_peer_connection_mock_pair(1, node1.to_peer(), peer2).await;
+    let (_, _, node3_conn, _) = create_peer_connection_mock_pair(1, node1.to_peer(), peer3).await;
+    connectivity_events_tx
+        .send(Arc::new(ConnectivityEvent::PeerConnected(node2_conn)))
+        .unwrap();
+    connectivity_events_tx
+        .send(Arc::new(ConnectivityEvent::PeerConnected(node3_conn)))
+        .unwrap();
+
+    async_assert_eventually!(
+        handles.announcer.announcement_peers(),
+        expect = vec![node2.node_id().clone()],
+        max_attempts = 10
+    );
+    assert!(!handles.announcer.supports_announcements(node3.node_id()));
+
+    connectivity_events_tx
+        .send(Arc::new(ConnectivityEvent::PeerDisconnected(node2.node_id().clone())))
+        .unwrap();
+    async_assert_eventually!(
+        handles.announcer.announcement_peers(),
+        expect = Vec::<NodeId>::new(),
+        max_attempts = 10
+    );
+}
+
 #[tokio_macros::test_basic]
 async fn responder_messages() {
     let (_, connectivity_events_tx, _, transactions1) = setup(1);
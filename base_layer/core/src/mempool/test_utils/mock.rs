@@ -22,10 +22,12 @@
 
 use crate::mempool::{
     service::{MempoolHandle, MempoolRequest, MempoolResponse},
+    FeeHistogramResponse,
     MempoolServiceError,
     StateResponse,
     StatsResponse,
     TxStorageResponse,
+    ValidationTimingResponse,
 };
 use futures::StreamExt;
 use std::sync::{
@@ -49,6 +51,9 @@ pub struct MempoolMockState {
     get_state: Arc<Mutex<StateResponse>>,
     get_tx_state_by_excess_sig: Arc<Mutex<TxStorageResponse>>,
     submit_transaction: Arc<Mutex<TxStorageResponse>>,
+    get_validation_timing: Arc<Mutex<ValidationTimingResponse>>,
+    get_tx_state_by_output_commitment: Arc<Mutex<TxStorageResponse>>,
+    get_fee_histogram: Arc<Mutex<FeeHistogramResponse>>,
     calls: Arc<AtomicUsize>,
 }
 
@@ -60,6 +65,8 @@ impl Default for MempoolMockState {
                 unconfirmed_txs: 0,
                 reorg_txs: 0,
                 total_weight: 0,
+                avg_fee_per_gram: 0,
+                min_fee_per_gram: 0,
             })),
             get_state: Arc::new(Mutex::new(StateResponse {
                 unconfirmed_pool: vec![],
@@ -67,6 +74,14 @@ impl Default for MempoolMockState {
             })),
             get_tx_state_by_excess_sig: Arc::new(Mutex::new(TxStorageResponse::NotStored)),
             submit_transaction: Arc::new(Mutex::new(TxStorageResponse::NotStored)),
+            get_validation_timing: Arc::new(Mutex::new(ValidationTimingResponse {
+                num_validations: 0,
+                mean_duration_us: 0,
+                max_duration_us: 0,
+                buckets: vec![],
+            })),
+            get_tx_state_by_output_commitment: Arc::new(Mutex::new(TxStorageResponse::NotStored)),
+            get_fee_histogram: Arc::new(Mutex::new(FeeHistogramResponse { buckets: vec![] })),
             calls: Arc::new(Default::default()),
         }
     }
@@ -89,6 +104,18 @@ impl MempoolMockState {
         *self.submit_transaction.lock().await = resp;
     }
 
+    pub async fn set_get_validation_timing_response(&self, resp: ValidationTimingResponse) {
+        *self.get_validation_timing.lock().await = resp;
+    }
+
+    pub async fn set_get_tx_by_output_commitment_response(&self, resp: TxStorageResponse) {
+        *self.get_tx_state_by_output_commitment.lock().await = resp;
+    }
+
+    pub async fn set_get_fee_histogram_response(&self, resp: FeeHistogramResponse) {
+        *self.get_fee_histogram.lock().await = resp;
+    }
+
     fn inc_call_count(&self) {
         self.calls.fetch_add(1, Ordering::SeqCst);
     }
@@ -135,6 +162,15 @@ impl MempoolServiceMock {
             SubmitTransaction(_) => Ok(MempoolResponse::TxStorage(
                 self.state.submit_transaction.lock().await.clone(),
             )),
+            GetValidationTiming => Ok(MempoolResponse::ValidationTiming(
+                self.state.get_validation_timing.lock().await.clone(),
+            )),
+            GetTxStateByOutputCommitment(_) => Ok(MempoolResponse::TxStorage(
+                self.state.get_tx_state_by_output_commitment.lock().await.clone(),
+            )),
+            GetFeeHistogram => Ok(MempoolResponse::FeeHistogram(
+                self.state.get_fee_histogram.lock().await.clone(),
+            )),
         }
     }
 }
@@ -23,17 +23,27 @@
 use crate::{
     blocks::Block,
     mempool::{
-        consts::{MEMPOOL_UNCONFIRMED_POOL_STORAGE_CAPACITY, MEMPOOL_UNCONFIRMED_POOL_WEIGHT_TRANSACTION_SKIP_COUNT},
-        priority::{FeePriority, PrioritizedTransaction},
+        consts::{
+            MEMPOOL_UNCONFIRMED_POOL_MAX_BYTES,
+            MEMPOOL_UNCONFIRMED_POOL_STORAGE_CAPACITY,
+            MEMPOOL_UNCONFIRMED_POOL_WEIGHT_TRANSACTION_SKIP_COUNT,
+        },
+        priority::{compare_fee_per_gram_then_age, FeePriority, PrioritizedTransaction},
         unconfirmed_pool::UnconfirmedPoolError,
+        FeeHistogramBucket,
+        FeeHistogramResponse,
+        TransactionPackage,
+        TxStorageResponse,
+    },
+    transactions::{
+        transaction::Transaction,
+        types::{Commitment, Signature},
     },
-    transactions::{transaction::Transaction, types::Signature},
 };
 use log::*;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{BTreeMap, HashMap},
-    convert::TryFrom,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
     sync::Arc,
 };
 use tari_crypto::tari_utilities::hex::Hex;
@@ -48,6 +58,9 @@ pub struct UnconfirmedPoolConfig {
     /// The maximum number of transactions that can be skipped when compiling a set of highest priority transactions,
     /// skipping over large transactions are performed in an attempt to fit more transactions into the remaining space.
     pub weight_tx_skip_count: usize,
+    /// The maximum total serialized size, in bytes, of the transactions stored in the Unconfirmed Transaction pool.
+    /// The transactions with the lowest fee per gram are evicted to make space for a higher priority transaction.
+    pub max_mempool_bytes: u64,
 }
 
 impl Default for UnconfirmedPoolConfig {
@@ -55,6 +68,7 @@ impl Default for UnconfirmedPoolConfig {
         Self {
             storage_capacity: MEMPOOL_UNCONFIRMED_POOL_STORAGE_CAPACITY,
             weight_tx_skip_count: MEMPOOL_UNCONFIRMED_POOL_WEIGHT_TRANSACTION_SKIP_COUNT,
+            max_mempool_bytes: MEMPOOL_UNCONFIRMED_POOL_MAX_BYTES,
         }
     }
 }
@@ -66,11 +80,18 @@ impl Default for UnconfirmedPoolConfig {
 /// transactions in the pool according to TXPriority, it allows transactions to be inserted in sorted order by their
 /// priority. The txs_by_priority BTreeMap makes it easier to select the set of highest priority transactions that can
 /// be included in a block. The excess_sig of a transaction is used a key to uniquely identify a specific transaction in
-/// these containers.
+/// these containers. The txs_by_output BTreeMap maps the output commitments of the stored transactions to the
+/// excess_sig of the transaction that created them and the txs_by_input BTreeMap maps the input commitments of the
+/// stored transactions to the excess_sigs of the transactions that spend them. Together they are used to follow the
+/// dependencies between transactions that spend the outputs of other unconfirmed transactions.
 pub struct UnconfirmedPool {
     config: UnconfirmedPoolConfig,
     txs_by_signature: HashMap<Signature, PrioritizedTransaction>,
     txs_by_priority: BTreeMap<FeePriority, Signature>,
+    txs_by_output: BTreeMap<Commitment, Signature>,
+    txs_by_input: BTreeMap<Commitment, Vec<Signature>>,
+    insert_epoch: u64,
+    total_size: u64,
 }
 
 impl UnconfirmedPool {
@@ -80,25 +101,156 @@ impl UnconfirmedPool {
             config,
             txs_by_signature: HashMap::new(),
             txs_by_priority: BTreeMap::new(),
+            txs_by_output: BTreeMap::new(),
+            txs_by_input: BTreeMap::new(),
+            insert_epoch: 0,
+            total_size: 0,
+        }
+    }
+
+    // Removes the stored transaction with the provided excess_sig and returns it
+    fn remove_tx(&mut self, tx_key: &Signature) -> Option<Arc<Transaction>> {
+        let ptx = self.txs_by_signature.remove(tx_key)?;
+        self.txs_by_priority.remove(&ptx.priority);
+        self.untrack_removed_tx(&ptx);
+        Some(ptx.transaction)
+    }
+
+    // Removes the inputs and outputs of a transaction that was removed from txs_by_signature and releases its size
+    fn untrack_removed_tx(&mut self, ptx: &PrioritizedTransaction) {
+        for output in ptx.transaction.body.outputs() {
+            self.txs_by_output.remove(&output.commitment);
+        }
+        if let Some(tx_key) = ptx.transaction.first_kernel_excess_sig() {
+            for input in ptx.transaction.body.inputs() {
+                if let Some(spenders) = self.txs_by_input.get_mut(&input.commitment) {
+                    spenders.retain(|spender| spender != tx_key);
+                    if spenders.is_empty() {
+                        self.txs_by_input.remove(&input.commitment);
+                    }
+                }
+            }
         }
+        self.total_size -= ptx.size;
+    }
+
+    // Removes the stored transactions with the provided excess_sigs together with their descendants, which spend
+    // outputs that no longer exist once their ancestors are removed, and returns them
+    fn remove_txs_and_descendants(&mut self, tx_keys: Vec<Signature>) -> Vec<Arc<Transaction>> {
+        let mut package_tx_keys = Vec::new();
+        let mut visited = HashSet::new();
+        for tx_key in tx_keys {
+            let descendants = match self.txs_by_signature.get(&tx_key) {
+                Some(ptx) => self.related_txs(&ptx.transaction, |tx| self.children_of(tx)),
+                None => continue,
+            };
+            let descendant_keys = descendants
+                .iter()
+                .filter_map(|tx| tx.first_kernel_excess_sig())
+                .cloned();
+            for package_tx_key in Some(tx_key).into_iter().chain(descendant_keys) {
+                if visited.insert(package_tx_key.clone()) {
+                    package_tx_keys.push(package_tx_key);
+                }
+            }
+        }
+        package_tx_keys
+            .iter()
+            .filter_map(|tx_key| self.remove_tx(tx_key))
+            .collect()
     }
 
-    fn lowest_priority(&self) -> &FeePriority {
-        self.txs_by_priority.iter().next().unwrap().0
+    // Evicts the lowest priority packages until the provided transaction fits within the storage capacity and the
+    // maximum size of the pool, and returns the evicted transactions. Nothing is evicted and None is returned if the
+    // transaction does not fit.
+    fn make_space_for(
+        &mut self,
+        prioritized_tx: &PrioritizedTransaction,
+    ) -> Result<Option<Vec<Arc<Transaction>>>, UnconfirmedPoolError>
+    {
+        Ok(self
+            .txs_to_evict_for(prioritized_tx)?
+            .map(|tx_keys| tx_keys.iter().filter_map(|tx_key| self.remove_tx(tx_key)).collect()))
     }
 
-    fn remove_lowest_priority_tx(&mut self) {
-        if let Some((priority, sig)) = self.txs_by_priority.iter().next().map(|(p, s)| (p.clone(), s.clone())) {
-            self.txs_by_signature.remove(&sig);
-            self.txs_by_priority.remove(&priority);
+    // Returns the excess_sigs of the transactions that have to be evicted for the provided transaction to fit. Every
+    // transaction is evicted together with its descendants, so no stored transaction is left spending the outputs of an
+    // evicted one, and these packages are evicted from the lowest fee per gram, so a low fee parent is kept while its
+    // children pay for it. None is returned if the transaction can only fit by evicting its own ancestors or packages
+    // with a higher fee per gram than the package it forms with its ancestors.
+    fn txs_to_evict_for(
+        &self,
+        prioritized_tx: &PrioritizedTransaction,
+    ) -> Result<Option<Vec<Signature>>, UnconfirmedPoolError>
+    {
+        if prioritized_tx.size > self.config.max_mempool_bytes {
+            return Ok(None);
         }
+        let ancestors = self.related_txs(&prioritized_tx.transaction, |tx| self.parents_of(tx));
+        let ancestor_keys = ancestors
+            .iter()
+            .filter_map(|tx| tx.first_kernel_excess_sig())
+            .collect::<HashSet<_>>();
+        let package_priority = (
+            package_fee_per_gram(&prioritized_tx.transaction, &ancestors),
+            &prioritized_tx.priority,
+        );
+
+        let mut evict_tx_keys = Vec::new();
+        let mut evicted_tx_keys = HashSet::new();
+        let mut evict_size = 0;
+        let mut lowest_priority_packages = self.package_priorities(|tx| self.children_of(tx))?.into_iter().rev();
+        while self.txs_by_signature.len() - evict_tx_keys.len() >= self.config.storage_capacity ||
+            self.total_size - evict_size + prioritized_tx.size > self.config.max_mempool_bytes
+        {
+            match lowest_priority_packages.next() {
+                Some((fee_per_gram, priority, tx_key)) if (fee_per_gram, priority) < package_priority => {
+                    if evicted_tx_keys.contains(tx_key) || ancestor_keys.contains(&tx_key) {
+                        continue;
+                    }
+                    let ptx = self
+                        .txs_by_signature
+                        .get(tx_key)
+                        .ok_or_else(|| UnconfirmedPoolError::StorageOutofSync)?;
+                    let descendants = self.related_txs(&ptx.transaction, |tx| self.children_of(tx));
+                    let package_tx_keys = descendants
+                        .iter()
+                        .filter_map(|tx| tx.first_kernel_excess_sig())
+                        .chain(Some(tx_key));
+                    for package_tx_key in package_tx_keys {
+                        if evicted_tx_keys.insert(package_tx_key.clone()) {
+                            evict_size += self
+                                .txs_by_signature
+                                .get(package_tx_key)
+                                .map(|ptx| ptx.size)
+                                .unwrap_or(0);
+                            evict_tx_keys.push(package_tx_key.clone());
+                        }
+                    }
+                },
+                _ => return Ok(None),
+            }
+        }
+        Ok(Some(evict_tx_keys))
     }
 
     /// Insert a new transaction into the UnconfirmedPool. Low priority transactions will be removed to make space for
-    /// higher priority transactions. The lowest priority transactions will be removed when the maximum capacity is
-    /// reached and the new transaction has a higher priority than the currently stored lowest priority transaction.
+    /// higher priority transactions. The lowest priority transactions will be removed when the maximum capacity or the
+    /// maximum size in bytes is reached and the new transaction has a higher priority than the removed transactions.
+    /// Returns `TxStorageResponse::NotStored` if there is no space for the transaction.
+    pub fn insert(&mut self, tx: Arc<Transaction>) -> Result<TxStorageResponse, UnconfirmedPoolError> {
+        self.insert_and_evict(tx).map(|(response, _)| response)
+    }
+
+    /// Insert a new transaction into the UnconfirmedPool in the same way as `insert`, and also return the lower
+    /// priority transactions that were evicted to make space for it.
     #[allow(clippy::map_entry)]
-    pub fn insert(&mut self, tx: Arc<Transaction>) -> Result<(), UnconfirmedPoolError> {
+    pub fn insert_and_evict(
+        &mut self,
+        tx: Arc<Transaction>,
+    ) -> Result<(TxStorageResponse, Vec<Arc<Transaction>>), UnconfirmedPoolError>
+    {
+        let mut evicted_txs = Vec::new();
         let tx_key = tx
             .first_kernel_excess_sig()
             .ok_or_else(|| UnconfirmedPoolError::TransactionNoKernels)?;
@@ -109,18 +261,34 @@ impl UnconfirmedPool {
                 tx_key.get_signature().to_hex()
             );
             trace!(target: LOG_TARGET, "Transaction inserted: {}", tx);
-            let prioritized_tx = PrioritizedTransaction::try_from((*tx).clone())?;
-            if self.txs_by_signature.len() >= self.config.storage_capacity {
-                if prioritized_tx.priority < *self.lowest_priority() {
-                    return Ok(());
-                }
-                self.remove_lowest_priority_tx();
+            let prioritized_tx = PrioritizedTransaction::try_construct((*tx).clone(), self.insert_epoch)?;
+            match self.make_space_for(&prioritized_tx)? {
+                Some(txs) => evicted_txs = txs,
+                None => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Unconfirmed pool is full, not storing lower priority tx: {}",
+                        tx_key.get_signature().to_hex()
+                    );
+                    return Ok((TxStorageResponse::NotStored, evicted_txs));
+                },
             }
+            self.total_size += prioritized_tx.size;
             self.txs_by_priority
                 .insert(prioritized_tx.priority.clone(), tx_key.clone());
+            for output in tx.body.outputs() {
+                self.txs_by_output.insert(output.commitment.clone(), tx_key.clone());
+            }
+            for input in tx.body.inputs() {
+                self.txs_by_input
+                    .entry(input.commitment.clone())
+                    .or_insert_with(Vec::new)
+                    .push(tx_key.clone());
+            }
             self.txs_by_signature.insert(tx_key.clone(), prioritized_tx);
+            self.insert_epoch += 1;
         }
-        Ok(())
+        Ok((TxStorageResponse::UnconfirmedPool, evicted_txs))
     }
 
     /// Insert a set of new transactions into the UnconfirmedPool
@@ -137,21 +305,62 @@ impl UnconfirmedPool {
         self.txs_by_signature.contains_key(excess_sig)
     }
 
-    /// Returns a set of the highest priority unconfirmed transactions, that can be included in a block
+    /// Check if a transaction that creates the output with the provided commitment is available in the
+    /// UnconfirmedPool
+    pub fn has_tx_with_output(&self, commitment: &Commitment) -> bool {
+        self.txs_by_output.contains_key(commitment)
+    }
+
+    /// Returns the transactions in the UnconfirmedPool that spend any of the inputs of the provided transaction. The
+    /// provided transaction itself is not reported if it is stored in the pool.
+    pub fn find_conflicting_txs(&self, tx: &Transaction) -> Vec<Arc<Transaction>> {
+        let excess_sig = tx.first_kernel_excess_sig();
+        self.txs_by_signature
+            .iter()
+            .filter(|(sig, _)| Some(*sig) != excess_sig)
+            .filter(|(_, ptx)| {
+                ptx.transaction
+                    .body
+                    .inputs()
+                    .iter()
+                    .any(|input| tx.body.inputs().contains(input))
+            })
+            .map(|(_, ptx)| ptx.transaction.clone())
+            .collect()
+    }
+
+    /// Returns a set of the highest priority unconfirmed transactions, that can be included in a block. Transactions
+    /// are prioritised by the fee per gram of the package they form with their unconfirmed ancestors, so that a high
+    /// fee child can pay for a low fee parent, and a transaction is only selected together with its ancestors.
     pub fn highest_priority_txs(&self, total_weight: u64) -> Result<Vec<Arc<Transaction>>, UnconfirmedPoolError> {
         let mut selected_txs: Vec<Arc<Transaction>> = Vec::new();
+        let mut selected_tx_keys: HashSet<Signature> = HashSet::new();
         let mut curr_weight: u64 = 0;
         let mut curr_skip_count: usize = 0;
-        for (_, tx_key) in self.txs_by_priority.iter().rev() {
-            let ptx = self
-                .txs_by_signature
-                .get(tx_key)
-                .ok_or_else(|| UnconfirmedPoolError::StorageOutofSync)?;
+        for tx_key in self.txs_by_package_priority()? {
+            if selected_tx_keys.contains(&tx_key) {
+                continue;
+            }
+            let mut package = Vec::new();
+            for package_tx_key in self.unselected_ancestors_first(&tx_key, &selected_tx_keys) {
+                let ptx = self
+                    .txs_by_signature
+                    .get(&package_tx_key)
+                    .ok_or_else(|| UnconfirmedPoolError::StorageOutofSync)?;
+                package.push((package_tx_key, ptx));
+            }
+            let package_weight = package.iter().map(|(_, ptx)| ptx.weight).sum::<u64>();
 
-            if curr_weight + ptx.weight <= total_weight {
-                if !UnconfirmedPool::find_duplicate_input(&selected_txs, &ptx.transaction) {
-                    curr_weight += ptx.weight;
-                    selected_txs.push(ptx.transaction.clone());
+            if curr_weight + package_weight <= total_weight {
+                if !package
+                    .iter()
+                    .any(|(_, ptx)| UnconfirmedPool::find_duplicate_input(&selected_txs, &ptx.transaction))
+                {
+                    curr_weight += package_weight;
+                    for (package_tx_key, ptx) in package {
+                        selected_tx_keys.insert(package_tx_key);
+                        selected_txs.push(ptx.transaction.clone());
+                    }
                 }
             } else {
                 // Check if some the next few txs with slightly lower priority wont fit in the remaining space.
@@ -164,6 +373,120 @@ impl UnconfirmedPool {
         Ok(selected_txs)
     }
 
+    /// Returns a set of the highest priority unconfirmed transactions with a total weight of at most `weight_limit`.
+    /// Unlike [highest_priority_txs](UnconfirmedPool::highest_priority_txs), each transaction is ranked by its own fee
+    /// per gram, with ties broken by age (see [compare_fee_per_gram_then_age]). A transaction that spends the outputs
+    /// of another unconfirmed transaction is only selected once its parents have been selected; if it is ranked above
+    /// one of its parents it is reconsidered as soon as its last parent is selected.
+    pub fn highest_fee_per_gram_txs(&self, weight_limit: u64) -> Vec<Arc<Transaction>> {
+        let mut txs = self.txs_by_signature.iter().collect::<Vec<_>>();
+        txs.sort_by(|(_, a), (_, b)| compare_fee_per_gram_then_age(a, b));
+        let mut txs = txs.into_iter();
+
+        let mut selected_txs: Vec<Arc<Transaction>> = Vec::new();
+        let mut selected_tx_keys: HashSet<Signature> = HashSet::new();
+        let mut waiting_on_parents = Vec::new();
+        let mut requeued = VecDeque::new();
+        let mut curr_weight: u64 = 0;
+        let mut curr_skip_count: usize = 0;
+        while let Some((tx_key, ptx)) = requeued.pop_front().or_else(|| txs.next()) {
+            if curr_weight + ptx.weight > weight_limit {
+                // Check if some the next few txs with slightly lower priority wont fit in the remaining space.
+                curr_skip_count += 1;
+                if curr_skip_count >= self.config.weight_tx_skip_count {
+                    break;
+                }
+                continue;
+            }
+            let has_unselected_parent = self
+                .parents_of(&ptx.transaction)
+                .iter()
+                .any(|parent| !selected_tx_keys.contains(parent));
+            if has_unselected_parent {
+                waiting_on_parents.push((tx_key, ptx));
+                continue;
+            }
+            if UnconfirmedPool::find_duplicate_input(&selected_txs, &ptx.transaction) {
+                continue;
+            }
+            curr_weight += ptx.weight;
+            selected_tx_keys.insert(tx_key.clone());
+            selected_txs.push(ptx.transaction.clone());
+
+            // Dependents that were passed over for this transaction are reconsidered, in rank order, before moving on
+            let (ready, waiting): (Vec<_>, Vec<_>) = waiting_on_parents.into_iter().partition(|(_, waiting_ptx)| {
+                self.parents_of(&waiting_ptx.transaction)
+                    .iter()
+                    .all(|parent| selected_tx_keys.contains(parent))
+            });
+            waiting_on_parents = waiting;
+            requeued.extend(ready);
+        }
+        selected_txs
+    }
+
+    // Returns the excess_sigs of all stored transactions ordered from the highest to the lowest fee per gram of the
+    // package formed by the transaction and its ancestors. The fee per gram is floored to 3 decimal places, as in
+    // FeePriority, and ties are broken by the FeePriority of the transaction, so transactions without ancestors keep
+    // their FeePriority order.
+    fn txs_by_package_priority(&self) -> Result<Vec<Signature>, UnconfirmedPoolError> {
+        Ok(self
+            .package_priorities(|tx| self.parents_of(tx))?
+            .into_iter()
+            .map(|(_, _, tx_key)| tx_key.clone())
+            .collect())
+    }
+
+    // Returns the fee per gram and FeePriority of the packages formed by every stored transaction and the transactions
+    // that are reachable from it through `neighbours`, ordered from the highest to the lowest priority
+    fn package_priorities<F>(
+        &self,
+        neighbours: F,
+    ) -> Result<Vec<(u64, &FeePriority, &Signature)>, UnconfirmedPoolError>
+    where
+        F: Fn(&Transaction) -> Vec<Signature>,
+    {
+        let mut txs = Vec::with_capacity(self.txs_by_priority.len());
+        for (priority, tx_key) in self.txs_by_priority.iter() {
+            let ptx = self
+                .txs_by_signature
+                .get(tx_key)
+                .ok_or_else(|| UnconfirmedPoolError::StorageOutofSync)?;
+            let package = self.related_txs(&ptx.transaction, &neighbours);
+            txs.push((package_fee_per_gram(&ptx.transaction, &package), priority, tx_key));
+        }
+        txs.sort_by(|a, b| (b.0, b.1).cmp(&(a.0, a.1)));
+        Ok(txs)
+    }
+
+    // Returns the excess_sigs of the transaction and its ancestors that are not in `selected_tx_keys`, ordered so that
+    // every transaction comes after the ancestors it spends from.
+    fn unselected_ancestors_first(&self, tx_key: &Signature, selected_tx_keys: &HashSet<Signature>) -> Vec<Signature> {
+        let mut ordered = Vec::new();
+        let mut visited = HashSet::new();
+        self.visit_ancestors_first(tx_key, selected_tx_keys, &mut visited, &mut ordered);
+        ordered
+    }
+
+    fn visit_ancestors_first(
+        &self,
+        tx_key: &Signature,
+        selected_tx_keys: &HashSet<Signature>,
+        visited: &mut HashSet<Signature>,
+        ordered: &mut Vec<Signature>,
+    )
+    {
+        if selected_tx_keys.contains(tx_key) || !visited.insert(tx_key.clone()) {
+            return;
+        }
+        if let Some(ptx) = self.txs_by_signature.get(tx_key) {
+            for parent in self.parents_of(&ptx.transaction) {
+                self.visit_ancestors_first(&parent, selected_tx_keys, visited, ordered);
+            }
+            ordered.push(tx_key.clone());
+        }
+    }
+
     // This will search a Vec<Arc<Transaction>> for duplicate inputs of a tx
     fn find_duplicate_input(array_of_tx: &[Arc<Transaction>], tx: &Arc<Transaction>) -> bool {
         for transaction in array_of_tx {
@@ -176,68 +499,138 @@ impl UnconfirmedPool {
         false
     }
 
-    /// Remove all published transactions from the UnconfirmedPool and discard all double spend transactions.
-    /// Returns a list of all transactions that were removed the unconfirmed pool as a result of appearing in the block.
-    fn discard_double_spends(&mut self, published_block: &Block) {
-        let mut removed_tx_keys = Vec::new();
-        for (tx_key, ptx) in self.txs_by_signature.iter() {
-            for input in ptx.transaction.body.inputs() {
-                if published_block.body.inputs().contains(input) {
-                    self.txs_by_priority.remove(&ptx.priority);
-                    removed_tx_keys.push(tx_key.clone());
-                }
-            }
-        }
-
+    /// Discard all the transactions that spend the inputs of the published block, together with their descendants, and
+    /// return them.
+    fn discard_double_spends(&mut self, published_block: &Block) -> Vec<Arc<Transaction>> {
+        let removed_tx_keys = self
+            .txs_by_signature
+            .iter()
+            .filter(|(_, ptx)| {
+                ptx.transaction
+                    .body
+                    .inputs()
+                    .iter()
+                    .any(|input| published_block.body.inputs().contains(input))
+            })
+            .map(|(tx_key, _)| tx_key.clone())
+            .collect::<Vec<_>>();
         for tx_key in &removed_tx_keys {
             trace!(
                 target: LOG_TARGET,
                 "Removing double spends from unconfirmed pool: {:?}",
                 tx_key
             );
-            self.txs_by_signature.remove(&tx_key);
         }
+        self.remove_txs_and_descendants(removed_tx_keys)
     }
 
-    /// Remove all published transactions from the UnconfirmedPoolStorage and discard double spends
-    pub fn remove_published_and_discard_double_spends(&mut self, published_block: &Block) -> Vec<Arc<Transaction>> {
+    /// Remove all published transactions from the UnconfirmedPoolStorage and discard double spends. Returns the
+    /// published transactions and the discarded double spends.
+    pub fn remove_published_and_discard_double_spends(
+        &mut self,
+        published_block: &Block,
+    ) -> (Vec<Arc<Transaction>>, Vec<Arc<Transaction>>)
+    {
         let mut removed_txs = Vec::new();
         published_block.body.kernels().iter().for_each(|kernel| {
             if let Some(ptx) = self.txs_by_signature.get(&kernel.excess_sig) {
                 self.txs_by_priority.remove(&ptx.priority);
                 if let Some(ptx) = self.txs_by_signature.remove(&kernel.excess_sig) {
+                    self.untrack_removed_tx(&ptx);
                     removed_txs.push(ptx.transaction);
                 }
             }
         });
         // First remove published transactions before discarding double spends
-        self.discard_double_spends(published_block);
+        let discarded_txs = self.discard_double_spends(published_block);
 
-        removed_txs
+        (removed_txs, discarded_txs)
     }
 
-    /// Remove all unconfirmed transactions that have become time locked. This can happen when the chain height was
-    /// reduced on some reorgs.
+    /// Remove all unconfirmed transactions that have become time locked, together with their descendants. This can
+    /// happen when the chain height was reduced on some reorgs.
     pub fn remove_timelocked(&mut self, tip_height: u64) -> Vec<Arc<Transaction>> {
-        let mut removed_tx_keys: Vec<Signature> = Vec::new();
-        for (tx_key, ptx) in self.txs_by_signature.iter() {
-            if ptx.transaction.min_spendable_height() > tip_height + 1 {
-                self.txs_by_priority.remove(&ptx.priority);
-                removed_tx_keys.push(tx_key.clone());
-            }
-        }
-        let mut removed_txs: Vec<Arc<Transaction>> = Vec::new();
-        for tx_key in removed_tx_keys {
+        let removed_tx_keys = self
+            .txs_by_signature
+            .iter()
+            .filter(|(_, ptx)| ptx.transaction.min_spendable_height() > tip_height + 1)
+            .map(|(tx_key, _)| tx_key.clone())
+            .collect::<Vec<_>>();
+        for tx_key in &removed_tx_keys {
             trace!(
                 target: LOG_TARGET,
                 "Removing time locked transaction from unconfirmed pool: {:?}",
                 tx_key
             );
-            if let Some(ptx) = self.txs_by_signature.remove(&tx_key) {
-                removed_txs.push(ptx.transaction);
+        }
+        self.remove_txs_and_descendants(removed_tx_keys)
+    }
+
+    /// Returns the ancestors and descendants of the stored transaction with the provided excess_sig, together with the
+    /// aggregate fees and weights of the packages they form with it. Returns None if the transaction is not stored in
+    /// the UnconfirmedPool.
+    pub fn transaction_package(&self, excess_sig: &Signature) -> Option<TransactionPackage> {
+        let ptx = self.txs_by_signature.get(excess_sig)?;
+        let ancestors = self.related_txs(&ptx.transaction, |tx| self.parents_of(tx));
+        let descendants = self.related_txs(&ptx.transaction, |tx| self.children_of(tx));
+
+        let fee = ptx.transaction.body.get_total_fee();
+        let sum_fees = |txs: &[Arc<Transaction>]| txs.iter().fold(fee, |total, tx| total + tx.body.get_total_fee());
+        let sum_weights =
+            |txs: &[Arc<Transaction>]| txs.iter().fold(ptx.weight, |total, tx| total + tx.calculate_weight());
+        Some(TransactionPackage {
+            ancestor_fee: sum_fees(&ancestors),
+            ancestor_weight: sum_weights(&ancestors),
+            descendant_fee: sum_fees(&descendants),
+            descendant_weight: sum_weights(&descendants),
+            ancestors: ancestors.iter().map(|tx| tx.as_ref().clone()).collect(),
+            descendants: descendants.iter().map(|tx| tx.as_ref().clone()).collect(),
+        })
+    }
+
+    // Returns the excess_sigs of the stored transactions that created the outputs spent by the provided transaction
+    fn parents_of(&self, tx: &Transaction) -> Vec<Signature> {
+        tx.body
+            .inputs()
+            .iter()
+            .filter_map(|input| self.txs_by_output.get(&input.commitment))
+            .cloned()
+            .collect()
+    }
+
+    // Returns the excess_sigs of the stored transactions that spend the outputs of the provided transaction
+    fn children_of(&self, tx: &Transaction) -> Vec<Signature> {
+        tx.body
+            .outputs()
+            .iter()
+            .filter_map(|output| self.txs_by_input.get(&output.commitment))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    // Walks the dependency graph from the provided transaction and returns all the stored transactions that are
+    // reachable through `neighbours`, excluding the starting transaction. The starting transaction does not have to be
+    // stored in the pool.
+    fn related_txs<F>(&self, tx: &Transaction, neighbours: F) -> Vec<Arc<Transaction>>
+    where F: Fn(&Transaction) -> Vec<Signature> {
+        let mut visited = tx
+            .first_kernel_excess_sig()
+            .into_iter()
+            .cloned()
+            .collect::<HashSet<_>>();
+        let mut pending = neighbours(tx);
+        let mut related = Vec::new();
+        while let Some(tx_key) = pending.pop() {
+            if !visited.insert(tx_key.clone()) {
+                continue;
+            }
+            if let Some(ptx) = self.txs_by_signature.get(&tx_key) {
+                related.push(ptx.transaction.clone());
+                pending.extend(neighbours(&ptx.transaction));
             }
         }
-        removed_txs
+        related
     }
 
     /// Returns the total number of unconfirmed transactions stored in the UnconfirmedPool.
@@ -260,18 +653,90 @@ impl UnconfirmedPool {
             .fold(0, |weight, (_, ptx)| weight + ptx.transaction.calculate_weight())
     }
 
+    /// Returns the total fees of all transactions stored in the pool divided by their total weight, or zero if the pool
+    /// is empty.
+    pub fn calculate_avg_fee_per_gram(&self) -> u64 {
+        let (total_fee, total_weight) = self.txs_by_signature.iter().fold((0, 0), |(fee, weight), (_, ptx)| {
+            (fee + ptx.transaction.body.get_total_fee().0, weight + ptx.weight)
+        });
+        if total_weight == 0 {
+            0
+        } else {
+            total_fee / total_weight
+        }
+    }
+
+    /// Returns a histogram of the fee per gram paid by the transactions stored in the pool, ordered from the highest to
+    /// the lowest fee per gram.
+    pub fn fee_histogram(&self) -> FeeHistogramResponse {
+        let mut buckets = BTreeMap::<u64, FeeHistogramBucket>::new();
+        for ptx in self.txs_by_signature.values().filter(|ptx| ptx.weight > 0) {
+            let fee_per_gram = ptx.transaction.body.get_total_fee().0 / ptx.weight;
+            let bucket = buckets.entry(fee_per_gram).or_insert_with(|| FeeHistogramBucket {
+                fee_per_gram,
+                transaction_count: 0,
+                weight: 0,
+            });
+            bucket.transaction_count += 1;
+            bucket.weight += ptx.weight;
+        }
+        FeeHistogramResponse {
+            buckets: buckets.into_iter().rev().map(|(_, bucket)| bucket).collect(),
+        }
+    }
+
+    /// Returns the lowest fee per gram of the transactions stored in the pool, or zero if the pool is empty.
+    pub fn calculate_min_fee_per_gram(&self) -> u64 {
+        self.txs_by_signature
+            .iter()
+            .filter(|(_, ptx)| ptx.weight > 0)
+            .map(|(_, ptx)| ptx.transaction.body.get_total_fee().0 / ptx.weight)
+            .min()
+            .unwrap_or(0)
+    }
+
     #[cfg(test)]
     /// Returns false if there are any inconsistencies in the internal mempool state, otherwise true
     fn check_status(&self) -> bool {
         if self.txs_by_priority.len() != self.txs_by_signature.len() {
             return false;
         }
+        if self.txs_by_signature.values().map(|ptx| ptx.size).sum::<u64>() != self.total_size {
+            return false;
+        }
+        if !self
+            .txs_by_output
+            .values()
+            .all(|tx_key| self.txs_by_signature.contains_key(tx_key))
+        {
+            return false;
+        }
+        if !self
+            .txs_by_input
+            .values()
+            .flatten()
+            .all(|tx_key| self.txs_by_signature.contains_key(tx_key))
+        {
+            return false;
+        }
         self.txs_by_priority
             .iter()
             .all(|(_, tx_key)| self.txs_by_signature.contains_key(tx_key))
     }
 }
 
+// Returns the fee per gram of the package formed by the transaction and the related transactions, floored to 3 decimal
+// places as in FeePriority
+fn package_fee_per_gram(tx: &Transaction, related_txs: &[Arc<Transaction>]) -> u64 {
+    let package_fee = related_txs
+        .iter()
+        .fold(tx.body.get_total_fee().0, |total, tx| total + tx.body.get_total_fee().0);
+    let package_weight = related_txs
+        .iter()
+        .fold(tx.calculate_weight(), |total, tx| total + tx.calculate_weight());
+    (package_fee as f64 / package_weight as f64 * 1000.0) as u64
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -282,7 +747,7 @@ mod test {
             fee::Fee,
             helpers::TestParams,
             tari_amount::MicroTari,
-            transaction::{KernelFeatures, UnblindedOutput},
+            transaction::{KernelFeatures, TransactionInput, UnblindedOutput},
             types::{CryptoFactories, HashDigest},
             SenderTransactionProtocol,
         },
@@ -314,6 +779,7 @@ mod test {
         let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
             storage_capacity: 4,
             weight_tx_skip_count: 3,
+            ..Default::default()
         });
         unconfirmed_pool
             .insert_txs(vec![tx1.clone(), tx2.clone(), tx3.clone(), tx4.clone(), tx5.clone()])
@@ -352,6 +818,101 @@ mod test {
         assert!(unconfirmed_pool.check_status());
     }
 
+    #[test]
+    fn test_highest_fee_per_gram_txs() {
+        let tx1 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1).0);
+        let tx2 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 4, outputs: 1).0);
+        let tx3 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(100), inputs: 5, outputs: 1).0);
+        let tx4 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(30), inputs: 3, outputs: 1).0);
+        // Pays the same fee per gram as tx1, but is inserted after it
+        let tx5 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1).0);
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            storage_capacity: 10,
+            weight_tx_skip_count: 3,
+            ..Default::default()
+        });
+        unconfirmed_pool
+            .insert_txs(vec![tx2.clone(), tx1.clone(), tx4.clone(), tx5.clone(), tx3.clone()])
+            .unwrap();
+
+        let selected_txs = unconfirmed_pool.highest_fee_per_gram_txs(u64::MAX);
+        assert_eq!(selected_txs, vec![
+            tx3.clone(),
+            tx1.clone(),
+            tx5.clone(),
+            tx4.clone(),
+            tx2.clone()
+        ]);
+
+        let weight_limit = tx3.calculate_weight() + tx1.calculate_weight() + tx5.calculate_weight();
+        let selected_txs = unconfirmed_pool.highest_fee_per_gram_txs(weight_limit);
+        assert_eq!(selected_txs, vec![tx3.clone(), tx1.clone(), tx5]);
+
+        for weight_limit in &[
+            0,
+            tx1.calculate_weight(),
+            tx3.calculate_weight(),
+            tx2.calculate_weight() + tx4.calculate_weight(),
+            weight_limit - 1,
+        ] {
+            let selected_txs = unconfirmed_pool.highest_fee_per_gram_txs(*weight_limit);
+            let total_weight = selected_txs.iter().map(|tx| tx.calculate_weight()).sum::<u64>();
+            assert!(total_weight <= *weight_limit);
+        }
+    }
+
+    #[test]
+    fn test_max_mempool_bytes() {
+        let tx1 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 2, outputs: 1).0);
+        let tx2 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(30), inputs: 2, outputs: 1).0);
+        let tx3 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(40), inputs: 2, outputs: 1).0);
+        let tx4 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1).0);
+        let tx5 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(10), inputs: 2, outputs: 1).0);
+        let tx_size = |tx: &Arc<Transaction>| bincode::serialized_size(tx.as_ref()).unwrap();
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            max_mempool_bytes: tx_size(&tx1) + tx_size(&tx2) + tx_size(&tx3),
+            ..Default::default()
+        });
+        for tx in &[tx1.clone(), tx2.clone(), tx3.clone()] {
+            assert_eq!(
+                unconfirmed_pool.insert(tx.clone()).unwrap(),
+                TxStorageResponse::UnconfirmedPool
+            );
+        }
+        assert_eq!(unconfirmed_pool.total_size, unconfirmed_pool.config.max_mempool_bytes);
+
+        // The pool is full, so the higher fee transaction evicts the lowest fee transaction
+        assert_eq!(
+            unconfirmed_pool.insert(tx4.clone()).unwrap(),
+            TxStorageResponse::UnconfirmedPool
+        );
+        assert!(!unconfirmed_pool.has_tx_with_excess_sig(&tx1.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&tx2.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&tx3.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&tx4.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.total_size <= unconfirmed_pool.config.max_mempool_bytes);
+
+        // A transaction that can't beat the current minimum is rejected
+        assert_eq!(
+            unconfirmed_pool.insert(tx5.clone()).unwrap(),
+            TxStorageResponse::NotStored
+        );
+        assert!(!unconfirmed_pool.has_tx_with_excess_sig(&tx5.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&tx2.body.kernels()[0].excess_sig));
+        assert_eq!(unconfirmed_pool.len(), 3);
+        assert!(unconfirmed_pool.check_status());
+
+        // A transaction larger than the pool itself is never stored
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            max_mempool_bytes: tx_size(&tx4) - 1,
+            ..Default::default()
+        });
+        assert_eq!(unconfirmed_pool.insert(tx4).unwrap(), TxStorageResponse::NotStored);
+        assert_eq!(unconfirmed_pool.len(), 0);
+    }
+
     #[test]
     fn test_double_spend_inputs() {
         let (tx1, _, _) = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 1, outputs: 1);
@@ -391,6 +952,7 @@ mod test {
         let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
             storage_capacity: 4,
             weight_tx_skip_count: 3,
+            ..Default::default()
         });
 
         unconfirmed_pool
@@ -420,6 +982,7 @@ mod test {
         let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
             storage_capacity: 10,
             weight_tx_skip_count: 3,
+            ..Default::default()
         });
         unconfirmed_pool
             .insert_txs(vec![tx1.clone(), tx2.clone(), tx3.clone(), tx4.clone(), tx5.clone()])
@@ -485,6 +1048,7 @@ mod test {
         let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
             storage_capacity: 10,
             weight_tx_skip_count: 3,
+            ..Default::default()
         });
         unconfirmed_pool
             .insert_txs(vec![
@@ -529,4 +1093,316 @@ mod test {
 
         assert!(unconfirmed_pool.check_status());
     }
+
+    #[test]
+    fn test_fee_per_gram_stats() {
+        let tx1 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1).0);
+        let tx2 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 4, outputs: 1).0);
+        let tx3 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(100), inputs: 5, outputs: 1).0);
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            storage_capacity: 10,
+            weight_tx_skip_count: 3,
+            ..Default::default()
+        });
+        assert_eq!(unconfirmed_pool.calculate_avg_fee_per_gram(), 0);
+        assert_eq!(unconfirmed_pool.calculate_min_fee_per_gram(), 0);
+
+        unconfirmed_pool
+            .insert_txs(vec![tx1.clone(), tx2.clone(), tx3.clone()])
+            .unwrap();
+
+        let txs = [tx1, tx2, tx3];
+        let total_fee = txs.iter().map(|tx| tx.body.get_total_fee().0).sum::<u64>();
+        let total_weight = txs.iter().map(|tx| tx.calculate_weight()).sum::<u64>();
+        assert_eq!(unconfirmed_pool.calculate_avg_fee_per_gram(), total_fee / total_weight);
+        let tx2_fee_per_gram = txs[1].body.get_total_fee().0 / txs[1].calculate_weight();
+        assert_eq!(unconfirmed_pool.calculate_min_fee_per_gram(), tx2_fee_per_gram);
+        assert!(unconfirmed_pool.calculate_avg_fee_per_gram() > tx2_fee_per_gram);
+    }
+
+    #[test]
+    fn test_fee_histogram() {
+        let tx1 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1).0);
+        let tx2 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1).0);
+        let tx3 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 4, outputs: 1).0);
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            storage_capacity: 10,
+            weight_tx_skip_count: 3,
+            ..Default::default()
+        });
+        assert!(unconfirmed_pool.fee_histogram().buckets.is_empty());
+
+        unconfirmed_pool
+            .insert_txs(vec![tx1.clone(), tx2.clone(), tx3.clone()])
+            .unwrap();
+
+        let fee_per_gram = |tx: &Transaction| tx.body.get_total_fee().0 / tx.calculate_weight();
+        let histogram = unconfirmed_pool.fee_histogram();
+        assert_eq!(histogram.buckets.len(), 2);
+        assert_eq!(histogram.buckets[0].fee_per_gram, fee_per_gram(&tx1));
+        assert_eq!(histogram.buckets[0].transaction_count, 2);
+        assert_eq!(
+            histogram.buckets[0].weight,
+            tx1.calculate_weight() + tx2.calculate_weight()
+        );
+        assert_eq!(histogram.buckets[1].fee_per_gram, fee_per_gram(&tx3));
+        assert_eq!(histogram.buckets[1].transaction_count, 1);
+        assert_eq!(histogram.buckets[1].weight, tx3.calculate_weight());
+    }
+
+    #[test]
+    fn test_transaction_package() {
+        // Spend the first output of `parent` with the first input of `child`
+        fn spend_output_of(parent: &Transaction, child: &mut Transaction) {
+            let output = &parent.body.outputs()[0];
+            child.body.inputs_mut()[0] = TransactionInput::new(output.features.clone(), output.commitment.clone());
+        }
+
+        let tx1 = tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 2, outputs: 2).0;
+        let mut tx2 = tx!(MicroTari(5_000), fee: MicroTari(100), inputs: 1, outputs: 2).0;
+        let mut tx3 = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 1, outputs: 1).0;
+        let tx4 = tx!(MicroTari(5_000), fee: MicroTari(30), inputs: 2, outputs: 1).0;
+        // tx1 -> tx2 -> tx3 form a chain, tx4 is unrelated
+        spend_output_of(&tx1, &mut tx2);
+        spend_output_of(&tx2, &mut tx3);
+        let tx1 = Arc::new(tx1);
+        let tx2 = Arc::new(tx2);
+        let tx3 = Arc::new(tx3);
+        let tx4 = Arc::new(tx4);
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            storage_capacity: 10,
+            weight_tx_skip_count: 3,
+            ..Default::default()
+        });
+        unconfirmed_pool
+            .insert_txs(vec![tx1.clone(), tx2.clone(), tx3.clone(), tx4.clone()])
+            .unwrap();
+
+        let fee = |tx: &Arc<Transaction>| tx.body.get_total_fee();
+        let weight = |tx: &Arc<Transaction>| tx.calculate_weight();
+
+        let package = unconfirmed_pool
+            .transaction_package(&tx2.body.kernels()[0].excess_sig)
+            .unwrap();
+        assert_eq!(package.ancestors, vec![(*tx1).clone()]);
+        assert_eq!(package.descendants, vec![(*tx3).clone()]);
+        assert_eq!(package.ancestor_fee, fee(&tx1) + fee(&tx2));
+        assert_eq!(package.ancestor_weight, weight(&tx1) + weight(&tx2));
+        assert_eq!(package.descendant_fee, fee(&tx2) + fee(&tx3));
+        assert_eq!(package.descendant_weight, weight(&tx2) + weight(&tx3));
+
+        // The child pays for its low fee parent
+        let parent_package = unconfirmed_pool
+            .transaction_package(&tx1.body.kernels()[0].excess_sig)
+            .unwrap();
+        assert!(parent_package.ancestors.is_empty());
+        assert_eq!(parent_package.descendants.len(), 2);
+        let parent_fee_per_gram = fee(&tx1).0 as f64 / weight(&tx1) as f64;
+        assert!(package.ancestor_fee_per_gram() > parent_fee_per_gram);
+        assert!(parent_package.descendant_fee_per_gram() > parent_fee_per_gram);
+        let expected = (fee(&tx1) + fee(&tx2)).0 as f64 / (weight(&tx1) + weight(&tx2)) as f64;
+        assert!((package.ancestor_fee_per_gram() - expected).abs() < f64::EPSILON);
+
+        let unrelated_package = unconfirmed_pool
+            .transaction_package(&tx4.body.kernels()[0].excess_sig)
+            .unwrap();
+        assert!(unrelated_package.ancestors.is_empty());
+        assert!(unrelated_package.descendants.is_empty());
+        assert_eq!(unrelated_package.ancestor_fee, fee(&tx4));
+
+        // Removing the parent removes it from the dependency graph
+        let published_block = create_orphan_block(
+            0,
+            vec![(*tx1).clone()],
+            &ConsensusManagerBuilder::new(Network::LocalNet).build(),
+        );
+        unconfirmed_pool.remove_published_and_discard_double_spends(&published_block);
+        let package = unconfirmed_pool
+            .transaction_package(&tx2.body.kernels()[0].excess_sig)
+            .unwrap();
+        assert!(package.ancestors.is_empty());
+        assert!(unconfirmed_pool.check_status());
+    }
+
+    #[test]
+    fn test_child_pays_for_parent() {
+        let parent = tx!(MicroTari(5_000), fee: MicroTari(5), inputs: 1, outputs: 1).0;
+        let mut child = tx!(MicroTari(5_000), fee: MicroTari(200), inputs: 1, outputs: 1).0;
+        let other = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 1, outputs: 1).0;
+        let output = &parent.body.outputs()[0];
+        child.body.inputs_mut()[0] = TransactionInput::new(output.features.clone(), output.commitment.clone());
+        let parent = Arc::new(parent);
+        let child = Arc::new(child);
+        let other = Arc::new(other);
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            storage_capacity: 10,
+            weight_tx_skip_count: 3,
+            ..Default::default()
+        });
+        unconfirmed_pool
+            .insert_txs(vec![parent.clone(), child.clone(), other.clone()])
+            .unwrap();
+
+        // Only two of the three transactions fit. On its own the parent pays the lowest fee, but the package of the
+        // parent and child pays more per gram than the other transaction.
+        let desired_weight = parent.calculate_weight() + child.calculate_weight();
+        let selected_txs = unconfirmed_pool.highest_priority_txs(desired_weight).unwrap();
+        assert_eq!(selected_txs, vec![parent.clone(), child.clone()]);
+
+        // Without the child the parent is left out
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            storage_capacity: 10,
+            weight_tx_skip_count: 3,
+            ..Default::default()
+        });
+        unconfirmed_pool
+            .insert_txs(vec![parent.clone(), other.clone()])
+            .unwrap();
+        let selected_txs = unconfirmed_pool
+            .highest_priority_txs(parent.calculate_weight())
+            .unwrap();
+        assert_eq!(selected_txs, vec![other]);
+    }
+
+    #[test]
+    fn test_evict_packages() {
+        let create_txs = |child_fee: MicroTari| {
+            let parent = tx!(MicroTari(5_000), fee: MicroTari(5), inputs: 1, outputs: 1).0;
+            let mut child = tx!(MicroTari(5_000), fee: child_fee, inputs: 1, outputs: 1).0;
+            let other = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 1, outputs: 1).0;
+            let output = &parent.body.outputs()[0];
+            child.body.inputs_mut()[0] = TransactionInput::new(output.features.clone(), output.commitment.clone());
+            (Arc::new(parent), Arc::new(child), Arc::new(other))
+        };
+        let new_tx = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(60), inputs: 1, outputs: 1).0);
+        let has_tx = |pool: &UnconfirmedPool, tx: &Arc<Transaction>| {
+            pool.has_tx_with_excess_sig(&tx.body.kernels()[0].excess_sig)
+        };
+
+        // The low fee parent is evicted together with its low fee child, so the child is not left behind spending an
+        // output that no longer exists
+        let (parent, child, other) = create_txs(MicroTari(10));
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            storage_capacity: 3,
+            ..Default::default()
+        });
+        unconfirmed_pool
+            .insert_txs(vec![parent.clone(), child.clone(), other.clone()])
+            .unwrap();
+        let (response, evicted_txs) = unconfirmed_pool.insert_and_evict(new_tx.clone()).unwrap();
+        assert_eq!(response, TxStorageResponse::UnconfirmedPool);
+        assert_eq!(evicted_txs.len(), 2);
+        assert!(evicted_txs.contains(&parent));
+        assert!(evicted_txs.contains(&child));
+        assert!(!has_tx(&unconfirmed_pool, &child));
+        assert!(has_tx(&unconfirmed_pool, &other));
+        assert!(has_tx(&unconfirmed_pool, &new_tx));
+        assert_eq!(unconfirmed_pool.len(), 2);
+        assert!(unconfirmed_pool.check_status());
+
+        // A high fee child pays for its parent, so the unrelated transaction is evicted instead
+        let (parent, child, other) = create_txs(MicroTari(200));
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            storage_capacity: 3,
+            ..Default::default()
+        });
+        unconfirmed_pool
+            .insert_txs(vec![parent.clone(), child.clone(), other.clone()])
+            .unwrap();
+        let (response, evicted_txs) = unconfirmed_pool.insert_and_evict(new_tx.clone()).unwrap();
+        assert_eq!(response, TxStorageResponse::UnconfirmedPool);
+        assert_eq!(evicted_txs, vec![other]);
+        assert!(has_tx(&unconfirmed_pool, &parent));
+        assert!(has_tx(&unconfirmed_pool, &child));
+        assert!(unconfirmed_pool.check_status());
+    }
+
+    #[test]
+    fn test_remove_descendants_of_discarded_txs() {
+        let create_txs = || {
+            let parent = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 1, outputs: 1).0;
+            let mut child = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 1, outputs: 1).0;
+            let other = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 1, outputs: 1).0;
+            let output = &parent.body.outputs()[0];
+            child.body.inputs_mut()[0] = TransactionInput::new(output.features.clone(), output.commitment.clone());
+            (Arc::new(parent), Arc::new(child), Arc::new(other))
+        };
+        let has_tx = |pool: &UnconfirmedPool, tx: &Arc<Transaction>| {
+            pool.has_tx_with_excess_sig(&tx.body.kernels()[0].excess_sig)
+        };
+        let consensus = ConsensusManagerBuilder::new(Network::LocalNet).build();
+
+        // The child of a double spend is discarded with it
+        let (parent, child, other) = create_txs();
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            storage_capacity: 10,
+            ..Default::default()
+        });
+        unconfirmed_pool
+            .insert_txs(vec![parent.clone(), child.clone(), other.clone()])
+            .unwrap();
+        let mut double_spend = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 1, outputs: 1).0;
+        double_spend.body.inputs_mut()[0] = parent.body.inputs()[0].clone();
+        let published_block = create_orphan_block(0, vec![double_spend], &consensus);
+        let (_, discarded_txs) = unconfirmed_pool.remove_published_and_discard_double_spends(&published_block);
+        assert_eq!(discarded_txs.len(), 2);
+        assert!(discarded_txs.contains(&parent));
+        assert!(discarded_txs.contains(&child));
+        assert!(has_tx(&unconfirmed_pool, &other));
+        assert_eq!(unconfirmed_pool.len(), 1);
+        assert!(unconfirmed_pool.check_status());
+
+        // The child of a time locked transaction is removed with it
+        let (parent, child, other) = create_txs();
+        let mut parent = (*parent).clone();
+        parent.body.inputs_mut()[0].features.maturity = 10;
+        let parent = Arc::new(parent);
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            storage_capacity: 10,
+            ..Default::default()
+        });
+        unconfirmed_pool
+            .insert_txs(vec![parent.clone(), child.clone(), other.clone()])
+            .unwrap();
+        let removed_txs = unconfirmed_pool.remove_timelocked(5);
+        assert_eq!(removed_txs.len(), 2);
+        assert!(removed_txs.contains(&parent));
+        assert!(removed_txs.contains(&child));
+        assert!(has_tx(&unconfirmed_pool, &other));
+        assert!(unconfirmed_pool.check_status());
+    }
+
+    #[test]
+    fn test_highest_fee_per_gram_txs_selects_child_after_parent() {
+        let parent = tx!(MicroTari(5_000), fee: MicroTari(5), inputs: 1, outputs: 1).0;
+        let mut child = tx!(MicroTari(5_000), fee: MicroTari(200), inputs: 1, outputs: 1).0;
+        let other = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 1, outputs: 1).0;
+        let output = &parent.body.outputs()[0];
+        child.body.inputs_mut()[0] = TransactionInput::new(output.features.clone(), output.commitment.clone());
+        let parent = Arc::new(parent);
+        let child = Arc::new(child);
+        let other = Arc::new(other);
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            storage_capacity: 10,
+            weight_tx_skip_count: 3,
+            ..Default::default()
+        });
+        unconfirmed_pool
+            .insert_txs(vec![parent.clone(), child.clone(), other.clone()])
+            .unwrap();
+
+        // The child is ranked first but is only selected once the parent has been selected
+        let selected_txs = unconfirmed_pool.highest_fee_per_gram_txs(u64::MAX);
+        assert_eq!(selected_txs, vec![other.clone(), parent.clone(), child]);
+
+        // The child does not fit once the parent has been selected
+        let weight_limit = other.calculate_weight() + parent.calculate_weight();
+        let selected_txs = unconfirmed_pool.highest_fee_per_gram_txs(weight_limit);
+        assert_eq!(selected_txs, vec![other, parent]);
+    }
 }
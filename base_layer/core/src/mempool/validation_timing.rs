@@ -0,0 +1,120 @@
+// Copyright 2019. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::mempool::{ValidationTimingBucket, ValidationTimingResponse};
+use std::{collections::VecDeque, time::Duration};
+
+/// The upper bounds of the validation timing histogram buckets in microseconds. Durations above the last bound are
+/// counted in a final bucket with an upper bound of `u64::MAX`.
+const BUCKET_UPPER_BOUNDS_US: [u64; 8] = [100, 500, 1_000, 5_000, 10_000, 50_000, 100_000, 500_000];
+
+/// Records the duration of the most recent transaction validations in a rolling window.
+pub struct ValidationTimer {
+    durations: VecDeque<Duration>,
+    window_size: usize,
+}
+
+impl ValidationTimer {
+    pub fn new(window_size: usize) -> Self {
+        Self {
+            durations: VecDeque::with_capacity(window_size),
+            window_size,
+        }
+    }
+
+    /// Record the duration of a validation, discarding the oldest recorded duration once the window is full.
+    pub fn record(&mut self, duration: Duration) {
+        if self.window_size == 0 {
+            return;
+        }
+        if self.durations.len() >= self.window_size {
+            self.durations.pop_front();
+        }
+        self.durations.push_back(duration);
+    }
+
+    /// Returns a histogram of the validation durations in the window.
+    pub fn histogram(&self) -> ValidationTimingResponse {
+        let mut buckets = BUCKET_UPPER_BOUNDS_US
+            .iter()
+            .chain(std::iter::once(&u64::MAX))
+            .map(|upper_bound_us| ValidationTimingBucket {
+                upper_bound_us: *upper_bound_us,
+                count: 0,
+            })
+            .collect::<Vec<_>>();
+        let mut total_us = 0u64;
+        let mut max_us = 0u64;
+        for duration in &self.durations {
+            let duration_us = duration.as_micros() as u64;
+            total_us = total_us.saturating_add(duration_us);
+            max_us = max_us.max(duration_us);
+            if let Some(bucket) = buckets.iter_mut().find(|b| duration_us <= b.upper_bound_us) {
+                bucket.count += 1;
+            }
+        }
+
+        let num_validations = self.durations.len() as u64;
+        ValidationTimingResponse {
+            num_validations,
+            mean_duration_us: if num_validations == 0 {
+                0
+            } else {
+                total_us / num_validations
+            },
+            max_duration_us: max_us,
+            buckets,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ValidationTimer;
+    use std::time::Duration;
+
+    #[test]
+    fn it_builds_a_histogram_of_the_window() {
+        let mut timer = ValidationTimer::new(3);
+        let histogram = timer.histogram();
+        assert_eq!(histogram.num_validations, 0);
+        assert_eq!(histogram.mean_duration_us, 0);
+        assert!(histogram.buckets.iter().all(|b| b.count == 0));
+
+        timer.record(Duration::from_micros(50));
+        timer.record(Duration::from_micros(2_000));
+        timer.record(Duration::from_secs(2));
+        let histogram = timer.histogram();
+        assert_eq!(histogram.num_validations, 3);
+        assert_eq!(histogram.max_duration_us, 2_000_000);
+        assert_eq!(histogram.mean_duration_us, (50 + 2_000 + 2_000_000) / 3);
+        let counts = histogram.buckets.iter().map(|b| b.count).collect::<Vec<_>>();
+        assert_eq!(counts, vec![1, 0, 0, 1, 0, 0, 0, 0, 1]);
+
+        // The oldest validation drops out of the window
+        timer.record(Duration::from_micros(700));
+        let histogram = timer.histogram();
+        assert_eq!(histogram.num_validations, 3);
+        let counts = histogram.buckets.iter().map(|b| b.count).collect::<Vec<_>>();
+        assert_eq!(counts, vec![0, 0, 1, 1, 0, 0, 0, 0, 1]);
+    }
+}
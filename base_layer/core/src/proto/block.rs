@@ -22,14 +22,14 @@
 
 use super::core as proto;
 use crate::{
-    blocks::{Block, NewBlock, NewBlockHeaderTemplate, NewBlockTemplate},
+    blocks::{Block, CompactBlock, NewBlock, NewBlockHeaderTemplate, NewBlockTemplate},
     chain_storage::{BlockHeaderAccumulatedData, HistoricalBlock},
     proof_of_work::ProofOfWork,
     transactions::types::BlindingFactor,
 };
 use std::convert::{TryFrom, TryInto};
 use tari_common_types::types::BLOCK_HASH_LENGTH;
-use tari_crypto::tari_utilities::ByteArray;
+use tari_crypto::tari_utilities::{ByteArray, Hashable};
 
 //---------------------------------- Block --------------------------------------------//
 
@@ -230,7 +230,17 @@ impl TryFrom<proto::NewBlock> for NewBlock {
             ));
         }
 
-        Ok(Self { block_hash })
+        let compact_block = new_block.compact_block.map(CompactBlock::try_from).transpose()?;
+        if let Some(compact_block) = compact_block.as_ref() {
+            if compact_block.hash() != block_hash {
+                return Err("Compact block does not match the announced block hash".to_string());
+            }
+        }
+
+        Ok(Self {
+            block_hash,
+            compact_block,
+        })
     }
 }
 
@@ -238,6 +248,41 @@ impl From<NewBlock> for proto::NewBlock {
     fn from(new_block: NewBlock) -> Self {
         Self {
             block_hash: new_block.block_hash,
+            compact_block: new_block.compact_block.map(Into::into),
+        }
+    }
+}
+
+//---------------------------------- CompactBlock --------------------------------------------//
+
+impl TryFrom<proto::CompactBlock> for CompactBlock {
+    type Error = String;
+
+    fn try_from(compact_block: proto::CompactBlock) -> Result<Self, Self::Error> {
+        let header = compact_block
+            .header
+            .map(TryInto::try_into)
+            .ok_or_else(|| "Compact block header not provided".to_string())??;
+
+        let coinbase = compact_block
+            .coinbase
+            .map(TryInto::try_into)
+            .ok_or_else(|| "Compact block coinbase not provided".to_string())??;
+
+        Ok(Self {
+            header,
+            short_ids: compact_block.short_ids,
+            coinbase,
+        })
+    }
+}
+
+impl From<CompactBlock> for proto::CompactBlock {
+    fn from(compact_block: CompactBlock) -> Self {
+        Self {
+            header: Some(compact_block.header.into()),
+            short_ids: compact_block.short_ids,
+            coinbase: Some(compact_block.coinbase.into()),
         }
     }
 }
@@ -36,6 +36,7 @@ use crate::{
         DbTransaction,
         DbValue,
         HorizonData,
+        HorizonSyncProgress,
         LMDBDatabase,
         MmrTree,
         PrunedOutput,
@@ -78,12 +79,21 @@ pub fn create_new_blockchain() -> BlockchainDatabase<TempDatabase> {
         .with_block(genesis)
         .on_ties(ChainStrengthComparerBuilder::new().by_height().build())
         .build();
-    let validators = Validators::new(
+    create_store_with_mock_validators(consensus_manager)
+}
+
+/// Create validators that accept every block, header and orphan
+pub fn create_mock_validators() -> Validators<TempDatabase> {
+    Validators::new(
         MockValidator::new(true),
         MockValidator::new(true),
         MockValidator::new(true),
-    );
-    create_store_with_consensus_and_validators(consensus_manager, validators)
+    )
+}
+
+/// Create a new blockchain database with the given consensus rules that accepts every block
+pub fn create_store_with_mock_validators(rules: ConsensusManager) -> BlockchainDatabase<TempDatabase> {
+    create_store_with_consensus_and_validators(rules, create_mock_validators())
 }
 
 pub fn create_store_with_consensus_and_validators(
@@ -328,4 +338,8 @@ impl BlockchainBackend for TempDatabase {
     fn fetch_horizon_data(&self) -> Result<Option<HorizonData>, ChainStorageError> {
         self.db.fetch_horizon_data()
     }
+
+    fn fetch_horizon_sync_progress(&self) -> Result<Option<HorizonSyncProgress>, ChainStorageError> {
+        self.db.fetch_horizon_sync_progress()
+    }
 }
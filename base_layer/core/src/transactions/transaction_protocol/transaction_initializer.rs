@@ -67,6 +67,7 @@ pub struct SenderTransactionInitializer {
     inputs: Vec<TransactionInput>,
     unblinded_inputs: Vec<UnblindedOutput>,
     outputs: Vec<UnblindedOutput>,
+    output_rewind_data: Vec<Option<RewindData>>,
     change_secret: Option<BlindingFactor>,
     rewind_data: Option<RewindData>,
     offset: Option<BlindingFactor>,
@@ -97,6 +98,7 @@ impl SenderTransactionInitializer {
             inputs: Vec::new(),
             unblinded_inputs: Vec::new(),
             outputs: Vec::new(),
+            output_rewind_data: Vec::new(),
             change_secret: None,
             rewind_data: None,
             offset: None,
@@ -145,6 +147,15 @@ impl SenderTransactionInitializer {
     pub fn with_output(&mut self, output: UnblindedOutput) -> &mut Self {
         self.excess_blinding_factor = &self.excess_blinding_factor + &output.spending_key;
         self.outputs.push(output);
+        self.output_rewind_data.push(None);
+        self
+    }
+
+    /// Adds an output whose range proof can be rewound with the provided rewind keys. This can be called multiple times
+    pub fn with_rewindable_output(&mut self, output: UnblindedOutput, rewind_data: RewindData) -> &mut Self {
+        self.excess_blinding_factor = &self.excess_blinding_factor + &output.spending_key;
+        self.outputs.push(output);
+        self.output_rewind_data.push(Some(rewind_data));
         self
     }
 
@@ -285,7 +296,11 @@ impl SenderTransactionInitializer {
         let mut outputs = match self
             .outputs
             .iter()
-            .map(|o| o.as_transaction_output(factories))
+            .zip(self.output_rewind_data.iter())
+            .map(|(o, rewind_data)| match rewind_data {
+                Some(rewind_data) => o.as_rewindable_transaction_output(factories, rewind_data),
+                None => o.as_transaction_output(factories),
+            })
             .collect::<Result<Vec<TransactionOutput>, _>>()
         {
             Ok(o) => o,
@@ -404,13 +419,14 @@ mod test {
             transaction_protocol::{
                 sender::SenderState,
                 transaction_initializer::SenderTransactionInitializer,
+                RewindData,
                 TransactionProtocolError,
             },
-            types::CryptoFactories,
+            types::{CryptoFactories, PrivateKey},
         },
     };
     use rand::rngs::OsRng;
-    use tari_crypto::common::Blake256;
+    use tari_crypto::{common::Blake256, keys::SecretKey, range_proof::REWIND_USER_MESSAGE_LENGTH};
 
     /// One input, 2 outputs
     #[test]
@@ -493,6 +509,46 @@ mod test {
         }
     }
 
+    /// One rewindable output, one input
+    #[test]
+    fn rewindable_output() {
+        let factories = CryptoFactories::default();
+        let p = TestParams::new();
+        let rewind_data = RewindData {
+            rewind_key: PrivateKey::random(&mut OsRng),
+            rewind_blinding_key: PrivateKey::random(&mut OsRng),
+            proof_message: [0u8; REWIND_USER_MESSAGE_LENGTH],
+        };
+        let (utxo, input) = make_input(&mut OsRng, MicroTari(500), &factories.commitment);
+        let expected_fee = Fee::calculate(MicroTari(20), 1, 1, 1);
+        let value = MicroTari(500) - expected_fee;
+        let output = UnblindedOutput::new(value, p.spend_key.clone(), None);
+        let mut builder = SenderTransactionInitializer::new(0);
+        builder
+            .with_lock_height(0)
+            .with_offset(p.offset)
+            .with_private_nonce(p.nonce)
+            .with_rewindable_output(output, rewind_data.clone())
+            .with_input(utxo, input)
+            .with_fee_per_gram(MicroTari(20))
+            .with_prevent_fee_gt_amount(false);
+        let result = builder.build::<Blake256>(&factories).unwrap();
+        if let SenderState::Finalizing(info) = result.state {
+            assert_eq!(info.outputs.len(), 1, "There should be 1 output");
+            let rewound = info.outputs[0]
+                .full_rewind_range_proof(
+                    &factories.range_proof,
+                    &rewind_data.rewind_key,
+                    &rewind_data.rewind_blinding_key,
+                )
+                .unwrap();
+            assert_eq!(rewound.committed_value, value);
+            assert_eq!(rewound.blinding_factor, p.spend_key);
+        } else {
+            panic!("There were no recipients, so we should be finalizing");
+        }
+    }
+
     /// Hit the edge case where our change isn't enough to cover the cost of an extra output
     #[test]
     #[allow(clippy::identity_op)]
@@ -22,7 +22,7 @@
 use crate::{
     blocks::{Block, BlockValidationError},
     chain_storage,
-    chain_storage::{BlockchainBackend, ChainBlock, MmrTree},
+    chain_storage::{BlockchainBackend, ChainBlock, DbKey, DbValue, MmrTree},
     consensus::ConsensusManager,
     transactions::{
         aggregated_body::AggregateBody,
@@ -36,6 +36,7 @@ use crate::{
             check_coinbase_output,
             check_cut_through,
             is_all_unique_and_sorted,
+            is_genesis,
         },
         traits::PostOrphanBodyValidation,
         CandidateBlockBodyValidation,
@@ -44,7 +45,8 @@ use crate::{
     },
 };
 use log::*;
-use std::marker::PhantomData;
+use std::{marker::PhantomData, sync::Arc};
+use tari_common_types::types::BlockHash;
 use tari_crypto::{
     commitment::HomomorphicCommitmentFactory,
     tari_utilities::{hash::Hashable, hex::Hex},
@@ -74,7 +76,7 @@ impl OrphanValidation for OrphanBlockValidator {
     /// 1. Is there precisely one Coinbase output and is it correctly defined with the correct amount?
     /// 1. Is the accounting correct?
     fn validate(&self, block: &Block) -> Result<(), ValidationError> {
-        if block.header.height == 0 {
+        if is_genesis(block) {
             warn!(target: LOG_TARGET, "Attempt to validate genesis block");
             return Err(ValidationError::ValidatingGenesis);
         }
@@ -440,6 +442,11 @@ impl<B: BlockchainBackend> CandidateBlockBodyValidation<B> for BlockValidator<B>
     /// 1. Does the block satisfy the stateless checks?
     /// 1. Are the block header MMR roots valid?
     fn validate_body(&self, block: &Block, backend: &B) -> Result<(), ValidationError> {
+        if is_genesis(block) {
+            warn!(target: LOG_TARGET, "Attempt to validate genesis block");
+            return Err(ValidationError::ValidatingGenesis);
+        }
+
         let block_id = format!("block #{}", block.header.height);
         trace!(target: LOG_TARGET, "Validating {}", block_id);
 
@@ -468,3 +475,89 @@ impl<B: BlockchainBackend> CandidateBlockBodyValidation<B> for BlockValidator<B>
         Ok(())
     }
 }
+
+/// This validator skips the expensive body validation of blocks at or below a trusted ("assume valid") block on the
+/// main header chain, only checking that their MMR roots match their headers. Any other block is validated by the
+/// wrapped validator.
+pub struct AssumeValidBlockValidator<B> {
+    inner: Arc<dyn CandidateBlockBodyValidation<B>>,
+    assume_valid_hash: BlockHash,
+}
+
+impl<B: BlockchainBackend> AssumeValidBlockValidator<B> {
+    pub fn new(inner: Arc<dyn CandidateBlockBodyValidation<B>>, assume_valid_hash: BlockHash) -> Self {
+        Self {
+            inner,
+            assume_valid_hash,
+        }
+    }
+
+    /// Returns true if the block is the trusted block or one of its ancestors in the header chain
+    fn is_assumed_valid(&self, block: &Block, db: &B) -> Result<bool, ValidationError> {
+        let assume_valid_height = match db.fetch(&DbKey::BlockHash(self.assume_valid_hash.clone()))? {
+            Some(DbValue::BlockHash(header)) => header.height,
+            _ => return Ok(false),
+        };
+        if block.header.height > assume_valid_height {
+            return Ok(false);
+        }
+        let header_at_height = db.fetch_chain_header_by_height(block.header.height)?;
+        Ok(*header_at_height.hash() == block.hash())
+    }
+}
+
+impl<B: BlockchainBackend> CandidateBlockBodyValidation<B> for AssumeValidBlockValidator<B> {
+    fn validate_body(&self, block: &Block, backend: &B) -> Result<(), ValidationError> {
+        if !self.is_assumed_valid(block, backend)? {
+            return self.inner.validate_body(block, backend);
+        }
+
+        check_mmr_roots(block, backend)?;
+        debug!(
+            target: LOG_TARGET,
+            "Block #{} is below the assume valid block {}. Only the MMR roots were validated.",
+            block.header.height,
+            self.assume_valid_hash.to_hex()
+        );
+        Ok(())
+    }
+}
+
+/// Runs every independent block body check and returns all of the failures found, instead of stopping at the first
+/// one. This is a diagnostic aid for operators investigating a rejected block and must not be used for consensus
+/// validation, which remains fail-fast. An empty `Vec` means that none of the checks failed.
+#[cfg(feature = "diagnostics")]
+pub fn validate_block_collect_errors<B: BlockchainBackend>(
+    block: &Block,
+    db: &B,
+    rules: &ConsensusManager,
+) -> Vec<ValidationError>
+{
+    if is_genesis(block) {
+        return vec![ValidationError::ValidatingGenesis];
+    }
+
+    let factories = CryptoFactories::default();
+    let constants = rules.consensus_constants(block.header.height);
+    let results = vec![
+        check_block_weight(block, constants),
+        check_sorting_and_duplicates(&block.body),
+        block.check_stxo_rules().map_err(ValidationError::from),
+        check_cut_through(block),
+        check_coinbase_output(block, rules, &factories),
+        check_accounting_balance(block, rules, &factories),
+        check_inputs_are_utxos(block, db),
+        check_not_duplicate_txos(block, db),
+        check_mmr_roots(block, db),
+    ];
+
+    let errors = results.into_iter().filter_map(Result::err).collect::<Vec<_>>();
+    debug!(
+        target: LOG_TARGET,
+        "Diagnostic validation of block #{} ({}) found {} error(s)",
+        block.header.height,
+        block.hash().to_hex(),
+        errors.len()
+    );
+    errors
+}
@@ -26,9 +26,10 @@ use crate::{
     proof_of_work::{monero_rx::MergeMineError, PowError},
     transactions::transaction::TransactionError,
 };
+use strum_macros::IntoStaticStr;
 use thiserror::Error;
 
-#[derive(Debug, Error)]
+#[derive(Debug, Error, IntoStaticStr)]
 pub enum ValidationError {
     #[error("Block header validation failed: {0}")]
     BlockHeaderError(#[from] BlockHeaderValidationError),
@@ -67,6 +68,8 @@ pub enum ValidationError {
     ProofOfWorkError(#[from] PowError),
     #[error("Attempted to validate genesis block")]
     ValidatingGenesis,
+    #[error("Block does not match the genesis block for this network")]
+    InvalidGenesisBlock,
     #[error("Previous block hash not found")]
     PreviousHashNotFound,
     #[error("Duplicate or unsorted input found in block body")]
@@ -1,10 +1,15 @@
 use crate::{
     blocks::BlockHeader,
-    chain_storage::{fetch_headers, BlockchainBackend},
+    chain_storage::BlockchainBackend,
     consensus::ConsensusManager,
     proof_of_work::AchievedTargetDifficulty,
     validation::{
-        helpers::{check_header_timestamp_greater_than_median, check_pow_data, check_timestamp_ftl},
+        helpers::{
+            check_header_timestamp_greater_than_median,
+            check_pow_data,
+            check_timestamp_ftl,
+            fetch_median_timestamp_window,
+        },
         DifficultyCalculator,
         HeaderValidation,
         ValidationError,
@@ -35,17 +40,7 @@ impl HeaderValidator {
             return Ok(()); // Its the genesis block, so we dont have to check median
         }
 
-        let height = block_header.height - 1;
-        let min_height = height.saturating_sub(
-            self.rules
-                .consensus_constants(block_header.height)
-                .get_median_timestamp_count() as u64,
-        );
-        let timestamps = fetch_headers(db, min_height, height)?
-            .iter()
-            .map(|h| h.timestamp)
-            .collect::<Vec<_>>();
-
+        let timestamps = fetch_median_timestamp_window(db, block_header.height, &self.rules)?;
         check_header_timestamp_greater_than_median(block_header, &timestamps)?;
 
         Ok(())
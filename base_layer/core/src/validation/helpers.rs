@@ -26,7 +26,7 @@ use crate::{
         Block,
         BlockValidationError,
     },
-    chain_storage::BlockchainBackend,
+    chain_storage::{fetch_headers, BlockchainBackend},
     consensus::{ConsensusConstants, ConsensusManager},
     proof_of_work::{
         monero_difficulty,
@@ -38,7 +38,7 @@ use crate::{
         PowAlgorithm,
         PowError,
     },
-    transactions::types::CryptoFactories,
+    transactions::{aggregated_body::AggregateBody, types::CryptoFactories},
     validation::ValidationError,
 };
 use log::*;
@@ -88,6 +88,27 @@ pub fn calc_median_timestamp(timestamps: &[EpochTime]) -> EpochTime {
     median_timestamp
 }
 
+/// Returns the timestamps of the headers in the median timestamp window of a block at `height` i.e. its parent and the
+/// `median_timestamp_window` headers preceding the parent, or all preceding headers if there are fewer. The window
+/// includes the parent in addition to `median_timestamp_window` headers for compatibility with the existing chain.
+pub fn fetch_median_timestamp_window<B: BlockchainBackend>(
+    db: &B,
+    height: u64,
+    rules: &ConsensusManager,
+) -> Result<Vec<EpochTime>, ValidationError>
+{
+    if height == 0 {
+        return Ok(Vec::new());
+    }
+    let window = rules.consensus_constants(height).median_timestamp_window() as u64;
+    let start = (height - 1).saturating_sub(window);
+    let timestamps = fetch_headers(db, start, height - 1)?
+        .iter()
+        .map(|h| h.timestamp)
+        .collect();
+    Ok(timestamps)
+}
+
 pub fn check_header_timestamp_greater_than_median(
     block_header: &BlockHeader,
     timestamps: &[EpochTime],
@@ -184,10 +205,53 @@ pub fn check_target_difficulty(
     }
 }
 
+/// Returns true if `block` is a genesis block. Genesis blocks do not satisfy all of the consensus rules that other
+/// blocks must satisfy (e.g. the genesis block may exceed the maximum block weight) so they must be validated with
+/// [`validate_genesis_block`] instead of the other block checks in this module, which assume a non-genesis block.
+pub fn is_genesis(block: &Block) -> bool {
+    block.header.height == 0
+}
+
+/// Validates a genesis block. The genesis block is hardcoded for each network, so rather than applying the consensus
+/// rules for other blocks, the block must match the genesis block given by the consensus rules. The header must be
+/// identical and the body must contain the same inputs, outputs and kernels, in any order.
+pub fn validate_genesis_block(block: &Block, rules: &ConsensusManager) -> Result<(), ValidationError> {
+    if !is_genesis(block) {
+        warn!(
+            target: LOG_TARGET,
+            "Block {} at height {} is not a genesis block",
+            block.hash().to_hex(),
+            block.header.height
+        );
+        return Err(ValidationError::InvalidGenesisBlock);
+    }
+    let genesis_block = rules.get_genesis_block();
+    if block.hash() != *genesis_block.hash() || !is_same_body(&block.body, &genesis_block.block().body) {
+        warn!(
+            target: LOG_TARGET,
+            "Block {} does not match the genesis block {}",
+            block.hash().to_hex(),
+            genesis_block.hash().to_hex()
+        );
+        return Err(ValidationError::InvalidGenesisBlock);
+    }
+    Ok(())
+}
+
+/// Bodies are compared once sorted, since the order of a block body (e.g. as loaded from the database) is not
+/// significant
+fn is_same_body(body: &AggregateBody, expected: &AggregateBody) -> bool {
+    let mut body = body.clone();
+    body.sort();
+    let mut expected = expected.clone();
+    expected.sort();
+    body.inputs() == expected.inputs() && body.outputs() == expected.outputs() && body.kernels() == expected.kernels()
+}
+
+/// Checks that the block weight is within the consensus limit. This check does not apply to the genesis block.
 pub fn check_block_weight(block: &Block, consensus_constants: &ConsensusConstants) -> Result<(), ValidationError> {
-    // The genesis block has a larger weight than other blocks may have so we have to exclude it here
     let block_weight = block.body.calculate_weight();
-    if block_weight <= consensus_constants.get_max_block_transaction_weight() || block.header.height == 0 {
+    if block_weight <= consensus_constants.get_max_block_transaction_weight() {
         trace!(
             target: LOG_TARGET,
             "SV - Block contents for block #{} : {}; weight {}.",
@@ -202,16 +266,14 @@ pub fn check_block_weight(block: &Block, consensus_constants: &ConsensusConstant
     }
 }
 
+/// Checks that the block body balances against the kernel offset and coinbase. This check does not apply to the
+/// genesis block.
 pub fn check_accounting_balance(
     block: &Block,
     rules: &ConsensusManager,
     factories: &CryptoFactories,
 ) -> Result<(), ValidationError>
 {
-    if block.header.height == 0 {
-        // Gen block does not need to be checked for this.
-        return Ok(());
-    }
     let offset = &block.header.total_kernel_offset;
     let total_coinbase = rules.calculate_coinbase_and_fees(block);
     block
@@ -349,5 +411,74 @@ mod test {
                 assert_eq!(median_timestamp, 3.into());
             }
         }
+
+        mod validate_genesis_block {
+            use super::*;
+            use crate::{
+                blocks::genesis_block::get_stibbons_genesis_block,
+                consensus::{ConsensusManagerBuilder, Network},
+            };
+
+            #[test]
+            fn it_passes_the_genesis_block() {
+                let rules = ConsensusManagerBuilder::new(Network::LocalNet).build();
+                let genesis_block = rules.get_genesis_block();
+                assert!(is_genesis(genesis_block.block()));
+                validate_genesis_block(genesis_block.block(), &rules).unwrap();
+            }
+
+            #[test]
+            fn it_passes_a_genesis_block_with_a_reordered_body() {
+                let genesis_block = get_stibbons_genesis_block();
+                let rules = ConsensusManagerBuilder::new(Network::Stibbons)
+                    .with_block(genesis_block.clone())
+                    .build();
+                // The block as rebuilt from the database has a sorted body
+                let mut block = genesis_block.block().clone();
+                block.body.sort();
+                assert_ne!(&block, genesis_block.block());
+                validate_genesis_block(&block, &rules).unwrap();
+
+                let mut block = genesis_block.block().clone();
+                let mut outputs = block.body.outputs().clone();
+                outputs.reverse();
+                block.body = AggregateBody::new(block.body.inputs().clone(), outputs, block.body.kernels().clone());
+                validate_genesis_block(&block, &rules).unwrap();
+            }
+
+            #[test]
+            fn it_fails_a_genesis_block_with_a_different_body() {
+                let rules = ConsensusManagerBuilder::new(Network::LocalNet).build();
+                let mut block = rules.get_genesis_block().block().clone();
+                block.body = AggregateBody::empty();
+                assert!(matches!(
+                    validate_genesis_block(&block, &rules),
+                    Err(ValidationError::InvalidGenesisBlock)
+                ));
+            }
+
+            #[test]
+            fn it_fails_a_tampered_genesis_block() {
+                let rules = ConsensusManagerBuilder::new(Network::LocalNet).build();
+                let mut block = rules.get_genesis_block().block().clone();
+                block.header.nonce += 1;
+                assert!(matches!(
+                    validate_genesis_block(&block, &rules),
+                    Err(ValidationError::InvalidGenesisBlock)
+                ));
+            }
+
+            #[test]
+            fn it_fails_a_block_that_is_not_at_height_zero() {
+                let rules = ConsensusManagerBuilder::new(Network::LocalNet).build();
+                let mut block = rules.get_genesis_block().block().clone();
+                block.header.height = 1;
+                assert!(!is_genesis(&block));
+                assert!(matches!(
+                    validate_genesis_block(&block, &rules),
+                    Err(ValidationError::InvalidGenesisBlock)
+                ));
+            }
+        }
     }
 }
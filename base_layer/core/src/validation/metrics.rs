@@ -0,0 +1,92 @@
+//  Copyright 2021, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::validation::ValidationError;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+/// Tallies validation rejections by [`ValidationError`] variant, so that operators can see which validation rules
+/// are rejecting blocks and transactions. Clones share the same tallies.
+#[derive(Clone, Default)]
+pub struct ValidationMetrics {
+    rejections: Arc<RwLock<HashMap<&'static str, u64>>>,
+}
+
+impl ValidationMetrics {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Increments the rejection count for the variant of `err`
+    pub fn record_rejection(&self, err: &ValidationError) {
+        let variant: &'static str = err.into();
+        let mut rejections = self.rejections.write().expect("ValidationMetrics lock poisoned");
+        *rejections.entry(variant).or_insert(0) += 1;
+    }
+
+    /// Returns a snapshot of the current rejection counts
+    pub fn snapshot(&self) -> ValidationMetricsSnapshot {
+        let rejections = self.rejections.read().expect("ValidationMetrics lock poisoned");
+        ValidationMetricsSnapshot {
+            rejections: rejections.clone(),
+        }
+    }
+}
+
+/// The validation rejection counts at a point in time, keyed by [`ValidationError`] variant name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationMetricsSnapshot {
+    pub rejections: HashMap<&'static str, u64>,
+}
+
+impl ValidationMetricsSnapshot {
+    /// Returns the number of rejections for the given `ValidationError` variant name e.g. `"ContainsTxO"`
+    pub fn rejection_count(&self, variant: &str) -> u64 {
+        self.rejections.get(variant).copied().unwrap_or(0)
+    }
+
+    /// Returns the total number of rejections across all variants
+    pub fn total_rejections(&self) -> u64 {
+        self.rejections.values().sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_tallies_rejections_by_variant() {
+        let metrics = ValidationMetrics::new();
+        metrics.record_rejection(&ValidationError::ContainsTxO);
+        metrics.record_rejection(&ValidationError::ContainsTxO);
+        metrics.record_rejection(&ValidationError::custom_error("test"));
+
+        let snapshot = metrics.clone().snapshot();
+        assert_eq!(snapshot.rejection_count("ContainsTxO"), 2);
+        assert_eq!(snapshot.rejection_count("CustomError"), 1);
+        assert_eq!(snapshot.rejection_count("MaturityError"), 0);
+        assert_eq!(snapshot.total_rejections(), 3);
+    }
+}
@@ -36,14 +36,19 @@ use crate::{
         PostOrphanBodyValidation,
     },
 };
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
 };
 
 #[derive(Clone)]
 pub struct MockValidator {
     is_valid: Arc<AtomicBool>,
+    delay: Duration,
 }
 
 pub struct SharedFlag(Arc<AtomicBool>);
@@ -58,9 +63,16 @@ impl MockValidator {
     pub fn new(is_valid: bool) -> Self {
         Self {
             is_valid: Arc::new(AtomicBool::new(is_valid)),
+            delay: Duration::from_secs(0),
         }
     }
 
+    /// Makes mempool transaction validation block for the given duration before returning
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
     pub fn shared_flag(&self) -> SharedFlag {
         SharedFlag(self.is_valid.clone())
     }
@@ -127,6 +139,9 @@ impl<B: BlockchainBackend> HeaderValidation<B> for MockValidator {
 
 impl MempoolTransactionValidation for MockValidator {
     fn validate(&self, _transaction: &Transaction) -> Result<(), ValidationError> {
+        if self.delay > Duration::from_secs(0) {
+            thread::sleep(self.delay);
+        }
         if self.is_valid.load(Ordering::SeqCst) {
             Ok(())
         } else {
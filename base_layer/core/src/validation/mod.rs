@@ -55,5 +55,8 @@ pub use chain_balance::ChainBalanceValidator;
 
 mod header_iter;
 
+mod metrics;
+pub use metrics::{ValidationMetrics, ValidationMetricsSnapshot};
+
 #[cfg(test)]
 mod test;
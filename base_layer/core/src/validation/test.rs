@@ -24,7 +24,7 @@ use crate::{
     blocks::BlockHeader,
     consensus::{ConsensusManagerBuilder, Network},
     test_helpers::{blockchain::create_store_with_consensus, create_chain_header},
-    validation::header_iter::HeaderIter,
+    validation::{header_iter::HeaderIter, helpers::fetch_median_timestamp_window},
 };
 
 #[test]
@@ -75,6 +75,47 @@ fn header_iter_fetch_in_chunks() {
     })
 }
 
+#[test]
+fn median_timestamp_window_uses_the_preceding_headers() {
+    let consensus_manager = ConsensusManagerBuilder::new(Network::LocalNet).build();
+    let db = create_store_with_consensus(consensus_manager.clone());
+    let headers = (1..=15).fold(vec![db.fetch_chain_header(0).unwrap()], |mut acc, i| {
+        let prev = acc.last().unwrap();
+        let mut header = BlockHeader::new(0);
+        header.height = i;
+        header.prev_hash = prev.hash().clone();
+        header.timestamp = prev.header().timestamp.increase(60);
+        // These have to be unique
+        header.kernel_mmr_size = 2 + i;
+        header.output_mmr_size = 4001 + i;
+
+        let chain_header = create_chain_header(header, &prev.accumulated_data());
+        acc.push(chain_header);
+        acc
+    });
+    db.insert_valid_headers(headers.iter().skip(1).cloned().collect())
+        .unwrap();
+
+    let window = consensus_manager.consensus_constants(16).median_timestamp_window();
+    assert!(window + 1 < 16);
+    let timestamps = fetch_median_timestamp_window(&*db.db_read_access().unwrap(), 16, &consensus_manager).unwrap();
+    let expected = headers[16 - window - 1..]
+        .iter()
+        .map(|h| h.header().timestamp)
+        .collect::<Vec<_>>();
+    assert_eq!(timestamps, expected);
+    // Header sync validates against the same window
+    let sync_timestamps = db.fetch_block_timestamps(headers[15].hash().clone()).unwrap();
+    assert_eq!(&*sync_timestamps, expected.as_slice());
+
+    // Fewer preceding headers than the window
+    let timestamps = fetch_median_timestamp_window(&*db.db_read_access().unwrap(), 3, &consensus_manager).unwrap();
+    assert_eq!(timestamps.len(), 3);
+
+    let timestamps = fetch_median_timestamp_window(&*db.db_read_access().unwrap(), 0, &consensus_manager).unwrap();
+    assert!(timestamps.is_empty());
+}
+
 #[test]
 #[ignore]
 // TODO: Fix this test with the new DB structure
@@ -41,6 +41,18 @@ pub trait PostOrphanBodyValidation<B>: Send + Sync {
 
 pub trait MempoolTransactionValidation: Send + Sync {
     fn validate(&self, transaction: &Transaction) -> Result<(), ValidationError>;
+
+    /// Validate a transaction of which the `unconfirmed_inputs` spend outputs of unconfirmed transactions in the
+    /// mempool. These inputs do not exist in the blockchain yet and are exempt from the UTXO set checks. Validators
+    /// that do not check the UTXO set validate the full transaction as usual.
+    fn validate_with_unconfirmed_inputs(
+        &self,
+        transaction: &Transaction,
+        _unconfirmed_inputs: &[Commitment],
+    ) -> Result<(), ValidationError>
+    {
+        self.validate(transaction)
+    }
 }
 
 pub trait OrphanValidation: Send + Sync {
@@ -23,7 +23,10 @@
 use crate::{
     chain_storage::{BlockchainBackend, BlockchainDatabase, MmrTree},
     tari_utilities::hex::Hex,
-    transactions::{transaction::Transaction, types::CryptoFactories},
+    transactions::{
+        transaction::Transaction,
+        types::{Commitment, CryptoFactories},
+    },
     validation::{MempoolTransactionValidation, ValidationError},
 };
 use log::*;
@@ -83,7 +86,8 @@ impl<B: BlockchainBackend> MempoolTransactionValidation for TxConsensusValidator
 }
 
 /// This validator assumes that the transaction was already validated and it will skip this step. It will only check, in
-/// order,: All inputs exist in the backend, All timelocks (kernel lock heights and output maturities) have passed
+/// order,: All inputs exist in the backend, All timelocks (kernel lock heights and output maturities) have passed.
+/// Inputs that spend outputs of unconfirmed mempool transactions are not checked against the backend.
 #[derive(Clone)]
 pub struct TxInputAndMaturityValidator<B> {
     db: BlockchainDatabase<B>,
@@ -97,8 +101,17 @@ impl<B: BlockchainBackend> TxInputAndMaturityValidator<B> {
 
 impl<B: BlockchainBackend> MempoolTransactionValidation for TxInputAndMaturityValidator<B> {
     fn validate(&self, tx: &Transaction) -> Result<(), ValidationError> {
+        self.validate_with_unconfirmed_inputs(tx, &[])
+    }
+
+    fn validate_with_unconfirmed_inputs(
+        &self,
+        tx: &Transaction,
+        unconfirmed_inputs: &[Commitment],
+    ) -> Result<(), ValidationError>
+    {
         let db = self.db.db_read_access()?;
-        verify_not_stxos(tx, &*db)?;
+        verify_not_stxos(tx, unconfirmed_inputs, &*db)?;
 
         let tip_height = db.fetch_chain_metadata()?.height_of_longest_chain();
         verify_timelocks(tx, tip_height)?;
@@ -116,8 +129,14 @@ fn verify_timelocks(tx: &Transaction, current_height: u64) -> Result<(), Validat
     Ok(())
 }
 
-// This function checks that the inputs and outputs do not exist in the STxO set.
-fn verify_not_stxos<B: BlockchainBackend>(tx: &Transaction, db: &B) -> Result<(), ValidationError> {
+// This function checks that the inputs and outputs do not exist in the STxO set. The `unconfirmed_inputs` are not in
+// the blockchain yet and are skipped.
+fn verify_not_stxos<B: BlockchainBackend>(
+    tx: &Transaction,
+    unconfirmed_inputs: &[Commitment],
+    db: &B,
+) -> Result<(), ValidationError>
+{
     // `ChainMetadata::best_block` must always have the hash of the tip block.
     // NOTE: the backend makes no guarantee that the tip header has a corresponding full body (interrupted header sync,
     // pruned node) however the chain metadata best height MUST always correspond to the highest full block
@@ -131,7 +150,12 @@ fn verify_not_stxos<B: BlockchainBackend>(tx: &Transaction, db: &B) -> Result<()
                 metadata.best_block().to_hex()
             )
         });
-    for input in tx.body.inputs() {
+    for input in tx
+        .body
+        .inputs()
+        .iter()
+        .filter(|input| !unconfirmed_inputs.contains(&input.commitment))
+    {
         if let Some(index) = db.fetch_mmr_leaf_index(MmrTree::Utxo, &input.hash())? {
             if data.deleted().contains(index) {
                 warn!(
@@ -182,4 +206,16 @@ impl MempoolTransactionValidation for MempoolValidator {
         }
         Ok(())
     }
+
+    fn validate_with_unconfirmed_inputs(
+        &self,
+        transaction: &Transaction,
+        unconfirmed_inputs: &[Commitment],
+    ) -> Result<(), ValidationError>
+    {
+        for v in &self.validators {
+            v.validate_with_unconfirmed_inputs(transaction, unconfirmed_inputs)?;
+        }
+        Ok(())
+    }
 }
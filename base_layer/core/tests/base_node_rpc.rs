@@ -45,11 +45,11 @@
 mod helpers;
 
 use crate::helpers::{
-    block_builders::{chain_block, create_genesis_block_with_coinbase_value},
+    block_builders::{append_block, chain_block, create_genesis_block_with_coinbase_value},
     nodes::{BaseNodeBuilder, NodeInterfaces},
 };
 use std::convert::TryFrom;
-use tari_comms::protocol::rpc::mock::RpcRequestMock;
+use tari_comms::protocol::rpc::{mock::RpcRequestMock, RpcStatusCode};
 use tari_core::{
     base_node::{
         comms_interface::Broadcast,
@@ -62,15 +62,26 @@ use tari_core::{
         },
         rpc::{BaseNodeWalletRpcService, BaseNodeWalletService},
         state_machine_service::states::{ListeningInfo, StateInfo, StatusInfo},
+        sync::rpc::BaseNodeSyncRpcService,
+        BaseNodeSyncService,
     },
-    chain_storage::ChainBlock,
-    consensus::{ConsensusManager, ConsensusManagerBuilder, Network},
+    blocks::{genesis_block, Block},
+    chain_storage::{BlockchainDatabaseConfig, ChainBlock},
+    consensus::{ConsensusConstantsBuilder, ConsensusManager, ConsensusManagerBuilder, Network},
     crypto::tari_utilities::Hashable,
     proto::{
-        base_node::{FetchMatchingUtxos, Signatures as SignaturesProto},
+        base_node::{FetchMatchingUtxos, GetBlocksByHashRequest, Signatures as SignaturesProto, SyncBlocksRequest},
         types::{Signature as SignatureProto, Transaction as TransactionProto},
     },
-    test_helpers::blockchain::TempDatabase,
+    test_helpers::{
+        blockchain::{
+            create_mock_validators,
+            create_store_with_consensus_and_validators_and_config,
+            create_store_with_mock_validators,
+            TempDatabase,
+        },
+        create_peer_manager,
+    },
     transactions::{
         helpers::schema_to_transaction,
         tari_amount::{uT, T},
@@ -314,3 +325,165 @@ fn test_base_node_wallet_rpc() {
             .any(|u| u.as_transaction_output(&factories).unwrap().commitment == output.commitment));
     }
 }
+
+#[test]
+fn test_base_node_wallet_rpc_get_block_reward() {
+    let network = Network::LocalNet;
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+    let temp_dir = tempdir().unwrap();
+    // Each block reward is half of the previous reward until it reaches the tail emission of 100 uT at height 7
+    let consensus_constants = ConsensusConstantsBuilder::new(network)
+        .with_emission_amounts(10_000 * uT, &[1], 100 * uT)
+        .build();
+    let (block0, _) = create_genesis_block_with_coinbase_value(&factories, 10_000 * uT, &consensus_constants);
+    let consensus_manager = ConsensusManagerBuilder::new(network)
+        .with_consensus_constants(consensus_constants)
+        .with_block(block0)
+        .build();
+    let (base_node, _consensus_manager) = BaseNodeBuilder::new(network)
+        .with_consensus_manager(consensus_manager)
+        .start(&mut runtime, temp_dir.path().to_str().unwrap());
+    let request_mock = runtime.enter(|| RpcRequestMock::new(base_node.comms.peer_manager()));
+    let service = BaseNodeWalletRpcService::new(
+        base_node.blockchain_db.clone().into(),
+        base_node.mempool_handle.clone(),
+        base_node.state_machine_handle.clone(),
+    );
+
+    let expected: [(u64, u64, bool); 5] = [
+        (0, 10_000, false),
+        (1, 5_000, false),
+        (6, 157, false),
+        (7, 100, true),
+        (1_000, 100, true),
+    ];
+    for (height, block_reward, is_tail_emission) in expected.iter() {
+        let req = request_mock.request_with_context(Default::default(), *height);
+        let resp = runtime.block_on(service.get_block_reward(req)).unwrap().into_message();
+        assert_eq!(resp.height, *height);
+        assert_eq!(resp.block_reward, *block_reward);
+        assert_eq!(resp.tail_emission, 100);
+        assert_eq!(resp.is_tail_emission, *is_tail_emission);
+    }
+}
+
+#[tokio_macros::test_basic]
+async fn test_sync_rpc_get_blocks_by_hash() {
+    let network = Network::LocalNet;
+    let consensus_constants = network.create_consensus_constants();
+    let block0 = genesis_block::get_stibbons_genesis_block();
+    let consensus_manager = ConsensusManagerBuilder::new(network)
+        .with_consensus_constants(consensus_constants[0].clone())
+        .with_block(block0.clone())
+        .build();
+    let create_store = || create_store_with_mock_validators(consensus_manager.clone());
+    let store = create_store();
+    let fork_store = create_store();
+
+    // Main chain: G -> A1 -> A2, orphan: G -> B1
+    let block_a1 = append_block(&store, &block0, vec![], &consensus_manager, 1.into()).unwrap();
+    let block_a2 = append_block(&store, &block_a1, vec![], &consensus_manager, 1.into()).unwrap();
+    let block_b1 = append_block(&fork_store, &block0, vec![], &consensus_manager, 1.into()).unwrap();
+    store.add_block(block_b1.to_arc_block()).unwrap().assert_orphaned();
+
+    let temp_dir = tempdir().unwrap();
+    let request_mock = RpcRequestMock::new(create_peer_manager(&temp_dir));
+    let service = BaseNodeSyncRpcService::new(store.into());
+
+    let unknown_hash1 = vec![1u8; 32];
+    let unknown_hash2 = vec![2u8; 32];
+    let msg = GetBlocksByHashRequest {
+        hashes: vec![
+            block_a2.hash().clone(),
+            unknown_hash1.clone(),
+            block_b1.hash().clone(),
+            block0.hash().clone(),
+            unknown_hash2.clone(),
+        ],
+    };
+    let resp = service
+        .get_blocks_by_hash(request_mock.request_with_context(Default::default(), msg))
+        .await
+        .unwrap()
+        .into_message();
+
+    let found = resp
+        .blocks
+        .into_iter()
+        .map(|b| Block::try_from(b).unwrap().hash())
+        .collect::<Vec<_>>();
+    assert_eq!(found, vec![
+        block_a2.hash().clone(),
+        block_b1.hash().clone(),
+        block0.hash().clone()
+    ]);
+    assert_eq!(resp.missing_hashes, vec![unknown_hash1, unknown_hash2]);
+    assert!(resp.pruned_hashes.is_empty());
+
+    // Batches over the limit are rejected
+    let msg = GetBlocksByHashRequest {
+        hashes: vec![block0.hash().clone(); 101],
+    };
+    let err = service
+        .get_blocks_by_hash(request_mock.request_with_context(Default::default(), msg))
+        .await
+        .unwrap_err();
+    assert_eq!(err.status_code(), RpcStatusCode::BadRequest);
+}
+
+#[tokio_macros::test_basic]
+async fn test_sync_rpc_sync_blocks_pruned_away() {
+    let network = Network::LocalNet;
+    let consensus_constants = network.create_consensus_constants();
+    let block0 = genesis_block::get_stibbons_genesis_block();
+    let consensus_manager = ConsensusManagerBuilder::new(network)
+        .with_consensus_constants(consensus_constants[0].clone())
+        .with_block(block0.clone())
+        .build();
+    let config = BlockchainDatabaseConfig {
+        pruning_horizon: 3,
+        pruning_interval: 1,
+        ..Default::default()
+    };
+    let store = create_store_with_consensus_and_validators_and_config(
+        consensus_manager.clone(),
+        create_mock_validators(),
+        config,
+    );
+
+    let mut blocks = vec![block0];
+    for _ in 0..8 {
+        let block = append_block(&store, blocks.last().unwrap(), vec![], &consensus_manager, 1.into()).unwrap();
+        blocks.push(block);
+    }
+    let metadata = store.get_chain_metadata().unwrap();
+    assert!(metadata.pruned_height() > 2);
+
+    let temp_dir = tempdir().unwrap();
+    let request_mock = RpcRequestMock::new(create_peer_manager(&temp_dir));
+    let service = BaseNodeSyncRpcService::new(store.into());
+    let tip_hash = blocks.last().unwrap().hash().clone();
+
+    // Block bodies below the pruned height existed but are no longer available
+    let msg = SyncBlocksRequest {
+        start_hash: blocks[0].hash().clone(),
+        end_hash: tip_hash.clone(),
+    };
+    let err = service
+        .sync_blocks(request_mock.request_with_context(Default::default(), msg))
+        .await
+        .unwrap_err();
+    assert_eq!(err.status_code(), RpcStatusCode::PrunedAway);
+
+    // Blocks that were never known are not found
+    let msg = SyncBlocksRequest {
+        start_hash: vec![1u8; 32],
+        end_hash: tip_hash,
+    };
+    let err = service
+        .sync_blocks(request_mock.request_with_context(Default::default(), msg))
+        .await
+        .unwrap_err();
+    assert_eq!(err.status_code(), RpcStatusCode::NotFound);
+}
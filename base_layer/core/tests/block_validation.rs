@@ -20,11 +20,14 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::helpers::block_builders::chain_block_with_new_coinbase;
+use crate::helpers::{
+    block_builders::{chain_block_with_new_coinbase, generate_new_block},
+    sample_blockchains::create_new_blockchain,
+};
 use monero::{blockdata::block::Block as MoneroBlock, consensus::deserialize};
 use std::sync::Arc;
 use tari_core::{
-    blocks::{Block, BlockHeaderValidationError},
+    blocks::{Block, BlockHeaderValidationError, BlockValidationError},
     chain_storage::{BlockchainDatabase, BlockchainDatabaseConfig, ChainStorageError, Validators},
     consensus::{
         consensus_constants::PowAlgorithmConstants,
@@ -34,15 +37,18 @@ use tari_core::{
     },
     proof_of_work::{monero_rx, monero_rx::MoneroData, PowAlgorithm},
     test_helpers::blockchain::{create_store_with_consensus_and_validators, create_test_db},
-    transactions::types::CryptoFactories,
+    transactions::{tari_amount::T, types::CryptoFactories},
+    txn_schema,
     validation::{
-        block_validators::{BodyOnlyValidator, OrphanBlockValidator},
+        block_validators::{AssumeValidBlockValidator, BodyOnlyValidator, OrphanBlockValidator},
         header_validator::HeaderValidator,
         mocks::MockValidator,
+        CandidateBlockBodyValidation,
         DifficultyCalculator,
         ValidationError,
     },
 };
+use tari_test_utils::unpack_enum;
 
 mod helpers;
 
@@ -143,6 +149,67 @@ fn test_monero_blocks() {
     db.add_block(Arc::new(block_3)).unwrap().assert_added();
 }
 
+#[test]
+fn test_assume_valid_block_validator() {
+    let network = Network::LocalNet;
+    let (mut db, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    let schema = vec![txn_schema!(from: vec![outputs[0][0].clone()], to: vec![6 * T, 3 * T])];
+    generate_new_block(&mut db, &mut blocks, &mut outputs, schema, &consensus_manager).unwrap();
+    let schema = vec![txn_schema!(from: vec![outputs[1][0].clone()], to: vec![3 * T, 1 * T])];
+    generate_new_block(&mut db, &mut blocks, &mut outputs, schema, &consensus_manager).unwrap();
+    let schema = vec![txn_schema!(from: vec![outputs[2][0].clone()], to: vec![2 * T])];
+    generate_new_block(&mut db, &mut blocks, &mut outputs, schema, &consensus_manager).unwrap();
+
+    // The wrapped validator stands in for the expensive validation and always fails, so only blocks that skip it pass
+    let validator = AssumeValidBlockValidator::new(Arc::new(MockValidator::new(false)), blocks[2].hash().clone());
+    let backend = db.db_read_access().unwrap();
+    validator.validate_body(blocks[1].block(), &*backend).unwrap();
+    validator.validate_body(blocks[2].block(), &*backend).unwrap();
+    assert!(validator.validate_body(blocks[3].block(), &*backend).is_err());
+
+    // MMR roots are still checked below the assume valid block
+    let block = Block::new(blocks[1].header().clone(), blocks[0].block().body.clone());
+    unpack_enum!(
+        ValidationError::BlockError(BlockValidationError::MismatchedMmrRoots) =
+            validator.validate_body(&block, &*backend).unwrap_err()
+    );
+
+    // An unknown assume valid hash results in full validation
+    let validator = AssumeValidBlockValidator::new(Arc::new(MockValidator::new(false)), vec![0u8; 32]);
+    assert!(validator.validate_body(blocks[1].block(), &*backend).is_err());
+}
+
+#[cfg(feature = "diagnostics")]
+#[test]
+fn test_validate_block_collect_errors() {
+    use tari_core::{transactions::helpers::spend_utxos, validation::block_validators::validate_block_collect_errors};
+
+    let factories = CryptoFactories::default();
+    let network = Network::LocalNet;
+    let (db, blocks, outputs, consensus_manager) = create_new_blockchain(network);
+    let (txn, _, _) = spend_utxos(txn_schema!(from: vec![outputs[0][0].clone()], to: vec![6 * T, 3 * T]));
+    let (template, _) = chain_block_with_new_coinbase(&blocks[0], vec![txn], &consensus_manager, &factories);
+    let mut block = db.prepare_block_merkle_roots(template).unwrap();
+    let backend = db.db_read_access().unwrap();
+    assert!(validate_block_collect_errors(&block, &*backend, &consensus_manager).is_empty());
+
+    // Unsorting the outputs invalidates the output MMR root and tampering with the offset breaks the accounting
+    block.body.outputs_mut().reverse();
+    block.header.total_kernel_offset = Default::default();
+    let errors = validate_block_collect_errors(&block, &*backend, &consensus_manager);
+    assert_eq!(errors.len(), 3);
+    assert!(errors
+        .iter()
+        .any(|err| matches!(err, ValidationError::UnsortedOrDuplicateOutput)));
+    assert!(errors
+        .iter()
+        .any(|err| matches!(err, ValidationError::TransactionError(_))));
+    assert!(errors.iter().any(|err| matches!(
+        err,
+        ValidationError::BlockError(BlockValidationError::MismatchedMmrRoots)
+    )));
+}
+
 fn add_monero_data(tblock: &mut Block, seed_hash: String) {
     let blocktemplate_blob = "0c0c8cd6a0fa057fe21d764e7abf004e975396a2160773b93712bf6118c3b4959ddd8ee0f76aad0000000002e1ea2701ffa5ea2701d5a299e2abb002028eb3066ced1b2cc82ea046f3716a48e9ae37144057d5fb48a97f941225a1957b2b0106225b7ec0a6544d8da39abe68d8bd82619b4a7c5bdae89c3783b256a8fa47820208f63aa86d2e857f070000".to_string();
     let bytes = hex::decode(blocktemplate_blob).unwrap();
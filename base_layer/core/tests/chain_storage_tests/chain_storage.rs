@@ -38,6 +38,7 @@ use crate::helpers::{
     test_blockchain::TestBlockchain,
 };
 use rand::{rngs::OsRng, RngCore};
+use std::sync::Arc;
 use tari_common_types::types::BlockHash;
 use tari_core::{
     blocks::{genesis_block, Block, BlockHeader},
@@ -48,6 +49,7 @@ use tari_core::{
         BlockchainDatabase,
         BlockchainDatabaseConfig,
         ChainStorageError,
+        DbKey,
         DbTransaction,
         Validators,
     },
@@ -1084,6 +1086,73 @@ fn store_and_retrieve_blocks_from_contents() {
     );
 }
 
+#[test]
+fn it_rejects_a_database_with_a_different_genesis_block() {
+    let path = create_temporary_data_path();
+    let validators = Validators::new(
+        MockValidator::new(true),
+        MockValidator::new(true),
+        MockValidator::new(true),
+    );
+    let network = Network::Stibbons;
+    let rules = ConsensusManagerBuilder::new(network)
+        .with_block(genesis_block::get_stibbons_genesis_block())
+        .build();
+    {
+        let db = create_lmdb_database(&path, LMDBConfig::default()).unwrap();
+        BlockchainDatabase::new(
+            db,
+            rules.clone(),
+            validators.clone(),
+            BlockchainDatabaseConfig::default(),
+            DifficultyCalculator::new(rules.clone(), Default::default()),
+            false,
+        )
+        .unwrap();
+    }
+    // Reopening the database with the same genesis block succeeds, even though the block body loaded from the
+    // database is ordered differently to the hardcoded genesis block
+    {
+        let db = create_lmdb_database(&path, LMDBConfig::default()).unwrap();
+        BlockchainDatabase::new(
+            db,
+            rules.clone(),
+            validators.clone(),
+            BlockchainDatabaseConfig::default(),
+            DifficultyCalculator::new(rules, Default::default()),
+            false,
+        )
+        .unwrap();
+    }
+    // Reopening the database with the genesis block of another network fails
+    {
+        let rules = ConsensusManagerBuilder::new(network)
+            .with_block(genesis_block::get_ridcully_genesis_block())
+            .build();
+        let db = create_lmdb_database(&path, LMDBConfig::default()).unwrap();
+        let result = BlockchainDatabase::new(
+            db,
+            rules.clone(),
+            validators,
+            BlockchainDatabaseConfig::default(),
+            DifficultyCalculator::new(rules, Default::default()),
+            false,
+        );
+        assert!(matches!(
+            result,
+            Err(ChainStorageError::ValidationError {
+                source: ValidationError::InvalidGenesisBlock
+            })
+        ));
+    }
+
+    if std::path::Path::new(&path).exists() {
+        if let Err(e) = std::fs::remove_dir_all(&path) {
+            println!("\n{:?}\n", e);
+        }
+    }
+}
+
 #[test]
 #[ignore = "To be completed with pruned mode"]
 fn restore_metadata_and_pruning_horizon_update() {
@@ -1652,6 +1721,199 @@ fn orphan_cleanup_delete_all_orphans() {
     }
 }
 
+#[test]
+fn lmdb_compaction_reduces_file_size() {
+    let path = create_temporary_data_path();
+    let network = Network::LocalNet;
+    let consensus_manager = ConsensusManagerBuilder::new(network).build();
+    let validators = Validators::new(
+        MockValidator::new(true),
+        MockValidator::new(true),
+        MockValidator::new(true),
+    );
+    let db = create_lmdb_database(&path, LMDBConfig::default()).unwrap();
+    let store = BlockchainDatabase::new(
+        db,
+        consensus_manager.clone(),
+        validators,
+        BlockchainDatabaseConfig::default(),
+        DifficultyCalculator::new(consensus_manager.clone(), Default::default()),
+        false,
+    )
+    .unwrap();
+
+    let orphans = (1..=500)
+        .map(|height| Arc::new(create_orphan_block(height, vec![], &consensus_manager)))
+        .collect::<Vec<_>>();
+    let mut txn = DbTransaction::new();
+    for orphan in &orphans {
+        txn.insert_orphan(orphan.clone());
+    }
+    store.write(txn).unwrap();
+
+    // Delete all but a few orphans, leaving free pages behind
+    let (kept, deleted) = orphans.split_at(5);
+    let mut txn = DbTransaction::new();
+    for orphan in deleted {
+        txn.delete_orphan(orphan.hash());
+    }
+    store.write(txn).unwrap();
+
+    let data_file = std::path::Path::new(&path).join("data.mdb");
+    let size_before = std::fs::metadata(&data_file).unwrap().len();
+    let metadata_before = store.get_chain_metadata().unwrap();
+    assert!(store.compact().unwrap());
+    let size_after = std::fs::metadata(&data_file).unwrap().len();
+    assert!(
+        size_after < size_before,
+        "Expected compaction to reduce the file size ({} >= {})",
+        size_after,
+        size_before
+    );
+
+    // No data was lost and the database is still writable
+    assert_eq!(store.get_chain_metadata().unwrap(), metadata_before);
+    for orphan in kept {
+        assert_eq!(store.fetch_orphan(orphan.hash()).unwrap(), **orphan);
+    }
+    for orphan in deleted.iter().take(5) {
+        assert!(store.fetch_orphan(orphan.hash()).is_err());
+    }
+    let mut txn = DbTransaction::new();
+    txn.delete_orphan(kept[0].hash());
+    store.write(txn).unwrap();
+    assert!(store.fetch_orphan(kept[0].hash()).is_err());
+
+    if std::path::Path::new(&path).exists() {
+        if let Err(e) = std::fs::remove_dir_all(&path) {
+            println!("\n{:?}\n", e)
+        }
+    }
+}
+
+#[test]
+fn lmdb_interrupted_compaction_is_recovered() {
+    let path = create_temporary_data_path();
+    let consensus_manager = ConsensusManagerBuilder::new(Network::LocalNet).build();
+    let orphan = Arc::new(create_orphan_block(1, vec![], &consensus_manager));
+    {
+        let mut db = create_lmdb_database(&path, LMDBConfig::default()).unwrap();
+        let mut txn = DbTransaction::new();
+        txn.insert_orphan(orphan.clone());
+        db.write(txn).unwrap();
+    }
+    let path = std::path::Path::new(&path);
+    let open_and_check_orphan = || {
+        let db = create_lmdb_database(path, LMDBConfig::default()).unwrap();
+        assert!(!path.join("compaction").exists());
+        assert!(!path.join("retired").exists());
+        db.contains(&DbKey::OrphanBlock(orphan.hash())).unwrap()
+    };
+
+    // Interrupted after the database file was retired, but before the copy was complete: roll back
+    std::fs::create_dir_all(path.join("compaction")).unwrap();
+    std::fs::create_dir_all(path.join("retired")).unwrap();
+    std::fs::write(path.join("compaction").join("data.mdb"), b"incomplete copy").unwrap();
+    std::fs::rename(path.join("data.mdb"), path.join("retired").join("data.mdb")).unwrap();
+    assert!(open_and_check_orphan());
+
+    // Interrupted after the database file was retired, with a complete copy: swap in the copy
+    std::fs::create_dir_all(path.join("compaction")).unwrap();
+    std::fs::create_dir_all(path.join("retired")).unwrap();
+    std::fs::copy(path.join("data.mdb"), path.join("compaction").join("data.mdb")).unwrap();
+    std::fs::write(path.join("compaction").join("complete"), b"").unwrap();
+    std::fs::write(path.join("data.mdb"), b"").unwrap();
+    std::fs::rename(path.join("data.mdb"), path.join("retired").join("data.mdb")).unwrap();
+    assert!(open_and_check_orphan());
+
+    if path.exists() {
+        if let Err(e) = std::fs::remove_dir_all(&path) {
+            println!("\n{:?}\n", e)
+        }
+    }
+}
+
+#[test]
+fn lmdb_compaction_keeps_the_original_database_if_the_environment_is_shared() {
+    let path = create_temporary_data_path();
+    let consensus_manager = ConsensusManagerBuilder::new(Network::LocalNet).build();
+    let orphan = Arc::new(create_orphan_block(1, vec![], &consensus_manager));
+    let mut db = create_lmdb_database(&path, LMDBConfig::default()).unwrap();
+    let mut txn = DbTransaction::new();
+    txn.insert_orphan(orphan.clone());
+    db.write(txn).unwrap();
+
+    // An unused compaction handle keeps a reference to the environment, so it cannot be closed
+    let handle = db.compaction_handle().unwrap();
+    let copy = db.compaction_handle().unwrap().create_compacted_copy().unwrap();
+    assert!(db.replace_with_compacted_copy(copy).is_err());
+
+    let path = std::path::Path::new(&path);
+    assert_eq!(db.path().unwrap(), path);
+    assert!(db.contains(&DbKey::OrphanBlock(orphan.hash())).unwrap());
+    assert!(!path.join("compaction").exists());
+    assert!(!path.join("swap").exists());
+
+    drop(db);
+    drop(handle);
+    if path.exists() {
+        if let Err(e) = std::fs::remove_dir_all(&path) {
+            println!("\n{:?}\n", e)
+        }
+    }
+}
+
+#[test]
+fn snapshot_export_and_import() {
+    let network = Network::LocalNet;
+    let (mut db, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+
+    // Block 1
+    let schema = vec![txn_schema!(from: vec![outputs[0][0].clone()], to: vec![6 * T, 3 * T])];
+    generate_new_block(&mut db, &mut blocks, &mut outputs, schema, &consensus_manager).unwrap();
+    // Block 2
+    let schema = vec![txn_schema!(from: vec![outputs[1][0].clone()], to: vec![3 * T, 1 * T])];
+    generate_new_block(&mut db, &mut blocks, &mut outputs, schema, &consensus_manager).unwrap();
+    // Block 3
+    let schema = vec![
+        txn_schema!(from: vec![outputs[2][0].clone()], to: vec![2 * T, 500_000 * uT]),
+        txn_schema!(from: vec![outputs[1][1].clone()], to: vec![500_000 * uT]),
+    ];
+    generate_new_block(&mut db, &mut blocks, &mut outputs, schema, &consensus_manager).unwrap();
+
+    let path = create_temporary_data_path().join("snapshot.bin");
+    db.export_snapshot(2, &path).unwrap();
+    unpack_enum!(ChainStorageError::InvalidArguments { .. } = db.export_snapshot(4, &path).unwrap_err());
+
+    let factories = CryptoFactories::default();
+    let fresh_db = create_store_with_consensus(consensus_manager.clone());
+    let tip = fresh_db
+        .import_snapshot(&path, &MockValidator::new(true), &factories.range_proof)
+        .unwrap();
+    assert_eq!(tip.height(), 2);
+    assert_eq!(tip.hash(), blocks[2].hash());
+    let metadata = fresh_db.get_chain_metadata().unwrap();
+    assert_eq!(metadata.height_of_longest_chain(), 2);
+    assert_eq!(metadata.best_block(), blocks[2].hash());
+    assert_eq!(metadata.pruned_height(), 2);
+    assert_eq!(fresh_db.fetch_chain_header(1).unwrap().hash(), blocks[1].hash());
+
+    // The imported MMR state must produce the same roots as the original chain for the next block
+    let roots = fresh_db.calculate_mmr_roots(blocks[3].block()).unwrap();
+    assert_eq!(roots.kernel_mr, blocks[3].header().kernel_mr);
+    assert_eq!(roots.output_mr, blocks[3].header().output_mr);
+    assert_eq!(roots.range_proof_mr, blocks[3].header().range_proof_mr);
+    unpack_enum!(BlockAddResult::Ok(_) = fresh_db.add_block(blocks[3].to_arc_block()).unwrap());
+    assert_eq!(fresh_db.get_height().unwrap(), 3);
+
+    // Snapshots can only be imported into an empty database
+    unpack_enum!(
+        ChainStorageError::InvalidOperation(_) = fresh_db
+            .import_snapshot(&path, &MockValidator::new(true), &factories.range_proof)
+            .unwrap_err()
+    );
+}
+
 #[test]
 fn fails_validation() {
     let network = Network::LocalNet;
@@ -28,6 +28,7 @@ use helpers::{
         chain_block,
         create_genesis_block,
         create_genesis_block_with_coinbase_value,
+        create_genesis_block_with_utxos,
         find_header_with_achieved_difficulty,
         generate_block,
         generate_new_block,
@@ -47,7 +48,18 @@ use tari_core::{
     },
     chain_storage::BlockchainDatabaseConfig,
     consensus::{ConsensusConstantsBuilder, ConsensusManagerBuilder, Network},
-    mempool::{Mempool, MempoolConfig, MempoolServiceConfig, MempoolServiceError, TxStorageResponse},
+    mempool::{
+        DoubleSpendAttempt,
+        Mempool,
+        MempoolConfig,
+        MempoolServiceConfig,
+        MempoolServiceError,
+        MempoolStateEvent,
+        PolicyViolation,
+        TransactionPolicy,
+        TxRemovalReason,
+        TxStorageResponse,
+    },
     proof_of_work::Difficulty,
     proto,
     transactions::{
@@ -60,7 +72,15 @@ use tari_core::{
     },
     tx,
     txn_schema,
-    validation::transaction_validators::{TxConsensusValidator, TxInputAndMaturityValidator},
+    validation::{
+        mocks::MockValidator,
+        transaction_validators::{
+            MempoolValidator,
+            TxConsensusValidator,
+            TxInputAndMaturityValidator,
+            TxInternalConsistencyValidator,
+        },
+    },
 };
 use tari_p2p::{services::liveness::LivenessConfig, tari_message::TariMessageType};
 use tari_test_utils::async_assert_eventually;
@@ -200,6 +220,91 @@ fn test_insert_and_process_published_block() {
     assert_eq!(stats.total_weight, 30);
 }
 
+#[test]
+#[allow(clippy::identity_op)]
+fn test_insert_child_of_unconfirmed_parent() {
+    let network = Network::LocalNet;
+    let (mut store, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    let mempool_validator = TxInputAndMaturityValidator::new(store.clone());
+    let mempool = Mempool::new(MempoolConfig::default(), Arc::new(mempool_validator));
+    let txs = vec![txn_schema!(from: vec![outputs[0][0].clone()], to: vec![2 * T, 2 * T])];
+    generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager).unwrap();
+    mempool.process_published_block(blocks[1].to_arc_block()).unwrap();
+
+    let parent = txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1 * T], fee: 20 * uT);
+    let (parent, parent_outputs, _) = spend_utxos(parent);
+    let parent = Arc::new(parent);
+    let child = txn_schema!(from: vec![parent_outputs[0].clone()], to: vec![500_000 * uT], fee: 100 * uT);
+    let child = Arc::new(spend_utxos(child).0);
+
+    // The parent output is unknown until the parent is in the mempool
+    assert_eq!(
+        mempool.insert(child.clone()).unwrap(),
+        TxStorageResponse::NotStoredOrphan
+    );
+    assert_eq!(
+        mempool.insert(parent.clone()).unwrap(),
+        TxStorageResponse::UnconfirmedPool
+    );
+    assert_eq!(
+        mempool.insert(child.clone()).unwrap(),
+        TxStorageResponse::UnconfirmedPool
+    );
+
+    let package = mempool
+        .transaction_package(child.first_kernel_excess_sig().unwrap().clone())
+        .unwrap()
+        .unwrap();
+    assert_eq!(package.ancestors, vec![parent.deref().clone()]);
+    assert_eq!(mempool.stats().unwrap().unconfirmed_txs, 2);
+}
+
+#[test]
+#[allow(clippy::identity_op)]
+fn test_insert_child_of_unconfirmed_parent_with_mempool_validator() {
+    let network = Network::LocalNet;
+    let (mut store, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    let mempool_validator = MempoolValidator::new(vec![
+        Box::new(TxInternalConsistencyValidator::new(CryptoFactories::default())),
+        Box::new(TxInputAndMaturityValidator::new(store.clone())),
+        Box::new(TxConsensusValidator::new(store.clone())),
+    ]);
+    let mempool = Mempool::new(MempoolConfig::default(), Arc::new(mempool_validator));
+    let txs = vec![txn_schema!(from: vec![outputs[0][0].clone()], to: vec![2 * T, 2 * T])];
+    generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager).unwrap();
+    mempool.process_published_block(blocks[1].to_arc_block()).unwrap();
+
+    let parent = txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1 * T], fee: 20 * uT);
+    let (parent, parent_outputs, _) = spend_utxos(parent);
+    let parent = Arc::new(parent);
+    let child = txn_schema!(from: vec![parent_outputs[0].clone()], to: vec![500_000 * uT], fee: 100 * uT);
+    let child = Arc::new(spend_utxos(child).0);
+
+    assert_eq!(
+        mempool.insert(child.clone()).unwrap(),
+        TxStorageResponse::NotStoredOrphan
+    );
+    assert_eq!(
+        mempool.insert(parent.clone()).unwrap(),
+        TxStorageResponse::UnconfirmedPool
+    );
+    // The child is validated in full, including the kernel sum over the input that spends the parent output
+    assert_eq!(
+        mempool.insert(child.clone()).unwrap(),
+        TxStorageResponse::UnconfirmedPool
+    );
+    assert_eq!(mempool.stats().unwrap().unconfirmed_txs, 2);
+
+    // A child of which the kernel sum does not balance is still rejected
+    let invalid_child = txn_schema!(from: vec![parent_outputs[0].clone()], to: vec![400_000 * uT], fee: 100 * uT);
+    let mut invalid_child = spend_utxos(invalid_child).0;
+    invalid_child.offset = Default::default();
+    assert_eq!(
+        mempool.insert(Arc::new(invalid_child)).unwrap(),
+        TxStorageResponse::NotStored
+    );
+}
+
 #[test]
 #[allow(clippy::identity_op)]
 fn test_time_locked() {
@@ -244,6 +349,214 @@ fn test_time_locked() {
     assert_eq!(mempool.insert(tx2).unwrap(), TxStorageResponse::UnconfirmedPool);
 }
 
+struct MaxWeightPolicy(u64);
+
+impl TransactionPolicy for MaxWeightPolicy {
+    fn check(&self, tx: &Transaction) -> Result<(), PolicyViolation> {
+        let weight = tx.calculate_weight();
+        if weight > self.0 {
+            return Err(PolicyViolation(format!(
+                "Transaction weight {} exceeds the limit of {}",
+                weight, self.0
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+#[allow(clippy::identity_op)]
+fn test_transaction_policy() {
+    let network = Network::LocalNet;
+    let (mut store, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    let mempool_validator = TxInputAndMaturityValidator::new(store.clone());
+    let mempool = Mempool::new(MempoolConfig::default(), Arc::new(mempool_validator));
+    // Create a block with 4 outputs
+    let txs = vec![txn_schema!(
+        from: vec![outputs[0][0].clone()],
+        to: vec![2 * T, 2 * T, 2 * T, 2 * T]
+    )];
+    generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager).unwrap();
+    mempool.process_published_block(blocks[1].to_arc_block()).unwrap();
+
+    let small_tx = txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1*T], fee: 20*uT);
+    let small_tx = Arc::new(spend_utxos(small_tx).0);
+    let large_tx = txn_schema!(
+        from: vec![outputs[1][1].clone()],
+        to: vec![200*uT, 200*uT, 200*uT, 200*uT],
+        fee: 20*uT
+    );
+    let large_tx = Arc::new(spend_utxos(large_tx).0);
+    assert!(large_tx.calculate_weight() > small_tx.calculate_weight());
+
+    mempool
+        .register_policy(Arc::new(MaxWeightPolicy(small_tx.calculate_weight())))
+        .unwrap();
+
+    // Both transactions are valid according to consensus, but the large one breaks the node's policy
+    assert_eq!(
+        mempool.insert(large_tx.clone()).unwrap(),
+        TxStorageResponse::NotStoredPolicyViolation
+    );
+    assert_eq!(
+        mempool.insert(small_tx.clone()).unwrap(),
+        TxStorageResponse::UnconfirmedPool
+    );
+    assert_eq!(
+        mempool
+            .has_tx_with_excess_sig(large_tx.body.kernels()[0].excess_sig.clone())
+            .unwrap(),
+        TxStorageResponse::NotStored
+    );
+    assert_eq!(mempool.stats().unwrap().unconfirmed_txs, 1);
+}
+
+#[test]
+#[allow(clippy::identity_op)]
+fn test_double_spend_attempt_event() {
+    let network = Network::LocalNet;
+    let (mut store, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    let mempool_validator = TxInputAndMaturityValidator::new(store.clone());
+    let mempool = Mempool::new(
+        MempoolConfig {
+            double_spend_alerts: true,
+            ..Default::default()
+        },
+        Arc::new(mempool_validator),
+    );
+    let mut double_spend_attempts = mempool.subscribe_double_spend_attempts().unwrap();
+    let txs = vec![txn_schema!(
+        from: vec![outputs[0][0].clone()],
+        to: vec![2 * T, 2 * T, 2 * T, 2 * T]
+    )];
+    generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager).unwrap();
+    mempool.process_published_block(blocks[1].to_arc_block()).unwrap();
+
+    // Both transactions spend the same output
+    let tx1 = txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1*T], fee: 20*uT);
+    let tx1 = Arc::new(spend_utxos(tx1).0);
+    let tx2 = txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1*T, 500*uT], fee: 25*uT);
+    let tx2 = Arc::new(spend_utxos(tx2).0);
+    let unrelated_tx = txn_schema!(from: vec![outputs[1][1].clone()], to: vec![1*T], fee: 20*uT);
+    let unrelated_tx = Arc::new(spend_utxos(unrelated_tx).0);
+
+    assert_eq!(mempool.insert(tx1.clone()).unwrap(), TxStorageResponse::UnconfirmedPool);
+    assert_eq!(
+        mempool.insert(unrelated_tx).unwrap(),
+        TxStorageResponse::UnconfirmedPool
+    );
+    assert_eq!(mempool.insert(tx2.clone()).unwrap(), TxStorageResponse::UnconfirmedPool);
+
+    let attempt = double_spend_attempts.try_recv().unwrap();
+    assert_eq!(attempt, DoubleSpendAttempt {
+        input: tx1.body.inputs()[0].commitment.clone(),
+        pooled_excess_sig: tx1.body.kernels()[0].excess_sig.clone(),
+        conflicting_excess_sig: tx2.body.kernels()[0].excess_sig.clone(),
+    });
+    assert!(double_spend_attempts.try_recv().is_err());
+}
+
+#[test]
+#[allow(clippy::identity_op)]
+fn test_mempool_state_events() {
+    let network = Network::LocalNet;
+    let (mut store, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    let mempool_validator = TxInputAndMaturityValidator::new(store.clone());
+    let mempool = Mempool::new(MempoolConfig::default(), Arc::new(mempool_validator));
+    let txs = vec![txn_schema!(
+        from: vec![outputs[0][0].clone()],
+        to: vec![2 * T, 2 * T, 2 * T, 2 * T]
+    )];
+    generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager).unwrap();
+    mempool.process_published_block(blocks[1].to_arc_block()).unwrap();
+    let mut events = mempool.subscribe_events().unwrap();
+
+    // Both transactions spend the same output
+    let tx1 = txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1*T], fee: 20*uT);
+    let tx1 = Arc::new(spend_utxos(tx1).0);
+    let tx1_sig = tx1.body.kernels()[0].excess_sig.clone();
+    let tx2 = txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1*T, 500*uT], fee: 25*uT);
+    let tx2 = Arc::new(spend_utxos(tx2).0);
+    let tx2_sig = tx2.body.kernels()[0].excess_sig.clone();
+
+    assert_eq!(mempool.insert(tx1.clone()).unwrap(), TxStorageResponse::UnconfirmedPool);
+    assert_eq!(events.try_recv().unwrap(), MempoolStateEvent::TxAdded(tx1_sig.clone()));
+    assert_eq!(events.try_recv().unwrap(), MempoolStateEvent::Updated);
+    assert_eq!(mempool.insert(tx2.clone()).unwrap(), TxStorageResponse::UnconfirmedPool);
+    assert_eq!(events.try_recv().unwrap(), MempoolStateEvent::TxAdded(tx2_sig.clone()));
+    assert_eq!(events.try_recv().unwrap(), MempoolStateEvent::Updated);
+
+    // Inserting a stored transaction again does not change the Mempool
+    assert_eq!(mempool.insert(tx1.clone()).unwrap(), TxStorageResponse::UnconfirmedPool);
+    assert!(events.try_recv().is_err());
+
+    // Mining tx1 moves it to the reorg pool and discards tx2
+    generate_block(&store, &mut blocks, vec![tx1.deref().clone()], &consensus_manager).unwrap();
+    mempool.process_published_block(blocks[2].to_arc_block()).unwrap();
+    assert_eq!(
+        events.try_recv().unwrap(),
+        MempoolStateEvent::TxRemoved(tx1_sig, TxRemovalReason::Mined)
+    );
+    assert_eq!(
+        events.try_recv().unwrap(),
+        MempoolStateEvent::TxRemoved(tx2_sig, TxRemovalReason::DoubleSpend)
+    );
+    assert_eq!(events.try_recv().unwrap(), MempoolStateEvent::Updated);
+    assert!(events.try_recv().is_err());
+}
+
+#[test]
+fn test_max_kernels_per_transaction() {
+    let mempool = Mempool::new(
+        MempoolConfig {
+            max_kernels_per_transaction: 2,
+            ..Default::default()
+        },
+        Arc::new(MockValidator::new(true)),
+    );
+    let tx1 = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 1, outputs: 1).0;
+    let tx2 = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 1, outputs: 1).0;
+    let tx3 = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 1, outputs: 1).0;
+    let tx4 = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 1, outputs: 1).0;
+    let tx5 = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 1, outputs: 1).0;
+
+    let at_limit = Arc::new(tx1 + tx2);
+    assert_eq!(at_limit.body.kernels().len(), 2);
+    let over_limit = Arc::new(tx3 + tx4 + tx5);
+    assert_eq!(over_limit.body.kernels().len(), 3);
+
+    assert_eq!(mempool.insert(at_limit).unwrap(), TxStorageResponse::UnconfirmedPool);
+    assert_eq!(
+        mempool.insert(over_limit.clone()).unwrap(),
+        TxStorageResponse::NotStored
+    );
+    assert_eq!(
+        mempool
+            .has_tx_with_excess_sig(over_limit.body.kernels()[0].excess_sig.clone())
+            .unwrap(),
+        TxStorageResponse::NotStored
+    );
+}
+
+#[test]
+fn test_validation_timing() {
+    let mempool = Mempool::new(
+        MempoolConfig::default(),
+        Arc::new(MockValidator::new(true).with_delay(Duration::from_millis(10))),
+    );
+    let tx = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 1, outputs: 1).0);
+    assert_eq!(mempool.insert(tx).unwrap(), TxStorageResponse::UnconfirmedPool);
+
+    let timing = mempool.validation_timing().unwrap();
+    assert_eq!(timing.num_validations, 1);
+    assert!(timing.max_duration_us >= 10_000);
+    assert_eq!(timing.mean_duration_us, timing.max_duration_us);
+    let non_empty = timing.buckets.iter().filter(|b| b.count > 0).collect::<Vec<_>>();
+    assert_eq!(non_empty.len(), 1);
+    assert_eq!(non_empty[0].count, 1);
+    assert!(non_empty[0].upper_bound_us >= 10_000);
+}
+
 #[test]
 #[allow(clippy::identity_op)]
 fn test_retrieve() {
@@ -750,6 +1063,73 @@ fn service_request_timeout() {
     });
 }
 
+#[test]
+#[allow(clippy::identity_op)]
+fn local_mempool_service_state_events() {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+    let temp_dir = tempdir().unwrap();
+    let network = Network::LocalNet;
+    let consensus_constants = network.create_consensus_constants();
+    let (block0, outputs) = create_genesis_block_with_utxos(&factories, &[T, T], &consensus_constants[0]);
+    let consensus_manager = ConsensusManagerBuilder::new(network)
+        .with_consensus_constants(consensus_constants[0].clone())
+        .with_block(block0.clone())
+        .build();
+    let (mut alice, _bob, consensus_manager) = create_network_with_2_base_nodes_with_config(
+        &mut runtime,
+        BlockchainDatabaseConfig::default(),
+        BaseNodeServiceConfig::default(),
+        MempoolServiceConfig::default(),
+        LivenessConfig::default(),
+        consensus_manager,
+        temp_dir.path().to_str().unwrap(),
+    );
+    alice.mock_base_node_state_machine.publish_status(StatusInfo {
+        bootstrapped: true,
+        state_info: StateInfo::Listening(ListeningInfo::new(true)),
+    });
+    let mut events = alice.local_mp_interface.get_mempool_state_event_stream();
+
+    let (txs, _) = schema_to_transaction(&[txn_schema!(from: vec![outputs[1].clone()], to: vec![500_000 * uT])]);
+    let tx = (*txs[0]).clone();
+    let tx_excess_sig = tx.body.kernels()[0].excess_sig.clone();
+    assert_eq!(
+        alice.mempool.insert(Arc::new(tx.clone())).unwrap(),
+        TxStorageResponse::UnconfirmedPool
+    );
+
+    let mut block1 = alice
+        .blockchain_db
+        .prepare_block_merkle_roots(chain_block(block0.block(), vec![tx], &consensus_manager))
+        .unwrap();
+    find_header_with_achieved_difficulty(&mut block1.header, Difficulty::from(1));
+
+    runtime.block_on(async {
+        // The detailed events of the mempool are available through the service handle
+        assert_eq!(
+            events.recv().await.unwrap(),
+            MempoolStateEvent::TxAdded(tx_excess_sig.clone())
+        );
+        assert_eq!(events.recv().await.unwrap(), MempoolStateEvent::Updated);
+
+        alice
+            .local_nci
+            .submit_block(block1, Broadcast::from(true))
+            .await
+            .unwrap();
+        assert_eq!(
+            events.recv().await.unwrap(),
+            MempoolStateEvent::TxRemoved(tx_excess_sig, TxRemovalReason::Mined)
+        );
+        assert_eq!(events.recv().await.unwrap(), MempoolStateEvent::Updated);
+
+        // Processing the block event publishes a single Updated event
+        tokio::time::delay_for(Duration::from_millis(500)).await;
+        assert!(events.try_recv().is_err());
+    });
+}
+
 #[test]
 #[ignore = "Flaky test that needs to be fixed"]
 #[allow(clippy::identity_op)]
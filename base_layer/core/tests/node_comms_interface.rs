@@ -23,22 +23,41 @@
 #[allow(dead_code)]
 mod helpers;
 
-use futures::{channel::mpsc, StreamExt};
-use helpers::block_builders::append_block;
+use futures::{channel::mpsc, FutureExt, StreamExt};
+use helpers::block_builders::{append_block, create_genesis_block_with_utxos};
 use std::sync::Arc;
 use tari_common_types::chain_metadata::ChainMetadata;
 use tari_comms::peer_manager::NodeId;
 use tari_core::{
     base_node::{
-        comms_interface::{CommsInterfaceError, InboundNodeCommsHandlers, NodeCommsRequest, NodeCommsResponse},
+        comms_interface::{
+            BlockEvent,
+            CommsInterfaceError,
+            InboundNodeCommsHandlers,
+            NodeCommsRequest,
+            NodeCommsResponse,
+        },
+        LocalNodeCommsInterface,
         OutboundNodeCommsInterface,
     },
-    blocks::{genesis_block, BlockBuilder, BlockHeader},
-    chain_storage::{BlockchainDatabaseConfig, DbTransaction, HistoricalBlock, Validators},
-    consensus::{ConsensusManagerBuilder, Network},
+    blocks::{genesis_block, short_tx_id, BlockBuilder, BlockHeader, NewBlock},
+    chain_storage::{BlockchainDatabaseConfig, ChainBlock, DbTransaction, HistoricalBlock, Validators},
+    consensus::{ConsensusManager, ConsensusManagerBuilder, Network},
     mempool::{Mempool, MempoolConfig},
-    test_helpers::blockchain::{create_store_with_consensus_and_validators_and_config, create_test_blockchain_db},
-    transactions::{helpers::create_utxo, tari_amount::MicroTari, types::CryptoFactories},
+    test_helpers::blockchain::{
+        create_store_with_consensus,
+        create_store_with_consensus_and_validators_and_config,
+        create_store_with_mock_validators,
+        create_test_blockchain_db,
+    },
+    transactions::{
+        aggregated_body::AggregateBody,
+        helpers::{create_utxo, schema_to_transaction},
+        tari_amount::{uT, MicroTari, T},
+        transaction::Transaction,
+        types::CryptoFactories,
+    },
+    txn_schema,
     validation::{mocks::MockValidator, transaction_validators::TxInputAndMaturityValidator},
 };
 use tari_crypto::tari_utilities::hash::Hashable;
@@ -432,3 +451,263 @@ async fn inbound_fetch_blocks_before_horizon_height() {
         panic!();
     }
 }
+
+#[tokio_macros::test]
+async fn inbound_handle_block_reorg_event() {
+    let network = Network::LocalNet;
+    let consensus_constants = network.create_consensus_constants();
+    let block0 = genesis_block::get_stibbons_genesis_block();
+    let consensus_manager = ConsensusManagerBuilder::new(network)
+        .with_consensus_constants(consensus_constants[0].clone())
+        .with_block(block0.clone())
+        .build();
+    let create_store = || create_store_with_mock_validators(consensus_manager.clone());
+    let store = create_store();
+    let fork_store = create_store();
+    let (block_event_sender, mut block_event_rx) = broadcast::channel(50);
+    let (request_sender, _) = reply_channel::unbounded();
+    let (block_sender, _) = mpsc::unbounded();
+    let outbound_nci = OutboundNodeCommsInterface::new(request_sender, block_sender);
+    let inbound_nch = InboundNodeCommsHandlers::new(
+        block_event_sender,
+        store.clone().into(),
+        new_mempool(),
+        consensus_manager.clone(),
+        outbound_nci,
+    );
+
+    // Main chain: G -> A1 -> A2
+    let block_a1 = append_block(&store, &block0, vec![], &consensus_manager, 1.into()).unwrap();
+    let block_a2 = append_block(&store, &block_a1, vec![], &consensus_manager, 1.into()).unwrap();
+    // Stronger fork: G -> B1 -> B2
+    let block_b1 = append_block(&fork_store, &block0, vec![], &consensus_manager, 2.into()).unwrap();
+    let block_b2 = append_block(&fork_store, &block_b1, vec![], &consensus_manager, 2.into()).unwrap();
+
+    store.add_block(block_b1.to_arc_block()).unwrap().assert_orphaned();
+    inbound_nch
+        .handle_block(block_b2.to_arc_block(), false.into(), None)
+        .await
+        .unwrap();
+
+    let event = block_event_rx.recv().await.unwrap();
+    if let BlockEvent::ValidBlockAdded(_, result, _) = &*event {
+        result.assert_reorg(2, 2);
+    } else {
+        panic!("Expected ValidBlockAdded event but got {}", event);
+    }
+
+    let event = block_event_rx.recv().await.unwrap();
+    if let BlockEvent::ReorgDetected {
+        fork_height,
+        old_tip,
+        new_tip,
+        depth,
+    } = &*event
+    {
+        assert_eq!(*fork_height, 0);
+        assert_eq!(*depth, 2);
+        assert_eq!(old_tip, block_a2.hash());
+        assert_eq!(new_tip, block_b2.hash());
+    } else {
+        panic!("Expected ReorgDetected event but got {}", event);
+    }
+}
+
+#[tokio_macros::test]
+async fn rewind_reorg_event() {
+    let network = Network::LocalNet;
+    let block0 = genesis_block::get_stibbons_genesis_block();
+    let consensus_manager = ConsensusManagerBuilder::new(network)
+        .with_consensus_constants(network.create_consensus_constants()[0].clone())
+        .with_block(block0.clone())
+        .build();
+    let store = create_store_with_mock_validators(consensus_manager.clone());
+
+    let block1 = append_block(&store, &block0, vec![], &consensus_manager, 1.into()).unwrap();
+    let block2 = append_block(&store, &block1, vec![], &consensus_manager, 1.into()).unwrap();
+    let block3 = append_block(&store, &block2, vec![], &consensus_manager, 1.into()).unwrap();
+
+    let (request_sender, _) = reply_channel::unbounded();
+    let (block_sender, _) = reply_channel::unbounded();
+    let (block_event_sender, mut block_event_rx) = broadcast::channel(10);
+    let local_nci = LocalNodeCommsInterface::new(request_sender, block_sender, block_event_sender);
+
+    let removed = store.rewind_to_height(1).unwrap();
+    local_nci.publish_rewind(removed);
+
+    let event = block_event_rx.recv().await.unwrap();
+    if let BlockEvent::BlockSyncRewind(blocks) = &*event {
+        assert_eq!(blocks.len(), 2);
+    } else {
+        panic!("Expected BlockSyncRewind event but got {}", event);
+    }
+
+    let event = block_event_rx.recv().await.unwrap();
+    match &*event {
+        BlockEvent::ReorgDetected {
+            fork_height,
+            old_tip,
+            new_tip,
+            depth,
+        } => {
+            assert_eq!(*fork_height, 1);
+            assert_eq!(*depth, 2);
+            assert_eq!(old_tip, block3.hash());
+            assert_eq!(new_tip, block1.hash());
+        },
+        event => panic!("Expected ReorgDetected event but got {}", event),
+    }
+
+    assert!(BlockEvent::rewind_detected(&[]).is_none());
+}
+
+fn create_compact_block_test_chain() -> (ConsensusManager, ChainBlock, Vec<Arc<Transaction>>) {
+    let factories = CryptoFactories::default();
+    let network = Network::LocalNet;
+    let consensus_constants = network.create_consensus_constants();
+    let (block0, outputs) = create_genesis_block_with_utxos(&factories, &[T, T], &consensus_constants[0]);
+    let consensus_manager = ConsensusManagerBuilder::new(network)
+        .with_consensus_constants(consensus_constants[0].clone())
+        .with_block(block0.clone())
+        .build();
+    let miner_store = create_store_with_mock_validators(consensus_manager.clone());
+
+    let schema = [
+        txn_schema!(from: vec![outputs[1].clone()], to: vec![10_000 * uT, 20_000 * uT]),
+        txn_schema!(from: vec![outputs[2].clone()], to: vec![30_000 * uT, 40_000 * uT]),
+    ];
+    let (txs, _) = schema_to_transaction(&schema);
+    let block1 = append_block(
+        &miner_store,
+        &block0,
+        txs.iter().map(|tx| (**tx).clone()).collect(),
+        &consensus_manager,
+        1.into(),
+    )
+    .unwrap();
+    (consensus_manager, block1, txs)
+}
+
+#[tokio_macros::test]
+async fn inbound_new_compact_block_reconstructed_from_mempool() {
+    let (consensus_manager, block1, txs) = create_compact_block_test_chain();
+    let store = create_store_with_consensus(consensus_manager.clone());
+    let mempool = new_mempool();
+    for tx in &txs {
+        mempool.insert(tx.clone()).unwrap();
+    }
+
+    let (block_event_sender, _) = broadcast::channel(50);
+    let (request_sender, mut request_receiver) = reply_channel::unbounded();
+    let (block_sender, _block_receiver) = mpsc::unbounded();
+    let outbound_nci = OutboundNodeCommsInterface::new(request_sender, block_sender);
+    let mut inbound_nch = InboundNodeCommsHandlers::new(
+        block_event_sender,
+        store.clone().into(),
+        mempool,
+        consensus_manager,
+        outbound_nci,
+    );
+
+    let new_block = NewBlock::from(block1.block());
+    assert!(new_block.compact_block.is_some());
+    inbound_nch
+        .handle_new_block_message(new_block, NodeId::new())
+        .await
+        .unwrap();
+
+    assert!(store.block_exists(block1.hash().clone()).unwrap());
+    // The block was reconstructed without requesting the full block or any transactions from the peer
+    assert!(request_receiver.next().now_or_never().is_none());
+}
+
+#[tokio_macros::test]
+async fn inbound_new_compact_block_requests_missing_transactions() {
+    let (consensus_manager, block1, txs) = create_compact_block_test_chain();
+    let store = create_store_with_consensus(consensus_manager.clone());
+    let mempool = new_mempool();
+    mempool.insert(txs[0].clone()).unwrap();
+
+    let (block_event_sender, _) = broadcast::channel(50);
+    let (request_sender, mut request_receiver) = reply_channel::unbounded();
+    let (block_sender, _block_receiver) = mpsc::unbounded();
+    let outbound_nci = OutboundNodeCommsInterface::new(request_sender, block_sender);
+    let mut inbound_nch = InboundNodeCommsHandlers::new(
+        block_event_sender,
+        store.clone().into(),
+        mempool,
+        consensus_manager,
+        outbound_nci,
+    );
+
+    let new_block = NewBlock::from(block1.block());
+    let expected_short_ids = vec![short_tx_id(block1.hash(), &txs[1].body.kernels()[0])];
+    let responder = async {
+        let req_context = request_receiver.next().await.unwrap();
+        match req_context.request().unwrap() {
+            (NodeCommsRequest::FetchTransactionsByShortIds(block_hash, short_ids), _) => {
+                assert_eq!(block_hash, block1.hash());
+                assert_eq!(*short_ids, expected_short_ids);
+            },
+            (req, _) => panic!("Unexpected request {}", req),
+        }
+        req_context
+            .reply(Ok(NodeCommsResponse::Transactions(vec![(*txs[1]).clone()])))
+            .unwrap();
+    };
+    let (result, _) = futures::join!(
+        inbound_nch.handle_new_block_message(new_block, NodeId::new()),
+        responder
+    );
+    result.unwrap();
+
+    assert!(store.block_exists(block1.hash().clone()).unwrap());
+    // The full block was not requested
+    assert!(request_receiver.next().now_or_never().is_none());
+}
+
+#[tokio_macros::test]
+async fn inbound_new_compact_block_falls_back_to_full_block() {
+    let (consensus_manager, block1, txs) = create_compact_block_test_chain();
+    let store = create_store_with_consensus(consensus_manager.clone());
+    let mempool = new_mempool();
+    for tx in &txs {
+        mempool.insert(tx.clone()).unwrap();
+    }
+
+    let (block_event_sender, _) = broadcast::channel(50);
+    let (request_sender, mut request_receiver) = reply_channel::unbounded();
+    let (block_sender, _block_receiver) = mpsc::unbounded();
+    let outbound_nci = OutboundNodeCommsInterface::new(request_sender, block_sender);
+    let mut inbound_nch = InboundNodeCommsHandlers::new(
+        block_event_sender,
+        store.clone().into(),
+        mempool,
+        consensus_manager,
+        outbound_nci,
+    );
+
+    // The reconstructed block is missing its coinbase and fails validation
+    let mut new_block = NewBlock::from(block1.block());
+    new_block.compact_block.as_mut().unwrap().coinbase = AggregateBody::empty();
+    let responder = async {
+        let req_context = request_receiver.next().await.unwrap();
+        match req_context.request().unwrap() {
+            (NodeCommsRequest::FetchBlocksWithHashes(hashes), _) => {
+                assert_eq!(hashes, &vec![block1.hash().clone()]);
+            },
+            (req, _) => panic!("Unexpected request {}", req),
+        }
+        let block = HistoricalBlock::new(block1.block().clone(), 0, Default::default(), vec![], 0);
+        req_context
+            .reply(Ok(NodeCommsResponse::HistoricalBlocks(vec![block])))
+            .unwrap();
+    };
+    let (result, _) = futures::join!(
+        inbound_nch.handle_new_block_message(new_block, NodeId::new()),
+        responder
+    );
+    result.unwrap();
+
+    assert!(store.block_exists(block1.hash().clone()).unwrap());
+}
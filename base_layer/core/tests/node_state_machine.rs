@@ -25,33 +25,68 @@ mod helpers;
 
 use futures::StreamExt;
 use helpers::{
-    block_builders::{append_block, chain_block, create_genesis_block},
+    block_builders::{append_block, chain_block, create_genesis_block, generate_new_block},
     chain_metadata::{random_peer_metadata, MockChainMetadata},
-    nodes::{create_network_with_2_base_nodes_with_config, wait_until_online, BaseNodeBuilder},
+    nodes::{create_network_with_2_base_nodes_with_config, random_node_identity, wait_until_online, BaseNodeBuilder},
+    sample_blockchains::create_new_blockchain,
 };
 use std::{thread, time::Duration};
+use tari_comms::{
+    protocol::rpc::{
+        mock::{MockRpcServer, RpcMock, RpcMockMethodState},
+        NamedProtocolService,
+        Request,
+        Response,
+        RpcStatus,
+        Streaming,
+    },
+    test_utils::mocks::create_connectivity_mock,
+};
 use tari_core::{
     base_node::{
         chain_metadata_service::PeerChainMetadata,
         comms_interface::Broadcast,
+        create_base_node_sync_rpc_service,
         service::BaseNodeServiceConfig,
         state_machine_service::{
-            states::{Listening, StateEvent, StatusInfo},
+            states::{BlockSync, HeaderSync, HorizonStateSync, Listening, StateEvent, StatusInfo},
             BaseNodeStateMachine,
             BaseNodeStateMachineConfig,
         },
+        sync::rpc::BaseNodeSyncRpcServer,
+        BaseNodeSyncService,
         SyncValidators,
     },
-    chain_storage::BlockchainDatabaseConfig,
+    chain_storage::{BlockchainDatabaseConfig, DbTransaction, HorizonSyncProgress, MmrTree, PrunedOutput},
     consensus::{ConsensusConstantsBuilder, ConsensusManagerBuilder, Network},
     mempool::MempoolServiceConfig,
     proof_of_work::randomx_factory::RandomXFactory,
-    test_helpers::blockchain::create_test_blockchain_db,
-    transactions::types::CryptoFactories,
+    proto,
+    proto::base_node::{
+        BlockBodyResponse,
+        FindChainSplitRequest,
+        FindChainSplitResponse,
+        GetBlocksByHashRequest,
+        GetBlocksByHashResponse,
+        SyncBlocksRequest,
+        SyncHeadersRequest,
+        SyncKernelsRequest,
+        SyncUtxosRequest,
+        SyncUtxosResponse,
+    },
+    test_helpers::blockchain::{
+        create_mock_validators,
+        create_store_with_consensus,
+        create_store_with_consensus_and_validators_and_config,
+        create_test_blockchain_db,
+    },
+    transactions::{tari_amount::T, types::CryptoFactories},
+    txn_schema,
     validation::mocks::MockValidator,
 };
 use tari_p2p::services::liveness::LivenessConfig;
 use tari_shutdown::Shutdown;
+use tari_test_utils::unpack_enum;
 use tempfile::tempdir;
 use tokio::{
     runtime::Runtime,
@@ -187,3 +222,467 @@ fn test_event_channel() {
         }
     });
 }
+
+#[test]
+fn test_header_sync_selects_highest_work_chain_before_block_sync() {
+    let mut runtime = Runtime::new().unwrap();
+    let factories = CryptoFactories::default();
+    let network = Network::LocalNet;
+    let temp_dir = tempdir().unwrap();
+    let consensus_constants = ConsensusConstantsBuilder::new(network)
+        .with_emission_amounts(100_000_000.into(), &EMISSION, 100.into())
+        .build();
+    let (genesis_block, _) = create_genesis_block(&factories, &consensus_constants);
+    let consensus_manager = ConsensusManagerBuilder::new(network)
+        .with_consensus_constants(consensus_constants)
+        .with_block(genesis_block.clone())
+        .build();
+    let (alice_node, consensus_manager) = BaseNodeBuilder::new(network)
+        .with_consensus_manager(consensus_manager)
+        .start(&mut runtime, temp_dir.path().to_str().unwrap());
+
+    // Bob and Carol advertise competing tips. Carol's chain has more accumulated work.
+    let bob_db = create_store_with_consensus(consensus_manager.clone());
+    let mut prev_block = genesis_block.clone();
+    for _ in 0..2 {
+        prev_block = append_block(&bob_db, &prev_block, vec![], &consensus_manager, 1.into()).unwrap();
+    }
+    let bob_tip = prev_block;
+
+    let carol_db = create_store_with_consensus(consensus_manager.clone());
+    let mut prev_block = genesis_block;
+    for _ in 0..3 {
+        prev_block = append_block(&carol_db, &prev_block, vec![], &consensus_manager, 1.into()).unwrap();
+    }
+    let carol_tip = prev_block;
+
+    let alice_db = create_store_with_consensus(consensus_manager.clone());
+    let (connectivity, connectivity_mock) = create_connectivity_mock();
+    let connectivity_mock_state = connectivity_mock.get_shared_state();
+    runtime.spawn(connectivity_mock.run());
+
+    let shutdown = Shutdown::new();
+    let (state_change_event_publisher, _) = broadcast::channel(10);
+    let (status_event_sender, _status_event_receiver) = watch::channel(StatusInfo::new());
+    let mut alice_state_machine = BaseNodeStateMachine::new(
+        alice_db.clone().into(),
+        alice_node.local_nci.clone(),
+        alice_node.outbound_nci.clone(),
+        connectivity,
+        alice_node.comms.peer_manager(),
+        alice_node.chain_metadata_handle.get_event_stream(),
+        BaseNodeStateMachineConfig::default(),
+        SyncValidators::new(MockValidator::new(true), MockValidator::new(true)),
+        status_event_sender,
+        state_change_event_publisher,
+        RandomXFactory::default(),
+        consensus_manager,
+        shutdown.to_signal(),
+    );
+
+    runtime.block_on(async move {
+        let mut sync_peers = Vec::new();
+        let mut rpc_servers = Vec::new();
+        for db in vec![bob_db, carol_db] {
+            let node_identity = random_node_identity();
+            let server = create_base_node_sync_rpc_service(db.into());
+            let protocol_name = server.as_protocol_name();
+            let mut mock_server = MockRpcServer::new(server, node_identity.clone());
+            mock_server.serve();
+            let conn = mock_server
+                .create_connection(node_identity.to_peer(), protocol_name.into())
+                .await;
+            connectivity_mock_state.add_active_connection(conn).await;
+            sync_peers.push(node_identity.node_id().clone());
+            rpc_servers.push(mock_server);
+        }
+        let carol_node_id = sync_peers[1].clone();
+
+        // Bob is attempted first, but Carol's chain must win because it has more accumulated work
+        let event = HeaderSync::new(sync_peers).next_event(&mut alice_state_machine).await;
+        unpack_enum!(StateEvent::HeadersSynchronized(sync_peer) = event);
+        assert_eq!(sync_peer.peer_node_id(), &carol_node_id);
+        let header_tip = alice_db.fetch_tip_header().unwrap();
+        assert_eq!(header_tip.hash(), carol_tip.hash());
+        // No block bodies are downloaded during header sync
+        assert_eq!(alice_db.get_height().unwrap(), 0);
+
+        let event = BlockSync::with_peer(sync_peer)
+            .next_event(&mut alice_state_machine)
+            .await;
+        assert_eq!(event, StateEvent::BlocksSynchronized);
+        let metadata = alice_db.get_chain_metadata().unwrap();
+        assert_eq!(metadata.height_of_longest_chain(), 3);
+        assert_eq!(metadata.best_block(), carol_tip.hash());
+        assert!(alice_db.fetch_block_by_hash(bob_tip.hash().clone()).unwrap().is_none());
+        assert!(alice_db
+            .fetch_header_by_block_hash(bob_tip.hash().clone())
+            .unwrap()
+            .is_none());
+    });
+}
+
+/// A sync peer that only serves blocks, with the blocks it sends set by the test
+#[derive(Default)]
+struct BlockSyncPeerMock {
+    sync_blocks: RpcMockMethodState<SyncBlocksRequest, Vec<BlockBodyResponse>>,
+}
+
+impl RpcMock for BlockSyncPeerMock {}
+
+#[tari_comms::async_trait]
+impl BaseNodeSyncService for BlockSyncPeerMock {
+    async fn sync_blocks(
+        &self,
+        request: Request<SyncBlocksRequest>,
+    ) -> Result<Streaming<BlockBodyResponse>, RpcStatus>
+    {
+        self.server_streaming(request, &self.sync_blocks).await
+    }
+
+    async fn sync_headers(
+        &self,
+        _: Request<SyncHeadersRequest>,
+    ) -> Result<Streaming<proto::core::BlockHeader>, RpcStatus>
+    {
+        Err(RpcStatus::not_implemented("sync_headers"))
+    }
+
+    async fn get_header_by_height(&self, _: Request<u64>) -> Result<Response<proto::core::BlockHeader>, RpcStatus> {
+        Err(RpcStatus::not_implemented("get_header_by_height"))
+    }
+
+    async fn find_chain_split(
+        &self,
+        _: Request<FindChainSplitRequest>,
+    ) -> Result<Response<FindChainSplitResponse>, RpcStatus>
+    {
+        Err(RpcStatus::not_implemented("find_chain_split"))
+    }
+
+    async fn get_chain_metadata(&self, _: Request<()>) -> Result<Response<proto::base_node::ChainMetadata>, RpcStatus> {
+        Err(RpcStatus::not_implemented("get_chain_metadata"))
+    }
+
+    async fn sync_kernels(
+        &self,
+        _: Request<SyncKernelsRequest>,
+    ) -> Result<Streaming<proto::types::TransactionKernel>, RpcStatus>
+    {
+        Err(RpcStatus::not_implemented("sync_kernels"))
+    }
+
+    async fn sync_utxos(&self, _: Request<SyncUtxosRequest>) -> Result<Streaming<SyncUtxosResponse>, RpcStatus> {
+        Err(RpcStatus::not_implemented("sync_utxos"))
+    }
+
+    async fn get_blocks_by_hash(
+        &self,
+        _: Request<GetBlocksByHashRequest>,
+    ) -> Result<Response<GetBlocksByHashResponse>, RpcStatus>
+    {
+        Err(RpcStatus::not_implemented("get_blocks_by_hash"))
+    }
+}
+
+#[test]
+fn test_block_sync_bans_peer_that_sends_blocks_not_in_the_header_chain() {
+    let mut runtime = Runtime::new().unwrap();
+    let factories = CryptoFactories::default();
+    let network = Network::LocalNet;
+    let temp_dir = tempdir().unwrap();
+    let consensus_constants = ConsensusConstantsBuilder::new(network)
+        .with_emission_amounts(100_000_000.into(), &EMISSION, 100.into())
+        .build();
+    let (genesis_block, _) = create_genesis_block(&factories, &consensus_constants);
+    let consensus_manager = ConsensusManagerBuilder::new(network)
+        .with_consensus_constants(consensus_constants)
+        .with_block(genesis_block.clone())
+        .build();
+    let (alice_node, consensus_manager) = BaseNodeBuilder::new(network)
+        .with_consensus_manager(consensus_manager)
+        .start(&mut runtime, temp_dir.path().to_str().unwrap());
+
+    let bob_db = create_store_with_consensus(consensus_manager.clone());
+    let mut blocks = Vec::new();
+    let mut prev_block = genesis_block;
+    for _ in 0..3 {
+        prev_block = append_block(&bob_db, &prev_block, vec![], &consensus_manager, 1.into()).unwrap();
+        blocks.push(prev_block.block().clone());
+    }
+    let bob_tip = prev_block;
+
+    let alice_db = create_store_with_consensus(consensus_manager.clone());
+    let (connectivity, connectivity_mock) = create_connectivity_mock();
+    let connectivity_mock_state = connectivity_mock.get_shared_state();
+    runtime.spawn(connectivity_mock.run());
+
+    let shutdown = Shutdown::new();
+    let (state_change_event_publisher, _) = broadcast::channel(10);
+    let (status_event_sender, _status_event_receiver) = watch::channel(StatusInfo::new());
+    let mut alice_state_machine = BaseNodeStateMachine::new(
+        alice_db.clone().into(),
+        alice_node.local_nci.clone(),
+        alice_node.outbound_nci.clone(),
+        connectivity.clone(),
+        alice_node.comms.peer_manager(),
+        alice_node.chain_metadata_handle.get_event_stream(),
+        BaseNodeStateMachineConfig::default(),
+        SyncValidators::new(MockValidator::new(true), MockValidator::new(true)),
+        status_event_sender,
+        state_change_event_publisher,
+        RandomXFactory::default(),
+        consensus_manager,
+        shutdown.to_signal(),
+    );
+
+    runtime.block_on(async move {
+        let bob_identity = random_node_identity();
+        let server = create_base_node_sync_rpc_service(bob_db.into());
+        let protocol_name = server.as_protocol_name();
+        let mut bob_server = MockRpcServer::new(server, bob_identity.clone());
+        bob_server.serve();
+        let bob_conn = bob_server
+            .create_connection(bob_identity.to_peer(), protocol_name.into())
+            .await;
+        connectivity_mock_state.add_active_connection(bob_conn).await;
+
+        // Mallory sends the blocks of the header chain, but in the wrong order
+        let mallory_identity = random_node_identity();
+        let service = BlockSyncPeerMock::default();
+        service
+            .sync_blocks
+            .set_response(Ok(blocks.into_iter().rev().map(Into::into).collect()))
+            .await;
+        let server = BaseNodeSyncRpcServer::new(service);
+        let protocol_name = server.as_protocol_name();
+        let mut mallory_server = MockRpcServer::new(server, mallory_identity.clone());
+        mallory_server.serve();
+        let mallory_conn = mallory_server
+            .create_connection(mallory_identity.to_peer(), protocol_name.into())
+            .await;
+        connectivity_mock_state.add_active_connection(mallory_conn.clone()).await;
+
+        let event = HeaderSync::new(vec![bob_identity.node_id().clone()])
+            .next_event(&mut alice_state_machine)
+            .await;
+        unpack_enum!(StateEvent::HeadersSynchronized(_sync_peer) = event);
+        connectivity_mock_state.take_calls().await;
+
+        // Mallory is used first, so the chunk has to be reassigned to Bob after Mallory is banned
+        let event = BlockSync::with_peer(mallory_conn)
+            .with_candidate_peers(vec![bob_identity.node_id().clone()])
+            .next_event(&mut alice_state_machine)
+            .await;
+        assert_eq!(event, StateEvent::BlocksSynchronized);
+        let metadata = alice_db.get_chain_metadata().unwrap();
+        assert_eq!(metadata.height_of_longest_chain(), 3);
+        assert_eq!(metadata.best_block(), bob_tip.hash());
+
+        // The mock handles requests in order, so once this request is answered all bans have been recorded
+        connectivity.clone().get_connectivity_status().await.unwrap();
+        let ban_calls = connectivity_mock_state
+            .take_calls()
+            .await
+            .into_iter()
+            .filter(|call| call.starts_with("BanPeer"))
+            .collect::<Vec<_>>();
+        assert_eq!(ban_calls.len(), 1);
+        assert!(ban_calls[0].starts_with(&format!("BanPeer({:?}", mallory_identity.node_id())));
+    });
+}
+
+#[test]
+fn test_horizon_sync_resumes_part_way_through_a_block() {
+    let mut runtime = Runtime::new().unwrap();
+    let network = Network::LocalNet;
+    let temp_dir = tempdir().unwrap();
+    let (mut bob_db, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    // Block 1 contains a transaction kernel and a coinbase kernel
+    let schema = vec![txn_schema!(from: vec![outputs[0][0].clone()], to: vec![6 * T, 3 * T])];
+    generate_new_block(&mut bob_db, &mut blocks, &mut outputs, schema, &consensus_manager).unwrap();
+    for _ in 0..2 {
+        generate_new_block(&mut bob_db, &mut blocks, &mut outputs, vec![], &consensus_manager).unwrap();
+    }
+
+    let (alice_node, consensus_manager) = BaseNodeBuilder::new(network)
+        .with_consensus_manager(consensus_manager)
+        .start(&mut runtime, temp_dir.path().to_str().unwrap());
+    let config = BlockchainDatabaseConfig {
+        pruning_horizon: 1,
+        ..Default::default()
+    };
+    let alice_db = create_store_with_consensus_and_validators_and_config(
+        consensus_manager.clone(),
+        create_mock_validators(),
+        config,
+    );
+    let (connectivity, connectivity_mock) = create_connectivity_mock();
+    let connectivity_mock_state = connectivity_mock.get_shared_state();
+    runtime.spawn(connectivity_mock.run());
+
+    let shutdown = Shutdown::new();
+    let (state_change_event_publisher, _) = broadcast::channel(10);
+    let (status_event_sender, _status_event_receiver) = watch::channel(StatusInfo::new());
+    let mut alice_state_machine = BaseNodeStateMachine::new(
+        alice_db.clone().into(),
+        alice_node.local_nci.clone(),
+        alice_node.outbound_nci.clone(),
+        connectivity,
+        alice_node.comms.peer_manager(),
+        alice_node.chain_metadata_handle.get_event_stream(),
+        BaseNodeStateMachineConfig::default(),
+        SyncValidators::new(MockValidator::new(true), MockValidator::new(true)),
+        status_event_sender,
+        state_change_event_publisher,
+        RandomXFactory::default(),
+        consensus_manager,
+        shutdown.to_signal(),
+    );
+
+    runtime.block_on(async move {
+        let node_identity = random_node_identity();
+        let server = create_base_node_sync_rpc_service(bob_db.clone().into());
+        let protocol_name = server.as_protocol_name();
+        let mut mock_server = MockRpcServer::new(server, node_identity.clone());
+        mock_server.serve();
+        let conn = mock_server
+            .create_connection(node_identity.to_peer(), protocol_name.into())
+            .await;
+        connectivity_mock_state.add_active_connection(conn).await;
+
+        let event = HeaderSync::new(vec![node_identity.node_id().clone()])
+            .next_event(&mut alice_state_machine)
+            .await;
+        unpack_enum!(StateEvent::HeadersSynchronized(sync_peer) = event);
+
+        // Simulate a horizon sync that was interrupted after committing the first kernel of block 1
+        let block1_hash = blocks[1].hash().clone();
+        let block1_kernels = bob_db.fetch_kernels_in_block(block1_hash.clone()).unwrap();
+        assert_eq!(block1_kernels.len(), 2);
+        let mmr_position = blocks[0].header().kernel_mmr_size;
+        let mut txn = DbTransaction::new();
+        txn.insert_kernel(block1_kernels[0].clone(), block1_hash.clone(), mmr_position as u32);
+        txn.set_horizon_sync_progress(Some(HorizonSyncProgress::new(
+            MmrTree::Kernel,
+            block1_hash.clone(),
+            mmr_position + 1,
+        )));
+        alice_db.write(txn).unwrap();
+
+        // Resuming requests only the remaining kernels, re-inserting the committed kernel would fail the sync
+        let event = HorizonStateSync::with_peer(sync_peer)
+            .next_event(&mut alice_state_machine)
+            .await;
+        assert_eq!(event, StateEvent::HorizonStateSynchronized);
+        assert_eq!(alice_db.fetch_kernels_in_block(block1_hash).unwrap(), block1_kernels);
+        assert_eq!(
+            alice_db.fetch_mmr_size(MmrTree::Kernel).unwrap(),
+            blocks[2].header().kernel_mmr_size
+        );
+        assert!(alice_db.fetch_horizon_sync_progress().unwrap().is_none());
+        let metadata = alice_db.get_chain_metadata().unwrap();
+        assert_eq!(metadata.height_of_longest_chain(), 2);
+    });
+}
+
+#[test]
+fn test_horizon_sync_resumes_outputs_part_way_through_a_block() {
+    let mut runtime = Runtime::new().unwrap();
+    let network = Network::LocalNet;
+    let temp_dir = tempdir().unwrap();
+    let (mut bob_db, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    // Block 1 contains the transaction outputs and a coinbase output
+    let schema = vec![txn_schema!(from: vec![outputs[0][0].clone()], to: vec![6 * T, 3 * T])];
+    generate_new_block(&mut bob_db, &mut blocks, &mut outputs, schema, &consensus_manager).unwrap();
+    for _ in 0..2 {
+        generate_new_block(&mut bob_db, &mut blocks, &mut outputs, vec![], &consensus_manager).unwrap();
+    }
+
+    let (alice_node, consensus_manager) = BaseNodeBuilder::new(network)
+        .with_consensus_manager(consensus_manager)
+        .start(&mut runtime, temp_dir.path().to_str().unwrap());
+    let config = BlockchainDatabaseConfig {
+        pruning_horizon: 1,
+        ..Default::default()
+    };
+    let alice_db = create_store_with_consensus_and_validators_and_config(
+        consensus_manager.clone(),
+        create_mock_validators(),
+        config,
+    );
+    let (connectivity, connectivity_mock) = create_connectivity_mock();
+    let connectivity_mock_state = connectivity_mock.get_shared_state();
+    runtime.spawn(connectivity_mock.run());
+
+    let shutdown = Shutdown::new();
+    let (state_change_event_publisher, _) = broadcast::channel(10);
+    let (status_event_sender, _status_event_receiver) = watch::channel(StatusInfo::new());
+    let mut alice_state_machine = BaseNodeStateMachine::new(
+        alice_db.clone().into(),
+        alice_node.local_nci.clone(),
+        alice_node.outbound_nci.clone(),
+        connectivity,
+        alice_node.comms.peer_manager(),
+        alice_node.chain_metadata_handle.get_event_stream(),
+        BaseNodeStateMachineConfig::default(),
+        SyncValidators::new(MockValidator::new(true), MockValidator::new(true)),
+        status_event_sender,
+        state_change_event_publisher,
+        RandomXFactory::default(),
+        consensus_manager,
+        shutdown.to_signal(),
+    );
+
+    runtime.block_on(async move {
+        let node_identity = random_node_identity();
+        let server = create_base_node_sync_rpc_service(bob_db.clone().into());
+        let protocol_name = server.as_protocol_name();
+        let mut mock_server = MockRpcServer::new(server, node_identity.clone());
+        mock_server.serve();
+        let conn = mock_server
+            .create_connection(node_identity.to_peer(), protocol_name.into())
+            .await;
+        connectivity_mock_state.add_active_connection(conn).await;
+
+        let event = HeaderSync::new(vec![node_identity.node_id().clone()])
+            .next_event(&mut alice_state_machine)
+            .await;
+        unpack_enum!(StateEvent::HeadersSynchronized(sync_peer) = event);
+
+        // Simulate a horizon sync that was interrupted after committing the first output of block 1
+        let block1_hash = blocks[1].hash().clone();
+        let block1_outputs = bob_db.fetch_outputs_in_block(block1_hash.clone()).unwrap();
+        assert!(block1_outputs.len() > 1);
+        let first_output = match block1_outputs.into_iter().next().unwrap() {
+            PrunedOutput::NotPruned { output } => output,
+            PrunedOutput::Pruned { .. } => panic!("Block 1 output is unexpectedly pruned"),
+        };
+        let mmr_position = blocks[0].header().output_mmr_size;
+        let mut txn = DbTransaction::new();
+        txn.insert_utxo(first_output, block1_hash.clone(), mmr_position as u32);
+        txn.set_horizon_sync_progress(Some(HorizonSyncProgress::new(
+            MmrTree::Utxo,
+            block1_hash.clone(),
+            mmr_position + 1,
+        )));
+        alice_db.write(txn).unwrap();
+
+        // The remaining outputs are requested from the start of the block, and the committed output is skipped
+        let event = HorizonStateSync::with_peer(sync_peer)
+            .next_event(&mut alice_state_machine)
+            .await;
+        assert_eq!(event, StateEvent::HorizonStateSynchronized);
+        assert_eq!(
+            alice_db.fetch_outputs_in_block(block1_hash.clone()).unwrap().len(),
+            bob_db.fetch_outputs_in_block(block1_hash).unwrap().len()
+        );
+        assert_eq!(
+            alice_db.fetch_mmr_size(MmrTree::Utxo).unwrap(),
+            blocks[2].header().output_mmr_size
+        );
+        assert!(alice_db.fetch_horizon_sync_progress().unwrap().is_none());
+        let metadata = alice_db.get_chain_metadata().unwrap();
+        assert_eq!(metadata.height_of_longest_chain(), 2);
+    });
+}
@@ -0,0 +1,68 @@
+//  Copyright 2021, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::seed_peer::SeedPeer;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use trust_dns_client::rr::Name;
+
+/// A cache of resolved seed peers keyed by query name. Entries expire after the TTL of the DNS answer they were
+/// resolved from. Clones share the same cache.
+#[derive(Clone, Default)]
+pub(super) struct SeedPeerCache {
+    entries: Arc<Mutex<HashMap<Name, CacheEntry>>>,
+}
+
+struct CacheEntry {
+    peers: Vec<SeedPeer>,
+    expires_at: Instant,
+}
+
+impl SeedPeerCache {
+    /// Returns the cached seed peers for `name` if they have not expired
+    pub fn get(&self, name: &Name) -> Option<Vec<SeedPeer>> {
+        let mut entries = self.entries.lock().expect("SeedPeerCache lock poisoned");
+        match entries.get(name) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.peers.clone()),
+            Some(_) => {
+                entries.remove(name);
+                None
+            },
+            None => None,
+        }
+    }
+
+    /// Caches the seed peers for `name` for `ttl`. Nothing is cached if the TTL is zero.
+    pub fn insert(&self, name: Name, peers: Vec<SeedPeer>, ttl: Duration) {
+        if ttl.as_secs() == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().expect("SeedPeerCache lock poisoned");
+        entries.insert(name, CacheEntry {
+            peers,
+            expires_at: Instant::now() + ttl,
+        });
+    }
+}
@@ -28,4 +28,6 @@ pub enum DnsSeedError {
     ClientError(#[from] ClientError),
     #[error("Client error: {0}")]
     ProtoError(#[from] ProtoError),
+    #[error("No DNS name servers were available")]
+    NoNameServers,
 }
@@ -1,6 +1,9 @@
 #[cfg(test)]
 mod test;
 
+mod cache;
+use cache::SeedPeerCache;
+
 mod error;
 pub use error::DnsSeedError;
 
@@ -12,33 +15,51 @@ pub use trust_dns_client::{
 };
 
 use crate::seed_peer::SeedPeer;
-use futures::future;
-use std::{net::SocketAddr, sync::Arc};
+use futures::{future, Future};
+use log::*;
+use rustls::ClientConfig;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 use tari_shutdown::Shutdown;
-use tokio::{net::UdpSocket, task};
+use tokio::{
+    net::{TcpStream, UdpSocket},
+    task,
+};
 use trust_dns_client::{
     client::{AsyncClient, AsyncDnssecClient},
-    op::Query,
-    proto::{rr::dnssec::public_key::Rsa, udp::UdpResponse, DnsHandle},
-    rr::{dnssec::TrustAnchor, DNSClass, RecordType},
+    op::{NoopMessageFinalizer, Query},
+    proto::{
+        iocompat::AsyncIo02As03,
+        rr::dnssec::public_key::Rsa,
+        udp::UdpResponse,
+        xfer::{DnsMultiplexer, DnsMultiplexerSerialResponse},
+        DnsHandle,
+    },
+    rr::{dnssec::TrustAnchor, DNSClass, Record, RecordType},
     serialize::binary::BinEncoder,
+    tcp::TcpClientStream,
     udp::UdpClientStream,
 };
+use trust_dns_https::{HttpsClientResponse, HttpsClientStreamBuilder};
 
 // This was copied from the trust-dns crate.
 const ROOT_ANCHOR_ORIG: &[u8] = include_bytes!("roots/19036.rsa");
 // This was generated from the `.` root domain in 10/2020.
 const ROOT_ANCHOR_CURRENT: &[u8] = include_bytes!("roots/20326.rsa");
 
+const LOG_TARGET: &str = "p2p::dns_seed";
+
 #[derive(Clone)]
 pub struct DnsSeedResolver {
     inner: Inner,
+    name_servers: Vec<SocketAddr>,
+    current_name_server: usize,
 }
 
 #[derive(Clone)]
 enum Inner {
     Secure(Resolver<AsyncDnssecClient<UdpResponse>>),
     Normal(Resolver<AsyncClient<UdpResponse>>),
+    Https(Resolver<AsyncClient<HttpsClientResponse>>),
 }
 
 #[inline]
@@ -56,9 +77,23 @@ impl DnsSeedResolver {
     /// ## Arguments
     /// -`name_server` - the DNS name server to use to resolve records
     pub async fn connect_secure(name_server: SocketAddr) -> Result<Self, DnsSeedError> {
-        let resolver = Resolver::connect_secure(name_server, default_trust_anchor()).await?;
+        Self::connect_secure_with_fallback(vec![name_server]).await
+    }
+
+    /// Connect with DNSSEC protection to the first of the given name servers that can be connected to. If a query
+    /// to that name server fails, the remaining name servers are tried in order.
+    ///
+    /// ## Arguments
+    /// -`name_servers` - the DNS name servers to use to resolve records, in order of preference
+    pub async fn connect_secure_with_fallback(name_servers: Vec<SocketAddr>) -> Result<Self, DnsSeedError> {
+        let (current_name_server, resolver) = connect_first(&name_servers, 0, |name_server| {
+            Resolver::connect_secure(name_server, default_trust_anchor())
+        })
+        .await?;
         Ok(Self {
             inner: Inner::Secure(resolver),
+            name_servers,
+            current_name_server,
         })
     }
 
@@ -67,20 +102,162 @@ impl DnsSeedResolver {
     /// ## Arguments
     /// -`name_server` - the DNS name server to use to resolve records
     pub async fn connect(name_server: SocketAddr) -> Result<Self, DnsSeedError> {
-        let resolver = Resolver::connect(name_server).await?;
+        Self::connect_with_fallback(vec![name_server]).await
+    }
+
+    /// Connect without DNSSEC protection to the first of the given name servers that can be connected to. If a query
+    /// to that name server fails, the remaining name servers are tried in order.
+    ///
+    /// ## Arguments
+    /// -`name_servers` - the DNS name servers to use to resolve records, in order of preference
+    pub async fn connect_with_fallback(name_servers: Vec<SocketAddr>) -> Result<Self, DnsSeedError> {
+        let (current_name_server, resolver) = connect_first(&name_servers, 0, Resolver::connect).await?;
         Ok(Self {
             inner: Inner::Normal(resolver),
+            name_servers,
+            current_name_server,
+        })
+    }
+
+    /// Connect using DNS-over-HTTPS (RFC 8484), for networks where outbound UDP DNS traffic is blocked
+    ///
+    /// The DoH server is given as a socket address rather than a URL or host name because resolving its host name
+    /// would itself require the plain DNS lookup that DoH is used to avoid. `dns_name` is only used for SNI and to
+    /// verify the server's TLS certificate, so it must match the certificate presented at `name_server`.
+    ///
+    /// ## Arguments
+    /// -`name_server` - the DoH name server to use to resolve records
+    /// -`dns_name` - the TLS name of the DoH name server e.g. `cloudflare-dns.com`
+    pub async fn connect_https(name_server: SocketAddr, dns_name: String) -> Result<Self, DnsSeedError> {
+        let resolver = Resolver::connect_https(name_server, dns_name).await?;
+        Ok(Self {
+            inner: Inner::Https(resolver),
+            name_servers: vec![name_server],
+            current_name_server: 0,
         })
     }
 }
 
 impl DnsSeedResolver {
+    /// Enables or disables caching of resolved seed peers. Cached seed peers are returned for repeated queries of the
+    /// same name until the TTL of the DNS answer expires.
+    pub fn with_cache(mut self, enabled: bool) -> Self {
+        self.inner = match self.inner {
+            Inner::Secure(inner) => Inner::Secure(inner.with_cache(enabled)),
+            Inner::Normal(inner) => Inner::Normal(inner.with_cache(enabled)),
+            Inner::Https(inner) => Inner::Https(inner.with_cache(enabled)),
+        };
+        self
+    }
+
+    /// Resolves the seed peers for the given domain. If a UDP response is truncated, the query is retried over TCP.
+    /// If the query fails, it is retried using the next name server, if any.
     pub async fn resolve<T: IntoName>(&mut self, addr: T) -> Result<Vec<SeedPeer>, DnsSeedError> {
+        let name = addr.into_name()?;
+        loop {
+            let name_server = self.name_servers[self.current_name_server];
+            let result = match self.inner {
+                Inner::Secure(ref mut inner) => {
+                    inner
+                        .resolve_with_fallback(
+                            name.clone(),
+                            Resolver::connect_secure_tcp(name_server, default_trust_anchor()),
+                        )
+                        .await
+                },
+                Inner::Normal(ref mut inner) => {
+                    inner
+                        .resolve_with_fallback(name.clone(), Resolver::connect_tcp(name_server))
+                        .await
+                },
+                Inner::Https(ref mut inner) => return inner.resolve(name).await,
+            };
+
+            match result {
+                Ok(peers) => return Ok(peers),
+                Err(err) if self.current_name_server + 1 < self.name_servers.len() => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Failed to resolve '{}' using name server {}: {}. Trying the next name server",
+                        name,
+                        name_server,
+                        err
+                    );
+                    self.connect_next_name_server().await?;
+                },
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    async fn connect_next_name_server(&mut self) -> Result<(), DnsSeedError> {
+        let start = self.current_name_server + 1;
+        let cache = self.inner.cache();
         match self.inner {
-            Inner::Secure(ref mut inner) => inner.resolve(addr).await,
-            Inner::Normal(ref mut inner) => inner.resolve(addr).await,
+            Inner::Secure(_) => {
+                let (current_name_server, resolver) = connect_first(&self.name_servers, start, |name_server| {
+                    Resolver::connect_secure(name_server, default_trust_anchor())
+                })
+                .await?;
+                self.inner = Inner::Secure(resolver);
+                self.current_name_server = current_name_server;
+            },
+            Inner::Normal(_) => {
+                let (current_name_server, resolver) =
+                    connect_first(&self.name_servers, start, Resolver::connect).await?;
+                self.inner = Inner::Normal(resolver);
+                self.current_name_server = current_name_server;
+            },
+            Inner::Https(_) => unreachable!("DNS-over-HTTPS resolvers have a single name server"),
+        }
+        self.inner.set_cache(cache);
+        Ok(())
+    }
+}
+
+impl Inner {
+    fn cache(&self) -> Option<SeedPeerCache> {
+        match self {
+            Inner::Secure(inner) => inner.cache.clone(),
+            Inner::Normal(inner) => inner.cache.clone(),
+            Inner::Https(inner) => inner.cache.clone(),
+        }
+    }
+
+    fn set_cache(&mut self, cache: Option<SeedPeerCache>) {
+        match self {
+            Inner::Secure(inner) => inner.cache = cache,
+            Inner::Normal(inner) => inner.cache = cache,
+            Inner::Https(inner) => inner.cache = cache,
+        }
+    }
+}
+
+/// Connects to the first name server, starting at index `start`, for which `connect` succeeds. Returns the index of
+/// that name server and the connected resolver.
+async fn connect_first<C, F, Fut>(
+    name_servers: &[SocketAddr],
+    start: usize,
+    mut connect: F,
+) -> Result<(usize, Resolver<C>), DnsSeedError>
+where
+    F: FnMut(SocketAddr) -> Fut,
+    Fut: Future<Output = Result<Resolver<C>, DnsSeedError>>,
+{
+    let mut last_err = None;
+    for (i, name_server) in name_servers.iter().enumerate().skip(start) {
+        match connect(*name_server).await {
+            Ok(resolver) => return Ok((i, resolver)),
+            Err(err) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Failed to connect to name server {}: {}", name_server, err
+                );
+                last_err = Some(err);
+            },
         }
     }
+    Err(last_err.unwrap_or(DnsSeedError::NoNameServers))
 }
 
 /// Resolves DNS TXT records and parses them into [`SeedPeer`]s.
@@ -93,6 +270,14 @@ impl DnsSeedResolver {
 struct Resolver<C> {
     client: C,
     shutdown: Arc<Shutdown>,
+    cache: Option<SeedPeerCache>,
+}
+
+impl<C> Resolver<C> {
+    pub fn with_cache(mut self, enabled: bool) -> Self {
+        self.cache = if enabled { Some(SeedPeerCache::default()) } else { None };
+        self
+    }
 }
 
 impl Resolver<AsyncDnssecClient<UdpResponse>> {
@@ -108,6 +293,7 @@ impl Resolver<AsyncDnssecClient<UdpResponse>> {
         Ok(Self {
             client,
             shutdown: Arc::new(shutdown),
+            cache: None,
         })
     }
 }
@@ -122,6 +308,71 @@ impl Resolver<AsyncClient<UdpResponse>> {
         Ok(Self {
             client,
             shutdown: Arc::new(shutdown),
+            cache: None,
+        })
+    }
+}
+
+impl Resolver<AsyncDnssecClient<DnsMultiplexerSerialResponse>> {
+    pub async fn connect_secure_tcp(name_server: SocketAddr, trust_anchor: TrustAnchor) -> Result<Self, DnsSeedError> {
+        let shutdown = Shutdown::new();
+        let (stream, handle) = TcpClientStream::<AsyncIo02As03<TcpStream>>::new(name_server);
+        let multiplexer = DnsMultiplexer::new(stream, handle, None::<Arc<NoopMessageFinalizer>>);
+        let (client, background) = AsyncDnssecClient::builder(multiplexer)
+            .trust_anchor(trust_anchor)
+            .build()
+            .await?;
+        task::spawn(future::select(shutdown.to_signal(), background));
+
+        Ok(Self {
+            client,
+            shutdown: Arc::new(shutdown),
+            cache: None,
+        })
+    }
+}
+
+impl Resolver<AsyncClient<DnsMultiplexerSerialResponse>> {
+    pub async fn connect_tcp(name_server: SocketAddr) -> Result<Self, DnsSeedError> {
+        let shutdown = Shutdown::new();
+        let (stream, handle) = TcpClientStream::<AsyncIo02As03<TcpStream>>::new(name_server);
+        let (client, background) = AsyncClient::new(stream, handle, None).await?;
+        task::spawn(future::select(shutdown.to_signal(), background));
+
+        Ok(Self {
+            client,
+            shutdown: Arc::new(shutdown),
+            cache: None,
+        })
+    }
+}
+
+impl Resolver<AsyncClient<HttpsClientResponse>> {
+    pub async fn connect_https(name_server: SocketAddr, dns_name: String) -> Result<Self, DnsSeedError> {
+        let mut client_config = ClientConfig::new();
+        client_config
+            .root_store
+            .add_server_trust_anchors(&webpki_roots::TLS_SERVER_ROOTS);
+        Self::connect_https_with_client_config(name_server, dns_name, client_config).await
+    }
+
+    async fn connect_https_with_client_config(
+        name_server: SocketAddr,
+        dns_name: String,
+        mut client_config: ClientConfig,
+    ) -> Result<Self, DnsSeedError>
+    {
+        let shutdown = Shutdown::new();
+        // DoH is only specified over HTTP/2
+        client_config.alpn_protocols.push(b"h2".to_vec());
+        let stream = HttpsClientStreamBuilder::with_client_config(Arc::new(client_config)).build(name_server, dns_name);
+        let (client, background) = AsyncClient::connect(stream).await?;
+        task::spawn(future::select(shutdown.to_signal(), background));
+
+        Ok(Self {
+            client,
+            shutdown: Arc::new(shutdown),
+            cache: None,
         })
     }
 }
@@ -130,34 +381,129 @@ impl<C> Resolver<C>
 where C: DnsHandle
 {
     pub async fn resolve<T: IntoName>(&mut self, addr: T) -> Result<Vec<SeedPeer>, DnsSeedError> {
+        let name = addr.into_name()?;
+        if let Some(peers) = self.get_cached(&name) {
+            return Ok(peers);
+        }
+        let (peers, _, ttl) = self.lookup_seed_peers(name.clone()).await?;
+        self.insert_cached(name, &peers, ttl);
+        Ok(peers)
+    }
+
+    /// Resolves the seed peers for the given domain. If the response is truncated, the resolver returned by
+    /// `connect_fallback` (typically a TCP resolver) is used to query again and any additional seed peers are merged
+    /// into the result. If the fallback query fails, the seed peers from the truncated response are returned.
+    pub async fn resolve_with_fallback<T, F, D>(
+        &mut self,
+        addr: T,
+        connect_fallback: F,
+    ) -> Result<Vec<SeedPeer>, DnsSeedError>
+    where
+        T: IntoName,
+        F: Future<Output = Result<Resolver<D>, DnsSeedError>>,
+        D: DnsHandle,
+    {
+        let name = addr.into_name()?;
+        if let Some(peers) = self.get_cached(&name) {
+            return Ok(peers);
+        }
+        let (mut peers, is_truncated, mut ttl) = self.lookup_seed_peers(name.clone()).await?;
+        if is_truncated {
+            debug!(
+                target: LOG_TARGET,
+                "DNS response for '{}' was truncated ({} seed peer(s) received). Retrying over TCP",
+                name,
+                peers.len()
+            );
+            let fallback_result = match connect_fallback.await {
+                Ok(mut fallback) => fallback.lookup_seed_peers(name.clone()).await,
+                Err(err) => Err(err),
+            };
+            match fallback_result {
+                Ok((fallback_peers, _, fallback_ttl)) => {
+                    for peer in fallback_peers {
+                        if peers.iter().all(|p| p.public_key != peer.public_key) {
+                            peers.push(peer);
+                        }
+                    }
+                    ttl = ttl.min(fallback_ttl);
+                },
+                Err(err) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Failed to retry DNS query for '{}' over TCP: {}. Using the {} seed peer(s) from the \
+                         truncated response",
+                        name,
+                        err,
+                        peers.len()
+                    );
+                },
+            }
+        }
+
+        self.insert_cached(name, &peers, ttl);
+        Ok(peers)
+    }
+
+    fn get_cached(&self, name: &Name) -> Option<Vec<SeedPeer>> {
+        let peers = self.cache.as_ref()?.get(name)?;
+        trace!(
+            target: LOG_TARGET,
+            "Using {} cached seed peer(s) for '{}'",
+            peers.len(),
+            name
+        );
+        Some(peers)
+    }
+
+    fn insert_cached(&self, name: Name, peers: &[SeedPeer], ttl: Duration) {
+        if let Some(cache) = self.cache.as_ref() {
+            cache.insert(name, peers.to_vec(), ttl);
+        }
+    }
+
+    /// Returns the seed peers in the TXT records for `name`, whether the response was truncated and the lowest TTL of
+    /// the answers
+    async fn lookup_seed_peers(&mut self, name: Name) -> Result<(Vec<SeedPeer>, bool, Duration), DnsSeedError> {
         let mut query = Query::new();
         query
-            .set_name(addr.into_name()?)
+            .set_name(name)
             .set_query_class(DNSClass::IN)
             .set_query_type(RecordType::TXT);
 
         let response = self.client.lookup(query, Default::default()).await?;
 
-        let peers = response
+        let is_truncated = response.messages().any(|msg| msg.truncated());
+        let peers = parse_seed_peers(response.messages().flat_map(|msg| msg.answers()));
+        let ttl = response
             .messages()
             .flat_map(|msg| msg.answers())
-            .map(|answer| {
-                let data = answer.rdata();
-                let mut buf = Vec::new();
-                let mut decoder = BinEncoder::new(&mut buf);
-                data.emit(&mut decoder).unwrap();
-                buf
-            })
-            .filter_map(|txt| {
-                if txt.is_empty() {
-                    return None;
-                }
-                // Exclude the first length octet from the string result
-                let txt = String::from_utf8_lossy(&txt[1..]);
-                txt.parse().ok()
-            })
-            .collect();
+            .map(|answer| answer.ttl())
+            .min()
+            .unwrap_or(0);
 
-        Ok(peers)
+        Ok((peers, is_truncated, Duration::from_secs(u64::from(ttl))))
     }
 }
+
+/// Parses the TXT record answers of a DNS response into [`SeedPeer`]s, ignoring any records that are not valid seeds.
+fn parse_seed_peers<'a, I: IntoIterator<Item = &'a Record>>(answers: I) -> Vec<SeedPeer> {
+    answers
+        .into_iter()
+        .map(|answer| {
+            let data = answer.rdata();
+            let mut buf = Vec::new();
+            let mut decoder = BinEncoder::new(&mut buf);
+            data.emit(&mut decoder).unwrap();
+            buf
+        })
+        .filter_map(|txt| {
+            if txt.is_empty() {
+                return None;
+            }
+            // Exclude the first length octet from the string result
+            let txt = String::from_utf8_lossy(&txt[1..]);
+            txt.parse().ok()
+        })
+        .collect()
+}
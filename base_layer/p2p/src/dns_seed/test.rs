@@ -20,9 +20,18 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use super::Resolver;
+use super::{connect_first, parse_seed_peers, DnsSeedResolver, Resolver};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
 use tari_utilities::hex::Hex;
-use trust_dns_client::rr::{rdata, RData, Record, RecordType};
+use trust_dns_client::{
+    client::AsyncClient,
+    op::Message,
+    proto::{error::ProtoError, xfer::DnsMultiplexerSerialResponse},
+    rr::{rdata, RData, Record, RecordType},
+};
 
 // Ignore as this test requires network IO
 #[ignore]
@@ -91,22 +100,233 @@ async fn it_returns_peer_seeds() {
     assert_eq!(seeds[1].addresses.len(), 1);
 }
 
+#[tokio_macros::test]
+async fn it_retries_over_tcp_if_the_response_is_truncated() {
+    let seed1 = "06e98e9c5eb52bd504836edec1878eccf12eb9f26a5fe5ec0e279423156e657a::/ip4/127.0.0.1/tcp/8000";
+    let seed2 = "e856839057aac496b9e25f10821116d02b58f20129e9b9ba681b830568e47c4d::/ip4/127.0.0.1/tcp/8001";
+    // The UDP answer only has room for the first record
+    let mut udp_resolver = Resolver::connect_test_truncated(vec![create_txt_record(vec![seed1.into()])])
+        .await
+        .unwrap();
+    let tcp_resolver = Resolver::connect_test(vec![
+        create_txt_record(vec![seed1.into()]),
+        create_txt_record(vec![seed2.into()]),
+    ]);
+    let seeds = udp_resolver
+        .resolve_with_fallback("tari.com", async { tcp_resolver.await.map_err(Into::into) })
+        .await
+        .unwrap();
+    assert_eq!(seeds.len(), 2);
+    assert_eq!(
+        seeds[0].public_key.to_hex(),
+        "06e98e9c5eb52bd504836edec1878eccf12eb9f26a5fe5ec0e279423156e657a"
+    );
+    assert_eq!(
+        seeds[1].public_key.to_hex(),
+        "e856839057aac496b9e25f10821116d02b58f20129e9b9ba681b830568e47c4d"
+    );
+}
+
+#[tokio_macros::test]
+async fn it_returns_the_truncated_response_if_the_tcp_retry_fails() {
+    let mut resolver = Resolver::connect_test_truncated(vec![create_txt_record(vec![
+        "06e98e9c5eb52bd504836edec1878eccf12eb9f26a5fe5ec0e279423156e657a::/ip4/127.0.0.1/tcp/8000".into(),
+    ])])
+    .await
+    .unwrap();
+    let seeds = resolver
+        .resolve_with_fallback("tari.com", async {
+            Err::<Resolver<AsyncClient<DnsMultiplexerSerialResponse>>, _>(ProtoError::from("connection refused").into())
+        })
+        .await
+        .unwrap();
+    assert_eq!(seeds.len(), 1);
+    assert_eq!(
+        seeds[0].public_key.to_hex(),
+        "06e98e9c5eb52bd504836edec1878eccf12eb9f26a5fe5ec0e279423156e657a"
+    );
+}
+
+#[tokio_macros::test]
+async fn it_does_not_retry_over_tcp_if_the_response_is_complete() {
+    let mut resolver = Resolver::connect_test(vec![create_txt_record(vec![
+        "06e98e9c5eb52bd504836edec1878eccf12eb9f26a5fe5ec0e279423156e657a::/ip4/127.0.0.1/tcp/8000".into(),
+    ])])
+    .await
+    .unwrap();
+    let seeds = resolver
+        .resolve_with_fallback("tari.com", async {
+            panic!("TCP fallback should not be used");
+            #[allow(unreachable_code)]
+            Resolver::connect_test(vec![]).await.map_err(Into::into)
+        })
+        .await
+        .unwrap();
+    assert_eq!(seeds.len(), 1);
+}
+
+#[tokio_macros::test]
+async fn it_falls_back_to_the_next_name_server_if_one_is_unreachable() {
+    let unreachable = "127.0.0.1:5301".parse().unwrap();
+    let reachable = "127.0.0.1:5302".parse().unwrap();
+    let (index, mut resolver) = connect_first(&[unreachable, reachable], 0, |name_server| async move {
+        if name_server == unreachable {
+            return Err(ProtoError::from("name server unreachable").into());
+        }
+        Resolver::connect_test(vec![create_txt_record(vec![
+            "06e98e9c5eb52bd504836edec1878eccf12eb9f26a5fe5ec0e279423156e657a::/ip4/127.0.0.1/tcp/8000".into(),
+        ])])
+        .await
+        .map_err(Into::into)
+    })
+    .await
+    .unwrap();
+    assert_eq!(index, 1);
+    let seeds = resolver.resolve("tari.com").await.unwrap();
+    assert_eq!(seeds.len(), 1);
+    assert_eq!(
+        seeds[0].public_key.to_hex(),
+        "06e98e9c5eb52bd504836edec1878eccf12eb9f26a5fe5ec0e279423156e657a"
+    );
+}
+
+#[tokio_macros::test]
+async fn it_fails_over_to_the_next_name_server_if_a_query_times_out() {
+    // The first name server accepts the query but never responds
+    let unresponsive = mock::spawn_udp_name_server(None).await;
+    let responsive = mock::spawn_udp_name_server(Some(vec![create_txt_record(vec![
+        "06e98e9c5eb52bd504836edec1878eccf12eb9f26a5fe5ec0e279423156e657a::/ip4/127.0.0.1/tcp/8000".into(),
+    ])]))
+    .await;
+    let mut resolver = DnsSeedResolver::connect_with_fallback(vec![unresponsive, responsive])
+        .await
+        .unwrap();
+    assert_eq!(resolver.current_name_server, 0);
+    let seeds = resolver.resolve("tari.com").await.unwrap();
+    assert_eq!(resolver.current_name_server, 1);
+    assert_eq!(seeds.len(), 1);
+    assert_eq!(
+        seeds[0].public_key.to_hex(),
+        "06e98e9c5eb52bd504836edec1878eccf12eb9f26a5fe5ec0e279423156e657a"
+    );
+}
+
+#[tokio_macros::test]
+async fn it_errors_if_no_name_server_is_reachable() {
+    let result = connect_first(&["127.0.0.1:5301".parse().unwrap()], 0, |_| async {
+        Err::<Resolver<()>, _>(ProtoError::from("name server unreachable").into())
+    })
+    .await;
+    assert!(result.is_err());
+}
+
+#[tokio_macros::test]
+async fn it_returns_cached_seed_peers_within_the_ttl() {
+    let mut record = create_txt_record(vec![
+        "06e98e9c5eb52bd504836edec1878eccf12eb9f26a5fe5ec0e279423156e657a::/ip4/127.0.0.1/tcp/8000".into(),
+    ]);
+    record.set_ttl(60);
+    let num_requests = Arc::new(AtomicUsize::new(0));
+    let name_server = mock::spawn_counted_udp_name_server(Some(vec![record]), num_requests.clone()).await;
+    let mut resolver = DnsSeedResolver::connect_with_fallback(vec![name_server])
+        .await
+        .unwrap()
+        .with_cache(true);
+
+    let seeds = resolver.resolve("tari.com").await.unwrap();
+    assert_eq!(seeds.len(), 1);
+    let seeds = resolver.resolve("tari.com").await.unwrap();
+    assert_eq!(seeds.len(), 1);
+    assert_eq!(num_requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio_macros::test]
+async fn it_does_not_cache_seed_peers_with_a_zero_ttl() {
+    let num_requests = Arc::new(AtomicUsize::new(0));
+    let mut resolver = Resolver::connect_test_counted(
+        vec![create_txt_record(vec![
+            "06e98e9c5eb52bd504836edec1878eccf12eb9f26a5fe5ec0e279423156e657a::/ip4/127.0.0.1/tcp/8000".into(),
+        ])],
+        num_requests.clone(),
+    )
+    .await
+    .unwrap()
+    .with_cache(true);
+
+    resolver.resolve("tari.com").await.unwrap();
+    resolver.resolve("tari.com").await.unwrap();
+    assert_eq!(num_requests.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn it_parses_peer_seeds_from_a_dns_over_https_response() {
+    // The body of a DoH response is a DNS message in wire format (RFC 8484)
+    let mut msg = Message::new();
+    msg.add_answers(vec![
+        create_txt_record(vec![
+            "06e98e9c5eb52bd504836edec1878eccf12eb9f26a5fe5ec0e279423156e657a::/ip4/127.0.0.1/tcp/8000::/onion3/\
+             bsmuof2cn4y2ysz253gzsvg3s72fcgh4f3qcm3hdlxdtcwe6al2dicyd:1234"
+                .into(),
+        ]),
+        create_txt_record(vec!["v=spf1 include:_spf.spf.com ~all".into()]),
+    ]);
+    let body = msg.to_vec().unwrap();
+
+    let response = Message::from_vec(&body).unwrap();
+    let seeds = parse_seed_peers(response.answers());
+    assert_eq!(seeds.len(), 1);
+    assert_eq!(
+        seeds[0].public_key.to_hex(),
+        "06e98e9c5eb52bd504836edec1878eccf12eb9f26a5fe5ec0e279423156e657a"
+    );
+    assert_eq!(seeds[0].addresses.len(), 2);
+}
+
+#[tokio_macros::test]
+async fn it_resolves_peer_seeds_over_https() {
+    let (name_server, client_config) = mock::spawn_doh_server(vec![
+        create_txt_record(vec![
+            "06e98e9c5eb52bd504836edec1878eccf12eb9f26a5fe5ec0e279423156e657a::/ip4/127.0.0.1/tcp/8000".into(),
+        ]),
+        create_txt_record(vec!["v=spf1 include:_spf.spf.com ~all".into()]),
+    ])
+    .await;
+    let mut resolver =
+        Resolver::connect_https_with_client_config(name_server, mock::TEST_DNS_NAME.to_string(), client_config)
+            .await
+            .unwrap();
+    let seeds = resolver.resolve("tari.com").await.unwrap();
+    assert_eq!(seeds.len(), 1);
+    assert_eq!(
+        seeds[0].public_key.to_hex(),
+        "06e98e9c5eb52bd504836edec1878eccf12eb9f26a5fe5ec0e279423156e657a"
+    );
+}
+
 mod mock {
     use crate::dns_seed::Resolver;
     use futures::{channel::mpsc, future, Stream, StreamExt};
+    use rustls::{internal::pemfile, ClientConfig, NoClientAuth, ServerConfig};
     use std::{
         fmt,
         fmt::Display,
         net::SocketAddr,
         pin::Pin,
-        sync::Arc,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
         task::{Context, Poll},
     };
     use tari_shutdown::Shutdown;
-    use tokio::task;
+    use tokio::{
+        net::{TcpListener, UdpSocket},
+        task,
+    };
+    use tokio_rustls::TlsAcceptor;
     use trust_dns_client::{
         client::AsyncClient,
-        op::Message,
+        op::{Message, MessageType},
         proto::{
             error::ProtoError,
             xfer::{DnsClientStream, DnsMultiplexerSerialResponse, SerialMessage},
@@ -115,9 +335,100 @@ mod mock {
         rr::Record,
     };
 
+    // A CA certificate and a certificate for TEST_DNS_NAME that it signed, valid from 2020 to 2120
+    const TEST_CA_CERT: &[u8] = include_bytes!("test_certs/ca.pem");
+    const TEST_CERT: &[u8] = include_bytes!("test_certs/cert.pem");
+    const TEST_KEY: &[u8] = include_bytes!("test_certs/key.pem");
+    pub const TEST_DNS_NAME: &str = "dns.tari.test";
+
+    /// Spawns a DNS-over-HTTPS server on a local port that responds to every query with the given answers. Returns the
+    /// address of the server and a TLS client config that trusts its certificate.
+    pub async fn spawn_doh_server(answers: Vec<Record>) -> (SocketAddr, ClientConfig) {
+        let mut server_config = ServerConfig::new(NoClientAuth::new());
+        let certs = pemfile::certs(&mut &TEST_CERT[..]).unwrap();
+        let key = pemfile::pkcs8_private_keys(&mut &TEST_KEY[..]).unwrap().remove(0);
+        server_config.set_single_cert(certs, key).unwrap();
+        server_config.set_protocols(&[b"h2".to_vec()]);
+        let acceptor = TlsAcceptor::from(Arc::new(server_config));
+
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        task::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+                let answers = answers.clone();
+                task::spawn(async move {
+                    let socket = acceptor.accept(socket).await.unwrap();
+                    let mut connection = h2::server::handshake(socket).await.unwrap();
+                    while let Some(Ok((request, mut respond))) = connection.accept().await {
+                        let mut body = request.into_body();
+                        let mut buf = Vec::new();
+                        while let Some(chunk) = body.data().await {
+                            let chunk = chunk.unwrap();
+                            let _ = body.flow_control().release_capacity(chunk.len());
+                            buf.extend_from_slice(&chunk);
+                        }
+                        let req = Message::from_vec(&buf).unwrap();
+                        let mut msg = Message::new();
+                        msg.set_id(req.id()).add_answers(answers.iter().cloned());
+                        let msg = msg.to_vec().unwrap();
+
+                        let response = http::Response::builder()
+                            .status(http::StatusCode::OK)
+                            .header(http::header::CONTENT_TYPE, "application/dns-message")
+                            .header(http::header::CONTENT_LENGTH, msg.len())
+                            .body(())
+                            .unwrap();
+                        let mut send = respond.send_response(response, false).unwrap();
+                        send.send_data(msg.into(), true).unwrap();
+                    }
+                });
+            }
+        });
+
+        let mut client_config = ClientConfig::new();
+        client_config.root_store.add_pem_file(&mut &TEST_CA_CERT[..]).unwrap();
+        (addr, client_config)
+    }
+
+    /// Spawns a UDP name server on a local port. If `answers` is `None` the name server receives queries but never
+    /// responds to them, otherwise it responds to every query with the given answers.
+    pub async fn spawn_udp_name_server(answers: Option<Vec<Record>>) -> SocketAddr {
+        spawn_counted_udp_name_server(answers, Default::default()).await
+    }
+
+    pub async fn spawn_counted_udp_name_server(
+        answers: Option<Vec<Record>>,
+        num_requests: Arc<AtomicUsize>,
+    ) -> SocketAddr
+    {
+        let mut socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        task::spawn(async move {
+            let mut buf = [0u8; 512];
+            while let Ok((len, from)) = socket.recv_from(&mut buf).await {
+                num_requests.fetch_add(1, Ordering::SeqCst);
+                let answers = match answers {
+                    Some(ref answers) => answers,
+                    None => continue,
+                };
+                let req = Message::from_vec(&buf[..len]).unwrap();
+                let mut msg = Message::new();
+                msg.set_id(req.id())
+                    .set_message_type(MessageType::Response)
+                    .add_queries(req.queries().iter().cloned())
+                    .add_answers(answers.iter().cloned());
+                socket.send_to(&msg.to_vec().unwrap(), &from).await.unwrap();
+            }
+        });
+        addr
+    }
+
     pub struct MockStream {
         receiver: mpsc::UnboundedReceiver<Vec<u8>>,
         answers: Vec<Record>,
+        truncated: bool,
+        num_requests: Arc<AtomicUsize>,
     }
 
     impl DnsClientStream for MockStream {
@@ -138,9 +449,12 @@ mod mock {
                 Some(r) => r,
                 None => return Poll::Ready(None),
             };
+            self.num_requests.fetch_add(1, Ordering::SeqCst);
             let req = Message::from_vec(&req).unwrap();
             let mut msg = Message::new();
-            msg.set_id(req.id()).add_answers(self.answers.iter().cloned());
+            msg.set_id(req.id())
+                .set_truncated(self.truncated)
+                .add_answers(self.answers.iter().cloned());
             Poll::Ready(Some(Ok(SerialMessage::new(
                 msg.to_vec().unwrap(),
                 self.name_server_addr(),
@@ -150,8 +464,36 @@ mod mock {
 
     impl Resolver<AsyncClient<DnsMultiplexerSerialResponse>> {
         pub async fn connect_test(answers: Vec<Record>) -> Result<Self, ProtoError> {
+            Self::connect_mock(answers, false, Default::default()).await
+        }
+
+        /// Responds to queries with the given answers and the truncated (TC) flag set
+        pub async fn connect_test_truncated(answers: Vec<Record>) -> Result<Self, ProtoError> {
+            Self::connect_mock(answers, true, Default::default()).await
+        }
+
+        /// Responds to queries with the given answers, incrementing `num_requests` for each query received
+        pub async fn connect_test_counted(
+            answers: Vec<Record>,
+            num_requests: Arc<AtomicUsize>,
+        ) -> Result<Self, ProtoError>
+        {
+            Self::connect_mock(answers, false, num_requests).await
+        }
+
+        async fn connect_mock(
+            answers: Vec<Record>,
+            truncated: bool,
+            num_requests: Arc<AtomicUsize>,
+        ) -> Result<Self, ProtoError>
+        {
             let (tx, rx) = mpsc::unbounded();
-            let stream = future::ready(Ok(MockStream { receiver: rx, answers }));
+            let stream = future::ready(Ok(MockStream {
+                receiver: rx,
+                answers,
+                truncated,
+                num_requests,
+            }));
             let (client, background) = AsyncClient::new(stream, Box::new(StreamHandle::new(tx)), None).await?;
 
             let shutdown = Shutdown::new();
@@ -159,6 +501,7 @@ mod mock {
             Ok(Self {
                 client,
                 shutdown: Arc::new(shutdown),
+                cache: None,
             })
         }
     }
@@ -22,7 +22,7 @@
 
 use crate::{
     comms_connector::{InboundDomainConnector, PeerMessage, PubsubDomainConnector},
-    dns_seed::DnsSeedResolver,
+    dns_seed::{DnsSeedError, DnsSeedResolver},
     seed_peer::SeedPeer,
     transport::{TorConfig, TransportType},
 };
@@ -47,7 +47,7 @@ use tari_comms::{
     pipeline,
     pipeline::SinkService,
     protocol::{
-        messaging::{MessagingEventSender, MessagingProtocolExtension},
+        messaging::{MessagingConfig, MessagingEventSender, MessagingProtocolExtension},
         rpc::RpcServer,
     },
     tor,
@@ -119,6 +119,9 @@ pub struct CommsConfig {
     pub max_concurrent_inbound_tasks: usize,
     /// The size of the buffer (channel) which holds pending outbound message requests
     pub outbound_buffer_size: usize,
+    /// The maximum number of messages that may be queued for a single peer. Further messages to that peer fail with
+    /// `SendFailReason::QueueFull` until the queue drains.
+    pub max_queued_messages_per_peer: usize,
     /// Configuration for DHT
     pub dht: DhtConfig,
     /// The identity of this node on the network
@@ -142,10 +145,13 @@ pub struct CommsConfig {
     /// DNS seeds hosts. The DNS TXT records are queried from these hosts and the resulting peers added to the comms
     /// peer list.
     pub dns_seeds: Vec<String>,
-    /// DNS resolver to use for DNS seeds.
-    pub dns_seeds_name_server: SocketAddr,
+    /// DNS resolvers to use for DNS seeds, in order of preference. If a query fails, the next name server is used.
+    pub dns_seeds_name_servers: Vec<SocketAddr>,
     /// All DNS seed records must pass DNSSEC validation
     pub dns_seeds_use_dnssec: bool,
+    /// The TLS name of the DNS seed name server. If set, DNS seeds are resolved using DNS-over-HTTPS with the first
+    /// name server in `dns_seeds_name_servers` and `dns_seeds_use_dnssec` is ignored.
+    pub dns_seeds_doh_name: Option<String>,
 }
 
 /// Initialize Tari Comms configured for tests
@@ -381,10 +387,12 @@ where
         )
         .build();
 
-    comms = comms.add_protocol_extension(MessagingProtocolExtension::new(
-        messaging_events_sender,
-        messaging_pipeline,
-    ));
+    comms = comms.add_protocol_extension(
+        MessagingProtocolExtension::new(messaging_events_sender, messaging_pipeline).with_config(MessagingConfig {
+            max_queued_messages_per_peer: config.max_queued_messages_per_peer,
+            ..Default::default()
+        }),
+    );
 
     Ok((comms, dht))
 }
@@ -470,9 +478,10 @@ impl P2pInitializer {
 
     #[inline(always)]
     async fn try_resolve_dns_seeds(
-        resolver_addr: SocketAddr,
+        name_servers: &[SocketAddr],
         dns_seeds: &[String],
         use_dnssec: bool,
+        doh_name: Option<String>,
     ) -> Result<Vec<Peer>, ServiceInitializationError>
     {
         if dns_seeds.is_empty() {
@@ -482,19 +491,28 @@ impl P2pInitializer {
         debug!(target: LOG_TARGET, "Resolving DNS seeds...");
         let start = Instant::now();
 
-        let resolver = if use_dnssec {
+        let resolver = if let Some(doh_name) = doh_name {
+            let name_server = *name_servers.first().ok_or(DnsSeedError::NoNameServers)?;
             debug!(
                 target: LOG_TARGET,
-                "Using {} to resove DNS seeds. DNSSEC is enabled", resolver_addr
+                "Using {} ({}) to resove DNS seeds over HTTPS", name_server, doh_name
             );
-            DnsSeedResolver::connect_secure(resolver_addr).await?
+            DnsSeedResolver::connect_https(name_server, doh_name).await?
+        } else if use_dnssec {
+            debug!(
+                target: LOG_TARGET,
+                "Using {:?} to resove DNS seeds. DNSSEC is enabled", name_servers
+            );
+            DnsSeedResolver::connect_secure_with_fallback(name_servers.to_vec()).await?
         } else {
             debug!(
                 target: LOG_TARGET,
-                "Using {} to resove DNS seeds. DNSSEC is disabled", resolver_addr
+                "Using {:?} to resove DNS seeds. DNSSEC is disabled", name_servers
             );
-            DnsSeedResolver::connect(resolver_addr).await?
+            DnsSeedResolver::connect_with_fallback(name_servers.to_vec()).await?
         };
+        // Resolved seed peers are reused until the TTL of their DNS answer expires
+        let resolver = resolver.with_cache(true);
         let resolving = dns_seeds.iter().map(|addr| {
             let mut resolver = resolver.clone();
             async move { (resolver.resolve(addr.clone()).await, addr) }
@@ -551,9 +569,10 @@ impl ServiceInitializer for P2pInitializer {
             add_all_peers(&comms.peer_manager(), &comms.node_identity(), peers).await?;
 
             let peers = Self::try_resolve_dns_seeds(
-                config.dns_seeds_name_server,
+                &config.dns_seeds_name_servers,
                 &config.dns_seeds,
                 config.dns_seeds_use_dnssec,
+                config.dns_seeds_doh_name.clone(),
             )
             .await?;
             add_all_peers(&comms.peer_manager(), &comms.node_identity(), peers).await?;
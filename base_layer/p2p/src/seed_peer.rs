@@ -24,16 +24,46 @@ use anyhow::anyhow;
 use std::str::FromStr;
 use tari_comms::{
     multiaddr::Multiaddr,
-    peer_manager::{NodeId, Peer, PeerFeatures},
+    peer_manager::{NodeId, Peer, PeerFeatures, PeerFlags},
     types::CommsPublicKey,
 };
 use tari_utilities::hex::Hex;
 
-/// Parsed information from a DNS seed record
+/// The level of trust placed in a seed peer. Trusted seeds are preferred for initial sync and are given more leeway
+/// before being banned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedPeerTrust {
+    Normal,
+    Trusted,
+}
+
+impl Default for SeedPeerTrust {
+    fn default() -> Self {
+        SeedPeerTrust::Normal
+    }
+}
+
+impl FromStr for SeedPeerTrust {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "normal" => Ok(SeedPeerTrust::Normal),
+            "trusted" => Ok(SeedPeerTrust::Trusted),
+            _ => Err(anyhow!("Invalid seed peer trust level '{}'", s)),
+        }
+    }
+}
+
+/// Parsed information from a DNS seed record or seed peer config string.
+///
+/// The format is `[trust::]public_key::address[::address...]` where the optional trust level is `trusted` or `normal`
+/// (the default).
 #[derive(Debug, Clone)]
 pub struct SeedPeer {
     pub public_key: CommsPublicKey,
     pub addresses: Vec<Multiaddr>,
+    pub trust: SeedPeerTrust,
 }
 
 impl SeedPeer {
@@ -47,7 +77,14 @@ impl FromStr for SeedPeer {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split("::").map(|s| s.trim());
+        let mut parts = s.split("::").map(|s| s.trim()).peekable();
+        let trust = match parts.peek().and_then(|s| SeedPeerTrust::from_str(s).ok()) {
+            Some(trust) => {
+                parts.next();
+                trust
+            },
+            None => SeedPeerTrust::default(),
+        };
         let public_key = parts
             .next()
             .and_then(|s| CommsPublicKey::from_hex(&s).ok())
@@ -56,18 +93,26 @@ impl FromStr for SeedPeer {
         if addresses.is_empty() || addresses.iter().any(|a| a.is_empty()) {
             return Err(anyhow!("Empty or invalid address in seed peer string"));
         }
-        Ok(SeedPeer { public_key, addresses })
+        Ok(SeedPeer {
+            public_key,
+            addresses,
+            trust,
+        })
     }
 }
 
 impl From<SeedPeer> for Peer {
     fn from(seed: SeedPeer) -> Self {
         let node_id = seed.get_node_id();
+        let flags = match seed.trust {
+            SeedPeerTrust::Normal => PeerFlags::empty(),
+            SeedPeerTrust::Trusted => PeerFlags::TRUSTED_SEED,
+        };
         Self::new(
             seed.public_key,
             node_id,
             seed.addresses.into(),
-            Default::default(),
+            flags,
             PeerFeatures::COMMUNICATION_NODE,
             Default::default(),
             Default::default(),
@@ -90,6 +135,35 @@ mod test {
         );
         assert_eq!(seed.addresses.len(), 1);
         assert_eq!(seed.addresses[0].to_string(), "/ip4/127.0.0.1/tcp/8000");
+        assert_eq!(seed.trust, SeedPeerTrust::Normal);
+    }
+
+    #[test]
+    fn it_parses_trust_level() {
+        let sample =
+            "trusted::06e98e9c5eb52bd504836edec1878eccf12eb9f26a5fe5ec0e279423156e657a::/ip4/127.0.0.1/tcp/8000";
+        let seed = SeedPeer::from_str(sample).unwrap();
+        assert_eq!(seed.trust, SeedPeerTrust::Trusted);
+        assert_eq!(
+            seed.public_key.to_hex(),
+            "06e98e9c5eb52bd504836edec1878eccf12eb9f26a5fe5ec0e279423156e657a"
+        );
+        assert_eq!(seed.addresses.len(), 1);
+        let peer = Peer::from(seed);
+        assert!(peer.flags.contains(PeerFlags::TRUSTED_SEED));
+
+        let sample =
+            "Normal::06e98e9c5eb52bd504836edec1878eccf12eb9f26a5fe5ec0e279423156e657a::/ip4/127.0.0.1/tcp/8000";
+        let seed = SeedPeer::from_str(sample).unwrap();
+        assert_eq!(seed.trust, SeedPeerTrust::Normal);
+        let peer = Peer::from(seed);
+        assert!(!peer.flags.contains(PeerFlags::TRUSTED_SEED));
+    }
+
+    #[test]
+    fn it_errors_if_only_trust_level() {
+        SeedPeer::from_str("trusted").unwrap_err();
+        SeedPeer::from_str("trusted::/ip4/127.0.0.1/tcp/8000").unwrap_err();
     }
 
     #[test]
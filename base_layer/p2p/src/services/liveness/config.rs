@@ -28,6 +28,10 @@ use tari_comms::peer_manager::NodeId;
 pub struct LivenessConfig {
     /// The interval to send Ping messages, or None to disable periodic pinging (default: None (disabled))
     pub auto_ping_interval: Option<Duration>,
+    /// The maximum random delay added to each auto ping interval, so that nodes sharing the same interval do not ping
+    /// in lock-step. Each round is scheduled within `[auto_ping_interval, auto_ping_interval + auto_ping_jitter]`
+    /// (default: 0s (no jitter))
+    pub auto_ping_jitter: Duration,
     /// The length of time between querying peer manager for closest neighbours. (default: 2 minutes)
     pub refresh_neighbours_interval: Duration,
     /// The length of time between querying peer manager for random neighbours. (default: 2 hours)
@@ -36,16 +40,24 @@ pub struct LivenessConfig {
     pub num_peers_per_round: usize,
     /// Peers to include in every auto ping round (Default: <empty>)
     pub monitored_peers: Vec<NodeId>,
+    /// The number of most recent pong latencies kept for each peer to calculate latency statistics (Default: 20)
+    pub num_latency_history_samples: usize,
+    /// The maximum total size in bytes of the metadata values attached to a ping/pong message. Setting metadata that
+    /// exceeds this limit fails and received messages that exceed it are discarded (Default: 1024)
+    pub max_metadata_size: usize,
 }
 
 impl Default for LivenessConfig {
     fn default() -> Self {
         Self {
             auto_ping_interval: None,
+            auto_ping_jitter: Duration::from_secs(0),
             refresh_neighbours_interval: Duration::from_secs(2 * 60),
             refresh_random_pool_interval: Duration::from_secs(2 * 60 * 60),
             num_peers_per_round: 8,
             monitored_peers: Default::default(),
+            num_latency_history_samples: 20,
+            max_metadata_size: 1024,
         }
     }
 }
@@ -49,4 +49,6 @@ pub enum LivenessError {
     InvalidPingPongType,
     #[error("NodeId does not exist")]
     NodeIdDoesNotExist,
+    #[error("Metadata size of {size} bytes exceeds the maximum of {max_size} bytes")]
+    MetadataTooLarge { size: usize, max_size: usize },
 }
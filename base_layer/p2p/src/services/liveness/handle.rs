@@ -20,7 +20,10 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use super::{error::LivenessError, state::Metadata};
+use super::{
+    error::LivenessError,
+    state::{LatencyStats, Metadata},
+};
 use crate::proto::liveness::MetadataKey;
 use std::sync::Arc;
 use tari_comms::peer_manager::NodeId;
@@ -39,6 +42,8 @@ pub enum LivenessRequest {
     GetPongCount,
     /// Get average latency for node ID
     GetAvgLatency(NodeId),
+    /// Get min/max/median/p95 latency of the most recent pongs from node ID
+    GetPeerLatencyStats(NodeId),
     /// Set the metadata attached to each ping/pong message
     SetMetadataEntry(MetadataKey, Vec<u8>),
 }
@@ -52,6 +57,8 @@ pub enum LivenessResponse {
     Count(usize),
     /// Response for GetAvgLatency
     AvgLatency(Option<u32>),
+    /// Response for GetPeerLatencyStats
+    PeerLatencyStats(Option<LatencyStats>),
     /// The number of active neighbouring peers
     NumActiveNeighbours(usize),
 }
@@ -138,7 +145,21 @@ impl LivenessHandle {
         }
     }
 
-    /// Set metadata entry for the pong message
+    /// Retrieve latency statistics for the most recent pongs received from the given peer. None is returned if no
+    /// pongs have been received from the peer.
+    pub async fn get_peer_latency_stats(&mut self, node_id: NodeId) -> Result<Option<LatencyStats>, LivenessError> {
+        match self
+            .handle
+            .call(LivenessRequest::GetPeerLatencyStats(node_id))
+            .await??
+        {
+            LivenessResponse::PeerLatencyStats(stats) => Ok(stats),
+            _ => Err(LivenessError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Set a metadata entry that is attached to every ping and pong message sent by this node. An error is returned if
+    /// the total metadata size would exceed the configured maximum.
     pub async fn set_metadata_entry(&mut self, key: MetadataKey, value: Vec<u8>) -> Result<(), LivenessError> {
         match self
             .handle
@@ -135,6 +135,9 @@ impl LivenessMock {
             GetAvgLatency(_) => {
                 reply.send(Ok(LivenessResponse::AvgLatency(None))).unwrap();
             },
+            GetPeerLatencyStats(_) => {
+                reply.send(Ok(LivenessResponse::PeerLatencyStats(None))).unwrap();
+            },
             SetMetadataEntry(_, _) => {
                 reply.send(Ok(LivenessResponse::Ok)).unwrap();
             },
@@ -54,7 +54,7 @@ mod message;
 mod service;
 
 mod state;
-pub use state::Metadata;
+pub use state::{LatencyStats, Metadata};
 
 #[cfg(feature = "test-mocks")]
 pub mod mock;
@@ -143,7 +143,7 @@ impl ServiceInitializer for LivenessInitializer {
                 config,
                 receiver,
                 ping_stream,
-                LivenessState::new(),
+                LivenessState::new().with_num_latency_history_samples(config.num_latency_history_samples),
                 connectivity,
                 outbound_messages,
                 publisher,
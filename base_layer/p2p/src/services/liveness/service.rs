@@ -34,9 +34,15 @@ use crate::{
     services::liveness::{handle::LivenessEventSender, LivenessEvent, PingPongEvent},
     tari_message::TariMessageType,
 };
-use futures::{future::Either, pin_mut, stream::StreamExt, Stream};
+use futures::{
+    future::Either,
+    pin_mut,
+    stream::{self, StreamExt},
+    Stream,
+};
 use log::*;
-use std::{iter, sync::Arc, time::Instant};
+use rand::{rngs::OsRng, Rng};
+use std::{iter, sync::Arc, time::Duration};
 use tari_comms::{
     connectivity::{ConnectivityRequester, ConnectivitySelection},
     peer_manager::NodeId,
@@ -101,8 +107,8 @@ where
         pin_mut!(request_stream);
 
         let mut ping_tick = match self.config.auto_ping_interval {
-            Some(interval) => Either::Left(time::interval_at((Instant::now() + interval).into(), interval)),
-            None => Either::Right(futures::stream::iter(iter::empty())),
+            Some(interval) => Either::Left(ping_ticker(interval, self.config.auto_ping_jitter)),
+            None => Either::Right(stream::iter(iter::empty())),
         }
         .fuse();
 
@@ -150,7 +156,10 @@ where
         let node_id = source_peer.node_id;
         let public_key = source_peer.public_key;
 
-        match ping_pong_msg.kind().ok_or_else(|| LivenessError::InvalidPingPongType)? {
+        let kind = ping_pong_msg.kind().ok_or_else(|| LivenessError::InvalidPingPongType)?;
+        self.check_metadata_size(ping_pong_msg.metadata.values().map(Vec::len).sum())?;
+
+        match kind {
             PingPong::Ping => {
                 self.state.inc_pings_received();
                 self.send_pong(ping_pong_msg.nonce, public_key).await.unwrap();
@@ -186,6 +195,19 @@ where
                     maybe_latency.map(|ms| format!("Latency: {}ms", ms)).unwrap_or_default(),
                 );
 
+                if let Some(latency) = maybe_latency {
+                    if let Err(err) = self
+                        .connectivity
+                        .record_peer_latency(node_id.clone(), Duration::from_millis(u64::from(latency)))
+                        .await
+                    {
+                        warn!(
+                            target: LOG_TARGET,
+                            "Failed to record latency for peer '{}': {}", node_id, err
+                        );
+                    }
+                }
+
                 let pong_event = PingPongEvent::new(node_id, maybe_latency, ping_pong_msg.metadata.into());
                 self.publish_event(LivenessEvent::ReceivedPong(Box::new(pong_event)));
             },
@@ -235,13 +257,28 @@ where
                 let latency = self.state.get_avg_latency_ms(&node_id);
                 Ok(LivenessResponse::AvgLatency(latency))
             },
+            GetPeerLatencyStats(node_id) => {
+                let stats = self.state.get_latency_stats(&node_id);
+                Ok(LivenessResponse::PeerLatencyStats(stats))
+            },
             SetMetadataEntry(key, value) => {
+                let mut metadata = self.state.metadata().clone();
+                metadata.insert(key, value.clone());
+                self.check_metadata_size(metadata.size())?;
                 self.state.set_metadata_entry(key, value);
                 Ok(LivenessResponse::Ok)
             },
         }
     }
 
+    fn check_metadata_size(&self, size: usize) -> Result<(), LivenessError> {
+        let max_size = self.config.max_metadata_size;
+        if size > max_size {
+            return Err(LivenessError::MetadataTooLarge { size, max_size });
+        }
+        Ok(())
+    }
+
     async fn start_ping_round(&mut self) -> Result<(), LivenessError> {
         let selected_peers = self
             .connectivity
@@ -296,6 +333,23 @@ where
     }
 }
 
+/// Returns a stream that yields once per auto ping round. A new random jitter is chosen for every round so that the
+/// ping schedules of nodes configured with the same interval drift apart over time.
+fn ping_ticker(interval: Duration, jitter: Duration) -> impl Stream<Item = ()> + Unpin {
+    Box::pin(stream::unfold((), move |_| async move {
+        time::delay_for(jittered_interval(interval, jitter)).await;
+        Some(((), ()))
+    }))
+}
+
+/// Returns a random duration within `[interval, interval + jitter]`
+fn jittered_interval(interval: Duration, jitter: Duration) -> Duration {
+    if jitter == Duration::from_secs(0) {
+        return interval;
+    }
+    interval + jitter.mul_f64(OsRng.gen_range(0.0, 1.0))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -306,11 +360,13 @@ mod test {
     use futures::{
         channel::{mpsc, oneshot},
         stream,
+        SinkExt,
     };
+    use prost::Message;
     use rand::rngs::OsRng;
     use std::time::Duration;
     use tari_comms::{
-        message::MessageTag,
+        message::{EnvelopeBody, MessageTag},
         multiaddr::Multiaddr,
         peer_manager::{NodeId, Peer, PeerFeatures, PeerFlags},
         test_utils::mocks::create_connectivity_mock,
@@ -324,6 +380,32 @@ mod test {
     use tari_shutdown::Shutdown;
     use tokio::{sync::broadcast, task};
 
+    #[test]
+    fn jittered_interval_is_within_bounds() {
+        let interval = Duration::from_secs(30);
+        let jitter = Duration::from_secs(10);
+        for _ in 0..100 {
+            let delay = jittered_interval(interval, jitter);
+            assert!(delay >= interval && delay <= interval + jitter);
+        }
+        assert_eq!(jittered_interval(interval, Duration::from_secs(0)), interval);
+    }
+
+    #[tokio_macros::test_basic]
+    async fn ping_ticker_applies_jitter_every_tick() {
+        let interval = Duration::from_millis(20);
+        let jitter = Duration::from_millis(20);
+        let mut ticker = ping_ticker(interval, jitter);
+        let mut last_tick = std::time::Instant::now();
+        for _ in 0..5 {
+            ticker.next().await.unwrap();
+            let elapsed = last_tick.elapsed();
+            // Allow for scheduling delays on a busy test runner
+            assert!(elapsed >= interval && elapsed <= interval + jitter + Duration::from_millis(200));
+            last_tick = std::time::Instant::now();
+        }
+    }
+
     #[tokio_macros::test_basic]
     async fn get_ping_pong_count() {
         let mut state = LivenessState::new();
@@ -403,7 +485,7 @@ mod test {
                     let (_, rx) = oneshot::channel();
                     reply_tx
                         .send(SendMessageResponse::Queued(
-                            vec![MessageSendState::new(MessageTag::new(), rx)].into(),
+                            vec![MessageSendState::new(MessageTag::new(), NodeId::default(), rx)].into(),
                         ))
                         .unwrap();
                 },
@@ -435,6 +517,7 @@ mod test {
                 flags: Default::default(),
                 message_tag: MessageTag::new(),
                 expires: None,
+                timestamp: None,
             },
             authenticated_origin: None,
             source_peer,
@@ -535,4 +618,181 @@ mod test {
         let msg = subscriber.next().await;
         assert_eq!(msg.is_none(), true);
     }
+
+    fn spawn_liveness_service(
+        config: LivenessConfig,
+    ) -> (
+        LivenessHandle,
+        mpsc::Sender<DomainMessage<PingPongMessage>>,
+        mpsc::Receiver<DhtOutboundRequest>,
+        Shutdown,
+    ) {
+        let (connectivity, mock) = create_connectivity_mock();
+        mock.spawn();
+        let (outbound_tx, outbound_rx) = mpsc::channel(10);
+        let (ping_tx, ping_rx) = mpsc::channel(10);
+        let (sender_service, receiver) = reply_channel::unbounded();
+        let (publisher, _) = broadcast::channel(200);
+        let liveness_handle = LivenessHandle::new(sender_service, publisher.clone());
+
+        let shutdown = Shutdown::new();
+        let service = LivenessService::new(
+            config,
+            receiver,
+            ping_rx,
+            LivenessState::new(),
+            connectivity,
+            OutboundMessageRequester::new(outbound_tx),
+            publisher,
+            shutdown.to_signal(),
+        );
+        task::spawn(service.run());
+
+        (liveness_handle, ping_tx, outbound_rx, shutdown)
+    }
+
+    fn decode_ping_pong(request: DhtOutboundRequest) -> PingPongMessage {
+        let (_, body) = unwrap_oms_send_msg!(request);
+        let envelope_body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
+        envelope_body.decode_part::<PingPongMessage>(1).unwrap().unwrap()
+    }
+
+    #[tokio_macros::test]
+    async fn ping_metadata_round_trip() {
+        let (mut alice_handle, mut alice_ping_tx, mut alice_outbound_rx, _alice_shutdown) =
+            spawn_liveness_service(Default::default());
+        let (mut bob_handle, mut bob_ping_tx, mut bob_outbound_rx, _bob_shutdown) =
+            spawn_liveness_service(Default::default());
+        let mut alice_events = alice_handle.get_event_stream().fuse();
+        let mut bob_events = bob_handle.get_event_stream().fuse();
+
+        alice_handle
+            .set_metadata_entry(MetadataKey::ChainMetadata, b"alice-metadata".to_vec())
+            .await
+            .unwrap();
+        bob_handle
+            .set_metadata_entry(MetadataKey::ChainMetadata, b"bob-metadata".to_vec())
+            .await
+            .unwrap();
+
+        // Alice pings Bob
+        let (_, pk) = CommsPublicKey::random_keypair(&mut OsRng);
+        let bob_node_id = NodeId::from_key(&pk).unwrap();
+        task::spawn(async move { alice_handle.send_ping(bob_node_id).await.unwrap() });
+        let ping = decode_ping_pong(alice_outbound_rx.select_next_some().await);
+        bob_ping_tx.send(create_dummy_message(ping)).await.unwrap();
+
+        let event = time::timeout(Duration::from_secs(10), bob_events.select_next_some())
+            .await
+            .unwrap()
+            .unwrap();
+        match &*event {
+            LivenessEvent::ReceivedPing(event) => {
+                assert_eq!(
+                    event.metadata.get(MetadataKey::ChainMetadata).unwrap(),
+                    b"alice-metadata"
+                );
+            },
+            _ => panic!("Unexpected event"),
+        }
+
+        // Bob's pong is returned to Alice
+        let pong = decode_ping_pong(bob_outbound_rx.select_next_some().await);
+        alice_ping_tx.send(create_dummy_message(pong)).await.unwrap();
+
+        let event = time::timeout(Duration::from_secs(10), alice_events.select_next_some())
+            .await
+            .unwrap()
+            .unwrap();
+        match &*event {
+            LivenessEvent::ReceivedPong(event) => {
+                assert_eq!(event.metadata.get(MetadataKey::ChainMetadata).unwrap(), b"bob-metadata");
+            },
+            _ => panic!("Unexpected event"),
+        }
+    }
+
+    #[tokio_macros::test_basic]
+    async fn set_metadata_entry_rejects_oversized_metadata() {
+        let (mut liveness_handle, _ping_tx, _outbound_rx, _shutdown) = spawn_liveness_service(LivenessConfig {
+            max_metadata_size: 10,
+            ..Default::default()
+        });
+
+        let err = liveness_handle
+            .set_metadata_entry(MetadataKey::ChainMetadata, vec![0u8; 11])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LivenessError::MetadataTooLarge {
+            size: 11,
+            max_size: 10
+        }));
+
+        liveness_handle
+            .set_metadata_entry(MetadataKey::ChainMetadata, vec![0u8; 10])
+            .await
+            .unwrap();
+    }
+
+    #[tokio_macros::test_basic]
+    async fn get_peer_latency_stats() {
+        let mut state = LivenessState::new();
+
+        let (connectivity, mock) = create_connectivity_mock();
+        mock.spawn();
+        let (outbound_tx, _) = mpsc::channel(10);
+        let outbound_messaging = OutboundMessageRequester::new(outbound_tx);
+
+        // Several pongs from the same peer in response to inflight pings
+        let source_peer = create_dummy_message(()).source_peer;
+        let msgs = (0..5)
+            .map(|nonce| {
+                let mut msg = create_dummy_message(PingPongMessage::pong_with_metadata(nonce, Metadata::new()));
+                msg.source_peer = source_peer.clone();
+                state.add_inflight_ping(nonce, source_peer.node_id.clone());
+                msg
+            })
+            .collect::<Vec<_>>();
+        let pingpong_stream = stream::iter(msgs);
+
+        let (sender_service, receiver) = reply_channel::unbounded();
+        let (publisher, _) = broadcast::channel(200);
+        let mut liveness_handle = LivenessHandle::new(sender_service, publisher.clone());
+        let subscriber = publisher.subscribe();
+
+        let shutdown = Shutdown::new();
+        let service = LivenessService::new(
+            Default::default(),
+            receiver,
+            pingpong_stream,
+            state,
+            connectivity,
+            outbound_messaging,
+            publisher,
+            shutdown.to_signal(),
+        );
+        task::spawn(service.run());
+
+        let events = time::timeout(Duration::from_secs(10), subscriber.take(5).collect::<Vec<_>>())
+            .await
+            .unwrap();
+        for event in events {
+            match &*event.unwrap() {
+                LivenessEvent::ReceivedPong(_) => {},
+                _ => panic!("Unexpected event"),
+            }
+        }
+
+        let stats = liveness_handle
+            .get_peer_latency_stats(source_peer.node_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stats.min <= stats.median);
+        assert!(stats.median <= stats.p95);
+        assert!(stats.p95 <= stats.max);
+
+        let stats = liveness_handle.get_peer_latency_stats(NodeId::default()).await.unwrap();
+        assert!(stats.is_none());
+    }
 }
@@ -23,13 +23,14 @@
 use crate::proto::liveness::MetadataKey;
 use chrono::{NaiveDateTime, Utc};
 use std::{
-    collections::{hash_map::RandomState, HashMap},
+    collections::{hash_map::RandomState, HashMap, VecDeque},
     time::Duration,
 };
 use tari_comms::peer_manager::NodeId;
 
 const LATENCY_SAMPLE_WINDOW_SIZE: usize = 25;
 const MAX_INFLIGHT_TTL: Duration = Duration::from_secs(20);
+const DEFAULT_NUM_LATENCY_HISTORY_SAMPLES: usize = 20;
 
 /// Represents metadata in a ping/pong message.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -49,6 +50,11 @@ impl Metadata {
     pub fn get(&self, key: MetadataKey) -> Option<&Vec<u8>> {
         self.inner.get(&(key as i32))
     }
+
+    /// Returns the total size in bytes of all metadata values
+    pub fn size(&self) -> usize {
+        self.inner.values().map(Vec::len).sum()
+    }
 }
 
 impl From<HashMap<i32, Vec<u8>>> for Metadata {
@@ -64,10 +70,12 @@ impl From<Metadata> for HashMap<i32, Vec<u8>, RandomState> {
 }
 
 /// State for the LivenessService.
-#[derive(Default, Debug)]
+#[derive(Debug)]
 pub struct LivenessState {
     inflight_pings: HashMap<u64, (NodeId, NaiveDateTime)>,
     peer_latency: HashMap<NodeId, AverageLatency>,
+    peer_latency_history: HashMap<NodeId, LatencyHistory>,
+    num_latency_history_samples: usize,
 
     pings_received: usize,
     pongs_received: usize,
@@ -78,11 +86,34 @@ pub struct LivenessState {
     local_metadata: Metadata,
 }
 
+impl Default for LivenessState {
+    fn default() -> Self {
+        Self {
+            inflight_pings: Default::default(),
+            peer_latency: Default::default(),
+            peer_latency_history: Default::default(),
+            num_latency_history_samples: DEFAULT_NUM_LATENCY_HISTORY_SAMPLES,
+            pings_received: 0,
+            pongs_received: 0,
+            pings_sent: 0,
+            pongs_sent: 0,
+            num_active_peers: 0,
+            local_metadata: Default::default(),
+        }
+    }
+}
+
 impl LivenessState {
     pub fn new() -> Self {
         Default::default()
     }
 
+    /// Set the number of most recent pong latencies to keep for each peer when calculating latency statistics
+    pub fn with_num_latency_history_samples(mut self, num_samples: usize) -> Self {
+        self.num_latency_history_samples = num_samples;
+        self
+    }
+
     pub fn inc_pings_sent(&mut self) {
         self.pings_sent += 1;
     }
@@ -166,6 +197,12 @@ impl LivenessState {
     }
 
     fn add_latency_sample(&mut self, node_id: NodeId, duration: Duration) -> &mut AverageLatency {
+        let num_samples = self.num_latency_history_samples;
+        self.peer_latency_history
+            .entry(node_id.clone())
+            .or_insert_with(|| LatencyHistory::new(num_samples))
+            .add_sample(duration);
+
         let latency = self
             .peer_latency
             .entry(node_id)
@@ -178,6 +215,14 @@ impl LivenessState {
     pub fn get_avg_latency_ms(&self, node_id: &NodeId) -> Option<u32> {
         self.peer_latency.get(node_id).map(|latency| latency.calc_average())
     }
+
+    /// Returns latency statistics for the most recent pongs received from the given peer, or None if no pongs have been
+    /// received from the peer.
+    pub fn get_latency_stats(&self, node_id: &NodeId) -> Option<LatencyStats> {
+        self.peer_latency_history
+            .get(node_id)
+            .and_then(LatencyHistory::calc_stats)
+    }
 }
 
 /// Convert `chrono::Duration` to `std::time::Duration`
@@ -220,6 +265,65 @@ impl AverageLatency {
     }
 }
 
+/// Latency statistics in milliseconds for a peer
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LatencyStats {
+    pub min: u32,
+    pub max: u32,
+    pub median: u32,
+    pub p95: u32,
+}
+
+/// A ring buffer of the most recent latency samples (in milliseconds) for a peer. Once `max_samples` samples have been
+/// added, the oldest sample is discarded for each new sample.
+#[derive(Clone, Debug)]
+struct LatencyHistory {
+    samples: VecDeque<u32>,
+    max_samples: usize,
+}
+
+impl LatencyHistory {
+    fn new(max_samples: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(max_samples),
+            max_samples,
+        }
+    }
+
+    fn add_sample(&mut self, sample: Duration) {
+        if self.max_samples == 0 {
+            return;
+        }
+        if self.samples.len() == self.max_samples {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample.as_millis() as u32);
+    }
+
+    fn calc_stats(&self) -> Option<LatencyStats> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted = self.samples.iter().copied().collect::<Vec<_>>();
+        sorted.sort_unstable();
+        let len = sorted.len();
+        let median = if len % 2 == 0 {
+            ((u64::from(sorted[len / 2 - 1]) + u64::from(sorted[len / 2])) / 2) as u32
+        } else {
+            sorted[len / 2]
+        };
+        // Nearest-rank percentile
+        let p95_rank = (len * 95 + 99) / 100;
+
+        Some(LatencyStats {
+            min: sorted[0],
+            max: sorted[len - 1],
+            median,
+            p95: sorted[p95_rank - 1],
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -286,6 +390,39 @@ mod test {
         assert!(latency < 50);
     }
 
+    #[test]
+    fn latency_stats() {
+        let mut state = LivenessState::new();
+        let node_id = NodeId::default();
+        assert!(state.get_latency_stats(&node_id).is_none());
+
+        for ms in &[40, 10, 50, 30, 20] {
+            state.add_latency_sample(node_id.clone(), Duration::from_millis(*ms));
+        }
+        let stats = state.get_latency_stats(&node_id).unwrap();
+        assert_eq!(stats.min, 10);
+        assert_eq!(stats.max, 50);
+        assert_eq!(stats.median, 30);
+        assert_eq!(stats.p95, 50);
+
+        state.add_latency_sample(node_id.clone(), Duration::from_millis(60));
+        let stats = state.get_latency_stats(&node_id).unwrap();
+        assert_eq!(stats.median, 35);
+    }
+
+    #[test]
+    fn latency_history_is_bounded() {
+        let mut state = LivenessState::new().with_num_latency_history_samples(3);
+        let node_id = NodeId::default();
+        for ms in 1..=5 {
+            state.add_latency_sample(node_id.clone(), Duration::from_millis(ms));
+        }
+        let stats = state.get_latency_stats(&node_id).unwrap();
+        assert_eq!(stats.min, 3);
+        assert_eq!(stats.max, 5);
+        assert_eq!(stats.median, 4);
+    }
+
     #[test]
     fn set_metadata_entry() {
         let mut state = LivenessState::new();
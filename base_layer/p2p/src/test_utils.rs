@@ -71,6 +71,7 @@ pub fn make_dht_header(trace: MessageTag) -> DhtMessageHeader {
         flags: DhtMessageFlags::NONE,
         message_tag: trace,
         expires: None,
+        timestamp: None,
     }
 }
 
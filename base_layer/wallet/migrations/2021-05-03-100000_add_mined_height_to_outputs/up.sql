@@ -0,0 +1 @@
+ALTER TABLE outputs ADD COLUMN mined_height INTEGER NULL;
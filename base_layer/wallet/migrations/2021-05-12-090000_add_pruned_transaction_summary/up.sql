@@ -0,0 +1,7 @@
+CREATE TABLE pruned_transaction_summary (
+    id INTEGER PRIMARY KEY NOT NULL,
+    num_transactions INTEGER NOT NULL,
+    total_received INTEGER NOT NULL,
+    total_sent INTEGER NOT NULL,
+    total_fees INTEGER NOT NULL
+);
@@ -0,0 +1,2 @@
+ALTER TABLE completed_transactions
+    ADD COLUMN one_sided_payment_public_key BLOB NULL;
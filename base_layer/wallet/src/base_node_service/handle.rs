@@ -25,7 +25,7 @@ use futures::{stream::Fuse, StreamExt};
 use std::sync::Arc;
 use tari_comms::peer_manager::Peer;
 
-use tari_common_types::chain_metadata::ChainMetadata;
+use tari_common_types::{chain_metadata::ChainMetadata, types::BlockHash};
 use tari_service_framework::reply_channel::SenderService;
 use tokio::sync::broadcast;
 use tower::Service;
@@ -48,6 +48,11 @@ pub enum BaseNodeServiceResponse {
 pub enum BaseNodeEvent {
     BaseNodeStateChanged(BaseNodeState),
     BaseNodePeerSet(Box<Peer>),
+    /// The chain tip of the connected base node has moved to a new block
+    TipChanged {
+        height: u64,
+        hash: BlockHash,
+    },
 }
 
 /// The Base Node Service Handle is a struct that contains the interfaces used to communicate with a running
@@ -200,6 +200,16 @@ impl<T: WalletBackend + 'static> BaseNodeMonitor<T> {
                 "Base node latency: {} ms",
                 latency.unwrap_or_default().as_millis()
             );
+            if let Some(latency) = latency {
+                if let Err(err) = self
+                    .connectivity_manager
+                    .clone()
+                    .record_peer_latency(peer_node_id.clone(), latency)
+                    .await
+                {
+                    warn!(target: LOG_TARGET, "Failed to record base node latency: {}", err);
+                }
+            }
 
             let tip_info = client.get_tip_info().await?;
             let is_synced = tip_info.is_synced;
@@ -213,6 +223,18 @@ impl<T: WalletBackend + 'static> BaseNodeMonitor<T> {
 
             self.db.set_chain_metadata(chain_metadata.clone()).await?;
 
+            let tip_changed = self
+                .state
+                .read()
+                .await
+                .chain_metadata
+                .as_ref()
+                .map_or(true, |m| m.best_block() != chain_metadata.best_block());
+            let tip_event = BaseNodeEvent::TipChanged {
+                height: chain_metadata.height_of_longest_chain(),
+                hash: chain_metadata.best_block().clone(),
+            };
+
             self.map_state(move |state| BaseNodeState {
                 chain_metadata: Some(chain_metadata),
                 is_synced: Some(is_synced),
@@ -223,6 +245,10 @@ impl<T: WalletBackend + 'static> BaseNodeMonitor<T> {
             })
             .await;
 
+            if tip_changed {
+                self.publish_event(tip_event);
+            }
+
             self.sleep_or_shutdown().await?;
             self.check_if_base_node_changed(&peer_node_id).await?;
         }
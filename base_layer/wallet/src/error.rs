@@ -33,7 +33,7 @@ use serde_json::Error as SerdeJsonError;
 use tari_comms::{
     connectivity::ConnectivityError,
     multiaddr,
-    peer_manager::{node_id::NodeIdError, PeerManagerError},
+    peer_manager::{node_id::NodeIdError, NodeIdentityError, PeerManagerError},
 };
 use tari_comms_dht::store_forward::StoreAndForwardError;
 use tari_crypto::tari_utilities::{hex::HexError, ByteArrayError};
@@ -71,8 +71,12 @@ pub enum WalletError {
     BaseNodeServiceError(#[from] BaseNodeServiceError),
     #[error("Node ID error: `{0}`")]
     NodeIdError(#[from] NodeIdError),
+    #[error("Node identity error: `{0}`")]
+    NodeIdentityError(#[from] NodeIdentityError),
     #[error("Error performing wallet recovery: '{0}'")]
     WalletRecoveryError(String),
+    #[error("Error scanning for one-sided payments: '{0}'")]
+    OneSidedPaymentScanError(String),
     #[error("Shutdown Signal Received")]
     Shutdown,
 }
@@ -21,6 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::time::Duration;
+use tari_core::transactions::transaction::OutputFlags;
 
 #[derive(Clone, Debug)]
 pub struct OutputManagerServiceConfig {
@@ -28,6 +29,11 @@ pub struct OutputManagerServiceConfig {
     pub max_utxo_query_size: usize,
     pub prevent_fee_gt_amount: bool,
     pub peer_dial_retry_timeout: Duration,
+    /// The number of confirmations an unspent output needs before it is selected to fund a transaction. Outputs with
+    /// an unknown mined height are not held back.
+    pub min_confirmations_for_spend: u64,
+    /// Unspent outputs with any of these feature flags set are never selected to fund an ordinary send
+    pub coin_selection_excluded_output_flags: OutputFlags,
 }
 
 impl Default for OutputManagerServiceConfig {
@@ -37,6 +43,8 @@ impl Default for OutputManagerServiceConfig {
             max_utxo_query_size: 5000,
             prevent_fee_gt_amount: true,
             peer_dial_retry_timeout: Duration::from_secs(20),
+            min_confirmations_for_spend: 0,
+            coin_selection_excluded_output_flags: OutputFlags::empty(),
         }
     }
 }
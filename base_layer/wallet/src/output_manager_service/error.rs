@@ -22,9 +22,10 @@
 
 use crate::{base_node_service::error::BaseNodeServiceError, output_manager_service::storage::database::DbKey};
 use diesel::result::Error as DieselError;
-use tari_comms::{peer_manager::node_id::NodeIdError, protocol::rpc::RpcError};
+use tari_comms::{connectivity::ConnectivityError, peer_manager::node_id::NodeIdError, protocol::rpc::RpcError};
 use tari_comms_dht::outbound::DhtOutboundError;
 use tari_core::transactions::{
+    tari_amount::MicroTari,
     transaction::TransactionError,
     transaction_protocol::TransactionProtocolError,
     CoinbaseBuildError,
@@ -53,6 +54,8 @@ pub enum OutputManagerError {
     MnemonicError(#[from] MnemonicError),
     #[error("Key manager error: `{0}`")]
     KeyManagerError(#[from] KeyManagerError),
+    #[error("Key manager backend error: `{0}`")]
+    KeyManagerBackendError(String),
     #[error("Transaction error: `{0}`")]
     TransactionError(#[from] TransactionError),
     #[error("DHT outbound error: `{0}`")]
@@ -65,6 +68,10 @@ pub enum OutputManagerError {
     NotEnoughFunds,
     #[error("Funds are still pending. Unable to fulfil transaction right now.")]
     FundsPending,
+    #[error("At least two spendable outputs are required to sweep, found {0}")]
+    NotEnoughOutputsToSweep(usize),
+    #[error("Sweep would produce a dust output of {0}")]
+    SweepOutputIsDust(MicroTari),
     #[error("Output already exists")]
     DuplicateOutput,
     #[error("Error sending a message to the public API")]
@@ -101,6 +108,8 @@ pub enum OutputManagerError {
     RpcError(#[from] RpcError),
     #[error("Node ID error: `{0}`")]
     NodeIdError(#[from] NodeIdError),
+    #[error("Connectivity error: `{0}`")]
+    ConnectivityError(#[from] ConnectivityError),
 }
 
 #[derive(Debug, Error, PartialEq)]
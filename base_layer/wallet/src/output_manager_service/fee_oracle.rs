@@ -0,0 +1,102 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::cmp;
+use tari_core::{proto::base_node::FeeHistogramBucket, transactions::tari_amount::MicroTari};
+
+/// A source of fee-per-gram recommendations consulted by the Output Manager Service when the caller asks for an
+/// automatically selected fee.
+pub trait FeeOracle: Send + Sync + 'static {
+    /// Recommend a fee per gram for a transaction that should be mined promptly. `mempool_fee_histogram` is the fee
+    /// histogram of the base node's mempool ordered from the highest to the lowest fee per gram, or `None` if it could
+    /// not be fetched.
+    fn recommended_fee_per_gram(&self, mempool_fee_histogram: Option<&[FeeHistogramBucket]>) -> MicroTari;
+}
+
+/// The default fee oracle. It outbids the mempool transactions that would not fit into the next block, so that a new
+/// transaction is among the first `target_weight` grams of transactions selected by a miner.
+pub struct MempoolFeeOracle {
+    minimum_fee_per_gram: MicroTari,
+    target_weight: u64,
+}
+
+impl MempoolFeeOracle {
+    pub fn new(minimum_fee_per_gram: MicroTari, target_weight: u64) -> Self {
+        Self {
+            minimum_fee_per_gram,
+            target_weight,
+        }
+    }
+}
+
+impl FeeOracle for MempoolFeeOracle {
+    fn recommended_fee_per_gram(&self, mempool_fee_histogram: Option<&[FeeHistogramBucket]>) -> MicroTari {
+        let buckets = match mempool_fee_histogram {
+            Some(buckets) => buckets,
+            None => return self.minimum_fee_per_gram,
+        };
+
+        let mut cumulative_weight = 0u64;
+        for bucket in buckets {
+            cumulative_weight = cumulative_weight.saturating_add(bucket.weight);
+            if cumulative_weight >= self.target_weight {
+                return cmp::max(MicroTari::from(bucket.fee_per_gram + 1), self.minimum_fee_per_gram);
+            }
+        }
+
+        self.minimum_fee_per_gram
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FeeOracle, MempoolFeeOracle};
+    use tari_core::{proto::base_node::FeeHistogramBucket, transactions::tari_amount::MicroTari};
+
+    fn bucket(fee_per_gram: u64, weight: u64) -> FeeHistogramBucket {
+        FeeHistogramBucket {
+            fee_per_gram,
+            transaction_count: 1,
+            weight,
+        }
+    }
+
+    #[test]
+    fn it_falls_back_to_the_minimum_fee() {
+        let oracle = MempoolFeeOracle::new(MicroTari::from(25), 100);
+        assert_eq!(oracle.recommended_fee_per_gram(None), MicroTari::from(25));
+        assert_eq!(oracle.recommended_fee_per_gram(Some(&[])), MicroTari::from(25));
+        // The whole mempool fits into the next block
+        let buckets = vec![bucket(100, 40), bucket(50, 40)];
+        assert_eq!(oracle.recommended_fee_per_gram(Some(&buckets)), MicroTari::from(25));
+        // A congested mempool paying less than the minimum
+        let buckets = vec![bucket(10, 200)];
+        assert_eq!(oracle.recommended_fee_per_gram(Some(&buckets)), MicroTari::from(25));
+    }
+
+    #[test]
+    fn it_outbids_the_transactions_outside_the_next_block() {
+        let oracle = MempoolFeeOracle::new(MicroTari::from(25), 100);
+        let buckets = vec![bucket(200, 30), bucket(120, 50), bucket(80, 60), bucket(30, 500)];
+        assert_eq!(oracle.recommended_fee_per_gram(Some(&buckets)), MicroTari::from(81));
+    }
+}
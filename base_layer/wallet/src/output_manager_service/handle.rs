@@ -23,6 +23,7 @@
 use crate::{
     output_manager_service::{
         error::OutputManagerError,
+        one_sided::OneSidedPaymentAddress,
         protocols::txo_validation_protocol::TxoValidationType,
         service::Balance,
         storage::database::PendingTransactionOutputs,
@@ -38,7 +39,7 @@ use tari_core::transactions::{
     tari_amount::MicroTari,
     transaction::{Transaction, TransactionInput, TransactionOutput, UnblindedOutput},
     transaction_protocol::sender::TransactionSenderMessage,
-    types::PublicKey,
+    types::{Commitment, PublicKey},
     ReceiverTransactionProtocol,
     SenderTransactionProtocol,
 };
@@ -53,9 +54,10 @@ pub enum OutputManagerRequest {
     GetRecipientTransaction(TransactionSenderMessage),
     GetCoinbaseTransaction((u64, MicroTari, MicroTari, u64)),
     ConfirmPendingTransaction(u64),
-    ConfirmTransaction((u64, Vec<TransactionInput>, Vec<TransactionOutput>)),
+    ConfirmTransaction((u64, Vec<TransactionInput>, Vec<TransactionOutput>, Option<u64>)),
     PrepareToSendTransaction((MicroTari, MicroTari, Option<u64>, String)),
     CreatePayToSelfTransaction((MicroTari, MicroTari, Option<u64>, String)),
+    CreateOneSidedTransaction((MicroTari, MicroTari, Option<u64>, String, Box<OneSidedPaymentAddress>)),
     CancelTransaction(u64),
     TimeoutTransactions(Duration),
     GetPendingTransactions,
@@ -66,11 +68,15 @@ pub enum OutputManagerRequest {
     SetBaseNodePublicKey(CommsPublicKey),
     ValidateUtxos(TxoValidationType, ValidationRetryStrategy),
     CreateCoinSplit((MicroTari, usize, MicroTari, Option<u64>)),
+    CreateSweepTransaction((MicroTari, usize, Option<u64>)),
     ApplyEncryption(Box<Aes256Gcm>),
     RemoveEncryption,
     GetPublicRewindKeys,
     FeeEstimate((MicroTari, MicroTari, u64, u64)),
+    GetRecommendedFeePerGram,
     RewindOutputs(Vec<TransactionOutput>),
+    GetOneSidedPaymentAddress,
+    ScanForOneSidedOutputs(Vec<TransactionOutput>),
 }
 
 impl fmt::Display for OutputManagerRequest {
@@ -84,6 +90,7 @@ impl fmt::Display for OutputManagerRequest {
             ConfirmPendingTransaction(v) => write!(f, "ConfirmPendingTransaction ({})", v),
             PrepareToSendTransaction((_, _, _, msg)) => write!(f, "PrepareToSendTransaction ({})", msg),
             CreatePayToSelfTransaction((_, _, _, msg)) => write!(f, "CreatePayToSelfTransaction ({})", msg),
+            CreateOneSidedTransaction((_, _, _, msg, _)) => write!(f, "CreateOneSidedTransaction ({})", msg),
             CancelTransaction(v) => write!(f, "CancelTransaction ({})", v),
             TimeoutTransactions(d) => write!(f, "TimeoutTransactions ({}s)", d.as_secs()),
             GetPendingTransactions => write!(f, "GetPendingTransactions"),
@@ -94,12 +101,16 @@ impl fmt::Display for OutputManagerRequest {
             SetBaseNodePublicKey(k) => write!(f, "SetBaseNodePublicKey ({})", k),
             ValidateUtxos(validation_type, retry) => write!(f, "{} ({:?})", validation_type, retry),
             CreateCoinSplit(v) => write!(f, "CreateCoinSplit ({})", v.0),
+            CreateSweepTransaction(v) => write!(f, "CreateSweepTransaction ({})", v.1),
             ApplyEncryption(_) => write!(f, "ApplyEncryption"),
             RemoveEncryption => write!(f, "RemoveEncryption"),
             GetCoinbaseTransaction(_) => write!(f, "GetCoinbaseTransaction"),
             GetPublicRewindKeys => write!(f, "GetPublicRewindKeys"),
             FeeEstimate(_) => write!(f, "FeeEstimate"),
+            GetRecommendedFeePerGram => write!(f, "GetRecommendedFeePerGram"),
             RewindOutputs(_) => write!(f, "RewindAndImportOutputs"),
+            GetOneSidedPaymentAddress => write!(f, "GetOneSidedPaymentAddress"),
+            ScanForOneSidedOutputs(_) => write!(f, "ScanForOneSidedOutputs"),
         }
     }
 }
@@ -114,6 +125,7 @@ pub enum OutputManagerResponse {
     OutputConfirmed,
     PendingTransactionConfirmed,
     PayToSelfTransaction((TxId, MicroTari, Transaction)),
+    OneSidedTransaction((TxId, MicroTari, Transaction)),
     TransactionConfirmed,
     TransactionToSend(SenderTransactionProtocol),
     TransactionCancelled,
@@ -129,7 +141,9 @@ pub enum OutputManagerResponse {
     EncryptionApplied,
     EncryptionRemoved,
     PublicRewindKeys(Box<PublicRewindKeys>),
+    OneSidedPaymentAddress(Box<OneSidedPaymentAddress>),
     FeeEstimate(MicroTari),
+    RecommendedFeePerGram(MicroTari),
     RewindOutputs(Vec<UnblindedOutput>),
 }
 
@@ -144,6 +158,16 @@ pub enum OutputManagerEvent {
     TxoValidationFailure(u64, TxoValidationType),
     TxoValidationAborted(u64, TxoValidationType),
     TxoValidationDelayed(u64, TxoValidationType),
+    /// Published after each batch of outputs has been validated by the base node
+    TxoValidationProgress {
+        operation_id: u64,
+        validation_type: TxoValidationType,
+        completed: u64,
+        total: u64,
+    },
+    /// An output the wallet considered spent was found unspent on-chain (e.g. after a reorg) and has been returned to
+    /// the unspent set
+    OutputReorged(Commitment),
     Error(String),
 }
 
@@ -275,6 +299,18 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Get the fee per gram recommended by the service's fee oracle
+    pub async fn get_recommended_fee_per_gram(&mut self) -> Result<MicroTari, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetRecommendedFeePerGram)
+            .await??
+        {
+            OutputManagerResponse::RecommendedFeePerGram(fee_per_gram) => Ok(fee_per_gram),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn confirm_pending_transaction(&mut self, tx_id: u64) -> Result<(), OutputManagerError> {
         match self
             .handle
@@ -286,11 +322,13 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Confirm that a transaction has been mined. `mined_height` is the height of the block it was mined in, if known.
     pub async fn confirm_transaction(
         &mut self,
         tx_id: u64,
         spent_outputs: Vec<TransactionInput>,
         received_outputs: Vec<TransactionOutput>,
+        mined_height: Option<u64>,
     ) -> Result<(), OutputManagerError>
     {
         match self
@@ -299,6 +337,7 @@ impl OutputManagerHandle {
                 tx_id,
                 spent_outputs,
                 received_outputs,
+                mined_height,
             )))
             .await??
         {
@@ -426,6 +465,29 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Create a transaction consolidating up to `max_inputs` of the smallest spendable outputs into a single output.
+    /// Returns (tx_id, tx, fee, utxos_total_value).
+    pub async fn create_sweep_transaction(
+        &mut self,
+        fee_per_gram: MicroTari,
+        max_inputs: usize,
+        lock_height: Option<u64>,
+    ) -> Result<(u64, Transaction, MicroTari, MicroTari), OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateSweepTransaction((
+                fee_per_gram,
+                max_inputs,
+                lock_height,
+            )))
+            .await??
+        {
+            OutputManagerResponse::Transaction(ct) => Ok(ct),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn apply_encryption(&mut self, cipher: Aes256Gcm) -> Result<(), OutputManagerError> {
         match self
             .handle
@@ -477,4 +539,62 @@ impl OutputManagerHandle {
             _ => Err(OutputManagerError::UnexpectedApiResponse),
         }
     }
+
+    /// Create a completed transaction paying `amount` to a one-sided payment address. The output's range proof can be
+    /// rewound by the owner of the address, so that the recipient can discover it without taking part in the
+    /// transaction. The spending key of the output is chosen by this wallet, so it can be spent by either party until
+    /// the recipient spends it. Returns (tx_id, fee, transaction).
+    pub async fn create_one_sided_transaction(
+        &mut self,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+        recipient_address: OneSidedPaymentAddress,
+    ) -> Result<(TxId, MicroTari, Transaction), OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateOneSidedTransaction((
+                amount,
+                fee_per_gram,
+                lock_height,
+                message,
+                Box::new(recipient_address),
+            )))
+            .await??
+        {
+            OutputManagerResponse::OneSidedTransaction(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Return the address that senders pay one-sided payments to this wallet with
+    pub async fn get_one_sided_payment_address(&mut self) -> Result<OneSidedPaymentAddress, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetOneSidedPaymentAddress)
+            .await??
+        {
+            OutputManagerResponse::OneSidedPaymentAddress(address) => Ok(*address),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Rewind the provided outputs that were paid to this wallet's one-sided payment address, returning the outputs
+    /// that could be rewound. The outputs are not added to the wallet.
+    pub async fn scan_for_one_sided_outputs(
+        &mut self,
+        outputs: Vec<TransactionOutput>,
+    ) -> Result<Vec<UnblindedOutput>, OutputManagerError>
+    {
+        match self
+            .handle
+            .call(OutputManagerRequest::ScanForOneSidedOutputs(outputs))
+            .await??
+        {
+            OutputManagerResponse::RewindOutputs(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
 }
@@ -0,0 +1,230 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{output_manager_service::error::OutputManagerError, types::KeyDigest};
+use digest::Digest;
+use tari_core::transactions::types::{CommitmentFactory, PrivateKey, PublicKey, Signature};
+use tari_crypto::{
+    commitment::HomomorphicCommitmentFactory,
+    keys::{DiffieHellmanSharedSecret, PublicKey as PublicKeyTrait},
+    tari_utilities::ByteArray,
+};
+use tari_key_manager::{
+    key_manager::KeyManager,
+    mnemonic::{from_secret_key, MnemonicLanguage},
+};
+
+const BLINDING_FACTOR_LABEL: &[u8] = b"key_manager_blinding_factor";
+const BRANCH_MASTER_KEY_LABEL: &[u8] = b"key_manager_branch_master_key";
+
+/// The source of the keys that the Output Manager Service spends and signs with, so that an external signer such as a
+/// hardware wallet can take the place of the default software key manager. The backend's keys never leave it: the
+/// service only learns their public keys and the results of operations performed with them.
+///
+/// Keys are identified by a branch and an index within it. Backends must keep the key chains of different branches
+/// independent, because the keys of some branches are published, such as the key of the one-sided payment address.
+/// The [SoftwareKeyManager] only shares keys between the legacy branches it is configured with.
+///
+/// Only the receiving side signs through the backend. A sender's partial signature is made with the excess of all of
+/// its inputs, change outputs and offset, which is not a single key of the backend, so transactions are sent with the
+/// spending keys returned by [KeyManagerBackend::spending_key].
+pub trait KeyManagerBackend: Send + Sync + 'static {
+    /// Return the public key of the key at `index` of the key chain identified by `branch`
+    fn public_key(&self, branch: &str, index: u64) -> Result<PublicKey, OutputManagerError>;
+
+    /// Return the Diffie-Hellman shared secret of the key at `index` of the key chain identified by `branch` and
+    /// `public_key`
+    fn diffie_hellman(&self, branch: &str, index: u64, public_key: &PublicKey)
+        -> Result<PublicKey, OutputManagerError>;
+
+    /// Return the spending key of the output at `index` of the key chain identified by `branch`. Mimblewimble outputs
+    /// are spent with knowledge of their blinding factor, so the wallet has to hold it. By default it is derived from
+    /// the backend's key through a Diffie-Hellman exchange with the value generator of the commitments, so that a
+    /// backend that never reveals its keys can still provide spending keys.
+    fn spending_key(&self, branch: &str, index: u64) -> Result<PrivateKey, OutputManagerError> {
+        let shared_secret = self.diffie_hellman(branch, index, &value_generator(&CommitmentFactory::default()))?;
+        let hash = KeyDigest::new()
+            .chain(BLINDING_FACTOR_LABEL)
+            .chain(shared_secret.as_bytes())
+            .result();
+        Ok(PrivateKey::from_bytes(hash.as_slice()).expect("A 32 byte hash is always a valid private key"))
+    }
+
+    /// Sign `challenge` with the spending key at `index` of the key chain identified by `branch` and the given nonce.
+    /// This makes the partial signature of a receiver for the output it receives at that key.
+    fn sign(
+        &self,
+        branch: &str,
+        index: u64,
+        nonce: &PrivateKey,
+        challenge: &[u8],
+    ) -> Result<Signature, OutputManagerError>;
+
+    /// Return the seed words that back up the master key. Backends that never reveal their master key return an
+    /// error.
+    fn seed_words(&self) -> Result<Vec<String>, OutputManagerError>;
+}
+
+/// The generator `H` that commitments multiply their value by
+pub fn value_generator(factory: &CommitmentFactory) -> PublicKey {
+    factory.commit_value(&PrivateKey::default(), 1).as_public_key().clone()
+}
+
+/// The default key manager backend, which derives keys from a master key held in memory. Its spending keys are the
+/// derived keys themselves, so that wallets recovered from their seed words find the outputs they already own.
+///
+/// Each branch derives its keys from its own master key, which is a hash of the branch label and the wallet's master
+/// key. The keys of legacy branches are derived from the wallet's master key directly, as they were before branches
+/// were separated.
+pub struct SoftwareKeyManager {
+    master_key: PrivateKey,
+    legacy_branches: Vec<String>,
+}
+
+impl SoftwareKeyManager {
+    pub fn new(master_key: PrivateKey) -> Self {
+        Self {
+            master_key,
+            legacy_branches: Vec::new(),
+        }
+    }
+
+    /// Derive the keys of the given branches from the wallet's master key and the key index alone. The key manager
+    /// used to ignore the branch, so these keys are shared between the branches.
+    pub fn with_legacy_branches<I, S>(mut self, branches: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.legacy_branches = branches.into_iter().map(Into::into).collect();
+        self
+    }
+
+    fn branch_master_key(&self, branch: &str) -> PrivateKey {
+        if self.legacy_branches.iter().any(|b| b == branch) {
+            return self.master_key.clone();
+        }
+        let hash = KeyDigest::new()
+            .chain(BRANCH_MASTER_KEY_LABEL)
+            .chain(branch.as_bytes())
+            .chain(self.master_key.as_bytes())
+            .result();
+        PrivateKey::from_bytes(hash.as_slice()).expect("A 32 byte hash is always a valid private key")
+    }
+
+    fn derive_key(&self, branch: &str, index: u64) -> Result<PrivateKey, OutputManagerError> {
+        let km = KeyManager::<PrivateKey, KeyDigest>::from(self.branch_master_key(branch), branch.to_string(), 0);
+        Ok(km.derive_key(index)?.k)
+    }
+}
+
+impl KeyManagerBackend for SoftwareKeyManager {
+    fn public_key(&self, branch: &str, index: u64) -> Result<PublicKey, OutputManagerError> {
+        Ok(PublicKey::from_secret_key(&self.derive_key(branch, index)?))
+    }
+
+    fn diffie_hellman(
+        &self,
+        branch: &str,
+        index: u64,
+        public_key: &PublicKey,
+    ) -> Result<PublicKey, OutputManagerError>
+    {
+        Ok(PublicKey::shared_secret(&self.derive_key(branch, index)?, public_key))
+    }
+
+    fn spending_key(&self, branch: &str, index: u64) -> Result<PrivateKey, OutputManagerError> {
+        self.derive_key(branch, index)
+    }
+
+    fn sign(
+        &self,
+        branch: &str,
+        index: u64,
+        nonce: &PrivateKey,
+        challenge: &[u8],
+    ) -> Result<Signature, OutputManagerError>
+    {
+        Signature::sign(self.derive_key(branch, index)?, nonce.clone(), challenge)
+            .map_err(|e| OutputManagerError::KeyManagerBackendError(e.to_string()))
+    }
+
+    fn seed_words(&self) -> Result<Vec<String>, OutputManagerError> {
+        Ok(from_secret_key(&self.master_key, &MnemonicLanguage::English)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{value_generator, KeyManagerBackend, SoftwareKeyManager};
+    use crate::types::KeyDigest;
+    use digest::Digest;
+    use rand::rngs::OsRng;
+    use tari_core::transactions::types::{CommitmentFactory, PrivateKey, PublicKey};
+    use tari_crypto::{
+        common::Blake256,
+        keys::{DiffieHellmanSharedSecret, PublicKey as PublicKeyTrait, SecretKey},
+    };
+    use tari_key_manager::key_manager::KeyManager;
+
+    #[test]
+    fn keys_are_only_used_through_the_backend() {
+        let key_manager = SoftwareKeyManager::new(PrivateKey::random(&mut OsRng));
+        let public_key = key_manager.public_key("branch", 1).unwrap();
+        assert_ne!(public_key, key_manager.public_key("branch", 2).unwrap());
+        // Each branch has its own key chain
+        assert_ne!(public_key, key_manager.public_key("other", 1).unwrap());
+        assert_ne!(public_key, key_manager.public_key("", 1).unwrap());
+
+        let (secret, other_public_key) = PublicKey::random_keypair(&mut OsRng);
+        assert_eq!(
+            key_manager.diffie_hellman("branch", 1, &other_public_key).unwrap(),
+            PublicKey::shared_secret(&secret, &public_key)
+        );
+
+        let challenge = Blake256::digest(b"message");
+        let nonce = PrivateKey::random(&mut OsRng);
+        let signature = key_manager.sign("branch", 1, &nonce, &challenge).unwrap();
+        assert!(signature.verify_challenge(&public_key, &challenge));
+
+        assert_eq!(
+            PublicKey::from_secret_key(&key_manager.spending_key("branch", 1).unwrap()),
+            public_key
+        );
+        assert_ne!(value_generator(&CommitmentFactory::default()), PublicKey::default());
+    }
+
+    #[test]
+    fn software_spending_keys_match_the_key_manager() {
+        // Wallets recovered from seed words must derive the same spending and rewind keys as before the key manager
+        // backend existed
+        let master_key = PrivateKey::random(&mut OsRng);
+        let key_manager = SoftwareKeyManager::new(master_key.clone()).with_legacy_branches(vec!["", "coinbase"]);
+        let km = KeyManager::<PrivateKey, KeyDigest>::from(master_key, "".to_string(), 0);
+        assert_eq!(key_manager.spending_key("", 5).unwrap(), km.derive_key(5).unwrap().k);
+        assert_eq!(
+            key_manager.spending_key("coinbase", 5).unwrap(),
+            km.derive_key(5).unwrap().k
+        );
+        assert_ne!(key_manager.spending_key("branch", 5).unwrap(), km.derive_key(5).unwrap().k);
+    }
+}
@@ -24,7 +24,9 @@ use crate::{
     base_node_service::handle::BaseNodeServiceHandle,
     output_manager_service::{
         config::OutputManagerServiceConfig,
+        fee_oracle::FeeOracle,
         handle::OutputManagerHandle,
+        key_manager_backend::KeyManagerBackend,
         service::OutputManagerService,
         storage::database::{OutputManagerBackend, OutputManagerDatabase},
     },
@@ -32,6 +34,7 @@ use crate::{
 };
 use futures::{future, Future};
 use log::*;
+use std::sync::Arc;
 use tari_comms::connectivity::ConnectivityRequester;
 use tari_core::{
     consensus::{ConsensusConstantsBuilder, Network},
@@ -47,7 +50,10 @@ use tokio::sync::broadcast;
 
 pub mod config;
 pub mod error;
+pub mod fee_oracle;
 pub mod handle;
+pub mod key_manager_backend;
+pub mod one_sided;
 pub mod protocols;
 #[allow(unused_assignments)]
 pub mod service;
@@ -64,6 +70,8 @@ where T: OutputManagerBackend
     backend: Option<T>,
     factories: CryptoFactories,
     network: Network,
+    fee_oracle: Option<Arc<dyn FeeOracle>>,
+    key_manager_backend: Option<Arc<dyn KeyManagerBackend>>,
 }
 
 impl<T> OutputManagerServiceInitializer<T>
@@ -75,8 +83,23 @@ where T: OutputManagerBackend + 'static
             backend: Some(backend),
             factories,
             network,
+            fee_oracle: None,
+            key_manager_backend: None,
         }
     }
+
+    /// Use a custom fee oracle instead of the default one backed by the base node's mempool
+    pub fn with_fee_oracle(mut self, fee_oracle: Arc<dyn FeeOracle>) -> Self {
+        self.fee_oracle = Some(fee_oracle);
+        self
+    }
+
+    /// Use another source of spending keys, such as an external signer, instead of the default software key manager.
+    /// The backend signs for received outputs; sending still needs the backend to provide spending keys.
+    pub fn with_key_manager_backend(mut self, key_manager_backend: Arc<dyn KeyManagerBackend>) -> Self {
+        self.key_manager_backend = Some(key_manager_backend);
+        self
+    }
 }
 
 impl<T> ServiceInitializer for OutputManagerServiceInitializer<T>
@@ -105,13 +128,15 @@ where T: OutputManagerBackend + 'static
         let factories = self.factories.clone();
         let config = self.config.clone();
         let constants = ConsensusConstantsBuilder::new(self.network).build();
+        let fee_oracle = self.fee_oracle.clone();
+        let key_manager_backend = self.key_manager_backend.clone();
 
         context.spawn_when_ready(move |handles| async move {
             let transaction_service = handles.expect_handle::<TransactionServiceHandle>();
             let base_node_service_handle = handles.expect_handle::<BaseNodeServiceHandle>();
             let connectivity_manager = handles.expect_handle::<ConnectivityRequester>();
 
-            let service = OutputManagerService::new(
+            let mut service = OutputManagerService::new(
                 config,
                 transaction_service,
                 receiver,
@@ -124,8 +149,16 @@ where T: OutputManagerBackend + 'static
                 connectivity_manager,
             )
             .await
-            .expect("Could not initialize Output Manager Service")
-            .start();
+            .expect("Could not initialize Output Manager Service");
+            if let Some(fee_oracle) = fee_oracle {
+                service = service.with_fee_oracle(fee_oracle);
+            }
+            if let Some(key_manager_backend) = key_manager_backend {
+                service = service
+                    .with_key_manager_backend(key_manager_backend)
+                    .expect("Could not initialize the Output Manager Service key manager backend");
+            }
+            let service = service.start();
 
             futures::pin_mut!(service);
             future::select(service, handles.get_shutdown_signal()).await;
@@ -0,0 +1,175 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! One-sided payments let a sender pay a recipient without the interactive transaction negotiation. The sender builds
+//! the recipient's output itself and makes its range proof rewindable with keys derived from a Diffie-Hellman shared
+//! secret, so that the recipient can find the output when it scans the UTXO set and rewind it to recover the value and
+//! spending key.
+//!
+//! The recipient publishes a [OneSidedPaymentAddress] for a dedicated one-sided key `a` derived from its wallet key
+//! manager, made up of `A = a·G` and `A_H = a·H`. The sender's ephemeral key is the blinding factor `k` it chooses for
+//! the output, and the shared secret is `k·A + v·A_H = a·(k·G + v·H)`, which the recipient computes from `a` and the
+//! output's commitment alone. Nothing ties the output to either party's node identity, and the recipient can recover
+//! its one-sided payments from its seed words.
+//!
+//! Mimblewimble outputs have no spending condition other than knowledge of the blinding factor, so the sender also
+//! knows the spending key of a one-sided output until the recipient spends it.
+
+use crate::types::KeyDigest;
+use digest::Digest;
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction_protocol::RewindData,
+    types::{CommitmentFactory, PrivateKey, PublicKey},
+};
+use tari_crypto::{
+    commitment::HomomorphicCommitmentFactory,
+    keys::{DiffieHellmanSharedSecret, PublicKey as PublicKeyTrait},
+    range_proof::REWIND_USER_MESSAGE_LENGTH,
+    tari_utilities::{
+        hex::{Hex, HexError},
+        ByteArray,
+    },
+};
+
+const ONE_SIDED_REWIND_KEY_LABEL: &[u8] = b"one_sided_payment_rewind_key";
+const ONE_SIDED_REWIND_BLINDING_KEY_LABEL: &[u8] = b"one_sided_payment_rewind_blinding_key";
+
+/// The public keys a sender needs to pay a recipient with a one-sided payment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OneSidedPaymentAddress {
+    /// The recipient's one-sided public key, `a·G`
+    pub public_key: PublicKey,
+    /// The recipient's one-sided secret key multiplied by the value generator of the commitments, `a·H`
+    pub public_key_h: PublicKey,
+}
+
+impl OneSidedPaymentAddress {
+    /// Construct the payment address for the one-sided secret key `secret_key`
+    pub fn from_secret_key(secret_key: &PrivateKey, factory: &CommitmentFactory) -> Self {
+        Self {
+            public_key: PublicKey::from_secret_key(secret_key),
+            public_key_h: factory
+                .commit(&PrivateKey::default(), secret_key)
+                .as_public_key()
+                .clone(),
+        }
+    }
+}
+
+impl Display for OneSidedPaymentAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.public_key.to_hex(), self.public_key_h.to_hex())
+    }
+}
+
+impl FromStr for OneSidedPaymentAddress {
+    type Err = HexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key_len = PublicKey::key_length() * 2;
+        if s.len() != key_len * 2 || !s.is_char_boundary(key_len) {
+            return Err(HexError::LengthError);
+        }
+        Ok(Self {
+            public_key: PublicKey::from_hex(&s[..key_len])?,
+            public_key_h: PublicKey::from_hex(&s[key_len..])?,
+        })
+    }
+}
+
+/// Derive the rewind data the sender of a one-sided payment uses for an output of `value` with blinding factor
+/// `spending_key` paid to `address`
+pub fn sender_rewind_data(address: &OneSidedPaymentAddress, spending_key: &PrivateKey, value: MicroTari) -> RewindData {
+    let shared_secret = &PublicKey::shared_secret(spending_key, &address.public_key) +
+        &PublicKey::shared_secret(&PrivateKey::from(value), &address.public_key_h);
+    rewind_data_from_shared_secret(&shared_secret)
+}
+
+/// Derive the rewind data the recipient uses to try to rewind an output from `shared_secret`, the Diffie-Hellman shared
+/// secret of its one-sided key and the output's commitment
+pub fn recipient_rewind_data(shared_secret: &PublicKey) -> RewindData {
+    rewind_data_from_shared_secret(shared_secret)
+}
+
+fn rewind_data_from_shared_secret(shared_secret: &PublicKey) -> RewindData {
+    RewindData {
+        rewind_key: derive_key(ONE_SIDED_REWIND_KEY_LABEL, shared_secret),
+        rewind_blinding_key: derive_key(ONE_SIDED_REWIND_BLINDING_KEY_LABEL, shared_secret),
+        proof_message: [0u8; REWIND_USER_MESSAGE_LENGTH],
+    }
+}
+
+fn derive_key(label: &[u8], shared_secret: &PublicKey) -> PrivateKey {
+    let hash = KeyDigest::new().chain(label).chain(shared_secret.as_bytes()).result();
+    PrivateKey::from_bytes(hash.as_slice()).expect("A 32 byte hash is always a valid private key")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{recipient_rewind_data, sender_rewind_data, OneSidedPaymentAddress};
+    use rand::rngs::OsRng;
+    use std::str::FromStr;
+    use tari_core::transactions::{
+        tari_amount::MicroTari,
+        types::{CommitmentFactory, PrivateKey, PublicKey},
+    };
+    use tari_crypto::{
+        commitment::HomomorphicCommitmentFactory,
+        keys::{DiffieHellmanSharedSecret, SecretKey},
+    };
+
+    #[test]
+    fn sender_and_recipient_derive_the_same_rewind_data() {
+        let factory = CommitmentFactory::default();
+        let recipient_secret = PrivateKey::random(&mut OsRng);
+        let address = OneSidedPaymentAddress::from_secret_key(&recipient_secret, &factory);
+        let spending_key = PrivateKey::random(&mut OsRng);
+        let value = MicroTari::from(1234);
+        let commitment = factory.commit_value(&spending_key, value.into());
+
+        let sender_view = sender_rewind_data(&address, &spending_key, value);
+        let recipient_view =
+            recipient_rewind_data(&PublicKey::shared_secret(&recipient_secret, commitment.as_public_key()));
+        assert_eq!(sender_view.rewind_key, recipient_view.rewind_key);
+        assert_eq!(sender_view.rewind_blinding_key, recipient_view.rewind_blinding_key);
+        assert_ne!(sender_view.rewind_key, sender_view.rewind_blinding_key);
+
+        let other_view = recipient_rewind_data(&PublicKey::shared_secret(
+            &PrivateKey::random(&mut OsRng),
+            commitment.as_public_key(),
+        ));
+        assert_ne!(other_view.rewind_key, recipient_view.rewind_key);
+    }
+
+    #[test]
+    fn address_round_trips_through_its_string_form() {
+        let factory = CommitmentFactory::default();
+        let address = OneSidedPaymentAddress::from_secret_key(&PrivateKey::random(&mut OsRng), &factory);
+        assert_eq!(OneSidedPaymentAddress::from_str(&address.to_string()).unwrap(), address);
+        assert!(OneSidedPaymentAddress::from_str(&address.public_key.to_string()).is_err());
+    }
+}
@@ -140,6 +140,8 @@ where TBackend: OutputManagerBackend + 'static
 
         let mut retries = 0;
         let batch_total = output_batches_to_query.len();
+        let mut total_outputs = count_outputs(&output_batches_to_query);
+        let mut completed_outputs = 0;
 
         'main: loop {
             if let ValidationRetryStrategy::Limited(max_retries) = self.retry_strategy {
@@ -343,10 +345,14 @@ where TBackend: OutputManagerBackend + 'static
                                     delay.await;
                                     self.update_retry_delay(false);
                                     output_batches_to_query = self.get_output_batches().await?;
+                                    total_outputs = count_outputs(&output_batches_to_query);
+                                    completed_outputs = 0;
                                     retries += 1;
                                     break 'per_batch;
                                 }
                                 self.update_retry_delay(true);
+                                completed_outputs += batch.len() as u64;
+                                self.publish_progress(completed_outputs, total_outputs);
                             },
                             Err(OutputManagerProtocolError{id: _, error: OutputManagerError::RpcError(e)}) => {
                                 warn!(target: LOG_TARGET, "Error with RPC Client: {}. Retrying RPC client connection.", e);
@@ -399,6 +405,26 @@ where TBackend: OutputManagerBackend + 'static
         Ok(self.id)
     }
 
+    fn publish_progress(&self, completed: u64, total: u64) {
+        let _ = self
+            .resources
+            .event_publisher
+            .send(Arc::new(OutputManagerEvent::TxoValidationProgress {
+                operation_id: self.id,
+                validation_type: self.validation_type,
+                completed,
+                total,
+            }))
+            .map_err(|e| {
+                trace!(
+                    target: LOG_TARGET,
+                    "Error sending event {:?}, because there are no subscribers.",
+                    e.0
+                );
+                e
+            });
+    }
+
     async fn send_query_batch(
         &mut self,
         batch: Vec<Vec<u8>>,
@@ -529,7 +555,9 @@ where TBackend: OutputManagerBackend + 'static
             },
             TxoValidationType::Spent => {
                 // Go through the response outputs and check if they are currently Spent, if they are then they can be
-                // marked as Unspent because they exist in the UTXO set. Hooray!
+                // marked as Unspent because they exist in the UTXO set. This happens when the transaction that spent
+                // them was reorged out of the chain, so any transaction built on the assumption that they are spent
+                // could now conflict with them.
                 for output in returned_outputs.iter() {
                     match self
                         .resources
@@ -537,10 +565,27 @@ where TBackend: OutputManagerBackend + 'static
                         .update_spent_output_to_unspent(output.clone().commitment)
                         .await
                     {
-                        Ok(uo) => info!(
-                            target: LOG_TARGET,
-                            "Spent output with value {} restored to Unspent output", uo.unblinded_output.value
-                        ),
+                        Ok(uo) => {
+                            warn!(
+                                target: LOG_TARGET,
+                                "Spent output {} with value {} reappeared as unspent on-chain and has been restored \
+                                 to an Unspent output",
+                                uo.commitment.to_hex(),
+                                uo.unblinded_output.value
+                            );
+                            let _ = self
+                                .resources
+                                .event_publisher
+                                .send(Arc::new(OutputManagerEvent::OutputReorged(uo.commitment)))
+                                .map_err(|e| {
+                                    trace!(
+                                        target: LOG_TARGET,
+                                        "Error sending event because there are no subscribers: {:?}",
+                                        e
+                                    );
+                                    e
+                                });
+                        },
                         Err(e) => debug!(target: LOG_TARGET, "Unable to restore Spent output to Unspent: {}", e),
                     }
                 }
@@ -638,3 +683,7 @@ impl fmt::Display for TxoValidationType {
         }
     }
 }
+
+fn count_outputs(batches: &[Vec<Vec<u8>>]) -> u64 {
+    batches.iter().map(|b| b.len() as u64).sum()
+}
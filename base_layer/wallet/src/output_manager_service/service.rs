@@ -21,11 +21,14 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    base_node_service::handle::BaseNodeServiceHandle,
+    base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
     output_manager_service::{
         config::OutputManagerServiceConfig,
         error::{OutputManagerError, OutputManagerProtocolError},
+        fee_oracle::{FeeOracle, MempoolFeeOracle},
         handle::{OutputManagerEventSender, OutputManagerRequest, OutputManagerResponse, PublicRewindKeys},
+        key_manager_backend::{value_generator, KeyManagerBackend, SoftwareKeyManager},
+        one_sided::{recipient_rewind_data, sender_rewind_data, OneSidedPaymentAddress},
         protocols::txo_validation_protocol::{TxoValidationProtocol, TxoValidationType},
         storage::{
             database::{KeyManagerState, OutputManagerBackend, OutputManagerDatabase, PendingTransactionOutputs},
@@ -34,32 +37,41 @@ use crate::{
         TxId,
     },
     transaction_service::handle::TransactionServiceHandle,
-    types::{HashDigest, KeyDigest, ValidationRetryStrategy},
+    types::{HashDigest, ValidationRetryStrategy, DEFAULT_FEE_PER_GRAM},
 };
-use futures::{pin_mut, stream::FuturesUnordered, StreamExt};
+use futures::{channel::oneshot, pin_mut, stream::FuturesUnordered, StreamExt};
 use log::*;
 use rand::{rngs::OsRng, RngCore};
 use std::{
     cmp::Ordering,
     collections::HashMap,
     fmt::{self, Display},
+    sync::Arc,
     time::Duration,
 };
-use tari_comms::{connectivity::ConnectivityRequester, types::CommsPublicKey};
+use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeId, types::CommsPublicKey};
 use tari_core::{
+    base_node::rpc::BaseNodeWalletRpcClient,
     consensus::ConsensusConstants,
+    proto::base_node::FeeHistogramBucket,
     transactions::{
         fee::Fee,
         tari_amount::MicroTari,
         transaction::{
             KernelFeatures,
             OutputFeatures,
+            OutputFlags,
             Transaction,
             TransactionInput,
             TransactionOutput,
             UnblindedOutput,
         },
-        transaction_protocol::{sender::TransactionSenderMessage, RewindData},
+        transaction_protocol::{
+            build_challenge,
+            recipient::RecipientState,
+            sender::TransactionSenderMessage,
+            RewindData,
+        },
         types::{CryptoFactories, PrivateKey, PublicKey},
         CoinbaseBuilder,
         ReceiverTransactionProtocol,
@@ -71,10 +83,6 @@ use tari_crypto::{
     range_proof::REWIND_USER_MESSAGE_LENGTH,
     tari_utilities::hex::Hex,
 };
-use tari_key_manager::{
-    key_manager::KeyManager,
-    mnemonic::{from_secret_key, MnemonicLanguage},
-};
 use tari_service_framework::reply_channel;
 use tari_shutdown::ShutdownSignal;
 use tokio::{
@@ -88,6 +96,7 @@ const LOG_TARGET_STRESS: &str = "stress_test::output_manager_service";
 const KEY_MANAGER_COINBASE_BRANCH_KEY: &str = "coinbase";
 const KEY_MANAGER_RECOVERY_VIEWONLY_BRANCH_KEY: &str = "recovery_viewonly";
 const KEY_MANAGER_RECOVERY_BLINDING_BRANCH_KEY: &str = "recovery_blinding";
+const KEY_MANAGER_ONE_SIDED_BRANCH_KEY: &str = "one_sided_payment";
 
 /// This service will manage a wallet's available outputs and the key manager that produces the keys for these outputs.
 /// The service will assemble transactions to be sent from the wallets available outputs and provide keys to receive
@@ -97,12 +106,14 @@ pub struct OutputManagerService<TBackend>
 where TBackend: OutputManagerBackend + 'static
 {
     resources: OutputManagerResources<TBackend>,
-    key_manager: Mutex<KeyManager<PrivateKey, KeyDigest>>,
-    coinbase_key_manager: Mutex<KeyManager<PrivateKey, KeyDigest>>,
+    key_manager: Arc<dyn KeyManagerBackend>,
+    key_manager_branch: String,
+    primary_key_index: Mutex<u64>,
     request_stream:
         Option<reply_channel::Receiver<OutputManagerRequest, Result<OutputManagerResponse, OutputManagerError>>>,
     base_node_update_publisher: broadcast::Sender<CommsPublicKey>,
     base_node_service: BaseNodeServiceHandle,
+    fee_oracle: Arc<dyn FeeOracle>,
 }
 
 impl<TBackend> OutputManagerService<TBackend>
@@ -139,42 +150,27 @@ where TBackend: OutputManagerBackend + 'static
             Some(km) => km,
         };
 
-        let coinbase_key_manager = KeyManager::<PrivateKey, KeyDigest>::from(
-            key_manager_state.master_key.clone(),
-            KEY_MANAGER_COINBASE_BRANCH_KEY.to_string(),
-            0,
-        );
-
-        let key_manager = KeyManager::<PrivateKey, KeyDigest>::from(
-            key_manager_state.master_key.clone(),
-            key_manager_state.branch_seed,
-            key_manager_state.primary_key_index,
-        );
-
-        let rewind_key_manager = KeyManager::<PrivateKey, KeyDigest>::from(
-            key_manager_state.master_key.clone(),
-            KEY_MANAGER_RECOVERY_VIEWONLY_BRANCH_KEY.to_string(),
-            0,
-        );
-        let rewind_key = rewind_key_manager.derive_key(0)?.k;
-
-        let rewind_blinding_key_manager = KeyManager::<PrivateKey, KeyDigest>::from(
-            key_manager_state.master_key,
-            KEY_MANAGER_RECOVERY_BLINDING_BRANCH_KEY.to_string(),
-            0,
+        // The one-sided payment branch is published in the payment address, so it must not share its keys with the
+        // rewind and spending keys of the legacy branches
+        let key_manager = Arc::new(
+            SoftwareKeyManager::new(key_manager_state.master_key).with_legacy_branches(vec![
+                key_manager_state.branch_seed.clone(),
+                KEY_MANAGER_COINBASE_BRANCH_KEY.to_string(),
+                KEY_MANAGER_RECOVERY_VIEWONLY_BRANCH_KEY.to_string(),
+                KEY_MANAGER_RECOVERY_BLINDING_BRANCH_KEY.to_string(),
+            ]),
         );
-        let rewind_blinding_key = rewind_blinding_key_manager.derive_key(0)?.k;
-
-        let rewind_data = RewindData {
-            rewind_key,
-            rewind_blinding_key,
-            proof_message: [0u8; REWIND_USER_MESSAGE_LENGTH],
-        };
+        let rewind_data = Self::derive_rewind_data(key_manager.as_ref())?;
 
         // Clear any encumberances for transactions that were being negotiated but did not complete to become official
         // Pending Transactions.
         db.clear_short_term_encumberances().await?;
 
+        let fee_oracle = Arc::new(MempoolFeeOracle::new(
+            DEFAULT_FEE_PER_GRAM,
+            consensus_constants.get_max_block_transaction_weight(),
+        ));
+
         let resources = OutputManagerResources {
             config,
             db,
@@ -192,11 +188,38 @@ where TBackend: OutputManagerBackend + 'static
 
         Ok(OutputManagerService {
             resources,
-            key_manager: Mutex::new(key_manager),
-            coinbase_key_manager: Mutex::new(coinbase_key_manager),
+            key_manager,
+            key_manager_branch: key_manager_state.branch_seed,
+            primary_key_index: Mutex::new(key_manager_state.primary_key_index),
             request_stream: Some(request_stream),
             base_node_update_publisher,
             base_node_service,
+            fee_oracle,
+        })
+    }
+
+    /// Replace the default mempool backed fee oracle with a custom one
+    pub fn with_fee_oracle(mut self, fee_oracle: Arc<dyn FeeOracle>) -> Self {
+        self.fee_oracle = fee_oracle;
+        self
+    }
+
+    /// Replace the default software key manager with another source of keys, such as an external signer
+    pub fn with_key_manager_backend(
+        mut self,
+        key_manager: Arc<dyn KeyManagerBackend>,
+    ) -> Result<Self, OutputManagerError>
+    {
+        self.resources.rewind_data = Self::derive_rewind_data(key_manager.as_ref())?;
+        self.key_manager = key_manager;
+        Ok(self)
+    }
+
+    fn derive_rewind_data(key_manager: &dyn KeyManagerBackend) -> Result<RewindData, OutputManagerError> {
+        Ok(RewindData {
+            rewind_key: key_manager.spending_key(KEY_MANAGER_RECOVERY_VIEWONLY_BRANCH_KEY, 0)?,
+            rewind_blinding_key: key_manager.spending_key(KEY_MANAGER_RECOVERY_BLINDING_BRANCH_KEY, 0)?,
+            proof_message: [0u8; REWIND_USER_MESSAGE_LENGTH],
         })
     }
 
@@ -207,6 +230,7 @@ where TBackend: OutputManagerBackend + 'static
             .expect("OutputManagerService initialized without request_stream")
             .fuse();
         pin_mut!(request_stream);
+        let mut base_node_service_event_stream = self.base_node_service.get_event_stream_fused();
 
         let mut shutdown = self.resources.shutdown_signal.clone();
 
@@ -219,14 +243,24 @@ where TBackend: OutputManagerBackend + 'static
                 request_context = request_stream.select_next_some() => {
                 trace!(target: LOG_TARGET, "Handling Service API Request");
                     let (request, reply_tx) = request_context.split();
-                    let response = self.handle_request(request, &mut txo_validation_handles).await.map_err(|e| {
-                        warn!(target: LOG_TARGET, "Error handling request: {:?}", e);
-                        e
-                    });
-                    let _ = reply_tx.send(response).map_err(|e| {
-                        warn!(target: LOG_TARGET, "Failed to send reply");
-                        e
-                    });
+                    if let OutputManagerRequest::GetRecommendedFeePerGram = request {
+                        self.spawn_get_recommended_fee_per_gram(reply_tx);
+                    } else {
+                        let response = self.handle_request(request, &mut txo_validation_handles).await.map_err(|e| {
+                            warn!(target: LOG_TARGET, "Error handling request: {:?}", e);
+                            e
+                        });
+                        let _ = reply_tx.send(response).map_err(|e| {
+                            warn!(target: LOG_TARGET, "Failed to send reply");
+                            e
+                        });
+                    }
+                },
+                event = base_node_service_event_stream.select_next_some() => {
+                    match event {
+                        Ok(event) => self.handle_base_node_service_event(&event, &mut txo_validation_handles),
+                        Err(e) => debug!(target: LOG_TARGET, "Lagging read on base node event stream: {}", e),
+                    }
                 },
                 join_result = txo_validation_handles.select_next_some() => {
                    trace!(target: LOG_TARGET, "TXO Validation protocol has ended with result {:?}", join_result);
@@ -286,16 +320,37 @@ where TBackend: OutputManagerBackend + 'static
                 .create_pay_to_self_transaction(amount, fee_per_gram, lock_height, message)
                 .await
                 .map(OutputManagerResponse::PayToSelfTransaction),
+            OutputManagerRequest::CreateOneSidedTransaction((
+                amount,
+                fee_per_gram,
+                lock_height,
+                message,
+                recipient_address,
+            )) => self
+                .create_one_sided_transaction(amount, fee_per_gram, lock_height, message, &recipient_address)
+                .await
+                .map(OutputManagerResponse::OneSidedTransaction),
             OutputManagerRequest::FeeEstimate((amount, fee_per_gram, num_kernels, num_outputs)) => self
                 .fee_estimate(amount, fee_per_gram, num_kernels, num_outputs)
                 .await
                 .map(OutputManagerResponse::FeeEstimate),
+            OutputManagerRequest::GetRecommendedFeePerGram => {
+                Ok(OutputManagerResponse::RecommendedFeePerGram(
+                    Self::get_recommended_fee_per_gram(
+                        self.fee_oracle.clone(),
+                        self.resources.base_node_public_key.clone(),
+                        self.resources.connectivity_manager.clone(),
+                        self.resources.config.base_node_query_timeout,
+                    )
+                    .await,
+                ))
+            },
             OutputManagerRequest::ConfirmPendingTransaction(tx_id) => self
                 .confirm_encumberance(tx_id)
                 .await
                 .map(|_| OutputManagerResponse::PendingTransactionConfirmed),
-            OutputManagerRequest::ConfirmTransaction((tx_id, spent_outputs, received_outputs)) => self
-                .confirm_transaction(tx_id, &spent_outputs, &received_outputs)
+            OutputManagerRequest::ConfirmTransaction((tx_id, spent_outputs, received_outputs, mined_height)) => self
+                .confirm_transaction(tx_id, &spent_outputs, &received_outputs, mined_height)
                 .await
                 .map(|_| OutputManagerResponse::TransactionConfirmed),
             OutputManagerRequest::CancelTransaction(tx_id) => self
@@ -349,6 +404,10 @@ where TBackend: OutputManagerBackend + 'static
                 .create_coin_split(amount_per_split, split_count, fee_per_gram, lock_height)
                 .await
                 .map(OutputManagerResponse::Transaction),
+            OutputManagerRequest::CreateSweepTransaction((fee_per_gram, max_inputs, lock_height)) => self
+                .create_sweep_transaction(fee_per_gram, max_inputs, lock_height)
+                .await
+                .map(OutputManagerResponse::Transaction),
             OutputManagerRequest::ApplyEncryption(cipher) => self
                 .resources
                 .db
@@ -366,13 +425,52 @@ where TBackend: OutputManagerBackend + 'static
             OutputManagerRequest::GetPublicRewindKeys => Ok(OutputManagerResponse::PublicRewindKeys(Box::new(
                 self.get_rewind_public_keys(),
             ))),
-            OutputManagerRequest::RewindOutputs(outputs) => self
-                .rewind_outputs(outputs)
-                .await
+            OutputManagerRequest::RewindOutputs(outputs) => Ok(OutputManagerResponse::RewindOutputs(
+                self.rewind_outputs(outputs, &self.resources.rewind_data),
+            )),
+            OutputManagerRequest::GetOneSidedPaymentAddress => self
+                .get_one_sided_payment_address()
+                .map(|address| OutputManagerResponse::OneSidedPaymentAddress(Box::new(address))),
+            OutputManagerRequest::ScanForOneSidedOutputs(outputs) => self
+                .scan_for_one_sided_outputs(outputs)
                 .map(OutputManagerResponse::RewindOutputs),
         }
     }
 
+    /// Start a validation of the unspent outputs as soon as the base node reports a new chain tip, so that newly mined
+    /// outputs are confirmed without waiting for the next scheduled validation.
+    fn handle_base_node_service_event(
+        &mut self,
+        event: &BaseNodeEvent,
+        txo_validation_handles: &mut FuturesUnordered<JoinHandle<Result<u64, OutputManagerProtocolError>>>,
+    )
+    {
+        if let BaseNodeEvent::TipChanged { height, .. } = event {
+            if !txo_validation_handles.is_empty() {
+                trace!(
+                    target: LOG_TARGET,
+                    "Chain tip changed to height {} while a TXO validation is in progress",
+                    height
+                );
+                return;
+            }
+            match self.validate_outputs(
+                TxoValidationType::Unspent,
+                ValidationRetryStrategy::Limited(1),
+                txo_validation_handles,
+            ) {
+                Ok(id) => debug!(
+                    target: LOG_TARGET,
+                    "Chain tip changed to height {}, started UTXO Validation Protocol (Id: {})", height, id
+                ),
+                Err(e) => debug!(
+                    target: LOG_TARGET,
+                    "Chain tip changed to height {} but could not start UTXO validation: {}", height, e
+                ),
+            }
+        }
+    }
+
     fn validate_outputs(
         &mut self,
         validation_type: TxoValidationType,
@@ -446,7 +544,7 @@ where TBackend: OutputManagerBackend + 'static
             _ => return Err(OutputManagerError::InvalidSenderMessage),
         };
 
-        let key = self.get_next_spend_key().await?;
+        let (key_index, key) = self.get_next_spend_key_and_index().await?;
         self.resources
             .db
             .accept_incoming_pending_transaction(
@@ -462,15 +560,27 @@ where TBackend: OutputManagerBackend + 'static
         self.confirm_encumberance(tx_id).await?;
 
         let nonce = PrivateKey::random(&mut OsRng);
+        let public_nonce = PublicKey::from_secret_key(&nonce);
+        let challenge = sender_message
+            .single()
+            .map(|data| build_challenge(&(&data.public_nonce + &public_nonce), &data.metadata))
+            .ok_or(OutputManagerError::InvalidSenderMessage)?;
 
-        let rtp = ReceiverTransactionProtocol::new_with_rewindable_output(
+        let mut rtp = ReceiverTransactionProtocol::new_with_rewindable_output(
             sender_message,
-            nonce,
+            nonce.clone(),
             key,
             OutputFeatures::default(),
             &self.resources.factories,
             &self.resources.rewind_data,
         );
+        // The partial signature is made by the key manager backend, so that an external signer signs for the outputs
+        // this wallet receives
+        if let RecipientState::Finalized(data) = &mut rtp.state {
+            data.partial_signature =
+                self.key_manager
+                    .sign(&self.key_manager_branch, key_index, &nonce, challenge.as_slice())?;
+        }
 
         Ok(rtp)
     }
@@ -551,7 +661,7 @@ where TBackend: OutputManagerBackend + 'static
 
         self.resources
             .db
-            .confirm_pending_transaction_outputs(pending_transaction.tx_id)
+            .confirm_pending_transaction_outputs(pending_transaction.tx_id, None)
             .await?;
 
         debug!(
@@ -592,6 +702,81 @@ where TBackend: OutputManagerBackend + 'static
         Ok(fee)
     }
 
+    /// Ask the fee oracle for a fee per gram in a separate task and reply to the request from there. Fetching the
+    /// mempool fee histogram dials the base node, which would otherwise hold up all other requests for up to the base
+    /// node query timeout.
+    fn spawn_get_recommended_fee_per_gram(
+        &self,
+        reply_tx: oneshot::Sender<Result<OutputManagerResponse, OutputManagerError>>,
+    )
+    {
+        let fee_oracle = self.fee_oracle.clone();
+        let base_node_public_key = self.resources.base_node_public_key.clone();
+        let connectivity_manager = self.resources.connectivity_manager.clone();
+        let base_node_query_timeout = self.resources.config.base_node_query_timeout;
+        tokio::spawn(async move {
+            let fee_per_gram = Self::get_recommended_fee_per_gram(
+                fee_oracle,
+                base_node_public_key,
+                connectivity_manager,
+                base_node_query_timeout,
+            )
+            .await;
+            if reply_tx
+                .send(Ok(OutputManagerResponse::RecommendedFeePerGram(fee_per_gram)))
+                .is_err()
+            {
+                warn!(target: LOG_TARGET, "Failed to send reply");
+            }
+        });
+    }
+
+    /// Ask the fee oracle for a fee per gram, providing it with the current mempool fee histogram of the base node if
+    /// it can be fetched.
+    async fn get_recommended_fee_per_gram(
+        fee_oracle: Arc<dyn FeeOracle>,
+        base_node_public_key: Option<CommsPublicKey>,
+        connectivity_manager: ConnectivityRequester,
+        base_node_query_timeout: Duration,
+    ) -> MicroTari
+    {
+        let histogram =
+            match Self::fetch_mempool_fee_histogram(base_node_public_key, connectivity_manager, base_node_query_timeout)
+                .await
+            {
+                Ok(histogram) => Some(histogram),
+                Err(e) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Could not fetch the mempool fee histogram from the base node: {}", e
+                    );
+                    None
+                },
+            };
+        let fee_per_gram = fee_oracle.recommended_fee_per_gram(histogram.as_deref());
+        debug!(target: LOG_TARGET, "Recommended fee per gram: {}", fee_per_gram);
+        fee_per_gram
+    }
+
+    async fn fetch_mempool_fee_histogram(
+        base_node_public_key: Option<CommsPublicKey>,
+        mut connectivity_manager: ConnectivityRequester,
+        base_node_query_timeout: Duration,
+    ) -> Result<Vec<FeeHistogramBucket>, OutputManagerError>
+    {
+        let base_node_public_key = base_node_public_key.ok_or(OutputManagerError::NoBaseNodeKeysProvided)?;
+        let base_node_node_id = NodeId::from_key(&base_node_public_key)?;
+        let mut connection = connectivity_manager.dial_peer(base_node_node_id).await?;
+        let mut client = connection
+            .connect_rpc_using_builder(BaseNodeWalletRpcClient::builder().with_deadline(base_node_query_timeout))
+            .await?;
+        let response = client.get_mempool_fee_histogram().await?;
+        if !response.is_synced {
+            return Err(OutputManagerError::BaseNodeNotSynced);
+        }
+        Ok(response.buckets)
+    }
+
     /// Prepare a Sender Transaction Protocol for the amount and fee_per_gram specified. If required a change output
     /// will be produced.
     pub async fn prepare_transaction_to_send(
@@ -761,6 +946,90 @@ where TBackend: OutputManagerBackend + 'static
         Ok((tx_id, fee, tx))
     }
 
+    async fn create_one_sided_transaction(
+        &mut self,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+        recipient_address: &OneSidedPaymentAddress,
+    ) -> Result<(TxId, MicroTari, Transaction), OutputManagerError>
+    {
+        let (inputs, _, total) = self.select_utxos(amount, fee_per_gram, 1, None).await?;
+
+        let offset = PrivateKey::random(&mut OsRng);
+        let nonce = PrivateKey::random(&mut OsRng);
+
+        // The recipient does not take part, so we build its output ourselves and finalize the transaction alone
+        let mut builder = SenderTransactionProtocol::builder(0);
+        builder
+            .with_lock_height(lock_height.unwrap_or(0))
+            .with_fee_per_gram(fee_per_gram)
+            .with_offset(offset)
+            .with_private_nonce(nonce)
+            .with_message(message)
+            .with_prevent_fee_gt_amount(self.resources.config.prevent_fee_gt_amount);
+
+        for uo in &inputs {
+            builder.with_input(
+                uo.unblinded_output.as_transaction_input(
+                    &self.resources.factories.commitment,
+                    uo.unblinded_output.features.clone(),
+                ),
+                uo.unblinded_output.clone(),
+            );
+        }
+
+        // The recipient recovers this key by rewinding the range proof. It is also our ephemeral key for the
+        // Diffie-Hellman exchange with the recipient's one-sided payment address.
+        let recipient_spend_key = PrivateKey::random(&mut OsRng);
+        let recipient_rewind_data = sender_rewind_data(recipient_address, &recipient_spend_key, amount);
+        builder.with_rewindable_output(
+            UnblindedOutput::new(amount, recipient_spend_key, None),
+            recipient_rewind_data,
+        );
+
+        let mut change_key = None;
+        let fee = Fee::calculate(fee_per_gram, 1, inputs.len(), 1);
+        let change_value = total.saturating_sub(amount).saturating_sub(fee);
+        if change_value > 0.into() {
+            let key = self.get_next_spend_key().await?;
+            change_key = Some(key.clone());
+            builder.with_rewindable_change_secret(key, self.resources.rewind_data.clone());
+        }
+
+        let mut stp = builder
+            .build::<HashDigest>(&self.resources.factories)
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+
+        let mut outputs = Vec::new();
+        if let Some(key) = change_key {
+            let change_amount = stp.get_change_amount()?;
+            if change_amount > MicroTari::from(0) {
+                outputs.push(DbUnblindedOutput::rewindable_from_unblinded_output(
+                    UnblindedOutput::new(change_amount, key, None),
+                    &self.resources.factories,
+                    &self.resources.rewind_data,
+                )?);
+            }
+        }
+
+        let tx_id = stp.get_tx_id()?;
+        trace!(
+            target: LOG_TARGET,
+            "Encumber one-sided transaction ({}) outputs.",
+            tx_id
+        );
+        self.resources.db.encumber_outputs(tx_id, inputs, outputs).await?;
+        self.confirm_encumberance(tx_id).await?;
+        let fee = stp.get_fee_amount()?;
+        trace!(target: LOG_TARGET, "Finalize one-sided transaction ({}).", tx_id);
+        stp.finalize(KernelFeatures::empty(), &self.resources.factories)?;
+        let tx = stp.take_transaction()?;
+
+        Ok((tx_id, fee, tx))
+    }
+
     /// Confirm that a transaction has finished being negotiated between parties so the short-term encumberance can be
     /// made official
     async fn confirm_encumberance(&mut self, tx_id: u64) -> Result<(), OutputManagerError> {
@@ -777,6 +1046,7 @@ where TBackend: OutputManagerBackend + 'static
         tx_id: u64,
         inputs: &[TransactionInput],
         outputs: &[TransactionOutput],
+        mined_height: Option<u64>,
     ) -> Result<(), OutputManagerError>
     {
         let pending_transaction = self.resources.db.fetch_pending_transaction_outputs(tx_id).await?;
@@ -812,7 +1082,7 @@ where TBackend: OutputManagerBackend + 'static
 
         self.resources
             .db
-            .confirm_pending_transaction_outputs(pending_transaction.tx_id)
+            .confirm_pending_transaction_outputs(pending_transaction.tx_id, mined_height)
             .await?;
 
         trace!(target: LOG_TARGET, "Confirm transaction (TxId: {})", tx_id);
@@ -834,6 +1104,33 @@ where TBackend: OutputManagerBackend + 'static
         Ok(self.resources.db.timeout_pending_transaction_outputs(period).await?)
     }
 
+    /// Returns true if the output has matured at `tip_height` and has at least `min_confirmations_for_spend`
+    /// confirmations. Outputs without a mined height, such as imported or recovered outputs, were already on the
+    /// blockchain when they were added, so only their maturity is checked.
+    fn is_spendable_at(&self, output: &DbUnblindedOutput, tip_height: u64) -> bool {
+        output.unblinded_output.features.maturity <= tip_height &&
+            output
+                .mined_height
+                .map(|h| tip_height.saturating_sub(h) >= self.resources.config.min_confirmations_for_spend)
+                .unwrap_or(true)
+    }
+
+    /// Returns true if the output is certainly spendable whatever the chain tip height is. That is the case if it has
+    /// no maturity, is not a coinbase and has the required confirmations at any height.
+    fn is_spendable_at_any_height(&self, output: &DbUnblindedOutput) -> bool {
+        let features = &output.unblinded_output.features;
+        features.maturity == 0 &&
+            !features.flags.contains(OutputFlags::COINBASE_OUTPUT) &&
+            self.has_min_confirmations_at_any_height(output)
+    }
+
+    /// Returns true if the output has at least `min_confirmations_for_spend` confirmations whatever the chain tip
+    /// height is. That is the case if no confirmations are required or if it was already on the blockchain when it was
+    /// added (see `is_spendable_at`).
+    fn has_min_confirmations_at_any_height(&self, output: &DbUnblindedOutput) -> bool {
+        self.resources.config.min_confirmations_for_spend == 0 || output.mined_height.is_none()
+    }
+
     /// Select which unspent transaction outputs to use to send a transaction of the specified amount. Use the specified
     /// selection strategy to choose the outputs. It also determines if a change output is required.
     async fn select_utxos(
@@ -859,6 +1156,22 @@ where TBackend: OutputManagerBackend + 'static
 
         let uo = self.resources.db.fetch_sorted_unspent_outputs().await?;
 
+        // Outputs carrying excluded features are not suitable inputs for an ordinary send
+        let excluded_flags = self.resources.config.coin_selection_excluded_output_flags;
+        let num_utxos = uo.len();
+        let uo = uo
+            .into_iter()
+            .filter(|u| !u.unblinded_output.features.flags.intersects(excluded_flags))
+            .collect::<Vec<DbUnblindedOutput>>();
+        if uo.len() < num_utxos {
+            trace!(
+                target: LOG_TARGET,
+                "Some UTXOs have excluded output features ({:?}), filtered {} UTXOs",
+                excluded_flags,
+                num_utxos - uo.len()
+            );
+        }
+
         // Attempt to get the chain tip height
         let chain_metadata = self.base_node_service.get_chain_metadata().await?;
         let (connected, tip_height) = match &chain_metadata {
@@ -873,24 +1186,39 @@ where TBackend: OutputManagerBackend + 'static
             (None, true) => None, // use the selection heuristic next
         };
 
-        // If we know the chain height then filter out unspendable UTXOs
+        // If we know the chain height then filter out unspendable UTXOs. Otherwise filter out the UTXOs that may not
+        // have enough confirmations, while immature UTXOs are selected last by the MaturityThenSmallest strategy.
         let num_utxos = uo.len();
         let uo = if connected {
+            let min_confirmations = self.resources.config.min_confirmations_for_spend;
             let mature_utxos = uo
                 .into_iter()
-                .filter(|u| u.unblinded_output.features.maturity <= tip_height)
+                .filter(|u| self.is_spendable_at(u, tip_height))
                 .collect::<Vec<DbUnblindedOutput>>();
 
             trace!(
                 target: LOG_TARGET,
-                "Some UTXOs have not matured yet at height {}, filtered {} UTXOs",
+                "Some UTXOs have not matured yet or have fewer than {} confirmations at height {}, filtered {} UTXOs",
+                min_confirmations,
                 tip_height,
                 num_utxos - mature_utxos.len()
             );
 
             mature_utxos
         } else {
-            uo
+            let confirmed_utxos = uo
+                .into_iter()
+                .filter(|u| self.has_min_confirmations_at_any_height(u))
+                .collect::<Vec<DbUnblindedOutput>>();
+
+            trace!(
+                target: LOG_TARGET,
+                "The chain tip height is unknown, filtered {} UTXOs that may have fewer than {} confirmations",
+                num_utxos - confirmed_utxos.len(),
+                self.resources.config.min_confirmations_for_spend
+            );
+
+            confirmed_utxos
         };
 
         // Heuristic for selection strategy: Default to MaturityThenSmallest, but if the amount is greater than
@@ -1099,12 +1427,110 @@ where TBackend: OutputManagerBackend + 'static
         Ok((tx_id, tx, fee, utxos_total_value))
     }
 
+    /// Consolidate up to `max_inputs` of the smallest spendable UTXOs into a single output paid back to this wallet.
+    /// Immature outputs (including immature coinbases) and outputs with fewer than `min_confirmations_for_spend`
+    /// confirmations are never selected. If the chain tip is unknown, only outputs that are spendable at any height are
+    /// selected.
+    async fn create_sweep_transaction(
+        &mut self,
+        fee_per_gram: MicroTari,
+        max_inputs: usize,
+        lock_height: Option<u64>,
+    ) -> Result<(u64, Transaction, MicroTari, MicroTari), OutputManagerError>
+    {
+        trace!(target: LOG_TARGET, "Select UTXOs for sweep transaction.");
+        let tip_height = self
+            .base_node_service
+            .get_chain_metadata()
+            .await?
+            .map(|metadata| metadata.height_of_longest_chain());
+
+        // Sorted from lowest value to highest
+        let inputs = self
+            .resources
+            .db
+            .fetch_sorted_unspent_outputs()
+            .await?
+            .into_iter()
+            .filter(|uo| {
+                !uo.unblinded_output
+                    .features
+                    .flags
+                    .intersects(self.resources.config.coin_selection_excluded_output_flags)
+            })
+            .filter(|uo| match tip_height {
+                Some(height) => self.is_spendable_at(uo, height),
+                None => self.is_spendable_at_any_height(uo),
+            })
+            .take(max_inputs)
+            .collect::<Vec<_>>();
+
+        if inputs.len() < 2 {
+            return Err(OutputManagerError::NotEnoughOutputsToSweep(inputs.len()));
+        }
+
+        let input_count = inputs.len();
+        let utxos_total_value = inputs
+            .iter()
+            .fold(MicroTari::from(0), |acc, uo| acc + uo.unblinded_output.value);
+        let fee = Fee::calculate(fee_per_gram, 1, input_count, 1);
+        let output_amount = utxos_total_value
+            .checked_sub(fee)
+            .ok_or(OutputManagerError::NotEnoughFunds)?;
+        // An output that would cost more to spend than it is worth is dust
+        let min_output_amount = Fee::calculate(fee_per_gram, 1, 1, 1);
+        if output_amount <= min_output_amount {
+            return Err(OutputManagerError::SweepOutputIsDust(output_amount));
+        }
+
+        trace!(target: LOG_TARGET, "Construct sweep transaction.");
+        let offset = PrivateKey::random(&mut OsRng);
+        let nonce = PrivateKey::random(&mut OsRng);
+        let mut builder = SenderTransactionProtocol::builder(0);
+        builder
+            .with_lock_height(lock_height.unwrap_or(0))
+            .with_fee_per_gram(fee_per_gram)
+            .with_offset(offset)
+            .with_private_nonce(nonce);
+        for uo in inputs.iter() {
+            builder.with_input(
+                uo.unblinded_output.as_transaction_input(
+                    &self.resources.factories.commitment,
+                    uo.unblinded_output.clone().features,
+                ),
+                uo.unblinded_output.clone(),
+            );
+        }
+
+        let spend_key = self.get_next_spend_key().await?;
+        let utxo = DbUnblindedOutput::from_unblinded_output(
+            UnblindedOutput::new(output_amount, spend_key, None),
+            &self.resources.factories,
+        )?;
+        builder.with_output(utxo.unblinded_output.clone());
+
+        trace!(target: LOG_TARGET, "Build sweep transaction.");
+        let mut stp = builder
+            .build::<HashDigest>(&self.resources.factories)
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+        let tx_id = stp.get_tx_id()?;
+        trace!(
+            target: LOG_TARGET,
+            "Encumber sweep transaction ({}) outputs: {} inputs into an output of {}",
+            tx_id,
+            input_count,
+            output_amount
+        );
+        self.resources.db.encumber_outputs(tx_id, inputs, vec![utxo]).await?;
+        self.confirm_encumberance(tx_id).await?;
+        stp.finalize(KernelFeatures::empty(), &self.resources.factories)?;
+        let tx = stp.take_transaction()?;
+        Ok((tx_id, tx, fee, utxos_total_value))
+    }
+
     /// Return the Seed words for the current Master Key set in the Key Manager
     pub async fn get_seed_words(&self) -> Result<Vec<String>, OutputManagerError> {
-        Ok(from_secret_key(
-            self.key_manager.lock().await.master_key(),
-            &MnemonicLanguage::English,
-        )?)
+        self.key_manager.seed_words()
     }
 
     /// Return the public rewind keys
@@ -1115,14 +1541,40 @@ where TBackend: OutputManagerBackend + 'static
         }
     }
 
-    /// Attempt to rewind all of the given transaction outputs into unblinded outputs
-    async fn rewind_outputs(
-        &mut self,
+    /// Return the address that senders pay one-sided payments to this wallet with
+    fn get_one_sided_payment_address(&self) -> Result<OneSidedPaymentAddress, OutputManagerError> {
+        Ok(OneSidedPaymentAddress {
+            public_key: self.key_manager.public_key(KEY_MANAGER_ONE_SIDED_BRANCH_KEY, 0)?,
+            public_key_h: self.key_manager.diffie_hellman(
+                KEY_MANAGER_ONE_SIDED_BRANCH_KEY,
+                0,
+                &value_generator(&self.resources.factories.commitment),
+            )?,
+        })
+    }
+
+    /// Rewind the outputs that were paid to our one-sided payment address. The rewind data of a one-sided payment is
+    /// derived from the output's own commitment, so each output is rewound separately.
+    fn scan_for_one_sided_outputs(
+        &self,
         outputs: Vec<TransactionOutput>,
     ) -> Result<Vec<UnblindedOutput>, OutputManagerError>
     {
-        let rewind_data = &self.resources.rewind_data;
+        let mut rewound_outputs = Vec::new();
+        for output in outputs {
+            let shared_secret = self.key_manager.diffie_hellman(
+                KEY_MANAGER_ONE_SIDED_BRANCH_KEY,
+                0,
+                output.commitment.as_public_key(),
+            )?;
+            let rewind_data = recipient_rewind_data(&shared_secret);
+            rewound_outputs.extend(self.rewind_outputs(vec![output], &rewind_data));
+        }
+        Ok(rewound_outputs)
+    }
 
+    /// Attempt to rewind all of the given transaction outputs into unblinded outputs
+    fn rewind_outputs(&self, outputs: Vec<TransactionOutput>, rewind_data: &RewindData) -> Vec<UnblindedOutput> {
         let rewound_outputs: Vec<UnblindedOutput> = outputs
             .into_iter()
             .filter_map(|output| {
@@ -1152,20 +1604,26 @@ where TBackend: OutputManagerBackend + 'static
             );
         }
 
-        Ok(rewound_outputs)
+        rewound_outputs
     }
 
     async fn get_next_spend_key(&self) -> Result<PrivateKey, OutputManagerError> {
-        let mut km = self.key_manager.lock().await;
-        let key = km.next_key()?;
+        let (_, key) = self.get_next_spend_key_and_index().await?;
+        Ok(key)
+    }
+
+    /// Returns the next spending key and its index in the key manager branch
+    async fn get_next_spend_key_and_index(&self) -> Result<(u64, PrivateKey), OutputManagerError> {
+        let mut primary_key_index = self.primary_key_index.lock().await;
+        let index = *primary_key_index + 1;
+        let key = self.key_manager.spending_key(&self.key_manager_branch, index)?;
         self.resources.db.increment_key_index().await?;
-        Ok(key.k)
+        *primary_key_index = index;
+        Ok((index, key))
     }
 
     async fn get_coinbase_key_for_height(&self, height: u64) -> Result<PrivateKey, OutputManagerError> {
-        let km = self.coinbase_key_manager.lock().await;
-        let key = km.derive_key(height)?;
-        Ok(key.k)
+        self.key_manager.spending_key(KEY_MANAGER_COINBASE_BRANCH_KEY, height)
     }
 }
 
@@ -1214,6 +1672,12 @@ impl Balance {
             pending_outgoing_balance: Default::default(),
         }
     }
+
+    /// The portion of the available balance that can be spent right now, i.e. excluding any time-locked funds
+    pub fn spendable_balance(&self) -> MicroTari {
+        self.available_balance
+            .saturating_sub(self.time_locked_balance.unwrap_or_default())
+    }
 }
 
 impl fmt::Display for Balance {
@@ -32,7 +32,7 @@ use log::*;
 use std::{
     collections::HashMap,
     fmt::{Display, Error, Formatter},
-    sync::Arc,
+    sync::{Arc, Mutex},
     time::Duration,
 };
 use tari_core::transactions::{
@@ -55,7 +55,7 @@ pub trait OutputManagerBackend: Send + Sync + Clone {
     /// This method is called when a pending transaction is to be confirmed. It must move the `outputs_to_be_spent` and
     /// `outputs_to_be_received` from a `PendingTransactionOutputs` record into the `unspent_outputs` and
     /// `spent_outputs` collections.
-    fn confirm_transaction(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError>;
+    fn confirm_transaction(&self, tx_id: TxId, mined_height: Option<u64>) -> Result<(), OutputManagerStorageError>;
     /// This method encumbers the specified outputs into a `PendingTransactionOutputs` record. This is a short term
     /// encumberance in case the app is closed or crashes before transaction neogtiation is complete. These will be
     /// cleared on startup of the service.
@@ -84,8 +84,11 @@ pub trait OutputManagerBackend: Send + Sync + Clone {
     /// If an unspent output is detected as invalid (i.e. not available on the blockchain) then it should be moved to
     /// the invalid outputs collection. The function will return the last recorded TxId associated with this output.
     fn invalidate_unspent_output(&self, output: &DbUnblindedOutput) -> Result<Option<TxId>, OutputManagerStorageError>;
-    /// If an invalid output is found to be valid this function will turn it back into an unspent output
-    fn revalidate_unspent_output(&self, spending_key: &Commitment) -> Result<(), OutputManagerStorageError>;
+    /// If an invalid output is found to be valid this function will turn it back into an unspent output and return it
+    fn revalidate_unspent_output(
+        &self,
+        spending_key: &Commitment,
+    ) -> Result<DbUnblindedOutput, OutputManagerStorageError>;
     /// Check to see if there exist any pending transaction with a blockheight equal that provided and cancel those
     /// pending transaction outputs.
     fn cancel_pending_transaction_at_block_height(&self, block_height: u64) -> Result<(), OutputManagerStorageError>;
@@ -168,6 +171,70 @@ macro_rules! fetch {
     }};
 }
 
+/// The parts of the balance that are maintained incrementally as outputs change state, so that a balance query does
+/// not have to scan every output in the database. The time-locked balance depends on the chain tip and is still
+/// queried when requested.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct CachedBalance {
+    available_balance: MicroTari,
+    pending_incoming_balance: MicroTari,
+    pending_outgoing_balance: MicroTari,
+}
+
+impl CachedBalance {
+    /// Recompute the balance from all the unspent outputs and pending transactions in the backend
+    fn calculate<T: OutputManagerBackend>(db: &T) -> Result<Self, OutputManagerStorageError> {
+        let unspent_outputs = match db.fetch(&DbKey::UnspentOutputs)? {
+            Some(DbValue::UnspentOutputs(uo)) => uo,
+            Some(other) => return unexpected_result(DbKey::UnspentOutputs, other),
+            None => {
+                return Err(OutputManagerStorageError::UnexpectedResult(
+                    "Unspent Outputs cannot be retrieved".to_string(),
+                ))
+            },
+        };
+        let pending_txs = fetch_all_pending(db)?;
+
+        let mut balance = Self {
+            available_balance: sum_values(&unspent_outputs),
+            ..Default::default()
+        };
+        for pending_tx in pending_txs.values() {
+            balance.add_pending(
+                sum_values(&pending_tx.outputs_to_be_received),
+                sum_values(&pending_tx.outputs_to_be_spent),
+            );
+        }
+        Ok(balance)
+    }
+
+    fn add_pending(&mut self, incoming: MicroTari, outgoing: MicroTari) {
+        self.pending_incoming_balance += incoming;
+        self.pending_outgoing_balance += outgoing;
+    }
+
+    fn remove_pending(&mut self, pending_tx: &PendingTransactionOutputs) {
+        self.pending_incoming_balance = self
+            .pending_incoming_balance
+            .saturating_sub(sum_values(&pending_tx.outputs_to_be_received));
+        self.pending_outgoing_balance = self
+            .pending_outgoing_balance
+            .saturating_sub(sum_values(&pending_tx.outputs_to_be_spent));
+    }
+
+    /// The received outputs of a confirmed transaction become available
+    fn confirm_pending(&mut self, pending_tx: &PendingTransactionOutputs) {
+        self.remove_pending(pending_tx);
+        self.available_balance += sum_values(&pending_tx.outputs_to_be_received);
+    }
+
+    /// The outputs that a cancelled transaction would have spent become available again
+    fn cancel_pending(&mut self, pending_tx: &PendingTransactionOutputs) {
+        self.remove_pending(pending_tx);
+        self.available_balance += sum_values(&pending_tx.outputs_to_be_spent);
+    }
+}
+
 /// This structure holds an inner type that implements the `OutputManagerBackend` trait and contains the more complex
 /// data access logic required by the module built onto the functionality defined by the trait
 #[derive(Clone)]
@@ -175,13 +242,56 @@ pub struct OutputManagerDatabase<T>
 where T: OutputManagerBackend + 'static
 {
     db: Arc<T>,
+    balance_cache: Arc<Mutex<Option<CachedBalance>>>,
 }
 
 impl<T> OutputManagerDatabase<T>
 where T: OutputManagerBackend + 'static
 {
     pub fn new(db: T) -> Self {
-        Self { db: Arc::new(db) }
+        Self {
+            db: Arc::new(db),
+            balance_cache: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Run a backend operation while holding the balance cache so that the operation and its balance update are applied
+    /// atomically with respect to balance queries. The cached balance is only provided once it has been initialized.
+    async fn with_balance_cache<F, R>(&self, f: F) -> Result<R, OutputManagerStorageError>
+    where
+        F: FnOnce(&T, Option<&mut CachedBalance>) -> Result<R, OutputManagerStorageError> + Send + 'static,
+        R: Send + 'static,
+    {
+        let db_clone = self.db.clone();
+        let balance_cache = self.balance_cache.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut balance_cache = acquire_lock!(balance_cache);
+            f(db_clone.as_ref(), balance_cache.as_mut())
+        })
+        .await
+        .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
+        .and_then(|inner_result| inner_result)
+    }
+
+    /// Run a backend operation that may cancel any number of pending transactions and update the cached balance for
+    /// each pending transaction that was removed by it.
+    async fn with_pending_cancellations<F>(&self, f: F) -> Result<(), OutputManagerStorageError>
+    where F: FnOnce(&T) -> Result<(), OutputManagerStorageError> + Send + 'static {
+        self.with_balance_cache(move |db, balance| match balance {
+            Some(balance) => {
+                let pending_before = fetch_all_pending(db)?;
+                f(db)?;
+                let pending_after = fetch_all_pending(db)?;
+                for (tx_id, pending_tx) in pending_before.iter() {
+                    if !pending_after.contains_key(tx_id) {
+                        balance.cancel_pending(pending_tx);
+                    }
+                }
+                Ok(())
+            },
+            None => f(db),
+        })
+        .await
     }
 
     pub async fn get_key_manager_state(&self) -> Result<Option<KeyManagerState>, OutputManagerStorageError> {
@@ -217,95 +327,77 @@ where T: OutputManagerBackend + 'static
     }
 
     pub async fn add_unspent_output(&self, output: DbUnblindedOutput) -> Result<(), OutputManagerStorageError> {
-        let db_clone = self.db.clone();
-        tokio::task::spawn_blocking(move || {
-            db_clone.write(WriteOperation::Insert(DbKeyValuePair::UnspentOutput(
+        let value = output.unblinded_output.value;
+        self.with_balance_cache(move |db, balance| {
+            db.write(WriteOperation::Insert(DbKeyValuePair::UnspentOutput(
                 output.commitment.clone(),
                 Box::new(output),
-            )))
+            )))?;
+            if let Some(balance) = balance {
+                balance.available_balance += value;
+            }
+            Ok(())
         })
         .await
-        .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))??;
-
-        Ok(())
     }
 
+    /// Returns the balance of the wallet. The available and pending balances are maintained incrementally and are only
+    /// computed from all the stored outputs the first time they are requested.
     pub async fn get_balance(&self, current_chain_tip: Option<u64>) -> Result<Balance, OutputManagerStorageError> {
         let db_clone = self.db.clone();
-        let db_clone2 = self.db.clone();
-        let db_clone3 = self.db.clone();
-
-        let pending_txs = tokio::task::spawn_blocking(move || {
-            db_clone.fetch(&DbKey::AllPendingTransactionOutputs)?.ok_or_else(|| {
-                OutputManagerStorageError::UnexpectedResult(
-                    "Pending Transaction Outputs cannot be retrieved".to_string(),
-                )
-            })
+        let balance_cache = self.balance_cache.clone();
+
+        let balance = tokio::task::spawn_blocking(move || {
+            let mut balance_cache = acquire_lock!(balance_cache);
+            match balance_cache.as_ref() {
+                Some(balance) => Ok(balance.clone()),
+                None => {
+                    let balance = CachedBalance::calculate(db_clone.as_ref())?;
+                    *balance_cache = Some(balance.clone());
+                    Ok(balance)
+                },
+            }
         })
         .await
         .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))??;
 
-        let unspent_outputs = tokio::task::spawn_blocking(move || {
-            db_clone2.fetch(&DbKey::UnspentOutputs)?.ok_or_else(|| {
-                OutputManagerStorageError::UnexpectedResult("Unspent Outputs cannot be retrieved".to_string())
-            })
+        let time_locked_balance = match current_chain_tip {
+            Some(tip) => Some(sum_values(&self.get_timelocked_outputs(tip).await?)),
+            None => None,
+        };
+
+        Ok(Balance {
+            available_balance: balance.available_balance,
+            time_locked_balance,
+            pending_incoming_balance: balance.pending_incoming_balance,
+            pending_outgoing_balance: balance.pending_outgoing_balance,
         })
-        .await
-        .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))??;
+    }
 
-        if let DbValue::UnspentOutputs(uo) = unspent_outputs {
-            if let DbValue::AllPendingTransactionOutputs(pto) = pending_txs {
-                let available_balance = uo
-                    .iter()
-                    .fold(MicroTari::from(0), |acc, x| acc + x.unblinded_output.value);
-                let time_locked_balance = if let Some(tip) = current_chain_tip {
-                    let time_locked_outputs = tokio::task::spawn_blocking(move || {
-                        db_clone3.fetch(&DbKey::TimeLockedUnspentOutputs(tip))?.ok_or_else(|| {
-                            OutputManagerStorageError::UnexpectedResult(
-                                "Time-locked Outputs cannot be retrieved".to_string(),
-                            )
-                        })
-                    })
-                    .await
-                    .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))??;
-                    if let DbValue::UnspentOutputs(time_locked_uo) = time_locked_outputs {
-                        Some(
-                            time_locked_uo
-                                .iter()
-                                .fold(MicroTari::from(0), |acc, x| acc + x.unblinded_output.value),
-                        )
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
-                let mut pending_incoming = MicroTari::from(0);
-                let mut pending_outgoing = MicroTari::from(0);
-
-                for v in pto.values() {
-                    pending_incoming += v
-                        .outputs_to_be_received
-                        .iter()
-                        .fold(MicroTari::from(0), |acc, x| acc + x.unblinded_output.value);
-                    pending_outgoing += v
-                        .outputs_to_be_spent
-                        .iter()
-                        .fold(MicroTari::from(0), |acc, x| acc + x.unblinded_output.value);
-                }
+    /// Compares the incrementally maintained balance against a full recompute from the stored outputs. Returns `false`
+    /// if the cached balance had drifted, in which case it is replaced by the recomputed balance.
+    pub async fn check_balance_cache(&self) -> Result<bool, OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        let balance_cache = self.balance_cache.clone();
 
-                return Ok(Balance {
-                    available_balance,
-                    time_locked_balance,
-                    pending_incoming_balance: pending_incoming,
-                    pending_outgoing_balance: pending_outgoing,
-                });
+        tokio::task::spawn_blocking(move || {
+            let mut balance_cache = acquire_lock!(balance_cache);
+            let balance = CachedBalance::calculate(db_clone.as_ref())?;
+            let consistent = balance_cache.as_ref().map_or(true, |cached| *cached == balance);
+            if !consistent {
+                warn!(
+                    target: LOG_TARGET,
+                    "Cached balance {:?} does not match the recomputed balance {:?}, resetting it",
+                    balance_cache,
+                    balance
+                );
             }
-        }
-
-        Err(OutputManagerStorageError::UnexpectedResult(
-            "Unexpected result from database backend".to_string(),
-        ))
+            *balance_cache = Some(balance);
+            Ok(consistent)
+        })
+        .await
+        .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
+        .and_then(|inner_result| inner_result)
     }
 
     pub async fn add_pending_transaction_outputs(
@@ -313,17 +405,19 @@ where T: OutputManagerBackend + 'static
         pending_transaction_outputs: PendingTransactionOutputs,
     ) -> Result<(), OutputManagerStorageError>
     {
-        let db_clone = self.db.clone();
-        tokio::task::spawn_blocking(move || {
-            db_clone.write(WriteOperation::Insert(DbKeyValuePair::PendingTransactionOutputs(
+        let incoming = sum_values(&pending_transaction_outputs.outputs_to_be_received);
+        let outgoing = sum_values(&pending_transaction_outputs.outputs_to_be_spent);
+        self.with_balance_cache(move |db, balance| {
+            db.write(WriteOperation::Insert(DbKeyValuePair::PendingTransactionOutputs(
                 pending_transaction_outputs.tx_id,
                 Box::new(pending_transaction_outputs),
-            )))
+            )))?;
+            if let Some(balance) = balance {
+                balance.add_pending(incoming, outgoing);
+            }
+            Ok(())
         })
         .await
-        .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))??;
-
-        Ok(())
     }
 
     pub async fn fetch_pending_transaction_outputs(
@@ -340,13 +434,23 @@ where T: OutputManagerBackend + 'static
 
     /// This method is called when a pending transaction is confirmed. It moves the `outputs_to_be_spent` and
     /// `outputs_to_be_received` from a `PendingTransactionOutputs` record into the `unspent_outputs` and
-    /// `spent_outputs` collections.
-    pub async fn confirm_pending_transaction_outputs(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError> {
-        let db_clone = self.db.clone();
-        tokio::task::spawn_blocking(move || db_clone.confirm_transaction(tx_id))
-            .await
-            .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
-            .and_then(|inner_result| inner_result)
+    /// `spent_outputs` collections. The received outputs are recorded as mined at `mined_height`, if it is known.
+    pub async fn confirm_pending_transaction_outputs(
+        &self,
+        tx_id: TxId,
+        mined_height: Option<u64>,
+    ) -> Result<(), OutputManagerStorageError>
+    {
+        self.with_balance_cache(move |db, balance| match balance {
+            Some(balance) => {
+                let pending_tx = fetch!(db, tx_id, PendingTransactionOutputs)?;
+                db.confirm_transaction(tx_id, mined_height)?;
+                balance.confirm_pending(&pending_tx);
+                Ok(())
+            },
+            None => db.confirm_transaction(tx_id, mined_height),
+        })
+        .await
     }
 
     /// This method accepts and stores a pending inbound transaction and creates the `output_to_be_received` from the
@@ -361,13 +465,12 @@ where T: OutputManagerBackend + 'static
         coinbase_block_height: Option<u64>,
     ) -> Result<(), OutputManagerStorageError>
     {
-        let db_clone = self.db.clone();
         let output = DbUnblindedOutput::from_unblinded_output(
             UnblindedOutput::new(amount, spending_key.clone(), Some(output_features)),
             factory,
         )?;
-        tokio::task::spawn_blocking(move || {
-            db_clone.write(WriteOperation::Insert(DbKeyValuePair::PendingTransactionOutputs(
+        self.with_balance_cache(move |db, balance| {
+            db.write(WriteOperation::Insert(DbKeyValuePair::PendingTransactionOutputs(
                 tx_id,
                 Box::new(PendingTransactionOutputs {
                     tx_id,
@@ -376,11 +479,13 @@ where T: OutputManagerBackend + 'static
                     timestamp: Utc::now().naive_utc(),
                     coinbase_block_height,
                 }),
-            )))
+            )))?;
+            if let Some(balance) = balance {
+                balance.add_pending(amount, MicroTari::from(0));
+            }
+            Ok(())
         })
         .await
-        .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))??;
-        Ok(())
     }
 
     /// This method is called when a transaction is built to be sent. It will encumber unspent outputs against a pending
@@ -392,13 +497,16 @@ where T: OutputManagerBackend + 'static
         outputs_to_receive: Vec<DbUnblindedOutput>,
     ) -> Result<(), OutputManagerStorageError>
     {
-        let db_clone = self.db.clone();
-        tokio::task::spawn_blocking(move || {
-            db_clone.short_term_encumber_outputs(tx_id, &outputs_to_send, &outputs_to_receive)
+        self.with_balance_cache(move |db, balance| {
+            db.short_term_encumber_outputs(tx_id, &outputs_to_send, &outputs_to_receive)?;
+            if let Some(balance) = balance {
+                let outgoing = sum_values(&outputs_to_send);
+                balance.available_balance = balance.available_balance.saturating_sub(outgoing);
+                balance.add_pending(sum_values(&outputs_to_receive), outgoing);
+            }
+            Ok(())
         })
         .await
-        .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
-        .and_then(|inner_result| inner_result)
     }
 
     /// This method is called when a transaction is finished being negotiated. This will fully encumber the outputs
@@ -414,31 +522,30 @@ where T: OutputManagerBackend + 'static
     /// Clear all pending transaction encumberances marked as short term. These are the result of an unfinished
     /// transaction negotiation
     pub async fn clear_short_term_encumberances(&self) -> Result<(), OutputManagerStorageError> {
-        let db_clone = self.db.clone();
-        tokio::task::spawn_blocking(move || db_clone.clear_short_term_encumberances())
+        self.with_pending_cancellations(|db| db.clear_short_term_encumberances())
             .await
-            .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
-            .and_then(|inner_result| inner_result)
     }
 
     /// When a pending transaction is cancelled the encumbered outputs are moved back to the `unspent_outputs`
     /// collection.
     pub async fn cancel_pending_transaction_outputs(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError> {
-        let db_clone = self.db.clone();
-        tokio::task::spawn_blocking(move || db_clone.cancel_pending_transaction(tx_id))
-            .await
-            .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
-            .and_then(|inner_result| inner_result)
+        self.with_balance_cache(move |db, balance| match balance {
+            Some(balance) => {
+                let pending_tx = fetch!(db, tx_id, PendingTransactionOutputs)?;
+                db.cancel_pending_transaction(tx_id)?;
+                balance.cancel_pending(&pending_tx);
+                Ok(())
+            },
+            None => db.cancel_pending_transaction(tx_id),
+        })
+        .await
     }
 
     /// This method is check all pending transactions to see if any are older that the provided duration. If they are
     /// they will be cancelled.
     pub async fn timeout_pending_transaction_outputs(&self, period: Duration) -> Result<(), OutputManagerStorageError> {
-        let db_clone = self.db.clone();
-        tokio::task::spawn_blocking(move || db_clone.timeout_pending_transactions(period))
+        self.with_pending_cancellations(move |db| db.timeout_pending_transactions(period))
             .await
-            .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
-            .and_then(|inner_result| inner_result)
     }
 
     /// Retrieves UTXOs sorted by value from smallest to largest.
@@ -572,19 +679,25 @@ where T: OutputManagerBackend + 'static
         output: DbUnblindedOutput,
     ) -> Result<Option<TxId>, OutputManagerStorageError>
     {
-        let db_clone = self.db.clone();
-        tokio::task::spawn_blocking(move || db_clone.invalidate_unspent_output(&output))
-            .await
-            .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
-            .and_then(|inner_result| inner_result)
+        self.with_balance_cache(move |db, balance| {
+            let tx_id = db.invalidate_unspent_output(&output)?;
+            if let Some(balance) = balance {
+                balance.available_balance = balance.available_balance.saturating_sub(output.unblinded_output.value);
+            }
+            Ok(tx_id)
+        })
+        .await
     }
 
     pub async fn revalidate_output(&self, commitment: Commitment) -> Result<(), OutputManagerStorageError> {
-        let db_clone = self.db.clone();
-        tokio::task::spawn_blocking(move || db_clone.revalidate_unspent_output(&commitment))
-            .await
-            .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
-            .and_then(|inner_result| inner_result)
+        self.with_balance_cache(move |db, balance| {
+            let output = db.revalidate_unspent_output(&commitment)?;
+            if let Some(balance) = balance {
+                balance.available_balance += output.unblinded_output.value;
+            }
+            Ok(())
+        })
+        .await
     }
 
     pub async fn update_spent_output_to_unspent(
@@ -592,11 +705,14 @@ where T: OutputManagerBackend + 'static
         commitment: Commitment,
     ) -> Result<DbUnblindedOutput, OutputManagerStorageError>
     {
-        let db_clone = self.db.clone();
-        tokio::task::spawn_blocking(move || db_clone.update_spent_output_to_unspent(&commitment))
-            .await
-            .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
-            .and_then(|inner_result| inner_result)
+        self.with_balance_cache(move |db, balance| {
+            let output = db.update_spent_output_to_unspent(&commitment)?;
+            if let Some(balance) = balance {
+                balance.available_balance += output.unblinded_output.value;
+            }
+            Ok(output)
+        })
+        .await
     }
 
     pub async fn cancel_pending_transaction_at_block_height(
@@ -604,11 +720,8 @@ where T: OutputManagerBackend + 'static
         block_height: u64,
     ) -> Result<(), OutputManagerStorageError>
     {
-        let db_clone = self.db.clone();
-        tokio::task::spawn_blocking(move || db_clone.cancel_pending_transaction_at_block_height(block_height))
+        self.with_pending_cancellations(move |db| db.cancel_pending_transaction_at_block_height(block_height))
             .await
-            .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
-            .and_then(|inner_result| inner_result)
     }
 
     pub async fn apply_encryption(&self, cipher: Aes256Gcm) -> Result<(), OutputManagerStorageError> {
@@ -628,6 +741,22 @@ where T: OutputManagerBackend + 'static
     }
 }
 
+fn fetch_all_pending<T: OutputManagerBackend>(
+    db: &T,
+) -> Result<HashMap<TxId, PendingTransactionOutputs>, OutputManagerStorageError> {
+    match db.fetch(&DbKey::AllPendingTransactionOutputs)? {
+        Some(DbValue::AllPendingTransactionOutputs(pto)) => Ok(pto),
+        Some(other) => unexpected_result(DbKey::AllPendingTransactionOutputs, other),
+        None => Err(OutputManagerStorageError::UnexpectedResult(
+            "Pending Transaction Outputs cannot be retrieved".to_string(),
+        )),
+    }
+}
+
+fn sum_values(outputs: &[DbUnblindedOutput]) -> MicroTari {
+    outputs.iter().map(|o| o.unblinded_output.value).sum()
+}
+
 fn unexpected_result<T>(req: DbKey, res: DbValue) -> Result<T, OutputManagerStorageError> {
     let msg = format!("Unexpected result for database query {}. Response: {}", req, res);
     error!(target: LOG_TARGET, "{}", msg);
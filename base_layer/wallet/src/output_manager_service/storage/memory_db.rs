@@ -220,7 +220,7 @@ impl OutputManagerBackend for OutputManagerMemoryDatabase {
         Ok(None)
     }
 
-    fn confirm_transaction(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError> {
+    fn confirm_transaction(&self, tx_id: TxId, mined_height: Option<u64>) -> Result<(), OutputManagerStorageError> {
         let mut db = acquire_write_lock!(self.db);
 
         let mut pending_tx = db.pending_transactions.remove(&tx_id);
@@ -240,10 +240,11 @@ impl OutputManagerBackend for OutputManagerMemoryDatabase {
         }
 
         // Add Unspent outputs
-        for o in pending_tx.outputs_to_be_received.drain(..) {
+        for mut o in pending_tx.outputs_to_be_received.drain(..) {
             if db.unspent_outputs.iter().any(|uo| uo.output == o) {
                 return Err(OutputManagerStorageError::DuplicateOutput);
             }
+            o.mined_height = mined_height;
             db.unspent_outputs.push(DbOutput::new(tx_id, o));
         }
 
@@ -385,7 +386,11 @@ impl OutputManagerBackend for OutputManagerMemoryDatabase {
         }
     }
 
-    fn revalidate_unspent_output(&self, commitment: &Commitment) -> Result<(), OutputManagerStorageError> {
+    fn revalidate_unspent_output(
+        &self,
+        commitment: &Commitment,
+    ) -> Result<DbUnblindedOutput, OutputManagerStorageError>
+    {
         let mut db = acquire_write_lock!(self.db);
         match db
             .invalid_outputs
@@ -394,8 +399,8 @@ impl OutputManagerBackend for OutputManagerMemoryDatabase {
         {
             Some(pos) => {
                 let output = db.invalid_outputs.remove(pos);
-                db.unspent_outputs.push(output);
-                Ok(())
+                db.unspent_outputs.push(output.clone());
+                Ok(output.output)
             },
             None => Err(OutputManagerStorageError::ValuesNotFound),
         }
@@ -36,6 +36,8 @@ pub struct DbUnblindedOutput {
     pub commitment: Commitment,
     pub unblinded_output: UnblindedOutput,
     pub hash: HashOutput,
+    /// The height of the block the output was mined in, if known
+    pub mined_height: Option<u64>,
 }
 
 impl DbUnblindedOutput {
@@ -49,6 +51,7 @@ impl DbUnblindedOutput {
             hash: tx_out.hash(),
             commitment: tx_out.commitment,
             unblinded_output: output,
+            mined_height: None,
         })
     }
 
@@ -63,6 +66,7 @@ impl DbUnblindedOutput {
             hash: tx_out.hash(),
             commitment: tx_out.commitment,
             unblinded_output: output,
+            mined_height: None,
         })
     }
 }
@@ -358,7 +358,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         Ok(None)
     }
 
-    fn confirm_transaction(&self, tx_id: u64) -> Result<(), OutputManagerStorageError> {
+    fn confirm_transaction(&self, tx_id: u64, mined_height: Option<u64>) -> Result<(), OutputManagerStorageError> {
         let conn = self.database_connection.acquire_lock();
 
         match PendingTransactionOutputSql::find(tx_id, &(*conn)) {
@@ -372,6 +372,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                                 status: Some(OutputStatus::Unspent),
                                 tx_id: None,
                                 spending_key: None,
+                                mined_height,
                             },
                             &(*conn),
                         )?;
@@ -381,6 +382,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                                 status: Some(OutputStatus::Spent),
                                 tx_id: None,
                                 spending_key: None,
+                                mined_height: None,
                             },
                             &(*conn),
                         )?;
@@ -426,6 +428,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                     status: Some(OutputStatus::EncumberedToBeSpent),
                     tx_id: Some(tx_id),
                     spending_key: None,
+                    mined_height: None,
                 },
                 &(*conn),
             )?;
@@ -489,6 +492,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                                 status: Some(OutputStatus::CancelledInbound),
                                 tx_id: None,
                                 spending_key: None,
+                                mined_height: None,
                             },
                             &(*conn),
                         )?;
@@ -498,6 +502,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                                 status: Some(OutputStatus::Unspent),
                                 tx_id: None,
                                 spending_key: None,
+                                mined_height: None,
                             },
                             &(*conn),
                         )?;
@@ -553,6 +558,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                 status: Some(OutputStatus::Invalid),
                 tx_id: None,
                 spending_key: None,
+                mined_height: None,
             },
             &(*conn),
         )?;
@@ -560,22 +566,29 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         Ok(tx_id)
     }
 
-    fn revalidate_unspent_output(&self, commitment: &Commitment) -> Result<(), OutputManagerStorageError> {
+    fn revalidate_unspent_output(
+        &self,
+        commitment: &Commitment,
+    ) -> Result<DbUnblindedOutput, OutputManagerStorageError>
+    {
         let conn = self.database_connection.acquire_lock();
         let output = OutputSql::find_by_commitment(&commitment.to_vec(), &conn)?;
 
         if OutputStatus::try_from(output.status)? != OutputStatus::Invalid {
             return Err(OutputManagerStorageError::ValuesNotFound);
         }
-        let _ = output.update(
+        let mut o = output.update(
             UpdateOutput {
                 status: Some(OutputStatus::Unspent),
                 tx_id: None,
                 spending_key: None,
+                mined_height: None,
             },
             &(*conn),
         )?;
-        Ok(())
+        self.decrypt_if_necessary(&mut o)?;
+
+        Ok(DbUnblindedOutput::try_from(o)?)
     }
 
     fn update_spent_output_to_unspent(
@@ -595,6 +608,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                 status: Some(OutputStatus::Unspent),
                 tx_id: None,
                 spending_key: None,
+                mined_height: None,
             },
             &(*conn),
         )?;
@@ -755,6 +769,7 @@ struct NewOutputSql {
     status: i32,
     tx_id: Option<i64>,
     hash: Option<Vec<u8>>,
+    mined_height: Option<i64>,
 }
 
 impl NewOutputSql {
@@ -768,6 +783,7 @@ impl NewOutputSql {
             status: status as i32,
             tx_id: tx_id.map(|i| i as i64),
             hash: Some(output.hash),
+            mined_height: output.mined_height.map(|h| h as i64),
         }
     }
 
@@ -802,6 +818,7 @@ struct OutputSql {
     status: i32,
     tx_id: Option<i64>,
     hash: Option<Vec<u8>>,
+    mined_height: Option<i64>,
 }
 
 impl OutputSql {
@@ -933,6 +950,7 @@ impl OutputSql {
                 status: None,
                 tx_id: None,
                 spending_key: Some(self.spending_key.clone()),
+                mined_height: None,
             },
             conn,
         )?;
@@ -981,6 +999,7 @@ impl TryFrom<OutputSql> for DbUnblindedOutput {
             commitment,
             unblinded_output,
             hash,
+            mined_height: o.mined_height.map(|h| h as u64),
         })
     }
 }
@@ -1008,6 +1027,7 @@ impl From<OutputSql> for NewOutputSql {
             status: o.status,
             tx_id: o.tx_id,
             hash: o.hash,
+            mined_height: o.mined_height,
         }
     }
 }
@@ -1023,6 +1043,7 @@ pub struct UpdateOutput {
     status: Option<OutputStatus>,
     tx_id: Option<TxId>,
     spending_key: Option<Vec<u8>>,
+    mined_height: Option<u64>,
 }
 
 #[derive(AsChangeset)]
@@ -1031,6 +1052,7 @@ pub struct UpdateOutputSql {
     status: Option<i32>,
     tx_id: Option<i64>,
     spending_key: Option<Vec<u8>>,
+    mined_height: Option<i64>,
 }
 
 #[derive(AsChangeset)]
@@ -1048,6 +1070,7 @@ impl From<UpdateOutput> for UpdateOutputSql {
             status: u.status.map(|t| t as i32),
             tx_id: u.tx_id.map(|t| t as i64),
             spending_key: u.spending_key,
+            mined_height: u.mined_height.map(|h| h as i64),
         }
     }
 }
@@ -1432,6 +1455,7 @@ mod test {
                     status: Some(OutputStatus::Unspent),
                     tx_id: Some(44u64),
                     spending_key: None,
+                    mined_height: None,
                 },
                 &conn,
             )
@@ -1444,6 +1468,7 @@ mod test {
                     status: Some(OutputStatus::EncumberedToBeReceived),
                     tx_id: Some(44u64),
                     spending_key: None,
+                    mined_height: None,
                 },
                 &conn,
             )
@@ -24,6 +24,7 @@ table! {
         valid -> Integer,
         confirmations -> Nullable<BigInt>,
         mined_height -> Nullable<BigInt>,
+        one_sided_payment_public_key -> Nullable<Binary>,
     }
 }
 
@@ -86,6 +87,7 @@ table! {
         status -> Integer,
         tx_id -> Nullable<BigInt>,
         hash -> Nullable<Binary>,
+        mined_height -> Nullable<BigInt>,
     }
 }
 
@@ -98,6 +100,16 @@ table! {
     }
 }
 
+table! {
+    pruned_transaction_summary (id) {
+        id -> Integer,
+        num_transactions -> BigInt,
+        total_received -> BigInt,
+        total_sent -> BigInt,
+        total_fees -> BigInt,
+    }
+}
+
 table! {
     wallet_settings (key) {
         key -> Text,
@@ -114,5 +126,6 @@ allow_tables_to_appear_in_same_query!(
     outbound_transactions,
     outputs,
     pending_transaction_outputs,
+    pruned_transaction_summary,
     wallet_settings,
 );
@@ -58,6 +58,10 @@ use tari_crypto::{
 
 const LOG_TARGET: &str = "wallet::storage::sqlite_db";
 
+/// The wallet setting holding a known value encrypted with the wallet's cipher, used to check a password on startup
+const ENCRYPTION_CANARY_KEY: &str = "EncryptionCanary";
+const ENCRYPTION_CANARY_VALUE: &[u8] = b"tari wallet encryption canary";
+
 /// A Sqlite backend for the Output Manager Service. The Backend is accessed via a connection pool to the Sqlite file.
 #[derive(Clone)]
 pub struct WalletSqliteDatabase {
@@ -167,6 +171,40 @@ impl WalletSqliteDatabase {
         })
     }
 
+    /// Check that the provided cipher decrypts the encryption canary written when the wallet was encrypted, so that a
+    /// wrong password fails before anything else is read from the database. Wallets that were encrypted before the
+    /// canary existed have none and are validated by their Comms Secret Key in `new` instead.
+    pub fn check_encryption_canary(
+        database_connection: &WalletDbConnection,
+        cipher: Option<&Aes256Gcm>,
+    ) -> Result<(), WalletStorageError>
+    {
+        let conn = database_connection.acquire_lock();
+        let canary = match WalletSettingSql::get(ENCRYPTION_CANARY_KEY.to_string(), &conn)? {
+            None => return Ok(()),
+            Some(canary) => canary,
+        };
+
+        let cipher = match cipher {
+            None => {
+                error!(
+                    target: LOG_TARGET,
+                    "Cipher was not provided but the database is encrypted"
+                );
+                return Err(WalletStorageError::NoPasswordError);
+            },
+            Some(cipher) => cipher,
+        };
+
+        match decrypt_bytes_integral_nonce(cipher, from_hex(canary.as_str())?) {
+            Ok(value) if value.as_slice() == ENCRYPTION_CANARY_VALUE => Ok(()),
+            _ => {
+                error!(target: LOG_TARGET, "Incorrect password (encryption canary mismatch)");
+                Err(WalletStorageError::IncorrectPassword)
+            },
+        }
+    }
+
     fn set_comms_private_key(
         &self,
         secret_key: &CommsSecretKey,
@@ -482,6 +520,10 @@ impl WalletBackend for WalletSqliteDatabase {
             WalletSettingSql::new(DbKey::TorId.to_string(), ciphertext_integral_nonce.to_hex()).set(&conn)?;
         }
 
+        let canary = encrypt_bytes_integral_nonce(&cipher, ENCRYPTION_CANARY_VALUE.to_vec())
+            .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e.to_string())))?;
+        WalletSettingSql::new(ENCRYPTION_CANARY_KEY.to_string(), canary.to_hex()).set(&conn)?;
+
         (*current_cipher) = Some(cipher);
 
         Ok(())
@@ -526,6 +568,8 @@ impl WalletBackend for WalletSqliteDatabase {
             WalletSettingSql::new(DbKey::TorId.to_string(), tor_string).set(&conn)?;
         }
 
+        let _ = WalletSettingSql::clear(ENCRYPTION_CANARY_KEY.to_string(), &conn)?;
+
         // Now that all the decryption has been completed we can safely remove the cipher fully
         let _ = (*current_cipher).take();
 
@@ -637,18 +681,23 @@ impl Encryptable<Aes256Gcm> for ClientKeyValueSql {
 
 #[cfg(test)]
 mod test {
-    use crate::storage::{
-        database::{DbKey, DbValue, WalletBackend},
-        sqlite_db::{ClientKeyValueSql, WalletSettingSql, WalletSqliteDatabase},
-        sqlite_utilities::run_migration_and_create_sqlite_connection,
+    use crate::{
+        error::WalletStorageError,
+        storage::{
+            database::{DbKey, DbValue, WalletBackend},
+            sqlite_db::{ClientKeyValueSql, WalletSettingSql, WalletSqliteDatabase},
+            sqlite_utilities::{initialize_sqlite_database_backends, run_migration_and_create_sqlite_connection},
+        },
     };
     use aes_gcm::{
         aead::{generic_array::GenericArray, Aead, NewAead},
         Aes256Gcm,
     };
+    use digest::Digest;
     use rand::{rngs::OsRng, RngCore};
     use tari_comms::types::{CommsPublicKey, CommsSecretKey};
     use tari_crypto::{
+        common::Blake256,
         keys::{PublicKey, SecretKey},
         tari_utilities::{hex::Hex, ByteArray},
     };
@@ -857,6 +906,46 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_wrong_password_fails_encryption_canary_check() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_tempdir = tempdir().unwrap();
+        let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}/{}", db_folder, db_name);
+
+        {
+            let (db, _, _, _) = initialize_sqlite_database_backends(db_path.clone().into(), None).unwrap();
+            let conn = db.database_connection.acquire_lock();
+            db.set_comms_private_key(&CommsSecretKey::random(&mut OsRng), &conn)
+                .unwrap();
+        }
+        {
+            let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+            let db = WalletSqliteDatabase::new(connection.clone(), None).unwrap();
+            let passphrase_hash = Blake256::new().chain(b"correct horse").result();
+            db.apply_encryption(Aes256Gcm::new(GenericArray::from_slice(passphrase_hash.as_slice())))
+                .unwrap();
+
+            // Without the Comms Secret Key only the canary can tell that a password is wrong
+            let conn = connection.acquire_lock();
+            WalletSettingSql::clear(DbKey::CommsSecretKey.to_string(), &conn).unwrap();
+        }
+
+        assert!(matches!(
+            initialize_sqlite_database_backends(db_path.clone().into(), Some("wrong horse".to_string())),
+            Err(WalletStorageError::IncorrectPassword)
+        ));
+        assert!(matches!(
+            initialize_sqlite_database_backends(db_path.clone().into(), None),
+            Err(WalletStorageError::NoPasswordError)
+        ));
+
+        let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+        let passphrase_hash = Blake256::new().chain(b"correct horse").result();
+        let cipher = Aes256Gcm::new(GenericArray::from_slice(passphrase_hash.as_slice()));
+        WalletSqliteDatabase::check_encryption_canary(&connection, Some(&cipher)).unwrap();
+    }
+
     #[test]
     fn test_client_key_value_store() {
         let db_name = format!("{}.sqlite3", string(8).as_str());
@@ -44,6 +44,9 @@ use tari_crypto::common::Blake256;
 
 const LOG_TARGET: &str = "wallet::storage:sqlite_utilities";
 
+/// The versions of the migrations whose `down.sql` cannot restore the database to its previous state
+const IRREVERSIBLE_MIGRATIONS: &[&str] = &["20190626130555"];
+
 #[derive(Clone)]
 pub struct WalletDbConnection {
     pub connection: Arc<Mutex<SqliteConnection>>,
@@ -82,6 +85,47 @@ pub fn run_migration_and_create_sqlite_connection<P: AsRef<Path>>(
     Ok(WalletDbConnection::new(connection, Some(file_lock)))
 }
 
+/// A database migration that has not been applied to a wallet database yet
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingMigration {
+    pub version: String,
+    pub reversible: bool,
+}
+
+/// Report the migrations that opening the wallet database at `db_path` would run, without applying them. The
+/// migrations are run inside a transaction that is always rolled back, so the database is left untouched.
+pub fn check_pending_migrations<P: AsRef<Path>>(db_path: P) -> Result<Vec<PendingMigration>, WalletStorageError> {
+    let _file_lock = acquire_exclusive_file_lock(&db_path.as_ref().to_path_buf())?;
+
+    let path_str = db_path
+        .as_ref()
+        .to_str()
+        .ok_or_else(|| WalletStorageError::InvalidUnicodePath)?;
+    let connection = SqliteConnection::establish(path_str)?;
+    connection.execute("PRAGMA busy_timeout = 60000;")?;
+
+    embed_migrations!("./migrations");
+    let mut output = Vec::new();
+    let mut migration_result = Ok(());
+    let _ = connection.transaction::<(), diesel::result::Error, _>(|| {
+        migration_result = embedded_migrations::run_with_output(&connection, &mut output);
+        Err(diesel::result::Error::RollbackTransaction)
+    });
+    migration_result
+        .map_err(|err| WalletStorageError::DatabaseMigrationError(format!("Database migration failed {}", err)))?;
+
+    let pending = String::from_utf8_lossy(&output)
+        .lines()
+        .filter_map(|line| line.strip_prefix("Running migration "))
+        .map(|version| PendingMigration {
+            version: version.to_string(),
+            reversible: !IRREVERSIBLE_MIGRATIONS.contains(&version),
+        })
+        .collect();
+
+    Ok(pending)
+}
+
 /// This function will copy a wallet database to the provided path and then clear the CommsPrivateKey from the database.
 pub async fn partial_wallet_backup<P: AsRef<Path>>(current_db: P, backup_path: P) -> Result<(), WalletStorageError> {
     // Copy the current db to the backup path
@@ -163,6 +207,8 @@ pub fn initialize_sqlite_database_backends(
         e
     })?;
 
+    WalletSqliteDatabase::check_encryption_canary(&connection, cipher.as_ref())?;
+
     let wallet_backend = WalletSqliteDatabase::new(connection.clone(), cipher.clone())?;
     let transaction_backend = TransactionServiceSqliteDatabase::new(connection.clone(), cipher.clone());
     let output_manager_backend = OutputManagerSqliteDatabase::new(connection.clone(), cipher);
@@ -175,3 +221,78 @@ pub fn initialize_sqlite_database_backends(
         contacts_backend,
     ))
 }
+
+#[cfg(test)]
+mod test {
+    use crate::storage::sqlite_utilities::{
+        check_pending_migrations,
+        run_migration_and_create_sqlite_connection,
+        IRREVERSIBLE_MIGRATIONS,
+    };
+    use diesel::{sql_query, sql_types::BigInt, Connection, RunQueryDsl, SqliteConnection};
+    use std::{fs, path::Path};
+    use tari_test_utils::random::string;
+    use tempfile::tempdir;
+
+    #[derive(QueryableByName)]
+    struct Count {
+        #[sql_type = "BigInt"]
+        count: i64,
+    }
+
+    fn migration_dirs() -> Vec<(String, String)> {
+        let migrations = Path::new(env!("CARGO_MANIFEST_DIR")).join("migrations");
+        let mut dirs = fs::read_dir(migrations)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.join("up.sql").exists())
+            .map(|path| {
+                let name = path.file_name().unwrap().to_str().unwrap().to_string();
+                let version = name.split('_').next().unwrap().replace('-', "");
+                let down_sql = fs::read_to_string(path.join("down.sql")).unwrap_or_default();
+                (version, down_sql)
+            })
+            .collect::<Vec<_>>();
+        dirs.sort();
+        dirs
+    }
+
+    #[test]
+    fn test_irreversible_migrations_are_listed() {
+        let irreversible = migration_dirs()
+            .into_iter()
+            .filter(|(_, down_sql)| {
+                down_sql
+                    .lines()
+                    .all(|line| line.trim().is_empty() || line.trim().starts_with("--"))
+            })
+            .map(|(version, _)| version)
+            .collect::<Vec<_>>();
+        assert_eq!(irreversible, IRREVERSIBLE_MIGRATIONS);
+    }
+
+    #[test]
+    fn test_check_pending_migrations_does_not_modify_db() {
+        let db_tempdir = tempdir().unwrap();
+        let db_path = db_tempdir.path().join(format!("{}.sqlite3", string(8)));
+
+        let pending = check_pending_migrations(&db_path).unwrap();
+        let versions = pending.iter().map(|m| m.version.clone()).collect::<Vec<_>>();
+        let expected = migration_dirs().into_iter().map(|(v, _)| v).collect::<Vec<_>>();
+        assert_eq!(versions, expected);
+        assert!(!pending[0].reversible);
+        assert!(pending[1..].iter().all(|m| m.reversible));
+
+        {
+            let conn = SqliteConnection::establish(db_path.to_str().unwrap()).unwrap();
+            let tables = sql_query("SELECT COUNT(*) AS count FROM sqlite_master WHERE type = 'table'")
+                .get_result::<Count>(&conn)
+                .unwrap();
+            assert_eq!(tables.count, 0);
+        }
+
+        let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+        drop(connection);
+        assert!(check_pending_migrations(&db_path).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,454 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
+    error::WalletError,
+    output_manager_service::{
+        error::{OutputManagerError, OutputManagerStorageError},
+        handle::OutputManagerHandle,
+        TxId,
+    },
+    storage::database::{WalletBackend, WalletDatabase},
+    transaction_service::handle::TransactionServiceHandle,
+};
+use chrono::Utc;
+use digest::Digest;
+use futures::StreamExt;
+use log::*;
+use std::{
+    convert::TryFrom,
+    fmt,
+    fmt::{Display, Formatter},
+    str::FromStr,
+    time::Duration,
+};
+use tari_common_types::types::BlockHash;
+use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeId, types::CommsPublicKey};
+use tari_core::{
+    base_node::sync::rpc::BaseNodeSyncRpcClient,
+    blocks::BlockHeader,
+    proto::base_node::SyncUtxosRequest,
+    tari_utilities::{hex::Hex, ByteArray, Hashable},
+    transactions::{
+        transaction::{TransactionOutput, UnblindedOutput},
+        types::CommitmentFactory,
+    },
+};
+use tari_crypto::common::Blake256;
+use tari_shutdown::ShutdownSignal;
+
+pub const LOG_TARGET: &str = "wallet::one_sided_scanner";
+
+/// The position in the output MMR from which the next scan starts
+pub const ONE_SIDED_SCAN_UTXO_INDEX_KEY: &str = "one-sided/utxo-index";
+/// The chain tips that previous scans reached, used to find where to rescan from after a reorg
+pub const ONE_SIDED_SCAN_HEADERS_KEY: &str = "one-sided/scanned-headers";
+
+/// The number of previously scanned chain tips that are kept. After a reorg deeper than all of them, all UTXOs are
+/// scanned again.
+const MAX_SCANNED_HEADERS: usize = 100;
+
+/// Scans the UTXOs added to the chain for one-sided payments to this wallet whenever the base node reports a new chain
+/// tip. One-sided payments are not announced to the recipient, so without this they would only be found by a wallet
+/// recovery. Scanning starts at the chain tip seen when the scanner first runs, and resumes from where it left off
+/// after a restart. If the chain tip that the last scan reached was reorged out, scanning resumes from the fork point.
+pub struct OneSidedPaymentScanner<T> {
+    db: WalletDatabase<T>,
+    node_public_key: CommsPublicKey,
+    connectivity: ConnectivityRequester,
+    base_node_service: BaseNodeServiceHandle,
+    output_manager_service: OutputManagerHandle,
+    transaction_service: TransactionServiceHandle,
+    shutdown_signal: ShutdownSignal,
+}
+
+impl<T> OneSidedPaymentScanner<T>
+where T: WalletBackend + 'static
+{
+    pub fn new(
+        db: WalletDatabase<T>,
+        node_public_key: CommsPublicKey,
+        connectivity: ConnectivityRequester,
+        base_node_service: BaseNodeServiceHandle,
+        output_manager_service: OutputManagerHandle,
+        transaction_service: TransactionServiceHandle,
+        shutdown_signal: ShutdownSignal,
+    ) -> Self
+    {
+        Self {
+            db,
+            node_public_key,
+            connectivity,
+            base_node_service,
+            output_manager_service,
+            transaction_service,
+            shutdown_signal,
+        }
+    }
+
+    pub async fn run(mut self) {
+        let mut base_node_events = self.base_node_service.get_event_stream_fused();
+        let mut shutdown = self.shutdown_signal.clone();
+        let mut base_node_peer = None;
+
+        loop {
+            futures::select! {
+                event = base_node_events.select_next_some() => {
+                    match event {
+                        Ok(event) => match &*event {
+                            BaseNodeEvent::BaseNodePeerSet(peer) => base_node_peer = Some(peer.node_id.clone()),
+                            BaseNodeEvent::BaseNodeStateChanged(state) => {
+                                if let Some(peer) = state.base_node_peer.as_ref() {
+                                    base_node_peer = Some(peer.node_id.clone());
+                                }
+                            },
+                            BaseNodeEvent::TipChanged { height, .. } => {
+                                if let Some(peer) = base_node_peer.clone() {
+                                    match self.scan(peer).await {
+                                        Ok(num_found) => debug!(
+                                            target: LOG_TARGET,
+                                            "Scanned UTXOs to height {} and found {} one-sided payment(s)",
+                                            height,
+                                            num_found
+                                        ),
+                                        Err(e) => warn!(
+                                            target: LOG_TARGET,
+                                            "Failed to scan UTXOs for one-sided payments: {}", e
+                                        ),
+                                    }
+                                }
+                            },
+                        },
+                        Err(e) => debug!(target: LOG_TARGET, "Lagging read on base node event stream: {}", e),
+                    }
+                },
+                _ = shutdown => {
+                    info!(target: LOG_TARGET, "One-sided payment scanner shutting down because it received the shutdown signal");
+                    break;
+                },
+            }
+        }
+    }
+
+    /// Scan the UTXOs added since the last scan, returning the number of one-sided payments that were found
+    async fn scan(&mut self, peer: NodeId) -> Result<u64, WalletError> {
+        let mut connection = self.connectivity.dial_peer(peer).await?;
+        let mut client = connection
+            .connect_rpc_using_builder(BaseNodeSyncRpcClient::builder().with_deadline(Duration::from_secs(60)))
+            .await
+            .map_err(to_scan_error)?;
+
+        let chain_metadata = client.get_chain_metadata().await.map_err(to_scan_error)?;
+        let tip_header = client
+            .get_header_by_height(chain_metadata.height_of_longest_chain())
+            .await
+            .map_err(to_scan_error)?;
+        let tip_header = BlockHeader::try_from(tip_header).map_err(to_scan_error)?;
+
+        let scanned_headers = self
+            .db
+            .get_client_key_from_str::<ScannedHeaders>(ONE_SIDED_SCAN_HEADERS_KEY.to_string())
+            .await?
+            .unwrap_or_default();
+        let (start_index, scanned_headers) = match self
+            .db
+            .get_client_key_from_str::<u64>(ONE_SIDED_SCAN_UTXO_INDEX_KEY.to_string())
+            .await?
+        {
+            Some(index) => {
+                self.find_resume_index(&mut client, index, scanned_headers, tip_header.height)
+                    .await?
+            },
+            None => {
+                // One-sided payments received before the scanner first ran are found by wallet recovery
+                self.set_scanned_tip(ScannedHeaders::default(), &tip_header).await?;
+                return Ok(0);
+            },
+        };
+        if start_index >= tip_header.output_mmr_size {
+            return Ok(0);
+        }
+
+        let request = SyncUtxosRequest {
+            start: start_index,
+            end_header_hash: tip_header.hash(),
+            include_pruned_utxos: false,
+            include_deleted_bitmaps: false,
+        };
+        let mut utxo_stream = client.sync_utxos(request).await.map_err(to_scan_error)?;
+        let mut outputs = Vec::new();
+        while let Some(response) = utxo_stream.next().await {
+            let response = response.map_err(to_scan_error)?;
+            if let Some(output) = response
+                .into_utxo()
+                .and_then(|o| o.utxo)
+                .and_then(|utxo| utxo.into_transaction_output())
+            {
+                outputs.push(TransactionOutput::try_from(output).map_err(to_scan_error)?);
+            }
+        }
+
+        let mut num_found = 0;
+        for output in self.output_manager_service.scan_for_one_sided_outputs(outputs).await? {
+            if self.import_one_sided_payment(output).await? {
+                num_found += 1;
+            }
+        }
+
+        self.set_scanned_tip(scanned_headers, &tip_header).await?;
+        Ok(num_found)
+    }
+
+    /// Returns the UTXO index to resume scanning from along with the scanned chain tips that are still in the main
+    /// chain. If the chain tip that the last scan reached was reorged out, scanning resumes from the most recent
+    /// scanned chain tip that is still in the main chain, which is at or below the fork point.
+    async fn find_resume_index(
+        &self,
+        client: &mut BaseNodeSyncRpcClient,
+        index: u64,
+        mut scanned_headers: ScannedHeaders,
+        tip_height: u64,
+    ) -> Result<(u64, ScannedHeaders), WalletError>
+    {
+        // The progress of scans made before the scanned chain tips were stored cannot be checked
+        let last_scanned_height = match scanned_headers.last() {
+            Some(scanned) => scanned.height,
+            None => return Ok((index, scanned_headers)),
+        };
+
+        while let Some(scanned) = scanned_headers.last() {
+            if scanned.height <= tip_height {
+                let header = client.get_header_by_height(scanned.height).await.map_err(to_scan_error)?;
+                let header = BlockHeader::try_from(header).map_err(to_scan_error)?;
+                if header.hash() == scanned.hash {
+                    if scanned.height == last_scanned_height {
+                        return Ok((index, scanned_headers));
+                    }
+                    warn!(
+                        target: LOG_TARGET,
+                        "Scanned chain tip at height {} was reorged out, rescanning for one-sided payments from height \
+                         {}",
+                        last_scanned_height,
+                        header.height
+                    );
+                    return Ok((header.output_mmr_size, scanned_headers));
+                }
+            }
+            scanned_headers.pop();
+        }
+
+        warn!(
+            target: LOG_TARGET,
+            "None of the scanned chain tips are in the main chain anymore, rescanning all UTXOs for one-sided payments"
+        );
+        Ok((0, scanned_headers))
+    }
+
+    /// Adds a one-sided payment to the wallet, returning false if it had already been added. Both the output and its
+    /// transaction record are only added if they do not exist yet, so an import that failed part way through is
+    /// completed when the payment is scanned again.
+    async fn import_one_sided_payment(&mut self, output: UnblindedOutput) -> Result<bool, WalletError> {
+        let tx_id = one_sided_payment_tx_id(&output);
+        let value = output.value;
+        match self.output_manager_service.add_output(output).await {
+            Ok(_) => {},
+            Err(OutputManagerError::OutputManagerStorageError(OutputManagerStorageError::DuplicateOutput)) => {
+                debug!(target: LOG_TARGET, "One-sided payment output already in database");
+            },
+            Err(e) => return Err(e.into()),
+        }
+
+        if self.transaction_service.get_any_transaction(tx_id).await?.is_some() {
+            debug!(target: LOG_TARGET, "One-sided payment (TxId: {}) already imported", tx_id);
+            return Ok(false);
+        }
+        // The sender of a one-sided payment is not recorded on-chain
+        self.transaction_service
+            .import_utxo_with_tx_id(
+                tx_id,
+                value,
+                self.node_public_key.clone(),
+                format!("One-sided payment received on {}.", Utc::now().naive_utc()),
+            )
+            .await?;
+        Ok(true)
+    }
+
+    /// Records the chain tip that a scan reached. The next scan starts after the UTXOs of this chain tip.
+    async fn set_scanned_tip(
+        &self,
+        mut scanned_headers: ScannedHeaders,
+        tip_header: &BlockHeader,
+    ) -> Result<(), WalletError>
+    {
+        scanned_headers.push(ScannedHeader {
+            height: tip_header.height,
+            hash: tip_header.hash(),
+        });
+        self.db
+            .set_client_key_value(ONE_SIDED_SCAN_HEADERS_KEY.to_string(), scanned_headers.to_string())
+            .await?;
+        self.db
+            .set_client_key_value(
+                ONE_SIDED_SCAN_UTXO_INDEX_KEY.to_string(),
+                tip_header.output_mmr_size.to_string(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+/// The TxId of the transaction that records a one-sided payment. It is derived from the commitment of the output, so
+/// that scanning the payment again finds the existing record.
+fn one_sided_payment_tx_id(output: &UnblindedOutput) -> TxId {
+    let commitment = output
+        .as_transaction_input(&CommitmentFactory::default(), output.features.clone())
+        .commitment;
+    let hash = Blake256::new()
+        .chain(b"one_sided_payment_tx_id")
+        .chain(commitment.as_bytes())
+        .result();
+    let mut tx_id = [0u8; 8];
+    tx_id.copy_from_slice(&hash[..8]);
+    u64::from_le_bytes(tx_id)
+}
+
+/// A chain tip that a scan reached
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ScannedHeader {
+    height: u64,
+    hash: BlockHash,
+}
+
+/// The chain tips that the most recent scans reached, oldest first. Stored as comma separated `height:hash` pairs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ScannedHeaders(Vec<ScannedHeader>);
+
+impl ScannedHeaders {
+    fn last(&self) -> Option<&ScannedHeader> {
+        self.0.last()
+    }
+
+    fn pop(&mut self) -> Option<ScannedHeader> {
+        self.0.pop()
+    }
+
+    /// Adds a chain tip, forgetting the oldest chain tip once `MAX_SCANNED_HEADERS` are kept
+    fn push(&mut self, header: ScannedHeader) {
+        self.0.push(header);
+        if self.0.len() > MAX_SCANNED_HEADERS {
+            self.0.remove(0);
+        }
+    }
+}
+
+impl Display for ScannedHeaders {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let headers = self
+            .0
+            .iter()
+            .map(|header| format!("{}:{}", header.height, header.hash.to_hex()))
+            .collect::<Vec<_>>();
+        f.write_str(&headers.join(","))
+    }
+}
+
+impl FromStr for ScannedHeaders {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Ok(Default::default());
+        }
+        let headers = s
+            .split(',')
+            .map(|header| {
+                let mut parts = header.splitn(2, ':');
+                let height = parts
+                    .next()
+                    .and_then(|height| height.parse().ok())
+                    .ok_or_else(|| format!("Invalid scanned header height in `{}`", header))?;
+                let hash = parts
+                    .next()
+                    .and_then(|hash| BlockHash::from_hex(hash).ok())
+                    .ok_or_else(|| format!("Invalid scanned header hash in `{}`", header))?;
+                Ok(ScannedHeader { height, hash })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(ScannedHeaders(headers))
+    }
+}
+
+fn to_scan_error<T: ToString>(err: T) -> WalletError {
+    WalletError::OneSidedPaymentScanError(err.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::OsRng;
+    use tari_core::transactions::{tari_amount::MicroTari, types::PrivateKey};
+    use tari_crypto::keys::SecretKey;
+
+    fn scanned_header(height: u64) -> ScannedHeader {
+        ScannedHeader {
+            height,
+            hash: vec![height as u8; 32],
+        }
+    }
+
+    #[test]
+    fn scanned_headers_round_trip_through_a_string() {
+        assert_eq!("".parse::<ScannedHeaders>().unwrap(), ScannedHeaders::default());
+
+        let mut headers = ScannedHeaders::default();
+        headers.push(scanned_header(1));
+        headers.push(scanned_header(5));
+        assert_eq!(headers.to_string().parse::<ScannedHeaders>().unwrap(), headers);
+
+        assert!("5".parse::<ScannedHeaders>().is_err());
+        assert!("x:00".parse::<ScannedHeaders>().is_err());
+        assert!("5:xyz".parse::<ScannedHeaders>().is_err());
+    }
+
+    #[test]
+    fn scanned_headers_only_keep_the_most_recent_chain_tips() {
+        let mut headers = ScannedHeaders::default();
+        for height in 0..MAX_SCANNED_HEADERS as u64 + 10 {
+            headers.push(scanned_header(height));
+        }
+        assert_eq!(headers.0.len(), MAX_SCANNED_HEADERS);
+        assert_eq!(headers.0[0], scanned_header(10));
+        assert_eq!(headers.last(), Some(&scanned_header(MAX_SCANNED_HEADERS as u64 + 9)));
+    }
+
+    #[test]
+    fn one_sided_payment_tx_id_is_derived_from_the_output() {
+        let output = UnblindedOutput::new(MicroTari::from(1000), PrivateKey::random(&mut OsRng), None);
+        let tx_id = one_sided_payment_tx_id(&output);
+        assert_eq!(one_sided_payment_tx_id(&output), tx_id);
+
+        let other = UnblindedOutput::new(MicroTari::from(1000), PrivateKey::random(&mut OsRng), None);
+        assert_ne!(one_sided_payment_tx_id(&other), tx_id);
+    }
+}
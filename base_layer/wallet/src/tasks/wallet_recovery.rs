@@ -370,23 +370,40 @@ impl WalletRecoveryTask {
             }
 
             iteration_count += 1;
-            let unblinded_outputs = self.wallet.output_manager_service.rewind_outputs(outputs).await?;
-            if unblinded_outputs.is_empty() {
-                continue;
-            }
-
             let source_public_key = self.wallet.comms.node_identity_ref().public_key().clone();
-
-            for uo in unblinded_outputs {
-                match self
-                    .wallet
-                    .import_utxo(
-                        uo.value,
-                        &uo.spending_key,
-                        &source_public_key,
-                        uo.features,
+            let mut unblinded_outputs = self
+                .wallet
+                .output_manager_service
+                .rewind_outputs(outputs.clone())
+                .await?
+                .into_iter()
+                .map(|uo| {
+                    (
+                        uo,
+                        source_public_key.clone(),
                         format!("Recovered on {}.", Utc::now().naive_utc()),
                     )
+                })
+                .collect::<Vec<_>>();
+
+            // The sender of a one-sided payment is not recorded on-chain
+            let one_sided_outputs = self
+                .wallet
+                .output_manager_service
+                .scan_for_one_sided_outputs(outputs)
+                .await?;
+            unblinded_outputs.extend(one_sided_outputs.into_iter().map(|uo| {
+                (
+                    uo,
+                    source_public_key.clone(),
+                    format!("One-sided payment recovered on {}.", Utc::now().naive_utc()),
+                )
+            }));
+
+            for (uo, source_public_key, message) in unblinded_outputs {
+                match self
+                    .wallet
+                    .import_utxo(uo.value, &uo.spending_key, &source_public_key, uo.features, message)
                     .await
                 {
                     Ok(_) => {
@@ -147,6 +147,7 @@ pub async fn create_wallet(
         peer_database_name: random_string(8),
         max_concurrent_inbound_tasks: 100,
         outbound_buffer_size: 100,
+        max_queued_messages_per_peer: 100,
         user_agent: "/tari/wallet/test".to_string(),
         dht: DhtConfig {
             discovery_request_timeout: Duration::from_secs(30),
@@ -157,8 +158,9 @@ pub async fn create_wallet(
         listener_liveness_allowlist_cidrs: Vec::new(),
         listener_liveness_max_sessions: 0,
         dns_seeds: Default::default(),
-        dns_seeds_name_server: "1.1.1.1:53".parse().unwrap(),
+        dns_seeds_name_servers: vec!["1.1.1.1:53".parse().unwrap()],
         dns_seeds_use_dnssec: false,
+        dns_seeds_doh_name: None,
         peer_seeds: Default::default(),
     };
 
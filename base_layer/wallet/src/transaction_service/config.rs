@@ -21,6 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use log::*;
+use rand::{rngs::OsRng, Rng};
 use std::{fmt, time::Duration};
 
 const LOG_TARGET: &str = "wallet::transaction_service::config";
@@ -28,6 +29,12 @@ const LOG_TARGET: &str = "wallet::transaction_service::config";
 #[derive(Clone, Debug)]
 pub struct TransactionServiceConfig {
     pub broadcast_monitoring_timeout: Duration,
+    /// The shortest delay between base node polls for a freshly broadcast transaction when
+    /// `adaptive_broadcast_monitoring` is enabled
+    pub broadcast_monitoring_interval: Duration,
+    /// Poll freshly broadcast transactions more often and back off while they wait to be mined. When disabled,
+    /// transactions are always polled at the power mode's monitoring timeout.
+    pub adaptive_broadcast_monitoring: bool,
     pub chain_monitoring_timeout: Duration,
     pub direct_send_timeout: Duration,
     pub broadcast_send_timeout: Duration,
@@ -38,12 +45,22 @@ pub struct TransactionServiceConfig {
     pub num_confirmations_required: u64,
     pub max_tx_query_batch_size: usize,
     pub transaction_routing_mechanism: TransactionRoutingMechanism,
+    /// The shortest time that this wallet holds one of its own transactions before relaying it to the network
+    pub relay_delay_min: Duration,
+    /// The longest time that this wallet holds one of its own transactions before relaying it to the network. The
+    /// actual delay is picked at random from this range, and a zero range disables the delay.
+    pub relay_delay_max: Duration,
+    /// Completed transactions that are mined with at least this many confirmations are pruned from the database after
+    /// each transaction validation, and their net effect is kept in a compact summary. `None` keeps all transactions.
+    pub transaction_retention_depth: Option<u64>,
 }
 
 impl Default for TransactionServiceConfig {
     fn default() -> Self {
         Self {
             broadcast_monitoring_timeout: Duration::from_secs(30),
+            broadcast_monitoring_interval: Duration::from_secs(5),
+            adaptive_broadcast_monitoring: false,
             chain_monitoring_timeout: Duration::from_secs(60),
             direct_send_timeout: Duration::from_secs(20),
             broadcast_send_timeout: Duration::from_secs(60),
@@ -54,10 +71,26 @@ impl Default for TransactionServiceConfig {
             num_confirmations_required: 3,
             max_tx_query_batch_size: 5000,
             transaction_routing_mechanism: TransactionRoutingMechanism::default(),
+            relay_delay_min: Duration::from_secs(0),
+            relay_delay_max: Duration::from_secs(0),
+            transaction_retention_depth: None,
         }
     }
 }
 
+impl TransactionServiceConfig {
+    /// Returns a random delay between `relay_delay_min` and `relay_delay_max`, or `relay_delay_min` if the range is
+    /// empty.
+    pub fn random_relay_delay(&self) -> Duration {
+        if self.relay_delay_max <= self.relay_delay_min {
+            return self.relay_delay_min;
+        }
+        let min = self.relay_delay_min.as_millis() as u64;
+        let max = self.relay_delay_max.as_millis() as u64;
+        Duration::from_millis(OsRng.gen_range(min, max + 1))
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum TransactionRoutingMechanism {
     DirectOnly,
@@ -21,7 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    output_manager_service::TxId,
+    output_manager_service::{one_sided::OneSidedPaymentAddress, TxId},
     transaction_service::{
         error::TransactionServiceError,
         storage::models::{CompletedTransaction, InboundTransaction, OutboundTransaction, WalletTransaction},
@@ -53,8 +53,9 @@ pub enum TransactionServiceRequest {
     GetAnyTransaction(TxId),
     SetBaseNodePublicKey(CommsPublicKey),
     SendTransaction((CommsPublicKey, MicroTari, MicroTari, String)),
+    SendOneSidedTransaction((OneSidedPaymentAddress, MicroTari, MicroTari, String)),
     CancelTransaction(TxId),
-    ImportUtxo(MicroTari, CommsPublicKey, String),
+    ImportUtxo(MicroTari, CommsPublicKey, String, Option<TxId>),
     SubmitTransaction((TxId, Transaction, MicroTari, MicroTari, String)),
     SetLowPowerMode,
     SetNormalPowerMode,
@@ -92,8 +93,11 @@ impl fmt::Display for TransactionServiceRequest {
             Self::SendTransaction((k, v, _, msg)) => {
                 f.write_str(&format!("SendTransaction (to {}, {}, {})", k, v, msg))
             },
+            Self::SendOneSidedTransaction((k, v, _, msg)) => {
+                f.write_str(&format!("SendOneSidedTransaction (to {}, {}, {})", k, v, msg))
+            },
             Self::CancelTransaction(t) => f.write_str(&format!("CancelTransaction ({})", t)),
-            Self::ImportUtxo(v, k, msg) => f.write_str(&format!("ImportUtxo (from {}, {}, {})", k, v, msg)),
+            Self::ImportUtxo(v, k, msg, _) => f.write_str(&format!("ImportUtxo (from {}, {}, {})", k, v, msg)),
             Self::SubmitTransaction((id, _, _, _, _)) => f.write_str(&format!("SubmitTransaction ({})", id)),
             Self::SetLowPowerMode => f.write_str("SetLowPowerMode "),
             Self::SetNormalPowerMode => f.write_str("SetNormalPowerMode"),
@@ -234,6 +238,32 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Send a one-sided payment to a recipient's one-sided payment address. The recipient discovers it by scanning the
+    /// UTXO set, without taking part in the transaction. This wallet knows the spending key of the payment output, so
+    /// the output is not exclusively the recipient's until the recipient spends it.
+    pub async fn send_one_sided_transaction(
+        &mut self,
+        dest_address: OneSidedPaymentAddress,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<TxId, TransactionServiceError>
+    {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendOneSidedTransaction((
+                dest_address,
+                amount,
+                fee_per_gram,
+                message,
+            )))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn cancel_transaction(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
         match self
             .handle
@@ -381,6 +411,32 @@ impl TransactionServiceHandle {
                 amount,
                 source_public_key,
                 message,
+                None,
+            ))
+            .await??
+        {
+            TransactionServiceResponse::UtxoImported(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Records an imported UTXO with the given TxId rather than a random one, so that the caller can find the record
+    /// again
+    pub async fn import_utxo_with_tx_id(
+        &mut self,
+        tx_id: TxId,
+        amount: MicroTari,
+        source_public_key: CommsPublicKey,
+        message: String,
+    ) -> Result<TxId, TransactionServiceError>
+    {
+        match self
+            .handle
+            .call(TransactionServiceRequest::ImportUtxo(
+                amount,
+                source_public_key,
+                message,
+                Some(tx_id),
             ))
             .await??
         {
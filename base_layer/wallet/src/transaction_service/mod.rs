@@ -27,6 +27,7 @@ pub mod protocols;
 pub mod service;
 pub mod storage;
 pub mod tasks;
+pub mod transaction_detail;
 
 use crate::{
     output_manager_service::handle::OutputManagerHandle,
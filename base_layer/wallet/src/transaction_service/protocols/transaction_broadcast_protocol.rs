@@ -34,7 +34,7 @@ use crate::{
 };
 use futures::{FutureExt, StreamExt};
 use log::*;
-use std::{convert::TryFrom, sync::Arc, time::Duration};
+use std::{cmp::min, convert::TryFrom, sync::Arc, time::Duration};
 use tari_comms::{peer_manager::NodeId, types::CommsPublicKey, PeerConnection};
 use tari_core::{
     base_node::{
@@ -44,7 +44,10 @@ use tari_core::{
     transactions::{transaction::Transaction, types::Signature},
 };
 use tari_crypto::tari_utilities::hex::Hex;
-use tokio::{sync::broadcast, time::delay_for};
+use tokio::{
+    sync::broadcast,
+    time::{delay_for, delay_until, Instant},
+};
 
 const LOG_TARGET: &str = "wallet::transaction_service::protocols::broadcast_protocol";
 
@@ -55,10 +58,12 @@ where TBackend: TransactionBackend + 'static
     mode: TxBroadcastMode,
     resources: TransactionServiceResources<TBackend>,
     timeout: Duration,
+    poll_interval: BroadcastPollInterval,
     base_node_public_key: CommsPublicKey,
     timeout_update_receiver: Option<broadcast::Receiver<Duration>>,
     base_node_update_receiver: Option<broadcast::Receiver<CommsPublicKey>>,
     first_rejection: bool,
+    mined_height: Option<u64>,
 }
 
 impl<TBackend> TransactionBroadcastProtocol<TBackend>
@@ -73,15 +78,22 @@ where TBackend: TransactionBackend + 'static
         base_node_update_receiver: broadcast::Receiver<CommsPublicKey>,
     ) -> Self
     {
+        let poll_interval = BroadcastPollInterval::new(
+            resources.config.broadcast_monitoring_interval,
+            timeout,
+            resources.config.adaptive_broadcast_monitoring,
+        );
         Self {
             tx_id,
             mode: TxBroadcastMode::TransactionSubmission,
             resources,
             timeout,
+            poll_interval,
             base_node_public_key,
             timeout_update_receiver: Some(timeout_update_receiver),
             base_node_update_receiver: Some(base_node_update_receiver),
             first_rejection: false,
+            mined_height: None,
         }
     }
 
@@ -104,6 +116,32 @@ where TBackend: TransactionBackend + 'static
             .fuse();
 
         let mut shutdown = self.resources.shutdown_signal.clone();
+
+        // Hold a transaction that has not been broadcast yet for a random period before relaying it, so that the
+        // first node to see it is less likely to be identified as its origin
+        let relay_delay = self.resources.config.random_relay_delay();
+        if self.mode == TxBroadcastMode::TransactionSubmission &&
+            relay_delay > Duration::from_secs(0) &&
+            self.resources
+                .db
+                .get_completed_transaction(self.tx_id)
+                .await
+                .map(|tx| tx.status == TransactionStatus::Completed)
+                .unwrap_or(false)
+        {
+            debug!(
+                target: LOG_TARGET,
+                "Delaying relay of Transaction (TxId: {}) by {:.2?}", self.tx_id, relay_delay
+            );
+            futures::select! {
+                _ = delay_for(relay_delay).fuse() => {},
+                _ = shutdown => {
+                    info!(target: LOG_TARGET, "Transaction Broadcast Protocol (TxId: {}) shutting down because it received the shutdown signal", self.tx_id);
+                    return Err(TransactionServiceProtocolError::new(self.tx_id, TransactionServiceError::Shutdown))
+                },
+            }
+        }
+
         // Main protocol loop
         loop {
             let base_node_node_id = NodeId::from_key(&self.base_node_public_key.clone())
@@ -145,6 +183,7 @@ where TBackend: TransactionBackend + 'static
                     match updated_timeout {
                         Ok(to) => {
                             self.timeout = to;
+                            self.poll_interval.set_max(to);
                              info!(
                                 target: LOG_TARGET,
                                 "Transaction Broadcast protocol (TxId: {}) timeout updated to {:?}", self.tx_id, self.timeout
@@ -244,7 +283,7 @@ where TBackend: TransactionBackend + 'static
                 },
             };
 
-            let delay = delay_for(self.timeout);
+            let poll_started = Instant::now();
             loop {
                 futures::select! {
                     new_base_node = base_node_update_receiver.select_next_some() => {
@@ -273,6 +312,7 @@ where TBackend: TransactionBackend + 'static
                             TxBroadcastMode::TransactionSubmission => {
                                 if result? {
                                     self.mode = TxBroadcastMode::TransactionQuery;
+                                    self.poll_interval.on_broadcast();
                                 }
                             },
                             TxBroadcastMode::TransactionQuery => {
@@ -284,6 +324,7 @@ where TBackend: TransactionBackend + 'static
                                             completed_tx.tx_id,
                                             completed_tx.transaction.body.inputs().clone(),
                                             completed_tx.transaction.body.outputs().clone(),
+                                            self.mined_height,
                                         )
                                         .await
                                         .map_err(|e| TransactionServiceProtocolError::new(self.tx_id, TransactionServiceError::from(e)))?;
@@ -311,14 +352,15 @@ where TBackend: TransactionBackend + 'static
                                 }
                             },
                         }
-                        // Wait out the remainder of the delay before proceeding with next loop
+                        // Wait out the remainder of the poll interval before proceeding with next loop
                         drop(client);
-                        delay.await;
+                        delay_until(poll_started + self.poll_interval.current()).await;
                         break;
                     },
                     updated_timeout = timeout_update_receiver.select_next_some() => {
                         if let Ok(to) = updated_timeout {
                             self.timeout = to;
+                            self.poll_interval.set_max(to);
                              info!(
                                 target: LOG_TARGET,
                                 "Transaction Broadcast protocol (TxId: {}) timeout updated to {:?}", self.tx_id, self.timeout
@@ -494,14 +536,13 @@ where TBackend: TransactionBackend + 'static
                 .await
                 .map_err(|e| TransactionServiceProtocolError::new(self.tx_id, TransactionServiceError::from(e)))?;
 
+            let mined_height = response.height_of_longest_chain.saturating_sub(response.confirmations);
             self.resources
                 .db
-                .set_transaction_mined_height(
-                    self.tx_id,
-                    response.height_of_longest_chain.saturating_sub(response.confirmations),
-                )
+                .set_transaction_mined_height(self.tx_id, mined_height)
                 .await
                 .map_err(|e| TransactionServiceProtocolError::new(self.tx_id, TransactionServiceError::from(e)))?;
+            self.mined_height = Some(mined_height);
 
             if response.confirmations >= self.resources.config.num_confirmations_required as u64 {
                 info!(
@@ -518,6 +559,7 @@ where TBackend: TransactionBackend + 'static
                 self.tx_id,
                 response.confirmations
             );
+            self.poll_interval.on_mined();
             self.resources
                 .db
                 .mine_completed_transaction(self.tx_id)
@@ -577,6 +619,7 @@ where TBackend: TransactionBackend + 'static
                 target: LOG_TARGET,
                 "Transaction (TxId: {}) found in mempool.", self.tx_id
             );
+            self.poll_interval.on_pending();
         }
 
         Ok(false)
@@ -637,3 +680,55 @@ pub enum TxBroadcastMode {
     TransactionSubmission,
     TransactionQuery,
 }
+
+/// Determines how long the broadcast protocol waits between base node polls. In adaptive mode a transaction is polled
+/// at the `min` interval right after it has been broadcast, the interval doubles every time the transaction is still
+/// found waiting in the mempool and it drops straight to the `max` interval once the transaction has been mined. When
+/// not adaptive, the `max` interval is always used.
+#[derive(Debug, Clone)]
+pub struct BroadcastPollInterval {
+    min: Duration,
+    max: Duration,
+    current: Duration,
+    is_adaptive: bool,
+}
+
+impl BroadcastPollInterval {
+    pub fn new(min: Duration, max: Duration, is_adaptive: bool) -> Self {
+        Self {
+            min,
+            max,
+            current: max,
+            is_adaptive,
+        }
+    }
+
+    pub fn current(&self) -> Duration {
+        if self.is_adaptive {
+            self.current
+        } else {
+            self.max
+        }
+    }
+
+    /// Update the slowest polling interval, e.g. when the wallet's power mode changes
+    pub fn set_max(&mut self, max: Duration) {
+        self.max = max;
+        self.current = min(self.current, max);
+    }
+
+    /// The transaction has just been accepted into the mempool
+    pub fn on_broadcast(&mut self) {
+        self.current = min(self.min, self.max);
+    }
+
+    /// The transaction is still waiting in the mempool
+    pub fn on_pending(&mut self) {
+        self.current = min(self.current * 2, self.max);
+    }
+
+    /// The transaction has been mined and is only waiting for confirmations
+    pub fn on_mined(&mut self) {
+        self.current = self.max;
+    }
+}
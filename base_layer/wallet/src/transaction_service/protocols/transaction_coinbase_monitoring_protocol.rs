@@ -510,6 +510,7 @@ where TBackend: TransactionBackend + 'static
                         self.tx_id,
                         completed_tx.transaction.body.inputs().clone(),
                         completed_tx.transaction.body.outputs().clone(),
+                        Some(self.block_height),
                     )
                     .await
                     .map_err(|e| TransactionServiceProtocolError::new(self.tx_id, TransactionServiceError::from(e)))?;
@@ -397,6 +397,17 @@ where TBackend: TransactionBackend + 'static
             }
         }
 
+        if let Some(retention_depth) = self.resources.config.transaction_retention_depth {
+            match self.resources.db.prune_completed_transactions(retention_depth).await {
+                Ok(num_pruned) if num_pruned > 0 => info!(
+                    target: LOG_TARGET,
+                    "Pruned {} completed transactions with at least {} confirmations", num_pruned, retention_depth
+                ),
+                Ok(_) => {},
+                Err(e) => warn!(target: LOG_TARGET, "Error pruning completed transactions: {}", e),
+            }
+        }
+
         let _ = self
             .resources
             .event_publisher
@@ -473,6 +484,19 @@ where TBackend: TransactionBackend + 'static
                             );
                         }
                     }
+                    if queried_tx.confirmations != Some(response.confirmations) {
+                        if let Err(e) = self
+                            .resources
+                            .db
+                            .set_transaction_confirmations(queried_tx.tx_id, response.confirmations)
+                            .await
+                        {
+                            warn!(
+                                target: LOG_TARGET,
+                                "Error updating transaction (TxId: {}) confirmations: {}", queried_tx.tx_id, e
+                            );
+                        }
+                    }
                     if response.confirmations >= self.resources.config.num_confirmations_required as u64 {
                         if queried_tx.status == TransactionStatus::MinedUnconfirmed {
                             info!(
@@ -499,6 +523,7 @@ where TBackend: TransactionBackend + 'static
                                     queried_tx.tx_id,
                                     queried_tx.transaction.body.inputs().clone(),
                                     queried_tx.transaction.body.outputs().clone(),
+                                    queried_tx.mined_height,
                                 )
                                 .await
                             {
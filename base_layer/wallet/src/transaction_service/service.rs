@@ -21,7 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    output_manager_service::{handle::OutputManagerHandle, TxId},
+    output_manager_service::{handle::OutputManagerHandle, one_sided::OneSidedPaymentAddress, TxId},
     transaction_service::{
         config::TransactionServiceConfig,
         error::{TransactionServiceError, TransactionServiceProtocolError},
@@ -466,6 +466,16 @@ where
                 )
                 .await
                 .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::SendOneSidedTransaction((dest_address, amount, fee_per_gram, message)) => self
+                .send_one_sided_transaction(
+                    dest_address,
+                    amount,
+                    fee_per_gram,
+                    message,
+                    transaction_broadcast_join_handles,
+                )
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
             TransactionServiceRequest::CancelTransaction(tx_id) => self
                 .cancel_transaction(tx_id)
                 .await
@@ -511,8 +521,8 @@ where
                 self.set_base_node_public_key(public_key).await;
                 Ok(TransactionServiceResponse::BaseNodePublicKeySet)
             },
-            TransactionServiceRequest::ImportUtxo(value, source_public_key, message) => self
-                .add_utxo_import_transaction(value, source_public_key, message)
+            TransactionServiceRequest::ImportUtxo(value, source_public_key, message, tx_id) => self
+                .add_utxo_import_transaction(value, source_public_key, message, tx_id)
                 .await
                 .map(TransactionServiceResponse::UtxoImported),
             TransactionServiceRequest::SubmitTransaction((tx_id, tx, fee, amount, message)) => self
@@ -673,6 +683,51 @@ where
         Ok(tx_id)
     }
 
+    /// Build and broadcast a one-sided payment to `dest_address`. The recipient's output can be rewound with keys
+    /// derived from the one-sided key behind the address, so the recipient can find it on-chain without replying to us.
+    pub async fn send_one_sided_transaction(
+        &mut self,
+        dest_address: OneSidedPaymentAddress,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError>
+    {
+        let (tx_id, fee, transaction) = self
+            .output_manager_service
+            .create_one_sided_transaction(amount, fee_per_gram, None, message.clone(), dest_address.clone())
+            .await?;
+
+        // The recipient's comms public key is not known for a one-sided payment, so the address key is kept separately
+        let mut completed_tx = CompletedTransaction::new(
+            tx_id,
+            self.node_identity.public_key().clone(),
+            CommsPublicKey::default(),
+            amount,
+            fee,
+            transaction,
+            TransactionStatus::Completed,
+            message,
+            Utc::now().naive_utc(),
+            TransactionDirection::Outbound,
+            None,
+        );
+        completed_tx.one_sided_payment_public_key = Some(dest_address.public_key);
+        self.db.insert_completed_transaction(tx_id, completed_tx).await?;
+
+        let _ = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)));
+
+        self.complete_send_transaction_protocol(Ok(tx_id), transaction_broadcast_join_handles)
+            .await;
+
+        Ok(tx_id)
+    }
+
     /// Accept the public reply from a recipient and apply the reply to the relevant transaction protocol
     /// # Arguments
     /// 'recipient_reply' - The public response from a recipient with data required to complete the transaction
@@ -1509,9 +1564,10 @@ where
         value: MicroTari,
         source_public_key: CommsPublicKey,
         message: String,
+        tx_id: Option<TxId>,
     ) -> Result<TxId, TransactionServiceError>
     {
-        let tx_id = OsRng.next_u64();
+        let tx_id = tx_id.unwrap_or_else(|| OsRng.next_u64());
         self.db
             .add_utxo_import_transaction(
                 tx_id,
@@ -1846,6 +1902,7 @@ where
                             .expect("Failed to convert to Transaction Output")
                     })
                     .collect(),
+                None,
             )
             .await?;
 
@@ -28,6 +28,7 @@ use crate::{
             CompletedTransaction,
             InboundTransaction,
             OutboundTransaction,
+            PrunedTransactionSummary,
             TransactionDirection,
             TransactionStatus,
         },
@@ -124,6 +125,11 @@ pub trait TransactionBackend: Send + Sync + Clone {
     fn update_confirmations(&self, tx_id: TxId, confirmations: u64) -> Result<(), TransactionStorageError>;
     /// Update a transactions mined height
     fn update_mined_height(&self, tx_id: TxId, mined_height: u64) -> Result<(), TransactionStorageError>;
+    /// Remove the valid, mined and confirmed completed transactions with at least `min_confirmations` confirmations
+    /// and add their net effect to the pruned transaction summary. Returns the number of pruned transactions.
+    fn prune_completed_transactions(&self, min_confirmations: u64) -> Result<usize, TransactionStorageError>;
+    /// Fetch the summary of all the completed transactions that have been pruned
+    fn fetch_pruned_transaction_summary(&self) -> Result<PrunedTransactionSummary, TransactionStorageError>;
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -756,6 +762,22 @@ where T: TransactionBackend + 'static
             .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))??;
         Ok(())
     }
+
+    pub async fn prune_completed_transactions(&self, min_confirmations: u64) -> Result<usize, TransactionStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.prune_completed_transactions(min_confirmations))
+            .await
+            .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))
+            .and_then(|inner_result| inner_result)
+    }
+
+    pub async fn get_pruned_transaction_summary(&self) -> Result<PrunedTransactionSummary, TransactionStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.fetch_pruned_transaction_summary())
+            .await
+            .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))
+            .and_then(|inner_result| inner_result)
+    }
 }
 
 impl Display for DbKey {
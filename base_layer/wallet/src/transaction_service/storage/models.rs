@@ -191,6 +191,7 @@ pub struct CompletedTransaction {
     pub destination_public_key: CommsPublicKey,
     pub amount: MicroTari,
     pub fee: MicroTari,
+    pub weight: u64,
     pub transaction: Transaction,
     pub status: TransactionStatus,
     pub message: String,
@@ -203,6 +204,9 @@ pub struct CompletedTransaction {
     pub valid: bool,
     pub confirmations: Option<u64>,
     pub mined_height: Option<u64>,
+    /// The public key of the one-sided payment address this transaction was sent to, if it was a one-sided payment
+    #[serde(default)]
+    pub one_sided_payment_public_key: Option<CommsPublicKey>,
 }
 
 impl CompletedTransaction {
@@ -227,6 +231,7 @@ impl CompletedTransaction {
             destination_public_key,
             amount,
             fee,
+            weight: transaction.calculate_weight(),
             transaction,
             status,
             message,
@@ -239,8 +244,20 @@ impl CompletedTransaction {
             valid: true,
             confirmations: None,
             mined_height: None,
+            one_sided_payment_public_key: None,
         }
     }
+
+    /// Returns the total fee paid by this transaction
+    pub fn fee(&self) -> MicroTari {
+        self.fee
+    }
+
+    /// Returns the weight of this transaction, calculated in the same way as the block weight during block validation.
+    /// This is calculated when the `CompletedTransaction` is constructed.
+    pub fn weight(&self) -> u64 {
+        self.weight
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -325,6 +342,7 @@ impl From<OutboundTransaction> for CompletedTransaction {
             message: tx.message,
             timestamp: tx.timestamp,
             cancelled: tx.cancelled,
+            weight: 0,
             transaction: Transaction::new(vec![], vec![], vec![], PrivateKey::default()),
             direction: TransactionDirection::Outbound,
             coinbase_block_height: None,
@@ -333,6 +351,7 @@ impl From<OutboundTransaction> for CompletedTransaction {
             valid: true,
             confirmations: None,
             mined_height: None,
+            one_sided_payment_public_key: None,
         }
     }
 }
@@ -349,6 +368,7 @@ impl From<InboundTransaction> for CompletedTransaction {
             message: tx.message,
             timestamp: tx.timestamp,
             cancelled: tx.cancelled,
+            weight: 0,
             transaction: Transaction::new(vec![], vec![], vec![], PrivateKey::default()),
             direction: TransactionDirection::Inbound,
             coinbase_block_height: None,
@@ -357,6 +377,7 @@ impl From<InboundTransaction> for CompletedTransaction {
             valid: true,
             confirmations: None,
             mined_height: None,
+            one_sided_payment_public_key: None,
         }
     }
 }
@@ -378,3 +399,29 @@ impl From<WalletTransaction> for CompletedTransaction {
         }
     }
 }
+
+/// The net effect of the completed transactions that have been pruned from the wallet database. Only the amounts that
+/// affect the wallet balance are kept, i.e. the amounts received, the amounts sent and the fees paid on sent
+/// transactions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PrunedTransactionSummary {
+    pub num_transactions: u64,
+    pub total_received: MicroTari,
+    pub total_sent: MicroTari,
+    pub total_fees: MicroTari,
+}
+
+impl PrunedTransactionSummary {
+    /// Adds the net effect of a pruned transaction to this summary
+    pub fn add(&mut self, direction: &TransactionDirection, amount: MicroTari, fee: MicroTari) {
+        self.num_transactions += 1;
+        match direction {
+            TransactionDirection::Inbound => self.total_received += amount,
+            TransactionDirection::Outbound => {
+                self.total_sent += amount;
+                self.total_fees += fee;
+            },
+            TransactionDirection::Unknown => {},
+        }
+    }
+}
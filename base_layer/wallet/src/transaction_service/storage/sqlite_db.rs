@@ -22,7 +22,7 @@
 
 use crate::{
     output_manager_service::TxId,
-    schema::{completed_transactions, inbound_transactions, outbound_transactions},
+    schema::{completed_transactions, inbound_transactions, outbound_transactions, pruned_transaction_summary},
     storage::sqlite_utilities::WalletDbConnection,
     transaction_service::{
         error::TransactionStorageError,
@@ -32,6 +32,7 @@ use crate::{
                 CompletedTransaction,
                 InboundTransaction,
                 OutboundTransaction,
+                PrunedTransactionSummary,
                 TransactionDirection,
                 TransactionStatus,
                 WalletTransaction,
@@ -51,7 +52,7 @@ use std::{
     sync::{Arc, MutexGuard, RwLock},
 };
 use tari_comms::types::CommsPublicKey;
-use tari_core::transactions::{tari_amount::MicroTari, types::PublicKey};
+use tari_core::transactions::{tari_amount::MicroTari, transaction::Transaction, types::PublicKey};
 use tari_crypto::tari_utilities::{
     hex::{from_hex, Hex},
     ByteArray,
@@ -939,6 +940,38 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         };
         Ok(())
     }
+
+    fn prune_completed_transactions(&self, min_confirmations: u64) -> Result<usize, TransactionStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        conn.transaction::<_, TransactionStorageError, _>(|| {
+            let prunable_txs = CompletedTransactionSql::index_prunable(min_confirmations as i64, &(*conn))?;
+            if prunable_txs.is_empty() {
+                return Ok(0);
+            }
+            let mut summary = PrunedTransactionSummarySql::get(&(*conn))?;
+            for tx in prunable_txs.iter() {
+                summary.add(
+                    &TransactionDirection::try_from(tx.direction.unwrap_or(2i32))?,
+                    MicroTari::from(tx.amount as u64),
+                    MicroTari::from(tx.fee as u64),
+                );
+                tx.delete(&(*conn))?;
+            }
+            PrunedTransactionSummarySql::from(summary).set(&(*conn))?;
+            debug!(
+                target: LOG_TARGET,
+                "Pruned {} completed transactions with at least {} confirmations",
+                prunable_txs.len(),
+                min_confirmations
+            );
+            Ok(prunable_txs.len())
+        })
+    }
+
+    fn fetch_pruned_transaction_summary(&self) -> Result<PrunedTransactionSummary, TransactionStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        PrunedTransactionSummarySql::get(&(*conn))
+    }
 }
 
 #[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
@@ -1334,6 +1367,7 @@ struct CompletedTransactionSql {
     valid: i32,
     confirmations: Option<i64>,
     mined_height: Option<i64>,
+    one_sided_payment_public_key: Option<Vec<u8>>,
 }
 
 impl CompletedTransactionSql {
@@ -1369,6 +1403,25 @@ impl CompletedTransactionSql {
             .load::<CompletedTransactionSql>(conn)?)
     }
 
+    /// Returns the valid, mined and confirmed transactions with a known direction that have at least
+    /// `min_confirmations` confirmations
+    pub fn index_prunable(
+        min_confirmations: i64,
+        conn: &SqliteConnection,
+    ) -> Result<Vec<CompletedTransactionSql>, TransactionStorageError>
+    {
+        Ok(completed_transactions::table
+            .filter(completed_transactions::status.eq(TransactionStatus::MinedConfirmed as i32))
+            .filter(completed_transactions::cancelled.eq(false as i32))
+            .filter(completed_transactions::valid.eq(true as i32))
+            .filter(completed_transactions::direction.eq_any(vec![
+                TransactionDirection::Inbound as i32,
+                TransactionDirection::Outbound as i32,
+            ]))
+            .filter(completed_transactions::confirmations.ge(min_confirmations))
+            .load::<CompletedTransactionSql>(conn)?)
+    }
+
     pub fn find(tx_id: TxId, conn: &SqliteConnection) -> Result<CompletedTransactionSql, TransactionStorageError> {
         Ok(completed_transactions::table
             .filter(completed_transactions::tx_id.eq(tx_id as i64))
@@ -1612,6 +1665,7 @@ impl TryFrom<CompletedTransaction> for CompletedTransactionSql {
             valid: c.valid as i32,
             confirmations: c.confirmations.map(|ic| ic as i64),
             mined_height: c.mined_height.map(|ic| ic as i64),
+            one_sided_payment_public_key: c.one_sided_payment_public_key.map(|k| k.to_vec()),
         })
     }
 }
@@ -1620,6 +1674,7 @@ impl TryFrom<CompletedTransactionSql> for CompletedTransaction {
     type Error = TransactionStorageError;
 
     fn try_from(c: CompletedTransactionSql) -> Result<Self, Self::Error> {
+        let transaction: Transaction = serde_json::from_str(&c.transaction_protocol)?;
         Ok(Self {
             tx_id: c.tx_id as u64,
             source_public_key: PublicKey::from_vec(&c.source_public_key)
@@ -1628,7 +1683,8 @@ impl TryFrom<CompletedTransactionSql> for CompletedTransaction {
                 .map_err(|_| TransactionStorageError::ConversionError("Invalid destination PublicKey".to_string()))?,
             amount: MicroTari::from(c.amount as u64),
             fee: MicroTari::from(c.fee as u64),
-            transaction: serde_json::from_str(&c.transaction_protocol)?,
+            weight: transaction.calculate_weight(),
+            transaction,
             status: TransactionStatus::try_from(c.status)?,
             message: c.message,
             timestamp: c.timestamp,
@@ -1640,6 +1696,13 @@ impl TryFrom<CompletedTransactionSql> for CompletedTransaction {
             valid: c.valid != 0,
             confirmations: c.confirmations.map(|ic| ic as u64),
             mined_height: c.mined_height.map(|ic| ic as u64),
+            one_sided_payment_public_key: c
+                .one_sided_payment_public_key
+                .map(|k| PublicKey::from_vec(&k))
+                .transpose()
+                .map_err(|_| {
+                    TransactionStorageError::ConversionError("Invalid one-sided payment PublicKey".to_string())
+                })?,
         })
     }
 }
@@ -1690,6 +1753,63 @@ impl From<UpdateCompletedTransaction> for UpdateCompletedTransactionSql {
     }
 }
 
+/// The pruned transaction summary is stored in a single row of the pruned_transaction_summary table
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "pruned_transaction_summary"]
+struct PrunedTransactionSummarySql {
+    id: i32,
+    num_transactions: i64,
+    total_received: i64,
+    total_sent: i64,
+    total_fees: i64,
+}
+
+impl PrunedTransactionSummarySql {
+    const ID: i32 = 0;
+
+    pub fn get(conn: &SqliteConnection) -> Result<PrunedTransactionSummary, TransactionStorageError> {
+        pruned_transaction_summary::table
+            .filter(pruned_transaction_summary::id.eq(Self::ID))
+            .first::<PrunedTransactionSummarySql>(conn)
+            .map(PrunedTransactionSummary::from)
+            .or_else(|err| match err {
+                DieselError::NotFound => Ok(PrunedTransactionSummary::default()),
+                err => Err(err.into()),
+            })
+    }
+
+    pub fn set(&self, conn: &SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::replace_into(pruned_transaction_summary::table)
+            .values(self)
+            .execute(conn)?;
+
+        Ok(())
+    }
+}
+
+impl From<PrunedTransactionSummary> for PrunedTransactionSummarySql {
+    fn from(s: PrunedTransactionSummary) -> Self {
+        Self {
+            id: Self::ID,
+            num_transactions: s.num_transactions as i64,
+            total_received: u64::from(s.total_received) as i64,
+            total_sent: u64::from(s.total_sent) as i64,
+            total_fees: u64::from(s.total_fees) as i64,
+        }
+    }
+}
+
+impl From<PrunedTransactionSummarySql> for PrunedTransactionSummary {
+    fn from(s: PrunedTransactionSummarySql) -> Self {
+        Self {
+            num_transactions: s.num_transactions as u64,
+            total_received: MicroTari::from(s.total_received as u64),
+            total_sent: MicroTari::from(s.total_sent as u64),
+            total_fees: MicroTari::from(s.total_fees as u64),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     #[cfg(feature = "test_harness")]
@@ -1723,6 +1843,7 @@ mod test {
     use rand::rngs::OsRng;
     use std::convert::TryFrom;
     use tari_core::transactions::{
+        helpers::create_tx,
         tari_amount::MicroTari,
         transaction::{OutputFeatures, Transaction, UnblindedOutput},
         transaction_protocol::sender::TransactionSenderMessage,
@@ -1878,6 +1999,7 @@ mod test {
             destination_public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
             amount,
             fee: MicroTari::from(100),
+            weight: tx.calculate_weight(),
             transaction: tx.clone(),
             status: TransactionStatus::MinedUnconfirmed,
             message: "Yo!".to_string(),
@@ -1890,6 +2012,7 @@ mod test {
             valid: true,
             confirmations: None,
             mined_height: None,
+            one_sided_payment_public_key: None,
         };
         let completed_tx2 = CompletedTransaction {
             tx_id: 3,
@@ -1897,6 +2020,7 @@ mod test {
             destination_public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
             amount,
             fee: MicroTari::from(100),
+            weight: tx.calculate_weight(),
             transaction: tx.clone(),
             status: TransactionStatus::Broadcast,
             message: "Hey!".to_string(),
@@ -1909,6 +2033,7 @@ mod test {
             valid: true,
             confirmations: None,
             mined_height: None,
+            one_sided_payment_public_key: None,
         };
 
         CompletedTransactionSql::try_from(completed_tx1.clone())
@@ -2014,6 +2139,7 @@ mod test {
             destination_public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
             amount,
             fee: MicroTari::from(100),
+            weight: tx.calculate_weight(),
             transaction: tx.clone(),
             status: TransactionStatus::Coinbase,
             message: "Hey!".to_string(),
@@ -2026,6 +2152,7 @@ mod test {
             valid: true,
             confirmations: None,
             mined_height: None,
+            one_sided_payment_public_key: None,
         };
 
         let coinbase_tx2 = CompletedTransaction {
@@ -2034,6 +2161,7 @@ mod test {
             destination_public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
             amount,
             fee: MicroTari::from(100),
+            weight: tx.calculate_weight(),
             transaction: tx.clone(),
             status: TransactionStatus::Coinbase,
             message: "Hey!".to_string(),
@@ -2046,6 +2174,7 @@ mod test {
             valid: true,
             confirmations: None,
             mined_height: None,
+            one_sided_payment_public_key: None,
         };
 
         let coinbase_tx3 = CompletedTransaction {
@@ -2054,6 +2183,7 @@ mod test {
             destination_public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
             amount,
             fee: MicroTari::from(100),
+            weight: tx.calculate_weight(),
             transaction: tx,
             status: TransactionStatus::Coinbase,
             message: "Hey!".to_string(),
@@ -2066,6 +2196,7 @@ mod test {
             valid: true,
             confirmations: None,
             mined_height: None,
+            one_sided_payment_public_key: None,
         };
 
         CompletedTransactionSql::try_from(coinbase_tx1)
@@ -2109,6 +2240,33 @@ mod test {
             .unwrap();
     }
 
+    #[test]
+    fn test_completed_transaction_fee_and_weight() {
+        let (tx, _, _) = create_tx(MicroTari::from(5000), MicroTari::from(15), 0, 2, 0, 3);
+        let completed_tx = CompletedTransaction::new(
+            1,
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            MicroTari::from(5000),
+            tx.body.get_total_fee(),
+            tx.clone(),
+            TransactionStatus::Completed,
+            "Yo!".to_string(),
+            Utc::now().naive_utc(),
+            TransactionDirection::Outbound,
+            None,
+        );
+        assert_eq!(completed_tx.fee(), tx.body.get_total_fee());
+        assert_eq!(completed_tx.weight(), tx.body.calculate_weight());
+        assert!(completed_tx.weight() > 0);
+
+        // The cached weight is recalculated when the transaction is read back from the database
+        let completed_tx_sql = CompletedTransactionSql::try_from(completed_tx.clone()).unwrap();
+        let returned_tx = CompletedTransaction::try_from(completed_tx_sql).unwrap();
+        assert_eq!(returned_tx.fee(), completed_tx.fee());
+        assert_eq!(returned_tx.weight(), completed_tx.weight());
+    }
+
     #[test]
     fn test_encryption_crud() {
         let db_name = format!("{}.sqlite3", string(8).as_str());
@@ -2178,6 +2336,7 @@ mod test {
             destination_public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
             amount: MicroTari::from(100),
             fee: MicroTari::from(100),
+            weight: 0,
             transaction: Transaction::new(vec![], vec![], vec![], PrivateKey::random(&mut OsRng)),
             status: TransactionStatus::MinedUnconfirmed,
             message: "Yo!".to_string(),
@@ -2190,6 +2349,7 @@ mod test {
             valid: true,
             confirmations: None,
             mined_height: None,
+            one_sided_payment_public_key: None,
         };
 
         let mut completed_tx_sql = CompletedTransactionSql::try_from(completed_tx.clone()).unwrap();
@@ -2253,6 +2413,7 @@ mod test {
             destination_public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
             amount: MicroTari::from(100),
             fee: MicroTari::from(100),
+            weight: 0,
             transaction: Transaction::new(vec![], vec![], vec![], PrivateKey::random(&mut OsRng)),
             status: TransactionStatus::MinedUnconfirmed,
             message: "Yo!".to_string(),
@@ -2265,6 +2426,7 @@ mod test {
             valid: true,
             confirmations: None,
             mined_height: None,
+            one_sided_payment_public_key: None,
         };
         let completed_tx_sql = CompletedTransactionSql::try_from(completed_tx).unwrap();
         completed_tx_sql.commit(&conn).unwrap();
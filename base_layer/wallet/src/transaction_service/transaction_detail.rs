@@ -0,0 +1,202 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A serializable, audit-friendly breakdown of a completed transaction. Unlike the summary information kept in
+//! [CompletedTransaction], the [TransactionDetail] lists every input, output and kernel of the underlying
+//! transaction.
+
+use crate::{output_manager_service::TxId, transaction_service::storage::models::CompletedTransaction};
+use serde::Serialize;
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction::{TransactionInput, TransactionKernel, TransactionOutput},
+};
+use tari_crypto::tari_utilities::hex::Hex;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TransactionDetail {
+    pub tx_id: TxId,
+    pub status: String,
+    pub direction: String,
+    pub amount: MicroTari,
+    pub fee: MicroTari,
+    pub lock_height: u64,
+    pub timestamp: String,
+    pub cancelled: bool,
+    pub valid: bool,
+    pub mined_height: Option<u64>,
+    pub confirmations: Option<u64>,
+    pub inputs: Vec<InputDetail>,
+    pub outputs: Vec<OutputDetail>,
+    pub kernels: Vec<KernelDetail>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InputDetail {
+    pub commitment: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OutputDetail {
+    pub commitment: String,
+    pub flags: String,
+    pub maturity: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KernelDetail {
+    pub features: String,
+    pub fee: MicroTari,
+    pub lock_height: u64,
+    pub excess: String,
+    pub excess_sig_public_nonce: String,
+    pub excess_sig: String,
+}
+
+impl TransactionDetail {
+    /// Serialize this detail as pretty printed JSON
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl From<&CompletedTransaction> for TransactionDetail {
+    fn from(tx: &CompletedTransaction) -> Self {
+        let body = &tx.transaction.body;
+        Self {
+            tx_id: tx.tx_id,
+            status: tx.status.to_string(),
+            direction: tx.direction.to_string(),
+            amount: tx.amount,
+            fee: tx.fee,
+            lock_height: tx.transaction.max_kernel_timelock(),
+            timestamp: tx.timestamp.to_string(),
+            cancelled: tx.cancelled,
+            valid: tx.valid,
+            mined_height: tx.mined_height,
+            confirmations: tx.confirmations,
+            inputs: body.inputs().iter().map(InputDetail::from).collect(),
+            outputs: body.outputs().iter().map(OutputDetail::from).collect(),
+            kernels: body.kernels().iter().map(KernelDetail::from).collect(),
+        }
+    }
+}
+
+impl From<&TransactionInput> for InputDetail {
+    fn from(input: &TransactionInput) -> Self {
+        Self {
+            commitment: input.commitment.to_hex(),
+        }
+    }
+}
+
+impl From<&TransactionOutput> for OutputDetail {
+    fn from(output: &TransactionOutput) -> Self {
+        Self {
+            commitment: output.commitment.to_hex(),
+            flags: format!("{:?}", output.features.flags),
+            maturity: output.features.maturity,
+        }
+    }
+}
+
+impl From<&TransactionKernel> for KernelDetail {
+    fn from(kernel: &TransactionKernel) -> Self {
+        Self {
+            features: format!("{:?}", kernel.features),
+            fee: kernel.fee,
+            lock_height: kernel.lock_height,
+            excess: kernel.excess.to_hex(),
+            excess_sig_public_nonce: kernel.excess_sig.get_public_nonce().to_hex(),
+            excess_sig: kernel.excess_sig.get_signature().to_hex(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TransactionDetail;
+    use crate::transaction_service::storage::models::{CompletedTransaction, TransactionDirection, TransactionStatus};
+    use chrono::Utc;
+    use rand::rngs::OsRng;
+    use tari_core::transactions::{
+        helpers::create_tx,
+        tari_amount::MicroTari,
+        types::{PrivateKey, PublicKey},
+    };
+    use tari_crypto::{
+        keys::{PublicKey as PublicKeyTrait, SecretKey},
+        tari_utilities::hex::Hex,
+    };
+
+    #[test]
+    fn export_contains_all_kernels_and_outputs() {
+        let (tx, _, _) = create_tx(MicroTari::from(5000), MicroTari::from(15), 10, 2, 0, 3);
+        let mut completed_tx = CompletedTransaction::new(
+            1,
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            MicroTari::from(5000),
+            tx.body.get_total_fee(),
+            tx.clone(),
+            TransactionStatus::MinedConfirmed,
+            "Yo!".to_string(),
+            Utc::now().naive_utc(),
+            TransactionDirection::Outbound,
+            None,
+        );
+        completed_tx.mined_height = Some(123);
+        completed_tx.confirmations = Some(3);
+
+        let detail = TransactionDetail::from(&completed_tx);
+        assert_eq!(detail.inputs.len(), 2);
+        assert_eq!(detail.outputs.len(), tx.body.outputs().len());
+        assert!(detail.outputs.len() >= 3);
+        assert_eq!(detail.kernels.len(), tx.body.kernels().len());
+        assert_eq!(detail.lock_height, 10);
+        assert_eq!(detail.fee, tx.body.get_total_fee());
+
+        let json = detail.to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["tx_id"], 1);
+        assert_eq!(value["mined_height"], 123);
+        assert_eq!(value["confirmations"], 3);
+        assert_eq!(value["status"], "Mined Confirmed");
+
+        let inputs = value["inputs"].as_array().unwrap();
+        for input in tx.body.inputs() {
+            assert!(inputs.iter().any(|i| i["commitment"] == input.commitment.to_hex()));
+        }
+        let outputs = value["outputs"].as_array().unwrap();
+        assert_eq!(outputs.len(), tx.body.outputs().len());
+        for output in tx.body.outputs() {
+            assert!(outputs.iter().any(|o| o["commitment"] == output.commitment.to_hex()));
+        }
+        let kernels = value["kernels"].as_array().unwrap();
+        assert_eq!(kernels.len(), tx.body.kernels().len());
+        for kernel in tx.body.kernels() {
+            assert!(kernels.iter().any(|k| k["excess"] == kernel.excess.to_hex() &&
+                k["excess_sig"] == kernel.excess_sig.get_signature().to_hex() &&
+                k["lock_height"] == kernel.lock_height));
+        }
+    }
+}
@@ -32,6 +32,7 @@ use crate::{
         TxId,
     },
     storage::database::{WalletBackend, WalletDatabase},
+    tasks::one_sided_scanner::OneSidedPaymentScanner,
     transaction_service::{
         config::TransactionServiceConfig,
         handle::TransactionServiceHandle,
@@ -45,10 +46,11 @@ use aes_gcm::{
 };
 use digest::Digest;
 use log::*;
-use std::{marker::PhantomData, sync::Arc};
+use rand::rngs::OsRng;
+use std::{marker::PhantomData, sync::Arc, time::Duration};
 use tari_comms::{
     multiaddr::Multiaddr,
-    peer_manager::{NodeId, Peer, PeerFeatures, PeerFlags},
+    peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerFlags},
     types::CommsPublicKey,
     CommsNode,
     UnspawnedCommsNode,
@@ -64,6 +66,7 @@ use tari_core::{
 };
 use tari_crypto::{
     common::Blake256,
+    keys::PublicKey,
     ristretto::{RistrettoPublicKey, RistrettoSchnorr, RistrettoSecretKey},
     signatures::{SchnorrSignature, SchnorrSignatureError},
     tari_utilities::hex::Hex,
@@ -72,12 +75,15 @@ use tari_p2p::{
     comms_connector::pubsub_connector,
     initialization,
     initialization::{CommsConfig, P2pInitializer},
+    transport::TransportType,
 };
 use tari_service_framework::StackBuilder;
 use tari_shutdown::ShutdownSignal;
-use tokio::runtime;
+use tokio::{runtime, task};
 
 const LOG_TARGET: &str = "wallet";
+/// How long to wait for connectivity before giving up on announcing a rotated node identity
+const ROTATED_IDENTITY_ANNOUNCE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
 
 #[derive(Clone)]
 pub struct WalletConfig {
@@ -115,6 +121,35 @@ impl WalletConfig {
             base_node_service_config: base_node_service_config.unwrap_or_default(),
         }
     }
+
+    /// Replace the comms identity in this config with a freshly generated keypair and the given public address. The
+    /// features of the previous identity are retained. If the Tor transport is used, the hidden service identity is
+    /// cleared so that a new onion address is created.
+    ///
+    /// To rotate the identity of a running wallet, shut it down, rotate the identity of its config and start a new
+    /// `Wallet` with the same backends. Shutting down drops every connection made with the old identity, and the new
+    /// wallet establishes its connections afresh. When the new wallet starts it detects that the comms secret key
+    /// persisted in the wallet database differs from the configured one, persists the new key, removes the old
+    /// identity's entry from the peer database and announces the new identity to the network once it is online.
+    ///
+    /// The wallet's spend keys and outputs are kept by the output manager backend and are not affected. This includes
+    /// one-sided payments: the one-sided payment address is derived from the wallet key manager and not from the node
+    /// identity, so outputs sent to it are still found after the identity has been rotated.
+    pub fn rotate_node_identity(&mut self, public_address: Multiaddr) -> Result<Arc<NodeIdentity>, WalletError> {
+        let features = self.comms_config.node_identity.features();
+        let node_identity = Arc::new(NodeIdentity::random(&mut OsRng, public_address, features)?);
+        if let TransportType::Tor(tor_config) = &mut self.comms_config.transport_type {
+            tor_config.identity = None;
+        }
+        info!(
+            target: LOG_TARGET,
+            "Rotated wallet node identity from '{}' to '{}'",
+            self.comms_config.node_identity.node_id(),
+            node_identity.node_id()
+        );
+        self.comms_config.node_identity = node_identity.clone();
+        Ok(node_identity)
+    }
 }
 
 /// A structure containing the config and services that a Wallet application will require. This struct will start up all
@@ -160,6 +195,12 @@ where
     ) -> Result<Wallet<T, U, V, W>, WalletError>
     {
         let db = WalletDatabase::new(wallet_backend);
+        let rotated_from = db
+            .get_comms_secret_key()
+            .await?
+            .filter(|key| key != config.comms_config.node_identity.secret_key())
+            .map(|key| NodeId::from_key(&CommsPublicKey::from_secret_key(&key)))
+            .transpose()?;
         // Persist the Comms Private Key provided to this function
         db.set_comms_secret_key(config.comms_config.node_identity.secret_key().clone())
             .await?;
@@ -227,8 +268,25 @@ where
         let dht = handles.expect_handle::<Dht>();
         let store_and_forward_requester = dht.store_and_forward_requester();
 
+        if let Some(old_node_id) = rotated_from {
+            Self::complete_identity_rotation(&comms, &dht, old_node_id).await?;
+        }
+
         let base_node_service_handle = handles.expect_handle::<BaseNodeServiceHandle>();
 
+        task::spawn(
+            OneSidedPaymentScanner::new(
+                db.clone(),
+                comms.node_identity().public_key().clone(),
+                comms.connectivity(),
+                base_node_service_handle.clone(),
+                output_manager_handle.clone(),
+                transaction_service_handle.clone(),
+                comms.shutdown_signal(),
+            )
+            .run(),
+        );
+
         Ok(Wallet {
             comms,
             dht_service: dht,
@@ -247,6 +305,46 @@ where
         })
     }
 
+    /// Remove the entry of the previous node identity from the peer database and announce the new identity to the
+    /// network as soon as the wallet has connectivity.
+    async fn complete_identity_rotation(comms: &CommsNode, dht: &Dht, old_node_id: NodeId) -> Result<(), WalletError> {
+        info!(
+            target: LOG_TARGET,
+            "Wallet node identity was rotated from '{}' to '{}'",
+            old_node_id,
+            comms.node_identity().node_id()
+        );
+        match comms.peer_manager().delete_peer(&old_node_id).await {
+            Ok(_) => debug!(
+                target: LOG_TARGET,
+                "Removed previous node identity '{}' from peer database", old_node_id
+            ),
+            Err(err) if err.is_peer_not_found() => {},
+            Err(err) => return Err(err.into()),
+        }
+
+        let mut connectivity = comms.connectivity();
+        let mut dht_requester = dht.dht_requester();
+        task::spawn(async move {
+            if let Err(err) = connectivity
+                .wait_for_connectivity(ROTATED_IDENTITY_ANNOUNCE_TIMEOUT)
+                .await
+            {
+                warn!(
+                    target: LOG_TARGET,
+                    "Wallet did not come online to announce its rotated node identity: {}", err
+                );
+                return;
+            }
+            match dht_requester.send_join().await {
+                Ok(_) => info!(target: LOG_TARGET, "Announced rotated node identity to the network"),
+                Err(err) => warn!(target: LOG_TARGET, "Failed to announce rotated node identity: {}", err),
+            }
+        });
+
+        Ok(())
+    }
+
     /// This method consumes the wallet so that the handles are dropped which will result in the services async loops
     /// exiting.
     pub async fn wait_until_shutdown(self) {
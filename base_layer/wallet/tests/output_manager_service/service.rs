@@ -24,9 +24,14 @@ use crate::support::{
     rpc::{BaseNodeWalletRpcMockService, BaseNodeWalletRpcMockState},
     utils::{make_input, make_input_with_features, random_string, TestParams},
 };
+use digest::Digest;
 use futures::{FutureExt, StreamExt};
 use rand::{rngs::OsRng, RngCore};
-use std::{sync::Arc, thread, time::Duration};
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
 use tari_comms::{
     peer_manager::{NodeIdentity, PeerFeatures},
     protocol::rpc::{mock::MockRpcServer, NamedProtocolService, RpcStatus},
@@ -39,28 +44,40 @@ use tari_comms::{
 use tari_core::{
     base_node::rpc::BaseNodeWalletRpcServer,
     consensus::{ConsensusConstantsBuilder, Network},
+    proto::base_node::{FeeHistogramBucket, FeeHistogramResponse},
     transactions::{
         fee::Fee,
         tari_amount::{uT, MicroTari},
-        transaction::{KernelFeatures, OutputFeatures, Transaction, UnblindedOutput},
+        transaction::{KernelFeatures, OutputFeatures, OutputFlags, Transaction, UnblindedOutput},
         transaction_protocol::{
+            build_challenge,
             recipient::RecipientState,
             sender::TransactionSenderMessage,
             single_receiver::SingleReceiverTransactionProtocol,
         },
-        types::{CryptoFactories, PrivateKey},
+        types::{CryptoFactories, PrivateKey, PublicKey, Signature},
         SenderTransactionProtocol,
     },
 };
-use tari_crypto::{hash::blake2::Blake256, keys::SecretKey};
+use tari_crypto::{
+    hash::blake2::Blake256,
+    keys::{DiffieHellmanSharedSecret, PublicKey as PublicKeyTrait, SecretKey},
+    tari_utilities::ByteArray,
+};
+use tari_key_manager::{key_manager::KeyManager, mnemonic::Mnemonic};
 use tari_service_framework::reply_channel;
 use tari_shutdown::Shutdown;
 use tari_wallet::{
-    base_node_service::{handle::BaseNodeServiceHandle, mock_base_node_service::MockBaseNodeService},
+    base_node_service::{
+        handle::{BaseNodeEvent, BaseNodeEventSender, BaseNodeServiceHandle},
+        mock_base_node_service::MockBaseNodeService,
+    },
     output_manager_service::{
         config::OutputManagerServiceConfig,
         error::{OutputManagerError, OutputManagerStorageError},
+        fee_oracle::FeeOracle,
         handle::{OutputManagerEvent, OutputManagerHandle},
+        key_manager_backend::KeyManagerBackend,
         protocols::txo_validation_protocol::TxoValidationType,
         service::OutputManagerService,
         storage::{
@@ -73,7 +90,7 @@ use tari_wallet::{
     },
     storage::sqlite_utilities::run_migration_and_create_sqlite_connection,
     transaction_service::handle::TransactionServiceHandle,
-    types::ValidationRetryStrategy,
+    types::{KeyDigest, ValidationRetryStrategy},
 };
 use tempfile::tempdir;
 use tokio::{
@@ -96,6 +113,27 @@ pub fn setup_output_manager_service<T: OutputManagerBackend + 'static>(
     BaseNodeWalletRpcMockState,
     ConnectivityManagerMockState,
 )
+{
+    setup_output_manager_service_with_extensions(runtime, backend, with_connection, None, None, None)
+}
+
+#[allow(clippy::type_complexity)]
+pub fn setup_output_manager_service_with_extensions<T: OutputManagerBackend + 'static>(
+    runtime: &mut Runtime,
+    backend: T,
+    with_connection: bool,
+    fee_oracle: Option<Arc<dyn FeeOracle>>,
+    key_manager_backend: Option<Arc<dyn KeyManagerBackend>>,
+    base_node_event_publisher: Option<BaseNodeEventSender>,
+) -> (
+    OutputManagerHandle,
+    Shutdown,
+    TransactionServiceHandle,
+    MockRpcServer<BaseNodeWalletRpcServer<BaseNodeWalletRpcMockService>, Substream>,
+    Arc<NodeIdentity>,
+    BaseNodeWalletRpcMockState,
+    ConnectivityManagerMockState,
+)
 {
     let shutdown = Shutdown::new();
     let factories = CryptoFactories::default();
@@ -110,7 +148,7 @@ pub fn setup_output_manager_service<T: OutputManagerBackend + 'static>(
     let constants = ConsensusConstantsBuilder::new(Network::Stibbons).build();
 
     let (sender, receiver_bns) = reply_channel::unbounded();
-    let (event_publisher_bns, _) = broadcast::channel(100);
+    let event_publisher_bns = base_node_event_publisher.unwrap_or_else(|| broadcast::channel(100).0);
 
     let basenode_service_handle = BaseNodeServiceHandle::new(sender, event_publisher_bns);
     let mut mock_base_node_service = MockBaseNodeService::new(receiver_bns, shutdown.to_signal());
@@ -161,6 +199,16 @@ pub fn setup_output_manager_service<T: OutputManagerBackend + 'static>(
             connectivity_manager,
         ))
         .unwrap();
+    let output_manager_service = match fee_oracle {
+        Some(fee_oracle) => output_manager_service.with_fee_oracle(fee_oracle),
+        None => output_manager_service,
+    };
+    let output_manager_service = match key_manager_backend {
+        Some(key_manager_backend) => output_manager_service
+            .with_key_manager_backend(key_manager_backend)
+            .unwrap(),
+        None => output_manager_service,
+    };
     let output_manager_service_handle = OutputManagerHandle::new(oms_request_sender, oms_event_publisher);
 
     runtime.spawn(async move { output_manager_service.start().await.unwrap() });
@@ -277,7 +325,7 @@ fn sending_transaction_and_confirmation<T: Clone + OutputManagerBackend + 'stati
     }
 
     runtime
-        .block_on(oms.confirm_transaction(sender_tx_id, tx.body.inputs().clone(), tx.body.outputs().clone()))
+        .block_on(oms.confirm_transaction(sender_tx_id, tx.body.inputs().clone(), tx.body.outputs().clone(), None))
         .unwrap();
 
     assert_eq!(
@@ -348,6 +396,26 @@ pub fn setup_oms_with_bn_state<T: OutputManagerBackend + 'static>(
     TransactionServiceHandle,
     BaseNodeServiceHandle,
 )
+{
+    setup_oms_with_bn_state_and_config(runtime, backend, height, OutputManagerServiceConfig {
+        base_node_query_timeout: Duration::from_secs(10),
+        max_utxo_query_size: 2,
+        peer_dial_retry_timeout: Duration::from_secs(5),
+        ..Default::default()
+    })
+}
+
+pub fn setup_oms_with_bn_state_and_config<T: OutputManagerBackend + 'static>(
+    runtime: &mut Runtime,
+    backend: T,
+    height: Option<u64>,
+    config: OutputManagerServiceConfig,
+) -> (
+    OutputManagerHandle,
+    Shutdown,
+    TransactionServiceHandle,
+    BaseNodeServiceHandle,
+)
 {
     let shutdown = Shutdown::new();
     let factories = CryptoFactories::default();
@@ -375,12 +443,7 @@ pub fn setup_oms_with_bn_state<T: OutputManagerBackend + 'static>(
 
     let output_manager_service = runtime
         .block_on(OutputManagerService::new(
-            OutputManagerServiceConfig {
-                base_node_query_timeout: Duration::from_secs(10),
-                max_utxo_query_size: 2,
-                peer_dial_retry_timeout: Duration::from_secs(5),
-                ..Default::default()
-            },
+            config,
             ts_handle.clone(),
             oms_request_receiver,
             OutputManagerDatabase::new(backend),
@@ -579,6 +642,158 @@ fn test_utxo_selection_with_chain_metadata<T: Clone + OutputManagerBackend + 'st
     }
 }
 
+fn test_utxo_selection_with_min_confirmations<T: Clone + OutputManagerBackend + 'static>(backend: T) {
+    let mut runtime = Runtime::new().unwrap();
+    let config = OutputManagerServiceConfig {
+        min_confirmations_for_spend: 3,
+        ..Default::default()
+    };
+
+    // The output is mined at height 10 and the tip is at height 11, so it only has 1 confirmation
+    let (mut oms, _shutdown, _, _) =
+        setup_oms_with_bn_state_and_config(&mut runtime, backend.clone(), Some(11), config.clone());
+
+    let (tx_id, sender_message) = generate_sender_transaction_message(MicroTari::from(10_000));
+    let rtp = runtime.block_on(oms.get_recipient_transaction(sender_message)).unwrap();
+    let output = match rtp.state {
+        RecipientState::Finalized(s) => s.output,
+        RecipientState::Failed(_) => panic!("Should not be in Failed state"),
+    };
+    runtime
+        .block_on(oms.confirm_transaction(tx_id, vec![], vec![output], Some(10)))
+        .unwrap();
+    let utxos = runtime.block_on(oms.get_unspent_outputs()).unwrap();
+    assert_eq!(utxos.len(), 1);
+
+    let amount = MicroTari::from(1000);
+    let fee_per_gram = MicroTari::from(10);
+    let err = runtime
+        .block_on(oms.prepare_transaction_to_send(amount, fee_per_gram, None, "".to_string()))
+        .unwrap_err();
+    assert!(matches!(err, OutputManagerError::NotEnoughFunds));
+
+    // Once the tip reaches height 13 the output has the required confirmations
+    let (mut oms, _shutdown, _, _) = setup_oms_with_bn_state_and_config(&mut runtime, backend, Some(13), config);
+    let stp = runtime
+        .block_on(oms.prepare_transaction_to_send(amount, fee_per_gram, None, "".to_string()))
+        .unwrap();
+    assert!(stp.get_tx_id().is_ok());
+}
+
+#[test]
+fn test_utxo_selection_with_min_confirmations_memory_db() {
+    test_utxo_selection_with_min_confirmations(OutputManagerMemoryDatabase::new());
+}
+
+#[test]
+fn test_utxo_selection_with_min_confirmations_sqlite_db() {
+    let db_name = format!("{}.sqlite3", random_string(8).as_str());
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+
+    test_utxo_selection_with_min_confirmations(OutputManagerSqliteDatabase::new(connection, None));
+}
+
+fn test_utxo_selection_with_min_confirmations_and_unknown_tip<T: Clone + OutputManagerBackend + 'static>(backend: T) {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+    let config = OutputManagerServiceConfig {
+        min_confirmations_for_spend: 3,
+        ..Default::default()
+    };
+    let (mut oms, _shutdown, _, _) = setup_oms_with_bn_state_and_config(&mut runtime, backend, None, config);
+
+    // Without a chain tip the confirmations of a mined output cannot be counted
+    let (tx_id, sender_message) = generate_sender_transaction_message(MicroTari::from(10_000));
+    let rtp = runtime.block_on(oms.get_recipient_transaction(sender_message)).unwrap();
+    let output = match rtp.state {
+        RecipientState::Finalized(s) => s.output,
+        RecipientState::Failed(_) => panic!("Should not be in Failed state"),
+    };
+    runtime
+        .block_on(oms.confirm_transaction(tx_id, vec![], vec![output], Some(10)))
+        .unwrap();
+
+    let amount = MicroTari::from(1000);
+    let fee_per_gram = MicroTari::from(10);
+    let err = runtime
+        .block_on(oms.prepare_transaction_to_send(amount, fee_per_gram, None, "".to_string()))
+        .unwrap_err();
+    assert!(matches!(err, OutputManagerError::NotEnoughFunds));
+
+    // Recovered outputs have no mined height
+    let (_, uo) = make_input(&mut OsRng.clone(), MicroTari::from(5_000), &factories.commitment);
+    runtime.block_on(oms.add_output(uo)).unwrap();
+    let stp = runtime
+        .block_on(oms.prepare_transaction_to_send(amount, fee_per_gram, None, "".to_string()))
+        .unwrap();
+    assert!(stp.get_tx_id().is_ok());
+
+    // Only the mined output was left unspent
+    let utxos = runtime.block_on(oms.get_unspent_outputs()).unwrap();
+    assert_eq!(utxos.len(), 1);
+    assert_eq!(utxos[0].value, MicroTari::from(10_000));
+}
+
+#[test]
+fn test_utxo_selection_with_min_confirmations_and_unknown_tip_memory_db() {
+    test_utxo_selection_with_min_confirmations_and_unknown_tip(OutputManagerMemoryDatabase::new());
+}
+
+#[test]
+fn test_utxo_selection_with_min_confirmations_and_unknown_tip_sqlite_db() {
+    let db_name = format!("{}.sqlite3", random_string(8).as_str());
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+
+    test_utxo_selection_with_min_confirmations_and_unknown_tip(OutputManagerSqliteDatabase::new(connection, None));
+}
+
+#[test]
+fn test_utxo_selection_excludes_outputs_with_excluded_features() {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+    let config = OutputManagerServiceConfig {
+        coin_selection_excluded_output_flags: OutputFlags::COINBASE_OUTPUT,
+        ..Default::default()
+    };
+    let (mut oms, _shutdown, _, _) =
+        setup_oms_with_bn_state_and_config(&mut runtime, OutputManagerMemoryDatabase::new(), Some(10), config);
+
+    let amount = MicroTari::from(1000);
+    let fee_per_gram = MicroTari::from(10);
+    let (_, plain_output) = make_input(&mut OsRng.clone(), 2 * amount, &factories.commitment);
+    runtime.block_on(oms.add_output(plain_output)).unwrap();
+    let (_, feature_output) = make_input_with_features(
+        &mut OsRng.clone(),
+        10 * amount,
+        &factories.commitment,
+        Some(OutputFeatures {
+            flags: OutputFlags::COINBASE_OUTPUT,
+            maturity: 0,
+        }),
+    );
+    runtime.block_on(oms.add_output(feature_output)).unwrap();
+
+    // Only the plain output can fund a send
+    let err = runtime
+        .block_on(oms.prepare_transaction_to_send(5 * amount, fee_per_gram, None, "".to_string()))
+        .unwrap_err();
+    assert!(matches!(err, OutputManagerError::NotEnoughFunds));
+
+    let stp = runtime
+        .block_on(oms.prepare_transaction_to_send(amount, fee_per_gram, None, "".to_string()))
+        .unwrap();
+    assert!(stp.get_tx_id().is_ok());
+    let utxos = runtime.block_on(oms.get_unspent_outputs()).unwrap();
+    assert_eq!(utxos.len(), 1);
+    assert_eq!(utxos[0].features.flags, OutputFlags::COINBASE_OUTPUT);
+}
+
 #[test]
 fn fee_estimate_sqlite_db() {
     let db_name = format!("{}.sqlite3", random_string(8).as_str());
@@ -704,7 +919,7 @@ fn send_no_change<T: OutputManagerBackend + 'static>(backend: T) {
     let tx = stp.get_transaction().unwrap();
 
     runtime
-        .block_on(oms.confirm_transaction(sender_tx_id, tx.body.inputs().clone(), tx.body.outputs().clone()))
+        .block_on(oms.confirm_transaction(sender_tx_id, tx.body.inputs().clone(), tx.body.outputs().clone(), None))
         .unwrap();
 
     assert_eq!(runtime.block_on(oms.get_pending_transactions()).unwrap().len(), 0);
@@ -816,7 +1031,7 @@ fn receiving_and_confirmation<T: OutputManagerBackend + 'static>(backend: T) {
     };
 
     runtime
-        .block_on(oms.confirm_transaction(tx_id, vec![], vec![output]))
+        .block_on(oms.confirm_transaction(tx_id, vec![], vec![output], None))
         .unwrap();
 
     assert_eq!(runtime.block_on(oms.get_pending_transactions()).unwrap().len(), 0);
@@ -996,6 +1211,76 @@ fn test_get_balance_sqlite_db() {
     test_get_balance(OutputManagerSqliteDatabase::new(connection, None));
 }
 
+fn test_get_balance_breakdown<T: OutputManagerBackend + 'static>(backend: T) {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+
+    // setup with chain metadata at a height of 6
+    let (mut oms, _shutdown, _, _) = setup_oms_with_bn_state(&mut runtime, backend, Some(6));
+
+    let amount = MicroTari::from(1000);
+    let mut mature = MicroTari::from(0);
+    let mut immature = MicroTari::from(0);
+    // utxos with maturities 1 to 3 are spendable, 8 to 9 are still time-locked
+    for i in (1..=3).chain(8..=9) {
+        let (_, uo) = make_input_with_features(
+            &mut OsRng.clone(),
+            i * amount,
+            &factories.commitment,
+            Some(OutputFeatures::with_maturity(i)),
+        );
+        if i <= 6 {
+            mature += uo.value;
+        } else {
+            immature += uo.value;
+        }
+        runtime.block_on(oms.add_output(uo)).unwrap();
+    }
+
+    let balance = runtime.block_on(oms.get_balance()).unwrap();
+    assert_eq!(balance.available_balance, mature + immature);
+    assert_eq!(balance.time_locked_balance, Some(immature));
+    assert_eq!(balance.spendable_balance(), mature);
+    assert_eq!(balance.pending_incoming_balance, MicroTari::from(0));
+    assert_eq!(balance.pending_outgoing_balance, MicroTari::from(0));
+
+    let stp = runtime
+        .block_on(oms.prepare_transaction_to_send(MicroTari::from(500), MicroTari::from(10), None, "".to_string()))
+        .unwrap();
+    let change_val = stp.get_change_amount().unwrap();
+
+    let recv_value = MicroTari::from(1500);
+    let (_tx_id, sender_message) = generate_sender_transaction_message(recv_value);
+    let _rtp = runtime.block_on(oms.get_recipient_transaction(sender_message)).unwrap();
+
+    let balance = runtime.block_on(oms.get_balance()).unwrap();
+    assert!(balance.pending_outgoing_balance > MicroTari::from(0));
+    assert_eq!(
+        balance.available_balance,
+        mature + immature - balance.pending_outgoing_balance
+    );
+    // Only mature outputs may be encumbered by the send, so the time-locked amount is untouched
+    assert_eq!(balance.time_locked_balance, Some(immature));
+    assert_eq!(balance.spendable_balance(), mature - balance.pending_outgoing_balance);
+    assert_eq!(balance.pending_incoming_balance, recv_value + change_val);
+}
+
+#[test]
+fn test_get_balance_breakdown_memory_db() {
+    test_get_balance_breakdown(OutputManagerMemoryDatabase::new());
+}
+
+#[test]
+fn test_get_balance_breakdown_sqlite_db() {
+    let db_name = format!("{}.sqlite3", random_string(8).as_str());
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+
+    test_get_balance_breakdown(OutputManagerSqliteDatabase::new(connection, None));
+}
+
 fn test_confirming_received_output<T: OutputManagerBackend + 'static>(backend: T) {
     let mut runtime = Runtime::new().unwrap();
 
@@ -1012,7 +1297,7 @@ fn test_confirming_received_output<T: OutputManagerBackend + 'static>(backend: T
         RecipientState::Failed(_) => panic!("Should not be in Failed state"),
     };
     runtime
-        .block_on(oms.confirm_transaction(tx_id, vec![], vec![output.clone()]))
+        .block_on(oms.confirm_transaction(tx_id, vec![], vec![output.clone()], None))
         .unwrap();
     assert_eq!(runtime.block_on(oms.get_balance()).unwrap().available_balance, value);
 
@@ -1098,7 +1383,7 @@ fn sending_transaction_with_short_term_clear<T: Clone + OutputManagerBackend + '
     let tx = runtime.block_on(complete_transaction(stp, oms.clone()));
 
     runtime
-        .block_on(oms.confirm_transaction(sender_tx_id, tx.body.inputs().clone(), tx.body.outputs().clone()))
+        .block_on(oms.confirm_transaction(sender_tx_id, tx.body.inputs().clone(), tx.body.outputs().clone(), None))
         .unwrap();
 
     let balance = runtime.block_on(oms.get_balance()).unwrap();
@@ -1206,77 +1491,265 @@ fn coin_split_no_change_sqlite_db() {
     coin_split_no_change(OutputManagerSqliteDatabase::new(connection, None));
 }
 
-fn handle_coinbase<T: Clone + OutputManagerBackend + 'static>(backend: T) {
-    let mut runtime = Runtime::new().unwrap();
+fn sweep_small_outputs<T: Clone + OutputManagerBackend + 'static>(backend: T) {
     let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
 
-    let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(&mut runtime, backend, true);
+    // setup with chain metadata at a height of 10
+    let (mut oms, _shutdown, _, _) = setup_oms_with_bn_state(&mut runtime, backend, Some(10));
 
-    let reward1 = MicroTari::from(1000);
-    let fees1 = MicroTari::from(500);
-    let value1 = reward1 + fees1;
-    let reward2 = MicroTari::from(2000);
-    let fees2 = MicroTari::from(500);
-    let value2 = reward2 + fees2;
-    let reward3 = MicroTari::from(3000);
-    let fees3 = MicroTari::from(500);
-    let value3 = reward3 + fees3;
+    let fee_per_gram = MicroTari::from(5);
+    let err = runtime
+        .block_on(oms.create_sweep_transaction(fee_per_gram, 10, None))
+        .unwrap_err();
+    assert!(matches!(err, OutputManagerError::NotEnoughOutputsToSweep(0)));
 
-    let _ = runtime
-        .block_on(oms.get_coinbase_transaction(1, reward1, fees1, 1))
-        .unwrap();
-    assert_eq!(runtime.block_on(oms.get_unspent_outputs()).unwrap().len(), 0);
-    assert_eq!(runtime.block_on(oms.get_pending_transactions()).unwrap().len(), 1);
-    assert_eq!(
-        runtime.block_on(oms.get_balance()).unwrap().pending_incoming_balance,
-        value1
-    );
-    let _tx2 = runtime
-        .block_on(oms.get_coinbase_transaction(2, reward2, fees2, 1))
-        .unwrap();
-    assert_eq!(runtime.block_on(oms.get_unspent_outputs()).unwrap().len(), 0);
-    assert_eq!(runtime.block_on(oms.get_pending_transactions()).unwrap().len(), 1);
-    assert_eq!(
-        runtime.block_on(oms.get_balance()).unwrap().pending_incoming_balance,
-        value2
-    );
-    let tx3 = runtime
-        .block_on(oms.get_coinbase_transaction(3, reward3, fees3, 2))
-        .unwrap();
-    assert_eq!(runtime.block_on(oms.get_unspent_outputs()).unwrap().len(), 0);
-    assert_eq!(runtime.block_on(oms.get_pending_transactions()).unwrap().len(), 2);
-    assert_eq!(
-        runtime.block_on(oms.get_balance()).unwrap().pending_incoming_balance,
-        value2 + value3
+    // An immature coinbase that is smaller than every other output must never be swept
+    let (_, coinbase) = make_input_with_features(
+        &mut OsRng.clone(),
+        MicroTari::from(100),
+        &factories.commitment,
+        Some(OutputFeatures::create_coinbase(20)),
     );
+    runtime.block_on(oms.add_output(coinbase)).unwrap();
 
-    let output = tx3.body.outputs()[0].clone();
+    let mut values = Vec::new();
+    for i in 1..=12 {
+        let value = i * MicroTari::from(1000);
+        let (_, uo) = make_input(&mut OsRng.clone(), value, &factories.commitment);
+        runtime.block_on(oms.add_output(uo)).unwrap();
+        values.push(value);
+    }
 
-    let rewind_public_keys = runtime.block_on(oms.get_rewind_public_keys()).unwrap();
-    let rewind_result = output
-        .rewind_range_proof_value_only(
-            &factories.range_proof,
-            &rewind_public_keys.rewind_public_key,
-            &rewind_public_keys.rewind_blinding_public_key,
-        )
-        .unwrap();
-    assert_eq!(rewind_result.committed_value, value3);
+    // A fee this large would leave an output worth less than it costs to spend
+    let err = runtime
+        .block_on(oms.create_sweep_transaction(MicroTari::from(2_000), 10, None))
+        .unwrap_err();
+    assert!(matches!(err, OutputManagerError::SweepOutputIsDust(_)));
 
-    runtime
-        .block_on(oms.confirm_transaction(3, vec![], vec![output]))
+    let (_tx_id, tx, fee, amount) = runtime
+        .block_on(oms.create_sweep_transaction(fee_per_gram, 10, None))
         .unwrap();
+    assert_eq!(tx.body.inputs().len(), 10);
+    assert_eq!(tx.body.outputs().len(), 1);
+    assert_eq!(fee, Fee::calculate(fee_per_gram, 1, 10, 1));
+    assert_eq!(tx.body.get_total_fee(), fee);
+    assert_eq!(amount, values.iter().take(10).sum::<MicroTari>());
 
-    assert_eq!(runtime.block_on(oms.get_pending_transactions()).unwrap().len(), 1);
-    assert_eq!(runtime.block_on(oms.get_unspent_outputs()).unwrap().len(), 1);
-    assert_eq!(runtime.block_on(oms.get_balance()).unwrap().available_balance, value3);
-    assert_eq!(
-        runtime.block_on(oms.get_balance()).unwrap().pending_incoming_balance,
-        value2
-    );
-    assert_eq!(
-        runtime.block_on(oms.get_balance()).unwrap().pending_outgoing_balance,
-        MicroTari::from(0)
-    );
+    // The two largest outputs and the immature coinbase remain
+    let utxos = runtime.block_on(oms.get_unspent_outputs()).unwrap();
+    assert_eq!(utxos.len(), 3);
+    assert!(utxos.iter().any(|u| u.features.maturity == 20));
+
+    let balance = runtime.block_on(oms.get_balance()).unwrap();
+    assert_eq!(balance.pending_outgoing_balance, amount);
+    assert_eq!(balance.pending_incoming_balance, amount - fee);
+}
+
+#[test]
+fn sweep_small_outputs_memory_db() {
+    sweep_small_outputs(OutputManagerMemoryDatabase::new());
+}
+
+#[test]
+fn sweep_small_outputs_sqlite_db() {
+    let db_name = format!("{}.sqlite3", random_string(8).as_str());
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+
+    sweep_small_outputs(OutputManagerSqliteDatabase::new(connection, None));
+}
+
+fn sweep_with_min_confirmations<T: Clone + OutputManagerBackend + 'static>(backend: T) {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+    let config = OutputManagerServiceConfig {
+        min_confirmations_for_spend: 3,
+        ..Default::default()
+    };
+    let (mut oms, _shutdown, _, _) = setup_oms_with_bn_state_and_config(&mut runtime, backend, Some(11), config);
+
+    // The output is mined at height 10 and the tip is at height 11, so it only has 1 confirmation
+    let (tx_id, sender_message) = generate_sender_transaction_message(MicroTari::from(10_000));
+    let rtp = runtime.block_on(oms.get_recipient_transaction(sender_message)).unwrap();
+    let output = match rtp.state {
+        RecipientState::Finalized(s) => s.output,
+        RecipientState::Failed(_) => panic!("Should not be in Failed state"),
+    };
+    runtime
+        .block_on(oms.confirm_transaction(tx_id, vec![], vec![output], Some(10)))
+        .unwrap();
+
+    // Recovered outputs have no mined height
+    let mut recovered_value = MicroTari::from(0);
+    for i in 1..=2 {
+        let value = i * MicroTari::from(5_000);
+        let (_, uo) = make_input(&mut OsRng.clone(), value, &factories.commitment);
+        runtime.block_on(oms.add_output(uo)).unwrap();
+        recovered_value += value;
+    }
+
+    let fee_per_gram = MicroTari::from(5);
+    let (_tx_id, tx, fee, amount) = runtime
+        .block_on(oms.create_sweep_transaction(fee_per_gram, 10, None))
+        .unwrap();
+    assert_eq!(tx.body.inputs().len(), 2);
+    assert_eq!(fee, Fee::calculate(fee_per_gram, 1, 2, 1));
+    assert_eq!(amount, recovered_value);
+
+    // Only the output without enough confirmations remains
+    let utxos = runtime.block_on(oms.get_unspent_outputs()).unwrap();
+    assert_eq!(utxos.len(), 1);
+    assert_eq!(utxos[0].value, MicroTari::from(10_000));
+}
+
+#[test]
+fn sweep_with_min_confirmations_memory_db() {
+    sweep_with_min_confirmations(OutputManagerMemoryDatabase::new());
+}
+
+#[test]
+fn sweep_with_min_confirmations_sqlite_db() {
+    let db_name = format!("{}.sqlite3", random_string(8).as_str());
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+
+    sweep_with_min_confirmations(OutputManagerSqliteDatabase::new(connection, None));
+}
+
+fn sweep_with_min_confirmations_and_unknown_tip<T: Clone + OutputManagerBackend + 'static>(backend: T) {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+    let config = OutputManagerServiceConfig {
+        min_confirmations_for_spend: 3,
+        ..Default::default()
+    };
+    let (mut oms, _shutdown, _, _) = setup_oms_with_bn_state_and_config(&mut runtime, backend, None, config);
+
+    // Without a chain tip the confirmations of a mined output cannot be counted
+    let (tx_id, sender_message) = generate_sender_transaction_message(MicroTari::from(10_000));
+    let rtp = runtime.block_on(oms.get_recipient_transaction(sender_message)).unwrap();
+    let output = match rtp.state {
+        RecipientState::Finalized(s) => s.output,
+        RecipientState::Failed(_) => panic!("Should not be in Failed state"),
+    };
+    runtime
+        .block_on(oms.confirm_transaction(tx_id, vec![], vec![output], Some(10)))
+        .unwrap();
+
+    let fee_per_gram = MicroTari::from(5);
+    let err = runtime
+        .block_on(oms.create_sweep_transaction(fee_per_gram, 10, None))
+        .unwrap_err();
+    assert!(matches!(err, OutputManagerError::NotEnoughOutputsToSweep(0)));
+
+    // Recovered outputs have no mined height
+    let mut recovered_value = MicroTari::from(0);
+    for i in 1..=2 {
+        let value = i * MicroTari::from(5_000);
+        let (_, uo) = make_input(&mut OsRng.clone(), value, &factories.commitment);
+        runtime.block_on(oms.add_output(uo)).unwrap();
+        recovered_value += value;
+    }
+
+    let (_tx_id, tx, _fee, amount) = runtime
+        .block_on(oms.create_sweep_transaction(fee_per_gram, 10, None))
+        .unwrap();
+    assert_eq!(tx.body.inputs().len(), 2);
+    assert_eq!(amount, recovered_value);
+}
+
+#[test]
+fn sweep_with_min_confirmations_and_unknown_tip_memory_db() {
+    sweep_with_min_confirmations_and_unknown_tip(OutputManagerMemoryDatabase::new());
+}
+
+#[test]
+fn sweep_with_min_confirmations_and_unknown_tip_sqlite_db() {
+    let db_name = format!("{}.sqlite3", random_string(8).as_str());
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+
+    sweep_with_min_confirmations_and_unknown_tip(OutputManagerSqliteDatabase::new(connection, None));
+}
+
+fn handle_coinbase<T: Clone + OutputManagerBackend + 'static>(backend: T) {
+    let mut runtime = Runtime::new().unwrap();
+    let factories = CryptoFactories::default();
+
+    let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(&mut runtime, backend, true);
+
+    let reward1 = MicroTari::from(1000);
+    let fees1 = MicroTari::from(500);
+    let value1 = reward1 + fees1;
+    let reward2 = MicroTari::from(2000);
+    let fees2 = MicroTari::from(500);
+    let value2 = reward2 + fees2;
+    let reward3 = MicroTari::from(3000);
+    let fees3 = MicroTari::from(500);
+    let value3 = reward3 + fees3;
+
+    let _ = runtime
+        .block_on(oms.get_coinbase_transaction(1, reward1, fees1, 1))
+        .unwrap();
+    assert_eq!(runtime.block_on(oms.get_unspent_outputs()).unwrap().len(), 0);
+    assert_eq!(runtime.block_on(oms.get_pending_transactions()).unwrap().len(), 1);
+    assert_eq!(
+        runtime.block_on(oms.get_balance()).unwrap().pending_incoming_balance,
+        value1
+    );
+    let _tx2 = runtime
+        .block_on(oms.get_coinbase_transaction(2, reward2, fees2, 1))
+        .unwrap();
+    assert_eq!(runtime.block_on(oms.get_unspent_outputs()).unwrap().len(), 0);
+    assert_eq!(runtime.block_on(oms.get_pending_transactions()).unwrap().len(), 1);
+    assert_eq!(
+        runtime.block_on(oms.get_balance()).unwrap().pending_incoming_balance,
+        value2
+    );
+    let tx3 = runtime
+        .block_on(oms.get_coinbase_transaction(3, reward3, fees3, 2))
+        .unwrap();
+    assert_eq!(runtime.block_on(oms.get_unspent_outputs()).unwrap().len(), 0);
+    assert_eq!(runtime.block_on(oms.get_pending_transactions()).unwrap().len(), 2);
+    assert_eq!(
+        runtime.block_on(oms.get_balance()).unwrap().pending_incoming_balance,
+        value2 + value3
+    );
+
+    let output = tx3.body.outputs()[0].clone();
+
+    let rewind_public_keys = runtime.block_on(oms.get_rewind_public_keys()).unwrap();
+    let rewind_result = output
+        .rewind_range_proof_value_only(
+            &factories.range_proof,
+            &rewind_public_keys.rewind_public_key,
+            &rewind_public_keys.rewind_blinding_public_key,
+        )
+        .unwrap();
+    assert_eq!(rewind_result.committed_value, value3);
+
+    runtime
+        .block_on(oms.confirm_transaction(3, vec![], vec![output], None))
+        .unwrap();
+
+    assert_eq!(runtime.block_on(oms.get_pending_transactions()).unwrap().len(), 1);
+    assert_eq!(runtime.block_on(oms.get_unspent_outputs()).unwrap().len(), 1);
+    assert_eq!(runtime.block_on(oms.get_balance()).unwrap().available_balance, value3);
+    assert_eq!(
+        runtime.block_on(oms.get_balance()).unwrap().pending_incoming_balance,
+        value2
+    );
+    assert_eq!(
+        runtime.block_on(oms.get_balance()).unwrap().pending_outgoing_balance,
+        MicroTari::from(0)
+    );
 }
 
 #[test]
@@ -1496,6 +1969,217 @@ fn test_utxo_stxo_invalid_txo_validation() {
     assert!(outputs.iter().any(|o| o == &spent_output1));
 }
 
+#[test]
+fn test_spent_output_reappearing_on_chain_is_reorged() {
+    let factories = CryptoFactories::default();
+
+    let mut runtime = Runtime::new().unwrap();
+    let backend = OutputManagerMemoryDatabase::new();
+
+    let spent_key = PrivateKey::random(&mut OsRng);
+    let spent_output = UnblindedOutput::new(MicroTari::from(1000), spent_key, None);
+    let spent_tx_output = spent_output.as_transaction_output(&factories).unwrap();
+    let spent_db_output = DbUnblindedOutput::from_unblinded_output(spent_output.clone(), &factories).unwrap();
+    let spent_commitment = spent_db_output.commitment.clone();
+
+    backend
+        .write(WriteOperation::Insert(DbKeyValuePair::SpentOutput(
+            spent_db_output.commitment.clone(),
+            Box::new(spent_db_output),
+        )))
+        .unwrap();
+
+    let (mut oms, _shutdown, _ts, _mock_rpc_server, server_node_identity, rpc_service_state, _) =
+        setup_output_manager_service(&mut runtime, backend, true);
+    let mut event_stream = oms.get_event_stream_fused();
+
+    let balance = runtime.block_on(oms.get_balance()).unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(0));
+
+    // The spending transaction was reorged out so the base node reports our spent output as unspent again
+    rpc_service_state.set_utxos(vec![spent_tx_output]);
+
+    runtime
+        .block_on(oms.set_base_node_public_key(server_node_identity.public_key().clone()))
+        .unwrap();
+
+    runtime
+        .block_on(oms.validate_txos(TxoValidationType::Spent, ValidationRetryStrategy::UntilSuccess))
+        .unwrap();
+
+    runtime.block_on(async {
+        let mut delay = delay_for(Duration::from_secs(60)).fuse();
+        let mut reorged = false;
+        let mut success = false;
+        loop {
+            futures::select! {
+                event = event_stream.select_next_some() => {
+                    if let Ok(msg) = event {
+                        match (*msg).clone() {
+                            OutputManagerEvent::OutputReorged(commitment) => {
+                                assert_eq!(commitment, spent_commitment);
+                                reorged = true;
+                            },
+                            OutputManagerEvent::TxoValidationSuccess(_, TxoValidationType::Spent) => {
+                                success = true;
+                            },
+                            _ => (),
+                        }
+                        if reorged && success {
+                            break;
+                        }
+                    }
+                },
+                () = delay => {
+                    break;
+                },
+            }
+        }
+        assert!(reorged, "Did not receive output reorged event");
+        assert!(success, "Did not receive validation success event");
+    });
+
+    let outputs = runtime.block_on(oms.get_unspent_outputs()).unwrap();
+    assert_eq!(outputs.len(), 1);
+    assert!(outputs.iter().any(|o| o == &spent_output));
+
+    let balance = runtime.block_on(oms.get_balance()).unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(1000));
+}
+
+#[test]
+fn test_txo_validation_publishes_progress_per_batch() {
+    let factories = CryptoFactories::default();
+
+    let mut runtime = Runtime::new().unwrap();
+    let backend = OutputManagerMemoryDatabase::new();
+
+    // With a max query size of 2 the 5 outputs are validated in 3 batches
+    let mut tx_outputs = Vec::new();
+    for i in 1..=5 {
+        let output = UnblindedOutput::new(MicroTari::from(i * 1000), PrivateKey::random(&mut OsRng), None);
+        tx_outputs.push(output.as_transaction_output(&factories).unwrap());
+        let db_output = DbUnblindedOutput::from_unblinded_output(output, &factories).unwrap();
+        backend
+            .write(WriteOperation::Insert(DbKeyValuePair::UnspentOutput(
+                db_output.commitment.clone(),
+                Box::new(db_output),
+            )))
+            .unwrap();
+    }
+
+    let (mut oms, _shutdown, _ts, _mock_rpc_server, server_node_identity, rpc_service_state, _) =
+        setup_output_manager_service(&mut runtime, backend, true);
+    let mut event_stream = oms.get_event_stream_fused();
+    rpc_service_state.set_utxos(tx_outputs);
+
+    runtime
+        .block_on(oms.set_base_node_public_key(server_node_identity.public_key().clone()))
+        .unwrap();
+
+    runtime
+        .block_on(oms.validate_txos(TxoValidationType::Unspent, ValidationRetryStrategy::UntilSuccess))
+        .unwrap();
+
+    runtime.block_on(async {
+        let mut delay = delay_for(Duration::from_secs(60)).fuse();
+        let mut progress = Vec::new();
+        let mut success = false;
+        loop {
+            futures::select! {
+                event = event_stream.select_next_some() => {
+                    if let Ok(msg) = event {
+                        match (*msg).clone() {
+                            OutputManagerEvent::TxoValidationProgress { completed, total, validation_type, .. } => {
+                                assert_eq!(validation_type, TxoValidationType::Unspent);
+                                assert_eq!(total, 5);
+                                progress.push(completed);
+                            },
+                            OutputManagerEvent::TxoValidationSuccess(_, TxoValidationType::Unspent) => {
+                                success = true;
+                                break;
+                            },
+                            _ => (),
+                        }
+                    }
+                },
+                () = delay => {
+                    break;
+                },
+            }
+        }
+        assert!(success, "Did not receive validation success event");
+        assert_eq!(progress.len(), 3);
+        assert!(progress.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(progress.last(), Some(&5));
+    });
+}
+
+#[test]
+fn test_tip_change_triggers_utxo_validation() {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+
+    let (base_node_event_publisher, _) = broadcast::channel(100);
+    let (mut oms, _shutdown, _ts, _mock_rpc_server, server_node_identity, rpc_service_state, _) =
+        setup_output_manager_service_with_extensions(
+            &mut runtime,
+            OutputManagerMemoryDatabase::new(),
+            true,
+            None,
+            None,
+            Some(base_node_event_publisher.clone()),
+        );
+    let mut event_stream = oms.get_event_stream_fused();
+
+    let (_ti, uo) = make_input(&mut OsRng, MicroTari::from(1000), &factories.commitment);
+    runtime.block_on(oms.add_output(uo.clone())).unwrap();
+    rpc_service_state.set_utxos(vec![uo.as_transaction_output(&factories).unwrap()]);
+    runtime
+        .block_on(oms.set_base_node_public_key(server_node_identity.public_key().clone()))
+        .unwrap();
+
+    // No validation is started until the chain tip changes
+    assert!(runtime
+        .block_on(rpc_service_state.wait_pop_fetch_utxos_calls(1, Duration::from_secs(1)))
+        .is_err());
+
+    base_node_event_publisher
+        .send(Arc::new(BaseNodeEvent::TipChanged {
+            height: 10,
+            hash: vec![1u8; 32],
+        }))
+        .unwrap();
+
+    let _fetch_utxo_calls = runtime
+        .block_on(rpc_service_state.wait_pop_fetch_utxos_calls(1, Duration::from_secs(60)))
+        .unwrap();
+
+    runtime.block_on(async {
+        let mut delay = delay_for(Duration::from_secs(60)).fuse();
+        let mut success = false;
+        loop {
+            futures::select! {
+                event = event_stream.select_next_some() => {
+                    if let Ok(msg) = event {
+                        if let OutputManagerEvent::TxoValidationSuccess(_, TxoValidationType::Unspent) = (*msg).clone() {
+                            success = true;
+                            break;
+                        };
+                    }
+                },
+                () = delay => {
+                    break;
+                },
+            }
+        }
+        assert!(success, "Did not receive validation success event");
+    });
+
+    let outputs = runtime.block_on(oms.get_unspent_outputs()).unwrap();
+    assert_eq!(outputs, vec![uo]);
+}
+
 #[test]
 fn test_base_node_switch_during_validation() {
     let factories = CryptoFactories::default();
@@ -1842,3 +2526,306 @@ fn test_txo_validation_base_node_not_synced() {
     assert_eq!(outputs.len(), 1);
     assert!(outputs.iter().any(|o| o == &unspent_output1));
 }
+
+/// A fee oracle that always recommends the same fee per gram and records the mempool histograms it was given
+struct StubFeeOracle {
+    fee_per_gram: MicroTari,
+    histograms: Mutex<Vec<Option<Vec<FeeHistogramBucket>>>>,
+}
+
+impl FeeOracle for StubFeeOracle {
+    fn recommended_fee_per_gram(&self, mempool_fee_histogram: Option<&[FeeHistogramBucket]>) -> MicroTari {
+        self.histograms
+            .lock()
+            .unwrap()
+            .push(mempool_fee_histogram.map(|h| h.to_vec()));
+        self.fee_per_gram
+    }
+}
+
+#[test]
+fn test_send_with_recommended_fee_per_gram() {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+
+    let fee_oracle = Arc::new(StubFeeOracle {
+        fee_per_gram: MicroTari::from(40),
+        histograms: Mutex::new(Vec::new()),
+    });
+    let (mut oms, _shutdown, _ts, _mock_rpc_server, server_node_identity, rpc_service_state, _) =
+        setup_output_manager_service_with_extensions(
+            &mut runtime,
+            OutputManagerMemoryDatabase::new(),
+            true,
+            Some(fee_oracle.clone()),
+            None,
+            None,
+        );
+
+    let histogram = vec![FeeHistogramBucket {
+        fee_per_gram: 30,
+        transaction_count: 2,
+        weight: 200,
+    }];
+    rpc_service_state.set_fee_histogram_response(FeeHistogramResponse {
+        buckets: histogram.clone(),
+        is_synced: true,
+    });
+
+    // Without a base node the oracle is still consulted, just without a histogram
+    let fee_per_gram = runtime.block_on(oms.get_recommended_fee_per_gram()).unwrap();
+    assert_eq!(fee_per_gram, MicroTari::from(40));
+
+    runtime
+        .block_on(oms.set_base_node_public_key(server_node_identity.public_key().clone()))
+        .unwrap();
+
+    let fee_per_gram = runtime.block_on(oms.get_recommended_fee_per_gram()).unwrap();
+    assert_eq!(fee_per_gram, MicroTari::from(40));
+    assert_eq!(*fee_oracle.histograms.lock().unwrap(), vec![None, Some(histogram)]);
+
+    let (_ti, uo) = make_input(&mut OsRng.clone(), MicroTari::from(100_000), &factories.commitment);
+    runtime.block_on(oms.add_output(uo)).unwrap();
+
+    let stp = runtime
+        .block_on(oms.prepare_transaction_to_send(MicroTari::from(5000), fee_per_gram, None, "".to_string()))
+        .unwrap();
+    let tx = runtime.block_on(complete_transaction(stp, oms.clone()));
+    assert_eq!(tx.body.get_total_fee(), Fee::calculate(MicroTari::from(40), 1, 1, 2));
+}
+
+#[test]
+fn test_one_sided_payment_is_found_by_recipient() {
+    let mut runtime = Runtime::new().unwrap();
+
+    let (mut alice_oms, _shutdown_a, _, _, _, _, _) =
+        setup_output_manager_service(&mut runtime, OutputManagerMemoryDatabase::new(), true);
+    let (mut bob_oms, _shutdown_b, _, _, _, _, _) =
+        setup_output_manager_service(&mut runtime, OutputManagerMemoryDatabase::new(), true);
+    let (mut eve_oms, _shutdown_e, _, _, _, _, _) =
+        setup_output_manager_service(&mut runtime, OutputManagerMemoryDatabase::new(), true);
+
+    let bob_address = runtime.block_on(bob_oms.get_one_sided_payment_address()).unwrap();
+
+    runtime
+        .block_on(alice_oms.add_output(UnblindedOutput::new(
+            MicroTari::from(10_000),
+            PrivateKey::random(&mut OsRng),
+            None,
+        )))
+        .unwrap();
+
+    let amount = MicroTari::from(5_000);
+    let (_, fee, tx) = runtime
+        .block_on(alice_oms.create_one_sided_transaction(
+            amount,
+            MicroTari::from(20),
+            None,
+            "one-sided".to_string(),
+            bob_address,
+        ))
+        .unwrap();
+    assert_eq!(tx.body.outputs().len(), 2);
+    let alice_balance = runtime.block_on(alice_oms.get_balance()).unwrap();
+    assert_eq!(
+        alice_balance.pending_incoming_balance,
+        MicroTari::from(10_000) - amount - fee
+    );
+
+    // Only the intended recipient can rewind the payment
+    let found = runtime
+        .block_on(eve_oms.scan_for_one_sided_outputs(tx.body.outputs().clone()))
+        .unwrap();
+    assert!(found.is_empty());
+
+    let found = runtime
+        .block_on(bob_oms.scan_for_one_sided_outputs(tx.body.outputs().clone()))
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].value, amount);
+
+    runtime.block_on(bob_oms.add_output(found[0].clone())).unwrap();
+    let bob_balance = runtime.block_on(bob_oms.get_balance()).unwrap();
+    assert_eq!(bob_balance.available_balance, amount);
+
+    let stp = runtime
+        .block_on(bob_oms.prepare_transaction_to_send(
+            MicroTari::from(1_000),
+            MicroTari::from(20),
+            None,
+            "".to_string(),
+        ))
+        .unwrap();
+    let tx = runtime.block_on(complete_transaction(stp, bob_oms.clone()));
+    assert_eq!(tx.body.inputs().len(), 1);
+}
+
+#[test]
+fn test_one_sided_payment_address_does_not_reveal_other_keys() {
+    let mut runtime = Runtime::new().unwrap();
+    let (mut oms, _shutdown, _, _, _, _, _) =
+        setup_output_manager_service(&mut runtime, OutputManagerMemoryDatabase::new(), true);
+
+    let address = runtime.block_on(oms.get_one_sided_payment_address()).unwrap();
+    let rewind_keys = runtime.block_on(oms.get_rewind_public_keys()).unwrap();
+    assert_ne!(address.public_key, rewind_keys.rewind_public_key);
+    assert_ne!(address.public_key, rewind_keys.rewind_blinding_public_key);
+
+    let seed_words = runtime.block_on(oms.get_seed_words()).unwrap();
+    let master_key = PrivateKey::from_mnemonic(&seed_words).unwrap();
+    // The coinbase output of the genesis block is spent with the key at index 0
+    let key_manager = KeyManager::<PrivateKey, KeyDigest>::from(master_key, "coinbase".to_string(), 0);
+    let coinbase_key = key_manager.derive_key(0).unwrap().k;
+    assert_ne!(address.public_key, PublicKey::from_secret_key(&coinbase_key));
+}
+
+/// An external signer with predictable keys that records every key it was asked to use
+#[derive(Default)]
+struct MockExternalSigner {
+    requests: Mutex<Vec<(String, u64)>>,
+    signed: Mutex<Vec<(String, u64)>>,
+}
+
+impl MockExternalSigner {
+    fn key(branch: &str, index: u64) -> PrivateKey {
+        let hash = Blake256::new()
+            .chain(branch.as_bytes())
+            .chain(index.to_le_bytes())
+            .result();
+        PrivateKey::from_bytes(hash.as_slice()).unwrap()
+    }
+}
+
+impl KeyManagerBackend for MockExternalSigner {
+    fn public_key(&self, branch: &str, index: u64) -> Result<PublicKey, OutputManagerError> {
+        self.requests.lock().unwrap().push((branch.to_string(), index));
+        Ok(PublicKey::from_secret_key(&Self::key(branch, index)))
+    }
+
+    fn diffie_hellman(
+        &self,
+        branch: &str,
+        index: u64,
+        public_key: &PublicKey,
+    ) -> Result<PublicKey, OutputManagerError>
+    {
+        self.requests.lock().unwrap().push((branch.to_string(), index));
+        Ok(PublicKey::shared_secret(&Self::key(branch, index), public_key))
+    }
+
+    fn sign(
+        &self,
+        branch: &str,
+        index: u64,
+        nonce: &PrivateKey,
+        challenge: &[u8],
+    ) -> Result<Signature, OutputManagerError>
+    {
+        self.signed.lock().unwrap().push((branch.to_string(), index));
+        Signature::sign(self.spending_key(branch, index)?, nonce.clone(), challenge)
+            .map_err(|e| OutputManagerError::KeyManagerBackendError(e.to_string()))
+    }
+
+    fn seed_words(&self) -> Result<Vec<String>, OutputManagerError> {
+        Err(OutputManagerError::KeyManagerBackendError(
+            "The master key never leaves the device".to_string(),
+        ))
+    }
+}
+
+#[test]
+fn test_key_manager_backend_provides_spending_keys() {
+    let expected_keys = MockExternalSigner::default();
+    let mut runtime = Runtime::new().unwrap();
+
+    let signer = Arc::new(MockExternalSigner::default());
+    let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service_with_extensions(
+        &mut runtime,
+        OutputManagerMemoryDatabase::new(),
+        true,
+        None,
+        Some(signer.clone()),
+        None,
+    );
+
+    let rewind_keys = runtime.block_on(oms.get_rewind_public_keys()).unwrap();
+    assert_eq!(
+        rewind_keys.rewind_public_key,
+        PublicKey::from_secret_key(&expected_keys.spending_key("recovery_viewonly", 0).unwrap())
+    );
+    let one_sided_address = runtime.block_on(oms.get_one_sided_payment_address()).unwrap();
+    assert_eq!(
+        one_sided_address.public_key,
+        PublicKey::from_secret_key(&MockExternalSigner::key("one_sided_payment", 0))
+    );
+    assert!(matches!(
+        runtime.block_on(oms.get_seed_words()),
+        Err(OutputManagerError::KeyManagerBackendError(_))
+    ));
+
+    runtime
+        .block_on(oms.add_output(UnblindedOutput::new(
+            MicroTari::from(10_000),
+            PrivateKey::random(&mut OsRng),
+            None,
+        )))
+        .unwrap();
+    let stp = runtime
+        .block_on(oms.prepare_transaction_to_send(MicroTari::from(1_000), MicroTari::from(20), None, "".to_string()))
+        .unwrap();
+    let tx_id = stp.get_tx_id().unwrap();
+    let pending_txs = runtime.block_on(oms.get_pending_transactions()).unwrap();
+    assert_eq!(
+        pending_txs.get(&tx_id).unwrap().outputs_to_be_received[0]
+            .unblinded_output
+            .spending_key,
+        expected_keys.spending_key("", 1).unwrap()
+    );
+
+    runtime
+        .block_on(oms.get_coinbase_transaction(2, MicroTari::from(5_000), MicroTari::from(0), 7))
+        .unwrap();
+    let pending_txs = runtime.block_on(oms.get_pending_transactions()).unwrap();
+    assert_eq!(
+        pending_txs.get(&2).unwrap().outputs_to_be_received[0]
+            .unblinded_output
+            .spending_key,
+        expected_keys.spending_key("coinbase", 7).unwrap()
+    );
+
+    let requests = signer.requests.lock().unwrap();
+    assert!(requests.contains(&("".to_string(), 1)));
+    assert!(requests.contains(&("coinbase".to_string(), 7)));
+}
+
+#[test]
+fn test_key_manager_backend_signs_received_outputs() {
+    let mut runtime = Runtime::new().unwrap();
+    let signer = Arc::new(MockExternalSigner::default());
+    let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service_with_extensions(
+        &mut runtime,
+        OutputManagerMemoryDatabase::new(),
+        true,
+        None,
+        Some(signer.clone()),
+        None,
+    );
+
+    let (_, sender_message) = generate_sender_transaction_message(MicroTari::from(10_000));
+    let sender_data = sender_message.single().unwrap().clone();
+    let rtp = runtime.block_on(oms.get_recipient_transaction(sender_message)).unwrap();
+    let signed_data = rtp.get_signed_data().unwrap();
+
+    assert_eq!(*signer.signed.lock().unwrap(), vec![("".to_string(), 1)]);
+    assert_eq!(
+        signed_data.public_spend_key,
+        PublicKey::from_secret_key(&signer.spending_key("", 1).unwrap())
+    );
+    let challenge = build_challenge(
+        &(&sender_data.public_nonce + signed_data.partial_signature.get_public_nonce()),
+        &sender_data.metadata,
+    );
+    assert!(signed_data
+        .partial_signature
+        .verify_challenge(&signed_data.public_spend_key, &challenge));
+}
@@ -164,7 +164,7 @@ pub fn test_db_backend<T: OutputManagerBackend + 'static>(backend: T) {
     });
 
     runtime
-        .block_on(db.confirm_pending_transaction_outputs(pending_txs[0].tx_id))
+        .block_on(db.confirm_pending_transaction_outputs(pending_txs[0].tx_id, None))
         .unwrap();
 
     available_balance += pending_txs[0]
@@ -522,7 +522,9 @@ pub async fn test_short_term_encumberance<T: OutputManagerBackend + 'static>(bac
     .await
     .unwrap();
 
-    db.confirm_pending_transaction_outputs(pending_tx.tx_id).await.unwrap();
+    db.confirm_pending_transaction_outputs(pending_tx.tx_id, None)
+        .await
+        .unwrap();
 
     let balance = db.get_balance(None).await.unwrap();
     assert_eq!(
@@ -594,7 +596,7 @@ pub async fn test_no_duplicate_outputs<T: OutputManagerBackend + 'static>(backen
         Ok(()) => {
             // memory db storage allows the pending tx but trying to confirm the transaction should be an error
             let err = db
-                .confirm_pending_transaction_outputs(pending_tx.tx_id)
+                .confirm_pending_transaction_outputs(pending_tx.tx_id, None)
                 .await
                 .unwrap_err();
             assert!(matches!(err, OutputManagerStorageError::DuplicateOutput));
@@ -617,3 +619,120 @@ pub async fn test_no_duplicate_outputs<T: OutputManagerBackend + 'static>(backen
     let outputs = db.get_unspent_outputs().await.unwrap();
     assert_eq!(outputs.len(), 1);
 }
+
+pub async fn test_balance_cache_consistency<T: OutputManagerBackend + 'static>(backend: T) {
+    let factories = CryptoFactories::default();
+    let db = OutputManagerDatabase::new(backend);
+
+    for i in 1..6 {
+        let (_ti, uo) = make_input(&mut OsRng, MicroTari::from(1000 * i), &factories.commitment);
+        let uo = DbUnblindedOutput::from_unblinded_output(uo, &factories).unwrap();
+        db.add_unspent_output(uo).await.unwrap();
+    }
+    // Initialize the cache so that the operations below update it incrementally
+    let balance = db.get_balance(None).await.unwrap();
+    assert_eq!(balance.available_balance, MicroTari::from(15_000));
+
+    // Receive a payment
+    let receive_tx_id = OsRng.next_u64();
+    db.accept_incoming_pending_transaction(
+        receive_tx_id,
+        MicroTari::from(700),
+        PrivateKey::random(&mut OsRng),
+        OutputFeatures::default(),
+        &factories,
+        None,
+    )
+    .await
+    .unwrap();
+    assert!(db.check_balance_cache().await.unwrap());
+    db.confirm_pending_transaction_outputs(receive_tx_id, Some(5))
+        .await
+        .unwrap();
+    assert!(db.check_balance_cache().await.unwrap());
+
+    // Send a payment with change
+    let unspent_outputs = db.fetch_sorted_unspent_outputs().await.unwrap();
+    let (_ti, change) = make_input(&mut OsRng, MicroTari::from(200), &factories.commitment);
+    let change = DbUnblindedOutput::from_unblinded_output(change, &factories).unwrap();
+    let send_tx_id = OsRng.next_u64();
+    db.encumber_outputs(send_tx_id, unspent_outputs[..2].to_vec(), vec![change])
+        .await
+        .unwrap();
+    assert!(db.check_balance_cache().await.unwrap());
+    db.confirm_encumbered_outputs(send_tx_id).await.unwrap();
+    db.confirm_pending_transaction_outputs(send_tx_id, None).await.unwrap();
+    assert!(db.check_balance_cache().await.unwrap());
+
+    // Cancel a send and abandon a send negotiation
+    let unspent_outputs = db.fetch_sorted_unspent_outputs().await.unwrap();
+    let cancelled_tx_id = OsRng.next_u64();
+    db.encumber_outputs(cancelled_tx_id, unspent_outputs[..1].to_vec(), vec![])
+        .await
+        .unwrap();
+    db.confirm_encumbered_outputs(cancelled_tx_id).await.unwrap();
+    db.cancel_pending_transaction_outputs(cancelled_tx_id).await.unwrap();
+    assert!(db.check_balance_cache().await.unwrap());
+    db.encumber_outputs(OsRng.next_u64(), unspent_outputs[1..2].to_vec(), vec![])
+        .await
+        .unwrap();
+    db.clear_short_term_encumberances().await.unwrap();
+    assert!(db.check_balance_cache().await.unwrap());
+
+    // A coinbase is reorged out of the chain
+    let coinbase_tx_id = OsRng.next_u64();
+    db.accept_incoming_pending_transaction(
+        coinbase_tx_id,
+        MicroTari::from(10_000),
+        PrivateKey::random(&mut OsRng),
+        OutputFeatures::create_coinbase(10),
+        &factories,
+        Some(10),
+    )
+    .await
+    .unwrap();
+    db.confirm_encumbered_outputs(coinbase_tx_id).await.unwrap();
+    assert!(db.check_balance_cache().await.unwrap());
+    db.cancel_pending_transaction_at_block_height(10).await.unwrap();
+    assert!(db.check_balance_cache().await.unwrap());
+
+    // The spending transaction is reorged out and an output disappears and reappears on-chain
+    let spent_outputs = db.fetch_spent_outputs().await.unwrap();
+    db.update_spent_output_to_unspent(spent_outputs[0].commitment.clone())
+        .await
+        .unwrap();
+    assert!(db.check_balance_cache().await.unwrap());
+    let unspent_outputs = db.fetch_sorted_unspent_outputs().await.unwrap();
+    db.invalidate_output(unspent_outputs[0].clone()).await.unwrap();
+    assert!(db.check_balance_cache().await.unwrap());
+    db.revalidate_output(unspent_outputs[0].commitment.clone())
+        .await
+        .unwrap();
+    assert!(db.check_balance_cache().await.unwrap());
+
+    let unspent_outputs = db.fetch_sorted_unspent_outputs().await.unwrap();
+    let balance = db.get_balance(None).await.unwrap();
+    assert_eq!(
+        balance.available_balance,
+        unspent_outputs
+            .iter()
+            .fold(MicroTari::from(0), |acc, x| acc + x.unblinded_output.value)
+    );
+    assert_eq!(balance.pending_incoming_balance, MicroTari::from(0));
+    assert_eq!(balance.pending_outgoing_balance, MicroTari::from(0));
+}
+
+#[tokio_macros::test]
+pub async fn test_balance_cache_consistency_memory_db() {
+    test_balance_cache_consistency(OutputManagerMemoryDatabase::new()).await;
+}
+
+#[tokio_macros::test]
+pub async fn test_balance_cache_consistency_sqlite_db() {
+    let db_name = format!("{}.sqlite3", random_string(8).as_str());
+    let temp_dir = tempdir().unwrap();
+    let db_folder = temp_dir.path().to_str().unwrap().to_string();
+    let connection = run_migration_and_create_sqlite_connection(&format!("{}/{}", db_folder, db_name)).unwrap();
+
+    test_balance_cache_consistency(OutputManagerSqliteDatabase::new(connection, None)).await;
+}
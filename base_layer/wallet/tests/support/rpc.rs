@@ -33,7 +33,9 @@ use tari_core::{
     },
     proto::{
         base_node::{
+            BlockRewardResponse,
             ChainMetadata,
+            FeeHistogramResponse,
             FetchMatchingUtxos,
             FetchUtxosResponse,
             Signatures as SignaturesProto,
@@ -83,6 +85,7 @@ pub struct BaseNodeWalletRpcMockState {
     submit_transaction_response: Arc<Mutex<TxSubmissionResponse>>,
     transaction_query_response: Arc<Mutex<TxQueryResponse>>,
     tip_info_response: Arc<Mutex<TipInfoResponse>>,
+    fee_histogram_response: Arc<Mutex<FeeHistogramResponse>>,
     fetch_utxos_calls: Arc<Mutex<Vec<Vec<Vec<u8>>>>>,
     response_delay: Arc<Mutex<Option<Duration>>>,
     rpc_status_error: Arc<Mutex<Option<RpcStatus>>>,
@@ -119,6 +122,10 @@ impl BaseNodeWalletRpcMockState {
                 }),
                 is_synced: true,
             })),
+            fee_histogram_response: Arc::new(Mutex::new(FeeHistogramResponse {
+                buckets: Vec::new(),
+                is_synced: true,
+            })),
             fetch_utxos_calls: Arc::new(Mutex::new(Vec::new())),
             response_delay: Arc::new(Mutex::new(None)),
             rpc_status_error: Arc::new(Mutex::new(None)),
@@ -132,6 +139,11 @@ impl BaseNodeWalletRpcMockState {
         *lock = response;
     }
 
+    pub fn set_fee_histogram_response(&self, response: FeeHistogramResponse) {
+        let mut lock = acquire_lock!(self.fee_histogram_response);
+        *lock = response;
+    }
+
     pub fn set_submit_transaction_response(&self, response: TxSubmissionResponse) {
         let mut lock = acquire_lock!(self.submit_transaction_response);
         *lock = response;
@@ -462,6 +474,47 @@ impl BaseNodeWalletService for BaseNodeWalletRpcMockService {
 
         Ok(Response::new(tip_info_response_lock.clone()))
     }
+
+    async fn get_mempool_fee_histogram(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<FeeHistogramResponse>, RpcStatus>
+    {
+        let delay_lock = (*acquire_lock!(self.state.response_delay));
+        if let Some(delay) = delay_lock {
+            delay_for(delay).await;
+        }
+
+        log::info!("Get mempool fee histogram call received");
+
+        let status_lock = acquire_lock!(self.state.rpc_status_error);
+        if let Some(status) = (*status_lock).clone() {
+            return Err(status);
+        }
+
+        let fee_histogram_response_lock = acquire_lock!(self.state.fee_histogram_response);
+
+        Ok(Response::new(fee_histogram_response_lock.clone()))
+    }
+
+    async fn get_block_reward(&self, request: Request<u64>) -> Result<Response<BlockRewardResponse>, RpcStatus> {
+        let delay_lock = (*acquire_lock!(self.state.response_delay));
+        if let Some(delay) = delay_lock {
+            delay_for(delay).await;
+        }
+
+        log::info!("Get block reward call received");
+
+        let status_lock = acquire_lock!(self.state.rpc_status_error);
+        if let Some(status) = (*status_lock).clone() {
+            return Err(status);
+        }
+
+        Ok(Response::new(BlockRewardResponse {
+            height: request.into_message(),
+            ..Default::default()
+        }))
+    }
 }
 
 #[cfg(test)]
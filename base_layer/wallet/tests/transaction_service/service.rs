@@ -585,7 +585,7 @@ fn manage_single_transaction() {
         .expect("Could not find tx");
 
     runtime
-        .block_on(bob_oms.confirm_transaction(tx_id, vec![], bob_completed_tx.transaction.body.outputs().clone()))
+        .block_on(bob_oms.confirm_transaction(tx_id, vec![], bob_completed_tx.transaction.body.outputs().clone(), None))
         .unwrap();
 
     assert_eq!(
@@ -662,7 +662,7 @@ fn single_transaction_to_self() {
             .expect("Could not find tx");
 
         alice_oms
-            .confirm_transaction(tx_id, vec![utxo], completed_tx.transaction.body.outputs().clone())
+            .confirm_transaction(tx_id, vec![utxo], completed_tx.transaction.body.outputs().clone(), None)
             .await
             .unwrap();
         let fees = completed_tx.fee;
@@ -1461,6 +1461,7 @@ fn test_power_mode_updates() {
         destination_public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
         amount: 5000 * uT,
         fee: MicroTari::from(100),
+        weight: tx.calculate_weight(),
         transaction: tx.clone(),
         status: TransactionStatus::Completed,
         message: "Yo!".to_string(),
@@ -1473,6 +1474,7 @@ fn test_power_mode_updates() {
         valid: true,
         confirmations: None,
         mined_height: None,
+        one_sided_payment_public_key: None,
     };
 
     let completed_tx2 = CompletedTransaction {
@@ -1481,6 +1483,7 @@ fn test_power_mode_updates() {
         destination_public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
         amount: 6000 * uT,
         fee: MicroTari::from(200),
+        weight: tx.calculate_weight(),
         transaction: tx,
         status: TransactionStatus::Completed,
         message: "Yo!".to_string(),
@@ -1493,6 +1496,7 @@ fn test_power_mode_updates() {
         valid: true,
         confirmations: None,
         mined_height: None,
+        one_sided_payment_public_key: None,
     };
 
     backend
@@ -4331,6 +4335,7 @@ fn broadcast_all_completed_transactions_on_startup() {
         destination_public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
         amount: 5000 * uT,
         fee: MicroTari::from(100),
+        weight: tx.calculate_weight(),
         transaction: tx,
         status: TransactionStatus::Completed,
         message: "Yo!".to_string(),
@@ -4343,6 +4348,7 @@ fn broadcast_all_completed_transactions_on_startup() {
         valid: true,
         confirmations: None,
         mined_height: None,
+        one_sided_payment_public_key: None,
     };
 
     let completed_tx2 = CompletedTransaction {
@@ -4662,6 +4668,7 @@ fn only_start_one_tx_broadcast_protocol_at_a_time() {
         destination_public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
         amount: 5000 * uT,
         fee: MicroTari::from(100),
+        weight: tx.calculate_weight(),
         transaction: tx,
         status: TransactionStatus::Completed,
         message: "Yo!".to_string(),
@@ -4674,6 +4681,7 @@ fn only_start_one_tx_broadcast_protocol_at_a_time() {
         valid: true,
         confirmations: None,
         mined_height: None,
+        one_sided_payment_public_key: None,
     };
 
     backend
@@ -4723,6 +4731,7 @@ fn dont_broadcast_invalid_transactions() {
         destination_public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
         amount: 5000 * uT,
         fee: MicroTari::from(100),
+        weight: tx.calculate_weight(),
         transaction: tx,
         status: TransactionStatus::Completed,
         message: "Yo!".to_string(),
@@ -4735,6 +4744,7 @@ fn dont_broadcast_invalid_transactions() {
         valid: false,
         confirmations: None,
         mined_height: None,
+        one_sided_payment_public_key: None,
     };
 
     backend
@@ -44,6 +44,7 @@ use tari_wallet::{
             CompletedTransaction,
             InboundTransaction,
             OutboundTransaction,
+            PrunedTransactionSummary,
             TransactionDirection,
             TransactionStatus,
             WalletTransaction,
@@ -227,6 +228,7 @@ pub fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
             destination_public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
             amount: outbound_txs[i].amount,
             fee: MicroTari::from(200),
+            weight: tx.calculate_weight(),
             transaction: tx.clone(),
             status: match i {
                 0 => TransactionStatus::Completed,
@@ -243,6 +245,7 @@ pub fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
             valid: true,
             confirmations: None,
             mined_height: None,
+            one_sided_payment_public_key: None,
         });
         runtime
             .block_on(db.complete_outbound_transaction(outbound_txs[i].tx_id, completed_txs[i].clone()))
@@ -552,3 +555,135 @@ pub fn test_transaction_service_sqlite_db_encrypted() {
 
     test_db_backend(TransactionServiceSqliteDatabase::new(connection, Some(cipher)));
 }
+
+/// The net effect of the transaction history on the wallet balance, including the transactions that were pruned
+fn transaction_history_balance<T: TransactionBackend + 'static>(
+    runtime: &mut Runtime,
+    db: &TransactionDatabase<T>,
+) -> i64
+{
+    let summary = runtime.block_on(db.get_pruned_transaction_summary()).unwrap();
+    let completed_txs = runtime.block_on(db.get_completed_transactions()).unwrap();
+    completed_txs.values().fold(
+        summary.total_received.0 as i64 - summary.total_sent.0 as i64 - summary.total_fees.0 as i64,
+        |balance, tx| match tx.direction {
+            TransactionDirection::Inbound => balance + tx.amount.0 as i64,
+            TransactionDirection::Outbound => balance - tx.amount.0 as i64 - tx.fee.0 as i64,
+            TransactionDirection::Unknown => balance,
+        },
+    )
+}
+
+#[test]
+pub fn test_prune_completed_transactions() {
+    let db_name = format!("{}.sqlite3", random_string(8).as_str());
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(&db_path).unwrap();
+
+    let mut runtime = Runtime::new().unwrap();
+    let db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(connection, None));
+    let tx = Transaction::new(vec![], vec![], vec![], PrivateKey::random(&mut OsRng));
+
+    let inbound_tx = CompletedTransaction {
+        tx_id: 0,
+        source_public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        destination_public_key: PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        amount: MicroTari::from(0),
+        fee: MicroTari::from(0),
+        weight: tx.calculate_weight(),
+        transaction: tx,
+        status: TransactionStatus::MinedConfirmed,
+        message: "Yo!".to_string(),
+        timestamp: Utc::now().naive_utc(),
+        cancelled: false,
+        direction: TransactionDirection::Inbound,
+        coinbase_block_height: None,
+        send_count: 0,
+        last_send_timestamp: None,
+        valid: true,
+        confirmations: Some(100),
+        mined_height: Some(1),
+        one_sided_payment_public_key: None,
+    };
+    let outbound_tx = CompletedTransaction {
+        direction: TransactionDirection::Outbound,
+        ..inbound_tx.clone()
+    };
+    let completed_txs = vec![
+        CompletedTransaction {
+            tx_id: 1,
+            amount: MicroTari::from(10_000),
+            ..inbound_tx.clone()
+        },
+        CompletedTransaction {
+            tx_id: 2,
+            amount: MicroTari::from(3_000),
+            fee: MicroTari::from(200),
+            confirmations: Some(50),
+            ..outbound_tx.clone()
+        },
+        CompletedTransaction {
+            tx_id: 3,
+            amount: MicroTari::from(1_000),
+            fee: MicroTari::from(100),
+            confirmations: Some(5),
+            ..outbound_tx
+        },
+        CompletedTransaction {
+            tx_id: 4,
+            amount: MicroTari::from(2_000),
+            status: TransactionStatus::MinedUnconfirmed,
+            confirmations: Some(1),
+            ..inbound_tx.clone()
+        },
+        CompletedTransaction {
+            tx_id: 5,
+            amount: MicroTari::from(7_000),
+            valid: false,
+            ..inbound_tx
+        },
+    ];
+    for completed_tx in completed_txs {
+        runtime
+            .block_on(db.insert_completed_transaction(completed_tx.tx_id, completed_tx))
+            .unwrap();
+    }
+    let balance = transaction_history_balance(&mut runtime, &db);
+    assert_eq!(balance, 10_000 - 3_200 - 1_100 + 2_000 + 7_000);
+    assert_eq!(
+        runtime.block_on(db.get_pruned_transaction_summary()).unwrap(),
+        PrunedTransactionSummary::default()
+    );
+
+    // Only the valid, mined and confirmed transactions that are deep enough are pruned
+    assert_eq!(runtime.block_on(db.prune_completed_transactions(10)).unwrap(), 2);
+    let completed_txs = runtime.block_on(db.get_completed_transactions()).unwrap();
+    assert_eq!(completed_txs.len(), 3);
+    assert!(!completed_txs.contains_key(&1));
+    assert!(!completed_txs.contains_key(&2));
+    assert_eq!(
+        runtime.block_on(db.get_pruned_transaction_summary()).unwrap(),
+        PrunedTransactionSummary {
+            num_transactions: 2,
+            total_received: MicroTari::from(10_000),
+            total_sent: MicroTari::from(3_000),
+            total_fees: MicroTari::from(200),
+        }
+    );
+    assert_eq!(transaction_history_balance(&mut runtime, &db), balance);
+
+    assert_eq!(runtime.block_on(db.prune_completed_transactions(10)).unwrap(), 0);
+    assert_eq!(transaction_history_balance(&mut runtime, &db), balance);
+
+    // Pruning again adds to the existing summary
+    runtime.block_on(db.set_transaction_confirmations(3, 20)).unwrap();
+    assert_eq!(runtime.block_on(db.prune_completed_transactions(10)).unwrap(), 1);
+    let summary = runtime.block_on(db.get_pruned_transaction_summary()).unwrap();
+    assert_eq!(summary.num_transactions, 3);
+    assert_eq!(summary.total_sent, MicroTari::from(4_000));
+    assert_eq!(summary.total_fees, MicroTari::from(300));
+    assert_eq!(runtime.block_on(db.get_completed_transactions()).unwrap().len(), 2);
+    assert_eq!(transaction_history_balance(&mut runtime, &db), balance);
+}
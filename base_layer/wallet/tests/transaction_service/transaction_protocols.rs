@@ -27,7 +27,10 @@ use crate::support::{
 use chrono::Utc;
 use futures::{FutureExt, StreamExt};
 use rand::rngs::OsRng;
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tari_comms::{
     peer_manager::PeerFeatures,
     protocol::rpc::{mock::MockRpcServer, NamedProtocolService, RpcStatus},
@@ -66,7 +69,7 @@ use tari_wallet::{
         error::TransactionServiceError,
         handle::{TransactionEvent, TransactionEventSender},
         protocols::{
-            transaction_broadcast_protocol::TransactionBroadcastProtocol,
+            transaction_broadcast_protocol::{BroadcastPollInterval, TransactionBroadcastProtocol},
             transaction_validation_protocol::TransactionValidationProtocol,
         },
         service::TransactionServiceResources,
@@ -412,6 +415,48 @@ async fn tx_broadcast_protocol_submit_success() {
     assert!(broadcast, "Should have received a broadcast event");
 }
 
+/// Test that a new transaction is held for a random period within the configured relay delay before it is submitted
+#[tokio_macros::test]
+#[allow(clippy::identity_op)]
+async fn tx_broadcast_protocol_relay_delay() {
+    let (
+        mut resources,
+        _connectivity_mock_state,
+        _outbound_mock_state,
+        _mock_rpc_server,
+        server_node_identity,
+        rpc_service_state,
+        timeout_update_publisher,
+        _shutdown,
+        _temp_dir,
+    ) = setup(TxProtocolTestConfig::WithConnection).await;
+    let (base_node_update_publisher, _) = broadcast::channel(20);
+    resources.config.relay_delay_min = Duration::from_millis(500);
+    resources.config.relay_delay_max = Duration::from_millis(1500);
+
+    add_transaction_to_database(1, 1 * T, true, None, resources.db.clone()).await;
+
+    let protocol = TransactionBroadcastProtocol::new(
+        1,
+        resources.clone(),
+        Duration::from_secs(1),
+        server_node_identity.public_key().clone(),
+        timeout_update_publisher.subscribe(),
+        base_node_update_publisher.subscribe(),
+    );
+    let started = Instant::now();
+    let _join_handle = task::spawn(protocol.execute());
+
+    let _ = rpc_service_state
+        .wait_pop_submit_transaction_calls(1, Duration::from_secs(5))
+        .await
+        .unwrap();
+    let elapsed = started.elapsed();
+    assert!(elapsed >= resources.config.relay_delay_min);
+    // Allow some slack for connecting to the base node after the delay
+    assert!(elapsed < resources.config.relay_delay_max + Duration::from_secs(1));
+}
+
 /// Test submitting a transaction that is immediately rejected
 #[tokio_macros::test]
 #[allow(clippy::identity_op)]
@@ -1683,3 +1728,43 @@ async fn tx_validation_protocol_base_node_not_synced() {
     assert!(delayed >= 1, "Should have been delayed");
     assert_eq!(failures, 0, "Should not have failed when BN is not synced");
 }
+
+#[test]
+fn broadcast_poll_interval_adapts_to_transaction_state() {
+    let min = Duration::from_secs(5);
+    let max = Duration::from_secs(60);
+
+    let mut interval = BroadcastPollInterval::new(min, max, true);
+    assert_eq!(interval.current(), max);
+
+    // Poll quickly straight after broadcast
+    interval.on_broadcast();
+    assert_eq!(interval.current(), min);
+
+    // Back off while the transaction sits in the mempool
+    interval.on_pending();
+    assert_eq!(interval.current(), Duration::from_secs(10));
+    interval.on_pending();
+    assert_eq!(interval.current(), Duration::from_secs(20));
+    for _ in 0..5 {
+        interval.on_pending();
+    }
+    assert_eq!(interval.current(), max);
+
+    // Once mined, only confirmations are awaited so poll at the slowest rate
+    interval.on_broadcast();
+    assert_eq!(interval.current(), min);
+    interval.on_mined();
+    assert_eq!(interval.current(), max);
+
+    // A lower maximum (e.g. from a power mode change) caps the current interval
+    interval.set_max(Duration::from_secs(30));
+    assert_eq!(interval.current(), Duration::from_secs(30));
+
+    // Without adaptive monitoring the interval never changes
+    let mut interval = BroadcastPollInterval::new(min, max, false);
+    interval.on_broadcast();
+    assert_eq!(interval.current(), max);
+    interval.on_pending();
+    assert_eq!(interval.current(), max);
+}
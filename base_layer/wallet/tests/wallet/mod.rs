@@ -93,6 +93,11 @@ async fn create_wallet(
     passphrase: Option<String>,
 ) -> WalletSqlite
 {
+    let config = create_wallet_config(node_identity, data_path, factories);
+    create_wallet_with_config(config, database_name, shutdown_signal, passphrase).await
+}
+
+fn create_wallet_config(node_identity: NodeIdentity, data_path: &Path, factories: CryptoFactories) -> WalletConfig {
     let comms_config = CommsConfig {
         node_identity: Arc::new(node_identity.clone()),
         transport_type: TransportType::Memory {
@@ -102,6 +107,7 @@ async fn create_wallet(
         peer_database_name: random_string(8),
         max_concurrent_inbound_tasks: 100,
         outbound_buffer_size: 100,
+        max_queued_messages_per_peer: 100,
         dht: DhtConfig {
             discovery_request_timeout: Duration::from_secs(1),
             auto_join: true,
@@ -112,27 +118,19 @@ async fn create_wallet(
         listener_liveness_allowlist_cidrs: Vec::new(),
         listener_liveness_max_sessions: 0,
         user_agent: "tari/test-wallet".to_string(),
-        dns_seeds_name_server: DEFAULT_DNS_SEED_RESOLVER.parse().unwrap(),
+        dns_seeds_name_servers: vec![DEFAULT_DNS_SEED_RESOLVER.parse().unwrap()],
         peer_seeds: Default::default(),
         dns_seeds: Default::default(),
         dns_seeds_use_dnssec: false,
+        dns_seeds_doh_name: None,
     };
 
-    let sql_database_path = comms_config
-        .datastore_path
-        .clone()
-        .join(database_name)
-        .with_extension("sqlite3");
-
-    let (wallet_backend, transaction_backend, output_manager_backend, contacts_backend) =
-        initialize_sqlite_database_backends(sql_database_path, passphrase).unwrap();
-
     let transaction_service_config = TransactionServiceConfig {
         resend_response_cooldown: Duration::from_secs(1),
         ..Default::default()
     };
 
-    let config = WalletConfig::new(
+    WalletConfig::new(
         comms_config,
         factories,
         Some(transaction_service_config),
@@ -141,7 +139,26 @@ async fn create_wallet(
         None,
         None,
         None,
-    );
+    )
+}
+
+async fn create_wallet_with_config(
+    config: WalletConfig,
+    database_name: &str,
+    shutdown_signal: ShutdownSignal,
+    passphrase: Option<String>,
+) -> WalletSqlite
+{
+    let sql_database_path = config
+        .comms_config
+        .datastore_path
+        .clone()
+        .join(database_name)
+        .with_extension("sqlite3");
+
+    let (wallet_backend, transaction_backend, output_manager_backend, contacts_backend) =
+        initialize_sqlite_database_backends(sql_database_path, passphrase).unwrap();
+
     let meta_data = ChainMetadata::new(std::u64::MAX, Vec::new(), 0, 0, 0);
 
     let _ = wallet_backend.write(WriteOperation::Insert(DbKeyValuePair::BaseNodeChainMetadata(meta_data)));
@@ -560,6 +577,102 @@ fn test_store_and_forward_send_tx() {
     carol_runtime.block_on(carol_wallet.wait_until_shutdown());
 }
 
+#[tokio_macros::test]
+async fn test_rotate_node_identity() {
+    let mut shutdown_a = Shutdown::new();
+    let mut shutdown_b = Shutdown::new();
+    let alice_db_tempdir = tempdir().unwrap();
+    let bob_db_tempdir = tempdir().unwrap();
+
+    let factories = CryptoFactories::default();
+    let alice_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap();
+    let bob_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE).unwrap();
+
+    let mut alice_config = create_wallet_config(alice_identity.clone(), alice_db_tempdir.path(), factories.clone());
+    let mut alice_wallet =
+        create_wallet_with_config(alice_config.clone(), "alice_db", shutdown_a.to_signal(), None).await;
+
+    let bob_wallet = create_wallet(
+        bob_identity.clone(),
+        &bob_db_tempdir.path(),
+        "bob_db",
+        factories.clone(),
+        shutdown_b.to_signal(),
+        None,
+    )
+    .await;
+
+    let (_utxo, uo1) = make_input(&mut OsRng, MicroTari(2500), &factories.commitment);
+    alice_wallet.output_manager_service.add_output(uo1).await.unwrap();
+    let balance_before = alice_wallet.output_manager_service.get_balance().await.unwrap();
+
+    shutdown_a.trigger().unwrap();
+    alice_wallet.wait_until_shutdown().await;
+
+    let new_address = get_next_memory_address();
+    let new_identity = alice_config.rotate_node_identity(new_address.clone()).unwrap();
+    alice_config.comms_config.transport_type = TransportType::Memory {
+        listener_address: new_address,
+    };
+    // The peer database of the previous instance may still be open
+    alice_config.comms_config.peer_database_name = random_string(8);
+    assert_ne!(new_identity.node_id(), alice_identity.node_id());
+    assert_ne!(new_identity.public_key(), alice_identity.public_key());
+    assert_eq!(new_identity.features(), alice_identity.features());
+
+    let mut shutdown_a = Shutdown::new();
+    let mut alice_wallet = create_wallet_with_config(alice_config, "alice_db", shutdown_a.to_signal(), None).await;
+    assert_eq!(alice_wallet.comms.node_identity().node_id(), new_identity.node_id());
+
+    let comms_secret_key = alice_wallet.db.get_comms_secret_key().await.unwrap().unwrap();
+    assert_eq!(&comms_secret_key, new_identity.secret_key());
+
+    let balance_after = alice_wallet.output_manager_service.get_balance().await.unwrap();
+    assert_eq!(balance_after, balance_before);
+
+    alice_wallet
+        .comms
+        .peer_manager()
+        .add_peer(create_peer(
+            bob_identity.public_key().clone(),
+            bob_identity.public_address(),
+        ))
+        .await
+        .unwrap();
+    alice_wallet
+        .comms
+        .connectivity()
+        .dial_peer(bob_identity.node_id().clone())
+        .await
+        .unwrap();
+
+    // Allow Bob's connectivity manager to process the inbound connection
+    delay_for(Duration::from_secs(1)).await;
+
+    // Bob sees the connection from Alice's new node id
+    let conn = bob_wallet
+        .comms
+        .connectivity()
+        .get_connection(new_identity.node_id().clone())
+        .await
+        .unwrap();
+    assert!(conn.is_some());
+    let conn = bob_wallet
+        .comms
+        .connectivity()
+        .get_connection(alice_identity.node_id().clone())
+        .await
+        .unwrap();
+    assert!(conn.is_none());
+
+    shutdown_a.trigger().unwrap();
+    shutdown_b.trigger().unwrap();
+    alice_wallet.wait_until_shutdown().await;
+    bob_wallet.wait_until_shutdown().await;
+}
+
 #[tokio_macros::test]
 async fn test_import_utxo() {
     let shutdown = Shutdown::new();
@@ -588,15 +701,17 @@ async fn test_import_utxo() {
         peer_database_name: random_string(8),
         max_concurrent_inbound_tasks: 100,
         outbound_buffer_size: 100,
+        max_queued_messages_per_peer: 100,
         dht: Default::default(),
         allow_test_addresses: true,
         listener_liveness_allowlist_cidrs: Vec::new(),
         listener_liveness_max_sessions: 0,
         user_agent: "tari/test-wallet".to_string(),
-        dns_seeds_name_server: DEFAULT_DNS_SEED_RESOLVER.parse().unwrap(),
+        dns_seeds_name_servers: vec![DEFAULT_DNS_SEED_RESOLVER.parse().unwrap()],
         peer_seeds: Default::default(),
         dns_seeds: Default::default(),
         dns_seeds_use_dnssec: false,
+        dns_seeds_doh_name: None,
     };
     let config = WalletConfig::new(
         comms_config,
@@ -667,6 +782,7 @@ async fn test_data_generation() {
         peer_database_name: random_string(8),
         max_concurrent_inbound_tasks: 100,
         outbound_buffer_size: 100,
+        max_queued_messages_per_peer: 100,
         dht: DhtConfig {
             discovery_request_timeout: Duration::from_millis(500),
             network: DhtNetwork::Stibbons,
@@ -676,10 +792,11 @@ async fn test_data_generation() {
         listener_liveness_allowlist_cidrs: Vec::new(),
         listener_liveness_max_sessions: 0,
         user_agent: "tari/test-wallet".to_string(),
-        dns_seeds_name_server: DEFAULT_DNS_SEED_RESOLVER.parse().unwrap(),
+        dns_seeds_name_servers: vec![DEFAULT_DNS_SEED_RESOLVER.parse().unwrap()],
         peer_seeds: Default::default(),
         dns_seeds: Default::default(),
         dns_seeds_use_dnssec: false,
+        dns_seeds_doh_name: None,
     };
 
     let config = WalletConfig::new(comms_config, factories, None, None, Network::Stibbons, None, None, None);
@@ -2753,6 +2753,7 @@ pub unsafe extern "C" fn comms_config_create(
                         peer_database_name: database_name_string,
                         max_concurrent_inbound_tasks: 100,
                         outbound_buffer_size: 100,
+                        max_queued_messages_per_peer: 100,
                         dht: DhtConfig {
                             discovery_request_timeout: Duration::from_secs(discovery_timeout_in_secs),
                             database_url: DbConnectionUrl::File(dht_database_path),
@@ -2767,10 +2768,11 @@ pub unsafe extern "C" fn comms_config_create(
                         listener_liveness_allowlist_cidrs: Vec::new(),
                         listener_liveness_max_sessions: 0,
                         user_agent: format!("tari/wallet/{}", env!("CARGO_PKG_VERSION")),
-                        dns_seeds_name_server: "1.1.1.1:53".parse().unwrap(),
+                        dns_seeds_name_servers: vec!["1.1.1.1:53".parse().unwrap()],
                         peer_seeds: Default::default(),
                         dns_seeds: Default::default(),
                         dns_seeds_use_dnssec: true,
+                        dns_seeds_doh_name: None,
                     };
 
                     Box::into_raw(Box::new(config))
@@ -120,6 +120,9 @@ pub struct ConfigBootstrap {
     /// Change the password for the console wallet
     #[structopt(long, alias("update-password"))]
     pub change_password: bool,
+    /// Report the pending wallet database migrations without applying them
+    #[structopt(long, alias("check_migrations"))]
+    pub check_migrations: bool,
     /// Force wallet recovery
     #[structopt(long, alias("recover"))]
     pub recovery: bool,
@@ -163,6 +166,7 @@ impl Default for ConfigBootstrap {
             clean_orphans_db: false,
             password: None,
             change_password: false,
+            check_migrations: false,
             recovery: false,
             seed_words: None,
             seed_words_file_name: None,
@@ -61,6 +61,7 @@ pub struct GlobalConfig {
     pub orphan_db_clean_out_threshold: usize,
     pub pruning_horizon: u64,
     pub pruned_mode_cleanup_interval: u64,
+    pub advertise_archival: bool,
     pub core_threads: Option<usize>,
     pub max_threads: Option<usize>,
     pub base_node_identity_file: PathBuf,
@@ -70,8 +71,9 @@ pub struct GlobalConfig {
     pub grpc_console_wallet_address: SocketAddr,
     pub peer_seeds: Vec<String>,
     pub dns_seeds: Vec<String>,
-    pub dns_seeds_name_server: SocketAddr,
+    pub dns_seeds_name_servers: Vec<SocketAddr>,
     pub dns_seeds_use_dnssec: bool,
+    pub dns_seeds_doh_name: Option<String>,
     pub peer_db_path: PathBuf,
     pub enable_wallet: bool,
     pub num_mining_threads: usize,
@@ -86,17 +88,23 @@ pub struct GlobalConfig {
     pub buffer_size_base_node_wallet: usize,
     pub buffer_rate_limit_base_node: usize,
     pub buffer_rate_limit_base_node_wallet: usize,
+    pub max_queued_messages_per_peer: usize,
     pub fetch_blocks_timeout: Duration,
     pub fetch_utxos_timeout: Duration,
     pub service_request_timeout: Duration,
     pub base_node_query_timeout: Duration,
     pub saf_expiry_duration: Duration,
     pub transaction_broadcast_monitoring_timeout: Duration,
+    pub transaction_broadcast_monitoring_interval: Duration,
+    pub transaction_adaptive_broadcast_monitoring: bool,
     pub transaction_chain_monitoring_timeout: Duration,
     pub transaction_direct_send_timeout: Duration,
     pub transaction_broadcast_send_timeout: Duration,
     pub transaction_routing_mechanism: String,
+    pub transaction_relay_delay_min: Duration,
+    pub transaction_relay_delay_max: Duration,
     pub transaction_num_confirmations_required: u64,
+    pub transaction_retention_depth: Option<u64>,
     pub console_wallet_password: Option<String>,
     pub wallet_command_send_wait_stage: String,
     pub wallet_command_send_wait_timeout: u64,
@@ -111,6 +119,9 @@ pub struct GlobalConfig {
     pub proxy_host_address: SocketAddr,
     pub proxy_submit_to_origin: bool,
     pub force_sync_peers: Vec<String>,
+    pub max_block_download_parallelism: usize,
+    pub assume_valid_block_hash: Option<String>,
+    pub mempool_relay_transactions: bool,
     pub wait_for_initial_sync_at_startup: bool,
     pub max_randomx_vms: usize,
     pub console_wallet_notify_file: Option<PathBuf>,
@@ -218,7 +229,22 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
         },
     };
 
-    let db_config = LMDBConfig::new_from_mb(init_size_mb, grow_size_mb, resize_threshold_mb);
+    let mut db_config = LMDBConfig::new_from_mb(init_size_mb, grow_size_mb, resize_threshold_mb);
+
+    let key = config_string("base_node", &net_str, "db_compaction_interval_secs");
+    match optional(cfg.get_int(&key))? {
+        // Compaction is disabled by default
+        None | Some(0) => {},
+        Some(secs) if secs < 0 => {
+            return Err(ConfigurationError::new(
+                &key,
+                "DB compaction interval must not be negative.",
+            ))
+        },
+        Some(secs) => {
+            db_config = db_config.with_compaction_interval(Duration::from_secs(secs as u64));
+        },
+    }
 
     let key = config_string("base_node", &net_str, "orphan_storage_capacity");
     let orphan_storage_capacity = cfg
@@ -240,6 +266,11 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
         .get_int(&key)
         .map_err(|e| ConfigurationError::new(&key, &e.to_string()))? as u64;
 
+    let key = config_string("base_node", &net_str, "advertise_archival");
+    let advertise_archival = cfg
+        .get_bool(&key)
+        .map_err(|e| ConfigurationError::new(&key, &e.to_string()))?;
+
     // Thread counts
     let key = config_string("base_node", &net_str, "core_threads");
     let core_threads =
@@ -336,19 +367,30 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
     };
 
     let key = config_string("base_node", &net_str, "dns_seeds_name_server");
-    let dns_seeds_name_server = cfg
-        .get_str(&key)
-        .map_err(|e| ConfigurationError::new(&key, &e.to_string()))
-        .and_then(|s| {
-            s.parse::<SocketAddr>()
-                .map_err(|e| ConfigurationError::new(&key, &e.to_string()))
-        })?;
+    // Name servers can be a single address, an array or a comma separated list (e.g. in an ENVVAR)
+    let dns_seeds_name_servers = match cfg.get_array(&key) {
+        Ok(name_servers) => name_servers.into_iter().map(|v| v.into_str().unwrap()).collect(),
+        Err(..) => match cfg.get_str(&key) {
+            Ok(s) => s.split(',').map(|v| v.trim().to_string()).collect::<Vec<_>>(),
+            Err(err) => return Err(ConfigurationError::new(&key, &err.to_string())),
+        },
+    }
+    .into_iter()
+    .map(|s| {
+        s.parse::<SocketAddr>()
+            .map_err(|e| ConfigurationError::new(&key, &e.to_string()))
+    })
+    .collect::<Result<Vec<_>, _>>()?;
 
     let key = config_string("base_node", &net_str, "dns_seeds_use_dnssec");
     let dns_seeds_use_dnssec = cfg
         .get_bool(&key)
         .map_err(|e| ConfigurationError::new(&key, &e.to_string()))?;
 
+    // TLS name of the name server, if DNS seeds are resolved using DNS-over-HTTPS
+    let key = config_string("base_node", &net_str, "dns_seeds_doh_name");
+    let dns_seeds_doh_name = optional(cfg.get_str(&key))?.filter(|name| !name.is_empty());
+
     let key = config_string("base_node", &net_str, "dns_seeds");
     let dns_seeds = optional(cfg.get_array(&key))?
         .unwrap_or_default()
@@ -385,6 +427,17 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
     )?
     .unwrap_or_default();
 
+    let key = config_string("base_node", &net_str, "max_block_download_parallelism");
+    let max_block_download_parallelism = optional(cfg.get_int(&key))?.unwrap_or(4) as usize;
+
+    // Hex-encoded hash of a block below which synced block bodies are not fully validated
+    let key = config_string("base_node", &net_str, "assume_valid_block_hash");
+    let assume_valid_block_hash = optional(cfg.get_str(&key))?.filter(|hash| !hash.is_empty());
+
+    // If false, mempool sync peers are asked not to push transactions to this node
+    let key = config_string("base_node", &net_str, "mempool_relay_transactions");
+    let mempool_relay_transactions = optional(cfg.get_bool(&key))?.unwrap_or(true);
+
     // Liveness auto ping interval
     let key = config_string("base_node", &net_str, "auto_ping_interval");
     let auto_ping_interval = match cfg.get_int(&key) {
@@ -426,6 +479,13 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
             .map_err(|e| ConfigurationError::new(&key, &e.to_string()))? as u64,
     );
 
+    let key = "wallet.transaction_broadcast_monitoring_interval";
+    let transaction_broadcast_monitoring_interval =
+        Duration::from_secs(optional(cfg.get_int(&key))?.unwrap_or(5) as u64);
+
+    let key = "wallet.transaction_adaptive_broadcast_monitoring";
+    let transaction_adaptive_broadcast_monitoring = optional(cfg.get_bool(&key))?.unwrap_or(false);
+
     let key = "wallet.transaction_chain_monitoring_timeout";
     let transaction_chain_monitoring_timeout = Duration::from_secs(
         cfg.get_int(&key)
@@ -456,6 +516,15 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
     let transaction_routing_mechanism =
         optional(cfg.get_str(key))?.unwrap_or_else(|| "DirectAndStoreAndForward".to_string());
 
+    let key = "wallet.transaction_relay_delay_min";
+    let transaction_relay_delay_min = Duration::from_secs(optional(cfg.get_int(&key))?.unwrap_or(0) as u64);
+
+    let key = "wallet.transaction_relay_delay_max";
+    let transaction_relay_delay_max = Duration::from_secs(optional(cfg.get_int(&key))?.unwrap_or(0) as u64);
+
+    let key = "wallet.transaction_retention_depth";
+    let transaction_retention_depth = optional(cfg.get_int(&key))?.map(|d| d as u64);
+
     let key = "wallet.command_send_wait_stage";
     let wallet_command_send_wait_stage = optional(cfg.get_str(key))?.unwrap_or_else(|| "Broadcast".to_string());
 
@@ -538,6 +607,11 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
         cfg.get_int(&key)
             .map_err(|e| ConfigurationError::new(&key, &e.to_string()))? as usize;
 
+    let key = "common.max_queued_messages_per_peer";
+    let max_queued_messages_per_peer = cfg
+        .get_int(&key)
+        .map_err(|e| ConfigurationError::new(&key, &e.to_string()))? as usize;
+
     let key = "common.fetch_blocks_timeout";
     let fetch_blocks_timeout = Duration::from_secs(
         cfg.get_int(&key)
@@ -610,6 +684,7 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
         orphan_db_clean_out_threshold,
         pruning_horizon,
         pruned_mode_cleanup_interval,
+        advertise_archival,
         core_threads,
         max_threads,
         base_node_identity_file,
@@ -619,8 +694,9 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
         grpc_console_wallet_address,
         peer_seeds,
         dns_seeds,
-        dns_seeds_name_server,
+        dns_seeds_name_servers,
         dns_seeds_use_dnssec,
+        dns_seeds_doh_name,
         peer_db_path,
         enable_wallet,
         num_mining_threads,
@@ -635,17 +711,23 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
         buffer_size_base_node_wallet,
         buffer_rate_limit_base_node,
         buffer_rate_limit_base_node_wallet,
+        max_queued_messages_per_peer,
         fetch_blocks_timeout,
         fetch_utxos_timeout,
         service_request_timeout,
         base_node_query_timeout,
         saf_expiry_duration,
         transaction_broadcast_monitoring_timeout,
+        transaction_broadcast_monitoring_interval,
+        transaction_adaptive_broadcast_monitoring,
         transaction_chain_monitoring_timeout,
         transaction_direct_send_timeout,
         transaction_broadcast_send_timeout,
         transaction_routing_mechanism,
+        transaction_relay_delay_min,
+        transaction_relay_delay_max,
         transaction_num_confirmations_required,
+        transaction_retention_depth,
         console_wallet_password,
         wallet_command_send_wait_stage,
         wallet_command_send_wait_timeout,
@@ -660,6 +742,9 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
         monerod_password,
         monerod_use_auth,
         force_sync_peers,
+        max_block_download_parallelism,
+        assume_valid_block_hash,
+        mempool_relay_transactions,
         wait_for_initial_sync_at_startup,
         max_randomx_vms,
         console_wallet_notify_file,
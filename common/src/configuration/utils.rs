@@ -57,6 +57,7 @@ pub fn default_config(bootstrap: &ConfigBootstrap) -> Config {
     cfg.set_default("common.buffer_rate_limit_base_node", 1_000).unwrap();
     cfg.set_default("common.buffer_rate_limit_base_node_wallet", 1_000)
         .unwrap();
+    cfg.set_default("common.max_queued_messages_per_peer", 100).unwrap();
     cfg.set_default("common.fetch_blocks_timeout", 150).unwrap();
     cfg.set_default("common.fetch_utxos_timeout", 600).unwrap();
     cfg.set_default("common.service_request_timeout", 180).unwrap();
@@ -103,6 +104,7 @@ pub fn default_config(bootstrap: &ConfigBootstrap) -> Config {
     cfg.set_default("base_node.mainnet.pruning_horizon", 0).unwrap();
     cfg.set_default("base_node.mainnet.pruned_mode_cleanup_interval", 50)
         .unwrap();
+    cfg.set_default("base_node.mainnet.advertise_archival", true).unwrap();
     cfg.set_default("base_node.mainnet.peer_seeds", Vec::<String>::new())
         .unwrap();
     cfg.set_default("base_node.mainnet.dns_seeds", Vec::<String>::new())
@@ -162,6 +164,7 @@ pub fn default_config(bootstrap: &ConfigBootstrap) -> Config {
     cfg.set_default("base_node.stibbons.pruning_horizon", 0).unwrap();
     cfg.set_default("base_node.stibbons.pruned_mode_cleanup_interval", 50)
         .unwrap();
+    cfg.set_default("base_node.stibbons.advertise_archival", true).unwrap();
     cfg.set_default("base_node.stibbons.flood_ban_max_msg_count", 10000)
         .unwrap();
     cfg.set_default("base_node.stibbons.peer_seeds", Vec::<String>::new())
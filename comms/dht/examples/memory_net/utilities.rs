@@ -617,14 +617,15 @@ fn connection_manager_logger(
                     println!("'{}' connected to '{}'", node_name, get_name(conn.peer_node_id()),);
                 },
             },
-            PeerDisconnected(node_id) => {
+            PeerDisconnected(_, node_id) => {
                 println!("'{}' disconnected from '{}'", get_name(node_id), node_name);
             },
-            PeerConnectFailed(node_id, err) => {
+            PeerConnectFailed(node_id, reason, err) => {
                 println!(
-                    "'{}' failed to connect to '{}' because '{:?}'",
+                    "'{}' failed to connect to '{}' ({}) because '{:?}'",
                     node_name,
                     get_name(node_id),
+                    reason,
                     err
                 );
             },
@@ -955,3 +956,12 @@ pub async fn take_a_break(num_nodes: usize) {
     banner!("Taking a break for a few seconds to let things settle...");
     time::delay_for(Duration::from_millis(num_nodes as u64 * 100)).await;
 }
+
+/// Sends a join request from `node` and waits until other peers have connected to it in response, or until `timeout`
+/// elapses
+pub async fn join_and_wait(node: &TestNode, timeout: Duration) {
+    match node.dht.dht_requester().join_and_wait(timeout).await {
+        Ok(num_peers) => println!("{} joined the network ({} peer(s) connected)", node, num_peers),
+        Err(err) => println!("{} did not see its join propagate: {}", node, err),
+    }
+}
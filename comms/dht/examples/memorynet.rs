@@ -43,6 +43,7 @@ use crate::memory_net::utilities::{
     do_store_and_forward_message_propagation,
     drain_messaging_events,
     get_name,
+    join_and_wait,
     make_node,
     network_connectivity_stats,
     network_peer_list_stats,
@@ -163,7 +164,7 @@ async fn main() {
     //             .await
     //             .unwrap();
     //
-    //         node.dht.dht_requester().send_join().await.unwrap();
+    //         join_and_wait(node, Duration::from_secs(10)).await;
     //     }
     // }
 
@@ -184,10 +185,9 @@ async fn main() {
             .wait_for_connectivity(Duration::from_secs(10))
             .await
             .unwrap();
-        wallet.dht.dht_requester().send_join().await.unwrap();
+        join_and_wait(wallet, Duration::from_secs(10)).await;
     }
 
-    take_a_break(NUM_NODES).await;
     let mut total_messages = 0;
     total_messages += drain_messaging_events(&mut messaging_events_rx, false).await;
 
@@ -57,6 +57,7 @@ mod memory_net;
 use crate::{
     graphing_utilities::utilities::{network_graph_snapshot, run_python_network_graph_render, PythonRenderType},
     memory_net::utilities::{
+        join_and_wait,
         make_node,
         make_node_from_node_identities,
         make_node_identity,
@@ -165,14 +166,10 @@ async fn main() {
                 .await
                 .unwrap();
 
-            node.dht.dht_requester().send_join().await.unwrap();
+            join_and_wait(node, Duration::from_secs(10)).await;
         }
-
-        // Let the network settle before taking the snapshot.
-        take_a_break(10).await;
     }
 
-    take_a_break(NUM_NODES).await;
     let _ = network_graph_snapshot(
         "base_node_join_multi_seed",
         &seed_nodes,
@@ -64,6 +64,7 @@ use crate::{
     },
     memory_net::utilities::{
         drain_messaging_events,
+        join_and_wait,
         make_node,
         make_node_from_node_identities,
         make_node_identity,
@@ -176,11 +177,10 @@ async fn main() {
                 .await
                 .unwrap();
 
-            node.dht.dht_requester().send_join().await.unwrap();
+            join_and_wait(node, Duration::from_secs(10)).await;
         }
     }
 
-    take_a_break(NUM_NODES).await;
     let _ = network_graph_snapshot(
         "base_node_track_join",
         &seed_nodes,
@@ -234,11 +234,9 @@ async fn main() {
             .await
             .unwrap();
 
-        nodes[nodes.len() - 1].dht.dht_requester().send_join().await.unwrap();
+        join_and_wait(&nodes[nodes.len() - 1], Duration::from_secs(10)).await;
     }
 
-    take_a_break(NUM_NODES).await;
-
     // Log all the message sent in that join attempt
     banner!("Summary of message propagation");
     let message_tree = track_join_message_drain_messaging_events(&mut messaging_events_rx).await;
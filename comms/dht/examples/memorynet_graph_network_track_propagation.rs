@@ -169,11 +169,8 @@ async fn main() {
     //             .await
     //             .unwrap();
     //
-    //         node.dht.dht_requester().send_join().await.unwrap();
+    //         join_and_wait(node, Duration::from_secs(10)).await;
     //     }
-    //
-    //     // Let the network settle before taking the snapshot.
-    //     take_a_break(1).await;
     // }
 
     let _ = drain_messaging_events(&mut messaging_events_rx, false).await;
@@ -30,7 +30,7 @@
 use crate::{
     broadcast_strategy::BroadcastStrategy,
     discovery::DhtDiscoveryError,
-    outbound::{DhtOutboundError, OutboundMessageRequester, SendMessageParams},
+    outbound::{message::SendFailure, DhtOutboundError, MessagePriority, OutboundMessageRequester, SendMessageParams},
     proto::{dht::JoinMessage, envelope::DhtMessageType},
     storage::{DbConnection, DhtDatabase, DhtMetadataKey, StorageError},
     DhtConfig,
@@ -45,15 +45,21 @@ use futures::{
     StreamExt,
 };
 use log::*;
-use std::{cmp, fmt, fmt::Display, sync::Arc};
+use std::{cmp, collections::HashSet, fmt, fmt::Display, sync::Arc, time::Duration};
 use tari_comms::{
-    connectivity::{ConnectivityError, ConnectivityRequester, ConnectivitySelection},
+    connectivity::{
+        ConnectivityError,
+        ConnectivityEvent,
+        ConnectivityEventRx,
+        ConnectivityRequester,
+        ConnectivitySelection,
+    },
     peer_manager::{NodeId, NodeIdentity, PeerFeatures, PeerManager, PeerManagerError, PeerQuery, PeerQuerySortBy},
 };
 use tari_shutdown::ShutdownSignal;
 use tari_utilities::message_format::{MessageFormat, MessageFormatError};
 use thiserror::Error;
-use tokio::task;
+use tokio::{task, time};
 use ttl_cache::TtlCache;
 
 const LOG_TARGET: &str = "comms::dht::actor";
@@ -82,6 +88,8 @@ pub enum DhtActorError {
     ConnectivityError(#[from] ConnectivityError),
     #[error("Connectivity event stream closed")]
     ConnectivityEventStreamClosed,
+    #[error("Timed out waiting for the join message to propagate ({0} peer(s) connected)")]
+    JoinTimeout(usize),
 }
 
 impl From<SendError> for DhtActorError {
@@ -96,10 +104,27 @@ impl From<SendError> for DhtActorError {
     }
 }
 
+/// The result of sending a Join request to the network
+#[derive(Debug, Clone, Default)]
+pub struct JoinResult {
+    /// The neighbouring peers that the Join message was queued for
+    pub neighbours: Vec<NodeId>,
+}
+
+impl JoinResult {
+    /// The number of neighbouring peers that the Join message was queued for
+    pub fn num_neighbours(&self) -> usize {
+        self.neighbours.len()
+    }
+}
+
 #[derive(Debug)]
 pub enum DhtRequest {
-    /// Send a Join request to the network
-    SendJoin,
+    /// Send a Join request to the network and reply with the neighbours the request was sent to
+    SendJoin(oneshot::Sender<Result<JoinResult, DhtActorError>>),
+    /// Send a Join request to the network and reply with the number of peers that connected to this node before the
+    /// given timeout elapsed
+    SendJoinAndWait(Duration, oneshot::Sender<Result<usize, DhtActorError>>),
     /// Inserts a message signature to the msg hash cache. This operation replies with a boolean
     /// which is true if the signature already exists in the cache, otherwise false
     MsgHashCacheInsert(Vec<u8>, oneshot::Sender<bool>),
@@ -113,7 +138,8 @@ impl Display for DhtRequest {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use DhtRequest::*;
         match self {
-            SendJoin => f.write_str("SendJoin"),
+            SendJoin(_) => f.write_str("SendJoin"),
+            SendJoinAndWait(timeout, _) => f.write_str(&format!("SendJoinAndWait (timeout={:.2?})", timeout)),
             MsgHashCacheInsert(_, _) => f.write_str("MsgHashCacheInsert"),
             SelectPeers(s, _) => f.write_str(&format!("SelectPeers (Strategy={})", s)),
             GetMetadata(key, _) => f.write_str(&format!("GetMetadata (key={})", key)),
@@ -134,8 +160,22 @@ impl DhtRequester {
         Self { sender }
     }
 
-    pub async fn send_join(&mut self) -> Result<(), DhtActorError> {
-        self.sender.send(DhtRequest::SendJoin).await.map_err(Into::into)
+    /// Send a Join request to the network. The returned `JoinResult` contains the neighbours that the Join was queued
+    /// for, which callers can use to decide whether to retry. Note that this resolves once the message is queued, use
+    /// `join_and_wait` to wait for the join to propagate.
+    pub async fn send_join(&mut self) -> Result<JoinResult, DhtActorError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.sender.send(DhtRequest::SendJoin(reply_tx)).await?;
+        reply_rx.await.map_err(|_| DhtActorError::ReplyCanceled)?
+    }
+
+    /// Send a Join request to the network and wait until there is evidence that it has propagated, that is, until
+    /// `DhtConfig::join_wait_min_peers` distinct peers have connected to this node. Returns the number of peers that
+    /// connected or `DhtActorError::JoinTimeout` if that did not happen within the given `timeout`.
+    pub async fn join_and_wait(&mut self, timeout: Duration) -> Result<usize, DhtActorError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.sender.send(DhtRequest::SendJoinAndWait(timeout, reply_tx)).await?;
+        reply_rx.await.map_err(|_| DhtActorError::ReplyCanceled)?
     }
 
     pub async fn select_peers(&mut self, broadcast_strategy: BroadcastStrategy) -> Result<Vec<NodeId>, DhtActorError> {
@@ -277,10 +317,31 @@ impl DhtActor {
     fn request_handler(&mut self, request: DhtRequest) -> BoxFuture<'static, Result<(), DhtActorError>> {
         use DhtRequest::*;
         match request {
-            SendJoin => {
+            SendJoin(reply_tx) => {
                 let node_identity = Arc::clone(&self.node_identity);
                 let outbound_requester = self.outbound_requester.clone();
-                Box::pin(Self::broadcast_join(node_identity, outbound_requester))
+                Box::pin(async move {
+                    let result = Self::broadcast_join(node_identity, outbound_requester).await;
+                    reply_tx.send(result).map_err(|_| DhtActorError::ReplyCanceled)
+                })
+            },
+            SendJoinAndWait(timeout, reply_tx) => {
+                let node_identity = Arc::clone(&self.node_identity);
+                let outbound_requester = self.outbound_requester.clone();
+                // Subscribe before the join is sent so that no connections are missed
+                let connectivity_events = self.connectivity.get_event_subscription();
+                let min_peers = self.config.join_wait_min_peers;
+                Box::pin(async move {
+                    let result = Self::broadcast_join_and_wait(
+                        node_identity,
+                        outbound_requester,
+                        connectivity_events,
+                        min_peers,
+                        timeout,
+                    )
+                    .await;
+                    reply_tx.send(result).map_err(|_| DhtActorError::ReplyCanceled)
+                })
             },
             MsgHashCacheInsert(hash, reply_tx) => {
                 // No locks needed here. Downside is this isn't really async, however this should be
@@ -338,18 +399,19 @@ impl DhtActor {
     async fn broadcast_join(
         node_identity: Arc<NodeIdentity>,
         mut outbound_requester: OutboundMessageRequester,
-    ) -> Result<(), DhtActorError>
+    ) -> Result<JoinResult, DhtActorError>
     {
         let message = JoinMessage::from(&node_identity);
 
         debug!(target: LOG_TARGET, "Sending Join message to closest peers");
 
-        outbound_requester
+        let response = outbound_requester
             .send_message_no_header(
                 SendMessageParams::new()
                     .closest(node_identity.node_id().clone(), vec![])
                     .with_destination(node_identity.node_id().clone().into())
                     .with_dht_message_type(DhtMessageType::Join)
+                    .with_priority(MessagePriority::Control)
                     .force_origin()
                     .finish(),
                 message,
@@ -357,7 +419,67 @@ impl DhtActor {
             .await
             .map_err(DhtActorError::FailedToBroadcastJoinMessage)?;
 
-        Ok(())
+        let neighbours = match response.resolve().await {
+            Ok(send_states) => send_states.to_destination_node_ids(),
+            Err(SendFailure::NoMessagesQueued) => Vec::new(),
+            Err(err) => return Err(DhtActorError::FailedToBroadcastJoinMessage(err.into())),
+        };
+        debug!(
+            target: LOG_TARGET,
+            "Join message sent to {} neighbour(s)",
+            neighbours.len()
+        );
+
+        Ok(JoinResult { neighbours })
+    }
+
+    async fn broadcast_join_and_wait(
+        node_identity: Arc<NodeIdentity>,
+        outbound_requester: OutboundMessageRequester,
+        connectivity_events: ConnectivityEventRx,
+        min_peers: usize,
+        timeout: Duration,
+    ) -> Result<usize, DhtActorError>
+    {
+        Self::broadcast_join(node_identity, outbound_requester).await?;
+
+        let mut connectivity_events = connectivity_events.fuse();
+        let mut connected_peers = HashSet::new();
+        let wait_for_peers = async {
+            while connected_peers.len() < min_peers {
+                match connectivity_events.next().await {
+                    Some(Ok(event)) => {
+                        if let ConnectivityEvent::PeerConnected(conn) = &*event {
+                            if conn.direction().is_inbound() {
+                                connected_peers.insert(conn.peer_node_id().clone());
+                            }
+                        }
+                    },
+                    Some(Err(err)) => {
+                        debug!(
+                            target: LOG_TARGET,
+                            "Missed connectivity events while waiting for join: {:?}", err
+                        );
+                    },
+                    None => return Err(DhtActorError::ConnectivityEventStreamClosed),
+                }
+            }
+            Ok(())
+        };
+
+        let result = time::timeout(timeout, wait_for_peers).await;
+        match result {
+            Ok(Ok(_)) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Join propagated: {} peer(s) connected",
+                    connected_peers.len()
+                );
+                Ok(connected_peers.len())
+            },
+            Ok(Err(err)) => Err(err),
+            Err(_) => Err(DhtActorError::JoinTimeout(connected_peers.len())),
+        }
     }
 
     async fn select_peers(
@@ -439,6 +561,12 @@ impl DhtActor {
                     .map(|p| p.node_id)
                     .collect())
             },
+            RandomConnected(n, exclude) => {
+                let connections = connectivity
+                    .select_connections(ConnectivitySelection::random_nodes(n, exclude))
+                    .await?;
+                Ok(connections.into_iter().map(|c| c.peer_node_id().clone()).collect())
+            },
             Broadcast(exclude) => {
                 let connections = connectivity
                     .select_connections(ConnectivitySelection::random_nodes(
@@ -644,10 +772,14 @@ mod test {
     use crate::{
         broadcast_strategy::BroadcastClosestRequest,
         envelope::NodeDestination,
+        outbound::{MessageSendState, SendMessageResponse},
         test_utils::{build_peer_manager, make_client_identity, make_node_identity},
     };
     use chrono::{DateTime, Utc};
-    use tari_comms::test_utils::mocks::{create_connectivity_mock, create_peer_connection_mock_pair};
+    use tari_comms::{
+        message::MessageTag,
+        test_utils::mocks::{create_connectivity_mock, create_dummy_peer_connection, create_peer_connection_mock_pair},
+    };
     use tari_shutdown::Shutdown;
     use tari_test_utils::random;
 
@@ -681,9 +813,101 @@ mod test {
 
         actor.spawn();
 
-        requester.send_join().await.unwrap();
+        let join_task = task::spawn(async move { requester.send_join().await });
+
+        let neighbours = (0..3)
+            .map(|_| make_node_identity().node_id().clone())
+            .collect::<Vec<_>>();
+        let send_states = neighbours
+            .iter()
+            .map(|node_id| {
+                let (_, reply_rx) = oneshot::channel();
+                MessageSendState::new(MessageTag::new(), node_id.clone(), reply_rx)
+            })
+            .collect::<Vec<_>>();
+        let (params, _) = unwrap_oms_send_msg!(
+            out_rx.next().await.unwrap(),
+            reply_value = SendMessageResponse::Queued(send_states.into())
+        );
+        assert_eq!(params.dht_message_type, DhtMessageType::Join);
+
+        let join_result = join_task.await.unwrap().unwrap();
+        assert_eq!(join_result.num_neighbours(), 3);
+        assert_eq!(join_result.neighbours, neighbours);
+    }
+
+    #[tokio_macros::test_basic]
+    async fn join_and_wait() {
+        let node_identity = make_node_identity();
+        let peer_manager = build_peer_manager();
+        let (out_tx, mut out_rx) = mpsc::channel(1);
+        let (connectivity_manager, mock) = create_connectivity_mock();
+        let mock_state = mock.get_shared_state();
+        mock.spawn();
+        let (actor_tx, actor_rx) = mpsc::channel(1);
+        let mut requester = DhtRequester::new(actor_tx);
+        let outbound_requester = OutboundMessageRequester::new(out_tx);
+        let shutdown = Shutdown::new();
+        let actor = DhtActor::new(
+            DhtConfig {
+                join_wait_min_peers: 2,
+                ..Default::default()
+            },
+            db_connection().await,
+            node_identity,
+            peer_manager,
+            connectivity_manager,
+            outbound_requester,
+            actor_rx,
+            shutdown.to_signal(),
+        );
+
+        actor.spawn();
+
+        let mut join_task = task::spawn(async move { requester.join_and_wait(Duration::from_secs(10)).await });
         let (params, _) = unwrap_oms_send_msg!(out_rx.next().await.unwrap());
         assert_eq!(params.dht_message_type, DhtMessageType::Join);
+
+        let (conn, _) = create_dummy_peer_connection(make_node_identity().node_id().clone());
+        mock_state.publish_event(ConnectivityEvent::PeerConnected(conn.clone()));
+        // The same peer connecting again does not count
+        mock_state.publish_event(ConnectivityEvent::PeerConnected(conn));
+        assert!(time::timeout(Duration::from_millis(50), &mut join_task).await.is_err());
+
+        let (conn, _) = create_dummy_peer_connection(make_node_identity().node_id().clone());
+        mock_state.publish_event(ConnectivityEvent::PeerConnected(conn));
+        let num_peers = join_task.await.unwrap().unwrap();
+        assert_eq!(num_peers, 2);
+    }
+
+    #[tokio_macros::test_basic]
+    async fn join_and_wait_timeout() {
+        let node_identity = make_node_identity();
+        let peer_manager = build_peer_manager();
+        let (out_tx, mut out_rx) = mpsc::channel(1);
+        let (connectivity_manager, mock) = create_connectivity_mock();
+        mock.spawn();
+        let (actor_tx, actor_rx) = mpsc::channel(1);
+        let mut requester = DhtRequester::new(actor_tx);
+        let outbound_requester = OutboundMessageRequester::new(out_tx);
+        let shutdown = Shutdown::new();
+        let actor = DhtActor::new(
+            Default::default(),
+            db_connection().await,
+            node_identity,
+            peer_manager,
+            connectivity_manager,
+            outbound_requester,
+            actor_rx,
+            shutdown.to_signal(),
+        );
+
+        actor.spawn();
+
+        let join_task = task::spawn(async move { requester.join_and_wait(Duration::from_millis(10)).await });
+        let _ = unwrap_oms_send_msg!(out_rx.next().await.unwrap());
+        let err = join_task.await.unwrap().unwrap_err();
+        assert!(matches!(err, DhtActorError::JoinTimeout(0)));
     }
 
     #[tokio_macros::test_basic]
@@ -771,6 +995,12 @@ mod test {
             .unwrap();
         assert_eq!(peers.len(), 1);
 
+        let peers = requester
+            .select_peers(BroadcastStrategy::RandomConnected(1, Vec::new()))
+            .await
+            .unwrap();
+        assert_eq!(peers, vec![conn_out.peer_node_id().clone()]);
+
         let peers = requester
             .select_peers(BroadcastStrategy::Propagate(NodeDestination::Unknown, Vec::new()))
             .await
@@ -56,6 +56,9 @@ pub enum BroadcastStrategy {
     Flood(Vec<NodeId>),
     /// Send to a random set of peers of size n that are Communication Nodes, excluding the given node IDs
     Random(usize, Vec<NodeId>),
+    /// Send to a random set of connected peers of size n that are Communication Nodes, excluding the given node IDs.
+    /// If no peers are connected, no messages are sent.
+    RandomConnected(usize, Vec<NodeId>),
     /// Send to all n nearest Communication Nodes according to the given BroadcastClosestRequest
     Closest(Box<BroadcastClosestRequest>),
     Broadcast(Vec<NodeId>),
@@ -72,6 +75,7 @@ impl fmt::Display for BroadcastStrategy {
             Flood(excluded) => write!(f, "Flood({} excluded)", excluded.len()),
             Closest(request) => write!(f, "Closest({})", request),
             Random(n, excluded) => write!(f, "Random({}, {} excluded)", n, excluded.len()),
+            RandomConnected(n, excluded) => write!(f, "RandomConnected({}, {} excluded)", n, excluded.len()),
             Broadcast(excluded) => write!(f, "Broadcast({} excluded)", excluded.len()),
             Propagate(destination, excluded) => write!(f, "Propagate({}, {} excluded)", destination, excluded.len(),),
         }
@@ -82,7 +86,17 @@ impl BroadcastStrategy {
     /// Returns true if this strategy will send multiple messages, otherwise false
     pub fn is_multi_message(&self) -> bool {
         use BroadcastStrategy::*;
-        matches!(self, Closest(_) | Flood(_) | Broadcast(_) | Random(_, _) | Propagate(_, _))
+        matches!(
+            self,
+            Closest(_) | Flood(_) | Broadcast(_) | Random(_, _) | RandomConnected(_, _) | Propagate(_, _)
+        )
+    }
+
+    /// Returns true if the message is gossiped to the network (broadcast, flood or propagate). These messages are
+    /// best effort, so they are dropped rather than queued when the outbound pipeline is saturated.
+    pub fn is_gossip(&self) -> bool {
+        use BroadcastStrategy::*;
+        matches!(self, Flood(_) | Broadcast(_) | Propagate(_, _))
     }
 
     pub fn is_direct(&self) -> bool {
@@ -141,6 +155,27 @@ mod test {
         assert_eq!(BroadcastStrategy::Random(0, vec![]).is_direct(), false);
     }
 
+    #[test]
+    fn is_gossip() {
+        assert!(BroadcastStrategy::Broadcast(Default::default()).is_gossip());
+        assert!(BroadcastStrategy::Flood(Default::default()).is_gossip());
+        assert!(BroadcastStrategy::Propagate(Default::default(), Default::default()).is_gossip());
+        assert_eq!(
+            BroadcastStrategy::DirectNodeId(Box::new(NodeId::default())).is_gossip(),
+            false
+        );
+        assert_eq!(
+            BroadcastStrategy::Closest(Box::new(BroadcastClosestRequest {
+                node_id: NodeId::default(),
+                excluded_peers: Default::default(),
+                connected_only: false
+            }))
+            .is_gossip(),
+            false
+        );
+        assert_eq!(BroadcastStrategy::Random(0, vec![]).is_gossip(), false);
+    }
+
     #[test]
     fn direct_public_key() {
         assert!(BroadcastStrategy::DirectPublicKey(Box::new(CommsPublicKey::default()))
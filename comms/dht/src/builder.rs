@@ -20,6 +20,8 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+#[cfg(feature = "message-journal")]
+use crate::inbound::MessageJournal;
 use crate::{dht::DhtInitializationError, outbound::DhtOutboundRequest, DbConnectionUrl, Dht, DhtConfig};
 use futures::channel::mpsc;
 use std::{sync::Arc, time::Duration};
@@ -36,6 +38,8 @@ pub struct DhtBuilder {
     outbound_tx: mpsc::Sender<DhtOutboundRequest>,
     connectivity: ConnectivityRequester,
     shutdown_signal: ShutdownSignal,
+    #[cfg(feature = "message-journal")]
+    message_journal: Option<MessageJournal>,
 }
 
 impl DhtBuilder {
@@ -57,6 +61,8 @@ impl DhtBuilder {
             outbound_tx,
             connectivity,
             shutdown_signal,
+            #[cfg(feature = "message-journal")]
+            message_journal: None,
         }
     }
 
@@ -130,11 +136,19 @@ impl DhtBuilder {
         self
     }
 
+    /// Record every message received through the inbound middleware to the given journal, so that they can be replayed
+    /// with `Dht::replay_message_journal`
+    #[cfg(feature = "message-journal")]
+    pub fn with_message_journal(mut self, journal: MessageJournal) -> Self {
+        self.message_journal = Some(journal);
+        self
+    }
+
     /// Build and initialize a Dht object.
     ///
     /// Will panic not in a tokio runtime context
     pub async fn build(self) -> Result<Dht, DhtInitializationError> {
-        Dht::initialize(
+        let dht = Dht::initialize(
             self.config,
             self.node_identity,
             self.peer_manager,
@@ -142,6 +156,9 @@ impl DhtBuilder {
             self.connectivity,
             self.shutdown_signal,
         )
-        .await
+        .await?;
+        #[cfg(feature = "message-journal")]
+        let dht = dht.with_message_journal(self.message_journal);
+        Ok(dht)
     }
 }
@@ -64,6 +64,13 @@ pub struct DhtConfig {
     pub saf_high_priority_msg_storage_ttl: Duration,
     /// The limit on the message size to store in SAF storage in bytes. Default 500 KiB
     pub saf_max_message_size: usize,
+    /// The maximum number of messages that will be stored for a single destination. Further messages for that
+    /// destination are refused until some have been collected or expire.
+    /// Default: 1,000
+    pub saf_max_messages_per_destination: usize,
+    /// The maximum total size in bytes of message bodies that will be stored for a single destination.
+    /// Default: 10 MiB
+    pub saf_max_bytes_per_destination: usize,
     /// When true, store and forward messages are requested from peers on connect (Default: true)
     pub saf_auto_request: bool,
     /// The minimum period used to request SAF messages from a peer. When requesting SAF messages,
@@ -77,9 +84,28 @@ pub struct DhtConfig {
     /// The time-to-live for items in the message hash cache
     /// Default: 300s (5 mins)
     pub msg_hash_cache_ttl: Duration,
+    /// Message types (the `i32` value of the message type in the message header) for which inbound messages are also
+    /// deduplicated by their decrypted content, ignoring the envelope. This prevents functionally identical messages
+    /// that were wrapped in different envelopes from being propagated more than once. Only message types where
+    /// identical content always means the same message should be added.
+    /// Default: empty (disabled)
+    pub content_dedup_message_types: Vec<i32>,
+    /// Inbound messages with an origin timestamp older than this are discarded to resist replay of old messages that
+    /// are no longer in the message hash cache. Messages without a timestamp are discarded. Messages received via
+    /// store and forward are not checked.
+    /// Default: 10 mins
+    pub max_message_age: Duration,
+    /// The maximum number of inbound messages that are decrypted at the same time. Decryption is done on the blocking
+    /// thread pool so that it does not starve other async tasks under load. Must be at least 1.
+    /// Default: 4
+    pub decryption_pool_size: usize,
     /// The duration to wait for a peer discovery to complete before giving up.
     /// Default: 2 minutes
     pub discovery_request_timeout: Duration,
+    /// The length of time that a successful peer discovery result is cached. Discovery requests for the same public
+    /// key within this period are answered from the cache without sending a discovery message to the network.
+    /// Default: 1 minute
+    pub discovery_cache_ttl: Duration,
     /// Set to true to automatically broadcast a join message when ready, otherwise false. Default: false
     pub auto_join: bool,
     /// The minimum time between sending a Join message to the network. Joins are only sent when the node establishes
@@ -87,6 +113,10 @@ pub struct DhtConfig {
     /// change happens again after this period, another join will be sent.
     /// Default: 10 minutes
     pub join_cooldown_interval: Duration,
+    /// The number of distinct peers that must connect to this node after a join has been sent before
+    /// `DhtRequester::join_and_wait` considers the join to have propagated.
+    /// Default: 1
+    pub join_wait_min_peers: usize,
     /// The interval to update the neighbouring and random pools, if necessary.
     /// Default: 2 minutes
     pub connectivity_update_interval: Duration,
@@ -160,15 +190,22 @@ impl Default for DhtConfig {
             saf_high_priority_msg_storage_ttl: Duration::from_secs(3 * 24 * 60 * 60), // 3 days
             saf_auto_request: true,
             saf_max_message_size: 512 * 1024,
+            saf_max_messages_per_destination: 1_000,
+            saf_max_bytes_per_destination: 10 * 1024 * 1024,
             saf_minimum_request_period: Duration::from_secs(3 * 24 * 60 * 60), // 3 days
             msg_hash_cache_capacity: 100_000,
             msg_hash_cache_ttl: Duration::from_secs(5 * 60),
+            content_dedup_message_types: Vec::new(),
+            max_message_age: Duration::from_secs(10 * 60),
+            decryption_pool_size: 4,
             database_url: DbConnectionUrl::Memory,
             discovery_request_timeout: Duration::from_secs(2 * 60),
+            discovery_cache_ttl: Duration::from_secs(60),
             connectivity_update_interval: Duration::from_secs(2 * 60),
             connectivity_random_pool_refresh: Duration::from_secs(2 * 60 * 60),
             auto_join: false,
             join_cooldown_interval: Duration::from_secs(10 * 60),
+            join_wait_min_peers: 1,
             network: Network::TestNet,
             network_discovery: Default::default(),
             ban_duration: Duration::from_secs(6 * 60 * 60),
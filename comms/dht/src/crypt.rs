@@ -28,6 +28,7 @@ use tari_crypto::{
             chacha20::ChaCha20,
             cipher::{Cipher, CipherError},
         },
+        epoch_time::EpochTime,
         ByteArray,
     },
 };
@@ -45,6 +46,16 @@ pub fn encrypt(cipher_key: &CommsPublicKey, plain_text: &[u8]) -> Result<Vec<u8>
     ChaCha20::seal_with_integral_nonce(&plain_text.to_vec(), cipher_key.as_bytes())
 }
 
+/// Returns the bytes that the origin of a message signs to produce the `OriginMac::timestamp_signature`. The origin
+/// timestamp is appended to the message body so that it cannot be changed in transit without invalidating the
+/// signature. The `OriginMac::signature` only signs the body, so that peers that do not know about the timestamp can
+/// still authenticate the message.
+pub fn create_origin_mac_timestamp_challenge(timestamp: EpochTime, body: &[u8]) -> Vec<u8> {
+    let mut challenge = body.to_vec();
+    challenge.extend_from_slice(&timestamp.as_u64().to_le_bytes());
+    challenge
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -59,6 +70,14 @@ mod test {
         assert_eq!(decrypted, plain_text);
     }
 
+    #[test]
+    fn origin_mac_timestamp_challenge() {
+        let body = b"body";
+        let challenge = create_origin_mac_timestamp_challenge(EpochTime::from(1_600_000_000), body);
+        assert_eq!(&challenge[..body.len()], body);
+        assert_eq!(&challenge[body.len()..], &1_600_000_000u64.to_le_bytes());
+    }
+
     #[test]
     fn decrypt_fn() {
         let key = CommsPublicKey::default();
@@ -31,7 +31,7 @@ use crate::{
     logging_middleware::MessageLoggingLayer,
     network_discovery::DhtNetworkDiscovery,
     outbound,
-    outbound::DhtOutboundRequest,
+    outbound::{DhtOutboundRequest, OutboundPriorityDispatcher},
     proto::envelope::DhtMessageType,
     rpc,
     storage::{DbConnection, StorageError},
@@ -44,6 +44,8 @@ use crate::{
 };
 use futures::{channel::mpsc, future, Future};
 use log::*;
+#[cfg(feature = "message-journal")]
+use std::path::Path;
 use std::sync::Arc;
 use tari_comms::{
     connectivity::ConnectivityRequester,
@@ -62,6 +64,8 @@ const DHT_ACTOR_CHANNEL_SIZE: usize = 100;
 const DHT_DISCOVERY_CHANNEL_SIZE: usize = 100;
 const DHT_SAF_SERVICE_CHANNEL_SIZE: usize = 100;
 const DHT_EVENT_BROADCAST_CHANNEL_SIZE: usize = 100;
+const DHT_OUTBOUND_PRIORITY_CHANNEL_SIZE: usize = 100;
+const DHT_OUTBOUND_PRIORITY_LANE_SIZE: usize = 1000;
 
 #[derive(Debug, Error)]
 pub enum DhtInitializationError {
@@ -71,6 +75,8 @@ pub enum DhtInitializationError {
     StoreAndForwardInitializationError(#[from] StoreAndForwardError),
     #[error("DhtActorInitializationError: {0}")]
     DhtActorInitializationError(#[from] DhtActorError),
+    #[error("Invalid DHT config: {0}")]
+    InvalidConfig(String),
 }
 
 /// Responsible for starting the DHT actor, building the DHT middleware stack and as a factory
@@ -83,7 +89,8 @@ pub struct Dht {
     peer_manager: Arc<PeerManager>,
     /// Dht configuration
     config: DhtConfig,
-    /// Used to create a OutboundMessageRequester.
+    /// Used to create a OutboundMessageRequester. Requests are prioritised by the `OutboundPriorityDispatcher` before
+    /// entering the outbound pipeline.
     outbound_tx: mpsc::Sender<DhtOutboundRequest>,
     /// Sender for DHT requests
     dht_sender: mpsc::Sender<DhtRequest>,
@@ -99,6 +106,9 @@ pub struct Dht {
     event_publisher: DhtEventSender,
     /// Used by MetricsLayer to collect metrics and to inform heuristics for peer banning
     metrics_collector: MetricsCollectorHandle,
+    /// Journal that inbound messages are recorded to, if set
+    #[cfg(feature = "message-journal")]
+    message_journal: Option<inbound::MessageJournal>,
 }
 
 impl Dht {
@@ -111,11 +121,27 @@ impl Dht {
         shutdown_signal: ShutdownSignal,
     ) -> Result<Self, DhtInitializationError>
     {
+        if config.decryption_pool_size == 0 {
+            return Err(DhtInitializationError::InvalidConfig(
+                "decryption_pool_size must be at least 1".to_string(),
+            ));
+        }
+
         let (dht_sender, dht_receiver) = mpsc::channel(DHT_ACTOR_CHANNEL_SIZE);
         let (discovery_sender, discovery_receiver) = mpsc::channel(DHT_DISCOVERY_CHANNEL_SIZE);
         let (saf_sender, saf_receiver) = mpsc::channel(DHT_SAF_SERVICE_CHANNEL_SIZE);
         let (saf_response_signal_sender, saf_response_signal_receiver) = mpsc::channel(DHT_SAF_SERVICE_CHANNEL_SIZE);
         let (event_publisher, _) = broadcast::channel(DHT_EVENT_BROADCAST_CHANNEL_SIZE);
+        // Requesters send to the priority dispatcher, which forwards the highest priority request to the outbound
+        // pipeline whenever it has capacity
+        let (priority_tx, priority_rx) = mpsc::channel(DHT_OUTBOUND_PRIORITY_CHANNEL_SIZE);
+        OutboundPriorityDispatcher::new(
+            priority_rx,
+            outbound_tx,
+            DHT_OUTBOUND_PRIORITY_LANE_SIZE,
+            shutdown_signal.clone(),
+        )
+        .spawn();
 
         let metrics_collector = MetricsCollector::spawn();
 
@@ -124,13 +150,15 @@ impl Dht {
             peer_manager,
             metrics_collector,
             config,
-            outbound_tx,
+            outbound_tx: priority_tx,
             dht_sender,
             saf_sender,
             saf_response_signal_sender,
             connectivity,
             discovery_sender,
             event_publisher: event_publisher.clone(),
+            #[cfg(feature = "message-journal")]
+            message_journal: None,
         };
 
         let conn = DbConnection::connect_and_migrate(dht.config.database_url.clone())
@@ -154,6 +182,13 @@ impl Dht {
         Ok(dht)
     }
 
+    /// Record inbound messages received through the inbound middleware to the given journal
+    #[cfg(feature = "message-journal")]
+    pub(crate) fn with_message_journal(mut self, message_journal: Option<inbound::MessageJournal>) -> Self {
+        self.message_journal = message_journal;
+        self
+    }
+
     /// Create a DHT RPC service
     pub fn rpc_service(&self) -> rpc::DhtService<rpc::DhtRpcServiceImpl> {
         rpc::DhtService::new(rpc::DhtRpcServiceImpl::new(self.peer_manager.clone()))
@@ -290,9 +325,12 @@ impl Dht {
         S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError> + Clone + Send + Sync + 'static,
         S::Future: Send,
     {
+        let builder = ServiceBuilder::new();
+        #[cfg(feature = "message-journal")]
+        let builder = builder.layer(inbound::MessageJournalLayer::optional(self.message_journal.clone()));
         // FIXME: There is an unresolved stack overflow issue on windows in debug mode during runtime, but not in
         //        release mode, related to the amount of layers. (issue #1416)
-        ServiceBuilder::new()
+        builder
             .layer(MetricsLayer::new(self.metrics_collector.clone()))
             .layer(inbound::DeserializeLayer::new(self.peer_manager.clone()))
             .layer(inbound::ValidateLayer::new(self.config.network))
@@ -307,6 +345,11 @@ impl Dht {
                 self.node_identity.clone(),
                 self.connectivity.clone(),
             ))
+            .layer(inbound::MaxAgeLayer::new(self.config.max_message_age))
+            .layer(inbound::ContentDedupLayer::new(
+                self.dht_requester(),
+                self.config.content_dedup_message_types.clone(),
+            ))
             .layer(store_forward::StoreLayer::new(
                 self.config.clone(),
                 Arc::clone(&self.peer_manager),
@@ -335,6 +378,57 @@ impl Dht {
             .into_inner()
     }
 
+    /// Returns the DHT stack used to replay journaled inbound messages as a `tower::layer::Layer`. Replayed messages
+    /// were already accepted and forwarded when they were first received, so this is the inbound stack without the
+    /// dedup, max age and forward layers. Replaying must not have any effect on the network or on the DHT state, so
+    /// only domain messages are replayed and the store and forward and DHT handlers are left out. Peers are never
+    /// banned for a replayed message.
+    pub fn replay_middleware_layer<S>(
+        &self,
+    ) -> impl Layer<
+        S,
+        Service = impl Service<
+            InboundMessage,
+            Response = (),
+            Error = PipelineError,
+            Future = impl Future<Output = Result<(), PipelineError>> + Send,
+        > + Clone
+                      + Send,
+    >
+    where
+        S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError> + Clone + Send + Sync + 'static,
+        S::Future: Send,
+    {
+        ServiceBuilder::new()
+            .layer(MetricsLayer::new(self.metrics_collector.clone()))
+            .layer(inbound::DeserializeLayer::new(self.peer_manager.clone()))
+            .layer(inbound::ValidateLayer::new(self.config.network))
+            .layer(tower_filter::FilterLayer::new(Self::domain_messages_filter()))
+            .layer(MessageLoggingLayer::new(format!(
+                "Replay [{}]",
+                self.node_identity.node_id().short_str()
+            )))
+            .layer(inbound::DecryptionLayer::new(
+                self.config.clone(),
+                self.node_identity.clone(),
+                Self::disconnected_connectivity_requester(),
+            ))
+            .into_inner()
+    }
+
+    /// Replay the messages recorded in the message journal at `path` through the replay middleware and then the given
+    /// service. Returns the number of messages that were replayed.
+    #[cfg(feature = "message-journal")]
+    pub async fn replay_message_journal<P, S>(&self, path: P, service: S) -> Result<usize, inbound::MessageJournalError>
+    where
+        P: AsRef<Path>,
+        S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError> + Clone + Send + Sync + 'static,
+        S::Future: Send,
+    {
+        let service = self.replay_middleware_layer().layer(service);
+        inbound::replay_journal(path, &self.node_identity, service).await
+    }
+
     /// Returns an the full DHT stack as a `tower::layer::Layer`. This can be composed with
     /// other outbound middleware services which expect an OutboundMessage
     pub fn outbound_middleware_layer<S>(
@@ -369,6 +463,27 @@ impl Dht {
             .into_inner()
     }
 
+    /// Produces a filter predicate which only allows domain messages, i.e. messages that are not handled by the DHT
+    fn domain_messages_filter(
+    ) -> impl tower_filter::Predicate<DhtInboundMessage, Future = future::Ready<Result<(), PipelineError>>> + Clone + Send
+    {
+        |msg: &DhtInboundMessage| match msg.dht_header.message_type {
+            DhtMessageType::None => future::ready(Ok(())),
+            message_type => future::ready(Err(anyhow::anyhow!(
+                "Message filtered out because {:?} messages are not replayed",
+                message_type
+            ))),
+        }
+    }
+
+    /// A connectivity requester that is not connected to the connectivity manager, so that any request made with it
+    /// fails without effect
+    fn disconnected_connectivity_requester() -> ConnectivityRequester {
+        let (request_tx, _) = mpsc::channel(1);
+        let (event_tx, _) = broadcast::channel(1);
+        ConnectivityRequester::new(request_tx, event_tx)
+    }
+
     /// Produces a filter predicate which disallows store and forward messages if that feature is not
     /// supported by the node.
     fn unsupported_saf_messages_filter(
@@ -414,6 +529,8 @@ mod test {
             make_node_identity,
         },
         DhtBuilder,
+        DhtConfig,
+        DhtInitializationError,
     };
     use futures::{channel::mpsc, StreamExt};
     use std::{sync::Arc, time::Duration};
@@ -427,6 +544,28 @@ mod test {
     use tokio::{task, time};
     use tower::{layer::Layer, Service};
 
+    #[tokio_macros::test_basic]
+    async fn zero_decryption_pool_size_is_rejected() {
+        let (connectivity, _) = create_connectivity_mock();
+        let (out_tx, _) = mpsc::channel(10);
+        let shutdown = Shutdown::new();
+        let result = DhtBuilder::new(
+            make_node_identity(),
+            build_peer_manager(),
+            out_tx,
+            connectivity,
+            shutdown.to_signal(),
+        )
+        .with_config(DhtConfig {
+            decryption_pool_size: 0,
+            ..DhtConfig::default_local_test()
+        })
+        .build()
+        .await;
+
+        assert!(matches!(result, Err(DhtInitializationError::InvalidConfig(_))));
+    }
+
     #[tokio_macros::test_basic]
     async fn stack_unencrypted() {
         let node_identity = make_node_identity();
@@ -26,4 +26,8 @@ mod service;
 
 pub(crate) use self::requester::DhtDiscoveryRequest;
 
-pub use self::{error::DhtDiscoveryError, requester::DhtDiscoveryRequester, service::DhtDiscoveryService};
+pub use self::{
+    error::DhtDiscoveryError,
+    requester::{DhtDiscoveryRequester, DiscoveryOptions},
+    service::DhtDiscoveryService,
+};
@@ -32,11 +32,33 @@ use std::{
 use tari_comms::{peer_manager::Peer, types::CommsPublicKey};
 use tokio::time;
 
+/// Per-request overrides for a peer discovery. Any option that is not set uses the value from `DhtConfig`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiscoveryOptions {
+    /// The duration to wait for the discovery to complete. Overrides `DhtConfig::discovery_request_timeout`.
+    pub timeout: Option<Duration>,
+    /// The number of connected peers to send the discovery message to. Overrides `DhtConfig::broadcast_factor`.
+    pub fan_out: Option<usize>,
+}
+
+impl DiscoveryOptions {
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_fan_out(mut self, fan_out: usize) -> Self {
+        self.fan_out = Some(fan_out);
+        self
+    }
+}
+
 #[derive(Debug)]
 pub enum DhtDiscoveryRequest {
     DiscoverPeer(
         Box<CommsPublicKey>,
         NodeDestination,
+        DiscoveryOptions,
         oneshot::Sender<Result<Peer, DhtDiscoveryError>>,
     ),
     NotifyDiscoveryResponseReceived(Box<DiscoveryResponseMessage>),
@@ -46,7 +68,9 @@ impl Display for DhtDiscoveryRequest {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         use DhtDiscoveryRequest::*;
         match self {
-            DiscoverPeer(public_key, dest, _) => write!(f, "DiscoverPeer({}, {})", public_key, dest),
+            DiscoverPeer(public_key, dest, options, _) => {
+                write!(f, "DiscoverPeer({}, {}, {:?})", public_key, dest, options)
+            },
             NotifyDiscoveryResponseReceived(discovery_resp) => {
                 write!(f, "NotifyDiscoveryResponseReceived({:#?})", discovery_resp)
             },
@@ -87,6 +111,19 @@ impl DhtDiscoveryRequester {
         dest_public_key: Box<CommsPublicKey>,
         destination: NodeDestination,
     ) -> Result<Peer, DhtDiscoveryError>
+    {
+        self.discover_peer_with_options(dest_public_key, destination, Default::default())
+            .await
+    }
+
+    /// Initiate a peer discovery using the given `DiscoveryOptions` to override the configured timeout and fan-out
+    /// for this request only. See `discover_peer` for a description of the other arguments.
+    pub async fn discover_peer_with_options(
+        &mut self,
+        dest_public_key: Box<CommsPublicKey>,
+        destination: NodeDestination,
+        options: DiscoveryOptions,
+    ) -> Result<Peer, DhtDiscoveryError>
     {
         let (reply_tx, reply_rx) = oneshot::channel();
 
@@ -94,12 +131,13 @@ impl DhtDiscoveryRequester {
             .send(DhtDiscoveryRequest::DiscoverPeer(
                 dest_public_key,
                 destination,
+                options,
                 reply_tx,
             ))
             .await?;
 
         time::timeout(
-            self.discovery_timeout,
+            options.timeout.unwrap_or(self.discovery_timeout),
             reply_rx
         )
             .await
@@ -23,7 +23,7 @@
 use crate::{
     discovery::{requester::DhtDiscoveryRequest, DhtDiscoveryError},
     envelope::{DhtMessageType, NodeDestination},
-    outbound::{OutboundEncryption, OutboundMessageRequester, SendMessageParams},
+    outbound::{MessagePriority, OutboundEncryption, OutboundMessageRequester, SendMessageParams},
     proto::dht::{DiscoveryMessage, DiscoveryResponseMessage},
     DhtConfig,
 };
@@ -75,6 +75,7 @@ pub struct DhtDiscoveryService {
     request_rx: Option<mpsc::Receiver<DhtDiscoveryRequest>>,
     shutdown_signal: Option<ShutdownSignal>,
     inflight_discoveries: HashMap<u64, DiscoveryRequestState>,
+    discovery_cache: HashMap<CommsPublicKey, (Peer, Instant)>,
 }
 
 impl DhtDiscoveryService {
@@ -95,6 +96,7 @@ impl DhtDiscoveryService {
             shutdown_signal: Some(shutdown_signal),
             request_rx: Some(request_rx),
             inflight_discoveries: HashMap::new(),
+            discovery_cache: HashMap::new(),
         }
     }
 
@@ -137,10 +139,19 @@ impl DhtDiscoveryService {
     async fn handle_request(&mut self, request: DhtDiscoveryRequest) {
         use DhtDiscoveryRequest::*;
         match request {
-            DiscoverPeer(dest_pubkey, destination, reply_tx) => {
+            DiscoverPeer(dest_pubkey, destination, options, reply_tx) => {
+                if let Some(peer) = self.get_cached_discovery(&dest_pubkey) {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Using cached discovery result for peer '{}'", peer.node_id
+                    );
+                    let _ = reply_tx.send(Ok(peer));
+                    return;
+                }
                 log_if_error!(
                     target: LOG_TARGET,
-                    self.initiate_peer_discovery(dest_pubkey, destination, reply_tx).await,
+                    self.initiate_peer_discovery(dest_pubkey, destination, options.fan_out, reply_tx)
+                        .await,
                     "Failed to initiate a discovery request because '{error}'",
                 );
             },
@@ -149,6 +160,21 @@ impl DhtDiscoveryService {
         }
     }
 
+    fn get_cached_discovery(&self, public_key: &CommsPublicKey) -> Option<Peer> {
+        self.discovery_cache
+            .get(public_key)
+            .filter(|(_, cached_at)| cached_at.elapsed() < self.config.discovery_cache_ttl)
+            .map(|(peer, _)| peer.clone())
+    }
+
+    fn insert_cached_discovery(&mut self, public_key: &CommsPublicKey, peer: Peer) {
+        // Take this opportunity to clear expired entries
+        let ttl = self.config.discovery_cache_ttl;
+        self.discovery_cache
+            .retain(|_, (_, cached_at)| cached_at.elapsed() < ttl);
+        self.discovery_cache.insert(public_key.clone(), (peer, Instant::now()));
+    }
+
     fn collect_all_discovery_requests(&mut self, public_key: &CommsPublicKey) -> Vec<DiscoveryRequestState> {
         let mut requests = Vec::new();
         let mut remaining_requests = HashMap::new();
@@ -199,6 +225,8 @@ impl DhtDiscoveryService {
                             (Instant::now() - start_ts).as_secs_f32()
                         );
 
+                        self.insert_cached_discovery(&public_key, peer.clone());
+
                         for request in self.collect_all_discovery_requests(&public_key) {
                             if !reply_tx.is_canceled() {
                                 let _ = request.reply_tx.send(Ok(peer.clone()));
@@ -288,11 +316,15 @@ impl DhtDiscoveryService {
         &mut self,
         dest_pubkey: Box<CommsPublicKey>,
         destination: NodeDestination,
+        fan_out: Option<usize>,
         reply_tx: oneshot::Sender<Result<Peer, DhtDiscoveryError>>,
     ) -> Result<(), DhtDiscoveryError>
     {
         let nonce = OsRng.next_u64();
-        if let Err(err) = self.send_discover(nonce, destination, dest_pubkey.clone()).await {
+        if let Err(err) = self
+            .send_discover(nonce, destination, dest_pubkey.clone(), fan_out)
+            .await
+        {
             let _ = reply_tx.send(Err(err));
             return Ok(());
         }
@@ -330,6 +362,7 @@ impl DhtDiscoveryService {
         nonce: u64,
         destination: NodeDestination,
         dest_public_key: Box<CommsPublicKey>,
+        fan_out: Option<usize>,
     ) -> Result<(), DhtDiscoveryError>
     {
         let discover_msg = DiscoveryMessage {
@@ -343,14 +376,20 @@ impl DhtDiscoveryService {
             "Sending Discovery message for peer public key '{}' with destination {}", dest_public_key, destination
         );
 
+        let mut params = SendMessageParams::new();
+        match fan_out {
+            Some(n) => params.random_connected(n, Vec::new()),
+            None => params.broadcast(Vec::new()),
+        };
+
         let send_states = self
             .outbound_requester
             .send_message_no_header(
-                SendMessageParams::new()
-                    .broadcast(Vec::new())
+                params
                     .with_destination(destination)
                     .with_encryption(OutboundEncryption::EncryptFor(dest_public_key))
                     .with_dht_message_type(DhtMessageType::Discovery)
+                    .with_priority(MessagePriority::Control)
                     .finish(),
                 discover_msg,
             )
@@ -393,7 +432,8 @@ impl DhtDiscoveryService {
 mod test {
     use super::*;
     use crate::{
-        discovery::DhtDiscoveryRequester,
+        broadcast_strategy::BroadcastStrategy,
+        discovery::{DhtDiscoveryRequester, DiscoveryOptions},
         outbound::mock::create_outbound_service_mock,
         test_utils::{build_peer_manager, make_node_identity},
     };
@@ -438,4 +478,111 @@ mod test {
         assert_eq!(params.dht_message_type, DhtMessageType::Discovery);
         assert_eq!(params.encryption, OutboundEncryption::EncryptFor(dest_public_key));
     }
+
+    #[tokio_macros::test_basic]
+    async fn discovery_options_override_config() {
+        let node_identity = make_node_identity();
+        let peer_manager = build_peer_manager();
+        let (outbound_requester, outbound_mock) = create_outbound_service_mock(10);
+        let oms_mock_state = outbound_mock.get_state();
+        task::spawn(outbound_mock.run());
+
+        let (sender, receiver) = mpsc::channel(10);
+        // Requester which would wait a long time by default
+        let mut requester = DhtDiscoveryRequester::new(sender, Duration::from_secs(60));
+        let shutdown = Shutdown::new();
+
+        DhtDiscoveryService::new(
+            DhtConfig::default(),
+            node_identity,
+            peer_manager,
+            outbound_requester,
+            receiver,
+            shutdown.to_signal(),
+        )
+        .spawn();
+
+        let dest_public_key = Box::new(CommsPublicKey::default());
+        let discovery = requester.discover_peer_with_options(
+            dest_public_key.clone(),
+            NodeDestination::PublicKey(dest_public_key),
+            DiscoveryOptions::default()
+                .with_timeout(Duration::from_millis(10))
+                .with_fan_out(20),
+        );
+        let result = time::timeout(Duration::from_secs(5), discovery)
+            .await
+            .expect("per-call timeout was not honoured");
+
+        assert!(result.unwrap_err().is_timeout());
+
+        oms_mock_state.wait_call_count(1, Duration::from_secs(5)).unwrap();
+        let (params, _) = oms_mock_state.pop_call().unwrap();
+        assert_eq!(params.dht_message_type, DhtMessageType::Discovery);
+        match params.broadcast_strategy {
+            BroadcastStrategy::RandomConnected(n, _) => assert_eq!(n, 20),
+            s => panic!("Unexpected broadcast strategy {}", s),
+        }
+    }
+
+    #[tokio_macros::test_basic]
+    async fn discovery_result_is_cached() {
+        let node_identity = make_node_identity();
+        let peer_manager = build_peer_manager();
+        let (outbound_requester, outbound_mock) = create_outbound_service_mock(10);
+        let oms_mock_state = outbound_mock.get_state();
+        task::spawn(outbound_mock.run());
+
+        let (_sender, receiver) = mpsc::channel(10);
+        let shutdown = Shutdown::new();
+        let mut service = DhtDiscoveryService::new(
+            DhtConfig::default_local_test(),
+            node_identity,
+            peer_manager,
+            outbound_requester,
+            receiver,
+            shutdown.to_signal(),
+        );
+
+        let dest_identity = make_node_identity();
+        let dest_public_key = Box::new(dest_identity.public_key().clone());
+        let (reply_tx, reply_rx) = oneshot::channel();
+        service
+            .handle_request(DhtDiscoveryRequest::DiscoverPeer(
+                dest_public_key.clone(),
+                NodeDestination::Unknown,
+                Default::default(),
+                reply_tx,
+            ))
+            .await;
+        oms_mock_state.wait_call_count(1, Duration::from_secs(5)).unwrap();
+
+        let nonce = *service.inflight_discoveries.keys().next().unwrap();
+        service
+            .handle_request(DhtDiscoveryRequest::NotifyDiscoveryResponseReceived(Box::new(
+                DiscoveryResponseMessage {
+                    node_id: dest_identity.node_id().to_vec(),
+                    addresses: vec![dest_identity.public_address().to_string()],
+                    peer_features: dest_identity.features().bits(),
+                    nonce,
+                },
+            )))
+            .await;
+        let peer = reply_rx.await.unwrap().unwrap();
+        assert_eq!(&peer.node_id, dest_identity.node_id());
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        service
+            .handle_request(DhtDiscoveryRequest::DiscoverPeer(
+                dest_public_key,
+                NodeDestination::Unknown,
+                Default::default(),
+                reply_tx,
+            ))
+            .await;
+        let peer = reply_rx.await.unwrap().unwrap();
+        assert_eq!(&peer.node_id, dest_identity.node_id());
+        assert!(service.inflight_discoveries.is_empty());
+        assert_eq!(oms_mock_state.call_count(), 1);
+    }
 }
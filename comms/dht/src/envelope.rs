@@ -139,6 +139,8 @@ pub struct DhtMessageHeader {
     pub flags: DhtMessageFlags,
     pub message_tag: MessageTag,
     pub expires: Option<EpochTime>,
+    /// The time that the message was created by the origin
+    pub timestamp: Option<EpochTime>,
 }
 
 impl DhtMessageHeader {
@@ -182,6 +184,7 @@ impl TryFrom<DhtHeader> for DhtMessageHeader {
         };
 
         let expires: Option<DateTime<Utc>> = header.expires.map(timestamp_to_datetime);
+        let timestamp: Option<DateTime<Utc>> = header.timestamp.map(timestamp_to_datetime);
 
         Ok(Self {
             version: header.version,
@@ -194,6 +197,7 @@ impl TryFrom<DhtHeader> for DhtMessageHeader {
             flags: DhtMessageFlags::from_bits(header.flags).ok_or_else(|| DhtMessageError::InvalidMessageFlags)?,
             message_tag: MessageTag::from(header.message_tag),
             expires: expires.map(datetime_to_epochtime),
+            timestamp: timestamp.map(datetime_to_epochtime),
         })
     }
 }
@@ -212,6 +216,7 @@ impl TryFrom<Option<DhtHeader>> for DhtMessageHeader {
 impl From<DhtMessageHeader> for DhtHeader {
     fn from(header: DhtMessageHeader) -> Self {
         let expires = header.expires.map(epochtime_to_datetime);
+        let timestamp = header.timestamp.map(epochtime_to_datetime);
         Self {
             version: header.version,
             ephemeral_public_key: header
@@ -226,6 +231,7 @@ impl From<DhtMessageHeader> for DhtHeader {
             flags: header.flags.bits(),
             message_tag: header.message_tag.as_value(),
             expires: expires.map(datetime_to_timestamp),
+            timestamp: timestamp.map(datetime_to_timestamp),
         }
     }
 }
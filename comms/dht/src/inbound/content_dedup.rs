@@ -0,0 +1,219 @@
+// Copyright 2021, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{actor::DhtRequester, domain_message::MessageHeader, inbound::DecryptedDhtMessage};
+use digest::Input;
+use futures::{task::Context, Future};
+use log::*;
+use std::{sync::Arc, task::Poll};
+use tari_comms::{message::EnvelopeBody, pipeline::PipelineError, types::Challenge};
+use tower::{layer::Layer, Service, ServiceExt};
+
+const LOG_TARGET: &str = "comms::dht::content_dedup";
+
+/// Returns the message type and content hash of the message body if the message type is one of the given
+/// `message_types`. The message header is excluded from the hash as it contains a nonce that differs between otherwise
+/// identical messages.
+fn hash_message_content(body: &EnvelopeBody, message_types: &[i32]) -> Option<(i32, Vec<u8>)> {
+    let header = body.decode_part::<MessageHeader>(0).ok().flatten()?;
+    if !message_types.contains(&header.message_type) {
+        return None;
+    }
+
+    let hash = body
+        .parts
+        .iter()
+        .skip(1)
+        .fold(
+            Challenge::new()
+                .chain(b"comms.dht.content_dedup")
+                .chain(header.message_type.to_le_bytes()),
+            |hasher, part| hasher.chain(part),
+        )
+        .result()
+        .to_vec();
+    Some((header.message_type, hash))
+}
+
+/// # DHT content deduplication middleware
+///
+/// Takes in a `DecryptedDhtMessage` and, for the opted-in message types, discards the message if a message with the
+/// same content has already been seen. Unlike the `DedupLayer`, which discards identical envelope bodies, this
+/// discards functionally identical messages that were wrapped in different envelopes. Messages of other types and
+/// messages that could not be decrypted are passed on unchecked.
+#[derive(Clone)]
+pub struct ContentDedupMiddleware<S> {
+    next_service: S,
+    dht_requester: DhtRequester,
+    message_types: Arc<Vec<i32>>,
+}
+
+impl<S> ContentDedupMiddleware<S> {
+    pub fn new(service: S, dht_requester: DhtRequester, message_types: Arc<Vec<i32>>) -> Self {
+        Self {
+            next_service: service,
+            dht_requester,
+            message_types,
+        }
+    }
+}
+
+impl<S> Service<DecryptedDhtMessage> for ContentDedupMiddleware<S>
+where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError> + Clone + 'static
+{
+    type Error = PipelineError;
+    type Response = ();
+
+    type Future = impl Future<Output = Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, message: DecryptedDhtMessage) -> Self::Future {
+        let next_service = self.next_service.clone();
+        let mut dht_requester = self.dht_requester.clone();
+        let message_types = self.message_types.clone();
+        async move {
+            let content_hash = message
+                .success()
+                .and_then(|body| hash_message_content(body, &message_types));
+
+            if let Some((message_type, hash)) = content_hash {
+                if dht_requester.insert_message_hash(hash).await? {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Received message {} (type = {}) from peer '{}' with duplicate content (Trace: {}). Message \
+                         discarded.",
+                        message.tag,
+                        message_type,
+                        message.source_peer.node_id.short_str(),
+                        message.dht_header.message_tag,
+                    );
+                    return Ok(());
+                }
+            }
+
+            trace!(
+                target: LOG_TARGET,
+                "Passing message {} to next service (Trace: {})",
+                message.tag,
+                message.dht_header.message_tag
+            );
+            next_service.oneshot(message).await
+        }
+    }
+}
+
+pub struct ContentDedupLayer {
+    dht_requester: DhtRequester,
+    message_types: Arc<Vec<i32>>,
+}
+
+impl ContentDedupLayer {
+    pub fn new(dht_requester: DhtRequester, message_types: Vec<i32>) -> Self {
+        Self {
+            dht_requester,
+            message_types: Arc::new(message_types),
+        }
+    }
+}
+
+impl<S> Layer<S> for ContentDedupLayer {
+    type Service = ContentDedupMiddleware<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        ContentDedupMiddleware::new(service, self.dht_requester.clone(), self.message_types.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        actor::DhtActor,
+        envelope::DhtMessageFlags,
+        outbound::OutboundMessageRequester,
+        storage::DbConnection,
+        test_utils::{build_peer_manager, make_dht_inbound_message, make_node_identity, service_spy},
+    };
+    use futures::channel::mpsc;
+    use tari_comms::{test_utils::mocks::create_connectivity_mock, wrap_in_envelope_body};
+    use tari_shutdown::Shutdown;
+    use tari_test_utils::random;
+
+    const OPTED_IN_TYPE: i32 = 123;
+    const OTHER_TYPE: i32 = 456;
+
+    async fn spawn_dht_actor(shutdown: &Shutdown) -> DhtRequester {
+        let conn = DbConnection::connect_memory(random::string(8)).await.unwrap();
+        conn.migrate().await.unwrap();
+        let (connectivity, mock) = create_connectivity_mock();
+        mock.spawn();
+        let (out_tx, _) = mpsc::channel(1);
+        let (actor_tx, actor_rx) = mpsc::channel(10);
+        DhtActor::new(
+            Default::default(),
+            conn,
+            make_node_identity(),
+            build_peer_manager(),
+            connectivity,
+            OutboundMessageRequester::new(out_tx),
+            actor_rx,
+            shutdown.to_signal(),
+        )
+        .spawn();
+        DhtRequester::new(actor_tx)
+    }
+
+    fn make_message(message_type: i32, payload: &[u8]) -> DecryptedDhtMessage {
+        // Each message has a new random header nonce, message tag and envelope
+        let node_identity = make_node_identity();
+        let body = wrap_in_envelope_body!(MessageHeader::new(message_type), payload.to_vec());
+        let inbound_msg = make_dht_inbound_message(&node_identity, Vec::new(), DhtMessageFlags::empty(), false);
+        DecryptedDhtMessage::succeeded(body, None, inbound_msg)
+    }
+
+    #[tokio_macros::test_basic]
+    async fn discards_messages_with_duplicate_content() {
+        let shutdown = Shutdown::new();
+        let dht_requester = spawn_dht_actor(&shutdown).await;
+        let spy = service_spy();
+        let mut content_dedup =
+            ContentDedupLayer::new(dht_requester, vec![OPTED_IN_TYPE]).layer(spy.to_service::<PipelineError>());
+
+        content_dedup.call(make_message(OPTED_IN_TYPE, b"block")).await.unwrap();
+        content_dedup.call(make_message(OPTED_IN_TYPE, b"block")).await.unwrap();
+        assert_eq!(spy.call_count(), 1);
+
+        content_dedup
+            .call(make_message(OPTED_IN_TYPE, b"other block"))
+            .await
+            .unwrap();
+        assert_eq!(spy.call_count(), 2);
+
+        // Message types that have not opted in are not deduped by content
+        content_dedup.call(make_message(OTHER_TYPE, b"block")).await.unwrap();
+        content_dedup.call(make_message(OTHER_TYPE, b"block")).await.unwrap();
+        assert_eq!(spy.call_count(), 4);
+    }
+}
@@ -39,8 +39,9 @@ use tari_comms::{
     types::CommsPublicKey,
     utils::signature,
 };
-use tari_utilities::ByteArray;
+use tari_utilities::{epoch_time::EpochTime, ByteArray};
 use thiserror::Error;
+use tokio::{sync::Semaphore, task};
 use tower::{layer::Layer, Service, ServiceExt};
 
 const LOG_TARGET: &str = "comms::middleware::decryption";
@@ -103,6 +104,7 @@ pub struct DecryptionService<S> {
     config: DhtConfig,
     node_identity: Arc<NodeIdentity>,
     connectivity: ConnectivityRequester,
+    decryption_permits: Arc<Semaphore>,
     inner: S,
 }
 
@@ -117,6 +119,7 @@ impl<S> DecryptionService<S> {
         Self {
             node_identity,
             connectivity,
+            decryption_permits: Arc::new(Semaphore::new(config.decryption_pool_size)),
             config,
             inner: service,
         }
@@ -140,6 +143,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError> + Cl
             self.inner.clone(),
             Arc::clone(&self.node_identity),
             self.connectivity.clone(),
+            self.decryption_permits.clone(),
             self.config.ban_duration,
             msg,
         )
@@ -153,6 +157,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         next_service: S,
         node_identity: Arc<NodeIdentity>,
         mut connectivity: ConnectivityRequester,
+        decryption_permits: Arc<Semaphore>,
         ban_duration: Duration,
         message: DhtInboundMessage,
     ) -> Result<(), PipelineError>
@@ -161,7 +166,13 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         let source = message.source_peer.clone();
         let trace_id = message.dht_header.message_tag;
         let tag = message.tag;
-        match Self::validate_and_decrypt_message(node_identity, message).await {
+        let result = {
+            // Decryption is CPU-bound, so it is done on the blocking thread pool to avoid starving other async tasks.
+            // The number of messages decrypted at once is limited to the configured pool size.
+            let _permit = decryption_permits.acquire().await;
+            task::spawn_blocking(move || validate_and_decrypt_message(node_identity, message)).await?
+        };
+        match result {
             Ok(msg) => next_service.oneshot(msg).await,
 
             Err(err @ OriginMacNotProvided) |
@@ -187,209 +198,229 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
             Err(err) => Err(err.into()),
         }
     }
+}
 
-    async fn validate_and_decrypt_message(
-        node_identity: Arc<NodeIdentity>,
-        message: DhtInboundMessage,
-    ) -> Result<DecryptedDhtMessage, DecryptionError>
-    {
-        let dht_header = &message.dht_header;
-
-        if !dht_header.flags.contains(DhtMessageFlags::ENCRYPTED) {
-            return Self::success_not_encrypted(message).await;
-        }
-        trace!(
-            target: LOG_TARGET,
-            "Decrypting message {} (Trace: {})",
-            message.tag,
-            message.dht_header.message_tag
-        );
+fn validate_and_decrypt_message(
+    node_identity: Arc<NodeIdentity>,
+    message: DhtInboundMessage,
+) -> Result<DecryptedDhtMessage, DecryptionError>
+{
+    let dht_header = &message.dht_header;
 
-        let e_pk = dht_header
-            .ephemeral_public_key
-            .as_ref()
-            // No ephemeral key with ENCRYPTED flag set
-            .ok_or_else(|| DecryptionError::EphemeralKeyNotProvided)?;
-
-        let shared_secret = crypt::generate_ecdh_secret(node_identity.secret_key(), e_pk);
-
-        // Decrypt and verify the origin
-        let authenticated_origin = match Self::attempt_decrypt_origin_mac(&shared_secret, dht_header) {
-            Ok((public_key, signature)) => {
-                // If this fails, discard the message because we decrypted and deserialized the message with our shared
-                // ECDH secret but the message could not be authenticated
-                Self::authenticate_origin_mac(&public_key, &signature, &message.body)?;
-                public_key
-            },
-            Err(err) => {
-                trace!(
+    if !dht_header.flags.contains(DhtMessageFlags::ENCRYPTED) {
+        return success_not_encrypted(message);
+    }
+    trace!(
+        target: LOG_TARGET,
+        "Decrypting message {} (Trace: {})",
+        message.tag,
+        message.dht_header.message_tag
+    );
+
+    let e_pk = dht_header
+        .ephemeral_public_key
+        .as_ref()
+        // No ephemeral key with ENCRYPTED flag set
+        .ok_or_else(|| DecryptionError::EphemeralKeyNotProvided)?;
+
+    let shared_secret = crypt::generate_ecdh_secret(node_identity.secret_key(), e_pk);
+
+    // Decrypt and verify the origin
+    let authenticated_origin = match attempt_decrypt_origin_mac(&shared_secret, dht_header) {
+        Ok((public_key, origin_mac)) => {
+            // If this fails, discard the message because we decrypted and deserialized the message with our shared
+            // ECDH secret but the message could not be authenticated
+            authenticate_origin_mac(&public_key, &origin_mac, dht_header.timestamp, &message.body)?;
+            public_key
+        },
+        Err(err) => {
+            trace!(
+                target: LOG_TARGET,
+                "Unable to decrypt message origin: {}, {} (Trace: {})",
+                err,
+                message.tag,
+                message.dht_header.message_tag
+            );
+            if message.dht_header.destination.equals_node_identity(&node_identity) {
+                warn!(
                     target: LOG_TARGET,
-                    "Unable to decrypt message origin: {}, {} (Trace: {})",
-                    err,
+                    "Received message from peer '{}' that is destined for this node that could not be decrypted. \
+                     Discarding message {} (Trace: {})",
+                    message.source_peer.node_id,
                     message.tag,
                     message.dht_header.message_tag
                 );
-                if message.dht_header.destination.equals_node_identity(&node_identity) {
-                    warn!(
-                        target: LOG_TARGET,
-                        "Received message from peer '{}' that is destined for this node that could not be decrypted. \
-                         Discarding message {} (Trace: {})",
-                        message.source_peer.node_id,
-                        message.tag,
-                        message.dht_header.message_tag
-                    );
-                    return Err(DecryptionError::OriginMacDecryptedFailed);
-                }
-                return Ok(DecryptedDhtMessage::failed(message));
-            },
-        };
+                return Err(DecryptionError::OriginMacDecryptedFailed);
+            }
+            return Ok(DecryptedDhtMessage::failed(message));
+        },
+    };
 
-        trace!(
-            target: LOG_TARGET,
-            "Attempting to decrypt message body from origin public key '{}', {} (Trace: {})",
-            authenticated_origin,
-            message.tag,
-            message.dht_header.message_tag
-        );
-        match Self::attempt_decrypt_message_body(&shared_secret, &message.body) {
-            Ok(message_body) => {
-                debug!(
-                    target: LOG_TARGET,
-                    "Message successfully decrypted, {} (Trace: {})", message.tag, message.dht_header.message_tag
-                );
-                Ok(DecryptedDhtMessage::succeeded(
-                    message_body,
-                    Some(authenticated_origin),
-                    message,
-                ))
-            },
-            Err(err) => {
-                debug!(
+    trace!(
+        target: LOG_TARGET,
+        "Attempting to decrypt message body from origin public key '{}', {} (Trace: {})",
+        authenticated_origin,
+        message.tag,
+        message.dht_header.message_tag
+    );
+    match attempt_decrypt_message_body(&shared_secret, &message.body) {
+        Ok(message_body) => {
+            debug!(
+                target: LOG_TARGET,
+                "Message successfully decrypted, {} (Trace: {})", message.tag, message.dht_header.message_tag
+            );
+            Ok(DecryptedDhtMessage::succeeded(
+                message_body,
+                Some(authenticated_origin),
+                message,
+            ))
+        },
+        Err(err) => {
+            debug!(
+                target: LOG_TARGET,
+                "Unable to decrypt message: {}, {} (Trace: {})", err, message.tag, message.dht_header.message_tag
+            );
+
+            if message.dht_header.destination.equals_node_identity(&node_identity) {
+                warn!(
                     target: LOG_TARGET,
-                    "Unable to decrypt message: {}, {} (Trace: {})", err, message.tag, message.dht_header.message_tag
+                    "Received message from peer '{}' that is destined for this node that could not be decrypted. \
+                     Discarding message {} (Trace: {})",
+                    message.source_peer.node_id,
+                    message.tag,
+                    message.dht_header.message_tag
                 );
+                return Err(DecryptionError::MessageRejectDecryptionFailed);
+            }
 
-                if message.dht_header.destination.equals_node_identity(&node_identity) {
-                    warn!(
-                        target: LOG_TARGET,
-                        "Received message from peer '{}' that is destined for this node that could not be decrypted. \
-                         Discarding message {} (Trace: {})",
-                        message.source_peer.node_id,
-                        message.tag,
-                        message.dht_header.message_tag
-                    );
-                    return Err(DecryptionError::MessageRejectDecryptionFailed);
-                }
-
-                Ok(DecryptedDhtMessage::failed(message))
-            },
-        }
+            Ok(DecryptedDhtMessage::failed(message))
+        },
     }
+}
 
-    fn attempt_decrypt_origin_mac(
-        shared_secret: &CommsPublicKey,
-        dht_header: &DhtMessageHeader,
-    ) -> Result<(CommsPublicKey, Vec<u8>), DecryptionError>
-    {
-        let encrypted_origin_mac = Some(&dht_header.origin_mac)
-            .filter(|b| !b.is_empty())
-            // This should not have been sent/propagated
-            .ok_or_else(|| DecryptionError::OriginMacNotProvided)?;
-
-        let decrypted_bytes = crypt::decrypt(shared_secret, encrypted_origin_mac)
-            .map_err(|_| DecryptionError::OriginMacDecryptedFailed)?;
-        let origin_mac =
-            OriginMac::decode(decrypted_bytes.as_slice()).map_err(|_| DecryptionError::OriginMacDecryptedFailed)?;
-        // Check the public key here, because it is possible (rare but possible) for an failed decrypted message to pass
-        // protobuf decoding of the relatively simple OriginMac struct but with invalid data
-        let public_key = CommsPublicKey::from_bytes(&origin_mac.public_key)
-            .map_err(|_| DecryptionError::OriginMacInvalidPublicKey)?;
-        Ok((public_key, origin_mac.signature))
-    }
+fn attempt_decrypt_origin_mac(
+    shared_secret: &CommsPublicKey,
+    dht_header: &DhtMessageHeader,
+) -> Result<(CommsPublicKey, OriginMac), DecryptionError>
+{
+    let encrypted_origin_mac = Some(&dht_header.origin_mac)
+        .filter(|b| !b.is_empty())
+        // This should not have been sent/propagated
+        .ok_or_else(|| DecryptionError::OriginMacNotProvided)?;
+
+    let decrypted_bytes =
+        crypt::decrypt(shared_secret, encrypted_origin_mac).map_err(|_| DecryptionError::OriginMacDecryptedFailed)?;
+    let origin_mac =
+        OriginMac::decode(decrypted_bytes.as_slice()).map_err(|_| DecryptionError::OriginMacDecryptedFailed)?;
+    // Check the public key here, because it is possible (rare but possible) for an failed decrypted message to pass
+    // protobuf decoding of the relatively simple OriginMac struct but with invalid data
+    let public_key =
+        CommsPublicKey::from_bytes(&origin_mac.public_key).map_err(|_| DecryptionError::OriginMacInvalidPublicKey)?;
+    Ok((public_key, origin_mac))
+}
 
-    fn authenticate_origin_mac(
-        public_key: &CommsPublicKey,
-        signature: &[u8],
-        body: &[u8],
-    ) -> Result<(), DecryptionError>
-    {
-        if signature::verify(public_key, signature, body) {
-            Ok(())
-        } else {
-            Err(DecryptionError::OriginMacInvalidSignature)
-        }
+/// Verifies the origin MAC signature of the message body and, if the message has a timestamp, the signature of the
+/// body and timestamp. An origin MAC that signs a timestamp is rejected if the timestamp was removed from the message,
+/// because that would let a replayed message pass the maximum message age check.
+fn authenticate_origin_mac(
+    public_key: &CommsPublicKey,
+    origin_mac: &OriginMac,
+    timestamp: Option<EpochTime>,
+    body: &[u8],
+) -> Result<(), DecryptionError>
+{
+    if !signature::verify(public_key, &origin_mac.signature, body) {
+        return Err(DecryptionError::OriginMacInvalidSignature);
     }
-
-    fn attempt_decrypt_message_body(
-        shared_secret: &CommsPublicKey,
-        message_body: &[u8],
-    ) -> Result<EnvelopeBody, DecryptionError>
-    {
-        let decrypted =
-            crypt::decrypt(shared_secret, message_body).map_err(|_| DecryptionError::MessageBodyDecryptionFailed)?;
-        // Deserialization into an EnvelopeBody is done here to determine if the
-        // decryption produced valid bytes or not.
-        EnvelopeBody::decode(decrypted.as_slice())
-            .and_then(|body| {
-                // Check if we received a body length of zero
-                //
-                // In addition to a peer sending a zero-length EnvelopeBody, decoding can erroneously succeed
-                // if the decrypted bytes happen to be valid protobuf encoding. This is very possible and
-                // the decrypt_inbound_fail test below _will_ sporadically fail without the following check.
-                // This is because proto3 will set fields to their default value if they don't exist in a valid
-                // encoding.
-                //
-                // For the parts of EnvelopeBody to be erroneously populated with bytes, all of these
-                // conditions would have to be true:
-                // 1. field type == 2 (length-delimited)
-                // 2. field number == 1
-                // 3. the subsequent byte(s) would have to be varint-encoded length which does not overflow
-                // 4. the rest of the bytes would have to be valid protobuf encoding
-                //
-                // The chance of this happening is extremely negligible.
-                if body.is_empty() {
-                    return Err(prost::DecodeError::new("EnvelopeBody has no parts"));
-                }
-                Ok(body)
-            })
-            .map_err(|_| DecryptionError::MessageBodyDecryptionFailed)
+    match timestamp {
+        Some(timestamp) => {
+            let challenge = crypt::create_origin_mac_timestamp_challenge(timestamp, body);
+            if !signature::verify(public_key, &origin_mac.timestamp_signature, &challenge) {
+                return Err(DecryptionError::OriginMacInvalidSignature);
+            }
+        },
+        None if !origin_mac.timestamp_signature.is_empty() => {
+            return Err(DecryptionError::OriginMacInvalidSignature);
+        },
+        None => {},
     }
+    Ok(())
+}
 
-    async fn success_not_encrypted(message: DhtInboundMessage) -> Result<DecryptedDhtMessage, DecryptionError> {
-        let authenticated_pk = if message.dht_header.origin_mac.is_empty() {
-            None
-        } else {
-            let origin_mac = OriginMac::decode(message.dht_header.origin_mac.as_slice())
-                .map_err(|_| DecryptionError::OriginMacClearTextDecodeFailed)?;
-            let public_key = CommsPublicKey::from_bytes(&origin_mac.public_key)
-                .map_err(|_| DecryptionError::OriginMacInvalidPublicKey)?;
-            Self::authenticate_origin_mac(&public_key, &origin_mac.signature, &message.body)?;
-            Some(public_key)
-        };
+fn attempt_decrypt_message_body(
+    shared_secret: &CommsPublicKey,
+    message_body: &[u8],
+) -> Result<EnvelopeBody, DecryptionError>
+{
+    let decrypted =
+        crypt::decrypt(shared_secret, message_body).map_err(|_| DecryptionError::MessageBodyDecryptionFailed)?;
+    // Deserialization into an EnvelopeBody is done here to determine if the
+    // decryption produced valid bytes or not.
+    EnvelopeBody::decode(decrypted.as_slice())
+        .and_then(|body| {
+            // Check if we received a body length of zero
+            //
+            // In addition to a peer sending a zero-length EnvelopeBody, decoding can erroneously succeed
+            // if the decrypted bytes happen to be valid protobuf encoding. This is very possible and
+            // the decrypt_inbound_fail test below _will_ sporadically fail without the following check.
+            // This is because proto3 will set fields to their default value if they don't exist in a valid
+            // encoding.
+            //
+            // For the parts of EnvelopeBody to be erroneously populated with bytes, all of these
+            // conditions would have to be true:
+            // 1. field type == 2 (length-delimited)
+            // 2. field number == 1
+            // 3. the subsequent byte(s) would have to be varint-encoded length which does not overflow
+            // 4. the rest of the bytes would have to be valid protobuf encoding
+            //
+            // The chance of this happening is extremely negligible.
+            if body.is_empty() {
+                return Err(prost::DecodeError::new("EnvelopeBody has no parts"));
+            }
+            Ok(body)
+        })
+        .map_err(|_| DecryptionError::MessageBodyDecryptionFailed)
+}
 
-        match EnvelopeBody::decode(message.body.as_slice()) {
-            Ok(deserialized) => {
-                trace!(
-                    target: LOG_TARGET,
-                    "Message {} is not encrypted. Passing onto next service (Trace: {})",
-                    message.tag,
-                    message.dht_header.message_tag
-                );
-                Ok(DecryptedDhtMessage::succeeded(deserialized, authenticated_pk, message))
-            },
-            Err(err) => {
-                // Message was not encrypted but failed to deserialize - immediately discard
-                // TODO: Bad node behaviour?
-                debug!(
-                    target: LOG_TARGET,
-                    "Unable to deserialize message {}: {}. Message will be discarded. (Trace: {})",
-                    message.tag,
-                    err,
-                    message.dht_header.message_tag
-                );
-                Err(DecryptionError::EnvelopeBodyDecodeFailed)
-            },
-        }
+fn success_not_encrypted(message: DhtInboundMessage) -> Result<DecryptedDhtMessage, DecryptionError> {
+    let authenticated_pk = if message.dht_header.origin_mac.is_empty() {
+        None
+    } else {
+        let origin_mac = OriginMac::decode(message.dht_header.origin_mac.as_slice())
+            .map_err(|_| DecryptionError::OriginMacClearTextDecodeFailed)?;
+        let public_key = CommsPublicKey::from_bytes(&origin_mac.public_key)
+            .map_err(|_| DecryptionError::OriginMacInvalidPublicKey)?;
+        authenticate_origin_mac(
+            &public_key,
+            &origin_mac,
+            message.dht_header.timestamp,
+            &message.body,
+        )?;
+        Some(public_key)
+    };
+
+    match EnvelopeBody::decode(message.body.as_slice()) {
+        Ok(deserialized) => {
+            trace!(
+                target: LOG_TARGET,
+                "Message {} is not encrypted. Passing onto next service (Trace: {})",
+                message.tag,
+                message.dht_header.message_tag
+            );
+            Ok(DecryptedDhtMessage::succeeded(deserialized, authenticated_pk, message))
+        },
+        Err(err) => {
+            // Message was not encrypted but failed to deserialize - immediately discard
+            // TODO: Bad node behaviour?
+            debug!(
+                target: LOG_TARGET,
+                "Unable to deserialize message {}: {}. Message will be discarded. (Trace: {})",
+                message.tag,
+                err,
+                message.dht_header.message_tag
+            );
+            Err(DecryptionError::EnvelopeBodyDecodeFailed)
+        },
     }
 }
 
@@ -398,12 +429,17 @@ mod test {
     use super::*;
     use crate::{
         envelope::DhtMessageFlags,
-        test_utils::{make_dht_inbound_message, make_node_identity},
+        test_utils::{make_dht_inbound_message, make_keypair, make_node_identity, make_valid_origin_mac},
+    };
+    use futures::future;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
     };
-    use futures::{executor::block_on, future};
-    use std::sync::Mutex;
     use tari_comms::{message::MessageExt, test_utils::mocks::create_connectivity_mock, wrap_in_envelope_body};
     use tari_test_utils::{counter_context, unpack_enum};
+    use tari_utilities::epoch_time::EpochTime;
+    use tokio::time;
     use tower::service_fn;
 
     #[test]
@@ -420,8 +456,8 @@ mod test {
         assert_eq!(counter.get(), 0);
     }
 
-    #[test]
-    fn decrypt_inbound_success() {
+    #[tokio_macros::test_basic]
+    async fn decrypt_inbound_success() {
         let result = Mutex::new(None);
         let service = service_fn(|msg: DecryptedDhtMessage| {
             *result.lock().unwrap() = Some(msg);
@@ -439,14 +475,14 @@ mod test {
             true,
         );
 
-        block_on(service.call(inbound_msg)).unwrap();
+        service.call(inbound_msg).await.unwrap();
         let decrypted = result.lock().unwrap().take().unwrap();
         assert_eq!(decrypted.decryption_succeeded(), true);
         assert_eq!(decrypted.decryption_result.unwrap(), plain_text_msg);
     }
 
-    #[test]
-    fn decrypt_inbound_fail() {
+    #[tokio_macros::test_basic]
+    async fn decrypt_inbound_fail() {
         let result = Mutex::new(None);
         let service = service_fn(|msg: DecryptedDhtMessage| {
             *result.lock().unwrap() = Some(msg);
@@ -461,7 +497,7 @@ mod test {
         let inbound_msg =
             make_dht_inbound_message(&some_other_node_identity, some_secret, DhtMessageFlags::ENCRYPTED, true);
 
-        block_on(service.call(inbound_msg.clone())).unwrap();
+        service.call(inbound_msg.clone()).await.unwrap();
         let decrypted = result.lock().unwrap().take().unwrap();
 
         assert_eq!(decrypted.decryption_succeeded(), false);
@@ -490,4 +526,126 @@ mod test {
         unpack_enum!(DecryptionError::MessageRejectDecryptionFailed = err);
         assert!(result.lock().unwrap().is_none());
     }
+
+    #[tokio_macros::test_basic]
+    async fn decrypt_inbound_fail_changed_timestamp() {
+        let (connectivity, mock) = create_connectivity_mock();
+        let mock_state = mock.get_shared_state();
+        mock.spawn();
+        let result = Mutex::new(None);
+        let service = service_fn(|msg: DecryptedDhtMessage| {
+            *result.lock().unwrap() = Some(msg);
+            future::ready(Result::<(), PipelineError>::Ok(()))
+        });
+        let node_identity = make_node_identity();
+        let mut service = DecryptionService::new(Default::default(), node_identity.clone(), connectivity, service);
+
+        let plain_text_msg = wrap_in_envelope_body!(b"Old news".to_vec());
+        let mut inbound_msg = make_dht_inbound_message(
+            &node_identity,
+            plain_text_msg.to_encoded_bytes(),
+            DhtMessageFlags::empty(),
+            true,
+        );
+        let timestamp = inbound_msg.dht_header.timestamp.unwrap();
+        inbound_msg.dht_header.timestamp = Some(EpochTime::from(timestamp.as_u64() + 60));
+
+        let err = service.call(inbound_msg).await.unwrap_err();
+        let err = err.downcast::<DecryptionError>().unwrap();
+        unpack_enum!(DecryptionError::OriginMacInvalidSignature = err);
+        assert!(result.lock().unwrap().is_none());
+        let calls = mock_state.take_calls().await;
+        assert!(calls.iter().any(|call| call.contains("BanPeer")));
+    }
+
+    #[tokio_macros::test_basic]
+    async fn decrypt_inbound_replay_with_stripped_timestamp() {
+        let (connectivity, mock) = create_connectivity_mock();
+        let mock_state = mock.get_shared_state();
+        mock.spawn();
+        let result = Mutex::new(None);
+        let service = service_fn(|msg: DecryptedDhtMessage| {
+            *result.lock().unwrap() = Some(msg);
+            future::ready(Result::<(), PipelineError>::Ok(()))
+        });
+        let node_identity = make_node_identity();
+        let mut service = DecryptionService::new(Default::default(), node_identity.clone(), connectivity, service);
+
+        let plain_text_msg = wrap_in_envelope_body!(b"Old news".to_vec());
+        for flags in vec![DhtMessageFlags::empty(), DhtMessageFlags::ENCRYPTED] {
+            // A replayer removes the timestamp so that the message is not discarded for being too old
+            let mut inbound_msg =
+                make_dht_inbound_message(&node_identity, plain_text_msg.to_encoded_bytes(), flags, true);
+            inbound_msg.dht_header.timestamp = None;
+
+            let err = service.call(inbound_msg).await.unwrap_err();
+            let err = err.downcast::<DecryptionError>().unwrap();
+            unpack_enum!(DecryptionError::OriginMacInvalidSignature = err);
+            assert!(result.lock().unwrap().is_none());
+            let calls = mock_state.take_calls().await;
+            assert!(calls.iter().any(|call| call.contains("BanPeer")));
+        }
+
+        // The origin MAC signature of a message with a timestamp still only signs the body, so that peers that do
+        // not know about the timestamp can authenticate it
+        let body = plain_text_msg.to_encoded_bytes();
+        let inbound_msg = make_dht_inbound_message(&node_identity, body.clone(), DhtMessageFlags::empty(), true);
+        let origin_mac = OriginMac::decode(inbound_msg.dht_header.origin_mac.as_slice()).unwrap();
+        assert!(signature::verify(node_identity.public_key(), &origin_mac.signature, &body));
+
+        // A message with a timestamp must have the timestamp signed
+        let (e_sk, _) = make_keypair();
+        let mut inbound_msg = make_dht_inbound_message(&node_identity, body.clone(), DhtMessageFlags::empty(), true);
+        inbound_msg.dht_header.origin_mac =
+            make_valid_origin_mac(&node_identity, &e_sk, None, &body, DhtMessageFlags::empty());
+        let err = service.call(inbound_msg).await.unwrap_err();
+        let err = err.downcast::<DecryptionError>().unwrap();
+        unpack_enum!(DecryptionError::OriginMacInvalidSignature = err);
+        assert!(result.lock().unwrap().is_none());
+    }
+
+    #[tokio_macros::test_basic]
+    async fn decryption_pool() {
+        const POOL_SIZE: usize = 2;
+        const NUM_MSGS: usize = 3;
+        let node_identity = make_node_identity();
+        let (connectivity, _) = create_connectivity_mock();
+        let num_decrypted = Arc::new(AtomicUsize::new(0));
+        let service = {
+            let num_decrypted = num_decrypted.clone();
+            service_fn(move |msg: DecryptedDhtMessage| {
+                assert!(msg.decryption_succeeded());
+                num_decrypted.fetch_add(1, Ordering::SeqCst);
+                future::ready(Result::<(), PipelineError>::Ok(()))
+            })
+        };
+        let config = DhtConfig {
+            decryption_pool_size: POOL_SIZE,
+            ..Default::default()
+        };
+        let mut service = DecryptionService::new(config, node_identity.clone(), connectivity, service);
+        let permits = service.decryption_permits.clone();
+        assert_eq!(permits.available_permits(), POOL_SIZE);
+
+        // Occupy the whole pool so that no message can be decrypted
+        let held_permits = future::join_all((0..POOL_SIZE).map(|_| permits.acquire())).await;
+
+        let plain_text_msg = wrap_in_envelope_body!(b"Secret plans".to_vec()).to_encoded_bytes();
+        let mut pending = Box::pin(future::join_all((0..NUM_MSGS).map(|_| {
+            service.call(make_dht_inbound_message(
+                &node_identity,
+                plain_text_msg.clone(),
+                DhtMessageFlags::ENCRYPTED,
+                true,
+            ))
+        })));
+        assert!(time::timeout(Duration::from_millis(100), &mut pending).await.is_err());
+        assert_eq!(num_decrypted.load(Ordering::SeqCst), 0);
+
+        drop(held_permits);
+        let results = pending.await;
+        assert!(results.into_iter().all(|r| r.is_ok()));
+        assert_eq!(num_decrypted.load(Ordering::SeqCst), NUM_MSGS);
+        assert_eq!(permits.available_permits(), POOL_SIZE);
+    }
 }
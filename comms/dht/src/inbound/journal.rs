@@ -0,0 +1,519 @@
+// Copyright 2021, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    crypt,
+    envelope::{datetime_to_timestamp, timestamp_to_datetime},
+    proto::journal::JournalEntry,
+};
+use chrono::{DateTime, Utc};
+use digest::Digest;
+use futures::{task::Context, Future};
+use log::*;
+use prost::Message;
+use std::{
+    fs::{File, OpenOptions},
+    io,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+    task::Poll,
+};
+use tari_comms::{
+    message::{InboundMessage, MessageExt},
+    peer_manager::{NodeId, NodeIdentity},
+    pipeline::PipelineError,
+    types::{Challenge, CommsPublicKey},
+};
+use tari_utilities::ByteArray;
+use thiserror::Error;
+use tower::{layer::Layer, Service, ServiceExt};
+
+const LOG_TARGET: &str = "comms::dht::inbound::journal";
+
+const JOURNAL_ENTRY_MAC_LENGTH: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum MessageJournalError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Failed to decode journal entry: {0}")]
+    DecodeError(#[from] prost::DecodeError),
+    #[error("Failed to encrypt journal entry")]
+    EncryptionFailed,
+    #[error("Failed to decrypt journal entry")]
+    DecryptionFailed,
+    #[error("Journal entry failed authentication. It was not written by this node or has been modified.")]
+    InvalidMac,
+    #[error("Journal entry contained an invalid source peer node id")]
+    InvalidSourcePeer,
+}
+
+/// Records raw inbound messages to a file so that they can later be replayed through the inbound pipeline using
+/// [replay_journal].
+///
+/// Entries are encrypted with a key derived from the node's secret key, so only the node that recorded the journal
+/// can read it. Each entry is written as a big-endian `u32` length followed by the encrypted entry and a MAC over the
+/// encrypted entry. Writes are blocking, so the journal should only be enabled while debugging.
+#[derive(Clone)]
+pub struct MessageJournal {
+    writer: Arc<Mutex<BufWriter<File>>>,
+    cipher_key: CommsPublicKey,
+}
+
+impl MessageJournal {
+    /// Open the journal at the given path for writing. New entries are appended if the journal already exists.
+    pub fn create<P: AsRef<Path>>(path: P, node_identity: &NodeIdentity) -> Result<Self, MessageJournalError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            writer: Arc::new(Mutex::new(BufWriter::new(file))),
+            cipher_key: journal_cipher_key(node_identity),
+        })
+    }
+
+    /// Append the message to the journal
+    pub fn append(&self, message: &InboundMessage) -> Result<(), MessageJournalError> {
+        let entry = JournalEntry {
+            received_at: Some(datetime_to_timestamp(Utc::now())),
+            source_peer: message.source_peer.to_vec(),
+            body: message.body.to_vec(),
+        };
+        let encrypted = crypt::encrypt(&self.cipher_key, &entry.to_encoded_bytes())
+            .map_err(|_| MessageJournalError::EncryptionFailed)?;
+        let mac = journal_entry_mac(&self.cipher_key, &encrypted);
+
+        let mut writer = self.writer.lock().expect("MessageJournal lock poisoned");
+        writer.write_all(&((encrypted.len() + mac.len()) as u32).to_be_bytes())?;
+        writer.write_all(&encrypted)?;
+        writer.write_all(&mac)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// A message read from the journal
+#[derive(Debug)]
+pub struct JournalRecord {
+    pub received_at: Option<DateTime<Utc>>,
+    pub message: InboundMessage,
+}
+
+/// Reads the messages recorded by a [MessageJournal], in the order that they were received.
+pub struct MessageJournalReader {
+    reader: BufReader<File>,
+    cipher_key: CommsPublicKey,
+}
+
+impl MessageJournalReader {
+    pub fn open<P: AsRef<Path>>(path: P, node_identity: &NodeIdentity) -> Result<Self, MessageJournalError> {
+        Ok(Self {
+            reader: BufReader::new(File::open(path)?),
+            cipher_key: journal_cipher_key(node_identity),
+        })
+    }
+
+    /// Read the next record from the journal, returning None once the end of the journal is reached
+    pub fn read_next(&mut self) -> Result<Option<JournalRecord>, MessageJournalError> {
+        let mut len_buf = [0u8; 4];
+        match self.reader.read_exact(&mut len_buf) {
+            Ok(_) => {},
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+        let mut buf = vec![0u8; u32::from_be_bytes(len_buf) as usize];
+        self.reader.read_exact(&mut buf)?;
+        if buf.len() < JOURNAL_ENTRY_MAC_LENGTH {
+            return Err(MessageJournalError::InvalidMac);
+        }
+        let (encrypted, mac) = buf.split_at(buf.len() - JOURNAL_ENTRY_MAC_LENGTH);
+        // Without this check, an entry read with the wrong key would decrypt to garbage instead of failing
+        if journal_entry_mac(&self.cipher_key, encrypted) != mac {
+            return Err(MessageJournalError::InvalidMac);
+        }
+
+        let decrypted =
+            crypt::decrypt(&self.cipher_key, encrypted).map_err(|_| MessageJournalError::DecryptionFailed)?;
+        let entry = JournalEntry::decode(decrypted.as_slice())?;
+        let source_peer = NodeId::from_bytes(&entry.source_peer).map_err(|_| MessageJournalError::InvalidSourcePeer)?;
+
+        Ok(Some(JournalRecord {
+            received_at: entry.received_at.map(timestamp_to_datetime),
+            message: InboundMessage::new(source_peer, entry.body.into()),
+        }))
+    }
+}
+
+impl Iterator for MessageJournalReader {
+    type Item = Result<JournalRecord, MessageJournalError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_next().transpose()
+    }
+}
+
+/// Replay every message in the journal at `path` through the given inbound pipeline service, in the order that they
+/// were received. The service should be built with `Dht::replay_middleware_layer`, because the full inbound stack
+/// discards messages that are older than the maximum message age or that were already seen, and acts on DHT messages
+/// as if they had just been received. `Dht::replay_message_journal` does this. Errors returned by the service are
+/// logged and do not stop the replay. Returns the number of messages that were replayed.
+pub async fn replay_journal<P, S>(
+    path: P,
+    node_identity: &NodeIdentity,
+    service: S,
+) -> Result<usize, MessageJournalError>
+where
+    P: AsRef<Path>,
+    S: Service<InboundMessage, Response = (), Error = PipelineError> + Clone,
+{
+    let mut num_replayed = 0;
+    for record in MessageJournalReader::open(path, node_identity)? {
+        let JournalRecord { received_at, message } = record?;
+        debug!(
+            target: LOG_TARGET,
+            "Replaying message from peer '{}' (received at {:?})",
+            message.source_peer.short_str(),
+            received_at
+        );
+        if let Err(err) = service.clone().oneshot(message).await {
+            warn!(target: LOG_TARGET, "Replayed message failed: {}", err);
+        }
+        num_replayed += 1;
+    }
+    Ok(num_replayed)
+}
+
+/// The journal key is derived from the node's own keypair. Any ECDH shared secret could be used, this one is simply
+/// one that only the node can compute.
+fn journal_cipher_key(node_identity: &NodeIdentity) -> CommsPublicKey {
+    crypt::generate_ecdh_secret(node_identity.secret_key(), node_identity.public_key())
+}
+
+fn journal_entry_mac(cipher_key: &CommsPublicKey, encrypted: &[u8]) -> Vec<u8> {
+    Challenge::new()
+        .chain(cipher_key.as_bytes())
+        .chain(encrypted)
+        .result()
+        .to_vec()
+}
+
+/// # Message journal middleware
+///
+/// Records every `InboundMessage` to a [MessageJournal] before passing it to the next service. A message that cannot be
+/// journaled is still passed on. Messages are passed on without being recorded if no journal is set.
+#[derive(Clone)]
+pub struct MessageJournalMiddleware<S> {
+    next_service: S,
+    journal: Option<MessageJournal>,
+}
+
+impl<S> MessageJournalMiddleware<S> {
+    pub fn new(journal: Option<MessageJournal>, service: S) -> Self {
+        Self {
+            journal,
+            next_service: service,
+        }
+    }
+}
+
+impl<S> Service<InboundMessage> for MessageJournalMiddleware<S>
+where S: Service<InboundMessage, Response = (), Error = PipelineError> + Clone + 'static
+{
+    type Error = PipelineError;
+    type Response = ();
+
+    type Future = impl Future<Output = Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, message: InboundMessage) -> Self::Future {
+        if let Some(journal) = self.journal.as_ref() {
+            if let Err(err) = journal.append(&message) {
+                warn!(target: LOG_TARGET, "Failed to journal message {}: {}", message.tag, err);
+            }
+        }
+        self.next_service.clone().oneshot(message)
+    }
+}
+
+/// Layer that records inbound messages to a [MessageJournal]. This should be the outermost layer of the inbound
+/// pipeline so that messages are recorded exactly as they were received.
+pub struct MessageJournalLayer {
+    journal: Option<MessageJournal>,
+}
+
+impl MessageJournalLayer {
+    pub fn new(journal: MessageJournal) -> Self {
+        Self { journal: Some(journal) }
+    }
+
+    /// Create a layer that only records messages if a journal is given
+    pub fn optional(journal: Option<MessageJournal>) -> Self {
+        Self { journal }
+    }
+}
+
+impl<S> Layer<S> for MessageJournalLayer {
+    type Service = MessageJournalMiddleware<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        MessageJournalMiddleware::new(self.journal.clone(), service)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        envelope::DhtMessageFlags,
+        inbound::DecryptedDhtMessage,
+        proto::envelope::DhtMessageType,
+        test_utils::{build_peer_manager, make_comms_inbound_message, make_dht_envelope, make_node_identity},
+        Dht,
+        DhtBuilder,
+        DhtConfig,
+    };
+    use futures::{channel::mpsc, StreamExt};
+    use std::{fs, sync::Arc, time::Duration};
+    use tari_comms::{
+        message::MessageTag,
+        pipeline::SinkService,
+        test_utils::mocks::create_connectivity_mock,
+        wrap_in_envelope_body,
+    };
+    use tari_shutdown::Shutdown;
+    use tari_test_utils::collect_stream;
+    use tempfile::tempdir;
+
+    async fn build_dht(node_identity: &Arc<NodeIdentity>, shutdown: &Shutdown, config: DhtConfig) -> Dht {
+        build_dht_with_journal(node_identity, shutdown, config, None).await
+    }
+
+    async fn build_dht_with_journal(
+        node_identity: &Arc<NodeIdentity>,
+        shutdown: &Shutdown,
+        config: DhtConfig,
+        journal: Option<MessageJournal>,
+    ) -> Dht
+    {
+        let peer_manager = build_peer_manager();
+        peer_manager.add_peer(node_identity.to_peer()).await.unwrap();
+        let (connectivity, _) = create_connectivity_mock();
+        // Dummy out channel, we are not testing outbound here.
+        let (out_tx, _) = mpsc::channel(10);
+        let mut builder = DhtBuilder::new(
+            Arc::clone(node_identity),
+            peer_manager,
+            out_tx,
+            connectivity,
+            shutdown.to_signal(),
+        )
+        .with_config(config);
+        if let Some(journal) = journal {
+            builder = builder.with_message_journal(journal);
+        }
+        builder.build().await.unwrap()
+    }
+
+    async fn build_replay_pipeline(
+        node_identity: &Arc<NodeIdentity>,
+        shutdown: &Shutdown,
+        config: DhtConfig,
+    ) -> (
+        impl Service<InboundMessage, Response = (), Error = PipelineError> + Clone,
+        mpsc::Receiver<DecryptedDhtMessage>,
+    )
+    {
+        let dht = build_dht(node_identity, shutdown, config).await;
+        let (out_tx, out_rx) = mpsc::channel(10);
+        (dht.replay_middleware_layer().layer(SinkService::new(out_tx)), out_rx)
+    }
+
+    fn make_inbound_message(node_identity: &NodeIdentity, body: Vec<u8>, flags: DhtMessageFlags) -> InboundMessage {
+        make_inbound_message_with_type(node_identity, body, flags, DhtMessageType::None)
+    }
+
+    fn make_inbound_message_with_type(
+        node_identity: &NodeIdentity,
+        body: Vec<u8>,
+        flags: DhtMessageFlags,
+        message_type: DhtMessageType,
+    ) -> InboundMessage
+    {
+        let msg = wrap_in_envelope_body!(body);
+        let mut dht_envelope =
+            make_dht_envelope(node_identity, msg.to_encoded_bytes(), flags, true, MessageTag::new());
+        dht_envelope.header.as_mut().unwrap().message_type = message_type as i32;
+        make_comms_inbound_message(node_identity, dht_envelope.to_encoded_bytes().into())
+    }
+
+    fn decode_bodies(messages: Vec<DecryptedDhtMessage>) -> Vec<Vec<u8>> {
+        messages
+            .into_iter()
+            .map(|msg| msg.success().unwrap().decode_part::<Vec<u8>>(0).unwrap().unwrap())
+            .collect()
+    }
+
+    #[tokio_macros::test_basic]
+    async fn journaled_messages_replay() {
+        let node_identity = make_node_identity();
+        let shutdown = Shutdown::new();
+        let dir = tempdir().unwrap();
+        let journal_path = dir.path().join("inbound.journal");
+
+        let messages = vec![
+            (b"public".to_vec(), DhtMessageFlags::empty()),
+            (b"secret".to_vec(), DhtMessageFlags::ENCRYPTED),
+        ]
+        .into_iter()
+        .map(|(body, flags)| make_inbound_message(&node_identity, body, flags))
+        .collect::<Vec<_>>();
+
+        let journal = MessageJournal::create(&journal_path, &node_identity).unwrap();
+        let dht = build_dht_with_journal(
+            &node_identity,
+            &shutdown,
+            DhtConfig::default_local_test(),
+            Some(journal),
+        )
+        .await;
+        let (out_tx, mut out_rx) = mpsc::channel(10);
+        let mut service = dht.inbound_middleware_layer().layer(SinkService::new(out_tx));
+        for msg in messages {
+            service.call(msg).await.unwrap();
+        }
+        let received = collect_stream!(out_rx, take = 2, timeout = Duration::from_secs(10));
+        let received = decode_bodies(received);
+        assert_eq!(received, vec![b"public".to_vec(), b"secret".to_vec()]);
+
+        // The journal does not contain any message contents in plain text
+        let raw = fs::read(&journal_path).unwrap();
+        assert!(!raw.windows(b"public".len()).any(|w| w == b"public"));
+
+        // Replayed messages have already been seen by this node, so they are replayed without deduplication
+        let (out_tx, mut out_rx) = mpsc::channel(10);
+        let num_replayed = dht
+            .replay_message_journal(&journal_path, SinkService::new(out_tx))
+            .await
+            .unwrap();
+        assert_eq!(num_replayed, 2);
+        let replayed = collect_stream!(out_rx, take = 2, timeout = Duration::from_secs(10));
+        assert_eq!(decode_bodies(replayed), received);
+    }
+
+    #[tokio_macros::test_basic]
+    async fn aged_messages_replay() {
+        let node_identity = make_node_identity();
+        let shutdown = Shutdown::new();
+        let dir = tempdir().unwrap();
+        let journal_path = dir.path().join("inbound.journal");
+
+        let journal = MessageJournal::create(&journal_path, &node_identity).unwrap();
+        journal
+            .append(&make_inbound_message(
+                &node_identity,
+                b"aged".to_vec(),
+                DhtMessageFlags::empty(),
+            ))
+            .unwrap();
+        let config = DhtConfig {
+            max_message_age: Duration::from_secs(1),
+            ..DhtConfig::default_local_test()
+        };
+        tokio::time::delay_for(Duration::from_secs(2)).await;
+
+        // The entry is now older than the maximum message age, so the inbound pipeline discards it
+        let dht = build_dht(&node_identity, &shutdown, config.clone()).await;
+        let (out_tx, mut out_rx) = mpsc::channel(10);
+        let mut service = dht.inbound_middleware_layer().layer(SinkService::new(out_tx));
+        let record = MessageJournalReader::open(&journal_path, &node_identity)
+            .unwrap()
+            .read_next()
+            .unwrap()
+            .unwrap();
+        let _ = service.call(record.message).await;
+        assert!(out_rx.try_next().is_err());
+
+        let (service, mut out_rx) = build_replay_pipeline(&node_identity, &shutdown, config).await;
+        let num_replayed = replay_journal(&journal_path, &node_identity, service).await.unwrap();
+        assert_eq!(num_replayed, 1);
+        let replayed = collect_stream!(out_rx, take = 1, timeout = Duration::from_secs(10));
+        assert_eq!(decode_bodies(replayed), vec![b"aged".to_vec()]);
+    }
+
+    #[tokio_macros::test_basic]
+    async fn dht_messages_are_not_replayed() {
+        let node_identity = make_node_identity();
+        let shutdown = Shutdown::new();
+        let dir = tempdir().unwrap();
+        let journal_path = dir.path().join("inbound.journal");
+
+        let journal = MessageJournal::create(&journal_path, &node_identity).unwrap();
+        let join = make_inbound_message_with_type(
+            &node_identity,
+            b"join".to_vec(),
+            DhtMessageFlags::empty(),
+            DhtMessageType::Join,
+        );
+        journal.append(&join).unwrap();
+        journal
+            .append(&make_inbound_message(
+                &node_identity,
+                b"domain".to_vec(),
+                DhtMessageFlags::empty(),
+            ))
+            .unwrap();
+
+        let (service, mut out_rx) =
+            build_replay_pipeline(&node_identity, &shutdown, DhtConfig::default_local_test()).await;
+        let num_replayed = replay_journal(&journal_path, &node_identity, service).await.unwrap();
+        assert_eq!(num_replayed, 2);
+        let replayed = collect_stream!(out_rx, take = 1, timeout = Duration::from_secs(10));
+        assert_eq!(decode_bodies(replayed), vec![b"domain".to_vec()]);
+        // The replay service has been dropped, so the stream ends once all replayed messages have been received
+        assert!(out_rx.next().await.is_none());
+    }
+
+    #[test]
+    fn journal_requires_node_key() {
+        let node_identity = make_node_identity();
+        let dir = tempdir().unwrap();
+        let journal_path = dir.path().join("inbound.journal");
+
+        let journal = MessageJournal::create(&journal_path, &node_identity).unwrap();
+        let msg = make_comms_inbound_message(&node_identity, b"message".to_vec().into());
+        journal.append(&msg).unwrap();
+
+        let mut reader = MessageJournalReader::open(&journal_path, &node_identity).unwrap();
+        let record = reader.read_next().unwrap().unwrap();
+        assert_eq!(record.message.source_peer, msg.source_peer);
+        assert_eq!(record.message.body, msg.body);
+        assert!(reader.read_next().unwrap().is_none());
+
+        // Another node cannot recover the message from the journal
+        let other_identity = make_node_identity();
+        let mut reader = MessageJournalReader::open(&journal_path, &other_identity).unwrap();
+        let err = reader.read_next().unwrap_err();
+        assert!(matches!(err, MessageJournalError::InvalidMac));
+    }
+}
@@ -0,0 +1,174 @@
+// Copyright 2021, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{envelope::epochtime_to_datetime, inbound::DecryptedDhtMessage};
+use chrono::Utc;
+use futures::{task::Context, Future};
+use log::*;
+use std::{task::Poll, time::Duration};
+use tari_comms::pipeline::PipelineError;
+use tower::{layer::Layer, Service, ServiceExt};
+
+const LOG_TARGET: &str = "comms::dht::max_age";
+
+/// # DHT max message age middleware
+///
+/// Takes in a `DecryptedDhtMessage` and discards it if the origin timestamp in the message header is older than the
+/// configured maximum age. This complements the message hash cache by rejecting replayed messages that have already
+/// been evicted from the cache. The timestamp is signed in the origin MAC of authenticated messages, so it cannot be
+/// refreshed by a peer replaying the message. Messages without a timestamp are discarded, so that a replayed message
+/// cannot skip the check by having its timestamp removed. Messages received via store and forward are passed on
+/// unchecked.
+#[derive(Clone)]
+pub struct MaxAgeMiddleware<S> {
+    next_service: S,
+    max_age: Duration,
+}
+
+impl<S> MaxAgeMiddleware<S> {
+    pub fn new(service: S, max_age: Duration) -> Self {
+        Self {
+            next_service: service,
+            max_age,
+        }
+    }
+}
+
+impl<S> Service<DecryptedDhtMessage> for MaxAgeMiddleware<S>
+where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError> + Clone + 'static
+{
+    type Error = PipelineError;
+    type Response = ();
+
+    type Future = impl Future<Output = Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, message: DecryptedDhtMessage) -> Self::Future {
+        let next_service = self.next_service.clone();
+        let max_age = self.max_age;
+        async move {
+            if !message.is_saf_message {
+                let timestamp = match message.dht_header.timestamp {
+                    Some(timestamp) => timestamp,
+                    None => {
+                        debug!(
+                            target: LOG_TARGET,
+                            "Discarding message {} from peer '{}' because it has no timestamp (Trace: {})",
+                            message.tag,
+                            message.source_peer.node_id.short_str(),
+                            message.dht_header.message_tag
+                        );
+                        return Ok(());
+                    },
+                };
+                let age = Utc::now().signed_duration_since(epochtime_to_datetime(timestamp));
+                if age.to_std().map(|age| age > max_age).unwrap_or(false) {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Discarding message {} from peer '{}' because it is older than the maximum message age ({}s \
+                         > {}s) (Trace: {})",
+                        message.tag,
+                        message.source_peer.node_id.short_str(),
+                        age.num_seconds(),
+                        max_age.as_secs(),
+                        message.dht_header.message_tag
+                    );
+                    return Ok(());
+                }
+            }
+
+            trace!(
+                target: LOG_TARGET,
+                "Passing message {} to next service (Trace: {})",
+                message.tag,
+                message.dht_header.message_tag
+            );
+            next_service.oneshot(message).await
+        }
+    }
+}
+
+pub struct MaxAgeLayer {
+    max_age: Duration,
+}
+
+impl MaxAgeLayer {
+    pub fn new(max_age: Duration) -> Self {
+        Self { max_age }
+    }
+}
+
+impl<S> Layer<S> for MaxAgeLayer {
+    type Service = MaxAgeMiddleware<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        MaxAgeMiddleware::new(service, self.max_age)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        envelope::{datetime_to_epochtime, DhtMessageFlags},
+        test_utils::{make_dht_inbound_message, make_node_identity, service_spy},
+    };
+    use tari_test_utils::panic_context;
+    use tokio::runtime::Runtime;
+
+    #[test]
+    fn discards_old_messages() {
+        let mut rt = Runtime::new().unwrap();
+        let spy = service_spy();
+
+        let mut max_age = MaxAgeLayer::new(Duration::from_secs(60)).layer(spy.to_service::<PipelineError>());
+
+        panic_context!(cx);
+
+        assert!(max_age.poll_ready(&mut cx).is_ready());
+        let node_identity = make_node_identity();
+        let inbound_msg = make_dht_inbound_message(&node_identity, Vec::new(), DhtMessageFlags::empty(), false);
+        let mut msg = DecryptedDhtMessage::failed(inbound_msg);
+
+        msg.dht_header.timestamp = Some(datetime_to_epochtime(Utc::now() - chrono::Duration::seconds(120)));
+        rt.block_on(max_age.call(msg.clone())).unwrap();
+        assert_eq!(spy.call_count(), 0);
+
+        // Old messages received via store and forward are not discarded
+        msg.is_saf_message = true;
+        rt.block_on(max_age.call(msg.clone())).unwrap();
+        assert_eq!(spy.call_count(), 1);
+        msg.is_saf_message = false;
+
+        msg.dht_header.timestamp = Some(datetime_to_epochtime(Utc::now()));
+        rt.block_on(max_age.call(msg.clone())).unwrap();
+        assert_eq!(spy.call_count(), 2);
+
+        // Messages without a timestamp are discarded
+        msg.dht_header.timestamp = None;
+        rt.block_on(max_age.call(msg)).unwrap();
+        assert_eq!(spy.call_count(), 2);
+    }
+}
@@ -20,6 +20,9 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+mod content_dedup;
+pub use content_dedup::ContentDedupLayer;
+
 mod decryption;
 pub use decryption::DecryptionLayer;
 
@@ -34,6 +37,21 @@ pub use metrics::MetricsLayer;
 
 mod error;
 
+#[cfg(feature = "message-journal")]
+mod journal;
+#[cfg(feature = "message-journal")]
+pub use journal::{
+    replay_journal,
+    JournalRecord,
+    MessageJournal,
+    MessageJournalError,
+    MessageJournalLayer,
+    MessageJournalReader,
+};
+
+mod max_age;
+pub use max_age::MaxAgeLayer;
+
 mod message;
 pub use message::{DecryptedDhtMessage, DhtInboundMessage};
 
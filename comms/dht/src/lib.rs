@@ -127,7 +127,7 @@ mod macros;
 mod test_utils;
 
 mod actor;
-pub use actor::{DhtActorError, DhtRequest, DhtRequester};
+pub use actor::{DhtActorError, DhtRequest, DhtRequester, JoinResult};
 
 mod builder;
 pub use builder::DhtBuilder;
@@ -145,7 +145,7 @@ mod dht;
 pub use dht::{Dht, DhtInitializationError};
 
 mod discovery;
-pub use discovery::DhtDiscoveryRequester;
+pub use discovery::{DhtDiscoveryRequester, DiscoveryOptions};
 
 mod network_discovery;
 pub use network_discovery::NetworkDiscoveryConfig;
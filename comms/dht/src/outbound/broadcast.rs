@@ -26,7 +26,14 @@ use crate::{
     broadcast_strategy::BroadcastStrategy,
     crypt,
     discovery::DhtDiscoveryRequester,
-    envelope::{datetime_to_timestamp, DhtMessageFlags, DhtMessageHeader, NodeDestination},
+    envelope::{
+        datetime_to_epochtime,
+        datetime_to_timestamp,
+        epochtime_to_datetime,
+        DhtMessageFlags,
+        DhtMessageHeader,
+        NodeDestination,
+    },
     outbound::{
         message::{DhtOutboundMessage, OutboundEncryption, SendFailure},
         message_params::FinalSendMessageParams,
@@ -57,7 +64,7 @@ use tari_comms::{
 };
 use tari_crypto::{
     keys::PublicKey,
-    tari_utilities::{message_format::MessageFormat, ByteArray},
+    tari_utilities::{epoch_time::EpochTime, message_format::MessageFormat, ByteArray},
 };
 use tari_utilities::hex::Hex;
 use tower::{layer::Layer, Service, ServiceExt};
@@ -428,7 +435,10 @@ where S: Service<DhtOutboundMessage, Response = (), Error = PipelineError>
     {
         let dht_flags = encryption.flags() | extra_flags;
 
-        let (ephemeral_public_key, origin_mac, body) = self.process_encryption(&encryption, force_origin, body)?;
+        // The timestamp is signed in the origin MAC, so it is truncated to the precision that the recipient sees
+        let timestamp = datetime_to_epochtime(Utc::now());
+        let (ephemeral_public_key, origin_mac, body) =
+            self.process_encryption(&encryption, force_origin, timestamp, body)?;
 
         if is_broadcast {
             self.add_to_dedup_cache(&body).await?;
@@ -438,7 +448,7 @@ where S: Service<DhtOutboundMessage, Response = (), Error = PipelineError>
         let messages = selected_peers.into_iter().map(|node_id| {
             let (reply_tx, reply_rx) = oneshot::channel();
             let tag = MessageTag::new();
-            let send_state = MessageSendState::new(tag, reply_rx);
+            let send_state = MessageSendState::new(tag, node_id.clone(), reply_rx);
             (
                 DhtOutboundMessage {
                     tag,
@@ -454,6 +464,7 @@ where S: Service<DhtOutboundMessage, Response = (), Error = PipelineError>
                     origin_mac: origin_mac.clone(),
                     is_broadcast,
                     expires: expires.map(datetime_to_timestamp),
+                    timestamp: Some(datetime_to_timestamp(epochtime_to_datetime(timestamp))),
                 },
                 send_state,
             )
@@ -480,6 +491,7 @@ where S: Service<DhtOutboundMessage, Response = (), Error = PipelineError>
         &self,
         encryption: &OutboundEncryption,
         include_origin: bool,
+        timestamp: EpochTime,
         body: Bytes,
     ) -> Result<FinalMessageParts, DhtOutboundError>
     {
@@ -493,7 +505,7 @@ where S: Service<DhtOutboundMessage, Response = (), Error = PipelineError>
                 let encrypted_body = crypt::encrypt(&shared_ephemeral_secret, &body)?;
 
                 // Sign the encrypted message
-                let origin_mac = create_origin_mac(&self.node_identity, &encrypted_body)?;
+                let origin_mac = create_origin_mac(&self.node_identity, timestamp, &encrypted_body)?;
                 // Encrypt and set the origin field
                 let encrypted_origin_mac = crypt::encrypt(&shared_ephemeral_secret, &origin_mac)?;
                 Ok((
@@ -506,7 +518,7 @@ where S: Service<DhtOutboundMessage, Response = (), Error = PipelineError>
                 trace!(target: LOG_TARGET, "Encryption not requested for message");
 
                 if include_origin {
-                    let origin_mac = create_origin_mac(&self.node_identity, &body)?;
+                    let origin_mac = create_origin_mac(&self.node_identity, timestamp, &body)?;
                     Ok((None, Some(origin_mac.into()), body))
                 } else {
                     Ok((None, None, body))
@@ -516,12 +528,23 @@ where S: Service<DhtOutboundMessage, Response = (), Error = PipelineError>
     }
 }
 
-fn create_origin_mac(node_identity: &NodeIdentity, body: &[u8]) -> Result<Vec<u8>, DhtOutboundError> {
+fn create_origin_mac(
+    node_identity: &NodeIdentity,
+    timestamp: EpochTime,
+    body: &[u8],
+) -> Result<Vec<u8>, DhtOutboundError>
+{
     let signature = signature::sign(&mut OsRng, node_identity.secret_key().clone(), body)?;
+    let timestamp_signature = signature::sign(
+        &mut OsRng,
+        node_identity.secret_key().clone(),
+        crypt::create_origin_mac_timestamp_challenge(timestamp, body),
+    )?;
 
     let mac = OriginMac {
         public_key: node_identity.public_key().to_vec(),
         signature: signature.to_binary()?,
+        timestamp_signature: timestamp_signature.to_binary()?,
     };
     Ok(mac.to_encoded_bytes())
 }
@@ -593,7 +616,7 @@ mod test {
             Network::LocalTest,
             chrono::Duration::seconds(10800),
         );
-        let (reply_tx, _reply_rx) = oneshot::channel();
+        let (reply_tx, reply_rx) = oneshot::channel();
 
         service
             .call(DhtOutboundRequest::SendMessage(
@@ -610,6 +633,12 @@ mod test {
             .iter()
             .any(|msg| msg.destination_node_id == example_peer.node_id));
         assert!(requests.iter().any(|msg| msg.destination_node_id == other_peer.node_id));
+
+        let send_states = reply_rx.await.unwrap().resolve().await.unwrap();
+        let node_ids = send_states.to_destination_node_ids();
+        assert_eq!(node_ids.len(), 2);
+        assert!(node_ids.contains(&example_peer.node_id));
+        assert!(node_ids.contains(&other_peer.node_id));
     }
 
     #[tokio_macros::test_basic]
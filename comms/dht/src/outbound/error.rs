@@ -59,12 +59,15 @@ pub enum DhtOutboundError {
     SendMessageFailed(SendFailure),
     #[error("No messages were queued for sending")]
     NoMessagesQueued,
+    #[error("The outbound message pipeline is saturated")]
+    PipelineSaturated,
 }
 
 impl From<SendFailure> for DhtOutboundError {
     fn from(err: SendFailure) -> Self {
         match err {
             SendFailure::NoMessagesQueued => DhtOutboundError::NoMessagesQueued,
+            SendFailure::PipelineSaturated => DhtOutboundError::PipelineSaturated,
             err => Self::SendMessageFailed(err),
         }
     }
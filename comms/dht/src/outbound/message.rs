@@ -22,7 +22,7 @@
 
 use crate::{
     envelope::{DhtMessageFlags, DhtMessageHeader, DhtMessageType, Network, NodeDestination},
-    outbound::{message_params::FinalSendMessageParams, message_send_state::MessageSendStates},
+    outbound::{message_params::FinalSendMessageParams, message_send_state::MessageSendStates, MessagePriority},
 };
 use bytes::Bytes;
 use futures::channel::oneshot;
@@ -92,6 +92,8 @@ pub enum SendFailure {
     FailedToGenerateMessages(String),
     #[error("No messages were queued for sending")]
     NoMessagesQueued,
+    #[error("The outbound pipeline is saturated")]
+    PipelineSaturated,
 }
 
 #[derive(Debug)]
@@ -141,6 +143,15 @@ pub enum DhtOutboundRequest {
     SendMessage(Box<FinalSendMessageParams>, Bytes, oneshot::Sender<SendMessageResponse>),
 }
 
+impl DhtOutboundRequest {
+    /// The priority lane of this request
+    pub fn priority(&self) -> MessagePriority {
+        match self {
+            DhtOutboundRequest::SendMessage(params, _, _) => params.priority,
+        }
+    }
+}
+
 impl fmt::Display for DhtOutboundRequest {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         match self {
@@ -168,6 +179,7 @@ pub struct DhtOutboundMessage {
     pub dht_flags: DhtMessageFlags,
     pub is_broadcast: bool,
     pub expires: Option<prost_types::Timestamp>,
+    pub timestamp: Option<prost_types::Timestamp>,
 }
 
 impl fmt::Display for DhtOutboundMessage {
@@ -23,7 +23,7 @@
 use crate::{
     broadcast_strategy::{BroadcastClosestRequest, BroadcastStrategy},
     envelope::{DhtMessageFlags, DhtMessageHeader, NodeDestination},
-    outbound::OutboundEncryption,
+    outbound::{MessagePriority, OutboundEncryption},
     proto::envelope::DhtMessageType,
 };
 use std::{fmt, fmt::Display};
@@ -66,6 +66,7 @@ pub struct FinalSendMessageParams {
     pub dht_message_type: DhtMessageType,
     pub dht_message_flags: DhtMessageFlags,
     pub dht_header: Option<DhtMessageHeader>,
+    pub priority: MessagePriority,
 }
 
 impl Default for FinalSendMessageParams {
@@ -79,6 +80,7 @@ impl Default for FinalSendMessageParams {
             force_origin: false,
             is_discovery_enabled: false,
             dht_header: None,
+            priority: Default::default(),
         }
     }
 }
@@ -159,6 +161,13 @@ impl SendMessageParams {
         self
     }
 
+    /// Set broadcast_strategy to RandomConnected. The message is sent to `n` randomly selected connected peers that are
+    /// not in `excluded_peers`.
+    pub fn random_connected(&mut self, n: usize, excluded_peers: Vec<NodeId>) -> &mut Self {
+        self.params_mut().broadcast_strategy = BroadcastStrategy::RandomConnected(n, excluded_peers);
+        self
+    }
+
     /// Set destination field in message header.
     pub fn with_destination(&mut self, destination: NodeDestination) -> &mut Self {
         self.params_mut().destination = destination;
@@ -202,6 +211,13 @@ impl SendMessageParams {
         self
     }
 
+    /// Set the priority lane for the message. Higher priority messages are dispatched first when the outbound
+    /// pipeline is saturated.
+    pub fn with_priority(&mut self, priority: MessagePriority) -> &mut Self {
+        self.params_mut().priority = priority;
+        self
+    }
+
     /// Return the final SendMessageParams
     pub fn finish(&mut self) -> FinalSendMessageParams {
         self.params.take().expect("cannot be None")
@@ -27,6 +27,7 @@ use std::{
 };
 use tari_comms::{
     message::{MessageTag, MessagingReplyRx},
+    peer_manager::NodeId,
     protocol::messaging::SendFailReason,
 };
 use tokio::time;
@@ -39,11 +40,16 @@ pub enum TimeoutResult<T> {
 #[derive(Debug)]
 pub struct MessageSendState {
     pub tag: MessageTag,
+    pub destination_node_id: NodeId,
     reply_rx: MessagingReplyRx,
 }
 impl MessageSendState {
-    pub fn new(tag: MessageTag, reply_rx: MessagingReplyRx) -> Self {
-        Self { tag, reply_rx }
+    pub fn new(tag: MessageTag, destination_node_id: NodeId, reply_rx: MessagingReplyRx) -> Self {
+        Self {
+            tag,
+            destination_node_id,
+            reply_rx,
+        }
     }
 
     pub fn wait_for_result(self) -> MessagingReplyRx {
@@ -231,6 +237,11 @@ impl MessageSendStates {
     pub fn to_tags(&self) -> Vec<MessageTag> {
         self.inner.iter().map(|s| s.tag).collect()
     }
+
+    /// Returns the `NodeId`s of the peers that the messages were queued for
+    pub fn to_destination_node_ids(&self) -> Vec<NodeId> {
+        self.inner.iter().map(|s| s.destination_node_id.clone()).collect()
+    }
 }
 
 impl From<Vec<MessageSendState>> for MessageSendStates {
@@ -256,7 +267,7 @@ mod test {
 
     fn create_send_state() -> (MessageSendState, MessagingReplyTx) {
         let (reply_tx, reply_rx) = oneshot::channel();
-        let state = MessageSendState::new(MessageTag::new(), reply_rx);
+        let state = MessageSendState::new(MessageTag::new(), NodeId::default(), reply_rx);
         (state, reply_tx.into())
     }
 
@@ -43,6 +43,7 @@ use std::{
 };
 use tari_comms::{
     message::{MessageTag, MessagingReplyTx},
+    peer_manager::NodeId,
     protocol::messaging::SendFailReason,
 };
 use tokio::time::delay_for;
@@ -237,7 +238,12 @@ impl OutboundServiceMock {
             .take_next_response()
             .or_else(|| {
                 Some(SendMessageResponse::Queued(
-                    vec![MessageSendState::new(MessageTag::new(), inner_reply_rx)].into(),
+                    vec![MessageSendState::new(
+                        MessageTag::new(),
+                        NodeId::default(),
+                        inner_reply_rx,
+                    )]
+                    .into(),
                 ))
             })
             .expect("never none");
@@ -35,6 +35,9 @@ pub use message_params::SendMessageParams;
 mod message_send_state;
 pub use message_send_state::{MessageSendState, MessageSendStates};
 
+mod priority;
+pub use priority::{MessagePriority, OutboundPriorityDispatcher};
+
 mod requester;
 pub use requester::OutboundMessageRequester;
 
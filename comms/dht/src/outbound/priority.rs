@@ -0,0 +1,279 @@
+// Copyright 2021, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use super::message::{DhtOutboundRequest, SendFailure, SendMessageResponse};
+use futures::{
+    channel::mpsc,
+    future,
+    stream::{Fuse, StreamExt},
+    FutureExt,
+};
+use log::*;
+use std::collections::{BTreeMap, VecDeque};
+use tari_shutdown::ShutdownSignal;
+use tokio::task;
+
+const LOG_TARGET: &str = "comms::dht::outbound::priority";
+
+/// The priority lane of an outbound message. When the outbound pipeline is saturated, queued messages in a higher
+/// priority lane are dispatched before those in a lower priority lane. Messages within a lane are dispatched in the
+/// order that they were sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MessagePriority {
+    /// Large or non-urgent messages e.g. syncing and store and forward responses
+    Bulk,
+    /// The default priority
+    Normal,
+    /// Messages that must propagate quickly for the network to reach consensus e.g. blocks and transactions
+    Consensus,
+    /// Network control messages e.g. joins and discovery
+    Control,
+}
+
+impl Default for MessagePriority {
+    fn default() -> Self {
+        MessagePriority::Normal
+    }
+}
+
+/// Sits between the `OutboundMessageRequester`s and the outbound pipeline. Requests are only forwarded once the
+/// pipeline is ready to accept them, at which point the highest priority request that is pending is chosen. Each lane
+/// holds at most `max_lane_size` requests, further requests for a full lane are rejected with
+/// `SendFailure::PipelineSaturated`.
+pub struct OutboundPriorityDispatcher {
+    request_rx: Fuse<mpsc::Receiver<DhtOutboundRequest>>,
+    outbound_tx: mpsc::Sender<DhtOutboundRequest>,
+    lanes: BTreeMap<MessagePriority, VecDeque<DhtOutboundRequest>>,
+    max_lane_size: usize,
+    shutdown_signal: Option<ShutdownSignal>,
+}
+
+impl OutboundPriorityDispatcher {
+    pub fn new(
+        request_rx: mpsc::Receiver<DhtOutboundRequest>,
+        outbound_tx: mpsc::Sender<DhtOutboundRequest>,
+        max_lane_size: usize,
+        shutdown_signal: ShutdownSignal,
+    ) -> Self
+    {
+        Self {
+            request_rx: request_rx.fuse(),
+            outbound_tx,
+            lanes: BTreeMap::new(),
+            max_lane_size,
+            shutdown_signal: Some(shutdown_signal),
+        }
+    }
+
+    pub fn spawn(self) -> task::JoinHandle<()> {
+        task::spawn(self.run())
+    }
+
+    pub async fn run(mut self) {
+        let mut shutdown_signal = self
+            .shutdown_signal
+            .take()
+            .expect("OutboundPriorityDispatcher initialized without a shutdown_signal");
+
+        loop {
+            if self.num_queued() == 0 {
+                futures::select! {
+                    request = self.request_rx.next() => match request {
+                        Some(request) => self.enqueue(request),
+                        None => break,
+                    },
+                    _ = shutdown_signal => {
+                        info!(target: LOG_TARGET, "OutboundPriorityDispatcher shutting down");
+                        break;
+                    }
+                }
+            }
+
+            // Wait until the pipeline can accept another message. Requests accumulate in the request channel until
+            // then.
+            let outbound_tx = &mut self.outbound_tx;
+            futures::select! {
+                result = future::poll_fn(|cx| outbound_tx.poll_ready(cx)).fuse() => {
+                    if result.is_err() {
+                        warn!(target: LOG_TARGET, "Outbound pipeline closed. OutboundPriorityDispatcher exiting");
+                        break;
+                    }
+                },
+                _ = shutdown_signal => {
+                    info!(target: LOG_TARGET, "OutboundPriorityDispatcher shutting down");
+                    break;
+                }
+            }
+
+            self.drain_pending_requests();
+            let request = self.dequeue().expect("num_queued() > 0 but all lanes are empty");
+            if self.outbound_tx.start_send(request).is_err() {
+                warn!(
+                    target: LOG_TARGET,
+                    "Outbound pipeline closed. OutboundPriorityDispatcher exiting"
+                );
+                break;
+            }
+        }
+    }
+
+    /// Move all requests that are immediately available into their lanes, so that the next request to be dispatched
+    /// is the highest priority of everything that has been sent so far.
+    fn drain_pending_requests(&mut self) {
+        while let Some(Some(request)) = self.request_rx.next().now_or_never() {
+            self.enqueue(request);
+        }
+    }
+
+    fn enqueue(&mut self, request: DhtOutboundRequest) {
+        let priority = request.priority();
+        let lane = self.lanes.entry(priority).or_insert_with(VecDeque::new);
+        if lane.len() >= self.max_lane_size {
+            warn!(
+                target: LOG_TARGET,
+                "Outbound {:?} lane is full ({} request(s)). Rejecting {}",
+                priority,
+                lane.len(),
+                request
+            );
+            match request {
+                DhtOutboundRequest::SendMessage(_, _, reply_tx) => {
+                    let _ = reply_tx.send(SendMessageResponse::Failed(SendFailure::PipelineSaturated));
+                },
+            }
+            return;
+        }
+        trace!(target: LOG_TARGET, "Queuing {} with priority {:?}", request, priority);
+        lane.push_back(request);
+    }
+
+    fn dequeue(&mut self) -> Option<DhtOutboundRequest> {
+        self.lanes.values_mut().rev().find_map(|lane| lane.pop_front())
+    }
+
+    fn num_queued(&self) -> usize {
+        self.lanes.values().map(VecDeque::len).sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::outbound::SendMessageParams;
+    use futures::channel::oneshot;
+    use tari_shutdown::Shutdown;
+
+    fn make_request(priority: MessagePriority, body: &'static [u8]) -> DhtOutboundRequest {
+        make_request_with_reply(priority, body).0
+    }
+
+    fn make_request_with_reply(
+        priority: MessagePriority,
+        body: &'static [u8],
+    ) -> (DhtOutboundRequest, oneshot::Receiver<SendMessageResponse>)
+    {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let params = SendMessageParams::new()
+            .broadcast(vec![])
+            .with_priority(priority)
+            .finish();
+        (
+            DhtOutboundRequest::SendMessage(Box::new(params), body.into(), reply_tx),
+            reply_rx,
+        )
+    }
+
+    fn unwrap_body(request: DhtOutboundRequest) -> Vec<u8> {
+        match request {
+            DhtOutboundRequest::SendMessage(_, body, _) => body.to_vec(),
+        }
+    }
+
+    #[tokio_macros::test_basic]
+    async fn high_priority_dispatched_before_bulk() {
+        const NUM_BULK_MSGS: usize = 10;
+        let shutdown = Shutdown::new();
+        let (mut request_tx, request_rx) = mpsc::channel(NUM_BULK_MSGS + 1);
+        // The pipeline only has capacity for one message at a time
+        let (outbound_tx, mut outbound_rx) = mpsc::channel(0);
+        OutboundPriorityDispatcher::new(request_rx, outbound_tx, NUM_BULK_MSGS, shutdown.to_signal()).spawn();
+
+        for _ in 0..NUM_BULK_MSGS {
+            request_tx
+                .try_send(make_request(MessagePriority::Bulk, b"bulk"))
+                .unwrap();
+        }
+        request_tx
+            .try_send(make_request(MessagePriority::Control, b"control"))
+            .unwrap();
+
+        let bodies = outbound_rx
+            .by_ref()
+            .take(NUM_BULK_MSGS + 1)
+            .map(unwrap_body)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(bodies[0], b"control");
+        assert!(bodies[1..].iter().all(|body| body == b"bulk"));
+    }
+
+    #[tokio_macros::test_basic]
+    async fn full_lane_is_rejected() {
+        const MAX_LANE_SIZE: usize = 2;
+        let shutdown = Shutdown::new();
+        let (mut request_tx, request_rx) = mpsc::channel(10);
+        // The pipeline does not accept any messages until it is read from
+        let (outbound_tx, mut outbound_rx) = mpsc::channel(0);
+        let mut dispatcher =
+            OutboundPriorityDispatcher::new(request_rx, outbound_tx, MAX_LANE_SIZE, shutdown.to_signal());
+
+        let mut bulk_replies = Vec::new();
+        for _ in 0..MAX_LANE_SIZE + 1 {
+            let (request, reply_rx) = make_request_with_reply(MessagePriority::Bulk, b"bulk");
+            request_tx.try_send(request).unwrap();
+            bulk_replies.push(reply_rx);
+        }
+        let (request, mut control_reply) = make_request_with_reply(MessagePriority::Control, b"control");
+        request_tx.try_send(request).unwrap();
+        dispatcher.drain_pending_requests();
+
+        // The last bulk request did not fit in the lane
+        let rejected = bulk_replies.pop().unwrap().await.unwrap();
+        assert!(matches!(
+            rejected,
+            SendMessageResponse::Failed(SendFailure::PipelineSaturated)
+        ));
+        // Other lanes are unaffected
+        assert!(control_reply.try_recv().unwrap().is_none());
+        assert_eq!(dispatcher.num_queued(), MAX_LANE_SIZE + 1);
+
+        dispatcher.spawn();
+        let bodies = outbound_rx
+            .by_ref()
+            .take(MAX_LANE_SIZE + 1)
+            .map(unwrap_body)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(bodies[0], b"control");
+        assert!(bodies[1..].iter().all(|body| body == b"bulk"));
+    }
+}
@@ -25,7 +25,7 @@ use crate::{
     domain_message::OutboundDomainMessage,
     envelope::NodeDestination,
     outbound::{
-        message::{OutboundEncryption, SendMessageResponse},
+        message::{OutboundEncryption, SendFailure, SendMessageResponse},
         message_params::{FinalSendMessageParams, SendMessageParams},
         message_send_state::MessageSendState,
         DhtOutboundError,
@@ -272,6 +272,11 @@ impl OutboundMessageRequester {
     }
 
     /// Send a raw message
+    ///
+    /// Gossip messages (broadcast, propagate and flood) are not queued if the outbound pipeline is saturated. In that
+    /// case `DhtOutboundError::PipelineSaturated` is returned so that the caller can back off rather than adding to the
+    /// backlog. Other messages, including closest and random sends, wait for the pipeline to accept them, but are
+    /// rejected in the same way if the queue for their priority is full.
     pub async fn send_raw(
         &mut self,
         params: FinalSendMessageParams,
@@ -279,13 +284,31 @@ impl OutboundMessageRequester {
     ) -> Result<SendMessageResponse, DhtOutboundError>
     {
         let (reply_tx, reply_rx) = oneshot::channel();
-        self.sender
-            .send(DhtOutboundRequest::SendMessage(Box::new(params), body.into(), reply_tx))
-            .await?;
+        let is_gossip = params.broadcast_strategy.is_gossip();
+        let request = DhtOutboundRequest::SendMessage(Box::new(params), body.into(), reply_tx);
+        if is_gossip {
+            self.sender.try_send(request).map_err(|err| {
+                if err.is_full() {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Outbound pipeline is saturated. Gossip message will not be sent."
+                    );
+                    DhtOutboundError::PipelineSaturated
+                } else {
+                    err.into_send_error().into()
+                }
+            })?;
+        } else {
+            self.sender.send(request).await?;
+        }
 
-        reply_rx
+        match reply_rx
             .await
-            .map_err(|_| DhtOutboundError::RequesterReplyChannelClosed)
+            .map_err(|_| DhtOutboundError::RequesterReplyChannelClosed)?
+        {
+            SendMessageResponse::Failed(SendFailure::PipelineSaturated) => Err(DhtOutboundError::PipelineSaturated),
+            response => Ok(response),
+        }
     }
 
     #[cfg(test)]
@@ -293,3 +316,61 @@ impl OutboundMessageRequester {
         self.sender.clone()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::make_node_identity;
+    use futures::{FutureExt, StreamExt};
+
+    fn try_broadcast(requester: &mut OutboundMessageRequester) -> Option<Result<MessageSendStates, DhtOutboundError>> {
+        requester
+            .broadcast(
+                NodeDestination::Unknown,
+                OutboundEncryption::ClearText,
+                vec![],
+                OutboundDomainMessage::new(0, b"msg".to_vec()),
+            )
+            .now_or_never()
+    }
+
+    #[tokio_macros::test_basic]
+    async fn broadcast_pipeline_saturated() {
+        // The capacity of the channel is the buffer size plus one slot for the sender
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut requester = OutboundMessageRequester::new(tx);
+
+        // The first two messages are queued and wait for a reply
+        assert!(try_broadcast(&mut requester).is_none());
+        assert!(try_broadcast(&mut requester).is_none());
+        let err = try_broadcast(&mut requester).unwrap().unwrap_err();
+        assert!(matches!(err, DhtOutboundError::PipelineSaturated));
+
+        // Direct messages are not rejected, they wait for the pipeline
+        let mut direct = requester
+            .send_direct_node_id(
+                make_node_identity().node_id().clone(),
+                OutboundDomainMessage::new(0, b"msg".to_vec()),
+            )
+            .boxed();
+        assert!((&mut direct).now_or_never().is_none());
+        drop(direct);
+
+        // Neither are closest sends, which are not gossip
+        let mut closest = requester
+            .closest_broadcast(
+                make_node_identity().node_id().clone(),
+                OutboundEncryption::ClearText,
+                vec![],
+                OutboundDomainMessage::new(0, b"msg".to_vec()),
+            )
+            .boxed();
+        assert!((&mut closest).now_or_never().is_none());
+        drop(closest);
+
+        // Once the pipeline drains, gossip messages are accepted again
+        let _ = rx.next().await.unwrap();
+        let _ = rx.next().await.unwrap();
+        assert!(try_broadcast(&mut requester).is_none());
+    }
+}
@@ -77,6 +77,7 @@ where S: Service<OutboundMessage, Response = (), Error = PipelineError> + Clone
                 origin_mac,
                 reply,
                 expires,
+                timestamp,
                 ..
             } = message;
             trace!(
@@ -95,6 +96,7 @@ where S: Service<OutboundMessage, Response = (), Error = PipelineError> + Clone
                 destination: Some(destination.into()),
                 message_tag: tag.as_value(),
                 expires,
+                timestamp,
             });
             let envelope = DhtEnvelope::new(dht_header, body);
 
@@ -52,6 +52,11 @@ pub mod message_header {
     tari_comms::outdir_include!("tari.dht.message_header.rs");
 }
 
+#[cfg(feature = "message-journal")]
+pub mod journal {
+    tari_comms::outdir_include!("tari.dht.journal.rs");
+}
+
 //---------------------------------- Network impl --------------------------------------------//
 
 impl envelope::Network {
@@ -30,10 +30,20 @@ use crate::{
     store_forward::message::StoredMessagePriority,
 };
 use chrono::{DateTime, NaiveDateTime, Utc};
-use diesel::{dsl, result::DatabaseErrorKind, BoolExpressionMethods, ExpressionMethods, QueryDsl, RunQueryDsl};
+use diesel::{
+    dsl,
+    result::DatabaseErrorKind,
+    sql_types::{Binary, Integer},
+    BoolExpressionMethods,
+    ExpressionMethods,
+    QueryDsl,
+    RunQueryDsl,
+};
 use tari_comms::{peer_manager::NodeId, types::CommsPublicKey};
 use tari_utilities::hex::Hex;
 
+sql_function!(fn length(x: Binary) -> Integer);
+
 pub struct StoreAndForwardDatabase {
     connection: DbConnection,
 }
@@ -205,6 +215,50 @@ impl StoreAndForwardDatabase {
             .await
     }
 
+    /// Returns true if a message with the given body hash is stored, otherwise false
+    pub(crate) async fn message_exists(&self, body_hash: String) -> Result<bool, StorageError> {
+        self.connection
+            .with_connection_async(move |conn| {
+                let count = stored_messages::table
+                    .filter(stored_messages::body_hash.eq(body_hash))
+                    .count()
+                    .get_result::<i64>(conn)?;
+                Ok(count > 0)
+            })
+            .await
+    }
+
+    /// Returns the number of messages and the total size in bytes of the message bodies stored for the given
+    /// destination. The public key is used if given, otherwise the node id. Messages for an unknown destination share
+    /// a single quota.
+    pub(crate) async fn get_destination_usage(
+        &self,
+        destination_pubkey: Option<String>,
+        destination_node_id: Option<String>,
+    ) -> Result<(usize, usize), StorageError>
+    {
+        self.connection
+            .with_connection_async(move |conn| {
+                let query = stored_messages::table
+                    .select((dsl::count(stored_messages::id), dsl::sum(length(stored_messages::body))))
+                    .into_boxed();
+
+                let query = match (destination_pubkey, destination_node_id) {
+                    (Some(pk_hex), _) => query.filter(stored_messages::destination_pubkey.eq(pk_hex)),
+                    (None, Some(node_id_hex)) => query.filter(stored_messages::destination_node_id.eq(node_id_hex)),
+                    (None, None) => query.filter(
+                        stored_messages::destination_pubkey
+                            .is_null()
+                            .and(stored_messages::destination_node_id.is_null()),
+                    ),
+                };
+
+                let (count, total_bytes) = query.first::<(i64, Option<i64>)>(conn)?;
+                Ok((count as usize, total_bytes.unwrap_or(0) as usize))
+            })
+            .await
+    }
+
     pub(crate) async fn delete_messages_with_priority_older_than(
         &self,
         priority: StoredMessagePriority,
@@ -71,6 +71,8 @@ pub enum StoreAndForwardError {
     RequestCancelled,
     #[error("The message was not valid for store and forward")]
     InvalidStoreMessage,
+    #[error("The store and forward quota for the message destination has been reached")]
+    DestinationQuotaExceeded,
     #[error("The envelope version is invalid")]
     InvalidEnvelopeVersion,
     #[error("MalformedNodeId: {0}")]
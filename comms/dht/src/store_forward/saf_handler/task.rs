@@ -26,7 +26,7 @@ use crate::{
     crypt,
     envelope::{timestamp_to_datetime, DhtMessageFlags, DhtMessageHeader, NodeDestination},
     inbound::{DecryptedDhtMessage, DhtInboundMessage},
-    outbound::{OutboundMessageRequester, SendMessageParams},
+    outbound::{MessagePriority, OutboundMessageRequester, SendMessageParams},
     proto::{
         envelope::{DhtMessageType, OriginMac},
         store_forward::{
@@ -209,6 +209,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
                     SendMessageParams::new()
                         .direct_public_key(message.source_peer.public_key.clone())
                         .with_dht_message_type(DhtMessageType::SafStoredMessages)
+                        .with_priority(MessagePriority::Bulk)
                         .finish(),
                     stored_messages,
                 )
@@ -475,7 +476,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
             );
             let shared_secret = crypt::generate_ecdh_secret(node_identity.secret_key(), ephemeral_public_key);
             let decrypted = crypt::decrypt(&shared_secret, &header.origin_mac)?;
-            let authenticated_pk = Self::authenticate_message(&decrypted, body)?;
+            let authenticated_pk = Self::authenticate_message(&decrypted, header, body)?;
 
             trace!(
                 target: LOG_TARGET,
@@ -491,7 +492,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
             Ok((Some(authenticated_pk), envelope_body))
         } else {
             let authenticated_pk = if !header.origin_mac.is_empty() {
-                Some(Self::authenticate_message(&header.origin_mac, body)?)
+                Some(Self::authenticate_message(&header.origin_mac, header, body)?)
             } else {
                 None
             };
@@ -500,16 +501,26 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         }
     }
 
-    fn authenticate_message(origin_mac_body: &[u8], body: &[u8]) -> Result<CommsPublicKey, StoreAndForwardError> {
+    fn authenticate_message(
+        origin_mac_body: &[u8],
+        header: &DhtMessageHeader,
+        body: &[u8],
+    ) -> Result<CommsPublicKey, StoreAndForwardError>
+    {
         let origin_mac = OriginMac::decode(origin_mac_body)?;
         let public_key =
             CommsPublicKey::from_bytes(&origin_mac.public_key).map_err(|_| StoreAndForwardError::InvalidOriginMac)?;
 
-        if signature::verify(&public_key, &origin_mac.signature, body) {
-            Ok(public_key)
-        } else {
-            Err(StoreAndForwardError::InvalidOriginMac)
+        if !signature::verify(&public_key, &origin_mac.signature, body) {
+            return Err(StoreAndForwardError::InvalidOriginMac);
+        }
+        if let Some(timestamp) = header.timestamp {
+            let challenge = crypt::create_origin_mac_timestamp_challenge(timestamp, body);
+            if !signature::verify(&public_key, &origin_mac.timestamp_signature, &challenge) {
+                return Err(StoreAndForwardError::InvalidOriginMac);
+            }
         }
+        Ok(public_key)
     }
 }
 
@@ -262,6 +262,30 @@ impl StoreAndForwardService {
             InsertMessage(msg, reply_tx) => {
                 let public_key = msg.destination_pubkey.clone();
                 let node_id = msg.destination_node_id.clone();
+                // Messages that are already stored do not count against the quota again
+                match self.database.message_exists(msg.body_hash.clone()).await {
+                    Ok(true) => {
+                        let _ = reply_tx.send(Ok(true));
+                        return;
+                    },
+                    Ok(false) => {},
+                    Err(err) => {
+                        error!(target: LOG_TARGET, "InsertMessage failed because '{:?}'", err);
+                        let _ = reply_tx.send(Err(err.into()));
+                        return;
+                    },
+                }
+                if let Err(err) = self.check_destination_quota(&msg).await {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Refusing to store message for destination (public key = {:?}, node id = {:?}) because '{}'",
+                        public_key,
+                        node_id,
+                        err
+                    );
+                    let _ = reply_tx.send(Err(err));
+                    return;
+                }
                 match self.database.insert_message_if_unique(msg).await {
                     Ok(existed) => {
                         let pub_key = public_key
@@ -417,6 +441,21 @@ impl StoreAndForwardService {
         }
     }
 
+    async fn check_destination_quota(&self, message: &NewStoredMessage) -> SafResult<()> {
+        let (num_messages, num_bytes) = self
+            .database
+            .get_destination_usage(message.destination_pubkey.clone(), message.destination_node_id.clone())
+            .await?;
+
+        if num_messages >= self.config.saf_max_messages_per_destination ||
+            num_bytes + message.body.len() > self.config.saf_max_bytes_per_destination
+        {
+            return Err(StoreAndForwardError::DestinationQuotaExceeded);
+        }
+
+        Ok(())
+    }
+
     async fn handle_fetch_message_query(&self, query: FetchStoredMessageQuery) -> SafResult<Vec<StoredMessage>> {
         use SafResponseType::*;
         let limit = i64::try_from(self.config.saf_max_returned_messages)
@@ -495,3 +534,100 @@ fn since(period: Duration) -> NaiveDateTime {
 fn since_utc(period: Duration) -> DateTime<Utc> {
     DateTime::<Utc>::from_utc(since(period), Utc)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        outbound::mock::create_outbound_service_mock,
+        test_utils::{build_peer_manager, create_dht_actor_mock},
+    };
+    use tari_comms::test_utils::mocks::create_connectivity_mock;
+    use tari_shutdown::Shutdown;
+    use tari_test_utils::random;
+    use tokio::sync::broadcast;
+
+    fn make_message(destination_pubkey: &str, body_hash: &str) -> NewStoredMessage {
+        let mut msg = NewStoredMessage::default();
+        msg.destination_pubkey = Some(destination_pubkey.to_string());
+        msg.body_hash = body_hash.to_string();
+        msg.body = vec![0u8; 10];
+        msg
+    }
+
+    async fn insert_message(service: &mut StoreAndForwardService, msg: NewStoredMessage) -> SafResult<bool> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        service
+            .handle_request(StoreAndForwardRequest::InsertMessage(msg, reply_tx))
+            .await;
+        reply_rx.await.unwrap()
+    }
+
+    #[tokio_macros::test_basic]
+    async fn insert_message_destination_quota() {
+        let conn = DbConnection::connect_memory(random::string(8)).await.unwrap();
+        conn.migrate().await.unwrap();
+        let (dht_requester, _) = create_dht_actor_mock(1);
+        let (connectivity, _connectivity_mock) = create_connectivity_mock();
+        let (outbound_requester, _) = create_outbound_service_mock(1);
+        let (_request_tx, request_rx) = mpsc::channel(1);
+        let (_saf_response_signal_tx, saf_response_signal_rx) = mpsc::channel(1);
+        let (event_publisher, _) = broadcast::channel(1);
+        let shutdown = Shutdown::new();
+        let config = DhtConfig {
+            saf_max_messages_per_destination: 3,
+            ..Default::default()
+        };
+        let mut service = StoreAndForwardService::new(
+            config,
+            conn,
+            build_peer_manager(),
+            dht_requester,
+            connectivity,
+            outbound_requester,
+            request_rx,
+            saf_response_signal_rx,
+            event_publisher,
+            shutdown.to_signal(),
+        );
+
+        for i in 0..3 {
+            let existed = insert_message(&mut service, make_message("aa", &format!("a{}", i)))
+                .await
+                .unwrap();
+            assert!(!existed);
+        }
+
+        let err = insert_message(&mut service, make_message("aa", "a3"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StoreAndForwardError::DestinationQuotaExceeded));
+
+        // A message that is already stored is reported as such even though the destination is full
+        let existed = insert_message(&mut service, make_message("aa", "a0")).await.unwrap();
+        assert!(existed);
+
+        let existed = insert_message(&mut service, make_message("bb", "b0")).await.unwrap();
+        assert!(!existed);
+
+        // Messages for unknown destinations share a quota
+        for i in 0..3 {
+            let mut msg = make_message("", &format!("u{}", i));
+            msg.destination_pubkey = None;
+            let existed = insert_message(&mut service, msg).await.unwrap();
+            assert!(!existed);
+        }
+        let mut msg = make_message("", "u3");
+        msg.destination_pubkey = None;
+        let err = insert_message(&mut service, msg).await.unwrap_err();
+        assert!(matches!(err, StoreAndForwardError::DestinationQuotaExceeded));
+
+        let (num_messages, num_bytes) = service
+            .database
+            .get_destination_usage(Some("aa".to_string()), None)
+            .await
+            .unwrap();
+        assert_eq!(num_messages, 3);
+        assert_eq!(num_bytes, 30);
+    }
+}
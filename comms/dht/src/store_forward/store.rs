@@ -189,9 +189,21 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
 
         message.set_saf_stored(false);
         if let Some(priority) = self.get_storage_priority(&message).await? {
-            message.set_saf_stored(true);
-            let existing = self.store(priority, message.clone()).await?;
-            message.set_already_forwarded(existing);
+            match self.store(priority, message.clone()).await {
+                Ok(existing) => {
+                    message.set_saf_stored(true);
+                    message.set_already_forwarded(existing);
+                },
+                Err(StoreAndForwardError::DestinationQuotaExceeded) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Message {} not stored because the destination quota has been reached (Trace: {})",
+                        message.tag,
+                        message.dht_header.message_tag
+                    );
+                },
+                Err(err) => return Err(err),
+            }
         }
 
         trace!(
@@ -110,7 +110,12 @@ impl DhtActorMock {
         use DhtRequest::*;
         self.state.inc_call_count();
         match req {
-            SendJoin => {},
+            SendJoin(reply_tx) => {
+                let _ = reply_tx.send(Ok(Default::default()));
+            },
+            SendJoinAndWait(_, reply_tx) => {
+                let _ = reply_tx.send(Ok(0));
+            },
             MsgHashCacheInsert(_, reply_tx) => {
                 let v = self.state.signature_cache_insert.load(Ordering::SeqCst);
                 reply_tx.send(v).unwrap();
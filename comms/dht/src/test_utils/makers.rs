@@ -20,6 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 use crate::{
+    consts::DHT_ENVELOPE_HEADER_VERSION,
     crypt,
     envelope::{DhtMessageFlags, DhtMessageHeader, NodeDestination},
     inbound::DhtInboundMessage,
@@ -39,7 +40,7 @@ use tari_comms::{
 };
 use tari_crypto::{
     keys::PublicKey,
-    tari_utilities::{message_format::MessageFormat, ByteArray},
+    tari_utilities::{epoch_time::EpochTime, message_format::MessageFormat, ByteArray},
 };
 use tari_storage::lmdb_store::{LMDBBuilder, LMDBConfig};
 use tari_test_utils::{paths::create_temporary_data_path, random};
@@ -77,12 +78,13 @@ pub fn make_dht_header(
     trace: MessageTag,
 ) -> DhtMessageHeader
 {
+    let timestamp = EpochTime::now();
     DhtMessageHeader {
-        version: 0,
+        version: DHT_ENVELOPE_HEADER_VERSION,
         destination: NodeDestination::Unknown,
         ephemeral_public_key: if flags.is_encrypted() { Some(e_pk.clone()) } else { None },
         origin_mac: if include_origin {
-            make_valid_origin_mac(node_identity, &e_sk, message, flags)
+            make_valid_origin_mac(node_identity, &e_sk, Some(timestamp), message, flags)
         } else {
             Vec::new()
         },
@@ -91,12 +93,14 @@ pub fn make_dht_header(
         flags,
         message_tag: trace,
         expires: None,
+        timestamp: Some(timestamp),
     }
 }
 
 pub fn make_valid_origin_mac(
     node_identity: &NodeIdentity,
     e_sk: &CommsSecretKey,
+    timestamp: Option<EpochTime>,
     body: &[u8],
     flags: DhtMessageFlags,
 ) -> Vec<u8>
@@ -107,6 +111,18 @@ pub fn make_valid_origin_mac(
             .unwrap()
             .to_binary()
             .unwrap(),
+        timestamp_signature: timestamp
+            .map(|timestamp| {
+                signature::sign(
+                    &mut OsRng,
+                    node_identity.secret_key().clone(),
+                    crypt::create_origin_mac_timestamp_challenge(timestamp, body),
+                )
+                .unwrap()
+                .to_binary()
+                .unwrap()
+            })
+            .unwrap_or_default(),
     };
     let body = mac.to_encoded_bytes();
     if flags.is_encrypted() {
@@ -197,5 +213,6 @@ pub fn create_outbound_message(body: &[u8]) -> DhtOutboundMessage {
         origin_mac: None,
         is_broadcast: false,
         expires: None,
+        timestamp: None,
     }
 }
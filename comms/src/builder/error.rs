@@ -50,4 +50,6 @@ pub enum CommsBuilderError {
     CommsProtocolExtensionError(#[from] ProtocolExtensionError),
     #[error("Failed to initialize tor hidden service: {0}")]
     HiddenServiceControllerError(#[from] HiddenServiceControllerError),
+    #[error("Invalid comms configuration: {0}")]
+    InvalidConfig(String),
 }
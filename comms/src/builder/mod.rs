@@ -46,13 +46,13 @@ use crate::{
     connection_manager::{ConnectionManagerConfig, ConnectionManagerRequester},
     connectivity::{ConnectivityConfig, ConnectivityRequester},
     multiaddr::Multiaddr,
-    peer_manager::{NodeIdentity, PeerManager},
+    peer_manager::{NodeId, NodeIdentity, PeerManager},
     protocol::ProtocolExtensions,
     tor,
     types::CommsDatabase,
 };
 use futures::channel::mpsc;
-use std::{fs::File, sync::Arc};
+use std::{fs::File, sync::Arc, time::Duration};
 use tari_shutdown::ShutdownSignal;
 use tokio::sync::broadcast;
 
@@ -143,6 +143,32 @@ impl CommsBuilder {
         self
     }
 
+    /// The maximum number of outbound connection attempts that will be in progress at the same time. Once this limit
+    /// is reached, new dials are queued until another dial completes. Must be greater than zero.
+    pub fn with_max_outbound_connections(mut self, max_outbound_connections: usize) -> Self {
+        self.connection_manager_config.max_outbound_connections = max_outbound_connections;
+        self
+    }
+
+    /// The maximum time to wait for open substreams to complete when the node is shutting down. Connections are closed
+    /// once all substreams have completed or the timeout has elapsed.
+    pub fn with_shutdown_drain_timeout(mut self, timeout: Duration) -> Self {
+        self.connection_manager_config.shutdown_drain_timeout = timeout;
+        self
+    }
+
+    /// Only accept inbound connections from these peers. An empty allow list allows all peers.
+    pub fn with_peer_allowlist(mut self, peer_allowlist: Vec<NodeId>) -> Self {
+        self.connection_manager_config.peer_allowlist = peer_allowlist;
+        self
+    }
+
+    /// Always refuse inbound connections from these peers, regardless of the allow list.
+    pub fn with_peer_denylist(mut self, peer_denylist: Vec<NodeId>) -> Self {
+        self.connection_manager_config.peer_denylist = peer_denylist;
+        self
+    }
+
     /// The number of dial attempts to make before giving up.
     pub fn with_max_dial_attempts(mut self, max_dial_attempts: usize) -> Self {
         self.connection_manager_config.max_dial_attempts = max_dial_attempts;
@@ -203,6 +229,11 @@ impl CommsBuilder {
             .shutdown_signal
             .take()
             .ok_or_else(|| CommsBuilderError::ShutdownSignalNotSet)?;
+        if self.connection_manager_config.max_outbound_connections == 0 {
+            return Err(CommsBuilderError::InvalidConfig(
+                "max_outbound_connections must be greater than zero".to_string(),
+            ));
+        }
 
         let peer_manager = self.make_peer_manager()?;
 
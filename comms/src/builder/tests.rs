@@ -22,7 +22,7 @@
 
 use crate::{
     backoff::ConstantBackoff,
-    builder::CommsBuilder,
+    builder::{CommsBuilder, CommsBuilderError},
     connection_manager::ConnectionManagerEvent,
     memsocket,
     message::{InboundMessage, OutboundMessage},
@@ -366,6 +366,20 @@ async fn peer_to_peer_messaging_simultaneous() {
     comms_node2.wait_until_shutdown().await;
 }
 
+#[test]
+fn zero_max_outbound_connections_is_rejected() {
+    let shutdown = Shutdown::new();
+    let err = CommsBuilder::new()
+        .with_shutdown_signal(shutdown.to_signal())
+        .with_peer_storage(HashmapDatabase::new(), None)
+        .with_node_identity(build_node_identity(PeerFeatures::COMMUNICATION_NODE))
+        .with_max_outbound_connections(0)
+        .build()
+        .err()
+        .unwrap();
+    unpack_enum!(CommsBuilderError::InvalidConfig(_msg) = err);
+}
+
 fn has_unique_elements<T>(iter: T) -> bool
 where
     T: IntoIterator,
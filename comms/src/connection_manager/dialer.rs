@@ -55,7 +55,7 @@ use log::*;
 use std::{collections::HashMap, sync::Arc, time::Duration};
 use tari_crypto::tari_utilities::hex::Hex;
 use tari_shutdown::{Shutdown, ShutdownSignal};
-use tokio::time;
+use tokio::{sync::Semaphore, time};
 
 const LOG_TARGET: &str = "comms::connection_manager::dialer";
 
@@ -85,6 +85,7 @@ pub struct Dialer<TTransport, TBackoff> {
     shutdown: Option<ShutdownSignal>,
     pending_dial_requests: HashMap<NodeId, Vec<oneshot::Sender<Result<PeerConnection, ConnectionManagerError>>>>,
     our_supported_protocols: Vec<ProtocolId>,
+    dial_permits: Arc<Semaphore>,
 }
 
 impl<TTransport, TBackoff> Dialer<TTransport, TBackoff>
@@ -106,6 +107,7 @@ where
         shutdown: ShutdownSignal,
     ) -> Self
     {
+        let dial_permits = Arc::new(Semaphore::new(config.max_outbound_connections));
         Self {
             config,
             node_identity,
@@ -119,6 +121,7 @@ where
             shutdown: Some(shutdown),
             pending_dial_requests: Default::default(),
             our_supported_protocols: Vec::new(),
+            dial_permits,
         }
     }
 
@@ -134,7 +137,10 @@ where
             .shutdown
             .take()
             .expect("Establisher initialized without a shutdown");
-        debug!(target: LOG_TARGET, "Connection dialer started");
+        debug!(
+            target: LOG_TARGET,
+            "Connection dialer started (max outbound connections: {})", self.config.max_outbound_connections
+        );
         loop {
             futures::select! {
                 request = self.request_rx.select_next_some() => self.handle_request(&mut pending_dials, request),
@@ -212,6 +218,7 @@ where
                 );
                 self.notify_connection_manager(ConnectionManagerEvent::PeerConnectFailed(
                     Box::new(node_id.clone()),
+                    err.connect_failure_reason(),
                     err.clone(),
                 ))
                 .await
@@ -283,8 +290,34 @@ where
         let user_agent = self.config.user_agent.clone();
         let noise_config = self.noise_config.clone();
         let allow_test_addresses = self.config.allow_test_addresses;
+        let substream_negotiation_timeout = self.config.substream_negotiation_timeout;
+
+        if self.dial_permits.available_permits() == 0 {
+            debug!(
+                target: LOG_TARGET,
+                "Dial for peer '{}' queued because {} outbound connection attempt(s) are in progress",
+                dial_state.peer.node_id.short_str(),
+                self.config.max_outbound_connections
+            );
+        }
+        let dial_permits = self.dial_permits.clone();
 
         let dial_fut = async move {
+            // Held until the dial completes, so that at most `max_outbound_connections` dials are in progress. A dial
+            // that is cancelled while it is queued does not wait for a permit.
+            let acquire_permit = dial_permits.acquire_owned();
+            pin_mut!(acquire_permit);
+            let _permit = match future::select(acquire_permit, dial_state.get_cancel_signal()).await {
+                Either::Left((permit, _)) => permit,
+                Either::Right(_) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Queued dial for peer '{}' cancelled",
+                        dial_state.peer.node_id.short_str()
+                    );
+                    return (dial_state, Err(ConnectionManagerError::DialCancelled));
+                },
+            };
             let (dial_state, dial_result) =
                 Self::dial_peer_with_retry(dial_state, noise_config, transport, backoff, max_attempts).await;
 
@@ -310,6 +343,7 @@ where
                         supported_protocols,
                         user_agent,
                         allow_test_addresses,
+                        substream_negotiation_timeout,
                         cancel_signal,
                     )
                     .await;
@@ -350,6 +384,7 @@ where
         our_supported_protocols: Vec<ProtocolId>,
         user_agent: String,
         allow_test_addresses: bool,
+        substream_negotiation_timeout: Duration,
         cancel_signal: ShutdownSignal,
     ) -> Result<PeerConnection, ConnectionManagerError>
     {
@@ -423,6 +458,7 @@ where
             conn_man_notifier,
             our_supported_protocols,
             their_supported_protocols,
+            substream_negotiation_timeout,
         )
     }
 
@@ -462,9 +498,13 @@ where
                         },
                         // Inflight dial was cancelled
                         (state, Err(ConnectionManagerError::DialCancelled)) => break (state, Err(ConnectionManagerError::DialCancelled)),
-                        (state, Err(_err)) => {
+                        // Retrying will not help if there are no addresses to dial
+                        (state, Err(ConnectionManagerError::PeerHasNoAddresses)) => break (state, Err(ConnectionManagerError::PeerHasNoAddresses)),
+                        (state, Err(err)) => {
                             if state.num_attempts() >= max_attempts {
-                                break (state, Err(ConnectionManagerError::ConnectFailedMaximumAttemptsReached));
+                                debug!(target: LOG_TARGET, "Dial failed for peer '{}' after the maximum of {} attempt(s)", state.peer.node_id.short_str(), max_attempts);
+                                // Report why the last attempt failed rather than only that we gave up
+                                break (state, Err(err));
                             }
 
                             // Put the dial state and transport back for the retry
@@ -494,8 +534,13 @@ where
         Result<(NoiseSocket<TTransport::Output>, Multiaddr), ConnectionManagerError>,
     )
     {
+        if dial_state.peer.addresses.is_empty() {
+            return (dial_state, Err(ConnectionManagerError::PeerHasNoAddresses));
+        }
+
         let mut addr_iter = dial_state.peer.addresses.iter();
         let cancel_signal = dial_state.get_cancel_signal();
+        let mut last_err = None;
         loop {
             let result = match addr_iter.next() {
                 Some(address) => {
@@ -544,6 +589,7 @@ where
                                 dial_state.peer.node_id.short_str(),
                                 err,
                             );
+                            last_err = Some(err);
                             // Try the next address
                             continue;
                         },
@@ -563,8 +609,10 @@ where
                         },
                     }
                 },
-                // No more addresses to try - returning failure
-                None => Err(ConnectionManagerError::DialConnectFailedAllAddresses),
+                // No more addresses to try - returning the failure of the last address
+                None => Err(last_err
+                    .take()
+                    .unwrap_or(ConnectionManagerError::DialConnectFailedAllAddresses)),
             };
 
             drop(addr_iter);
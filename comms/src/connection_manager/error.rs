@@ -26,6 +26,7 @@ use crate::{
     protocol::{IdentityProtocolError, ProtocolError},
 };
 use futures::channel::mpsc;
+use std::fmt;
 use thiserror::Error;
 
 #[derive(Debug, Error, Clone)]
@@ -44,8 +45,6 @@ pub enum ConnectionManagerError {
     DialReplyChannelClosed,
     #[error("Failed to connect on all addresses for peer")]
     DialConnectFailedAllAddresses,
-    #[error("Failed to connect to peer within the maximum number of attempts")]
-    ConnectFailedMaximumAttemptsReached,
     #[error("Yamux connection error: {0}")]
     YamuxConnectionError(String),
     #[error("Failed to perform yamux upgrade on socket: {0}")]
@@ -68,6 +67,10 @@ pub enum ConnectionManagerError {
     PeerIdentityInvalidNodeId,
     #[error("Peer is banned, denying connection")]
     PeerBanned,
+    #[error("Peer is on the deny list, denying connection")]
+    PeerDenied,
+    #[error("Peer is not on the allow list, denying connection")]
+    PeerNotAllowed,
     #[error("Unable to parse any of the network addresses offered by the connecting peer")]
     PeerIdentityNoValidAddresses,
     #[error("Identity protocol failed: {0}")]
@@ -80,6 +83,63 @@ pub enum ConnectionManagerError {
     WireFormatSendFailed,
     #[error("Noise protocol handshake timed out")]
     NoiseProtocolTimeout,
+    #[error("The peer has no addresses to dial")]
+    PeerHasNoAddresses,
+}
+
+impl ConnectionManagerError {
+    /// Returns the `ConnectFailureReason` that categorises this error when it causes a peer connection to fail
+    pub fn connect_failure_reason(&self) -> ConnectFailureReason {
+        use ConnectionManagerError::*;
+        match self {
+            NoiseProtocolTimeout => ConnectFailureReason::DialTimeout,
+            PeerHasNoAddresses | PeerIdentityNoValidAddresses | InvalidMultiaddr(_) => ConnectFailureReason::NoAddress,
+            NoiseError(_) |
+            IdentityProtocolError(_) |
+            DialedPublicKeyMismatch |
+            InvalidStaticPublicKey |
+            PeerIdentityInvalidNodeId |
+            YamuxUpgradeFailure(_) |
+            WireFormatSendFailed => ConnectFailureReason::HandshakeFailed,
+            PeerBanned | PeerDenied | PeerNotAllowed | PeerNotPersisted => ConnectFailureReason::Rejected,
+            TransportError(_) | DialConnectFailedAllAddresses | YamuxConnectionError(_) => {
+                ConnectFailureReason::TransportError
+            },
+            DialCancelled => ConnectFailureReason::Cancelled,
+            PeerManagerError(_) |
+            PeerConnectionError(_) |
+            SendToActorFailed |
+            ActorRequestCanceled |
+            DialReplyChannelClosed |
+            EstablisherChannelError |
+            IncomingListenerStreamClosed => ConnectFailureReason::Internal,
+        }
+    }
+}
+
+/// Machine-readable reason code for a failed peer connection, included in `PeerConnectFailed` events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConnectFailureReason {
+    /// The peer could not be reached within the allowed time or number of attempts
+    DialTimeout,
+    /// The peer has no usable addresses
+    NoAddress,
+    /// The connection was established but the noise, identity or multiplexer upgrade failed
+    HandshakeFailed,
+    /// The connection was refused by local policy (e.g. the peer is banned)
+    Rejected,
+    /// The underlying transport failed to connect
+    TransportError,
+    /// The dial was cancelled before it completed
+    Cancelled,
+    /// An internal error occurred that does not reflect on the peer
+    Internal,
+}
+
+impl fmt::Display for ConnectFailureReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
 }
 
 impl From<yamux::ConnectionError> for ConnectionManagerError {
@@ -110,4 +170,56 @@ pub enum PeerConnectionError {
     InternalRequestSendFailed(#[from] mpsc::SendError),
     #[error("Protocol error: {0}")]
     ProtocolError(#[from] ProtocolError),
+    #[error("Protocol negotiation timed out")]
+    ProtocolNegotiationTimeout,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn connect_failure_reason() {
+        use ConnectFailureReason::*;
+        let cases = vec![
+            (ConnectionManagerError::NoiseProtocolTimeout, DialTimeout),
+            (ConnectionManagerError::PeerHasNoAddresses, NoAddress),
+            (ConnectionManagerError::PeerIdentityNoValidAddresses, NoAddress),
+            (ConnectionManagerError::InvalidMultiaddr("/foo".to_string()), NoAddress),
+            (ConnectionManagerError::NoiseError("bad".to_string()), HandshakeFailed),
+            (ConnectionManagerError::DialedPublicKeyMismatch, HandshakeFailed),
+            (ConnectionManagerError::InvalidStaticPublicKey, HandshakeFailed),
+            (ConnectionManagerError::PeerIdentityInvalidNodeId, HandshakeFailed),
+            (
+                ConnectionManagerError::YamuxUpgradeFailure("bad".to_string()),
+                HandshakeFailed,
+            ),
+            (ConnectionManagerError::WireFormatSendFailed, HandshakeFailed),
+            (ConnectionManagerError::PeerBanned, Rejected),
+            (ConnectionManagerError::PeerDenied, Rejected),
+            (ConnectionManagerError::PeerNotAllowed, Rejected),
+            (ConnectionManagerError::PeerNotPersisted, Rejected),
+            (
+                ConnectionManagerError::TransportError("refused".to_string()),
+                TransportError,
+            ),
+            (ConnectionManagerError::DialConnectFailedAllAddresses, TransportError),
+            (
+                ConnectionManagerError::YamuxConnectionError("bad".to_string()),
+                TransportError,
+            ),
+            (ConnectionManagerError::DialCancelled, Cancelled),
+            (ConnectionManagerError::SendToActorFailed, Internal),
+            (ConnectionManagerError::EstablisherChannelError, Internal),
+        ];
+
+        for (err, expected) in cases {
+            assert_eq!(
+                err.connect_failure_reason(),
+                expected,
+                "Unexpected reason for {:?}",
+                err
+            );
+        }
+    }
 }
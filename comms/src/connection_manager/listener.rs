@@ -34,7 +34,7 @@ use crate::{
     multiaddr::Multiaddr,
     multiplexing::Yamux,
     noise::NoiseConfig,
-    peer_manager::{NodeIdentity, PeerFeatures},
+    peer_manager::{NodeId, NodeIdentity, PeerFeatures},
     protocol::ProtocolId,
     runtime,
     transports::Transport,
@@ -219,6 +219,9 @@ where
                         our_supported_protocols,
                         user_agent,
                         allow_test_addresses,
+                        config.substream_negotiation_timeout,
+                        &config.peer_allowlist,
+                        &config.peer_denylist,
                     )
                     .await;
 
@@ -300,6 +303,9 @@ where
         our_supported_protocols: Vec<ProtocolId>,
         user_agent: String,
         allow_test_addresses: bool,
+        substream_negotiation_timeout: Duration,
+        peer_allowlist: &[NodeId],
+        peer_denylist: &[NodeId],
     ) -> Result<PeerConnection, ConnectionManagerError>
     {
         static CONNECTION_DIRECTION: ConnectionDirection = ConnectionDirection::Inbound;
@@ -319,6 +325,14 @@ where
             .get_remote_public_key()
             .ok_or_else(|| ConnectionManagerError::InvalidStaticPublicKey)?;
 
+        // The peer's NodeId is only known once it has authenticated its static key, check the allow and deny lists
+        // before any protocols are negotiated
+        Self::check_peer_access(
+            &NodeId::from_public_key(&authenticated_public_key),
+            peer_allowlist,
+            peer_denylist,
+        )?;
+
         // Check if we know the peer and if it is banned
         let known_peer = common::find_unbanned_peer(&peer_manager, &authenticated_public_key).await?;
 
@@ -376,9 +390,35 @@ where
             conn_man_notifier,
             our_supported_protocols,
             their_supported_protocols,
+            substream_negotiation_timeout,
         )
     }
 
+    fn check_peer_access(
+        node_id: &NodeId,
+        peer_allowlist: &[NodeId],
+        peer_denylist: &[NodeId],
+    ) -> Result<(), ConnectionManagerError>
+    {
+        if peer_denylist.contains(node_id) {
+            debug!(
+                target: LOG_TARGET,
+                "Refusing inbound connection from peer '{}' because it is on the deny list",
+                node_id.short_str()
+            );
+            return Err(ConnectionManagerError::PeerDenied);
+        }
+        if !peer_allowlist.is_empty() && !peer_allowlist.contains(node_id) {
+            debug!(
+                target: LOG_TARGET,
+                "Refusing inbound connection from peer '{}' because it is not on the allow list",
+                node_id.short_str()
+            );
+            return Err(ConnectionManagerError::PeerNotAllowed);
+        }
+        Ok(())
+    }
+
     async fn listen(&mut self) -> Result<(TTransport::Listener, Multiaddr), ConnectionManagerError> {
         let listener_address = mem::replace(&mut self.config.listener_address, Multiaddr::empty());
         debug!(target: LOG_TARGET, "Attempting to listen on {}", listener_address);
@@ -22,9 +22,9 @@
 
 use super::{
     dialer::{Dialer, DialerRequest},
-    error::ConnectionManagerError,
+    error::{ConnectFailureReason, ConnectionManagerError},
     listener::PeerListener,
-    peer_connection::PeerConnection,
+    peer_connection::{ConnectionId, PeerConnection},
     requester::ConnectionManagerRequest,
 };
 use crate::{
@@ -43,27 +43,33 @@ use futures::{
     stream::Fuse,
     AsyncRead,
     AsyncWrite,
+    FutureExt,
     SinkExt,
     StreamExt,
 };
 use log::*;
 use multiaddr::Multiaddr;
-use std::{fmt, sync::Arc};
+use std::{cmp, collections::HashMap, fmt, sync::Arc};
 use tari_shutdown::{Shutdown, ShutdownSignal};
 use time::Duration;
-use tokio::{sync::broadcast, task, time};
+use tokio::{
+    sync::broadcast,
+    task,
+    time::{self, Instant},
+};
 
 const LOG_TARGET: &str = "comms::connection_manager::manager";
 
 const EVENT_CHANNEL_SIZE: usize = 32;
 const DIALER_REQUEST_CHANNEL_SIZE: usize = 32;
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(50);
 
 #[derive(Debug)]
 pub enum ConnectionManagerEvent {
     // Peer connection
     PeerConnected(PeerConnection),
-    PeerDisconnected(Box<NodeId>),
-    PeerConnectFailed(Box<NodeId>, ConnectionManagerError),
+    PeerDisconnected(ConnectionId, Box<NodeId>),
+    PeerConnectFailed(Box<NodeId>, ConnectFailureReason, ConnectionManagerError),
     PeerInboundConnectFailed(ConnectionManagerError),
 
     // Listener
@@ -79,8 +85,10 @@ impl fmt::Display for ConnectionManagerEvent {
         use ConnectionManagerEvent::*;
         match self {
             PeerConnected(conn) => write!(f, "PeerConnected({})", conn),
-            PeerDisconnected(node_id) => write!(f, "PeerDisconnected({})", node_id.short_str()),
-            PeerConnectFailed(node_id, err) => write!(f, "PeerConnectFailed({}, {:?})", node_id.short_str(), err),
+            PeerDisconnected(id, node_id) => write!(f, "PeerDisconnected({}, id={})", node_id.short_str(), id),
+            PeerConnectFailed(node_id, reason, err) => {
+                write!(f, "PeerConnectFailed({}, {}, {:?})", node_id.short_str(), reason, err)
+            },
             PeerInboundConnectFailed(err) => write!(f, "PeerInboundConnectFailed({:?})", err),
             Listening(addr) => write!(f, "Listening({})", addr),
             ListenFailed(err) => write!(f, "ListenFailed({:?})", err),
@@ -104,6 +112,13 @@ pub struct ConnectionManagerConfig {
     /// The maximum number of connection tasks that will be spawned at the same time. Once this limit is reached, peers
     /// attempting to connect will have to wait for another connection attempt to complete. Default: 20
     pub max_simultaneous_inbound_connects: usize,
+    /// The maximum number of outbound connection attempts that will be in progress at the same time. Once this limit
+    /// is reached, new dials are queued until another dial completes. Default: 50
+    pub max_outbound_connections: usize,
+    /// If not empty, inbound connections are only accepted from these peers. Default: empty (all peers are allowed)
+    pub peer_allowlist: Vec<NodeId>,
+    /// Inbound connections from these peers are always refused, even if they are on the allow list. Default: empty
+    pub peer_denylist: Vec<NodeId>,
     /// Set to true to allow peers to send loopback, local-link and other addresses normally not considered valid for
     /// peer-to-peer comms. Default: false
     pub allow_test_addresses: bool,
@@ -115,6 +130,12 @@ pub struct ConnectionManagerConfig {
     pub liveness_cidr_allowlist: Vec<cidr::AnyIpCidr>,
     /// The user agent string for this node
     pub user_agent: String,
+    /// The maximum time to wait for protocol negotiation to complete on a new substream before the substream is
+    /// closed. Default: 10s
+    pub substream_negotiation_timeout: Duration,
+    /// The maximum time to wait, once the shutdown signal is triggered, for open substreams to complete before all
+    /// peer connections are closed. New inbound substreams are refused while draining. Default: 0s (close immediately)
+    pub shutdown_drain_timeout: Duration,
 }
 
 impl Default for ConnectionManagerConfig {
@@ -125,6 +146,9 @@ impl Default for ConnectionManagerConfig {
                 .expect("DEFAULT_LISTENER_ADDRESS is malformed"),
             max_dial_attempts: 3,
             max_simultaneous_inbound_connects: 20,
+            max_outbound_connections: 50,
+            peer_allowlist: Vec::new(),
+            peer_denylist: Vec::new(),
             #[cfg(not(test))]
             allow_test_addresses: false,
             // This must always be true for internal crate tests
@@ -134,6 +158,8 @@ impl Default for ConnectionManagerConfig {
             time_to_first_byte: Duration::from_secs(7),
             liveness_cidr_allowlist: vec![cidr::AnyIpCidr::V4("127.0.0.1/32".parse().unwrap())],
             user_agent: Default::default(),
+            substream_negotiation_timeout: Duration::from_secs(10),
+            shutdown_drain_timeout: Duration::from_secs(0),
         }
     }
 }
@@ -149,8 +175,12 @@ pub struct ConnectionManager<TTransport, TBackoff> {
     protocols: Protocols<Substream>,
     listener_address: Option<Multiaddr>,
     listening_notifiers: Vec<oneshot::Sender<Multiaddr>>,
+    /// Keyed by connection id, a peer briefly has two connections while a simultaneous dial is resolved. Connections
+    /// that are closed silently do not publish PeerDisconnected, so closed connections are pruned before this is used.
+    active_connections: HashMap<ConnectionId, PeerConnection>,
     connection_manager_events_tx: broadcast::Sender<Arc<ConnectionManagerEvent>>,
     complete_trigger: Shutdown,
+    shutdown_drain_timeout: Duration,
 }
 
 impl<TTransport, TBackoff> ConnectionManager<TTransport, TBackoff>
@@ -175,6 +205,7 @@ where
         let (internal_event_tx, internal_event_rx) = mpsc::channel(EVENT_CHANNEL_SIZE);
 
         let (dialer_tx, dialer_rx) = mpsc::channel(DIALER_REQUEST_CHANNEL_SIZE);
+        let shutdown_drain_timeout = config.shutdown_drain_timeout;
 
         let listener = PeerListener::new(
             config.clone(),
@@ -209,8 +240,10 @@ where
             listener: Some(listener),
             listener_address: None,
             listening_notifiers: Vec::new(),
+            active_connections: HashMap::new(),
             connection_manager_events_tx,
             complete_trigger: Shutdown::new(),
+            shutdown_drain_timeout,
         }
     }
 
@@ -261,6 +294,82 @@ where
                 }
             }
         }
+
+        self.drain_connections().await;
+    }
+
+    /// Wait up to `shutdown_drain_timeout` for the substreams on all active connections to complete, then close the
+    /// connections. Inbound substreams that are opened while draining are refused. The long-lived messaging substreams
+    /// are closed by the messaging protocol on shutdown, so this waits on in-flight RPC (and other protocol)
+    /// substreams.
+    async fn drain_connections(&mut self) {
+        self.prune_closed_connections();
+        if self.active_connections.is_empty() {
+            return;
+        }
+
+        let deadline = Instant::now() + self.shutdown_drain_timeout;
+        loop {
+            let num_substreams = self
+                .active_connections
+                .values()
+                .map(PeerConnection::substream_count)
+                .sum::<usize>();
+            if num_substreams == 0 {
+                debug!(target: LOG_TARGET, "All substreams have completed");
+                break;
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                if self.shutdown_drain_timeout > Duration::from_secs(0) {
+                    warn!(
+                        target: LOG_TARGET,
+                        "{} substream(s) did not complete within the shutdown drain timeout ({:.0?})",
+                        num_substreams,
+                        self.shutdown_drain_timeout
+                    );
+                }
+                break;
+            }
+
+            let mut poll_delay = time::delay_for(cmp::min(DRAIN_POLL_INTERVAL, deadline - now)).fuse();
+            futures::select! {
+                event = self.internal_event_rx.select_next_some() => {
+                    self.handle_draining_event(event);
+                },
+                _ = poll_delay => {},
+            }
+        }
+
+        for (_, mut conn) in self.active_connections.drain() {
+            if let Err(err) = conn.disconnect_silent().await {
+                debug!(
+                    target: LOG_TARGET,
+                    "Failed to close connection to peer '{}' on shutdown because '{}'",
+                    conn.peer_node_id().short_str(),
+                    err
+                );
+            }
+        }
+    }
+
+    fn handle_draining_event(&mut self, event: ConnectionManagerEvent) {
+        use ConnectionManagerEvent::*;
+        match event {
+            NewInboundSubstream(node_id, protocol, _) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Refusing inbound substream for peer '{}' speaking protocol '{}' because the node is shutting down",
+                    node_id.short_str(),
+                    String::from_utf8_lossy(&protocol)
+                );
+            },
+            PeerDisconnected(id, _) => {
+                self.active_connections.remove(&id);
+            },
+            _ => {},
+        }
     }
 
     fn run_listener(&mut self) {
@@ -304,6 +413,10 @@ where
                     self.listening_notifiers.push(reply);
                 },
             },
+            GetActiveConnections(reply) => {
+                self.prune_closed_connections();
+                let _ = reply.send(self.active_connections.values().cloned().collect());
+            },
         }
     }
 
@@ -337,6 +450,17 @@ where
                     );
                 }
             },
+            PeerConnected(conn) => {
+                self.prune_closed_connections();
+                self.active_connections.insert(conn.id(), conn.clone());
+                self.publish_event(PeerConnected(conn));
+            },
+            PeerDisconnected(id, node_id) => {
+                // Only the connection that disconnected is removed, a duplicate connection to the same peer that lost
+                // a tie break must not take the surviving connection with it
+                self.active_connections.remove(&id);
+                self.publish_event(PeerDisconnected(id, node_id));
+            },
 
             event => {
                 self.publish_event(event);
@@ -344,6 +468,12 @@ where
         }
     }
 
+    /// Remove connections that have closed without a PeerDisconnected event, e.g. connections closed with
+    /// `disconnect_silent` by the connectivity manager
+    fn prune_closed_connections(&mut self) {
+        self.active_connections.retain(|_, conn| conn.is_connected());
+    }
+
     #[inline]
     async fn send_dialer_request(&mut self, req: DialerRequest) {
         if let Err(err) = self.dialer_tx.send(req).await {
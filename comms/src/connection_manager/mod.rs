@@ -37,7 +37,7 @@ mod manager;
 pub use manager::{ConnectionManager, ConnectionManagerConfig, ConnectionManagerEvent};
 
 mod error;
-pub use error::{ConnectionManagerError, PeerConnectionError};
+pub use error::{ConnectFailureReason, ConnectionManagerError, PeerConnectionError};
 
 mod peer_connection;
 pub use peer_connection::{ConnectionId, NegotiatedSubstream, PeerConnection, PeerConnectionRequest};
@@ -50,6 +50,7 @@ use std::{
     time::{Duration, Instant},
 };
 use tari_shutdown::Shutdown;
+use tokio::time;
 
 const LOG_TARGET: &str = "comms::connection_manager::peer_connection";
 
@@ -67,6 +68,7 @@ pub fn create(
     event_notifier: mpsc::Sender<ConnectionManagerEvent>,
     our_supported_protocols: Vec<ProtocolId>,
     their_supported_protocols: Vec<ProtocolId>,
+    substream_negotiation_timeout: Duration,
 ) -> Result<PeerConnection, ConnectionManagerError>
 {
     trace!(
@@ -95,6 +97,7 @@ pub fn create(
         event_notifier,
         our_supported_protocols,
         their_supported_protocols,
+        substream_negotiation_timeout,
     );
     runtime::current().spawn(peer_actor.run());
 
@@ -281,6 +284,7 @@ struct PeerConnectionActor {
     event_notifier: mpsc::Sender<ConnectionManagerEvent>,
     our_supported_protocols: Vec<ProtocolId>,
     their_supported_protocols: Vec<ProtocolId>,
+    substream_negotiation_timeout: Duration,
     shutdown: bool,
 }
 
@@ -295,6 +299,7 @@ impl PeerConnectionActor {
         event_notifier: mpsc::Sender<ConnectionManagerEvent>,
         our_supported_protocols: Vec<ProtocolId>,
         their_supported_protocols: Vec<ProtocolId>,
+        substream_negotiation_timeout: Duration,
     ) -> Self
     {
         Self {
@@ -309,6 +314,7 @@ impl PeerConnectionActor {
             shutdown: false,
             our_supported_protocols,
             their_supported_protocols,
+            substream_negotiation_timeout,
         }
     }
 
@@ -370,9 +376,19 @@ impl PeerConnectionActor {
     }
 
     async fn handle_incoming_substream(&mut self, mut stream: Substream) -> Result<(), PeerConnectionError> {
-        let selected_protocol = ProtocolNegotiation::new(&mut stream)
-            .negotiate_protocol_inbound(&self.our_supported_protocols)
-            .await?;
+        let negotiation = time::timeout(
+            self.substream_negotiation_timeout,
+            ProtocolNegotiation::new(&mut stream).negotiate_protocol_inbound(&self.our_supported_protocols),
+        )
+        .await;
+        let selected_protocol = match negotiation {
+            Ok(result) => result?,
+            Err(_) => {
+                // Close the substream so that it does not tie up resources for a peer that never negotiates
+                let _ = stream.close().await;
+                return Err(PeerConnectionError::ProtocolNegotiationTimeout);
+            },
+        };
 
         self.notify_event(ConnectionManagerEvent::NewInboundSubstream(
             Box::new(self.peer_node_id.clone()),
@@ -400,11 +416,16 @@ impl PeerConnectionActor {
 
         let mut negotiation = ProtocolNegotiation::new(&mut stream);
 
-        let selected_protocol = if self.their_supported_protocols.contains(&protocol) {
-            negotiation.negotiate_protocol_outbound_optimistic(&protocol).await?
-        } else {
-            negotiation.negotiate_protocol_outbound(&[protocol]).await?
+        let negotiate = async {
+            if self.their_supported_protocols.contains(&protocol) {
+                negotiation.negotiate_protocol_outbound_optimistic(&protocol).await
+            } else {
+                negotiation.negotiate_protocol_outbound(&[protocol]).await
+            }
         };
+        let selected_protocol = time::timeout(self.substream_negotiation_timeout, negotiate)
+            .await
+            .map_err(|_| PeerConnectionError::ProtocolNegotiationTimeout)??;
 
         Ok(NegotiatedSubstream::new(selected_protocol, stream))
     }
@@ -447,9 +468,10 @@ impl PeerConnectionActor {
         }
 
         if !silent {
-            self.notify_event(ConnectionManagerEvent::PeerDisconnected(Box::new(
-                self.peer_node_id.clone(),
-            )))
+            self.notify_event(ConnectionManagerEvent::PeerDisconnected(
+                self.id,
+                Box::new(self.peer_node_id.clone()),
+            ))
             .await;
         }
 
@@ -38,6 +38,8 @@ pub enum ConnectionManagerRequest {
     CancelDial(NodeId),
     /// Register a oneshot to get triggered when the node is listening, or has failed to listen
     NotifyListening(oneshot::Sender<Multiaddr>),
+    /// Get a snapshot of all currently active peer connections
+    GetActiveConnections(oneshot::Sender<Vec<PeerConnection>>),
 }
 
 /// Responsible for constructing requests to the ConnectionManagerService
@@ -123,4 +125,18 @@ impl ConnectionManagerRequester {
             .map_err(|_| ConnectionManagerError::SendToActorFailed)?;
         reply_rx.await.map_err(|_| ConnectionManagerError::ActorRequestCanceled)
     }
+
+    /// Return a snapshot of the currently active peer connections.
+    ///
+    /// Event subscribers that have lagged behind the event stream (i.e. received a `RecvError::Lagged`) have missed
+    /// `PeerConnected`/`PeerDisconnected` events and can use this snapshot to reconcile their view of the active
+    /// connections.
+    pub async fn get_active_connections_snapshot(&mut self) -> Result<Vec<PeerConnection>, ConnectionManagerError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.sender
+            .send(ConnectionManagerRequest::GetActiveConnections(reply_tx))
+            .await
+            .map_err(|_| ConnectionManagerError::SendToActorFailed)?;
+        reply_rx.await.map_err(|_| ConnectionManagerError::ActorRequestCanceled)
+    }
 }
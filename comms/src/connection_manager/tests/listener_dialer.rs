@@ -28,9 +28,11 @@ use crate::{
         manager::ConnectionManagerEvent,
         ConnectionManagerConfig,
         ConnectionManagerError,
+        PeerConnection,
     },
+    memsocket::{MemoryListener, MemorySocket},
     noise::NoiseConfig,
-    peer_manager::PeerFeatures,
+    peer_manager::{NodeId, NodeIdentity, PeerFeatures},
     protocol::ProtocolId,
     runtime,
     test_utils::{node_identity::build_node_identity, test_node::build_peer_manager},
@@ -44,7 +46,7 @@ use futures::{
     StreamExt,
 };
 use multiaddr::Protocol;
-use std::{error::Error, time::Duration};
+use std::{error::Error, sync::Arc, time::Duration};
 use tari_shutdown::Shutdown;
 use tari_test_utils::unpack_enum;
 use tokio::time::timeout;
@@ -267,3 +269,192 @@ async fn banned() {
     timeout(Duration::from_secs(5), listener_fut).await.unwrap().unwrap();
     timeout(Duration::from_secs(5), dialer_fut).await.unwrap().unwrap();
 }
+
+#[runtime::test_basic]
+async fn max_outbound_connections() {
+    const MAX_OUTBOUND_CONNECTIONS: usize = 3;
+    let rt_handle = runtime::current();
+    let (event_tx, _event_rx) = mpsc::channel(10);
+    let mut shutdown = Shutdown::new();
+
+    // These peers accept connections but never respond, so dials to them remain in progress
+    let mut listeners = (0..MAX_OUTBOUND_CONNECTIONS + 2)
+        .map(|_| MemoryListener::bind(0).unwrap())
+        .collect::<Vec<_>>();
+
+    let node_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let noise_config = NoiseConfig::new(node_identity.clone());
+    let (mut request_tx, request_rx) = mpsc::channel(MAX_OUTBOUND_CONNECTIONS + 2);
+    let dialer = Dialer::new(
+        ConnectionManagerConfig {
+            max_outbound_connections: MAX_OUTBOUND_CONNECTIONS,
+            max_dial_attempts: 1,
+            ..Default::default()
+        },
+        node_identity,
+        build_peer_manager(),
+        MemoryTransport,
+        noise_config,
+        ConstantBackoff::new(Duration::from_millis(0)),
+        request_rx,
+        event_tx,
+        shutdown.to_signal(),
+    );
+
+    let dialer_fut = rt_handle.spawn(dialer.run());
+
+    let mut reply_rxs = Vec::new();
+    let mut node_ids = Vec::new();
+    for (i, listener) in listeners.iter().enumerate() {
+        let mut peer = build_node_identity(PeerFeatures::COMMUNICATION_NODE).to_peer();
+        peer.addresses = vec![format!("/memory/{}", listener.local_addr()).parse().unwrap()].into();
+        peer.set_id_for_test(i as u64 + 1);
+        node_ids.push(peer.node_id.clone());
+        let (reply_tx, reply_rx) = oneshot::channel();
+        request_tx
+            .send(DialerRequest::Dial(Box::new(peer), reply_tx))
+            .await
+            .unwrap();
+        reply_rxs.push(Some(reply_rx));
+    }
+
+    /// Returns the sockets of the listeners that were dialed, along with the index of each listener
+    async fn accept_dials(listeners: &mut [MemoryListener]) -> Vec<(usize, MemorySocket)> {
+        let mut sockets = Vec::new();
+        for (i, listener) in listeners.iter_mut().enumerate() {
+            if let Ok(Some(socket)) = timeout(Duration::from_millis(100), listener.incoming().next()).await {
+                sockets.push((i, socket.unwrap()));
+            }
+        }
+        sockets
+    }
+
+    let mut sockets = accept_dials(&mut listeners).await;
+    assert_eq!(sockets.len(), MAX_OUTBOUND_CONNECTIONS);
+
+    // Closing one of the connections fails its dial, which allows a single queued dial to proceed
+    let (closed, _) = sockets.pop().unwrap();
+    let sockets = sockets
+        .into_iter()
+        .chain(accept_dials(&mut listeners).await)
+        .collect::<Vec<_>>();
+    assert_eq!(sockets.len(), MAX_OUTBOUND_CONNECTIONS);
+
+    // A dial that is waiting for a permit is cancelled without waiting for the permit
+    let queued = (0..listeners.len())
+        .rev()
+        .find(|i| *i != closed && sockets.iter().all(|(dialed, _)| dialed != i))
+        .unwrap();
+    request_tx
+        .send(DialerRequest::CancelPendingDial(node_ids[queued].clone()))
+        .await
+        .unwrap();
+    let result = timeout(Duration::from_secs(5), reply_rxs[queued].take().unwrap())
+        .await
+        .unwrap()
+        .unwrap();
+    unpack_enum!(ConnectionManagerError::DialCancelled = result.unwrap_err());
+
+    shutdown.trigger().unwrap();
+
+    timeout(Duration::from_secs(5), dialer_fut).await.unwrap().unwrap();
+}
+
+/// Dials a listener that has the given allow and deny lists, returning the result of the dial and the first event
+/// emitted by the listener after it started listening.
+async fn dial_with_peer_access_lists(
+    dialer_identity: Arc<NodeIdentity>,
+    peer_allowlist: Vec<NodeId>,
+    peer_denylist: Vec<NodeId>,
+) -> (Result<PeerConnection, ConnectionManagerError>, ConnectionManagerEvent)
+{
+    let rt_handle = runtime::current();
+    let (listener_event_tx, mut listener_event_rx) = mpsc::channel(10);
+    let (dialer_event_tx, _dialer_event_rx) = mpsc::channel(10);
+    let mut shutdown = Shutdown::new();
+
+    let listener_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let listener = PeerListener::new(
+        ConnectionManagerConfig {
+            listener_address: "/memory/0".parse().unwrap(),
+            peer_allowlist,
+            peer_denylist,
+            ..Default::default()
+        },
+        MemoryTransport,
+        NoiseConfig::new(listener_identity.clone()),
+        listener_event_tx,
+        build_peer_manager(),
+        listener_identity.clone(),
+        shutdown.to_signal(),
+    );
+    let listener_fut = rt_handle.spawn(listener.run());
+
+    let (mut request_tx, request_rx) = mpsc::channel(1);
+    let dialer = Dialer::new(
+        ConnectionManagerConfig::default(),
+        dialer_identity.clone(),
+        build_peer_manager(),
+        MemoryTransport,
+        NoiseConfig::new(dialer_identity),
+        ConstantBackoff::new(Duration::from_millis(100)),
+        request_rx,
+        dialer_event_tx,
+        shutdown.to_signal(),
+    );
+    let dialer_fut = rt_handle.spawn(dialer.run());
+
+    unpack_enum!(ConnectionManagerEvent::Listening(address) = listener_event_rx.next().await.unwrap());
+    let mut peer = listener_identity.to_peer();
+    peer.addresses = vec![address].into();
+    peer.set_id_for_test(1);
+
+    let (reply_tx, reply_rx) = oneshot::channel();
+    request_tx
+        .send(DialerRequest::Dial(Box::new(peer), reply_tx))
+        .await
+        .unwrap();
+    let dial_result = reply_rx.await.unwrap();
+    let listener_event = listener_event_rx.next().await.unwrap();
+
+    shutdown.trigger().unwrap();
+
+    timeout(Duration::from_secs(5), listener_fut).await.unwrap().unwrap();
+    timeout(Duration::from_secs(5), dialer_fut).await.unwrap().unwrap();
+
+    (dial_result, listener_event)
+}
+
+#[runtime::test_basic]
+async fn peer_allowlist_accepts_listed_peer() {
+    let dialer_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let (dial_result, listener_event) =
+        dial_with_peer_access_lists(dialer_identity.clone(), vec![dialer_identity.node_id().clone()], vec![]).await;
+
+    dial_result.unwrap();
+    unpack_enum!(ConnectionManagerEvent::PeerConnected(conn) = listener_event);
+    assert_eq!(conn.peer_node_id(), dialer_identity.node_id());
+}
+
+#[runtime::test_basic]
+async fn peer_access_lists_reject_peer() {
+    let dialer_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let other_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+
+    // The dialer is not on the allow list
+    let (dial_result, listener_event) =
+        dial_with_peer_access_lists(dialer_identity.clone(), vec![other_identity.node_id().clone()], vec![]).await;
+    assert!(dial_result.is_err());
+    unpack_enum!(ConnectionManagerEvent::PeerInboundConnectFailed(err) = listener_event);
+    unpack_enum!(ConnectionManagerError::PeerNotAllowed = err);
+
+    // The deny list takes precedence over the allow list
+    let (dial_result, listener_event) =
+        dial_with_peer_access_lists(dialer_identity.clone(), vec![dialer_identity.node_id().clone()], vec![
+            dialer_identity.node_id().clone(),
+        ])
+        .await;
+    assert!(dial_result.is_err());
+    unpack_enum!(ConnectionManagerEvent::PeerInboundConnectFailed(err) = listener_event);
+    unpack_enum!(ConnectionManagerError::PeerDenied = err);
+}
@@ -23,7 +23,7 @@
 use crate::{
     backoff::ConstantBackoff,
     connection_manager::{
-        error::ConnectionManagerError,
+        error::{ConnectFailureReason, ConnectionManagerError},
         manager::ConnectionManagerEvent,
         ConnectionManager,
         ConnectionManagerRequester,
@@ -51,7 +51,7 @@ use futures::{
 use std::time::Duration;
 use tari_shutdown::Shutdown;
 use tari_test_utils::{collect_stream, unpack_enum};
-use tokio::{runtime::Handle, sync::broadcast};
+use tokio::{runtime::Handle, sync::broadcast, time};
 
 #[runtime::test_basic]
 async fn connect_to_nonexistent_peer() {
@@ -346,7 +346,172 @@ async fn dial_cancelled() {
         .collect::<Vec<_>>();
 
     assert_eq!(events1.len(), 1);
-    unpack_enum!(ConnectionManagerEvent::PeerConnectFailed(node_id, err) = &*events1[0]);
+    unpack_enum!(ConnectionManagerEvent::PeerConnectFailed(node_id, reason, err) = &*events1[0]);
     assert_eq!(&**node_id, node_identity2.node_id());
+    assert_eq!(*reason, ConnectFailureReason::Cancelled);
     unpack_enum!(ConnectionManagerError::DialCancelled = err);
 }
+
+#[tokio_macros::test_basic]
+async fn dial_failure_reports_the_last_attempt_error() {
+    let mut shutdown = Shutdown::new();
+
+    let node_identity1 = build_node_identity(PeerFeatures::empty());
+    // Nothing listens on this node's address
+    let node_identity2 = build_node_identity(PeerFeatures::empty());
+
+    let peer_manager1 = build_peer_manager();
+    let mut conn_man1 = build_connection_manager(
+        {
+            let mut config = TestNodeConfig {
+                node_identity: node_identity1.clone(),
+                dial_backoff_duration: Duration::from_millis(10),
+                ..Default::default()
+            };
+            config.connection_manager_config.max_dial_attempts = 2;
+            config
+        },
+        peer_manager1.clone(),
+        Default::default(),
+        shutdown.to_signal(),
+    );
+    conn_man1.wait_until_listening().await.unwrap();
+    let mut subscription1 = conn_man1.get_event_subscription();
+
+    peer_manager1.add_peer(node_identity2.to_peer()).await.unwrap();
+
+    let err = conn_man1.dial_peer(node_identity2.node_id().clone()).await.unwrap_err();
+    unpack_enum!(ConnectionManagerError::TransportError(_err) = err);
+
+    let event = subscription1.next().await.unwrap().unwrap();
+    unpack_enum!(ConnectionManagerEvent::PeerConnectFailed(node_id, reason, _err) = &*event);
+    assert_eq!(&**node_id, node_identity2.node_id());
+    assert_eq!(*reason, ConnectFailureReason::TransportError);
+
+    shutdown.trigger().unwrap();
+}
+
+#[runtime::test_basic]
+async fn active_connections_snapshot_after_lag() {
+    let shutdown = Shutdown::new();
+
+    let node_identity1 = build_node_identity(PeerFeatures::empty());
+    let peer_manager1 = build_peer_manager();
+    let (request_tx, request_rx) = mpsc::channel(1);
+    // Capacity of 1 so that the subscriber lags behind after two events
+    let (event_tx, _) = broadcast::channel(1);
+    let mut conn_man1 = ConnectionManagerRequester::new(request_tx, event_tx.clone());
+    let connection_manager = ConnectionManager::new(
+        TestNodeConfig::default().connection_manager_config,
+        MemoryTransport,
+        NoiseConfig::new(node_identity1.clone()),
+        ConstantBackoff::new(Duration::from_secs(1)),
+        request_rx,
+        node_identity1,
+        peer_manager1.clone(),
+        event_tx,
+        shutdown.to_signal(),
+    );
+    task::spawn(connection_manager.run());
+    conn_man1.wait_until_listening().await.unwrap();
+
+    let mut subscription1 = conn_man1.get_event_subscription();
+
+    let mut expected_node_ids = Vec::new();
+    for _ in 0..2 {
+        let node_identity = build_node_identity(PeerFeatures::empty());
+        let mut conn_man = build_connection_manager(
+            TestNodeConfig {
+                node_identity: node_identity.clone(),
+                ..Default::default()
+            },
+            build_peer_manager(),
+            Default::default(),
+            shutdown.to_signal(),
+        );
+        let public_address = conn_man.wait_until_listening().await.unwrap();
+        peer_manager1
+            .add_peer(Peer::new(
+                node_identity.public_key().clone(),
+                node_identity.node_id().clone(),
+                vec![public_address].into(),
+                PeerFlags::empty(),
+                PeerFeatures::COMMUNICATION_CLIENT,
+                Default::default(),
+                Default::default(),
+            ))
+            .await
+            .unwrap();
+        conn_man1.dial_peer(node_identity.node_id().clone()).await.unwrap();
+        expected_node_ids.push(node_identity.node_id().clone());
+    }
+
+    // Allow the connection manager to publish all PeerConnected events
+    time::delay_for(Duration::from_millis(100)).await;
+
+    let err = subscription1.recv().await.unwrap_err();
+    unpack_enum!(broadcast::RecvError::Lagged(_n) = err);
+
+    // The subscriber missed a PeerConnected event, the snapshot is used to recover the true set of connections
+    let snapshot = conn_man1.get_active_connections_snapshot().await.unwrap();
+    let mut node_ids = snapshot
+        .iter()
+        .map(|conn| conn.peer_node_id().clone())
+        .collect::<Vec<_>>();
+    node_ids.sort();
+    expected_node_ids.sort();
+    assert_eq!(node_ids, expected_node_ids);
+}
+
+#[runtime::test_basic]
+async fn active_connections_snapshot_excludes_silently_closed_connections() {
+    let shutdown = Shutdown::new();
+
+    let node_identity1 = build_node_identity(PeerFeatures::empty());
+    let peer_manager1 = build_peer_manager();
+    let mut conn_man1 = build_connection_manager(
+        TestNodeConfig {
+            node_identity: node_identity1.clone(),
+            ..Default::default()
+        },
+        peer_manager1.clone(),
+        Default::default(),
+        shutdown.to_signal(),
+    );
+    conn_man1.wait_until_listening().await.unwrap();
+
+    let node_identity2 = build_node_identity(PeerFeatures::empty());
+    let mut conn_man2 = build_connection_manager(
+        TestNodeConfig {
+            node_identity: node_identity2.clone(),
+            ..Default::default()
+        },
+        build_peer_manager(),
+        Default::default(),
+        shutdown.to_signal(),
+    );
+    let public_address2 = conn_man2.wait_until_listening().await.unwrap();
+    peer_manager1
+        .add_peer(Peer::new(
+            node_identity2.public_key().clone(),
+            node_identity2.node_id().clone(),
+            vec![public_address2].into(),
+            PeerFlags::empty(),
+            PeerFeatures::COMMUNICATION_CLIENT,
+            Default::default(),
+            Default::default(),
+        ))
+        .await
+        .unwrap();
+
+    let mut conn = conn_man1.dial_peer(node_identity2.node_id().clone()).await.unwrap();
+    let snapshot = conn_man1.get_active_connections_snapshot().await.unwrap();
+    assert_eq!(snapshot.len(), 1);
+
+    // A silent disconnect does not publish PeerDisconnected
+    conn.disconnect_silent().await.unwrap();
+    time::delay_for(Duration::from_millis(100)).await;
+
+    let snapshot = conn_man1.get_active_connections_snapshot().await.unwrap();
+    assert!(snapshot.is_empty());
+}
@@ -22,3 +22,4 @@
 
 mod listener_dialer;
 mod manager;
+mod peer_connection;
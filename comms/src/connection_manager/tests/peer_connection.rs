@@ -0,0 +1,65 @@
+// Copyright 2021, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    connection_manager::{peer_connection, ConnectionDirection},
+    peer_manager::{NodeId, PeerFeatures},
+    protocol::ProtocolId,
+    runtime,
+    test_utils::transport,
+};
+use futures::{channel::mpsc, AsyncReadExt};
+use multiaddr::Multiaddr;
+use std::time::Duration;
+use tokio::time::timeout;
+
+#[runtime::test_basic]
+async fn substream_negotiation_timeout() {
+    let (_, muxer_out, muxer_in) = transport::build_multiplexed_connections().await;
+    let (event_tx, mut event_rx) = mpsc::channel(1);
+    let _conn = peer_connection::create(
+        muxer_in,
+        Multiaddr::empty(),
+        NodeId::default(),
+        PeerFeatures::COMMUNICATION_NODE,
+        ConnectionDirection::Inbound,
+        event_tx,
+        vec![ProtocolId::from_static(b"/tari/test/1")],
+        vec![],
+        Duration::from_millis(100),
+    )
+    .unwrap();
+
+    // Open a substream but never send a protocol id
+    let mut substream = muxer_out.get_yamux_control().open_stream().await.unwrap();
+
+    // The substream is closed once the negotiation times out
+    let mut buf = [0u8; 1];
+    let n = timeout(Duration::from_secs(5), substream.read(&mut buf))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(n, 0);
+
+    // No substream was negotiated
+    assert!(event_rx.try_next().is_err());
+}
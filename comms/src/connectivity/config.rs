@@ -42,6 +42,12 @@ pub struct ConnectivityConfig {
     /// The length of time to wait before disconnecting a connection that failed tie breaking.
     /// Default: 1s
     pub connection_tie_break_linger: Duration,
+    /// The length of time that a measured peer latency is considered current. Older measurements are discarded.
+    /// Default: 10 mins
+    pub peer_latency_ttl: Duration,
+    /// The number of ban requests a trusted seed peer is given before it is banned.
+    /// Default: 3
+    pub trusted_seed_ban_tolerance: usize,
 }
 
 impl Default for ConnectivityConfig {
@@ -53,6 +59,8 @@ impl Default for ConnectivityConfig {
             is_connection_reaping_enabled: true,
             max_failures_mark_offline: 1,
             connection_tie_break_linger: Duration::from_secs(2),
+            peer_latency_ttl: Duration::from_secs(10 * 60),
+            trusted_seed_ban_tolerance: 3,
         }
     }
 }
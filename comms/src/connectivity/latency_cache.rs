@@ -0,0 +1,88 @@
+// Copyright 2021, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::peer_manager::NodeId;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// Records the last measured round-trip latency of each peer. Latencies that are older than the configured TTL are
+/// no longer returned.
+#[derive(Debug, Clone)]
+pub struct PeerLatencyCache {
+    latencies: HashMap<NodeId, (Duration, Instant)>,
+    ttl: Duration,
+}
+
+impl PeerLatencyCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            latencies: HashMap::new(),
+            ttl,
+        }
+    }
+
+    /// Records the latency measured for the given peer, replacing any previous measurement
+    pub fn record(&mut self, node_id: NodeId, latency: Duration) {
+        self.latencies.insert(node_id, (latency, Instant::now()));
+    }
+
+    /// Returns the last measured latency for the given peer, or None if no latency has been measured within the TTL
+    pub fn get(&self, node_id: &NodeId) -> Option<Duration> {
+        self.latencies
+            .get(node_id)
+            .filter(|(_, measured_at)| measured_at.elapsed() <= self.ttl)
+            .map(|(latency, _)| *latency)
+    }
+
+    /// Removes all latencies that were measured longer than the TTL ago
+    pub fn remove_expired(&mut self) {
+        let ttl = self.ttl;
+        self.latencies
+            .retain(|_, (_, measured_at)| measured_at.elapsed() <= ttl);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn peer_latency_cache() {
+        let mut cache = PeerLatencyCache::new(Duration::from_millis(50));
+        let node_id = NodeId::default();
+        assert!(cache.get(&node_id).is_none());
+
+        cache.record(node_id.clone(), Duration::from_millis(120));
+        assert_eq!(cache.get(&node_id), Some(Duration::from_millis(120)));
+        cache.record(node_id.clone(), Duration::from_millis(80));
+        assert_eq!(cache.get(&node_id), Some(Duration::from_millis(80)));
+
+        thread::sleep(Duration::from_millis(60));
+        assert!(cache.get(&node_id).is_none());
+        assert_eq!(cache.latencies.len(), 1);
+        cache.remove_expired();
+        assert!(cache.latencies.is_empty());
+    }
+}
@@ -24,6 +24,7 @@ use super::{
     connection_pool::{ConnectionPool, ConnectionStatus},
     connection_stats::PeerConnectionStats,
     error::ConnectivityError,
+    latency_cache::PeerLatencyCache,
     requester::{ConnectivityEvent, ConnectivityRequest},
     selection::ConnectivitySelection,
 };
@@ -34,7 +35,7 @@ use crate::{
         ConnectionManagerEvent,
         ConnectionManagerRequester,
     },
-    peer_manager::NodeId,
+    peer_manager::{NodeId, PeerFlags},
     runtime::task,
     utils::datetime::format_duration,
     NodeIdentity,
@@ -94,6 +95,8 @@ impl ConnectivityManager {
 
             shutdown_signal: Some(self.shutdown_signal),
             pool: ConnectionPool::new(),
+            latency_cache: PeerLatencyCache::new(self.config.peer_latency_ttl),
+            trusted_seed_ban_requests: HashMap::new(),
         }
     }
 }
@@ -143,6 +146,8 @@ pub struct ConnectivityManagerActor {
 
     managed_peers: Vec<NodeId>,
     pool: ConnectionPool,
+    latency_cache: PeerLatencyCache,
+    trusted_seed_ban_requests: HashMap<NodeId, usize>,
 }
 
 impl ConnectivityManagerActor {
@@ -277,6 +282,12 @@ impl ConnectivityManagerActor {
                         .collect(),
                 );
             },
+            RecordPeerLatency(node_id, latency) => {
+                self.latency_cache.record(node_id, latency);
+            },
+            GetPeerLatency(node_id, reply) => {
+                let _ = reply.send(self.latency_cache.get(&node_id));
+            },
         }
     }
 
@@ -323,6 +334,7 @@ impl ConnectivityManagerActor {
         self.try_connect_managed_peers().await?;
         // Remove disconnected/failed peers from the connection pool
         self.clean_connection_pool();
+        self.latency_cache.remove_expired();
         self.update_connectivity_status();
         Ok(())
     }
@@ -608,23 +620,32 @@ impl ConnectivityManagerActor {
         }
 
         let (node_id, mut new_status, connection) = match event {
-            PeerDisconnected(node_id) => {
+            PeerDisconnected(id, node_id) => {
+                if let Some(conn) = self.pool.get_connection(node_id) {
+                    if conn.id() != *id {
+                        debug!(
+                            target: LOG_TARGET,
+                            "Ignoring disconnect of replaced connection (id={}) to peer '{}'", id, node_id
+                        );
+                        return Ok(());
+                    }
+                }
                 self.connection_stats.remove(&node_id);
                 (&**node_id, ConnectionStatus::Disconnected, None)
             },
             PeerConnected(conn) => (conn.peer_node_id(), ConnectionStatus::Connected, Some(conn.clone())),
 
-            PeerConnectFailed(node_id, ConnectionManagerError::DialCancelled) => {
+            PeerConnectFailed(node_id, _, ConnectionManagerError::DialCancelled) => {
                 debug!(
                     target: LOG_TARGET,
                     "Dial was cancelled before connection completed to peer '{}'", node_id
                 );
                 (&**node_id, ConnectionStatus::Failed, None)
             },
-            PeerConnectFailed(node_id, err) => {
+            PeerConnectFailed(node_id, reason, err) => {
                 debug!(
                     target: LOG_TARGET,
-                    "Connection to peer '{}' failed because '{:?}'", node_id, err
+                    "Connection to peer '{}' failed ({}) because '{:?}'", node_id, reason, err
                 );
                 self.handle_peer_connection_failure(node_id).await?;
                 (&**node_id, ConnectionStatus::Failed, None)
@@ -813,6 +834,24 @@ impl ConnectivityManagerActor {
         reason: String,
     ) -> Result<(), ConnectivityError>
     {
+        if self.is_trusted_seed(node_id).await {
+            let num_requests = self.trusted_seed_ban_requests.entry(node_id.clone()).or_insert(0);
+            *num_requests += 1;
+            if *num_requests < self.config.trusted_seed_ban_tolerance {
+                warn!(
+                    target: LOG_TARGET,
+                    "Not banning trusted seed peer {} ({} of {} ban request(s)) for {} because: {}",
+                    node_id,
+                    num_requests,
+                    self.config.trusted_seed_ban_tolerance,
+                    format_duration(duration),
+                    reason
+                );
+                return Ok(());
+            }
+            self.trusted_seed_ban_requests.remove(node_id);
+        }
+
         info!(
             target: LOG_TARGET,
             "Banning peer {} for {} because: {}",
@@ -840,6 +879,14 @@ impl ConnectivityManagerActor {
         }
         Ok(())
     }
+
+    async fn is_trusted_seed(&self, node_id: &NodeId) -> bool {
+        self.peer_manager
+            .find_by_node_id(node_id)
+            .await
+            .map(|peer| peer.flags.contains(PeerFlags::TRUSTED_SEED))
+            .unwrap_or(false)
+    }
 }
 
 fn delayed_close(conn: PeerConnection, delay: Duration) {
@@ -22,6 +22,8 @@
 
 mod connection_stats;
 
+mod latency_cache;
+
 mod config;
 pub use config::ConnectivityConfig;
 
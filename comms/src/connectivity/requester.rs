@@ -102,6 +102,8 @@ pub enum ConnectivityRequest {
     GetAllConnectionStates(oneshot::Sender<Vec<PeerConnectionState>>),
     GetActiveConnections(oneshot::Sender<Vec<PeerConnection>>),
     BanPeer(NodeId, Duration, String),
+    RecordPeerLatency(NodeId, Duration),
+    GetPeerLatency(NodeId, oneshot::Sender<Option<Duration>>),
 }
 
 #[derive(Debug, Clone)]
@@ -233,6 +235,26 @@ impl ConnectivityRequester {
             .await
     }
 
+    /// Record the round-trip latency measured for a peer so that it is available to other components
+    pub async fn record_peer_latency(&mut self, node_id: NodeId, latency: Duration) -> Result<(), ConnectivityError> {
+        self.sender
+            .send(ConnectivityRequest::RecordPeerLatency(node_id, latency))
+            .await
+            .map_err(|_| ConnectivityError::ActorDisconnected)?;
+        Ok(())
+    }
+
+    /// Returns the last round-trip latency measured for a peer, or None if the peer's latency has not been measured
+    /// recently
+    pub async fn peer_latency(&mut self, node_id: NodeId) -> Result<Option<Duration>, ConnectivityError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.sender
+            .send(ConnectivityRequest::GetPeerLatency(node_id, reply_tx))
+            .await
+            .map_err(|_| ConnectivityError::ActorDisconnected)?;
+        reply_rx.await.map_err(|_| ConnectivityError::ActorResponseCancelled)
+    }
+
     pub async fn wait_started(&mut self) -> Result<(), ConnectivityError> {
         let (reply_tx, reply_rx) = oneshot::channel();
         self.sender
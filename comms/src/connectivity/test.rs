@@ -27,8 +27,8 @@ use super::{
     selection::ConnectivitySelection,
 };
 use crate::{
-    connection_manager::{ConnectionManagerError, ConnectionManagerEvent},
-    peer_manager::{Peer, PeerFeatures},
+    connection_manager::{ConnectFailureReason, ConnectionManagerError, ConnectionManagerEvent},
+    peer_manager::{Peer, PeerFeatures, PeerFlags},
     runtime,
     runtime::task,
     test_utils::{
@@ -43,7 +43,7 @@ use futures::{channel::mpsc, future};
 use std::{sync::Arc, time::Duration};
 use tari_shutdown::Shutdown;
 use tari_test_utils::{collect_stream, streams, unpack_enum};
-use tokio::sync::broadcast;
+use tokio::{sync::broadcast, time};
 
 #[allow(clippy::type_complexity)]
 fn setup_connectivity_manager(
@@ -168,7 +168,8 @@ async fn add_many_managed_peers() {
     for i in peers.iter().take(10).skip(7) {
         cm_mock_state.publish_event(ConnectionManagerEvent::PeerConnectFailed(
             Box::new(i.node_id.clone()),
-            ConnectionManagerError::ConnectFailedMaximumAttemptsReached,
+            ConnectFailureReason::DialTimeout,
+            ConnectionManagerError::NoiseProtocolTimeout,
         ));
     }
 
@@ -260,11 +261,13 @@ async fn online_then_offline() {
         .unwrap();
     cm_mock_state.publish_event(ConnectionManagerEvent::PeerConnectFailed(
         connections[0].peer_node_id().clone().into(),
+        ConnectFailureReason::HandshakeFailed,
         ConnectionManagerError::InvalidStaticPublicKey,
     ));
 
     for conn in connections.iter().skip(1) {
         cm_mock_state.publish_event(ConnectionManagerEvent::PeerDisconnected(
+            conn.id(),
             conn.peer_node_id().clone().into(),
         ));
     }
@@ -288,6 +291,7 @@ async fn online_then_offline() {
     // Disconnect client connections
     for conn in &client_connections {
         cm_mock_state.publish_event(ConnectionManagerEvent::PeerDisconnected(
+            conn.id(),
             conn.peer_node_id().clone().into(),
         ));
     }
@@ -306,6 +310,26 @@ async fn online_then_offline() {
     assert!(is_offline);
 }
 
+#[runtime::test_basic]
+async fn peer_latency() {
+    let (mut connectivity, _, _, _, _, _shutdown) = setup_connectivity_manager(ConnectivityConfig {
+        peer_latency_ttl: Duration::from_millis(100),
+        ..Default::default()
+    });
+    let node_id = build_node_identity(PeerFeatures::COMMUNICATION_NODE).node_id().clone();
+    assert!(connectivity.peer_latency(node_id.clone()).await.unwrap().is_none());
+
+    connectivity
+        .record_peer_latency(node_id.clone(), Duration::from_millis(250))
+        .await
+        .unwrap();
+    let latency = connectivity.peer_latency(node_id.clone()).await.unwrap();
+    assert_eq!(latency, Some(Duration::from_millis(250)));
+
+    time::delay_for(Duration::from_millis(150)).await;
+    assert!(connectivity.peer_latency(node_id).await.unwrap().is_none());
+}
+
 #[runtime::test_basic]
 async fn ban_peer() {
     let (mut connectivity, mut event_stream, node_identity, peer_manager, cm_mock_state, _shutdown) =
@@ -347,6 +371,85 @@ async fn ban_peer() {
     assert!(conn.is_none());
 }
 
+#[runtime::test_basic]
+async fn disconnect_of_replaced_connection_is_ignored() {
+    let (mut connectivity, mut event_stream, node_identity, peer_manager, cm_mock_state, _shutdown) =
+        setup_connectivity_manager(Default::default());
+    let peer = add_test_peers(&peer_manager, 1).await.pop().unwrap();
+    let (conn, _, _, _) = create_peer_connection_mock_pair(1, node_identity.to_peer(), peer.clone()).await;
+
+    let mut events = collect_stream!(event_stream, take = 1, timeout = Duration::from_secs(10));
+    unpack_enum!(ConnectivityEvent::ConnectivityStateInitialized = &*events.remove(0).unwrap());
+
+    cm_mock_state.publish_event(ConnectionManagerEvent::PeerConnected(conn.clone()));
+    let mut events = collect_stream!(event_stream, take = 2, timeout = Duration::from_secs(10));
+    unpack_enum!(ConnectivityEvent::PeerConnected(_conn) = &*events.remove(0).unwrap());
+
+    // A duplicate connection to the same peer that lost a tie break disconnects
+    cm_mock_state.publish_event(ConnectionManagerEvent::PeerDisconnected(
+        conn.id() + 1,
+        peer.node_id.clone().into(),
+    ));
+    time::delay_for(Duration::from_millis(50)).await;
+
+    let active_conn = connectivity
+        .get_connection(peer.node_id.clone())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(active_conn.id(), conn.id());
+
+    cm_mock_state.publish_event(ConnectionManagerEvent::PeerDisconnected(
+        conn.id(),
+        peer.node_id.clone().into(),
+    ));
+    let event = collect_stream!(event_stream, take = 1, timeout = Duration::from_secs(10))
+        .pop()
+        .unwrap()
+        .unwrap();
+    unpack_enum!(ConnectivityEvent::PeerDisconnected(node_id) = &*event);
+    assert_eq!(node_id, &peer.node_id);
+}
+
+#[runtime::test_basic]
+async fn ban_trusted_seed_peer() {
+    let config = ConnectivityConfig {
+        trusted_seed_ban_tolerance: 2,
+        ..Default::default()
+    };
+    let (mut connectivity, mut event_stream, _node_identity, peer_manager, _cm_mock_state, _shutdown) =
+        setup_connectivity_manager(config);
+    let mut peer = build_node_identity(PeerFeatures::COMMUNICATION_NODE).to_peer();
+    peer.flags = PeerFlags::TRUSTED_SEED;
+    peer_manager.add_peer(peer.clone()).await.unwrap();
+
+    let mut events = collect_stream!(event_stream, take = 1, timeout = Duration::from_secs(10));
+    unpack_enum!(ConnectivityEvent::ConnectivityStateInitialized = &*events.remove(0).unwrap());
+
+    connectivity
+        .ban_peer_until(peer.node_id.clone(), Duration::from_secs(3600), "".to_string())
+        .await
+        .unwrap();
+    // Requests are handled in order, so the ban request has been handled once this returns
+    connectivity.get_connection(peer.node_id.clone()).await.unwrap();
+    let stored_peer = peer_manager.find_by_node_id(&peer.node_id).await.unwrap();
+    assert!(!stored_peer.is_banned());
+
+    connectivity
+        .ban_peer_until(peer.node_id.clone(), Duration::from_secs(3600), "".to_string())
+        .await
+        .unwrap();
+    let event = collect_stream!(event_stream, take = 1, timeout = Duration::from_secs(10))
+        .pop()
+        .unwrap()
+        .unwrap();
+    unpack_enum!(ConnectivityEvent::PeerBanned(node_id) = &*event);
+    assert_eq!(node_id, &peer.node_id);
+
+    let stored_peer = peer_manager.find_by_node_id(&peer.node_id).await.unwrap();
+    assert!(stored_peer.is_banned());
+}
+
 #[runtime::test_basic]
 async fn peer_selection() {
     let config = ConnectivityConfig {
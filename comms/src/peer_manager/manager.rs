@@ -464,8 +464,10 @@ mod test {
         // Create peer manager with random peers
         let peer_manager = PeerManager::new(HashmapDatabase::new(), None).unwrap();
         let network_region_node_id = create_test_peer(false, Default::default()).node_id;
-        let mut test_peers = (0..10)
+        // Archival base nodes are in the same region as other base nodes
+        let mut test_peers = (0..5)
             .map(|_| create_test_peer(false, PeerFeatures::COMMUNICATION_NODE))
+            .chain((0..5).map(|_| create_test_peer(false, PeerFeatures::COMMUNICATION_NODE | PeerFeatures::ARCHIVAL)))
             .chain((0..10).map(|_| create_test_peer(false, PeerFeatures::COMMUNICATION_CLIENT)))
             .collect::<Vec<_>>();
 
@@ -485,20 +487,12 @@ mod test {
             .unwrap();
 
         // First 5 base nodes should be within the region
-        for peer in test_peers
-            .iter()
-            .filter(|p| p.features == PeerFeatures::COMMUNICATION_NODE)
-            .take(n)
-        {
+        for peer in test_peers.iter().filter(|p| p.features.is_node()).take(n) {
             assert!(peer.node_id.distance(&network_region_node_id) <= node_region_threshold);
         }
 
         // Next 5 should not be in the region
-        for peer in test_peers
-            .iter()
-            .filter(|p| p.features == PeerFeatures::COMMUNICATION_NODE)
-            .skip(n)
-        {
+        for peer in test_peers.iter().filter(|p| p.features.is_node()).skip(n) {
             assert!(peer.node_id.distance(&network_region_node_id) >= node_region_threshold);
         }
 
@@ -49,6 +49,8 @@ bitflags! {
     #[derive(Default, Deserialize, Serialize)]
     pub struct PeerFlags: u8 {
         const NONE = 0x00;
+        /// The peer is a seed peer that the node operator has marked as trusted
+        const TRUSTED_SEED = 0x01;
     }
 }
 
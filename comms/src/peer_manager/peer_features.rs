@@ -30,6 +30,8 @@ bitflags! {
         const NONE = 0b0000_0000;
         const MESSAGE_PROPAGATION = 0b0000_0001;
         const DHT_STORE_FORWARD = 0b0000_0010;
+        /// Optional capability: the node retains the full history of the data it serves (e.g. an unpruned blockchain)
+        const ARCHIVAL = 0b0000_0100;
 
         const COMMUNICATION_NODE = Self::MESSAGE_PROPAGATION.bits | Self::DHT_STORE_FORWARD.bits;
         const COMMUNICATION_CLIENT = Self::NONE.bits;
@@ -44,7 +46,19 @@ impl PeerFeatures {
 
     #[inline]
     pub fn is_node(self) -> bool {
-        self == PeerFeatures::COMMUNICATION_NODE
+        self.without_capabilities() == PeerFeatures::COMMUNICATION_NODE
+    }
+
+    #[inline]
+    pub fn is_archival(self) -> bool {
+        self.contains(PeerFeatures::ARCHIVAL)
+    }
+
+    /// Returns these features with optional capability flags cleared, leaving only the flags that determine the role
+    /// (node or client) of the peer.
+    #[inline]
+    pub fn without_capabilities(self) -> PeerFeatures {
+        self - PeerFeatures::ARCHIVAL
     }
 }
 
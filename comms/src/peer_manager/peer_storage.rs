@@ -313,7 +313,9 @@ where DS: KeyValueStore<PeerId, Peer>
         let mut distances = Vec::new();
         self.peer_db
             .for_each_ok(|(_, peer)| {
-                if features.map(|f| peer.features == f).unwrap_or(true) &&
+                if features
+                    .map(|f| peer.features.without_capabilities() == f)
+                    .unwrap_or(true) &&
                     !peer.is_banned() &&
                     !peer.is_offline() &&
                     !excluded_peers.contains(&peer.node_id)
@@ -342,7 +344,7 @@ where DS: KeyValueStore<PeerId, Peer>
             .filter(|(_, peer)| {
                 !peer.is_offline() &&
                     !peer.is_banned() &&
-                    peer.features == PeerFeatures::COMMUNICATION_NODE &&
+                    peer.features.is_node() &&
                     !exclude_peers.contains(&peer.node_id)
             })
             .map(|pairs| pairs.into_iter().map(|(_, p)| p).collect::<Vec<_>>())
@@ -391,7 +393,7 @@ where DS: KeyValueStore<PeerId, Peer>
         let mut dists = Vec::new();
         self.peer_db
             .for_each_ok(|(_, peer)| {
-                if peer.features != features || peer.is_banned() || peer.is_offline() {
+                if peer.features.without_capabilities() != features || peer.is_banned() || peer.is_offline() {
                     return IterationResult::Continue;
                 }
                 dists.push(region_node_id.distance(&peer.node_id));
@@ -30,12 +30,18 @@ pub struct MessagingConfig {
     /// Inbound/outbound substreams are closed independently, and they may be reopened in the future once closed.
     /// (default: 8 mins)
     pub inactivity_timeout: Option<Duration>,
+    /// The maximum number of messages that may be queued for a single peer before further messages to that peer are
+    /// rejected with `SendFailReason::QueueFull`. Each peer has its own queue, so a slow or unresponsive peer cannot
+    /// delay messages to other peers.
+    /// (default: 100)
+    pub max_queued_messages_per_peer: usize,
 }
 
 impl Default for MessagingConfig {
     fn default() -> Self {
         Self {
             inactivity_timeout: Some(Duration::from_secs(8 * 60)),
+            max_queued_messages_per_peer: 100,
         }
     }
 }
@@ -26,7 +26,7 @@ use crate::{
     message::InboundMessage,
     pipeline,
     protocol::{
-        messaging::{consts, protocol::MESSAGING_PROTOCOL, MessagingEventSender},
+        messaging::{consts, protocol::MESSAGING_PROTOCOL, MessagingConfig, MessagingEventSender},
         ProtocolExtension,
         ProtocolExtensionContext,
         ProtocolExtensionError,
@@ -41,11 +41,21 @@ use tower::Service;
 pub struct MessagingProtocolExtension<TInPipe, TOutPipe, TOutReq> {
     event_tx: MessagingEventSender,
     pipeline: pipeline::Config<TInPipe, TOutPipe, TOutReq>,
+    config: MessagingConfig,
 }
 
 impl<TInPipe, TOutPipe, TOutReq> MessagingProtocolExtension<TInPipe, TOutPipe, TOutReq> {
     pub fn new(event_tx: MessagingEventSender, pipeline: pipeline::Config<TInPipe, TOutPipe, TOutReq>) -> Self {
-        Self { event_tx, pipeline }
+        Self {
+            event_tx,
+            pipeline,
+            config: Default::default(),
+        }
+    }
+
+    pub fn with_config(mut self, config: MessagingConfig) -> Self {
+        self.config = config;
+        self
     }
 }
 
@@ -67,7 +77,7 @@ where
         let (inbound_message_tx, inbound_message_rx) = mpsc::channel(consts::INBOUND_MESSAGE_BUFFER_SIZE);
 
         let messaging = MessagingProtocol::new(
-            Default::default(),
+            self.config,
             context.connectivity(),
             proto_rx,
             messaging_request_rx,
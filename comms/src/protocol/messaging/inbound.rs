@@ -29,6 +29,7 @@ use crate::{
 use futures::{channel::mpsc, future::Either, AsyncRead, AsyncWrite, SinkExt, StreamExt};
 use log::*;
 use std::{sync::Arc, time::Duration};
+use tari_shutdown::ShutdownSignal;
 use tokio::sync::broadcast;
 
 const LOG_TARGET: &str = "comms::protocol::messaging::inbound";
@@ -40,6 +41,7 @@ pub struct InboundMessaging {
     rate_limit_capacity: usize,
     rate_limit_restock_interval: Duration,
     inactivity_timeout: Option<Duration>,
+    shutdown_signal: ShutdownSignal,
 }
 
 impl InboundMessaging {
@@ -50,6 +52,7 @@ impl InboundMessaging {
         rate_limit_capacity: usize,
         rate_limit_restock_interval: Duration,
         inactivity_timeout: Option<Duration>,
+        shutdown_signal: ShutdownSignal,
     ) -> Self
     {
         Self {
@@ -59,6 +62,7 @@ impl InboundMessaging {
             rate_limit_capacity,
             rate_limit_restock_interval,
             inactivity_timeout,
+            shutdown_signal,
         }
     }
 
@@ -83,10 +87,12 @@ impl InboundMessaging {
         }
         let stream = stream.rate_limit(self.rate_limit_capacity, self.rate_limit_restock_interval);
 
-        let mut stream = match self.inactivity_timeout {
+        let stream = match self.inactivity_timeout {
             Some(timeout) => Either::Left(tokio::stream::StreamExt::timeout(stream, timeout)),
             None => Either::Right(stream.map(Ok)),
         };
+        // The substream is closed on shutdown so that it does not hold up the connection manager from draining
+        let mut stream = stream.take_until(self.shutdown_signal.clone());
 
         while let Some(result) = stream.next().await {
             match result {
@@ -45,7 +45,7 @@ const MAX_SEND_RETRIES: usize = 1;
 
 pub struct OutboundMessaging {
     connectivity: ConnectivityRequester,
-    request_rx: mpsc::UnboundedReceiver<OutboundMessage>,
+    request_rx: mpsc::Receiver<OutboundMessage>,
     messaging_events_tx: mpsc::Sender<MessagingEvent>,
     peer_node_id: NodeId,
     inactivity_timeout: Option<Duration>,
@@ -55,7 +55,7 @@ impl OutboundMessaging {
     pub fn new(
         connectivity: ConnectivityRequester,
         messaging_events_tx: mpsc::Sender<MessagingEvent>,
-        request_rx: mpsc::UnboundedReceiver<OutboundMessage>,
+        request_rx: mpsc::Receiver<OutboundMessage>,
         peer_node_id: NodeId,
         inactivity_timeout: Option<Duration>,
     ) -> Self
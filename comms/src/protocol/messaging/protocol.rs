@@ -36,7 +36,7 @@ use crate::{
     runtime::task,
 };
 use bytes::Bytes;
-use futures::{channel::mpsc, stream::Fuse, AsyncRead, AsyncWrite, SinkExt, StreamExt};
+use futures::{channel::mpsc, stream::Fuse, AsyncRead, AsyncWrite, StreamExt};
 use log::*;
 use std::{
     collections::{hash_map::Entry, HashMap},
@@ -81,6 +81,8 @@ pub enum SendFailReason {
     Dropped,
     #[error("Message could not send after {0} attempt(s)")]
     MaxRetriesReached(usize),
+    #[error("The outbound message queue for the peer is full")]
+    QueueFull,
 }
 
 #[derive(Debug)]
@@ -107,7 +109,7 @@ pub struct MessagingProtocol {
     config: MessagingConfig,
     connectivity: ConnectivityRequester,
     proto_notification: Fuse<mpsc::Receiver<ProtocolNotification<Substream>>>,
-    active_queues: HashMap<NodeId, mpsc::UnboundedSender<OutboundMessage>>,
+    active_queues: HashMap<NodeId, mpsc::Sender<OutboundMessage>>,
     request_rx: Fuse<mpsc::Receiver<MessagingRequest>>,
     messaging_events_tx: MessagingEventSender,
     inbound_message_tx: mpsc::Sender<InboundMessage>,
@@ -186,6 +188,9 @@ impl MessagingProtocol {
                 }
             }
         }
+
+        // Closing the outbound message queues ends the outbound messaging tasks, which closes their substreams
+        self.active_queues.clear();
     }
 
     #[inline]
@@ -201,7 +206,7 @@ impl MessagingProtocol {
             PeerConnectionWillClose(node_id, _) => {
                 // If the peer connection will close, cut off the pipe to send further messages.
                 // Any messages in the channel will be sent (hopefully) before the connection is disconnected.
-                if let Some(sender) = self.active_queues.remove(node_id) {
+                if let Some(mut sender) = self.active_queues.remove(node_id) {
                     sender.close_channel();
                 }
             },
@@ -265,6 +270,7 @@ impl MessagingProtocol {
                         self.internal_messaging_event_tx.clone(),
                         peer_node_id.clone(),
                         self.config.inactivity_timeout,
+                        self.config.max_queued_messages_per_peer,
                     );
                     break entry.insert(sender);
                 },
@@ -273,11 +279,29 @@ impl MessagingProtocol {
 
         debug!(target: LOG_TARGET, "Sending message {}", out_msg);
         let tag = out_msg.tag;
-        match sender.send(out_msg).await {
+        // Never wait on a peer's queue, otherwise a single slow peer would stall messages to every other peer
+        match sender.try_send(out_msg) {
             Ok(_) => {
                 debug!(target: LOG_TARGET, "Message ({}) dispatched to outbound handler", tag,);
                 Ok(())
             },
+            Err(err) if err.is_full() => {
+                let mut out_msg = err.into_inner();
+                warn!(
+                    target: LOG_TARGET,
+                    "Outbound message queue for peer '{}' is full. Message ({}) will not be sent",
+                    peer_node_id.short_str(),
+                    tag
+                );
+                out_msg.reply_fail(SendFailReason::QueueFull);
+                let _ = self
+                    .messaging_events_tx
+                    .send(Arc::new(MessagingEvent::SendMessageFailed(
+                        out_msg,
+                        SendFailReason::QueueFull,
+                    )));
+                Ok(())
+            },
             Err(err) => {
                 debug!(
                     target: LOG_TARGET,
@@ -293,9 +317,10 @@ impl MessagingProtocol {
         events_tx: mpsc::Sender<MessagingEvent>,
         peer_node_id: NodeId,
         inactivity_timeout: Option<Duration>,
-    ) -> mpsc::UnboundedSender<OutboundMessage>
+        max_queued_messages: usize,
+    ) -> mpsc::Sender<OutboundMessage>
     {
-        let (msg_tx, msg_rx) = mpsc::unbounded();
+        let (msg_tx, msg_rx) = mpsc::channel(max_queued_messages);
         let outbound_messaging =
             OutboundMessaging::new(connectivity, events_tx, msg_rx, peer_node_id, inactivity_timeout);
         task::spawn(outbound_messaging.run());
@@ -312,6 +337,7 @@ impl MessagingProtocol {
             RATE_LIMIT_CAPACITY,
             RATE_LIMIT_RESTOCK_INTERVAL,
             self.config.inactivity_timeout,
+            self.shutdown_signal.clone(),
         );
         task::spawn(inbound_messaging.run(substream));
     }
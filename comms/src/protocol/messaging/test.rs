@@ -34,7 +34,7 @@ use crate::{
     net_address::MultiaddressesWithStats,
     peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerFlags, PeerManager},
     protocol::{
-        messaging::{inbound::InboundMessaging, SendFailReason},
+        messaging::{inbound::InboundMessaging, MessagingConfig, SendFailReason},
         ProtocolEvent,
         ProtocolNotification,
     },
@@ -73,6 +73,21 @@ async fn spawn_messaging_protocol() -> (
     mpsc::Receiver<InboundMessage>,
     MessagingEventReceiver,
     Shutdown,
+) {
+    spawn_messaging_protocol_with_config(Default::default()).await
+}
+
+async fn spawn_messaging_protocol_with_config(
+    config: MessagingConfig,
+) -> (
+    Arc<PeerManager>,
+    Arc<NodeIdentity>,
+    ConnectivityManagerMockState,
+    mpsc::Sender<ProtocolNotification<Substream>>,
+    mpsc::Sender<MessagingRequest>,
+    mpsc::Receiver<InboundMessage>,
+    MessagingEventReceiver,
+    Shutdown,
 ) {
     let shutdown = Shutdown::new();
 
@@ -88,7 +103,7 @@ async fn spawn_messaging_protocol() -> (
     let (events_tx, events_rx) = broadcast::channel(100);
 
     let msg_proto = MessagingProtocol::new(
-        Default::default(),
+        config,
         requester,
         proto_rx,
         request_rx,
@@ -358,6 +373,78 @@ async fn many_concurrent_send_message_requests_that_fail() {
     assert_eq!(msg_tags.len(), 0);
 }
 
+#[runtime::test_basic]
+async fn slow_peer_does_not_delay_other_peers() {
+    const NUM_SLOW_MSGS: usize = 30;
+    let (_, node_identity, conn_man_mock, _, mut request_tx, _, _, _shutdown) =
+        spawn_messaging_protocol_with_config(MessagingConfig {
+            max_queued_messages_per_peer: 2,
+            ..Default::default()
+        })
+        .await;
+
+    let slow_peer = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let fast_peer = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+
+    let (slow_conn, _, _, _slow_peer_conn_mock) =
+        create_peer_connection_mock_pair(1, node_identity.to_peer(), slow_peer.to_peer()).await;
+    let (fast_conn, _, _, fast_peer_conn_mock) =
+        create_peer_connection_mock_pair(1, node_identity.to_peer(), fast_peer.to_peer()).await;
+    conn_man_mock.add_active_connection(slow_conn).await;
+    conn_man_mock.add_active_connection(fast_conn).await;
+
+    // The slow peer never reads its substream, so its outbound handler stalls once the substream window is full
+    let large_msg = Bytes::from(vec![0u8; 64 * 1024]);
+    let mut slow_reply_rxs = Vec::with_capacity(NUM_SLOW_MSGS);
+    for _ in 0..NUM_SLOW_MSGS {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let out_msg = OutboundMessage {
+            tag: MessageTag::new(),
+            reply: reply_tx.into(),
+            peer_node_id: slow_peer.node_id().clone(),
+            body: large_msg.clone(),
+        };
+        slow_reply_rxs.push(reply_rx);
+        request_tx.send(MessagingRequest::SendMessage(out_msg)).await.unwrap();
+    }
+
+    let (reply_tx, reply_rx) = oneshot::channel();
+    let out_msg = OutboundMessage {
+        tag: MessageTag::new(),
+        reply: reply_tx.into(),
+        peer_node_id: fast_peer.node_id().clone(),
+        body: TEST_MSG1.clone(),
+    };
+    request_tx.send(MessagingRequest::SendMessage(out_msg)).await.unwrap();
+
+    // The fast peer receives its message without waiting on the slow peer
+    let stream = time::timeout(Duration::from_secs(5), fast_peer_conn_mock.next_incoming_substream())
+        .await
+        .unwrap()
+        .unwrap();
+    let mut framed = MessagingProtocol::framed(stream);
+    let msg = time::timeout(Duration::from_secs(5), framed.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(msg, TEST_MSG1);
+    time::timeout(Duration::from_secs(5), reply_rx)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+
+    // Messages that did not fit in the slow peer's queue are rejected
+    let reply_rx = slow_reply_rxs.pop().unwrap();
+    let reason = time::timeout(Duration::from_secs(5), reply_rx)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap_err();
+    unpack_enum!(SendFailReason::QueueFull = reason);
+}
+
 #[runtime::test_basic]
 async fn inactivity_timeout() {
     let node_identity = build_node_identity(PeerFeatures::COMMUNICATION_CLIENT);
@@ -365,6 +452,7 @@ async fn inactivity_timeout() {
     let (events_tx, _) = broadcast::channel(1);
 
     let (socket_in, socket_out) = MemorySocket::new_pair();
+    let shutdown = Shutdown::new();
 
     task::spawn(
         InboundMessaging::new(
@@ -374,6 +462,7 @@ async fn inactivity_timeout() {
             10,
             Duration::from_millis(100),
             Some(Duration::from_millis(5)),
+            shutdown.to_signal(),
         )
         .run(socket_in),
     );
@@ -392,3 +481,36 @@ async fn inactivity_timeout() {
 
     let _ = collect_stream!(inbound_msg_rx, take = 5, timeout = Duration::from_secs(10));
 }
+
+#[runtime::test_basic]
+async fn inbound_messaging_closes_substream_on_shutdown() {
+    let node_identity = build_node_identity(PeerFeatures::COMMUNICATION_CLIENT);
+    let (inbound_msg_tx, mut inbound_msg_rx) = mpsc::channel(5);
+    let (events_tx, _) = broadcast::channel(1);
+
+    let (socket_in, socket_out) = MemorySocket::new_pair();
+    let mut shutdown = Shutdown::new();
+
+    let handle = task::spawn(
+        InboundMessaging::new(
+            node_identity.node_id().clone(),
+            inbound_msg_tx,
+            events_tx,
+            10,
+            Duration::from_millis(100),
+            None,
+            shutdown.to_signal(),
+        )
+        .run(socket_in),
+    );
+
+    let mut framed = MessagingProtocol::framed(socket_out);
+    framed.send(Bytes::from_static(b"some message")).await.unwrap();
+    let _ = collect_stream!(inbound_msg_rx, take = 1, timeout = Duration::from_secs(10));
+
+    shutdown.trigger().unwrap();
+    time::timeout(Duration::from_secs(10), handle).await.unwrap().unwrap();
+
+    let err = framed.send(Bytes::from_static(b"another message")).await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+}
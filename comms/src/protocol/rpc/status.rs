@@ -90,6 +90,15 @@ impl RpcStatus {
         }
     }
 
+    /// Returns a status indicating that the requested entity existed but is no longer available on this node (e.g. a
+    /// block body that was pruned). The requester should ask a node that retains the data (e.g. an archival node).
+    pub fn pruned_away<T: ToString>(details: T) -> Self {
+        Self {
+            code: RpcStatusCode::PrunedAway,
+            details: details.to_string(),
+        }
+    }
+
     /// Returns a closure that logs the given error and returns a generic general error that does not leak any
     /// potentially sensitive error information. Use this function with map_err to catch "miscellaneous" errors.
     pub fn log_internal_error<'a, E: std::error::Error + 'a>(target: &'a str) -> impl Fn(E) -> Self + 'a {
@@ -177,6 +186,8 @@ pub enum RpcStatusCode {
     General = 6,
     /// Entity not found
     NotFound = 7,
+    /// Entity existed but has been pruned and is no longer available
+    PrunedAway = 8,
     // The following status represents anything that is not recognised (i.e not one of the above codes).
     /// Unrecognised RPC status code
     InvalidRpcStatusCode,
@@ -194,6 +205,10 @@ impl RpcStatusCode {
     pub fn is_timeout(self) -> bool {
         self == Self::Timeout
     }
+
+    pub fn is_pruned_away(self) -> bool {
+        self == Self::PrunedAway
+    }
 }
 
 impl From<u32> for RpcStatusCode {
@@ -208,6 +223,7 @@ impl From<u32> for RpcStatusCode {
             5 => MalformedResponse,
             6 => General,
             7 => NotFound,
+            8 => PrunedAway,
             _ => InvalidRpcStatusCode,
         }
     }
@@ -228,6 +244,7 @@ mod test {
         assert_eq!(RpcStatusCode::from(MalformedResponse as u32), MalformedResponse);
         assert_eq!(RpcStatusCode::from(Timeout as u32), Timeout);
         assert_eq!(RpcStatusCode::from(NotFound as u32), NotFound);
+        assert_eq!(RpcStatusCode::from(PrunedAway as u32), PrunedAway);
         assert_eq!(RpcStatusCode::from(InvalidRpcStatusCode as u32), InvalidRpcStatusCode);
         assert_eq!(RpcStatusCode::from(123), InvalidRpcStatusCode);
     }
@@ -22,7 +22,10 @@
 
 use crate::{
     protocol::rpc::{
-        test::mock::{MockRpcClient, MockRpcService},
+        test::{
+            mock::{MockRpcClient, MockRpcService},
+            smoke::{GreetingClient, GreetingServer, SlowGreetingService},
+        },
         RpcError,
         RpcServer,
         RpcStatus,
@@ -34,8 +37,11 @@ use crate::{
     types::CommsDatabase,
     CommsBuilder,
 };
+use futures::StreamExt;
+use std::{sync::Arc, time::Duration};
 use tari_shutdown::Shutdown;
 use tari_test_utils::unpack_enum;
+use tokio::sync::RwLock;
 
 #[runtime::test_basic]
 async fn run_service() {
@@ -90,3 +96,62 @@ async fn run_service() {
     unpack_enum!(RpcStatusCode::BadRequest = status.status_code());
     assert_eq!(mock_state.call_count(), 2);
 }
+
+#[runtime::test_basic]
+async fn shutdown_drains_open_rpc_streams() {
+    let node_identity1 = build_node_identity(Default::default());
+    let delay = Arc::new(RwLock::new(Duration::from_millis(100)));
+    let mut shutdown1 = Shutdown::new();
+    let comms1 = CommsBuilder::new()
+        .with_listener_address(node_identity1.public_address())
+        .with_node_identity(node_identity1)
+        .with_shutdown_signal(shutdown1.to_signal())
+        .with_shutdown_drain_timeout(Duration::from_secs(10))
+        .with_peer_storage(CommsDatabase::new(), None)
+        .build()
+        .unwrap()
+        .add_rpc_server(RpcServer::new().add_service(GreetingServer::new(SlowGreetingService::new(delay))))
+        .spawn_with_transport(MemoryTransport)
+        .await
+        .unwrap();
+
+    let node_identity2 = build_node_identity(Default::default());
+    let shutdown2 = Shutdown::new();
+    let comms2 = CommsBuilder::new()
+        .with_listener_address(node_identity2.public_address())
+        .with_shutdown_signal(shutdown2.to_signal())
+        .with_node_identity(node_identity2.clone())
+        .with_peer_storage(CommsDatabase::new(), None)
+        .build()
+        .unwrap();
+
+    comms2
+        .peer_manager()
+        .add_peer(comms1.node_identity().to_peer())
+        .await
+        .unwrap();
+
+    let comms2 = comms2.spawn_with_transport(MemoryTransport).await.unwrap();
+
+    let mut conn = comms2
+        .connectivity()
+        .dial_peer(comms1.node_identity().node_id().clone())
+        .await
+        .unwrap();
+
+    let mut client = conn.connect_rpc::<GreetingClient>().await.unwrap();
+    let mut stream = client.get_greetings(5).await.unwrap();
+    let first = stream.next().await.unwrap().unwrap();
+    assert_eq!(first, "Greeting 0");
+
+    shutdown1.trigger().unwrap();
+
+    // The stream completes cleanly because the server waits for open substreams before closing the connection
+    let rest = stream.collect::<Vec<_>>().await;
+    assert_eq!(rest.len(), 4);
+    for (i, item) in rest.into_iter().enumerate() {
+        assert_eq!(item.unwrap(), format!("Greeting {}", i + 1));
+    }
+
+    comms1.wait_until_shutdown().await;
+}
@@ -410,8 +410,20 @@ impl GreetingRpc for SlowGreetingService {
         unimplemented!()
     }
 
-    async fn get_greetings(&self, _: Request<u32>) -> Result<Streaming<String>, RpcStatus> {
-        unimplemented!()
+    async fn get_greetings(&self, request: Request<u32>) -> Result<Streaming<String>, RpcStatus> {
+        let (mut tx, rx) = mpsc::channel(1);
+        let num = *request.message();
+        let delay = *self.delay.read().await;
+        task::spawn(async move {
+            for i in 0..num {
+                time::delay_for(delay).await;
+                if tx.send(Ok(format!("Greeting {}", i))).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Streaming::new(rx))
     }
 
     async fn streaming_error(&self, _: Request<String>) -> Result<Streaming<String>, RpcStatus> {
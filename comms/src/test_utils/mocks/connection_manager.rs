@@ -146,6 +146,9 @@ impl ConnectionManagerMock {
             },
             CancelDial(_) => {},
             NotifyListening(_reply_tx) => {},
+            GetActiveConnections(reply_tx) => {
+                let _ = reply_tx.send(self.state.active_conns.lock().await.values().cloned().collect());
+            },
         }
     }
 }
@@ -178,6 +178,8 @@ impl ConnectivityManagerMock {
             },
             GetAllConnectionStates(_) => unimplemented!(),
             BanPeer(_, _, _) => {},
+            RecordPeerLatency(_, _) => {},
+            GetPeerLatency(_, reply) => reply.send(None).unwrap(),
             GetActiveConnections(reply) => {
                 reply
                     .send(self.state.active_conns.lock().await.values().cloned().collect())
@@ -31,6 +31,7 @@ use std::{
     collections::HashMap,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
 const LOG_TARGET: &str = "lmdb";
@@ -44,6 +45,7 @@ pub struct LMDBConfig {
     init_size_bytes: usize,
     grow_size_bytes: usize,
     resize_threshold_bytes: usize,
+    compaction_interval: Option<Duration>,
 }
 
 impl LMDBConfig {
@@ -53,6 +55,7 @@ impl LMDBConfig {
             init_size_bytes,
             grow_size_bytes,
             resize_threshold_bytes,
+            compaction_interval: None,
         }
     }
 
@@ -62,9 +65,17 @@ impl LMDBConfig {
             init_size_bytes: init_size_mb * BYTES_PER_MB,
             grow_size_bytes: grow_size_mb * BYTES_PER_MB,
             resize_threshold_bytes: resize_threshold_mb * BYTES_PER_MB,
+            compaction_interval: None,
         }
     }
 
+    /// Enable periodic background compaction of the LMDB environment at the given interval. Compaction is only
+    /// performed by stores that support it.
+    pub fn with_compaction_interval(mut self, interval: Duration) -> Self {
+        self.compaction_interval = Some(interval);
+        self
+    }
+
     /// Get the initial size of the LMDB environment in bytes.
     pub fn init_size_bytes(&self) -> usize {
         self.init_size_bytes
@@ -80,6 +91,11 @@ impl LMDBConfig {
     pub fn resize_threshold_bytes(&self) -> usize {
         self.resize_threshold_bytes
     }
+
+    /// Get the background compaction interval. Compaction is disabled if this is `None` (the default).
+    pub fn compaction_interval(&self) -> Option<Duration> {
+        self.compaction_interval
+    }
 }
 
 impl Default for LMDBConfig {